@@ -42,6 +42,14 @@ pub const RECOMMENDED_MIN_TOKEN_STAKE: u64 = 1_000_000_000_000;
 /// not free to weaponize (register → co-sign → instantly unregister).
 pub const UNBONDING_SLOTS: u64 = 216_000;
 
+/// Slots per stake epoch, about an hour at ~2.5 slots/s. The first stake
+/// change of an epoch records the stake that stood when it began
+/// (`ValidatorAccount::epoch_stake`, `ValidatorRegistry::epoch_total_active_stake`),
+/// so the L2 can read the validator set at an epoch boundary however late it
+/// polls, and settlement counts its quorum by that same set. Must equal the
+/// L2's `consensus::epoch::EPOCH_SLOTS`.
+pub const STAKE_EPOCH_SLOTS: u64 = 9_000;
+
 /// Upper bound on the settlement proof blob. A BN254 Groth16 proof in the
 /// `alt_bn128` wire form is exactly 256 bytes (see
 /// [`transact_verifier::WIRE_PROOF_LEN`]); the cap rejects oversized blobs that
@@ -49,13 +57,32 @@ pub const UNBONDING_SLOTS: u64 = 216_000;
 pub const MAX_PROOF_LEN: usize = 256;
 
 /// Withdrawal fee, in basis points of the withdrawn amount (25 bps = 0.25%).
-/// The fee is credited to the validator that settles the withdrawal — the
-/// signer that gathered the BFT quorum and submitted the proof — so the
-/// people running the network are the people earning from it. No founder
-/// account sits in the withdraw path. The fee stays in the vault and is
-/// pulled out by the earner through `claim_rewards`.
+/// The fee is shared by the validators that settled the withdrawal — the
+/// signer that gathered the BFT quorum and submitted the proof, and every
+/// co-signer the quorum counted — so the people running the network are the
+/// people earning from it, whether or not they lead. No founder account sits
+/// in the withdraw path. See `quorum::split_fee` for the division; on the
+/// native path the shares stay in the vault and are pulled out by each earner
+/// through `claim_rewards`.
 pub const WITHDRAWAL_FEE_BPS: u64 = 25;
 
+/// Semver-encoded version of this program build, in the
+/// `BridgeState.program_version` encoding (v0.6.0 → 0x00060000). Must equal
+/// the L2's `EXPECTED_PROGRAM_VERSION`; bump both together with every breaking
+/// change to an instruction or account layout. v0.5.0: quorum-certified
+/// `transact`/`transact_spl` with writable quorum PDAs and a new
+/// remaining-accounts layout, `leader_bonus_bps`, at most 64 co-signers, and
+/// evidence-keyed `slash_validator`. v0.6.0: the epoch-boundary stake
+/// appended to `ValidatorAccount` and `ValidatorRegistry` (see
+/// [`STAKE_EPOCH_SLOTS`]). `migrate_bridge_state` stamps it onto an existing
+/// deployment.
+pub const PROGRAM_VERSION: u32 = 0x0006_0000;
+
+/// Ceiling on `BridgeState.leader_bonus_bps`: the settling validator may take
+/// at most half the fee off the top, so the co-signers' stake-weighted share
+/// can never be configured away.
+pub const MAX_LEADER_BONUS_BPS: u16 = 5_000;
+
 /// Verify a BPFLoaderUpgradeable `ProgramData` account's upgrade authority
 /// matches `expected` (#204). Closes the init front-run race: only the wallet
 /// holding the program's upgrade authority can call the `initialize_*`
@@ -150,6 +177,48 @@ fn public_amount_bytes(ext_amount: i64) -> [u8; 32] {
     out
 }
 
+/// Add a co-signer's fee share to its validator PDA's `pending_rewards`.
+/// `verify_validator_quorum` has already checked the PDA is the canonical,
+/// program-owned account of an active validator; it only has to be writable,
+/// which the settling validator cannot dodge to keep the share for itself.
+fn credit_pending_rewards(validator_pda: &AccountInfo, amount: u64) -> Result<()> {
+    require!(
+        validator_pda.is_writable,
+        BridgeError::QuorumAccountNotWritable
+    );
+    let mut data = validator_pda.try_borrow_mut_data()?;
+    let mut validator = ValidatorAccount::try_deserialize(&mut &data[..])?;
+    validator.pending_rewards = validator
+        .pending_rewards
+        .checked_add(amount)
+        .ok_or(BridgeError::InvalidAmount)?;
+    validator.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/// Whether a co-signer's fee account on the SPL path is a `token_program`
+/// account for `mint` held by the co-signer's own wallet. A co-signer that has
+/// no such account yet (its associated account for a newly shielded mint was
+/// never created) must not be able to block settlement, so this is a check the
+/// caller branches on rather than a hard error.
+fn is_fee_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    wallet: &Pubkey,
+    token_program: &Pubkey,
+) -> bool {
+    if account.owner != token_program || !account.is_writable {
+        return false;
+    }
+    let Ok(data) = account.try_borrow_data() else {
+        return false;
+    };
+    match TokenAccount::try_deserialize(&mut &data[..]) {
+        Ok(token_account) => token_account.mint == *mint && token_account.owner == *wallet,
+        Err(_) => false,
+    }
+}
+
 #[program]
 pub mod paraloom_program {
     use super::*;
@@ -181,6 +250,8 @@ pub mod paraloom_program {
         // uncapped (which would be the exact unbounded-loss risk the cap
         // exists to remove).
         bridge_state.deposit_cap = 0;
+        // Fees start split purely by stake; see `set_leader_bonus`.
+        bridge_state.leader_bonus_bps = 0;
 
         msg!(
             "Bridge initialized with merkle root, program_version={}",
@@ -293,6 +364,11 @@ pub mod paraloom_program {
     /// must be a registered validator and a supermajority of validators must
     /// co-sign, passed as `(wallet, validator PDA)` pairs in
     /// `remaining_accounts`. No single key can settle alone.
+    ///
+    /// A withdrawal's fee is split across the signer and the counted
+    /// co-signers (`quorum::split_fee`), each share credited to that
+    /// validator's `pending_rewards`. A co-signer owed a non-zero share must
+    /// pass its PDA writable.
     pub fn transact(
         ctx: Context<Transact>,
         nullifiers: [[u8; 32]; 2],
@@ -362,18 +438,19 @@ pub mod paraloom_program {
             BridgeError::ValidatorNotActive
         );
 
-        // Supermajority co-sign (#260) — no single key settles.
-        quorum::verify_validator_quorum(
+        // Supermajority co-sign (#260) — no single key settles. Stakes are
+        // read at the stake epoch boundary, the snapshot the L2 approved
+        // against, and the fee below is split by the same stakes.
+        let epoch = current_stake_epoch()?;
+        let leader_stake = ctx.accounts.validator_account.stake_at_epoch_start(epoch);
+        let members = quorum::verify_validator_quorum(
             ctx.program_id,
             &ctx.accounts.validator_registry,
+            epoch,
             // The settling `authority` is excluded from its own quorum, so a
             // supermajority of *independent* validator stake must co-sign.
             &ctx.accounts.authority.key(),
-            if ctx.accounts.validator_account.is_active {
-                ctx.accounts.validator_account.stake_amount
-            } else {
-                0
-            },
+            leader_stake,
             ctx.remaining_accounts,
         )?;
 
@@ -412,14 +489,15 @@ pub mod paraloom_program {
         // pre-append root the proof was checked against) is untouched; the new
         // notes extend the tree for future spends.
         let mut tree = ctx.accounts.merkle_tree.load_mut()?;
+        let leaf_index = tree.next_index;
         tree.append(output_commitments[0])?;
         let new_root = tree.append(output_commitments[1])?;
         drop(tree);
 
         // Move external funds. `ext_amount < 0` withdraws from the vault; the
-        // settling validator earns the same 25 bps fee as `withdraw`.
-        // `is_active` was already checked up front (#594); the settling
-        // validator is guaranteed active here.
+        // settling validator and its co-signers share the same 25 bps fee as
+        // `withdraw`. `is_active` was already checked up front (#594); the
+        // settling validator is guaranteed active here.
         let validator_account = &mut ctx.accounts.validator_account;
 
         let mut fee = 0u64;
@@ -459,9 +537,24 @@ pub mod paraloom_program {
                 ),
                 payout,
             )?;
+
+            // The fee never leaves the vault here: each share is a claim on it
+            // through `claim_rewards`.
+            let member_stakes: Vec<u64> = members.iter().map(|m| m.stake).collect();
+            let split = quorum::split_fee(
+                fee,
+                bridge_state.leader_bonus_bps,
+                leader_stake,
+                &member_stakes,
+            );
+            for (member, share) in members.iter().zip(&split.members) {
+                if *share > 0 {
+                    credit_pending_rewards(&ctx.remaining_accounts[2 * member.pair + 1], *share)?;
+                }
+            }
             validator_account.pending_rewards = validator_account
                 .pending_rewards
-                .checked_add(fee)
+                .checked_add(split.leader)
                 .ok_or(BridgeError::InvalidAmount)?;
 
             // Maintain the public withdrawal-volume aggregate, mirroring
@@ -497,6 +590,7 @@ pub mod paraloom_program {
             recipient: ctx.accounts.recipient.key(),
             timestamp: now,
             settlement_id,
+            leaf_index,
         });
 
         msg!(
@@ -522,6 +616,17 @@ pub mod paraloom_program {
     /// The proof is therefore bound to this exact mint, and `asset_vault` is
     /// derived from the same `mint`, so a note can only be paid from the vault
     /// of the asset it was shielded into.
+    ///
+    /// `remaining_accounts` are the quorum's `(wallet, validator PDA)` pairs
+    /// followed by one token account for `mint` per pair, in the same order.
+    /// The fee is split as in `transact`, but paid inline: the signer's share
+    /// to `fee_token_account`, each counted co-signer's to its token account.
+    /// A co-signer whose account is not its own token account for `mint`
+    /// forfeits its share to the settling validator, so no fee is stranded in
+    /// `asset_vault`. The settler cannot engineer a forfeit: every co-signer
+    /// derives the same account list (each member's associated token account)
+    /// and signs it, so a share is only forfeited when its owner has no
+    /// account for the mint.
     pub fn transact_spl(
         ctx: Context<TransactSpl>,
         nullifiers: [[u8; 32]; 2],
@@ -577,16 +682,24 @@ pub mod paraloom_program {
             BridgeError::ValidatorNotActive
         );
 
-        quorum::verify_validator_quorum(
+        // Two quorum accounts and one fee account per co-signer.
+        require!(
+            ctx.remaining_accounts.len() % 3 == 0,
+            BridgeError::InvalidFeeAccount
+        );
+        let (quorum_accounts, member_fee_accounts) = ctx
+            .remaining_accounts
+            .split_at(ctx.remaining_accounts.len() / 3 * 2);
+
+        let epoch = current_stake_epoch()?;
+        let leader_stake = ctx.accounts.validator_account.stake_at_epoch_start(epoch);
+        let members = quorum::verify_validator_quorum(
             ctx.program_id,
             &ctx.accounts.validator_registry,
+            epoch,
             &ctx.accounts.authority.key(),
-            if ctx.accounts.validator_account.is_active {
-                ctx.accounts.validator_account.stake_amount
-            } else {
-                0
-            },
-            ctx.remaining_accounts,
+            leader_stake,
+            quorum_accounts,
         )?;
 
         require!(
@@ -616,6 +729,7 @@ pub mod paraloom_program {
         nf1.withdrawal_id = settlement_id;
 
         let mut tree = ctx.accounts.merkle_tree.load_mut()?;
+        let leaf_index = tree.next_index;
         tree.append(output_commitments[0])?;
         let new_root = tree.append(output_commitments[1])?;
         drop(tree);
@@ -659,23 +773,64 @@ pub mod paraloom_program {
                 ctx.accounts.mint.decimals,
             )?;
 
-            // Settling-validator fee, paid in the withdrawn asset to the
-            // validator's own token account (constrained to `authority` in the
-            // accounts struct). The native path credits lamport `pending_rewards`
-            // for a later claim; here the token fee is paid inline.
-            if fee > 0 {
+            // Fee shares, paid in the withdrawn asset. The native path credits
+            // lamport `pending_rewards` for a later claim; here each share is
+            // paid inline — the settling validator's to its own token account
+            // (constrained to `authority` in the accounts struct), each
+            // co-signer's to a token account checked against its wallet. An
+            // unpayable share goes to the settling validator with its own.
+            let member_stakes: Vec<u64> = members.iter().map(|m| m.stake).collect();
+            let split = quorum::split_fee(
+                fee,
+                bridge_state.leader_bonus_bps,
+                leader_stake,
+                &member_stakes,
+            );
+            let mint_key = ctx.accounts.mint.key();
+            let token_program_key = ctx.accounts.token_program.key();
+            let split = split.forfeit_unpayable(|i| {
+                let member = &members[i];
+                let wallet = quorum_accounts[2 * member.pair].key;
+                let payable = is_fee_token_account(
+                    &member_fee_accounts[member.pair],
+                    &mint_key,
+                    wallet,
+                    &token_program_key,
+                );
+                if !payable {
+                    msg!(
+                        "Co-signer {} has no {} fee account; its share goes to the settler",
+                        wallet,
+                        mint_key
+                    );
+                }
+                payable
+            });
+            let mut payees = vec![(
+                ctx.accounts.fee_token_account.to_account_info(),
+                split.leader,
+            )];
+            for (member, share) in members.iter().zip(&split.members) {
+                if *share > 0 {
+                    payees.push((member_fee_accounts[member.pair].clone(), *share));
+                }
+            }
+            for (to, amount) in payees {
+                if amount == 0 {
+                    continue;
+                }
                 token_interface::transfer_checked(
                     CpiContext::new_with_signer(
                         ctx.accounts.token_program.to_account_info(),
                         TransferChecked {
                             from: ctx.accounts.asset_vault.to_account_info(),
                             mint: ctx.accounts.mint.to_account_info(),
-                            to: ctx.accounts.fee_token_account.to_account_info(),
+                            to,
                             authority: ctx.accounts.asset_vault_authority.to_account_info(),
                         },
                         signer_seeds,
                     ),
-                    fee,
+                    amount,
                     ctx.accounts.mint.decimals,
                 )?;
             }
@@ -699,6 +854,7 @@ pub mod paraloom_program {
             recipient: ctx.accounts.recipient_token_account.key(),
             timestamp: now,
             settlement_id,
+            leaf_index,
         });
 
         msg!(
@@ -731,12 +887,11 @@ pub mod paraloom_program {
     /// Rotate the bridge settlement authority to a new key.
     ///
     /// `initialize` (#204) pins the bridge authority to the program's upgrade
-    /// authority at genesis, to close the init front-run race. But ongoing
-    /// settlement (`transact`, `has_one = authority`) is performed by a
-    /// node-resident validator key — which must NOT be the upgrade authority
-    /// sitting on a public
-    /// host. This hands settlement control from the genesis authority to the
-    /// operating validator (a staked, slashable key), keeping the upgrade
+    /// authority at genesis, to close the init front-run race. Settlement
+    /// (`transact`) is not bound to this key: any active validator the quorum
+    /// co-signs may settle, so a backup leader can take over from a stalled
+    /// one. The rotation hands the bridge authority from the genesis key to
+    /// the operating validator (a staked, slashable key), keeping the upgrade
     /// authority offline. Gated on the COLD registry authority (not the current
     /// bridge authority), so the cold key always manages the hot settlement key
     /// and a compromised hot key cannot rotate control away.
@@ -780,6 +935,28 @@ pub mod paraloom_program {
         Ok(())
     }
 
+    /// Set the settling validator's bonus (`BridgeState.leader_bonus_bps`):
+    /// the share of every settlement fee it takes before the rest is split
+    /// by stake across it and its co-signers.
+    ///
+    /// It starts at 0 (a pure stake-weighted split). A bonus pays the leader
+    /// for the transaction fees and compute it alone spends submitting, but
+    /// it is capped at [`MAX_LEADER_BONUS_BPS`] so co-signing always pays.
+    /// Cold-authority gated like `set_deposit_cap`: a bonus set by the hot
+    /// settlement key would be the settlement key paying itself.
+    pub fn set_leader_bonus(ctx: Context<SetLeaderBonus>, bonus_bps: u16) -> Result<()> {
+        require!(
+            bonus_bps <= MAX_LEADER_BONUS_BPS,
+            BridgeError::LeaderBonusTooHigh
+        );
+        let bridge_state = &mut ctx.accounts.bridge_state;
+        let previous = bridge_state.leader_bonus_bps;
+        bridge_state.leader_bonus_bps = bonus_bps;
+
+        msg!("Leader bonus set: {} -> {} bps", previous, bonus_bps);
+        Ok(())
+    }
+
     /// Set the dual-stake token floor (`ValidatorRegistry.min_token_stake`): the
     /// minimum PARALOOM-token stake `register_validator` requires alongside the
     /// SOL stake.
@@ -889,6 +1066,10 @@ pub mod paraloom_program {
         let validator_account = &mut ctx.accounts.validator_account;
         let validator_registry = &mut ctx.accounts.validator_registry;
 
+        // A fresh PDA: this records a boundary stake of 0 for the epoch it
+        // joins in, so it enters the L2's set from the next boundary.
+        validator_account.roll_stake_epoch()?;
+        validator_registry.roll_stake_epoch()?;
         validator_account.validator = ctx.accounts.validator.key();
         validator_account.stake_amount = stake_amount;
         validator_account.reputation_score = 1000;
@@ -942,6 +1123,8 @@ pub mod paraloom_program {
         // rather than something an attacker can register, co-sign with, and
         // instantly reclaim.
         let now_slot = Clock::get()?.slot;
+        validator_account.roll_stake_epoch()?;
+        validator_registry.roll_stake_epoch()?;
         validator_account.is_active = false;
         validator_account.stake_amount = 0;
         validator_account.unbonding_amount = validator_account
@@ -1049,11 +1232,16 @@ pub mod paraloom_program {
         Ok(())
     }
 
-    /// Slash validator
+    /// Slash validator for the misbehaviour identified by `evidence_id`.
+    ///
+    /// The `slash_record` PDA is keyed by (validator, evidence_id) and created
+    /// here, so a retried or replayed slash for the same evidence fails at
+    /// account init instead of charging the validator twice.
     pub fn slash_validator(
         ctx: Context<SlashValidator>,
         validator: Pubkey,
         slash_percentage: u8, // 1-100
+        evidence_id: [u8; 32],
     ) -> Result<()> {
         let validator_account = &mut ctx.accounts.validator_account;
 
@@ -1092,6 +1280,8 @@ pub mod paraloom_program {
         };
         let token_slash = (old_token as u128 * slash_percentage as u128 / 100) as u64;
         validator_account.times_slashed = validator_account.times_slashed.saturating_add(1);
+        validator_account.roll_stake_epoch()?;
+        ctx.accounts.validator_registry.roll_stake_epoch()?;
 
         if was_active {
             validator_account.stake_amount = old_stake.saturating_sub(slash_amount);
@@ -1172,6 +1362,13 @@ pub mod paraloom_program {
             )?;
         }
 
+        let slash_record = &mut ctx.accounts.slash_record;
+        slash_record.validator = validator;
+        slash_record.evidence_id = evidence_id;
+        slash_record.slash_percentage = slash_percentage;
+        slash_record.slash_amount = slash_amount;
+        slash_record.slot = Clock::get()?.slot;
+
         emit!(ValidatorSlashedEvent {
             validator,
             slash_amount,
@@ -1199,6 +1396,8 @@ pub mod paraloom_program {
         registry.active_validators = 0;
         registry.minimum_stake = MIN_VALIDATOR_STAKE;
         registry.total_active_stake = 0;
+        registry.epoch_total_active_stake = 0;
+        registry.total_stake_epoch = current_stake_epoch()?;
         // Pin the dual-stake token: `register_validator` only accepts this mint
         // as the token half. The shared `stake_token_vault` is created by the
         // context's `init` constraint under the `stake_vault_authority` PDA.
@@ -1401,7 +1600,13 @@ pub mod paraloom_program {
     ///
     /// The extra bytes are zero-filled, so `deposit_cap` starts at 0 — the
     /// closed, safe default (every deposit refused) that `initialize` also uses.
-    /// Open it deliberately afterward with `set_deposit_cap`.
+    /// Open it deliberately afterward with `set_deposit_cap`. The same grow
+    /// covers `leader_bonus_bps`, appended later, which starts at 0 (no bonus).
+    ///
+    /// The upgrade that needs this migration is also what changed the
+    /// program's interface, so it stamps [`PROGRAM_VERSION`] over the recorded
+    /// `program_version`: an L2 built for the new layout then starts, and one
+    /// still built for the old layout refuses to.
     pub fn migrate_bridge_state(ctx: Context<MigrateBridgeState>) -> Result<()> {
         check_upgrade_authority(&ctx.accounts.program_data, &ctx.accounts.authority.key())?;
 
@@ -1440,10 +1645,13 @@ pub mod paraloom_program {
             }
             bridge_ai.resize(new_len)?;
         }
+        // `program_version` is the first field, right after the discriminator.
+        bridge_ai.try_borrow_mut_data()?[8..12].copy_from_slice(&PROGRAM_VERSION.to_le_bytes());
 
         msg!(
-            "BridgeState migrated to {} bytes (deposit_cap starts 0)",
-            new_len
+            "BridgeState migrated to {} bytes, program_version={:#010x} (deposit_cap and leader_bonus_bps start 0)",
+            new_len,
+            PROGRAM_VERSION
         );
         Ok(())
     }
@@ -1601,6 +1809,11 @@ pub mod paraloom_program {
             // means a forgotten step costs a rejected registration rather
             // than a validator slot bought with no token stake.
             min_token_stake: RECOMMENDED_MIN_TOKEN_STAKE,
+            // The rebuilt total stands for the whole current epoch: a reset
+            // redefines the denominator rather than changing stake, and there
+            // is no trustworthy earlier total to carry over.
+            epoch_total_active_stake: total_active_stake,
+            total_stake_epoch: current_stake_epoch()?,
         };
         let mut data = registry_ai.try_borrow_mut_data()?;
         let mut cursor = std::io::Cursor::new(&mut data[..]);
@@ -1638,6 +1851,7 @@ pub mod paraloom_program {
         if was_active {
             let now_slot = Clock::get()?.slot;
             let v = &mut ctx.accounts.validator_account;
+            v.roll_stake_epoch()?;
             v.is_active = false;
             // Route the stake into unbonding rather than stranding it: a
             // deactivated validator can't `unregister` (that requires
@@ -1648,6 +1862,7 @@ pub mod paraloom_program {
             v.unbonding_slot = now_slot.saturating_add(UNBONDING_SLOTS);
             v.stake_amount = 0;
             let registry = &mut ctx.accounts.validator_registry;
+            registry.roll_stake_epoch()?;
             registry.total_active_stake = registry.total_active_stake.saturating_sub(stake);
             registry.active_validators = registry.active_validators.saturating_sub(1);
         }
@@ -1747,7 +1962,9 @@ pub mod paraloom_program {
 
     /// One-time migration: grow an existing `ValidatorAccount` PDA to the
     /// current layout. The added unbonding fields zero-fill (resize clears the
-    /// tail), which reads as "nothing pending". Upgrade-authority gated (#204),
+    /// tail), which reads as "nothing pending"; a zero `stake_epoch` reads as
+    /// "unchanged since epoch 0", true of a PDA no instruction could load
+    /// before this grow. Upgrade-authority gated (#204),
     /// mirroring the registry migration; idempotent (a no-op once the account
     /// is already the new size).
     pub fn migrate_validator_account(
@@ -1883,7 +2100,7 @@ pub struct ResetValidatorRegistry<'info> {
 #[derive(Accounts)]
 #[instruction(nullifiers: [[u8; 32]; 2])]
 pub struct TransactSpl<'info> {
-    #[account(mut, seeds = [b"bridge_state"], bump, has_one = authority)]
+    #[account(mut, seeds = [b"bridge_state"], bump)]
     pub bridge_state: Account<'info, BridgeState>,
 
     #[account(mut, seeds = [b"merkle_tree"], bump)]
@@ -1956,13 +2173,14 @@ pub struct TransactSpl<'info> {
 #[derive(Accounts)]
 #[instruction(nullifiers: [[u8; 32]; 2])]
 pub struct Transact<'info> {
-    // `has_one = authority` binds settlement to the bridge authority /
-    // consensus leader, exactly as `Withdraw` does (#178).
+    // Not bound to the bridge authority: the settling `authority` may be any
+    // active validator, so when the scheduled leader stalls a backup can land
+    // the same spend. The supermajority co-sign is what gates settlement; the
+    // settler's own stake is excluded from it.
     #[account(
         mut,
         seeds = [b"bridge_state"],
-        bump,
-        has_one = authority
+        bump
     )]
     pub bridge_state: Account<'info, BridgeState>,
 
@@ -2011,8 +2229,8 @@ pub struct Transact<'info> {
     pub recipient: SystemAccount<'info>,
 
     /// The settling validator's account, bound by seeds to the `authority`
-    /// signer: only a registered validator can settle, and the fee is credited
-    /// here (mirrors `Withdraw`).
+    /// signer: only a registered validator can settle, and the leader's share
+    /// of the fee is credited here (mirrors `Withdraw`).
     #[account(
         mut,
         seeds = [b"validator", authority.key().as_ref()],
@@ -2043,10 +2261,10 @@ pub struct Pause<'info> {
 
     // Freeze/rotate power is gated on the COLD registry authority, NOT the hot
     // `bridge_state.authority` (the node-resident settlement key). Settlement
-    // (`transact`) stays bound to the hot key but is quorum-gated; pause/unpause
-    // and rotation are not quorum-gated, so a compromise of the deliberately-hot
-    // key must not be able to freeze the bridge or rotate itself in. Requiring
-    // the cold authority keeps those capabilities off the settlement host.
+    // (`transact`) is quorum-gated; pause/unpause and rotation are not, so a
+    // compromise of the deliberately-hot key must not be able to freeze the
+    // bridge or rotate itself in. Requiring the cold authority keeps those
+    // capabilities off the settlement host.
     #[account(
         seeds = [b"validator_registry"],
         bump,
@@ -2079,6 +2297,26 @@ pub struct SetBridgeAuthority<'info> {
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetLeaderBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"bridge_state"],
+        bump
+    )]
+    pub bridge_state: Account<'info, BridgeState>,
+
+    // Cold-authority gated (see `SetDepositCap`).
+    #[account(
+        seeds = [b"validator_registry"],
+        bump,
+        has_one = authority
+    )]
+    pub validator_registry: Account<'info, ValidatorRegistry>,
+
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct SetDepositCap<'info> {
     #[account(
@@ -2591,6 +2829,7 @@ pub struct MigrateValidatorAccount<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(validator: Pubkey, slash_percentage: u8, evidence_id: [u8; 32])]
 pub struct SlashValidator<'info> {
     #[account(
         mut,
@@ -2641,7 +2880,21 @@ pub struct SlashValidator<'info> {
 
     pub token_program: Interface<'info, TokenInterface>,
 
+    /// One record per (validator, evidence): `init` fails if this evidence has
+    /// already been slashed, so a retry after a lost confirmation is a no-op.
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + SlashRecord::INIT_SPACE,
+        seeds = [b"slash_record", validator.as_ref(), evidence_id.as_ref()],
+        bump
+    )]
+    pub slash_record: Account<'info, SlashRecord>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[account]
@@ -2677,6 +2930,12 @@ pub struct BridgeState {
     /// to a chosen ceiling via `set_deposit_cap`. Appended last to keep the
     /// fixed `program_version` offset the L2 reads unchanged.
     pub deposit_cap: u64,
+    /// Basis points of each settlement fee the settling validator takes before
+    /// the remainder is split by stake with its co-signers. At most
+    /// [`MAX_LEADER_BONUS_BPS`]; set by the cold authority via
+    /// `set_leader_bonus`. Appended after `deposit_cap`, so an older account
+    /// is grown by `migrate_bridge_state` and starts at 0.
+    pub leader_bonus_bps: u16,
 }
 
 /// Per-asset SPL shielding config (#779): one PDA per enabled mint, holding a
@@ -2709,6 +2968,18 @@ pub struct NullifierAccount {
     pub withdrawal_id: u64,
 }
 
+/// Proof that a validator was slashed for one piece of evidence; its
+/// existence is what makes `slash_validator` idempotent.
+#[account]
+#[derive(InitSpace)]
+pub struct SlashRecord {
+    pub validator: Pubkey,
+    pub evidence_id: [u8; 32],
+    pub slash_percentage: u8,
+    pub slash_amount: u64,
+    pub slot: u64,
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct ValidatorRegistry {
@@ -2736,6 +3007,36 @@ pub struct ValidatorRegistry {
     /// zero would mean every registration clears the gate, so the gate opens
     /// only when the authority lowers it on purpose.
     pub min_token_stake: u64,
+    /// `total_active_stake` as it stood when stake epoch `total_stake_epoch`
+    /// began, kept like [`ValidatorAccount::epoch_stake`].
+    pub epoch_total_active_stake: u64,
+    /// Stake epoch of the last change to `total_active_stake`.
+    pub total_stake_epoch: u64,
+}
+
+impl ValidatorRegistry {
+    /// Record `total_active_stake` as it stood when the current stake epoch
+    /// began. Called before every change to it; only the first change of an
+    /// epoch writes.
+    fn roll_stake_epoch(&mut self) -> Result<()> {
+        let epoch = current_stake_epoch()?;
+        if epoch != self.total_stake_epoch {
+            self.epoch_total_active_stake = self.total_active_stake;
+            self.total_stake_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    /// `total_active_stake` as it stood when stake epoch `epoch` began: what
+    /// `roll_stake_epoch` leaves in `epoch_total_active_stake`, read without
+    /// writing. `epoch` is the current stake epoch.
+    pub fn total_at_epoch_start(&self, epoch: u64) -> u64 {
+        if epoch == self.total_stake_epoch {
+            self.epoch_total_active_stake
+        } else {
+            self.total_active_stake
+        }
+    }
 }
 
 #[account]
@@ -2766,6 +3067,54 @@ pub struct ValidatorAccount {
     /// released to the validator by `withdraw_unbonded_stake` once the same
     /// `unbonding_slot` elapses. Parallels `unbonding_amount` for the token.
     pub token_unbonding_amount: u64,
+    /// Active stake (`stake_amount` while `is_active`, else 0) as it stood
+    /// when stake epoch `stake_epoch` began. The first change of an epoch
+    /// writes it before applying the change, so the L2 reads the stake at
+    /// the boundary after the fact (see [`STAKE_EPOCH_SLOTS`]). A PDA cannot
+    /// close within an epoch of leaving the set (`UNBONDING_SLOTS` is far
+    /// longer), so no boundary stake is lost with it.
+    pub epoch_stake: u64,
+    /// Stake epoch of the last change to the active stake.
+    pub stake_epoch: u64,
+}
+
+impl ValidatorAccount {
+    /// The stake this validator counts toward the quorum with.
+    pub fn active_stake(&self) -> u64 {
+        if self.is_active {
+            self.stake_amount
+        } else {
+            0
+        }
+    }
+
+    /// Record the active stake as it stood when the current stake epoch
+    /// began. Called before every change to `stake_amount` or `is_active`;
+    /// only the first change of an epoch writes.
+    fn roll_stake_epoch(&mut self) -> Result<()> {
+        let epoch = current_stake_epoch()?;
+        if epoch != self.stake_epoch {
+            self.epoch_stake = self.active_stake();
+            self.stake_epoch = epoch;
+        }
+        Ok(())
+    }
+
+    /// The active stake as it stood when stake epoch `epoch` began: what
+    /// `roll_stake_epoch` leaves in `epoch_stake`, read without writing.
+    /// `epoch` is the current stake epoch.
+    pub fn stake_at_epoch_start(&self, epoch: u64) -> u64 {
+        if epoch == self.stake_epoch {
+            self.epoch_stake
+        } else {
+            self.active_stake()
+        }
+    }
+}
+
+/// The stake epoch the current slot falls in.
+fn current_stake_epoch() -> Result<u64> {
+    Ok(Clock::get()?.slot / STAKE_EPOCH_SLOTS)
 }
 
 /// Emitted by `deposit_note` (circuit v3): the appended note commitment and its
@@ -2801,6 +3150,9 @@ pub struct TransactEvent {
     pub recipient: Pubkey,
     pub timestamp: i64,
     pub settlement_id: u64,
+    /// Leaf index of `out_commitment0`; `out_commitment1` is the next one.
+    /// Last, so decoders of the earlier layout still read every other field.
+    pub leaf_index: u64,
 }
 
 #[event]
@@ -2916,4 +3268,13 @@ pub enum BridgeError {
 
     #[msg("Registry reset rebuilt fewer active validators than the caller declared (incomplete remaining_accounts list)")]
     RegistryResetCountMismatch,
+
+    #[msg("A co-signer owed a fee share passed its validator account read-only")]
+    QuorumAccountNotWritable,
+
+    #[msg("transact_spl expects one co-signer fee account per quorum (wallet, PDA) pair")]
+    InvalidFeeAccount,
+
+    #[msg("Leader bonus exceeds MAX_LEADER_BONUS_BPS")]
+    LeaderBonusTooHigh,
 }
@@ -6,15 +6,28 @@
 //! natively; this module only confirms, for each counted member, that:
 //!   - its wallet signed this transaction (`is_signer`),
 //!   - it owns the canonical, program-owned [`ValidatorAccount`] PDA
-//!     (`seeds = [b"validator", wallet]`) and that account is `is_active`,
-//! and that each validator is counted at most once. Relying on the runtime for
-//! signature verification keeps the on-chain attack surface minimal.
+//!     (`seeds = [b"validator", wallet]`) and that account held stake when
+//!     the current stake epoch began, and
+//!   - it is counted at most once.
+//!
+//! Relying on the runtime for signature verification keeps the on-chain
+//! attack surface minimal.
+//!
+//! Stake is read at the stake epoch boundary
+//! ([`ValidatorAccount::stake_at_epoch_start`],
+//! [`ValidatorRegistry::total_at_epoch_start`]), the same snapshot the L2
+//! tallies a request's votes against, so a stake change mid-epoch moves
+//! neither side and a quorum the L2 approved clears here.
 //!
 //! `quorum_accounts` are `(validator_wallet, validator_pda)` pairs passed via
 //! `remaining_accounts`. Called by `transact` (which subsumed the former
 //! `withdraw` / `shielded_transfer` paths). The
 //! node-side co-signing round that produces a real multi-validator quorum is
 //! tracked separately in #260 (this enforces it on-chain).
+//!
+//! The members counted here are also the members paid: [`split_fee`] divides a
+//! settlement's fee between the settling validator and every counted
+//! co-signer, pro rata by stake, after an optional leader bonus.
 
 use crate::{BridgeError, ValidatorAccount, ValidatorRegistry};
 use anchor_lang::prelude::*;
@@ -28,30 +41,106 @@ pub fn quorum_threshold(total_active_stake: u64) -> u64 {
     total_active_stake.saturating_mul(2) / 3 + 1
 }
 
-/// Verify that a stake-weighted supermajority of registered, active validators
-/// co-signed this transaction. Returns [`BridgeError::QuorumNotMet`] if the
-/// summed stake of the distinct active validators present and signing is below
-/// [`quorum_threshold`] of the registry's total active stake.
+/// A co-signer counted toward the quorum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuorumMember {
+    /// Index of the member's `(wallet, pda)` pair in `quorum_accounts`, so its
+    /// wallet is `quorum_accounts[2 * pair]` and its PDA the account after.
+    pub pair: usize,
+    /// The stake it was counted with.
+    pub stake: u64,
+}
+
+/// How a settlement fee is divided: `leader` to the settling validator,
+/// `members[i]` to the i-th counted [`QuorumMember`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeSplit {
+    pub leader: u64,
+    pub members: Vec<u64>,
+}
+
+impl FeeSplit {
+    /// Move the share of every member `payable` refuses to the leader, so a
+    /// share that cannot be delivered is paid to the settler instead of being
+    /// stranded. `payable` is asked only about non-zero shares, by member
+    /// index. The total is unchanged, so the leader's sum cannot overflow.
+    pub fn forfeit_unpayable(mut self, mut payable: impl FnMut(usize) -> bool) -> Self {
+        for (i, share) in self.members.iter_mut().enumerate() {
+            if *share > 0 && !payable(i) {
+                self.leader += *share;
+                *share = 0;
+            }
+        }
+        self
+    }
+}
+
+/// Divide `fee` between the settling validator and the counted co-signers.
+///
+/// `leader_bonus_bps` of the fee goes to the leader off the top; the rest is
+/// shared by stake across the leader and every member. Each member's share is
+/// rounded down, and whatever the rounding leaves (the dust) goes to the
+/// leader, so the shares always sum to exactly `fee`. With no stake to weight
+/// by, the leader takes it all.
+pub fn split_fee(
+    fee: u64,
+    leader_bonus_bps: u16,
+    leader_stake: u64,
+    member_stakes: &[u64],
+) -> FeeSplit {
+    let bonus = (fee as u128 * leader_bonus_bps.min(10_000) as u128 / 10_000) as u64;
+    let pool = (fee - bonus) as u128;
+    let total_stake = member_stakes
+        .iter()
+        .fold(leader_stake as u128, |sum, s| sum + *s as u128);
+
+    let members: Vec<u64> = member_stakes
+        .iter()
+        .map(|stake| {
+            if total_stake == 0 {
+                0
+            } else {
+                // `stake <= total_stake`, so the share is at most `pool`.
+                (pool * *stake as u128 / total_stake) as u64
+            }
+        })
+        .collect();
+    let paid: u64 = members.iter().sum();
+    FeeSplit {
+        leader: fee - paid,
+        members,
+    }
+}
+
+/// Verify that a stake-weighted supermajority of registered validators
+/// co-signed this transaction, weighing each by its stake when stake epoch
+/// `epoch` began. Returns [`BridgeError::QuorumNotMet`] if the summed stake of
+/// the distinct validators present and signing is below [`quorum_threshold`]
+/// of the registry's total active stake at that boundary; otherwise the
+/// members it counted, in the order they were passed. `authority_active_stake`
+/// is the settlement authority's stake at the same boundary.
 pub fn verify_validator_quorum(
     program_id: &Pubkey,
     registry: &ValidatorRegistry,
+    epoch: u64,
     settlement_authority: &Pubkey,
     authority_active_stake: u64,
     quorum_accounts: &[AccountInfo],
-) -> Result<()> {
+) -> Result<Vec<QuorumMember>> {
     // The settlement authority is not an independent second factor: its own
     // stake counts toward neither the tally nor the denominator. Subtracting it
     // keeps the threshold a supermajority of exactly the stake that can be
     // counted, so a compromised settlement key still needs an independent
     // supermajority to settle.
     let eligible_stake = registry
-        .total_active_stake
+        .total_at_epoch_start(epoch)
         .saturating_sub(authority_active_stake);
     let threshold = quorum_threshold(eligible_stake);
     let mut counted_stake: u64 = 0;
     let mut seen: Vec<Pubkey> = Vec::new();
+    let mut members: Vec<QuorumMember> = Vec::new();
 
-    for pair in quorum_accounts.chunks(2) {
+    for (index, pair) in quorum_accounts.chunks(2).enumerate() {
         if pair.len() != 2 {
             break;
         }
@@ -83,7 +172,10 @@ pub fn verify_validator_quorum(
             Ok(v) => v,
             Err(_) => continue,
         };
-        if !validator.is_active || validator.validator != *wallet.key {
+        // A validator that joined this epoch, or held no active stake at its
+        // start, has no say until the next boundary.
+        let stake = validator.stake_at_epoch_start(epoch);
+        if stake == 0 || validator.validator != *wallet.key {
             continue;
         }
         // Count each validator at most once.
@@ -92,7 +184,8 @@ pub fn verify_validator_quorum(
         }
         seen.push(*wallet.key);
         // Weight by the validator's staked amount, not a head count.
-        counted_stake = counted_stake.saturating_add(validator.stake_amount);
+        counted_stake = counted_stake.saturating_add(stake);
+        members.push(QuorumMember { pair: index, stake });
     }
 
     // No signer set may count more stake than the eligible active total. A
@@ -102,13 +195,17 @@ pub fn verify_validator_quorum(
     // denominator be cleared by stake it does not account for.
     require!(counted_stake <= eligible_stake, BridgeError::QuorumNotMet);
     require!(counted_stake >= threshold, BridgeError::QuorumNotMet);
-    Ok(())
+    Ok(members)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A stake epoch after every fixture's last stake change, so each
+    /// fixture counts with its current stake.
+    const EPOCH: u64 = 1;
+
     fn prog() -> Pubkey {
         crate::ID
     }
@@ -128,6 +225,8 @@ mod tests {
             total_active_stake,
             stake_mint: Pubkey::default(),
             min_token_stake: 0,
+            epoch_total_active_stake: 0,
+            total_stake_epoch: 0,
         }
     }
 
@@ -136,6 +235,18 @@ mod tests {
     }
 
     fn validator_data_staked(wallet: Pubkey, is_active: bool, stake_amount: u64) -> Vec<u8> {
+        validator_data_at(wallet, is_active, stake_amount, 0, 0)
+    }
+
+    /// A validator whose stake last changed in `stake_epoch`, when it began
+    /// with `epoch_stake` active.
+    fn validator_data_at(
+        wallet: Pubkey,
+        is_active: bool,
+        stake_amount: u64,
+        stake_epoch: u64,
+        epoch_stake: u64,
+    ) -> Vec<u8> {
         let v = ValidatorAccount {
             validator: wallet,
             stake_amount,
@@ -152,6 +263,8 @@ mod tests {
             unbonding_slot: 0,
             token_stake_amount: 0,
             token_unbonding_amount: 0,
+            epoch_stake,
+            stake_epoch,
         };
         let mut buf = Vec::new();
         v.try_serialize(&mut buf).unwrap();
@@ -171,10 +284,103 @@ mod tests {
         assert_eq!(quorum_threshold(10), 7);
     }
 
+    #[test]
+    fn fee_is_split_pro_rata_after_the_leader_bonus() {
+        // 10% bonus off the top, then 9_000 shared over stakes 1:1:2.
+        let split = split_fee(10_000, 1_000, 1, &[1, 2]);
+        assert_eq!(split.members, vec![2_250, 4_500]);
+        assert_eq!(split.leader, 1_000 + 2_250);
+
+        // A full bonus pays the leader everything; no bonus, stake alone.
+        assert_eq!(split_fee(10_000, 10_000, 1, &[1, 2]).members, vec![0, 0]);
+        assert_eq!(split_fee(10_000, 0, 0, &[1, 3]).members, vec![2_500, 7_500]);
+    }
+
+    #[test]
+    fn rounding_dust_goes_to_the_leader() {
+        // 10 over three equal stakes: each member floors to 3, the leader
+        // takes its 3 plus the 1 left over.
+        let split = split_fee(10, 0, 1, &[1, 1]);
+        assert_eq!(split.members, vec![3, 3]);
+        assert_eq!(split.leader, 4);
+
+        // A 1-lamport fee cannot be shared: the whole unit is dust.
+        let split = split_fee(1, 0, 1_000_000_000, &[1_000_000_000]);
+        assert_eq!(
+            split,
+            FeeSplit {
+                leader: 1,
+                members: vec![0]
+            }
+        );
+
+        // Whatever the inputs, nothing is created or lost.
+        for (fee, bps, leader, members) in [
+            (u64::MAX, 333, u64::MAX, vec![u64::MAX, 7]),
+            (999, 1, 3, vec![5, 11, 13]),
+            (7, 0, 0, vec![0, 0]),
+        ] {
+            let split = split_fee(fee, bps, leader, &members);
+            assert_eq!(
+                split.leader as u128 + split.members.iter().map(|m| *m as u128).sum::<u128>(),
+                fee as u128
+            );
+        }
+    }
+
+    #[test]
+    fn a_settlement_fee_pays_every_cosigner_and_the_leader_keeps_the_dust() {
+        // 1 SOL withdrawn pays a 2_500_000-lamport fee. A 5% bonus (125_000)
+        // comes off the top; 2_375_000 is shared over the leader's 1 SOL and
+        // co-signers staking 1, 2 and 3 SOL (7 SOL in all).
+        const SOL: u64 = 1_000_000_000;
+        let fee = SOL * 25 / 10_000;
+        let split = split_fee(fee, 500, SOL, &[SOL, 2 * SOL, 3 * SOL]);
+        // 2_375_000 / 7 = 339_285.71…, so the shares floor to 339_285,
+        // 678_571 and 1_017_857.
+        assert_eq!(split.members, vec![339_285, 678_571, 1_017_857]);
+        // Bonus + the leader's own 339_285 + the 2 lamports of rounding dust.
+        assert_eq!(split.leader, 125_000 + 339_285 + 2);
+        assert_eq!(split.leader + split.members.iter().sum::<u64>(), fee);
+    }
+
+    #[test]
+    fn forfeited_shares_go_to_the_leader() {
+        // Shares 1_800 / 3_600 / 1_800 after a 10% bonus on stakes 1:1:2:1.
+        let split = split_fee(10_000, 1_000, 1, &[1, 2, 1]);
+        assert_eq!(split.leader, 2_800);
+
+        let mut asked = Vec::new();
+        let settled = split.clone().forfeit_unpayable(|i| {
+            asked.push(i);
+            i != 1
+        });
+        assert_eq!(asked, vec![0, 1, 2]);
+        assert_eq!(settled.members, vec![1_800, 0, 1_800]);
+        assert_eq!(settled.leader, 2_800 + 3_600);
+        assert_eq!(settled.leader + settled.members.iter().sum::<u64>(), 10_000);
+
+        // Every member forfeits: the leader is paid the whole fee.
+        let settled = split.clone().forfeit_unpayable(|_| false);
+        assert_eq!(
+            settled,
+            FeeSplit {
+                leader: 10_000,
+                members: vec![0, 0, 0]
+            }
+        );
+
+        // A zero share is never looked at: there is nothing to deliver.
+        let dust_only = split_fee(1, 0, 1, &[1]);
+        let settled = dust_only.clone().forfeit_unpayable(|_| panic!("asked"));
+        assert_eq!(settled, dust_only);
+    }
+
     #[test]
     fn empty_quorum_is_rejected() {
         assert!(
-            verify_validator_quorum(&prog(), &registry(3), &Pubkey::default(), 0, &[]).is_err()
+            verify_validator_quorum(&prog(), &registry(3), EPOCH, &Pubkey::default(), 0, &[])
+                .is_err()
         );
     }
 
@@ -196,7 +402,22 @@ mod tests {
         let s1 = AccountInfo::new(&w1, true, false, &mut l1, &mut e1, &sys, false, 0);
         let a1 = AccountInfo::new(&pda1, false, false, &mut lp1, &mut d1, &p, false, 0);
         let accts = [s0, a0, s1, a1];
-        assert!(verify_validator_quorum(&p, &registry(2), &Pubkey::default(), 0, &accts).is_ok());
+        let members =
+            verify_validator_quorum(&p, &registry(2), EPOCH, &Pubkey::default(), 0, &accts)
+                .unwrap();
+        assert_eq!(
+            members,
+            vec![
+                QuorumMember {
+                    pair: 0,
+                    stake: 1_000_000_000
+                },
+                QuorumMember {
+                    pair: 1,
+                    stake: 1_000_000_000
+                },
+            ]
+        );
     }
 
     #[test]
@@ -212,7 +433,10 @@ mod tests {
         let s0 = AccountInfo::new(&w0, true, false, &mut l0, &mut e0, &sys, false, 0);
         let a0 = AccountInfo::new(&pda0, false, false, &mut lp0, &mut d0, &p, false, 0);
         let accts = [s0, a0];
-        assert!(verify_validator_quorum(&p, &registry(3), &Pubkey::default(), 0, &accts).is_err());
+        assert!(
+            verify_validator_quorum(&p, &registry(3), EPOCH, &Pubkey::default(), 0, &accts)
+                .is_err()
+        );
     }
 
     #[test]
@@ -228,7 +452,10 @@ mod tests {
         let s0 = AccountInfo::new(&w0, false, false, &mut l0, &mut e0, &sys, false, 0);
         let a0 = AccountInfo::new(&pda0, false, false, &mut lp0, &mut d0, &p, false, 0);
         let accts = [s0, a0];
-        assert!(verify_validator_quorum(&p, &registry(1), &Pubkey::default(), 0, &accts).is_err());
+        assert!(
+            verify_validator_quorum(&p, &registry(1), EPOCH, &Pubkey::default(), 0, &accts)
+                .is_err()
+        );
     }
 
     #[test]
@@ -243,7 +470,10 @@ mod tests {
         let s0 = AccountInfo::new(&w0, true, false, &mut l0, &mut e0, &sys, false, 0);
         let a0 = AccountInfo::new(&pda0, false, false, &mut lp0, &mut d0, &p, false, 0);
         let accts = [s0, a0];
-        assert!(verify_validator_quorum(&p, &registry(1), &Pubkey::default(), 0, &accts).is_err());
+        assert!(
+            verify_validator_quorum(&p, &registry(1), EPOCH, &Pubkey::default(), 0, &accts)
+                .is_err()
+        );
     }
 
     #[test]
@@ -260,7 +490,10 @@ mod tests {
         let s0 = AccountInfo::new(&w0, true, false, &mut l0, &mut e0, &sys, false, 0);
         let a0 = AccountInfo::new(&bad_pda, false, false, &mut lp0, &mut d0, &p, false, 0);
         let accts = [s0, a0];
-        assert!(verify_validator_quorum(&p, &registry(1), &Pubkey::default(), 0, &accts).is_err());
+        assert!(
+            verify_validator_quorum(&p, &registry(1), EPOCH, &Pubkey::default(), 0, &accts)
+                .is_err()
+        );
     }
 
     #[test]
@@ -279,7 +512,10 @@ mod tests {
         let s0b = AccountInfo::new(&w0, true, false, &mut l0b, &mut e0b, &sys, false, 0);
         let a0b = AccountInfo::new(&pda0, false, false, &mut lp0b, &mut d0b, &p, false, 0);
         let accts = [s0, a0, s0b, a0b];
-        assert!(verify_validator_quorum(&p, &registry(2), &Pubkey::default(), 0, &accts).is_err());
+        assert!(
+            verify_validator_quorum(&p, &registry(2), EPOCH, &Pubkey::default(), 0, &accts)
+                .is_err()
+        );
     }
 
     #[test]
@@ -300,6 +536,7 @@ mod tests {
         assert!(verify_validator_quorum(
             &p,
             &registry_with_stake(3, 9_000_000_000),
+            EPOCH,
             &Pubkey::default(),
             0,
             &[s_big, a_big]
@@ -328,6 +565,7 @@ mod tests {
         assert!(verify_validator_quorum(
             &p,
             &registry_with_stake(3, 9_000_000_000),
+            EPOCH,
             &Pubkey::default(),
             0,
             &[s_small, a_small]
@@ -351,7 +589,8 @@ mod tests {
         let aa = AccountInfo::new(&pda_auth, false, false, &mut lpa, &mut d_auth, &p, false, 0);
         // The authority signing by itself contributes 0 → below threshold.
         assert!(
-            verify_validator_quorum(&p, &registry(2), &auth, 1_000_000_000, &[sa, aa]).is_err()
+            verify_validator_quorum(&p, &registry(2), EPOCH, &auth, 1_000_000_000, &[sa, aa])
+                .is_err()
         );
     }
 
@@ -370,7 +609,10 @@ mod tests {
         let mut ei = [0u8; 0];
         let si = AccountInfo::new(&ind, true, false, &mut li, &mut ei, &sys, false, 0);
         let ai = AccountInfo::new(&pda_ind, false, false, &mut lpi, &mut d_ind, &p, false, 0);
-        assert!(verify_validator_quorum(&p, &registry(2), &auth, 1_000_000_000, &[si, ai]).is_ok());
+        assert!(
+            verify_validator_quorum(&p, &registry(2), EPOCH, &auth, 1_000_000_000, &[si, ai])
+                .is_ok()
+        );
     }
 
     #[test]
@@ -392,10 +634,87 @@ mod tests {
         assert!(verify_validator_quorum(
             &p,
             &registry_with_stake(2, 2_000_000_000),
+            EPOCH,
             &Pubkey::default(),
             0,
             &[s, a]
         )
         .is_err());
     }
+
+    #[test]
+    fn stake_is_counted_as_it_stood_at_the_epoch_boundary() {
+        let p = prog();
+        let sys = anchor_lang::solana_program::system_program::ID;
+        // 3 SOL stood active when EPOCH began; since then one validator left
+        // and a newcomer staked 5 SOL, so the live total is 7 SOL.
+        let mut registry = registry_with_stake(3, 7_000_000_000);
+        registry.epoch_total_active_stake = 3_000_000_000;
+        registry.total_stake_epoch = EPOCH;
+
+        // The leaver held 2 SOL at the boundary and still counts with it.
+        let leaver = Pubkey::new_unique();
+        let (pda_leaver, _) = Pubkey::find_program_address(&[b"validator", leaver.as_ref()], &p);
+        let mut d_leaver = validator_data_at(leaver, false, 0, EPOCH, 2_000_000_000);
+        let (mut ll, mut lpl) = (0u64, 0u64);
+        let mut el = [0u8; 0];
+        let sl = AccountInfo::new(&leaver, true, false, &mut ll, &mut el, &sys, false, 0);
+        let al = AccountInfo::new(
+            &pda_leaver,
+            false,
+            false,
+            &mut lpl,
+            &mut d_leaver,
+            &p,
+            false,
+            0,
+        );
+        // The newcomer joined this epoch and counts for nothing until the
+        // next boundary, so its 5 SOL can neither clear nor break the quorum.
+        let newcomer = Pubkey::new_unique();
+        let (pda_new, _) = Pubkey::find_program_address(&[b"validator", newcomer.as_ref()], &p);
+        let mut d_new = validator_data_at(newcomer, true, 5_000_000_000, EPOCH, 0);
+        let (mut ln, mut lpn) = (0u64, 0u64);
+        let mut en = [0u8; 0];
+        let sn = AccountInfo::new(&newcomer, true, false, &mut ln, &mut en, &sys, false, 0);
+        let an = AccountInfo::new(&pda_new, false, false, &mut lpn, &mut d_new, &p, false, 0);
+
+        // 2 of the boundary's 3 SOL is not more than two thirds; the
+        // newcomer's live stake does not make it up.
+        let accts = [sl, al, sn, an];
+        assert!(
+            verify_validator_quorum(&p, &registry, EPOCH, &Pubkey::default(), 0, &accts).is_err()
+        );
+        // With the leader's 1 boundary SOL set aside, 2 SOL clears it, and
+        // the leaver is paid by the stake it was counted with.
+        let members = verify_validator_quorum(
+            &p,
+            &registry,
+            EPOCH,
+            &Pubkey::default(),
+            1_000_000_000,
+            &accts,
+        )
+        .unwrap();
+        assert_eq!(
+            members,
+            vec![QuorumMember {
+                pair: 0,
+                stake: 2_000_000_000
+            }]
+        );
+
+        // In the next epoch the live stakes are the boundary: the newcomer
+        // counts and the leaver does not.
+        let members =
+            verify_validator_quorum(&p, &registry, EPOCH + 1, &Pubkey::default(), 0, &accts)
+                .unwrap();
+        assert_eq!(
+            members,
+            vec![QuorumMember {
+                pair: 1,
+                stake: 5_000_000_000
+            }]
+        );
+    }
 }
@@ -9,7 +9,7 @@
 use anchor_lang::prelude::*;
 use anchor_lang::{InstructionData, ToAccountMetas};
 use paraloom_program::transact_fixture_data as fx;
-use paraloom_program::{accounts, instruction, ValidatorAccount};
+use paraloom_program::{accounts, instruction, ValidatorAccount, STAKE_EPOCH_SLOTS};
 use solana_program_test::{processor, tokio, ProgramTest};
 use solana_sdk::{
     account::Account,
@@ -84,7 +84,10 @@ async fn claim_rewards_drains_pending_and_accumulates_earnings() {
         cosigner.pubkey(),
         common::TEST_TOKEN_FUND,
     );
-    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let mut ctx = pt.start_with_context().await;
+    let mut banks_client = ctx.banks_client.clone();
+    let payer = ctx.payer.insecure_clone();
+    let recent_blockhash = ctx.last_blockhash;
 
     let (state_pda, _) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
     let (vault_pda, _) = Pubkey::find_program_address(&[b"bridge_vault"], &program_id);
@@ -109,7 +112,7 @@ async fn claim_rewards_drains_pending_and_accumulates_earnings() {
         Instruction {
             program_id,
             data: instruction::Initialize {
-                program_version: 0x0004_0000,
+                program_version: paraloom_program::PROGRAM_VERSION,
                 initial_merkle_root: [0u8; 32],
             }
             .data(),
@@ -300,6 +303,11 @@ async fn claim_rewards_drains_pending_and_accumulates_earnings() {
     )
     .await;
 
+    // Stake counts from the stake epoch after registration; settle there.
+    ctx.warp_to_slot(STAKE_EPOCH_SLOTS)
+        .expect("warp to the next stake epoch");
+    let recent_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
     // transact — credits EXPECTED_FEE to the settling validator's pending_rewards.
     // The authority is excluded from its own quorum, so the independent cosigner
     // supplies the satisfying (wallet, PDA) pair; the tx is signed by both.
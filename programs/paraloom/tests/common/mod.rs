@@ -290,13 +290,24 @@ pub fn register_validator_ix(
     }
 }
 
-/// Build the dual-stake `slash_validator` instruction (registry `authority` signs).
+/// The `slash_record` PDA that marks `evidence_id` as slashed for `validator`.
+pub fn slash_record_pda(program_id: Pubkey, validator: Pubkey, evidence_id: [u8; 32]) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"slash_record", validator.as_ref(), evidence_id.as_ref()],
+        &program_id,
+    )
+    .0
+}
+
+/// Build the dual-stake `slash_validator` instruction (registry `authority` signs
+/// and pays for the evidence's `slash_record`).
 pub fn slash_validator_ix(
     program_id: Pubkey,
     validator: Pubkey,
     stake_mint: Pubkey,
     authority: Pubkey,
     slash_percentage: u8,
+    evidence_id: [u8; 32],
 ) -> Instruction {
     let (registry, _) = Pubkey::find_program_address(&[b"validator_registry"], &program_id);
     let (vault, vault_authority) = stake_vault_pdas(program_id);
@@ -309,6 +320,7 @@ pub fn slash_validator_ix(
         data: paraloom_program::instruction::SlashValidator {
             validator,
             slash_percentage,
+            evidence_id,
         }
         .data(),
         accounts: paraloom_program::accounts::SlashValidator {
@@ -319,7 +331,9 @@ pub fn slash_validator_ix(
             stake_token_vault: vault,
             stake_vault_authority: vault_authority,
             token_program: spl_token::id(),
+            slash_record: slash_record_pda(program_id, validator, evidence_id),
             authority,
+            system_program: solana_sdk::system_program::ID,
         }
         .to_account_metas(None),
     }
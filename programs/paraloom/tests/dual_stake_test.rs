@@ -17,7 +17,9 @@ use solana_sdk::{
 };
 
 mod common;
-use common::{add_program_data, add_stake_mint, add_token_account, entry, stake_vault_pdas};
+use common::{
+    add_program_data, add_stake_mint, add_token_account, entry, slash_record_pda, stake_vault_pdas,
+};
 
 const SOL_STAKE: u64 = 1_000_000_000; // == MIN_VALIDATOR_STAKE
 /// Both registries now start at the recommended floor, so a registration
@@ -124,6 +126,7 @@ async fn register_locks_both_stakes_and_slash_burns_the_token() {
         data: instruction::SlashValidator {
             validator: validator.pubkey(),
             slash_percentage: 50,
+            evidence_id: [1; 32],
         }
         .data(),
         accounts: accounts::SlashValidator {
@@ -134,7 +137,9 @@ async fn register_locks_both_stakes_and_slash_burns_the_token() {
             stake_token_vault: vault,
             stake_vault_authority: vault_authority,
             token_program: spl_token::id(),
+            slash_record: slash_record_pda(program_id, validator.pubkey(), [1; 32]),
             authority: upgrade_authority.pubkey(),
+            system_program: solana_sdk::system_program::ID,
         }
         .to_account_metas(None),
     };
@@ -30,7 +30,7 @@ async fn initialize_persists_bridge_state_fields() {
     let (bridge_state_pda, _bump) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
 
     let initial_merkle_root = [7u8; 32];
-    let program_version = 0x0004_0000_u32;
+    let program_version = paraloom_program::PROGRAM_VERSION;
 
     let ix = Instruction {
         program_id,
@@ -73,7 +73,7 @@ async fn initialize_with_non_upgrade_authority_signer_fails() {
     let ix = Instruction {
         program_id,
         data: instruction::Initialize {
-            program_version: 0x0004_0000,
+            program_version: paraloom_program::PROGRAM_VERSION,
             initial_merkle_root: [0u8; 32],
         }
         .data(),
@@ -0,0 +1,86 @@
+//! On-chain test for `migrate_bridge_state`.
+//!
+//! The upgrade that appended `deposit_cap` and `leader_bonus_bps` to
+//! `BridgeState` is also the one that changed the program's interface, so the
+//! migration that grows the account must move `program_version` to
+//! `PROGRAM_VERSION` as well — otherwise the L2 built for the new layout
+//! refuses to start against the upgraded deployment, and one built for the
+//! old layout keeps sending instructions the program no longer accepts.
+//!
+//! This seeds a raw legacy-sized `BridgeState` recording v0.4.0, migrates it,
+//! and asserts the account is full size, typed-deserializes, keeps its other
+//! fields, and now records the current version.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, InstructionData, Space, ToAccountMetas};
+use paraloom_program::{accounts, instruction, BridgeState, PROGRAM_VERSION};
+use solana_program_test::{processor, tokio, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod common;
+use common::{add_program_data, entry};
+
+#[tokio::test]
+async fn migrate_grows_legacy_bridge_state_and_stamps_the_version() {
+    let program_id = paraloom_program::ID;
+    let mut pt = ProgramTest::new("paraloom_program", program_id, processor!(entry));
+    let (program_data_pda, upgrade_authority) = add_program_data(&mut pt, program_id);
+
+    // The legacy layout predates `deposit_cap` (u64) and `leader_bonus_bps`
+    // (u16), the last two fields.
+    let new_len = 8 + BridgeState::INIT_SPACE;
+    let legacy_len = new_len - 10;
+
+    // Seed a raw legacy account: correct discriminator, v0.4.0 in the
+    // `program_version` slot, a recognisable authority, the rest zero.
+    let authority = Keypair::new().pubkey();
+    let mut data = BridgeState::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&0x0004_0000u32.to_le_bytes());
+    data.extend_from_slice(authority.as_ref());
+    data.resize(legacy_len, 0);
+    let (bridge_state_pda, _) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
+    pt.add_account(
+        bridge_state_pda,
+        Account {
+            lamports: 10_000_000_000,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (mut banks_client, _payer, recent_blockhash) = pt.start().await;
+
+    let migrate_ix = Instruction {
+        program_id,
+        data: instruction::MigrateBridgeState {}.data(),
+        accounts: accounts::MigrateBridgeState {
+            bridge_state: bridge_state_pda,
+            authority: upgrade_authority.pubkey(),
+            program_data: program_data_pda,
+            system_program: solana_sdk::system_program::ID,
+        }
+        .to_account_metas(None),
+    };
+    let mut tx = Transaction::new_with_payer(&[migrate_ix], Some(&upgrade_authority.pubkey()));
+    tx.sign(&[&upgrade_authority], recent_blockhash);
+    banks_client.process_transaction(tx).await.expect("migrate");
+
+    let raw = banks_client
+        .get_account(bridge_state_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(raw.data.len(), new_len, "account grown to current layout");
+    let state = BridgeState::try_deserialize(&mut raw.data.as_slice())
+        .expect("legacy account now deserializes as the current type");
+    assert_eq!(state.program_version, PROGRAM_VERSION);
+    assert_eq!(state.authority, authority, "other fields are untouched");
+    assert_eq!(state.deposit_cap, 0);
+    assert_eq!(state.leader_bonus_bps, 0);
+}
@@ -37,7 +37,7 @@ async fn pause_flips_flag_and_blocks_deposit() {
     let init_ix = Instruction {
         program_id,
         data: instruction::Initialize {
-            program_version: 0x0004_0000,
+            program_version: paraloom_program::PROGRAM_VERSION,
             initial_merkle_root: [0u8; 32],
         }
         .data(),
@@ -77,6 +77,8 @@ async fn withdraw_unbonded_stake_rejects_while_rewards_are_unclaimed() {
         unbonding_slot: 0,
         token_stake_amount: 0,
         token_unbonding_amount: 0,
+        epoch_stake: 0,
+        stake_epoch: 0,
     };
     let mut data = Vec::new();
     seeded
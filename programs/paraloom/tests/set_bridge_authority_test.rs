@@ -39,7 +39,7 @@ async fn set_bridge_authority_rotates_and_rejects_non_authority() {
         &[Instruction {
             program_id,
             data: instruction::Initialize {
-                program_version: 0x0004_0000,
+                program_version: paraloom_program::PROGRAM_VERSION,
                 initial_merkle_root: [0u8; 32],
             }
             .data(),
@@ -11,7 +11,7 @@
 
 use anchor_lang::prelude::*;
 use anchor_lang::{Discriminator, InstructionData, ToAccountMetas};
-use paraloom_program::{accounts, instruction, ValidatorAccount, ValidatorRegistry};
+use paraloom_program::{accounts, instruction, SlashRecord, ValidatorAccount, ValidatorRegistry};
 use solana_program_test::{processor, tokio, ProgramTest};
 use solana_sdk::{
     account::Account,
@@ -24,7 +24,7 @@ use solana_sdk::{
 mod common;
 use common::{
     add_program_data, add_stake_mint, entry, funded_validator, init_validator_registry_ix,
-    register_validator_ix, slash_validator_ix,
+    register_validator_ix, slash_record_pda, slash_validator_ix,
 };
 
 const MIN_VALIDATOR_STAKE: u64 = 1_000_000_000;
@@ -106,6 +106,7 @@ async fn slash_reduces_stake_and_credits_vault() {
             stake_mint,
             upgrade_authority.pubkey(),
             50,
+            [1; 32],
         ),
     )
     .await;
@@ -206,6 +207,7 @@ async fn slash_above_minimum_keeps_validator_active() {
             stake_mint,
             upgrade_authority.pubkey(),
             25,
+            [1; 32],
         ),
     )
     .await;
@@ -234,6 +236,110 @@ async fn slash_above_minimum_keeps_validator_active() {
     );
 }
 
+/// A slash is keyed by its evidence: the first `slash_validator` for an
+/// evidence id creates its `slash_record`, and any retry for the same evidence
+/// (e.g. a node resubmitting after a lost confirmation) fails instead of
+/// charging the validator a second time.
+#[tokio::test]
+async fn slash_for_the_same_evidence_applies_once() {
+    let program_id = paraloom_program::ID;
+    let mut pt = ProgramTest::new("paraloom_program", program_id, processor!(entry));
+    let (program_data_pda, upgrade_authority) = add_program_data(&mut pt, program_id);
+    let stake_mint = add_stake_mint(&mut pt, upgrade_authority.pubkey());
+    let (validator, validator_token) = funded_validator(&mut pt, stake_mint);
+    let (mut banks_client, _payer, recent_blockhash) = pt.start().await;
+
+    let (validator_pda, _) =
+        Pubkey::find_program_address(&[b"validator", validator.pubkey().as_ref()], &program_id);
+    let evidence_id = [7u8; 32];
+
+    send(
+        &mut banks_client,
+        recent_blockhash,
+        &upgrade_authority,
+        init_validator_registry_ix(
+            program_id,
+            upgrade_authority.pubkey(),
+            program_data_pda,
+            stake_mint,
+        ),
+    )
+    .await;
+    send(
+        &mut banks_client,
+        recent_blockhash,
+        &validator,
+        register_validator_ix(
+            program_id,
+            validator.pubkey(),
+            stake_mint,
+            validator_token,
+            2 * MIN_VALIDATOR_STAKE,
+            TOKEN_STAKE,
+        ),
+    )
+    .await;
+    send(
+        &mut banks_client,
+        recent_blockhash,
+        &upgrade_authority,
+        slash_validator_ix(
+            program_id,
+            validator.pubkey(),
+            stake_mint,
+            upgrade_authority.pubkey(),
+            25,
+            evidence_id,
+        ),
+    )
+    .await;
+
+    let record_raw = banks_client
+        .get_account(slash_record_pda(
+            program_id,
+            validator.pubkey(),
+            evidence_id,
+        ))
+        .await
+        .unwrap()
+        .expect("the first slash must create the evidence's slash_record");
+    let record = SlashRecord::try_deserialize(&mut record_raw.data.as_slice()).unwrap();
+    assert_eq!(record.validator, validator.pubkey());
+    assert_eq!(record.evidence_id, evidence_id);
+    assert_eq!(record.slash_percentage, 25);
+    assert_eq!(record.slash_amount, 2 * MIN_VALIDATOR_STAKE / 4);
+
+    // A different percentage so the retry is a distinct transaction rather than
+    // a deduplicated replay: it must fail on the existing slash_record.
+    let retry = send_result(
+        &mut banks_client,
+        recent_blockhash,
+        &upgrade_authority,
+        slash_validator_ix(
+            program_id,
+            validator.pubkey(),
+            stake_mint,
+            upgrade_authority.pubkey(),
+            10,
+            evidence_id,
+        ),
+    )
+    .await;
+    assert!(
+        retry.is_err(),
+        "a second slash for the same evidence must fail"
+    );
+
+    let acc_raw = banks_client
+        .get_account(validator_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let acc = ValidatorAccount::try_deserialize(&mut acc_raw.data.as_slice()).unwrap();
+    assert_eq!(acc.stake_amount, 2 * MIN_VALIDATOR_STAKE * 75 / 100);
+    assert_eq!(acc.times_slashed, 1, "the retry must not slash again");
+}
+
 /// Regression (audit fix B4): an INACTIVE (unbonding) validator must be
 /// slashable from its `unbonding_amount`. Before the fix the slash was gated on
 /// `is_active` and did nothing to unbonding stake, so stake in the unbonding
@@ -325,6 +431,7 @@ async fn slash_inactive_validator_burns_unbonding() {
             stake_mint,
             upgrade_authority.pubkey(),
             100,
+            [1; 32],
         ),
     )
     .await;
@@ -355,9 +462,9 @@ async fn slash_inactive_validator_burns_unbonding() {
         "the slashed unbonding stake must land in the bridge vault"
     );
 
-    // A second slash on the drained account is a clean no-op: old_stake reads the
-    // (now zero) unbonding balance, so slash_amount saturates to 0 and no
-    // lamports move — no underflow.
+    // A second slash (for fresh evidence) on the drained account is a clean
+    // no-op: old_stake reads the (now zero) unbonding balance, so slash_amount
+    // saturates to 0 and no lamports move — no underflow.
     let vault_before = vault.lamports;
     let second = send_result(
         &mut banks_client,
@@ -369,6 +476,7 @@ async fn slash_inactive_validator_burns_unbonding() {
             stake_mint,
             upgrade_authority.pubkey(),
             50,
+            [2; 32],
         ),
     )
     .await;
@@ -475,6 +583,7 @@ async fn slash_rent_only_ghost_does_not_underflow() {
             stake_mint,
             upgrade_authority.pubkey(),
             50,
+            [1; 32],
         ),
     )
     .await;
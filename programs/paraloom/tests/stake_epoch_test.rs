@@ -0,0 +1,161 @@
+//! On-chain test for the epoch-boundary stake record.
+//!
+//! The first change to a validator's active stake in a stake epoch records
+//! the stake that stood when the epoch began (`epoch_stake` /
+//! `stake_epoch`), and the registry does the same for `total_active_stake`.
+//! The L2 reads these to count an epoch's requests against the set at its
+//! boundary, however late in the epoch it polls. This registers in one
+//! epoch, warps two epochs ahead, unregisters, then registers a second
+//! validator in the same epoch: the unregister writes the boundary values
+//! and the later registration leaves them alone.
+//!
+//! Uses `start_with_context()` so the slot can be warped across epochs.
+
+use anchor_lang::prelude::*;
+use anchor_lang::{InstructionData, ToAccountMetas};
+use paraloom_program::{
+    accounts, instruction, ValidatorAccount, ValidatorRegistry, MIN_VALIDATOR_STAKE,
+    STAKE_EPOCH_SLOTS,
+};
+use solana_program_test::{processor, tokio, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+mod common;
+use common::{
+    add_program_data, add_stake_mint, entry, funded_validator, init_validator_registry_ix,
+    register_validator_ix,
+};
+
+const TOKEN_STAKE: u64 = paraloom_program::RECOMMENDED_MIN_TOKEN_STAKE;
+
+async fn send(
+    ctx: &mut ProgramTestContext,
+    signer: &Keypair,
+    ix: Instruction,
+) -> std::result::Result<(), BanksClientError> {
+    let blockhash = ctx.get_new_latest_blockhash().await.expect("new blockhash");
+    let mut tx = Transaction::new_with_payer(&[ix], Some(&signer.pubkey()));
+    tx.sign(&[signer], blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+async fn load<T: AccountDeserialize>(ctx: &mut ProgramTestContext, pda: Pubkey) -> T {
+    let raw = ctx
+        .banks_client
+        .get_account(pda)
+        .await
+        .expect("rpc")
+        .expect("account exists");
+    T::try_deserialize(&mut raw.data.as_slice()).expect("deserialize")
+}
+
+#[tokio::test]
+async fn first_change_of_an_epoch_records_the_boundary_stake() {
+    let program_id = paraloom_program::ID;
+    let mut pt = ProgramTest::new("paraloom_program", program_id, processor!(entry));
+    let (program_data_pda, upgrade_authority) = add_program_data(&mut pt, program_id);
+    let stake_mint = add_stake_mint(&mut pt, upgrade_authority.pubkey());
+    let (validator, validator_token) = funded_validator(&mut pt, stake_mint);
+    let (late, late_token) = funded_validator(&mut pt, stake_mint);
+    let mut ctx = pt.start_with_context().await;
+
+    let (registry_pda, _) = Pubkey::find_program_address(&[b"validator_registry"], &program_id);
+    let (validator_pda, _) =
+        Pubkey::find_program_address(&[b"validator", validator.pubkey().as_ref()], &program_id);
+
+    // Epoch 1: registry init and the first registration.
+    ctx.warp_to_slot(STAKE_EPOCH_SLOTS)
+        .expect("warp to epoch 1");
+    send(
+        &mut ctx,
+        &upgrade_authority,
+        init_validator_registry_ix(
+            program_id,
+            upgrade_authority.pubkey(),
+            program_data_pda,
+            stake_mint,
+        ),
+    )
+    .await
+    .expect("init registry");
+    send(
+        &mut ctx,
+        &validator,
+        register_validator_ix(
+            program_id,
+            validator.pubkey(),
+            stake_mint,
+            validator_token,
+            MIN_VALIDATOR_STAKE,
+            TOKEN_STAKE,
+        ),
+    )
+    .await
+    .expect("register");
+
+    // A validator joining in epoch 1 held nothing when it began.
+    let acc: ValidatorAccount = load(&mut ctx, validator_pda).await;
+    assert_eq!((acc.epoch_stake, acc.stake_epoch), (0, 1));
+    let registry: ValidatorRegistry = load(&mut ctx, registry_pda).await;
+    assert_eq!(
+        (
+            registry.epoch_total_active_stake,
+            registry.total_stake_epoch
+        ),
+        (0, 1)
+    );
+
+    // Epoch 3: leave. The stake that stood at the boundary is recorded.
+    ctx.warp_to_slot(3 * STAKE_EPOCH_SLOTS + 10)
+        .expect("warp to epoch 3");
+    send(
+        &mut ctx,
+        &validator,
+        Instruction {
+            program_id,
+            data: instruction::UnregisterValidator {}.data(),
+            accounts: accounts::UnregisterValidator {
+                validator_account: validator_pda,
+                validator_registry: registry_pda,
+                validator: validator.pubkey(),
+            }
+            .to_account_metas(None),
+        },
+    )
+    .await
+    .expect("unregister");
+
+    let acc: ValidatorAccount = load(&mut ctx, validator_pda).await;
+    assert_eq!(acc.active_stake(), 0);
+    assert_eq!((acc.epoch_stake, acc.stake_epoch), (MIN_VALIDATOR_STAKE, 3));
+
+    // A second change in epoch 3 keeps the boundary total the first wrote.
+    send(
+        &mut ctx,
+        &late,
+        register_validator_ix(
+            program_id,
+            late.pubkey(),
+            stake_mint,
+            late_token,
+            MIN_VALIDATOR_STAKE,
+            TOKEN_STAKE,
+        ),
+    )
+    .await
+    .expect("register late");
+
+    let registry: ValidatorRegistry = load(&mut ctx, registry_pda).await;
+    assert_eq!(registry.total_active_stake, MIN_VALIDATOR_STAKE);
+    assert_eq!(
+        (
+            registry.epoch_total_active_stake,
+            registry.total_stake_epoch
+        ),
+        (MIN_VALIDATOR_STAKE, 3)
+    );
+}
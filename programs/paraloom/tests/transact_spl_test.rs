@@ -3,6 +3,7 @@
 //! register two validators (settling authority + independent cosigner) ->
 //! init the asset vault + open its cap -> `deposit_note_spl` recreates the
 //! fixture's spent note (tree reaches `SPL_FIXTURE_ROOT` and funds the vault)
+//! -> warp to the next stake epoch, where the validators' stake counts
 //! -> `transact_spl` spends it, paying a token withdraw out of the asset vault
 //! to the recipient token account and the fee to the validator's token account.
 //!
@@ -16,7 +17,7 @@ use anchor_lang::solana_program::program_pack::Pack;
 use anchor_lang::{InstructionData, ToAccountMetas};
 use paraloom_program::merkle_tree::{IncrementalMerkleTree, TREE_DEPTH, ZERO_HASHES};
 use paraloom_program::transact_spl_fixture_data as fx;
-use paraloom_program::{accounts, instruction, BridgeState, NullifierAccount};
+use paraloom_program::{accounts, instruction, BridgeState, NullifierAccount, STAKE_EPOCH_SLOTS};
 use solana_program_test::{tokio, ProgramTest};
 use solana_sdk::{
     account::Account,
@@ -150,10 +151,14 @@ async fn transact_spl_settles_a_token_withdrawal() {
     // Payout destination at the fixture's bound token-account address.
     let recipient_token = Pubkey::new_from_array(fx::SPL_FIXTURE_RECIPIENT_TOKEN_ACCOUNT);
     bake_token_account_at(&mut pt, recipient_token, asset_mint, Pubkey::new_unique(), 0);
-    // The settling validator's fee account for the withdrawn asset.
+    // The settling validator's fee account for the withdrawn asset, and the
+    // co-signer's.
     let fee_token = add_token_account(&mut pt, asset_mint, upgrade_authority.pubkey(), 0);
+    let cosigner_fee_token = add_token_account(&mut pt, asset_mint, cosigner.pubkey(), 0);
 
-    let (mut banks, _payer, blockhash) = pt.start().await;
+    let mut ctx = pt.start_with_context().await;
+    let mut banks = ctx.banks_client.clone();
+    let blockhash = ctx.last_blockhash;
 
     let (state_pda, _) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
     let (tree_pda, _) = Pubkey::find_program_address(&[b"merkle_tree"], &program_id);
@@ -376,6 +381,12 @@ async fn transact_spl_settles_a_token_withdrawal() {
     let vault_before = token_amount(&banks.get_account(asset_vault).await.unwrap().unwrap());
     assert_eq!(vault_before, fx::SPL_FIXTURE_DEPOSIT_AMOUNT, "vault funded by deposit");
 
+    // Both validators joined in this stake epoch, so neither held stake when
+    // it began. Settle in the next one, where both count.
+    ctx.warp_to_slot(STAKE_EPOCH_SLOTS)
+        .expect("warp to the next stake epoch");
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
     // 7. transact_spl: spend the note, pay the token withdraw out of the vault.
     let transact_ix = Instruction {
         program_id,
@@ -413,6 +424,8 @@ async fn transact_spl_settles_a_token_withdrawal() {
             .to_account_metas(None);
             metas.push(AccountMeta::new_readonly(cosigner.pubkey(), true));
             metas.push(AccountMeta::new_readonly(cosigner_pda, false));
+            // One fee account per (wallet, PDA) pair, after all the pairs.
+            metas.push(AccountMeta::new(cosigner_fee_token, false));
             metas
         },
     };
@@ -439,9 +452,16 @@ async fn transact_spl_settles_a_token_withdrawal() {
     // Recipient token account received the payout net of the fee.
     let recipient_after = token_amount(&banks.get_account(recipient_token).await.unwrap().unwrap());
     assert_eq!(recipient_after, payout, "recipient token account gains |ext| - fee");
-    // The fee landed in the settling validator's token account.
+    // The fee is split by stake over two equal validators; the co-signer's half
+    // of a 1-unit fee rounds to 0 and the settling validator keeps the dust.
+    // The fixture proof fixes the fee at 1 unit, so splits that pay several
+    // co-signers, the leader bonus and forfeited shares are pinned by the
+    // `quorum` unit tests instead.
     let fee_after = token_amount(&banks.get_account(fee_token).await.unwrap().unwrap());
     assert_eq!(fee_after, fee, "fee paid to the validator's token account");
+    let cosigner_fee_after =
+        token_amount(&banks.get_account(cosigner_fee_token).await.unwrap().unwrap());
+    assert_eq!(cosigner_fee_after, 0, "a rounded-down share is not paid");
     // The vault paid out exactly gross.
     let vault_after = token_amount(&banks.get_account(asset_vault).await.unwrap().unwrap());
     assert_eq!(vault_before - vault_after, gross, "vault debited by gross");
@@ -12,16 +12,20 @@
 //! if the two Poseidon implementations disagreed, no v3 proof would verify.
 //!
 //! Settlement is quorum-gated exactly like `withdraw` (#260). Because the
-//! settling authority is excluded from its own quorum tally, an INDEPENDENT
-//! registered validator (`cosigner`) must co-sign the `transact` as a
-//! (wallet, PDA) pair in `remaining_accounts`; the transaction is signed by
-//! both the authority and that cosigner.
+//! settler is excluded from its own quorum tally, an INDEPENDENT registered
+//! validator must co-sign the `transact` as a (wallet, PDA) pair in
+//! `remaining_accounts`. The settler need not be the bridge authority: here
+//! the second validator (`cosigner`) settles, as a backup leader would, and the
+//! bridge authority co-signs. Stake counts from the stake epoch after it is
+//! registered, so the test warps past the boundary before it settles.
 
 use anchor_lang::prelude::*;
 use anchor_lang::{InstructionData, ToAccountMetas};
 use paraloom_program::merkle_tree::{IncrementalMerkleTree, TREE_DEPTH, ZERO_HASHES};
 use paraloom_program::transact_fixture_data as fx;
-use paraloom_program::{accounts, instruction, BridgeState, NullifierAccount, ValidatorAccount};
+use paraloom_program::{
+    accounts, instruction, BridgeState, NullifierAccount, ValidatorAccount, STAKE_EPOCH_SLOTS,
+};
 use solana_program_test::{processor, tokio, ProgramTest};
 use solana_sdk::{
     account::Account,
@@ -82,10 +86,10 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
         },
     );
 
-    // An independent validator that co-signs the quorum. The settling authority
-    // is excluded from its own tally, so a second, unrelated validator is what
-    // actually satisfies the quorum. Created before `start` so its token-stake
-    // account can be baked into genesis alongside the authority's.
+    // A second, independent validator — the one that settles. The settler is
+    // excluded from its own tally, so the bridge authority's co-signature is
+    // what actually satisfies the quorum. Created before `start` so its
+    // token-stake account can be baked into genesis alongside the authority's.
     let cosigner = Keypair::new();
     let stake_mint = add_stake_mint(&mut pt, Pubkey::new_unique());
     let authority_token = add_token_account(
@@ -100,7 +104,10 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
         cosigner.pubkey(),
         common::TEST_TOKEN_FUND,
     );
-    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let mut ctx = pt.start_with_context().await;
+    let mut banks_client = ctx.banks_client.clone();
+    let payer = ctx.payer.insecure_clone();
+    let recent_blockhash = ctx.last_blockhash;
 
     let (state_pda, _) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
     let (vault_pda, _) = Pubkey::find_program_address(&[b"bridge_vault"], &program_id);
@@ -213,7 +220,37 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
     )
     .await;
 
-    // 4. register the settling authority as a validator (stakes 1 SOL).
+    // 3c. give the leader the largest bonus the program allows; one past it is
+    //     refused. On this fixture's 1-lamport fee the bonus rounds to 0, so
+    //     the split below is decided by stake and dust alone.
+    let set_leader_bonus = |bonus_bps: u16| Instruction {
+        program_id,
+        data: instruction::SetLeaderBonus { bonus_bps }.data(),
+        accounts: accounts::SetLeaderBonus {
+            bridge_state: state_pda,
+            validator_registry: registry_pda,
+            authority: upgrade_authority.pubkey(),
+        }
+        .to_account_metas(None),
+    };
+    let mut too_high = Transaction::new_with_payer(
+        &[set_leader_bonus(paraloom_program::MAX_LEADER_BONUS_BPS + 1)],
+        Some(&upgrade_authority.pubkey()),
+    );
+    too_high.sign(&[&upgrade_authority], recent_blockhash);
+    assert!(
+        banks_client.process_transaction(too_high).await.is_err(),
+        "a bonus above MAX_LEADER_BONUS_BPS must be rejected"
+    );
+    send(
+        &mut banks_client,
+        recent_blockhash,
+        &upgrade_authority,
+        set_leader_bonus(paraloom_program::MAX_LEADER_BONUS_BPS),
+    )
+    .await;
+
+    // 4. register the bridge authority as a validator (stakes 1 SOL).
     send(
         &mut banks_client,
         recent_blockhash,
@@ -244,11 +281,12 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
     )
     .await;
 
-    // 4b. register an INDEPENDENT validator that will co-sign the quorum. Fund it
-    //     from the payer (stake + fees), then self-register it (RegisterValidator
-    //     is permissionless — the validator signs for itself). This raises
-    //     total_active_stake to 2 SOL; with the authority's 1 SOL excluded, the
-    //     eligible stake is 1 SOL and the cosigner's 1 SOL clears the threshold.
+    // 4b. register an INDEPENDENT validator that will settle. Fund it from the
+    //     payer (stake + fees + nullifier rent), then self-register it
+    //     (RegisterValidator is permissionless — the validator signs for
+    //     itself). This raises total_active_stake to 2 SOL; with the settler's
+    //     1 SOL excluded, the eligible stake is 1 SOL and the bridge authority's
+    //     1 SOL clears the threshold.
     send(
         &mut banks_client,
         recent_blockhash,
@@ -341,11 +379,18 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
     );
     assert_ne!(tree.root, ZERO_HASHES[TREE_DEPTH]);
 
+    // Both validators joined in this stake epoch, so neither held stake when
+    // it began. Settle in the next one, where both count.
+    ctx.warp_to_slot(STAKE_EPOCH_SLOTS)
+        .expect("warp to the next stake epoch");
+    let recent_blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+
     // 7. transact — spend the note, withdraw 500 (net of the 25 bps fee) to the
     //    recipient, record both nullifiers, append both output commitments.
-    //    The authority is excluded from its own quorum, so the independent
-    //    `cosigner` supplies the (wallet, PDA) pair that satisfies it; the tx is
-    //    signed by both the authority (fee payer) and the cosigner.
+    //    `cosigner` settles although it is not the bridge authority; it is
+    //    excluded from its own quorum, so the bridge authority supplies the
+    //    (wallet, PDA) pair that satisfies it. The tx is signed by both, with
+    //    the settler paying.
     let transact_ix = Instruction {
         program_id,
         data: instruction::Transact {
@@ -364,24 +409,25 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
                 nullifier_account_0: nf0_pda,
                 nullifier_account_1: nf1_pda,
                 recipient,
-                validator_account: validator_pda,
+                validator_account: cosigner_pda,
                 validator_registry: registry_pda,
-                authority: upgrade_authority.pubkey(),
+                authority: cosigner.pubkey(),
                 system_program: solana_sdk::system_program::ID,
             }
             .to_account_metas(None);
             // Quorum co-signer (#260): an INDEPENDENT registered validator,
-            // co-signing as a (wallet, PDA) pair. The authority's own pair is
-            // not needed — it is skipped in the tally.
-            metas.push(AccountMeta::new_readonly(cosigner.pubkey(), true));
-            metas.push(AccountMeta::new_readonly(cosigner_pda, false));
+            // co-signing as a (wallet, PDA) pair. The settler's own pair is
+            // not needed — it is skipped in the tally. The PDA is writable so
+            // the co-signer's fee share can be credited to it.
+            metas.push(AccountMeta::new_readonly(upgrade_authority.pubkey(), true));
+            metas.push(AccountMeta::new(validator_pda, false));
             metas
         },
     };
     let transact_tx = Transaction::new_signed_with_payer(
         &[transact_ix],
-        Some(&upgrade_authority.pubkey()),
-        &[&upgrade_authority, &cosigner],
+        Some(&cosigner.pubkey()),
+        &[&cosigner, &upgrade_authority],
         recent_blockhash,
     );
     banks_client.process_transaction(transact_tx).await.unwrap();
@@ -403,17 +449,28 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
         "recipient gains |ext| - fee"
     );
 
-    // The fee landed in the settling validator's pending_rewards.
+    // The fee is split by stake between the leader and the co-signer (1 SOL
+    // each). Half of 1 lamport rounds down to 0 for the co-signer, and the
+    // leader keeps the dust — the whole fee lands in its pending_rewards.
+    // Larger fees over several co-signers are covered by the `quorum` unit
+    // tests; the fixture proof cannot withdraw more than 500 lamports.
+    let leader_raw = banks_client
+        .get_account(cosigner_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    let leader_val = ValidatorAccount::try_deserialize(&mut leader_raw.data.as_slice()).unwrap();
+    assert_eq!(leader_val.pending_rewards, fee);
+    assert_eq!(leader_val.successful_verifications, 1);
+    // The paired total is now maintained alongside successes (was dead).
+    assert_eq!(leader_val.total_tasks_verified, 1);
     let val_raw = banks_client
         .get_account(validator_pda)
         .await
         .unwrap()
         .unwrap();
     let val = ValidatorAccount::try_deserialize(&mut val_raw.data.as_slice()).unwrap();
-    assert_eq!(val.pending_rewards, fee);
-    assert_eq!(val.successful_verifications, 1);
-    // The paired total is now maintained alongside successes (was dead).
-    assert_eq!(val.total_tasks_verified, 1);
+    assert_eq!(val.pending_rewards, 0, "a rounded-down share is 0");
 
     // Both input nullifiers were recorded (double-spend defense).
     for (pda, expected) in [
@@ -442,4 +499,8 @@ async fn transact_spends_deposited_note_and_withdraws_net_of_fee() {
     assert_eq!(state.withdrawal_count, 1);
     // Withdrawal volume is now tracked (gross = |ext_amount|, was stuck at 0).
     assert_eq!(state.total_withdrawn, gross);
+    assert_eq!(
+        state.leader_bonus_bps,
+        paraloom_program::MAX_LEADER_BONUS_BPS
+    );
 }
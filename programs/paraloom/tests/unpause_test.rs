@@ -61,7 +61,7 @@ async fn unpause_clears_flag_and_unblocks_deposit() {
         Instruction {
             program_id,
             data: instruction::Initialize {
-                program_version: 0x0004_0000,
+                program_version: paraloom_program::PROGRAM_VERSION,
                 initial_merkle_root: [0u8; 32],
             }
             .data(),
@@ -308,6 +308,7 @@ async fn fully_slashed_validator_can_close_rent_only_pda_after_delay() {
             stake_mint,
             upgrade_authority.pubkey(),
             100,
+            [1; 32],
         ),
     )
     .await
@@ -0,0 +1,201 @@
+//! Benchmark: per-proof vs randomized batch Groth16 verification
+//!
+//! Usage: `benchmark-batch-verify [rounds]`. Proves 32 distinct
+//! `TransactCircuitV3` spends under a dev setup, then verifies batches of 1, 8
+//! and 32 of them one by one and as a single randomized batch (the path
+//! `consensus::verify_queue` takes), and reports throughput. A last row
+//! plants one invalid proof in a batch of 32 to show what the fallback to
+//! per-proof checks costs.
+//!
+//! Proving 32 transacts takes a while; run it with `--release`.
+
+use anyhow::Result;
+use ark_bn254::{Bn254, Fr};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
+use ark_snark::SNARK;
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use paraloom::privacy::circuits::{Groth16ProofSystem, TransactCircuitV3, TX_LEVELS};
+use paraloom::privacy::poseidon_circom::{
+    v3_commit, v3_merkle_pair, v3_nullifier, v3_pubkey, v3_signature,
+};
+use paraloom::privacy::BatchVerifier;
+use std::time::{Duration, Instant};
+
+const BATCH_SIZES: [usize; 3] = [1, 8, 32];
+
+fn fr_to_le(f: &Fr) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let le = f.into_bigint().to_bytes_le();
+    out[..le.len().min(32)].copy_from_slice(&le[..le.len().min(32)]);
+    out
+}
+
+/// Empty-subtree hashes under the v3 Merkle hash.
+fn zeros() -> Vec<Fr> {
+    let mut z = vec![Fr::from(0u64)];
+    for k in 0..TX_LEVELS {
+        z.push(v3_merkle_pair(z[k], z[k]));
+    }
+    z
+}
+
+/// The `i`-th spend: a 1000-unit note alone at leaf 0 of its tree plus a zero
+/// dummy, split into two outputs with nothing moving in or out of the pool.
+/// Each spend has its own keys, so its own root, nullifiers and commitments.
+/// Returns the witness and its public inputs in Groth16 slice order.
+fn transact(i: u64, zeros: &[Fr]) -> (TransactCircuitV3, Vec<Fr>) {
+    const IN_AMOUNT: u64 = 1000;
+    const OUT0: u64 = 600;
+    const OUT1: u64 = 400;
+    let asset = Fr::from(0u64);
+    let ext_data_hash = [0u8; 32];
+
+    let sk0 = Fr::from(1_000 + 4 * i);
+    let bl0 = Fr::from(1_001 + 4 * i);
+    let c0 = v3_commit(Fr::from(IN_AMOUNT), v3_pubkey(sk0), bl0, asset);
+    let nf0 = v3_nullifier(c0, Fr::from(0u64), v3_signature(sk0, c0, Fr::from(0u64)));
+    let root = zeros
+        .iter()
+        .take(TX_LEVELS)
+        .fold(c0, |node, zero| v3_merkle_pair(node, *zero));
+    let path: Vec<[u8; 32]> = zeros[..TX_LEVELS].iter().map(fr_to_le).collect();
+
+    let sk1 = Fr::from(1_002 + 4 * i);
+    let bl1 = Fr::from(1_003 + 4 * i);
+    let c1 = v3_commit(Fr::from(0u64), v3_pubkey(sk1), bl1, asset);
+    let nf1 = v3_nullifier(c1, Fr::from(0u64), v3_signature(sk1, c1, Fr::from(0u64)));
+
+    let opk0 = v3_pubkey(sk0 + Fr::from(1u64 << 32));
+    let opk1 = v3_pubkey(sk1 + Fr::from(1u64 << 32));
+    let (obl0, obl1) = (Fr::from(1u64), Fr::from(2u64));
+    let oc0 = v3_commit(Fr::from(OUT0), opk0, obl0, asset);
+    let oc1 = v3_commit(Fr::from(OUT1), opk1, obl1, asset);
+    let public_amount = Fr::from(0u64);
+
+    let circuit = TransactCircuitV3 {
+        root: Some(fr_to_le(&root)),
+        public_amount: Some(fr_to_le(&public_amount)),
+        ext_data_hash: Some(ext_data_hash),
+        asset_id: Some(fr_to_le(&asset)),
+        input_nullifiers: vec![Some(fr_to_le(&nf0)), Some(fr_to_le(&nf1))],
+        output_commitments: vec![Some(fr_to_le(&oc0)), Some(fr_to_le(&oc1))],
+        in_amounts: vec![Some(IN_AMOUNT), Some(0)],
+        in_privkeys: vec![Some(fr_to_le(&sk0)), Some(fr_to_le(&sk1))],
+        in_blindings: vec![Some(fr_to_le(&bl0)), Some(fr_to_le(&bl1))],
+        in_leaf_indices: vec![Some(0), Some(0)],
+        in_paths: vec![Some(path.clone()), Some(path)],
+        out_amounts: vec![Some(OUT0), Some(OUT1)],
+        out_pubkeys: vec![Some(fr_to_le(&opk0)), Some(fr_to_le(&opk1))],
+        out_blindings: vec![Some(fr_to_le(&obl0)), Some(fr_to_le(&obl1))],
+    };
+    let inputs = vec![
+        root,
+        public_amount,
+        Fr::from_le_bytes_mod_order(&ext_data_hash),
+        asset,
+        nf0,
+        nf1,
+        oc0,
+        oc1,
+    ];
+    (circuit, inputs)
+}
+
+fn per_proof(total: Duration, proofs: usize) -> String {
+    format!(
+        "{:>8.2} ms/proof {:>8.0} proofs/s",
+        total.as_secs_f64() * 1e3 / proofs as f64,
+        proofs as f64 / total.as_secs_f64()
+    )
+}
+
+fn time_individual(
+    vk: &VerifyingKey<Bn254>,
+    inputs: &[Vec<Fr>],
+    proofs: &[Proof<Bn254>],
+    rounds: u32,
+) -> Duration {
+    let pvk = Groth16::<Bn254>::process_vk(vk).expect("prepare verifying key");
+    let started = Instant::now();
+    for _ in 0..rounds {
+        for (proof, x) in proofs.iter().zip(inputs) {
+            Groth16ProofSystem::verify_with_prepared(&pvk, x, proof).expect("verify");
+        }
+    }
+    started.elapsed() / rounds
+}
+
+fn time_batch(
+    vk: &VerifyingKey<Bn254>,
+    inputs: &[Vec<Fr>],
+    proofs: &[Proof<Bn254>],
+    rounds: u32,
+) -> Duration {
+    let verifier = BatchVerifier::with_prepared_vk(vk);
+    let started = Instant::now();
+    for _ in 0..rounds {
+        verifier.verify_batch(vk, inputs, proofs);
+    }
+    started.elapsed() / rounds
+}
+
+fn main() -> Result<()> {
+    let rounds: u32 = match std::env::args().nth(1) {
+        Some(n) => n.parse()?,
+        None => 10,
+    };
+
+    println!("\nBENCHMARK: Batch Proof Verification\n");
+    let mut rng = StdRng::seed_from_u64(0);
+    let (pk, vk) = Groth16ProofSystem::setup(TransactCircuitV3::blank(), &mut rng)
+        .map_err(|e| anyhow::anyhow!("setup failed: {}", e))?;
+    let pvk = Groth16::<Bn254>::process_vk(&vk)?;
+
+    let max = *BATCH_SIZES.iter().max().unwrap_or(&1);
+    let zeros = zeros();
+    let mut inputs = Vec::with_capacity(max);
+    let mut proofs = Vec::with_capacity(max);
+    let started = Instant::now();
+    for i in 0..max {
+        let (circuit, x) = transact(i as u64, &zeros);
+        let proof = Groth16ProofSystem::prove(&pk, circuit, &mut rng)
+            .map_err(|e| anyhow::anyhow!("proving failed: {}", e))?;
+        anyhow::ensure!(
+            Groth16ProofSystem::verify_with_prepared(&pvk, &x, &proof)?,
+            "transact proof {} does not verify",
+            i
+        );
+        proofs.push(proof);
+        inputs.push(x);
+    }
+    println!(
+        "{} TransactCircuitV3 proofs ({} public inputs each) in {:.1}s, {} rounds\n",
+        max,
+        vk.gamma_abc_g1.len() - 1,
+        started.elapsed().as_secs_f64(),
+        rounds
+    );
+
+    for &n in &BATCH_SIZES {
+        let individual = time_individual(&vk, &inputs[..n], &proofs[..n], rounds);
+        let batch = time_batch(&vk, &inputs[..n], &proofs[..n], rounds);
+        println!("batch of {:>2}", n);
+        println!("  individual  {}", per_proof(individual, n));
+        println!("  batched     {}", per_proof(batch, n));
+        println!(
+            "  speedup     {:>8.2}x",
+            individual.as_secs_f64() / batch.as_secs_f64()
+        );
+    }
+
+    let mut tampered = inputs.clone();
+    tampered[max / 2][0] += Fr::from(1u64);
+    let fallback = time_batch(&vk, &tampered, &proofs, rounds);
+    println!("batch of {:>2} with one invalid proof", max);
+    println!("  batched     {}", per_proof(fallback, max));
+
+    println!("\nBenchmark completed");
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! Benchmark: commitment tree appends and authentication paths at scale
+//!
+//! Usage: `benchmark-merkle [leaves] [db-path]`. Builds a tree of `leaves`
+//! commitments (default one million), in memory or in a RocksDB privacy
+//! store at `db-path`, then times the operations the node runs per
+//! settlement and per wallet query.
+
+use anyhow::Result;
+use paraloom::privacy::merkle::MerkleTree;
+use paraloom::privacy::types::Commitment;
+use paraloom::storage::PrivacyStorage;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const BUILD_CHUNK: usize = 10_000;
+const SAMPLES: u32 = 200;
+
+fn leaf(i: u64) -> Commitment {
+    let mut bytes = [0x5a; 32];
+    bytes[..8].copy_from_slice(&i.to_le_bytes());
+    Commitment(bytes)
+}
+
+fn per_op(total: Duration, ops: u32) -> String {
+    format!("{:>10.1} µs/op", total.as_secs_f64() * 1e6 / ops as f64)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let mut args = std::env::args().skip(1);
+    let leaves: u64 = match args.next() {
+        Some(n) => n.parse()?,
+        None => 1_000_000,
+    };
+    let db_path = args.next();
+
+    println!("\nBENCHMARK: Commitment Tree\n");
+    let tree = match &db_path {
+        Some(path) => {
+            println!("Backend: RocksDB at {}", path);
+            MerkleTree::with_storage(Arc::new(PrivacyStorage::open(path)?)).await?
+        }
+        None => {
+            println!("Backend: in-memory");
+            MerkleTree::new()
+        }
+    };
+
+    let existing = tree.len().await as u64;
+    let started = Instant::now();
+    let mut next = existing;
+    while next < leaves {
+        let end = (next + BUILD_CHUNK as u64).min(leaves);
+        let chunk: Vec<Commitment> = (next..end).map(leaf).collect();
+        tree.insert_batch(&chunk).await?;
+        next = end;
+    }
+    let n = tree.len().await as u64;
+    println!(
+        "Build:        {} leaves in {:.1}s ({} already present)",
+        n,
+        started.elapsed().as_secs_f64(),
+        existing
+    );
+
+    let started = Instant::now();
+    for i in 0..SAMPLES {
+        tree.insert(&leaf(n + i as u64)).await?;
+    }
+    println!("insert        {}", per_op(started.elapsed(), SAMPLES));
+    let n = tree.len().await as u64;
+
+    let pending = [leaf(u64::MAX - 1), leaf(u64::MAX)];
+    let started = Instant::now();
+    for _ in 0..SAMPLES {
+        tree.root_after(&pending).await;
+    }
+    println!("root_after(2) {}", per_op(started.elapsed(), SAMPLES));
+
+    let started = Instant::now();
+    for _ in 0..SAMPLES {
+        let index = rand::random::<u64>() % n;
+        tree.path(index as usize).await;
+    }
+    println!("path          {}", per_op(started.elapsed(), SAMPLES));
+
+    let started = Instant::now();
+    for _ in 0..SAMPLES {
+        let index = rand::random::<u64>() % n;
+        tree.onchain_membership(&leaf(index)).await;
+    }
+    println!("onchain path  {}", per_op(started.elapsed(), SAMPLES));
+
+    println!("\nBenchmark completed");
+    Ok(())
+}
@@ -223,6 +223,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             &[fr_to_le(&nf0), fr_to_le(&nf1)],
             &[fr_to_le(&oc0), fr_to_le(&oc1)],
             &proof_bytes,
+            None,
         );
         println!("      node-path verify: {r:?}");
         // Where do they differ? public_amount is the prime suspect.
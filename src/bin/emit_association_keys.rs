@@ -0,0 +1,44 @@
+//! Dev tool: single-party setup of `AssociationCircuit`, saving its proving
+//! and verifying keys.
+//!
+//! An association proof has two public inputs (`[association_root,
+//! nullifier]`). The node loads the verifying key from
+//! `ASSOCIATION_VERIFYING_KEY_PATH` (default `keys/association_verifying.key`)
+//! to answer `POST /association/verify`, and `paraloom wallet
+//! prove-association` proves with the proving key. These are dev keys: whoever
+//! ran the setup can forge proofs, so a counterparty should only trust a
+//! ceremony key.
+//!
+//! `cargo run --release --bin emit_association_keys`
+
+use ark_serialize::CanonicalSerialize;
+use paraloom::privacy::circuits::{AssociationCircuit, Groth16ProofSystem};
+
+fn main() {
+    let pk_path = std::env::var("ASSOCIATION_PROVING_KEY")
+        .unwrap_or_else(|_| "keys/association_proving.key".to_string());
+    let vk_path = std::env::var("ASSOCIATION_VERIFYING_KEY_PATH")
+        .unwrap_or_else(|_| "keys/association_verifying.key".to_string());
+
+    // Never overwrite: proofs made under an existing key would stop verifying.
+    if std::path::Path::new(&pk_path).exists() {
+        eprintln!("{pk_path} already exists; remove it to regenerate");
+        return;
+    }
+
+    let mut rng = ark_std::rand::thread_rng();
+    let (pk, vk) =
+        Groth16ProofSystem::setup(AssociationCircuit::default(), &mut rng).expect("setup dev keys");
+    let mut pk_bytes = Vec::new();
+    pk.serialize_compressed(&mut pk_bytes)
+        .expect("serialize pk");
+    let mut vk_bytes = Vec::new();
+    vk.serialize_compressed(&mut vk_bytes)
+        .expect("serialize vk");
+    std::fs::write(&pk_path, pk_bytes).expect("write association proving key");
+    std::fs::write(&vk_path, vk_bytes).expect("write association verifying key");
+    eprintln!(
+        "saved association keys to {pk_path} and {vk_path} ({} public inputs)",
+        vk.gamma_abc_g1.len() - 1
+    );
+}
@@ -6,30 +6,45 @@
 //! the bytes is exactly the drift the version tag exists to surface, and prose
 //! cannot catch it.
 //!
-//! Inputs are fixed filler rather than real key material, deliberately. The
-//! codec is pure framing and never inspects the sealed bytes, so key material
-//! would add nothing a reader could check while making the vectors
+//! The v1 inputs are fixed filler rather than real key material, deliberately.
+//! The v1 codec is pure framing and never inspects the sealed bytes, so key
+//! material would add nothing a reader could check while making the vectors
 //! irreproducible. Real crypto is covered by the tweetnacl interop vector in
 //! `note_crypto`, which pins `crypto_box` against the wallet's `tweetnacl.box`.
 //!
-//! Writes `vectors/note_envelope_v1.json`, which is checked in and read back by
-//! `note_crypto`'s own tests, so the artifact cannot drift from the code that
-//! generated it.
+//! v2 is different: what it adds — the padding and the v2 note schema — lives
+//! inside the box, so its vectors are real boxes under fixed secret keys and
+//! nonces. The recipient secret is published with them; a reader opens each
+//! one and compares the note, and re-seals it and compares the bytes.
+//!
+//! Writes `vectors/note_envelope_v1.json` and `vectors/note_envelope_v2.json`,
+//! which are checked in and read back by `note_crypto`'s own tests, so the
+//! artifacts cannot drift from the code that generated them.
 //!
 //! `cargo run --bin emit_note_envelope_vectors`
 
 use paraloom::privacy::note_crypto::{
-    check_relayable, EncryptedNote, ENVELOPE_TAG_RESERVED, ENVELOPE_TAG_V1,
+    check_relayable, decrypt_note, pad_v2, seal_with_ephemeral, EncryptedNote, NotePlaintext,
+    ENVELOPE_TAG_RESERVED, ENVELOPE_TAG_V1, ENVELOPE_TAG_V2, ENVELOPE_TAG_V2_OUTGOING,
+    MAX_MEMO_LEN, NOTE_SCHEMA_V2, V2_PADDED_SIZES,
 };
+use paraloom::privacy::types::NATIVE_SOL_ASSET;
 use std::path::Path;
 
-const OUTPUT_PATH: &str = "vectors/note_envelope_v1.json";
+const OUTPUT_DIR: &str = "vectors";
+
+/// The published recipient secret every v2 vector is sealed to.
+const V2_RECIPIENT_SECRET: [u8; 32] = [0x07; 32];
+
+/// An envelope under the first unallocated tag, with a v1-shaped remainder.
+const UNKNOWN_TAG: u8 = 0x03;
 
 /// A v1 envelope with a `ct` of `ct_len` bytes, built from position-dependent
 /// filler so a reader that mixes up `epk` and `nonce` produces different bytes
 /// rather than the same ones.
 fn v1(ct_len: usize) -> EncryptedNote {
     EncryptedNote {
+        version: ENVELOPE_TAG_V1,
         epk: std::array::from_fn(|i| 0xA0u8.wrapping_add(i as u8)),
         nonce: std::array::from_fn(|i| 0x40u8.wrapping_add(i as u8)),
         ct: (0..ct_len).map(|i| 0x10u8.wrapping_add(i as u8)).collect(),
@@ -78,7 +93,13 @@ fn relay_case(name: &str, bytes: Vec<u8>, relayable: bool) -> serde_json::Value
     })
 }
 
-fn main() {
+fn with_tag(tag: u8, note: &EncryptedNote) -> Vec<u8> {
+    let mut b = vec![tag];
+    b.extend_from_slice(&note.to_bytes()[1..]);
+    b
+}
+
+fn v1_vectors() -> serde_json::Value {
     // The smallest well-formed v1 ct: crypto_box emits a 16-byte Poly1305 tag
     // even for an empty plaintext, so nothing shorter can ever authenticate.
     let minimal = v1(16);
@@ -86,13 +107,9 @@ fn main() {
     let note_shaped = v1(88);
     let long = v1(1024);
 
-    let unknown = {
-        let mut b = vec![0x02];
-        b.extend_from_slice(&minimal.to_bytes()[1..]);
-        b
-    };
+    let unknown = with_tag(UNKNOWN_TAG, &minimal);
 
-    let doc = serde_json::json!({
+    serde_json::json!({
         "description":
             "Canonical EncryptedNote envelope encoding, v1. Layout is \
              tag(1) || epk(32) || nonce(24) || ct, where ct carries its \
@@ -113,11 +130,7 @@ fn main() {
             reject_case("reserved_tag_alone", vec![ENVELOPE_TAG_RESERVED], "ReservedTag"),
             reject_case(
                 "reserved_tag_with_valid_remainder",
-                {
-                    let mut b = vec![ENVELOPE_TAG_RESERVED];
-                    b.extend_from_slice(&minimal.to_bytes()[1..]);
-                    b
-                },
+                with_tag(ENVELOPE_TAG_RESERVED, &minimal),
                 "ReservedTag",
             ),
             reject_case("v1_tag_alone", vec![ENVELOPE_TAG_V1], "Malformed"),
@@ -139,14 +152,148 @@ fn main() {
                 false,
             ),
         ],
-    });
+    })
+}
 
-    let path = std::env::var("NOTE_ENVELOPE_VECTORS").unwrap_or_else(|_| OUTPUT_PATH.to_string());
-    if let Some(parent) = Path::new(&path).parent() {
-        std::fs::create_dir_all(parent).expect("create vectors dir");
-    }
-    let mut out = serde_json::to_string_pretty(&doc).expect("serialize vectors");
+/// `note` as a v2 box to [`V2_RECIPIENT_SECRET`], under the ephemeral secret
+/// `[eph_fill; 32]` and the nonce `[nonce_fill; 24]`.
+fn v2_seal(note: &NotePlaintext, eph_fill: u8, nonce_fill: u8) -> EncryptedNote {
+    let recipient_pub = *crypto_box::SecretKey::from(V2_RECIPIENT_SECRET)
+        .public_key()
+        .as_bytes();
+    let plaintext = note.to_bytes_v2().expect("memo within bounds");
+    seal_with_ephemeral(
+        ENVELOPE_TAG_V2,
+        &[eph_fill; 32],
+        &[nonce_fill; 24],
+        &recipient_pub,
+        &plaintext,
+    )
+    .expect("note fits a padded size")
+}
+
+fn v2_note_case(
+    name: &str,
+    note: &NotePlaintext,
+    eph_fill: u8,
+    nonce_fill: u8,
+) -> serde_json::Value {
+    let plaintext = note.to_bytes_v2().expect("memo within bounds");
+    let sealed = v2_seal(note, eph_fill, nonce_fill);
+    let bytes = sealed.to_bytes();
+
+    // Open what was written, as a reader of the file would.
+    let parsed = EncryptedNote::from_bytes(&bytes).expect("vector must parse");
+    assert_eq!(
+        decrypt_note(&V2_RECIPIENT_SECRET, &parsed).as_ref(),
+        Some(note),
+        "vector must open to its note"
+    );
+
+    serde_json::json!({
+        "name": name,
+        "recipient_secret": hex::encode(V2_RECIPIENT_SECRET),
+        "ephemeral_secret": hex::encode([eph_fill; 32]),
+        "nonce": hex::encode(sealed.nonce),
+        "note": {
+            "amount": note.amount,
+            "randomness": hex::encode(note.randomness),
+            "recipient": hex::encode(note.recipient),
+            "asset_id": hex::encode(note.asset_id.expect("v2 notes name their asset")),
+            "memo": hex::encode(&note.memo),
+        },
+        "plaintext": hex::encode(&plaintext),
+        "padded": hex::encode(pad_v2(&plaintext).expect("note fits a padded size")),
+        "bytes": hex::encode(&bytes),
+    })
+}
+
+fn v2_vectors() -> serde_json::Value {
+    // A canonical field element: the top byte is clear.
+    let spl_asset: [u8; 32] = std::array::from_fn(|i| if i == 31 { 0 } else { 0x33 });
+    let note = |amount: u64, asset_id: [u8; 32], memo: Vec<u8>| NotePlaintext {
+        amount,
+        randomness: [0x11; 32],
+        recipient: [0x22; 32],
+        asset_id: Some(asset_id),
+        memo,
+        diversifier: None,
+    };
+    let bare = note(1_000_000, NATIVE_SOL_ASSET, Vec::new());
+    let second_bucket_memo = (0..100).map(|i| b'a' + (i % 26) as u8).collect();
+    let max_memo = (0..MAX_MEMO_LEN).map(|i| i as u8).collect();
+
+    let first = v2_seal(&bare, 0x41, 0x51);
+    let with_record = first.to_bytes_with_outgoing(&v1(16));
+    let unpadded = with_tag(ENVELOPE_TAG_V2, &v1(88));
+
+    serde_json::json!({
+        "description":
+            "EncryptedNote envelope v2. Framing is v1's, tag(1) || epk(32) || \
+             nonce(24) || ct, but the sealed bytes are len(2, LE) || payload || \
+             zeros, filled out to the smallest of padded_sizes that holds them, \
+             and a ct of any other size is malformed. On the note path the \
+             payload is schema(1) || amount(8, LE) || randomness(32) || \
+             recipient(32) || asset_id(32) || memo_len(2, LE) || memo. \
+             Tag v2_outgoing appends the sender's record as under v1_outgoing: \
+             epk(32) || nonce(24) || ct_len(2, LE) || ct || out_epk(32) || \
+             out_nonce(24) || out_ct, the record being a v1 box. Note vectors \
+             are real crypto_box output under the published secrets.",
+        "tags": {
+            "v1": ENVELOPE_TAG_V1,
+            "v2": ENVELOPE_TAG_V2,
+            "v2_outgoing": ENVELOPE_TAG_V2_OUTGOING,
+        },
+        "note_schema": NOTE_SCHEMA_V2,
+        "max_memo_len": MAX_MEMO_LEN,
+        "padded_sizes": V2_PADDED_SIZES,
+        "notes": [
+            v2_note_case("no_memo_native", &bare, 0x41, 0x51),
+            v2_note_case(
+                "short_memo_spl",
+                &note(250_000, spl_asset, b"lunch on friday".to_vec()),
+                0x42,
+                0x52,
+            ),
+            v2_note_case(
+                "memo_second_bucket",
+                &note(42, NATIVE_SOL_ASSET, second_bucket_memo),
+                0x43,
+                0x53,
+            ),
+            v2_note_case("max_memo", &note(7, spl_asset, max_memo), 0x44, 0x54),
+        ],
+        "reject": [
+            reject_case("v2_tag_alone", vec![ENVELOPE_TAG_V2], "Malformed"),
+            reject_case("v2_unpadded_ct", unpadded.clone(), "Malformed"),
+            reject_case(
+                "v2_outgoing_truncated_record",
+                with_record[..with_record.len() - 1].to_vec(),
+                "Malformed",
+            ),
+            reject_case("unknown_version", with_tag(UNKNOWN_TAG, &v1(16)), "UnknownVersion"),
+        ],
+        "relay": [
+            relay_case("v2_valid_relays", first.to_bytes(), true),
+            relay_case("v2_outgoing_valid_relays", with_record, true),
+            relay_case("v2_unpadded_rejected", unpadded, false),
+            relay_case("unknown_version_relays", with_tag(UNKNOWN_TAG, &v1(16)), true),
+        ],
+    })
+}
+
+fn write(dir: &Path, name: &str, doc: &serde_json::Value) {
+    let path = dir.join(name);
+    let mut out = serde_json::to_string_pretty(doc).expect("serialize vectors");
     out.push('\n');
     std::fs::write(&path, out).expect("write vectors");
-    println!("wrote {path}");
+    println!("wrote {}", path.display());
+}
+
+fn main() {
+    let dir = std::env::var("NOTE_ENVELOPE_VECTORS_DIR").unwrap_or_else(|_| OUTPUT_DIR.to_string());
+    let dir = Path::new(&dir);
+    std::fs::create_dir_all(dir).expect("create vectors dir");
+    write(dir, "note_envelope_v1.json", &v1_vectors());
+    write(dir, "note_envelope_v2.json", &v2_vectors());
 }
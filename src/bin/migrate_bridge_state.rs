@@ -10,11 +10,16 @@
 //! `migrate_bridge_state` migration, then — if `DEPOSIT_CAP` is set — opens the
 //! cap in a second transaction.
 //!
+//! The same grow brings in `leader_bonus_bps` (the settling validator's share
+//! of each fee before the stake-weighted split), which starts at 0;
+//! `LEADER_BONUS_BPS` sets it afterwards.
+//!
 //! Env:
 //!   SOLANA_RPC_URL                 (default: devnet)
 //!   SOLANA_PROGRAM_ID              the deployed bridge program id
 //!   BRIDGE_AUTHORITY_KEYPAIR_PATH  the program's upgrade / cold authority (signer)
 //!   DEPOSIT_CAP                    optional: lamport cap to set after migrating
+//!   LEADER_BONUS_BPS               optional: leader fee bonus to set after migrating
 
 use paraloom::bridge::solana::*;
 use solana_client::rpc_client::RpcClient;
@@ -35,6 +40,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         Ok(s) => Some(s.parse()?),
         Err(_) => None,
     };
+    let leader_bonus_bps: Option<u16> = match std::env::var("LEADER_BONUS_BPS") {
+        Ok(s) => Some(s.parse()?),
+        Err(_) => None,
+    };
 
     let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
 
@@ -77,5 +86,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         println!("\nDEPOSIT_CAP not set — cap stays 0 (closed). Set it deliberately later.");
     }
 
+    if let Some(bonus_bps) = leader_bonus_bps {
+        let ix = create_set_leader_bonus_instruction(&program_id, &authority.pubkey(), bonus_bps);
+        let blockhash = client.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(
+            &[ix],
+            Some(&authority.pubkey()),
+            &[&authority],
+            blockhash,
+        );
+        println!("\nSetting leader bonus to {bonus_bps} bps...");
+        let sig = client.send_and_confirm_transaction(&tx)?;
+        println!("Leader bonus set. Signature: {sig}");
+    }
+
     Ok(())
 }
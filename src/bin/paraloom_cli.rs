@@ -6,10 +6,19 @@
 //!
 //! ```bash
 //! # Wallet operations
+//! paraloom wallet create
+//! paraloom wallet restore --node http://127.0.0.1:8090
 //! paraloom wallet deposit --amount 10.0
 //! paraloom wallet transfer --to <address> --amount 5.0
+//! paraloom wallet pay "paraloom:<address>?amount=5000000000&memo=invoice-12"
 //! paraloom wallet withdraw --to <solana-address> --amount 5.0
 //! paraloom wallet balance
+//! paraloom wallet sync --node http://127.0.0.1:8090
+//! paraloom wallet audit --viewing-key <paraloomivk1...> --node http://127.0.0.1:8090
+//! paraloom wallet prove-association --commitment <hex> --root <hex> -o proof.json
+//!
+//! # Association sets
+//! paraloom association build --name clean --exclude blocked.txt -o clean.json
 //!
 //! # Compute operations
 //! paraloom compute submit --wasm program.wasm --input data.json
@@ -20,21 +29,21 @@
 //! paraloom validator start --config validator.toml
 //! paraloom validator stop
 //! paraloom validator status
+//! paraloom validator snapshot fetch --peer <multiaddr> -o pool.snapshot
+//! paraloom validator snapshot import pool.snapshot --data-dir ./data
+//! paraloom validator slashing list --data-dir ./data
+//! paraloom validator slashing export -o evidence.json
+//! paraloom validator certificate show <request-id> --data-dir ./data
+//! paraloom validator certificate verify certificate.json
 //! ```
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-#[cfg(feature = "solana-bridge")]
-use ark_bn254::Fr;
-#[cfg(feature = "solana-bridge")]
-use ark_ff::{BigInteger, PrimeField};
 #[cfg(feature = "solana-bridge")]
 use paraloom::bridge::solana::*;
 #[cfg(feature = "solana-bridge")]
-use paraloom::privacy::poseidon_circom::v3_pubkey;
-#[cfg(feature = "solana-bridge")]
 use solana_client::rpc_client::RpcClient;
 #[cfg(feature = "solana-bridge")]
 use solana_sdk::{
@@ -42,6 +51,8 @@ use solana_sdk::{
     signature::Signer, transaction::Transaction,
 };
 #[cfg(feature = "solana-bridge")]
+use solana_transaction_status::{option_serializer::OptionSerializer, UiTransactionEncoding};
+#[cfg(feature = "solana-bridge")]
 use std::str::FromStr;
 
 // Node boot (validator start) — core, not gated on the Solana bridge.
@@ -55,7 +66,26 @@ use std::sync::Arc;
 
 // Privacy layer imports
 use paraloom::compute::PrivateComputeJob;
-use paraloom::privacy::ShieldedAddress;
+use paraloom::privacy::types::NATIVE_SOL_ASSET;
+use paraloom::privacy::{DetectionKey, PaymentAddress, PaymentRequest, ShieldedAddress};
+
+// Snapshot imports
+use paraloom::network::{fetch_snapshot, NetworkManager};
+use paraloom::privacy::pool::ShieldedPool;
+use paraloom::privacy::snapshot::{PoolSnapshot, SnapshotCursor};
+use paraloom::storage::PrivacyStorage;
+use paraloom::types::NodeId;
+
+// Slashing imports
+use paraloom::consensus::{SlashStatus, SlashingEvidence, SlashingRecord};
+use paraloom::storage::SlashingStorage;
+
+// Quorum certificate imports
+use paraloom::consensus::QuorumCertificate;
+use paraloom::storage::CertificateStorage;
+#[cfg(feature = "solana-bridge")]
+use paraloom::wallet::PendingDeposit;
+use paraloom::wallet::{Wallet, WalletKeys};
 
 // Global job executor instance
 static JOB_EXECUTOR: Lazy<Arc<JobExecutor>> = Lazy::new(|| {
@@ -102,6 +132,12 @@ enum Commands {
         command: ValidatorCommands,
     },
 
+    /// Association-set (allow-list) curation
+    Association {
+        #[command(subcommand)]
+        command: AssociationCommands,
+    },
+
     /// Initialize Paraloom configuration
     Init {
         /// Directory to initialize
@@ -116,6 +152,48 @@ enum Commands {
 
 #[derive(Subcommand)]
 enum WalletCommands {
+    /// Create a shielded wallet from a new recovery phrase
+    Create {
+        /// Account index to derive the keys at
+        #[arg(long, default_value = "0")]
+        account: u32,
+    },
+
+    /// Restore a shielded wallet from its recovery phrase and rescan a node
+    ///
+    /// The phrase is read from PARALOOM_MNEMONIC or prompted for; an optional
+    /// BIP-39 passphrase from PARALOOM_MNEMONIC_PASSPHRASE.
+    Restore {
+        /// Account index the keys were derived at
+        #[arg(long, default_value = "0")]
+        account: u32,
+
+        /// Node transact ingress URL to rescan (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Node association server URL to rescan deposits from
+        /// (default: PARALOOM_ASSOCIATION_URL or http://127.0.0.1:9091)
+        #[arg(long)]
+        association_server: Option<String>,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Bridge program ID (enables the on-chain spent check)
+        #[arg(long)]
+        program_id: Option<String>,
+
+        /// Move an existing wallet aside instead of refusing
+        #[arg(long)]
+        force: bool,
+    },
+
     /// Deposit SOL to Paraloom privacy pool
     Deposit {
         /// Amount in SOL
@@ -145,28 +223,106 @@ enum WalletCommands {
         #[arg(short, long)]
         amount: f64,
 
-        /// Optional memo (encrypted)
+        /// Optional memo, encrypted to the recipient (at most 512 bytes)
         #[arg(short, long)]
         memo: Option<String>,
+
+        /// Node transact ingress URL (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Bearer token, if the node's ingress requires one
+        #[arg(long)]
+        ingress_token: Option<String>,
+
+        /// Transact (v3) proving key
+        #[arg(long, default_value = "keys/transact_v3_proving.key")]
+        proving_key: PathBuf,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Bridge program ID (enables waiting for settlement)
+        #[arg(long)]
+        program_id: Option<String>,
+    },
+
+    /// Pay a payment request (paraloom:<address>?amount=...&mint=...&memo=...)
+    Pay {
+        /// The payment-request URI
+        uri: String,
+
+        /// Amount in base units, for a request that leaves it to the payer
+        #[arg(long)]
+        amount: Option<u64>,
+
+        /// Node transact ingress URL (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Bearer token, if the node's ingress requires one
+        #[arg(long)]
+        ingress_token: Option<String>,
+
+        /// Transact (v3) proving key
+        #[arg(long, default_value = "keys/transact_v3_proving.key")]
+        proving_key: PathBuf,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Bridge program ID (enables waiting for settlement)
+        #[arg(long)]
+        program_id: Option<String>,
     },
 
-    /// Withdraw SOL from Paraloom to Solana
+    /// Withdraw SOL (or an SPL token) from Paraloom to Solana
     Withdraw {
-        /// Destination Solana address
+        /// Destination Solana address (the token account owner for SPL)
         #[arg(short, long)]
         to: String,
 
-        /// Amount in SOL
+        /// Amount in SOL, or in whole tokens with --mint
         #[arg(short, long)]
         amount: f64,
 
-        /// Solana RPC URL (default: devnet)
+        /// SPL mint to withdraw instead of SOL
         #[arg(long)]
-        rpc_url: Option<String>,
+        mint: Option<String>,
 
-        /// Authority keypair path
+        /// Recipient token account (default: the associated token account of --to)
         #[arg(long)]
-        keypair: Option<PathBuf>,
+        token_account: Option<String>,
+
+        /// Node transact ingress URL (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Bearer token, if the node's ingress requires one
+        #[arg(long)]
+        ingress_token: Option<String>,
+
+        /// Transact (v3) proving key
+        #[arg(long, default_value = "keys/transact_v3_proving.key")]
+        proving_key: PathBuf,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
 
         /// Bridge program ID
         #[arg(long)]
@@ -192,18 +348,150 @@ enum WalletCommands {
         program_id: Option<String>,
     },
 
-    /// List transaction history (encrypted)
+    /// List shielded wallet history (received, sent and change notes)
     History {
         /// Number of transactions to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
     },
 
+    /// Scan a node for notes delivered to this wallet and refresh spent status
+    Sync {
+        /// Node transact ingress URL (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Bridge program ID (enables the on-chain spent check)
+        #[arg(long)]
+        program_id: Option<String>,
+
+        /// Hand the node a detection key of this many bits (0-24) and fetch
+        /// only candidate notes. Each bit halves the download and the cover
+        /// the node cannot see through. Sent payments are not recovered from
+        /// a narrowed scan, and the notes it passes over are not fetched
+        /// again; `--rescan` without it scans everything.
+        #[arg(long)]
+        detection_bits: Option<u8>,
+
+        /// Scan the node's feed from its start instead of from where the last
+        /// sync stopped
+        #[arg(long)]
+        rescan: bool,
+    },
+
     /// Generate new shielded address
     NewAddress {
         /// Label for the address
         #[arg(short, long)]
         label: Option<String>,
+
+        /// Print the diversified address at this index instead of the
+        /// default one (e.g. one per invoice); the same index always gives
+        /// the same address
+        #[arg(long)]
+        index: Option<u64>,
+    },
+
+    /// Print this wallet's read-only incoming viewing key
+    ExportViewingKey,
+
+    /// List the notes a viewing key received, without any spend key
+    Audit {
+        /// Incoming viewing key (paraloomivk1...)
+        #[arg(long)]
+        viewing_key: String,
+
+        /// Node transact ingress URL (e.g. http://127.0.0.1:8090)
+        #[arg(long)]
+        node: Option<String>,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Write a signed opening of one owned note for an auditor
+    Disclose {
+        /// Note commitment (hex)
+        #[arg(long)]
+        commitment: String,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check a note disclosure against its commitment
+    VerifyDisclosure {
+        /// Disclosure file written by `wallet disclose`
+        file: PathBuf,
+
+        /// Also require it to come from the wallet behind this viewing key
+        #[arg(long)]
+        viewing_key: Option<String>,
+    },
+
+    /// Prove a deposited note is in a published association set
+    ///
+    /// The proof is bound to the nullifier spending the note reveals, so a
+    /// counterparty can match it to the withdrawal it accompanies.
+    ProveAssociation {
+        /// Note commitment (hex); must be a deposit the set lists
+        #[arg(long)]
+        commitment: String,
+
+        /// Root of the association set to prove against (hex)
+        #[arg(long)]
+        root: String,
+
+        /// Node association server URL (default: http://127.0.0.1:9091)
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Node Merkle path server URL (default: http://127.0.0.1:9090)
+        #[arg(long)]
+        path_server: Option<String>,
+
+        /// Association proving key
+        #[arg(long, default_value = "keys/association_proving.key")]
+        proving_key: PathBuf,
+
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AssociationCommands {
+    /// Build an association set from a node's recorded deposits
+    ///
+    /// Publish the written file by adding it to a node's
+    /// `privacy.association_sets`.
+    Build {
+        /// Name of the set
+        #[arg(long)]
+        name: String,
+
+        /// Exclusion list: `depositor <address>` / `commitment <hex>` lines
+        #[arg(long)]
+        exclude: Option<PathBuf>,
+
+        /// Node association server URL (default: http://127.0.0.1:9091)
+        #[arg(long)]
+        server: Option<String>,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
     },
 }
 
@@ -386,6 +674,133 @@ enum ValidatorCommands {
         #[arg(short, long, default_value = "24h")]
         range: String,
     },
+
+    /// Fast-sync snapshots of a bridge node's shielded pool
+    Snapshot {
+        #[command(subcommand)]
+        command: SnapshotCommands,
+    },
+
+    /// Equivocation evidence a bridge node has recorded
+    Slashing {
+        #[command(subcommand)]
+        command: SlashingCommands,
+    },
+
+    /// Quorum certificates of the transacts a bridge node saw approved
+    Certificate {
+        #[command(subcommand)]
+        command: CertificateCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum CertificateCommands {
+    /// Show who approved a transact, from a node's certificate store
+    Show {
+        /// Canonical request id of the transact
+        request_id: String,
+
+        /// The node's data directory (`storage.data_dir`)
+        #[arg(long, default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Also write the certificate, signatures included, to a JSON file
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Check a certificate file offline: every vote signature, and that the
+    /// approving wallets hold a supermajority of the stake it names
+    #[cfg(feature = "solana-bridge")]
+    Verify {
+        /// Certificate JSON (from `show --out` or `GET /transact/certificate/<id>`)
+        file: PathBuf,
+
+        /// Require the votes to be for this program (default: the one named)
+        #[arg(long)]
+        program_id: Option<String>,
+
+        /// Require the votes to be on this cluster (default: the one named)
+        #[arg(long)]
+        cluster_tag: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum SlashingCommands {
+    /// List recorded evidence and where each entry stands
+    List {
+        /// The node's data directory (`storage.data_dir`)
+        #[arg(long, default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Only evidence against this co-sign wallet
+        #[arg(long)]
+        wallet: Option<String>,
+    },
+
+    /// Write recorded evidence, signatures included, to a JSON file
+    Export {
+        /// The node's data directory (`storage.data_dir`)
+        #[arg(long, default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Only evidence against this co-sign wallet
+        #[arg(long)]
+        wallet: Option<String>,
+
+        /// File to write
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommands {
+    /// Write the pool of a stopped node to a snapshot file
+    Export {
+        /// The node's data directory (`storage.data_dir`)
+        #[arg(long, default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Snapshot file to write
+        #[arg(short, long)]
+        out: PathBuf,
+
+        /// Solana RPC URL, to look up the cursor's slot (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
+
+    /// Download the snapshot a running node serves
+    Fetch {
+        /// The node's multiaddr, ending in /p2p/<peer-id>
+        #[arg(long)]
+        peer: String,
+
+        /// Snapshot file to write
+        #[arg(short, long)]
+        out: PathBuf,
+    },
+
+    /// Check a snapshot against the chain and load it into a node's data directory
+    Import {
+        /// Snapshot file to import
+        file: PathBuf,
+
+        /// The node's data directory (`storage.data_dir`)
+        #[arg(long, default_value = "./data")]
+        data_dir: PathBuf,
+
+        /// Solana RPC URL (default: devnet)
+        #[arg(long)]
+        rpc_url: Option<String>,
+
+        /// Bridge program ID (default: canonical devnet deployment)
+        #[arg(long)]
+        program_id: Option<String>,
+    },
 }
 
 fn print_banner() {
@@ -426,10 +841,485 @@ async fn main() -> Result<()> {
         Commands::Wallet { command } => handle_wallet_command(command).await,
         Commands::Compute { command } => handle_compute_command(command).await,
         Commands::Validator { command } => handle_validator_command(command).await,
+        Commands::Association { command } => handle_association_command(command).await,
         Commands::Init { path, force } => handle_init_command(path, force).await,
     }
 }
 
+/// Directory holding the shielded wallet's keys and note database.
+const WALLET_DIR: &str = ".paraloom/wallet";
+
+/// Open the local shielded wallet. Keys are never created implicitly: a
+/// wallet starts from `wallet create` or `wallet restore`, so there is always
+/// a recovery phrase behind it.
+fn open_wallet() -> Result<Wallet> {
+    let dir = std::path::Path::new(WALLET_DIR);
+    if !Wallet::exists(dir) {
+        anyhow::bail!(
+            "No shielded wallet at {}. Run `paraloom wallet create`, or \
+             `paraloom wallet restore` with an existing recovery phrase",
+            WALLET_DIR
+        );
+    }
+    Wallet::open(dir).context("Failed to open shielded wallet")
+}
+
+/// The recovery phrase to restore from: `PARALOOM_MNEMONIC`, else a prompt.
+fn read_mnemonic() -> Result<String> {
+    if let Ok(phrase) = std::env::var("PARALOOM_MNEMONIC") {
+        return Ok(phrase);
+    }
+    use std::io::Write;
+    print!("Recovery phrase: ");
+    std::io::stdout().flush()?;
+    let mut phrase = String::new();
+    std::io::stdin()
+        .read_line(&mut phrase)
+        .context("Failed to read the recovery phrase")?;
+    Ok(phrase)
+}
+
+/// The optional BIP-39 passphrase, from `PARALOOM_MNEMONIC_PASSPHRASE`.
+fn mnemonic_passphrase() -> String {
+    std::env::var("PARALOOM_MNEMONIC_PASSPHRASE").unwrap_or_default()
+}
+
+/// The page of `base`'s scan feed after cursor `after`: every delivered
+/// note or, given a detection key, the candidates among them.
+async fn fetch_page(
+    http: &reqwest::Client,
+    base: &str,
+    detection: Option<&DetectionKey>,
+    after: u64,
+) -> Result<paraloom::node::transact_ingress::DeliveredPage> {
+    let request = match detection {
+        Some(key) => http
+            .post(format!("{}/transact/scan/detect", base))
+            .json(&serde_json::json!({ "detection_key": key.encode(), "after": after })),
+        None => http.get(format!("{}/transact/scan?after={}", base, after)),
+    };
+    request
+        .send()
+        .await
+        .context("Failed to reach the node's scan endpoint")?
+        .error_for_status()
+        .context("Scan endpoint returned an error")?
+        .json()
+        .await
+        .context("Failed to decode the scan response")
+}
+
+/// Every encrypted note `node` has delivered, walking the scan feed page by
+/// page from its start.
+async fn fetch_delivered(
+    node: &str,
+) -> Result<Vec<paraloom::node::transact_ingress::DeliveredNote>> {
+    let base = node.trim_end_matches('/');
+    let http = reqwest::Client::new();
+    println!("Scanning {}/transact/scan ...", base);
+
+    let mut notes = Vec::new();
+    let mut after = 0u64;
+    loop {
+        let page = fetch_page(&http, base, None, after).await?;
+        notes.extend(page.notes);
+        if !page.more || page.cursor <= after {
+            return Ok(notes);
+        }
+        after = page.cursor;
+    }
+}
+
+/// Scan `node`'s note feed into `wallet` from where the last sync stopped
+/// (from its start with `rescan`), look up the tree position of notes that
+/// lack one, and (with a program ID) mark spent notes from the chain's
+/// nullifier accounts.
+async fn sync_wallet(
+    wallet: &Wallet,
+    node: &str,
+    path_server: &str,
+    rpc_url: Option<String>,
+    program_id: Option<String>,
+    detection: Option<DetectionKey>,
+    rescan: bool,
+) -> Result<()> {
+    let base = node.trim_end_matches('/');
+    if rescan {
+        wallet.storage().reset_scan_cursor(base)?;
+    }
+    let http = reqwest::Client::new();
+    match &detection {
+        Some(key) => println!(
+            "Scanning {}/transact/scan/detect at {} detection bits ...",
+            base,
+            key.precision()
+        ),
+        None => println!("Scanning {}/transact/scan ...", base),
+    }
+    let report = wallet
+        .scan(base, |after| {
+            fetch_page(&http, base, detection.as_ref(), after)
+        })
+        .await?;
+    println!(
+        "[OK] Scanned {} delivered notes: {} new",
+        report.scanned, report.discovered
+    );
+    if report.sent > 0 {
+        println!(
+            "[OK] Recovered {} sent payments from outgoing records",
+            report.sent
+        );
+    }
+    if report.rejected > 0 {
+        println!(
+            "[WARNING] {} notes decrypted for this wallet but did not match their \
+             commitment, and were ignored",
+            report.rejected
+        );
+    }
+
+    // The scan files each note at the leaf index its feed entry carries. A
+    // note the feed had no position for is placed by the path server.
+    let http = reqwest::Client::new();
+    let mut positioned = 0;
+    let mut unpositioned = 0;
+    for note in wallet.unspent_notes()? {
+        if note.leaf_index.is_some() {
+            continue;
+        }
+        match paraloom::wallet::fetch_membership(&http, path_server, &note.commitment).await {
+            Ok(membership) => {
+                wallet.set_leaf_index(&note.commitment, membership.leaf_index)?;
+                positioned += 1;
+            }
+            Err(e) => {
+                log::debug!("no path for {}: {e:#}", hex::encode(note.commitment));
+                unpositioned += 1;
+            }
+        }
+    }
+    if positioned + unpositioned > 0 {
+        println!(
+            "[OK] Leaf positions: {} found, {} not yet indexed by {}",
+            positioned, unpositioned, path_server
+        );
+    }
+
+    #[cfg(feature = "solana-bridge")]
+    {
+        if let Some(program_id_str) = program_id.or_else(|| std::env::var("SOLANA_PROGRAM_ID").ok())
+        {
+            let rpc_url = rpc_url
+                .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+                .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+            let program_id = Pubkey::from_str(&program_id_str).context("Invalid program ID")?;
+            let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+
+            settle_pending_deposits(wallet, &client)?;
+
+            // A nullifier PDA owned by the program is the authoritative
+            // record that a note was spent.
+            let marked = wallet.refresh_spent(|nullifier| {
+                let (pda, _) = derive_nullifier_account(&program_id, nullifier);
+                let account = client
+                    .get_account_with_commitment(&pda, CommitmentConfig::confirmed())
+                    .context("Failed to query nullifier account")?
+                    .value;
+                Ok(account.is_some_and(|a| a.owner == program_id))
+            })?;
+            println!("[OK] Spent check: {} notes newly marked spent", marked);
+        } else {
+            println!("Skipping on-chain spent check (no --program-id)");
+            let pending = wallet.pending_deposits()?.len();
+            if pending > 0 {
+                println!(
+                    "[WARNING] {} deposits not yet seen on chain; pass --program-id to settle them",
+                    pending
+                );
+            }
+        }
+    }
+
+    #[cfg(not(feature = "solana-bridge"))]
+    {
+        let _ = (&rpc_url, &program_id);
+    }
+
+    Ok(())
+}
+
+/// The leaf a confirmed `deposit_note` transaction appended, from the
+/// program's log. `None` if the transaction or its logs cannot be read.
+#[cfg(feature = "solana-bridge")]
+fn deposit_leaf_index(
+    client: &RpcClient,
+    signature: &solana_sdk::signature::Signature,
+) -> Option<u64> {
+    client
+        .get_transaction(signature, UiTransactionEncoding::Json)
+        .ok()
+        .and_then(|tx| tx.transaction.meta)
+        .and_then(|meta| match meta.log_messages {
+            OptionSerializer::Some(logs) => deposit_note_leaf_indices(&logs).first().copied(),
+            _ => None,
+        })
+}
+
+/// Take every pending deposit whose transaction landed into the wallet, and
+/// drop the ones that failed or can no longer land.
+#[cfg(feature = "solana-bridge")]
+fn settle_pending_deposits(wallet: &Wallet, client: &RpcClient) -> Result<()> {
+    use solana_sdk::hash::Hash;
+    use solana_sdk::signature::Signature;
+
+    let (mut landed, mut dropped, mut waiting) = (0, 0, 0);
+    for pending in wallet.pending_deposits()? {
+        let commitment = pending.note.commitment;
+        let signature =
+            Signature::from_str(&pending.signature).context("Invalid pending deposit signature")?;
+        let blockhash =
+            Hash::from_str(&pending.blockhash).context("Invalid pending deposit blockhash")?;
+        // Ask about the blockhash before the signature: if it had already
+        // expired when the status came back empty, the deposit never landed.
+        let expired = !client
+            .is_blockhash_valid(&blockhash, CommitmentConfig::processed())
+            .context("Failed to check the deposit blockhash")?;
+        let status = client
+            .get_signature_status_with_commitment_and_history(
+                &signature,
+                CommitmentConfig::confirmed(),
+                true,
+            )
+            .context("Failed to query the deposit transaction")?;
+        match status {
+            Some(Ok(())) => {
+                wallet.confirm_deposit(&commitment, deposit_leaf_index(client, &signature))?;
+                landed += 1;
+            }
+            Some(Err(e)) => {
+                log::warn!("deposit {} failed on chain: {}", signature, e);
+                wallet.abandon_deposit(&commitment)?;
+                dropped += 1;
+            }
+            None if expired => {
+                wallet.abandon_deposit(&commitment)?;
+                dropped += 1;
+            }
+            None => waiting += 1,
+        }
+    }
+    if landed + dropped + waiting > 0 {
+        println!(
+            "[OK] Pending deposits: {} landed, {} dropped, {} still in flight",
+            landed, dropped, waiting
+        );
+    }
+    Ok(())
+}
+
+/// Read a Groth16 proving key: v3 transact or association.
+fn load_transact_proving_key(
+    path: &std::path::Path,
+) -> Result<ark_groth16::ProvingKey<ark_bn254::Bn254>> {
+    use ark_serialize::CanonicalDeserialize;
+    println!("Loading proving key {} ...", path.display());
+    let bytes = std::fs::read(path)
+        .with_context(|| format!("Failed to read proving key {}", path.display()))?;
+    ark_groth16::ProvingKey::deserialize_compressed(&bytes[..])
+        .context("Failed to decode proving key")
+}
+
+/// The association server URL: `--server`, else `PARALOOM_ASSOCIATION_URL`,
+/// else the local default.
+fn association_server(server: Option<String>) -> String {
+    server
+        .or_else(|| std::env::var("PARALOOM_ASSOCIATION_URL").ok())
+        .unwrap_or_else(|| "http://127.0.0.1:9091".to_string())
+}
+
+/// Every deposit the association server at `server` has indexed, in leaf
+/// order, walking its deposit list page by page.
+async fn fetch_deposits(server: &str) -> Result<Vec<paraloom::storage::DepositRecord>> {
+    use paraloom::node::association::{DepositResponse, MAX_DEPOSIT_PAGE};
+
+    let base = server.trim_end_matches('/');
+    let http = reqwest::Client::new();
+    println!("Fetching deposits from {} ...", server);
+    let mut deposits = Vec::new();
+    let mut from = 0u64;
+    loop {
+        let page: Vec<DepositResponse> = http
+            .get(format!(
+                "{base}/association/deposits?from={from}&limit={MAX_DEPOSIT_PAGE}"
+            ))
+            .send()
+            .await
+            .context("Failed to reach the association server")?
+            .error_for_status()
+            .context("Association server returned an error")?
+            .json()
+            .await
+            .context("Failed to decode the deposit page")?;
+        for deposit in &page {
+            deposits.push(
+                deposit
+                    .to_record()
+                    .context("Association server returned malformed hex")?,
+            );
+        }
+        match page.last() {
+            Some(last) if page.len() == MAX_DEPOSIT_PAGE => from = last.leaf_index + 1,
+            _ => return Ok(deposits),
+        }
+    }
+}
+
+/// Render a shielded amount: SOL for the native asset, raw base units for SPL.
+fn format_shielded_amount(amount: u64, asset_id: &[u8; 32]) -> String {
+    if asset_id == &NATIVE_SOL_ASSET {
+        format!("{} SOL", amount as f64 / 1_000_000_000f64)
+    } else {
+        format!("{} units", amount)
+    }
+}
+
+/// Short label for an asset id in tables.
+fn asset_label(asset_id: &[u8; 32]) -> String {
+    if asset_id == &NATIVE_SOL_ASSET {
+        "SOL".to_string()
+    } else {
+        format!("{}...", &hex::encode(asset_id)[..12])
+    }
+}
+
+/// Where a shielded spend is proved against and submitted to.
+struct SendOptions {
+    node: Option<String>,
+    path_server: Option<String>,
+    ingress_token: Option<String>,
+    proving_key: PathBuf,
+    rpc_url: Option<String>,
+    program_id: Option<String>,
+}
+
+/// Pay `amount` base units of `mint` (native SOL when `None`) to `recipient`
+/// from this wallet's notes, sealing `memo` to them, and file the spend once
+/// it settles. `counterparty` is what the history shows for it.
+async fn send_shielded(
+    recipient: &PaymentAddress,
+    counterparty: String,
+    mint: Option<[u8; 32]>,
+    amount: u64,
+    memo: &str,
+    options: SendOptions,
+) -> Result<()> {
+    let SendOptions {
+        node,
+        path_server,
+        ingress_token,
+        proving_key,
+        rpc_url,
+        program_id,
+    } = options;
+    let node = node
+        .or_else(|| std::env::var("PARALOOM_NODE_URL").ok())
+        .context("Node URL not specified. Use --node or PARALOOM_NODE_URL")?;
+    let path_server = path_server
+        .or_else(|| std::env::var("PARALOOM_PATH_SERVER_URL").ok())
+        .unwrap_or_else(|| "http://127.0.0.1:9090".to_string());
+    let ingress_token = ingress_token.or_else(|| std::env::var("PARALOOM_INGRESS_TOKEN").ok());
+    let asset_id = mint
+        .as_ref()
+        .map(paraloom::privacy::poseidon_circom::mint_to_asset)
+        .unwrap_or(NATIVE_SOL_ASSET);
+
+    let wallet = open_wallet()?;
+    let notes = paraloom::wallet::select_inputs(&wallet.unspent_notes()?, &asset_id, amount)?;
+    println!("\nSpending {} note(s)", notes.len());
+
+    let http = reqwest::Client::new();
+    println!("Fetching Merkle paths from {} ...", path_server);
+    let inputs = paraloom::wallet::fetch_inputs(&http, &path_server, notes).await?;
+    for input in &inputs {
+        wallet.set_leaf_index(&input.note.commitment, input.membership.leaf_index)?;
+    }
+    let spent: Vec<[u8; 32]> = inputs.iter().map(|i| i.note.commitment).collect();
+
+    let (plan, change) = paraloom::wallet::build_transfer(
+        wallet.keys(),
+        inputs,
+        recipient,
+        amount,
+        memo.as_bytes(),
+    )?;
+
+    let pk = load_transact_proving_key(&proving_key)?;
+    println!("Generating zkSNARK proof (this can take a while)...");
+    let proved = plan.prove(wallet.keys(), &pk)?;
+
+    println!("Submitting to {} ...", node);
+    let request_id =
+        paraloom::wallet::submit_transact(&http, &node, ingress_token.as_deref(), &proved).await?;
+    println!("  Request ID: {}", request_id);
+
+    #[cfg(feature = "solana-bridge")]
+    {
+        if let Some(program_id_str) = program_id.or_else(|| std::env::var("SOLANA_PROGRAM_ID").ok())
+        {
+            let rpc_url = rpc_url
+                .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+                .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+            let program_id = Pubkey::from_str(&program_id_str).context("Invalid program ID")?;
+            let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+
+            println!("Waiting for validator consensus and settlement...");
+            match paraloom::relayer::transact_submit::wait_for_nullifier(
+                &client,
+                &program_id,
+                &proved.nullifiers[0],
+                std::time::Duration::from_secs(120),
+            ) {
+                Ok(()) => {
+                    wallet.record_sent(
+                        &spent,
+                        amount,
+                        asset_id,
+                        Some(counterparty),
+                        Some(proved.output_commitments[0]),
+                        change,
+                    )?;
+                    wallet
+                        .storage()
+                        .put_memo(&proved.output_commitments[0], memo.as_bytes())?;
+                    println!("\n[OK] Private transfer settled!");
+                    println!("  Sent: {}", format_shielded_amount(amount, &asset_id));
+                    return Ok(());
+                }
+                Err(e) => {
+                    println!("\n[WARNING] {}", e);
+                    println!(
+                        "  The transfer may still settle. Run 'paraloom wallet sync' to \
+                         pick up the change note and spent status."
+                    );
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    #[cfg(not(feature = "solana-bridge"))]
+    {
+        let _ = (&rpc_url, &program_id, &change, &spent, &counterparty);
+    }
+
+    println!("\n[OK] Private transfer submitted.");
+    println!("  Not waiting for settlement (no --program-id).");
+    println!("  Run 'paraloom wallet sync' once it lands to pick up the change note.");
+
+    Ok(())
+}
+
 async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
     match command {
         WalletCommands::Deposit {
@@ -500,35 +1390,21 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
                 let (bridge_vault, _vault_bump) = derive_bridge_vault(&program_id);
                 println!("Bridge Vault PDA: {}\n", bridge_vault);
 
-                // Generate a fresh v3 note: a spend secret key + blinding. The
-                // program computes and appends the note commitment
+                // The note is owned by this wallet's spend key, so it shows up
+                // in `wallet balance --detailed` and can be spent by the CLI.
+                // The program computes and appends the note commitment
                 // Poseidon(amount, pubkey, blinding, asset) on-chain
                 // (`deposit_note`, #350); the legacy off-chain-root `deposit`
-                // handler was removed.
-                let fr_to_le = |f: &Fr| -> [u8; 32] {
-                    let mut out = [0u8; 32];
-                    let le = f.into_bigint().to_bytes_le();
-                    out[..le.len().min(32)].copy_from_slice(&le[..le.len().min(32)]);
-                    out
-                };
-                let note_sk = Fr::from_le_bytes_mod_order(&rand::random::<[u8; 32]>());
-                let note_blinding = Fr::from_le_bytes_mod_order(&rand::random::<[u8; 32]>());
-                let note_pubkey = v3_pubkey(note_sk);
-                let pubkey_bytes = fr_to_le(&note_pubkey);
-                let blinding_bytes = fr_to_le(&note_blinding);
+                // handler was removed. The blinding is derived from the spend
+                // key and a deposit nonce, so `wallet restore` finds the note
+                // again from the recovery phrase.
+                let wallet = open_wallet()?;
+                let pubkey_bytes = wallet.keys().spend_pubkey();
+                let note = wallet.next_deposit_note(deposit_lamports)?;
+                let commitment = note.commitment;
 
                 println!("Deposit Amount: {} SOL", amount);
-                println!("Note pubkey: {}", hex::encode(pubkey_bytes));
-                // The spend key + blinding are the ONLY way to spend this note
-                // later. This CLI does not persist them, so record them now.
-                println!(
-                    "Note spend key (SAVE THIS): {}",
-                    hex::encode(fr_to_le(&note_sk))
-                );
-                println!(
-                    "Note blinding  (SAVE THIS): {}\n",
-                    hex::encode(blinding_bytes)
-                );
+                println!("Note pubkey: {}\n", hex::encode(pubkey_bytes));
 
                 // Create deposit_note instruction
                 println!("Creating deposit_note instruction...");
@@ -538,7 +1414,7 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
                     &bridge_vault,
                     deposit_lamports,
                     pubkey_bytes,
-                    blinding_bytes,
+                    note.blinding,
                 )
                 .context("Failed to create deposit_note instruction")?;
 
@@ -557,16 +1433,46 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
                     blockhash,
                 );
 
+                // File the note before sending: a confirmation that times out,
+                // or a crash after the send, must not lose a deposit that
+                // landed. `wallet sync` settles whatever is still pending.
+                wallet
+                    .begin_deposit(&PendingDeposit {
+                        note,
+                        signature: tx.signatures[0].to_string(),
+                        blockhash: blockhash.to_string(),
+                    })
+                    .context("Failed to record the pending deposit in the wallet")?;
+
                 // Send transaction
                 println!("Sending transaction...");
-                let signature = client
-                    .send_and_confirm_transaction(&tx)
-                    .context("Failed to send transaction")?;
+                let signature = client.send_and_confirm_transaction(&tx).with_context(|| {
+                    format!(
+                        "Failed to confirm deposit {}. It is kept as pending: \
+                         `paraloom wallet sync --program-id {}` records the note once \
+                         the transaction lands, or drops it once it no longer can",
+                        tx.signatures[0], program_id
+                    )
+                })?;
+
+                // Its leaf index comes from the program's log: reading
+                // `next_index` afterwards would race other depositors.
+                let leaf_index = deposit_leaf_index(&client, &signature);
+                wallet
+                    .confirm_deposit(&commitment, leaf_index)
+                    .context("Failed to record the deposited note in the wallet")?;
 
                 println!("\n[OK] Deposit successful!");
                 println!("  Transaction: {}", signature);
                 println!("  Shielded balance: {} SOL", amount);
                 println!("  Note pubkey: {}", hex::encode(pubkey_bytes));
+                match leaf_index {
+                    Some(index) => println!("  Leaf index: {}", index),
+                    None => println!(
+                        "  [WARNING] Could not read the leaf index from the transaction logs; \
+                         the path server will supply it when the note is spent."
+                    ),
+                }
                 println!("\nView transaction:");
                 println!("  solana confirm -v {}", signature);
             }
@@ -581,102 +1487,286 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
             Ok(())
         }
 
-        WalletCommands::Transfer { to, amount, memo } => {
+        WalletCommands::Transfer {
+            to,
+            amount,
+            memo,
+            node,
+            path_server,
+            ingress_token,
+            proving_key,
+            rpc_url,
+            program_id,
+        } => {
             println!("Initiating private transfer...\n");
             println!("Recipient: {}", to);
             println!("Amount: {} SOL (hidden)", amount);
 
-            if let Some(memo_text) = &memo {
-                println!("Memo: {}", memo_text);
-            }
-
-            // Convert recipient address string to ShieldedAddress
-            let recipient_address: ShieldedAddress =
-                if let Some(hex_part) = to.strip_prefix("paraloom1") {
-                    // Parse paraloom address (hex after paraloom1 prefix)
-                    let bytes = hex::decode(hex_part).context("Invalid paraloom address format")?;
+            // A checksummed payment address: a typo fails here instead of
+            // paying a key nobody holds.
+            let recipient = PaymentAddress::parse(&to)
+                .map_err(|e| anyhow::anyhow!("Invalid recipient address: {}", e))?;
 
-                    if bytes.len() != 32 {
-                        anyhow::bail!(
-                            "Invalid address length. Expected 32 bytes, got {}",
-                            bytes.len()
-                        );
-                    }
-
-                    let mut addr = [0u8; 32];
-                    addr.copy_from_slice(&bytes);
-                    ShieldedAddress(addr)
-                } else {
-                    anyhow::bail!("Invalid recipient address. Must start with 'paraloom1'");
-                };
+            let lamports = (amount * 1_000_000_000f64) as u64;
+            if lamports == 0 {
+                anyhow::bail!("Amount must be positive");
+            }
 
-            println!("\nCreating private transfer job...");
+            send_shielded(
+                &recipient,
+                to,
+                None,
+                lamports,
+                &memo.unwrap_or_default(),
+                SendOptions {
+                    node,
+                    path_server,
+                    ingress_token,
+                    proving_key,
+                    rpc_url,
+                    program_id,
+                },
+            )
+            .await
+        }
 
-            // Create a simple WASM module that represents the transfer
-            // In production, this would be a standardized transfer contract
-            let transfer_wasm = vec![
-                0x00, 0x61, 0x73, 0x6d, // WASM magic
-                0x01, 0x00, 0x00, 0x00, // WASM version
-            ];
+        WalletCommands::Pay {
+            uri,
+            amount,
+            node,
+            path_server,
+            ingress_token,
+            proving_key,
+            rpc_url,
+            program_id,
+        } => {
+            let request = PaymentRequest::parse(&uri)
+                .map_err(|e| anyhow::anyhow!("Invalid payment request: {}", e))?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            if request.is_expired(now) {
+                anyhow::bail!("Payment request expired; ask the recipient for a new one");
+            }
+            let amount = match (request.amount, amount) {
+                (Some(requested), Some(given)) if requested != given => {
+                    anyhow::bail!("The request is for {} base units, not {}", requested, given)
+                }
+                (Some(requested), _) => requested,
+                (None, Some(given)) if given > 0 => given,
+                (None, _) => anyhow::bail!("The request names no amount; pass --amount"),
+            };
+            let asset_id = request.asset_id();
 
-            // Encode transfer data as input
-            let transfer_data = serde_json::json!({
-                "type": "transfer",
-                "amount": amount,
-                "recipient": hex::encode(recipient_address.as_bytes()),
-                "memo": memo.clone().unwrap_or_default(),
-            });
-            let input_data = serde_json::to_vec(&transfer_data)?;
+            println!("Paying payment request...\n");
+            println!("Recipient: {}", request.address);
+            println!("Amount: {}", format_shielded_amount(amount, &asset_id));
+            if let Some(mint) = &request.mint {
+                println!("Mint: {}", hex::encode(mint));
+            }
+            if let Some(memo) = &request.memo {
+                println!("Memo: {}", memo);
+            }
 
-            println!("Generating privacy commitment...");
+            send_shielded(
+                &request.address,
+                request.address.encode(),
+                request.mint,
+                amount,
+                request.memo.as_deref().unwrap_or_default(),
+                SendOptions {
+                    node,
+                    path_server,
+                    ingress_token,
+                    proving_key,
+                    rpc_url,
+                    program_id,
+                },
+            )
+            .await
+        }
 
-            // Create private compute job
-            let limits = ResourceLimits::default();
-            let sender_address = ShieldedAddress([0u8; 32]); // Would load from user's keypair
+        WalletCommands::Withdraw {
+            to,
+            amount,
+            mint,
+            token_account,
+            node,
+            path_server,
+            ingress_token,
+            proving_key,
+            rpc_url,
+            program_id,
+        } => {
+            #[cfg(feature = "solana-bridge")]
+            {
+                let rpc_url = rpc_url
+                    .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+                    .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+                let program_id_str = program_id
+                    .or_else(|| std::env::var("SOLANA_PROGRAM_ID").ok())
+                    .context(
+                        "Bridge program ID not specified. Use --program-id or SOLANA_PROGRAM_ID",
+                    )?;
+                let program_id = Pubkey::from_str(&program_id_str).context("Invalid program ID")?;
+                let node = node
+                    .or_else(|| std::env::var("PARALOOM_NODE_URL").ok())
+                    .context("Node URL not specified. Use --node or PARALOOM_NODE_URL")?;
+                let path_server = path_server
+                    .or_else(|| std::env::var("PARALOOM_PATH_SERVER_URL").ok())
+                    .unwrap_or_else(|| "http://127.0.0.1:9090".to_string());
+                let ingress_token =
+                    ingress_token.or_else(|| std::env::var("PARALOOM_INGRESS_TOKEN").ok());
+                let client = RpcClient::new_with_commitment(rpc_url, CommitmentConfig::confirmed());
+                let owner = Pubkey::from_str(&to).context("Invalid destination address")?;
+
+                // Native SOL pays the destination itself. SPL pays a token
+                // account of the mint, and the proof binds that account, so it
+                // has to be resolved (and exist) before proving.
+                let (mint, recipient, base_amount) = match mint {
+                    None => (None, owner, (amount * LAMPORTS_PER_SOL as f64) as u64),
+                    Some(mint_str) => {
+                        let mint = Pubkey::from_str(&mint_str).context("Invalid mint")?;
+                        let mint_account = client
+                            .get_account(&mint)
+                            .context("Failed to fetch the mint account")?;
+                        // SPL mint layout: decimals is the byte after
+                        // mint_authority(36) + supply(8).
+                        let decimals = *mint_account
+                            .data
+                            .get(44)
+                            .context("Account is not an SPL mint")?;
+                        let recipient = match token_account {
+                            Some(ta) => {
+                                Pubkey::from_str(&ta).context("Invalid recipient token account")?
+                            }
+                            // The mint's owner is the token program (classic
+                            // or Token-2022) the ATA is derived under.
+                            None => {
+                                derive_associated_token_address(&owner, &mint, &mint_account.owner)
+                            }
+                        };
+                        if client.get_account(&recipient).is_err() {
+                            anyhow::bail!(
+                                "Recipient token account {} does not exist; create it first",
+                                recipient
+                            );
+                        }
+                        let base = (amount * 10f64.powi(decimals as i32)) as u64;
+                        (Some(mint.to_bytes()), recipient, base)
+                    }
+                };
+                if base_amount == 0 {
+                    anyhow::bail!("Amount must be positive");
+                }
+                let asset_id = mint
+                    .as_ref()
+                    .map(paraloom::privacy::poseidon_circom::mint_to_asset)
+                    .unwrap_or(NATIVE_SOL_ASSET);
+
+                println!("Withdrawing {} to {}...\n", amount, recipient);
+                if let Some(mint) = &mint {
+                    println!("Mint: {}", Pubkey::new_from_array(*mint));
+                }
 
-            let private_job =
-                PrivateComputeJob::new(transfer_wasm, input_data, sender_address, limits)?;
+                let wallet = open_wallet()?;
+                let notes = paraloom::wallet::select_inputs(
+                    &wallet.unspent_notes()?,
+                    &asset_id,
+                    base_amount,
+                )?;
+                println!("Spending {} note(s)", notes.len());
+
+                let http = reqwest::Client::new();
+                println!("Fetching Merkle paths from {} ...", path_server);
+                let inputs = paraloom::wallet::fetch_inputs(&http, &path_server, notes).await?;
+                for input in &inputs {
+                    wallet.set_leaf_index(&input.note.commitment, input.membership.leaf_index)?;
+                }
+                let spent: Vec<[u8; 32]> = inputs.iter().map(|i| i.note.commitment).collect();
+
+                let (plan, change) = paraloom::wallet::build_withdraw(
+                    wallet.keys(),
+                    inputs,
+                    recipient.to_bytes(),
+                    base_amount,
+                )?;
+
+                let pk = load_transact_proving_key(&proving_key)?;
+                println!("Generating zkSNARK proof (this can take a while)...");
+                let proved = plan.prove(wallet.keys(), &pk)?;
+
+                println!("Submitting to {} ...", node);
+                let request_id = paraloom::wallet::submit_transact(
+                    &http,
+                    &node,
+                    ingress_token.as_deref(),
+                    &proved,
+                )
+                .await?;
+                println!("  Request ID: {}", request_id);
 
-            println!("Submitting to privacy network...");
-            println!("  Job ID: {}", private_job.job_id);
-            println!(
-                "  Input commitment: {}...",
-                &private_job.input_commitment.to_hex()[..16]
-            );
-            println!("  Status: Pending consensus");
+                println!("Waiting for validator consensus and settlement...");
+                match paraloom::relayer::transact_submit::wait_for_settlement(
+                    &client,
+                    &program_id,
+                    &recipient,
+                    &proved.nullifiers[0],
+                    std::time::Duration::from_secs(120),
+                ) {
+                    Ok(balance) => {
+                        wallet.record_sent(
+                            &spent,
+                            base_amount,
+                            asset_id,
+                            Some(recipient.to_string()),
+                            None,
+                            change,
+                        )?;
+                        println!("\n[OK] Withdrawal settled!");
+                        println!("  Recipient: {}", recipient);
+                        if mint.is_none() {
+                            println!(
+                                "  Recipient balance: {} SOL",
+                                balance as f64 / LAMPORTS_PER_SOL as f64
+                            );
+                        }
+                        println!("  (the settling validator's fee is deducted on chain)");
+                    }
+                    Err(e) => {
+                        println!("\n[WARNING] {}", e);
+                        println!(
+                            "  The withdrawal may still settle. Run 'paraloom wallet sync \
+                             --program-id {}' to pick up the change note and spent status.",
+                            program_id
+                        );
+                    }
+                }
+            }
 
-            println!("\n[OK] Private transfer initiated!");
-            println!("  Privacy: Full (amount/recipient/sender all hidden)");
-            println!("  Consensus: Waiting for 2/3 validator agreement");
-            println!("\n[NOTE] Transfer will be finalized once validators reach consensus.");
-            println!("       Use 'paraloom wallet history' to track status.");
+            #[cfg(not(feature = "solana-bridge"))]
+            {
+                let _ = (
+                    &to,
+                    &amount,
+                    &mint,
+                    &token_account,
+                    &node,
+                    &path_server,
+                    &ingress_token,
+                    &proving_key,
+                    &rpc_url,
+                    &program_id,
+                );
+                anyhow::bail!(
+                    "Solana bridge feature not enabled. Rebuild with --features solana-bridge"
+                );
+            }
 
             Ok(())
         }
 
-        WalletCommands::Withdraw {
-            to,
-            amount,
-            rpc_url,
-            keypair,
-            program_id,
-        } => {
-            // The legacy single-key `withdraw` instruction (and its mock-proof
-            // CLI flow) was removed with the off-chain-root shielded path.
-            // Native withdrawals now settle through the v3 `transact`
-            // instruction, which requires a real Groth16 proof and a validator
-            // co-sign quorum — driven by the wallet + the node's transact
-            // ingress, not this manual CLI.
-            let _ = (&rpc_url, &keypair, &program_id);
-            anyhow::bail!(
-                "`wallet withdraw` for {} SOL to {} is no longer settled by this CLI: native \
-                 withdrawals now go through the v3 shielded `transact` flow (real proof + \
-                 validator co-sign quorum). Use the Paraloom wallet, which drives that flow.",
-                amount,
-                to
-            );
-        }
-
         WalletCommands::Balance {
             detailed,
             rpc_url,
@@ -685,6 +1775,46 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
         } => {
             println!("Fetching balance...\n");
 
+            let wallet = open_wallet()?;
+            let balances = wallet.balances()?;
+            println!("Shielded Balance:");
+            if balances.is_empty() {
+                println!("  0 SOL");
+            }
+            for (asset_id, total) in &balances {
+                println!(
+                    "  {:<18} {}",
+                    asset_label(asset_id),
+                    format_shielded_amount(*total, asset_id)
+                );
+            }
+
+            if detailed {
+                let notes = wallet.unspent_notes()?;
+                println!("\nUnspent notes ({}):", notes.len());
+                println!(
+                    "  {:<20} {:<18} {:<22} {:<10} {:<8}",
+                    "Commitment", "Asset", "Amount", "Leaf", "Origin"
+                );
+                println!("  {}", "-".repeat(80));
+                for note in &notes {
+                    let leaf = note
+                        .leaf_index
+                        .map(|i| i.to_string())
+                        .unwrap_or_else(|| "pending".to_string());
+                    println!(
+                        "  {:<20} {:<18} {:<22} {:<10} {:<8}",
+                        format!("{}...", &hex::encode(note.commitment)[..16]),
+                        asset_label(&note.asset_id),
+                        format_shielded_amount(note.amount, &note.asset_id),
+                        leaf,
+                        format!("{:?}", note.origin)
+                    );
+                }
+                println!("\n[Note] Run 'paraloom wallet sync' to pick up newly delivered notes.");
+            }
+            println!();
+
             #[cfg(feature = "solana-bridge")]
             {
                 // Get RPC URL
@@ -737,149 +1867,513 @@ async fn handle_wallet_command(command: WalletCommands) -> Result<()> {
                         wallet_balance as f64 / LAMPORTS_PER_SOL as f64
                     );
                 }
-
-                if detailed {
-                    println!("Note: Shielded balance tracking not yet implemented.");
-                    println!("This would show:");
-                    println!("  - Unspent commitments in the privacy pool");
-                    println!("  - Individual commitment values (encrypted)");
-                    println!("  - Transaction history");
-                }
             }
 
             #[cfg(not(feature = "solana-bridge"))]
             {
-                anyhow::bail!("Solana bridge feature not enabled");
+                let _ = (&rpc_url, &keypair, &program_id);
+                println!("Solana balances unavailable: solana-bridge feature not enabled");
             }
 
             Ok(())
         }
 
         WalletCommands::History { limit } => {
-            println!("Transaction History (last {} transactions):\n", limit);
-
-            // Mock transaction history
-            // In production, this would query from local storage/blockchain
-            let mock_transactions = [
-                ("Deposit", "Pending", "0x7f3a...", "2 hours ago"),
-                ("Transfer", "Confirmed", "0x2b9c...", "5 hours ago"),
-                ("Withdraw", "Confirmed", "0x1a4d...", "1 day ago"),
-                ("Deposit", "Confirmed", "0x9e8f...", "2 days ago"),
-                ("Transfer", "Confirmed", "0x4c5d...", "3 days ago"),
-            ];
+            println!("Transaction History (last {} entries):\n", limit);
+
+            let wallet = open_wallet()?;
+            let entries = wallet.history(limit)?;
+            let total = wallet.storage().history_count()?;
 
-            // Print header
             println!(
-                "{:<12} {:<12} {:<20} {:<15}",
-                "Type", "Status", "Tx Hash", "Time"
+                "{:<10} {:<18} {:<22} {:<20} {:<12}",
+                "Type", "Asset", "Amount", "Note / To", "Time"
             );
-            println!("{}", "-".repeat(65));
+            println!("{}", "-".repeat(86));
 
-            // Print transactions (limited)
-            let display_count = std::cmp::min(mock_transactions.len(), limit);
-            for (tx_type, status, tx_hash, time) in mock_transactions.iter().take(display_count) {
+            for entry in &entries {
+                let reference = match (&entry.counterparty, &entry.commitment) {
+                    (Some(to), _) => to.chars().take(20).collect::<String>(),
+                    (None, Some(c)) => format!("{}...", &hex::encode(c)[..16]),
+                    (None, None) => "-".to_string(),
+                };
                 println!(
-                    "{:<12} {:<12} {:<20} {:<15}",
-                    tx_type, status, tx_hash, time
+                    "{:<10} {:<18} {:<22} {:<20} {:<12}",
+                    entry.kind.to_string(),
+                    asset_label(&entry.asset_id),
+                    format_shielded_amount(entry.amount, &entry.asset_id),
+                    reference,
+                    entry.timestamp
                 );
+                if let Some(memo) = match &entry.commitment {
+                    Some(c) => wallet.memo(c)?,
+                    None => None,
+                } {
+                    println!("{:<10} memo: {}", "", String::from_utf8_lossy(&memo));
+                }
             }
 
-            println!("\n[Note] Transaction amounts are hidden for privacy.");
-            println!("       Full details require zero-knowledge proof verification.");
+            if entries.is_empty() {
+                println!("(no shielded activity yet — run 'paraloom wallet sync')");
+            }
 
-            if mock_transactions.len() > limit {
+            if total > entries.len() as u64 {
                 println!(
-                    "\nShowing {} of {} transactions. Use --limit {} to see more.",
-                    display_count,
-                    mock_transactions.len(),
-                    mock_transactions.len()
+                    "\nShowing {} of {} entries. Use --limit {} to see more.",
+                    entries.len(),
+                    total,
+                    total
                 );
             }
 
             Ok(())
         }
 
-        WalletCommands::NewAddress { label } => {
-            println!("Generating new shielded address...\n");
+        WalletCommands::Sync {
+            node,
+            path_server,
+            rpc_url,
+            program_id,
+            detection_bits,
+            rescan,
+        } => {
+            let node = node
+                .or_else(|| std::env::var("PARALOOM_NODE_URL").ok())
+                .context("Node URL not specified. Use --node or PARALOOM_NODE_URL")?;
+            let path_server = path_server
+                .or_else(|| std::env::var("PARALOOM_PATH_SERVER_URL").ok())
+                .unwrap_or_else(|| "http://127.0.0.1:9090".to_string());
+            let wallet = open_wallet()?;
+            let detection = detection_bits
+                .map(|bits| wallet.keys().detection_key(bits))
+                .transpose()?;
+            sync_wallet(
+                &wallet,
+                &node,
+                &path_server,
+                rpc_url,
+                program_id,
+                detection,
+                rescan,
+            )
+            .await
+        }
+
+        WalletCommands::Create { account } => {
+            let dir = std::path::Path::new(WALLET_DIR);
+            if Wallet::exists(dir) {
+                anyhow::bail!("A shielded wallet already exists at {}", WALLET_DIR);
+            }
+            let phrase = paraloom::wallet::generate_mnemonic();
+            let keys = WalletKeys::from_mnemonic(&phrase, &mnemonic_passphrase(), account)?;
+            let wallet = Wallet::create(dir, keys)?;
+
+            println!("[OK] Shielded wallet created (account {})\n", account);
+            println!("Recovery phrase — write it down and keep it offline:\n");
+            for (i, word) in phrase.split(' ').enumerate() {
+                println!("  {:>2}. {}", i + 1, word);
+            }
+            println!(
+                "\nAnyone with these words can spend this wallet. They are not stored; \
+                 `paraloom wallet restore --account {}` rebuilds the wallet from them.",
+                account
+            );
+            println!("\nAddress: {}", wallet.keys().payment_address().encode());
+            Ok(())
+        }
+
+        WalletCommands::Restore {
+            account,
+            node,
+            path_server,
+            association_server: deposit_server,
+            rpc_url,
+            program_id,
+            force,
+        } => {
+            let node = node
+                .or_else(|| std::env::var("PARALOOM_NODE_URL").ok())
+                .context("Node URL not specified. Use --node or PARALOOM_NODE_URL")?;
+            let path_server = path_server
+                .or_else(|| std::env::var("PARALOOM_PATH_SERVER_URL").ok())
+                .unwrap_or_else(|| "http://127.0.0.1:9090".to_string());
+
+            let keys =
+                WalletKeys::from_mnemonic(&read_mnemonic()?, &mnemonic_passphrase(), account)?;
+
+            let dir = std::path::Path::new(WALLET_DIR);
+            if Wallet::exists(dir) {
+                if !force {
+                    anyhow::bail!(
+                        "A shielded wallet already exists at {}. Use --force to move it aside",
+                        WALLET_DIR
+                    );
+                }
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                let backup = format!("{}.bak-{}", WALLET_DIR, now);
+                std::fs::rename(dir, &backup)
+                    .with_context(|| format!("Failed to move the old wallet to {}", backup))?;
+                println!("Moved the existing wallet to {}", backup);
+            }
 
-            // Generate random keypair (32 bytes for private key)
-            let private_key: [u8; 32] = rand::random();
+            let wallet = Wallet::create(dir, keys)?;
+            println!(
+                "[OK] Restored account {}: {}",
+                account,
+                wallet.keys().payment_address().encode()
+            );
 
-            // Generate public key (in production, this would be derived from private key)
-            // For now, we'll use a simple hash as placeholder
-            use sha2::{Digest, Sha256};
-            let mut hasher = Sha256::new();
-            hasher.update(private_key);
-            let public_key = hasher.finalize();
+            // Notes and history live only in the local database, so rebuild
+            // them from the node's feed. Deposits are not in the feed: find
+            // them among the pool's deposits by their derived blindings.
+            let deposit_server = association_server(deposit_server);
+            match fetch_deposits(&deposit_server).await {
+                Ok(deposits) => {
+                    let found = wallet.recover_deposits(&deposits)?;
+                    println!(
+                        "[OK] Scanned {} pool deposits: {} made by this wallet",
+                        deposits.len(),
+                        found
+                    );
+                }
+                Err(e) => println!(
+                    "[WARNING] Deposits not rescanned from {}: {:#}. Restore again with \
+                     --force once it is reachable, or this wallet's deposits stay missing",
+                    deposit_server, e
+                ),
+            }
+            sync_wallet(
+                &wallet,
+                &node,
+                &path_server,
+                rpc_url,
+                program_id,
+                None,
+                true,
+            )
+            .await?;
+            for (asset, amount) in wallet.balances()? {
+                println!("  Balance: {}", format_shielded_amount(amount, &asset));
+            }
+            Ok(())
+        }
 
-            // Create address (paraloom1 + hex encoded public key)
-            let address = format!("paraloom1{}", hex::encode(&public_key[..]));
+        WalletCommands::NewAddress { label, index } => {
+            println!("Generating shielded address...\n");
+
+            // The address is derived from the wallet's keys: the v3 spend
+            // pubkey senders commit to, and the X25519 key they encrypt the
+            // note to. Both come from the recovery phrase the wallet was
+            // created or restored with. A diversified address has keys of
+            // its own, unlinkable to the others, that the wallet re-derives
+            // from its index when a note paid to it arrives.
+            let wallet = open_wallet()?;
+            let address = match index {
+                Some(i) => wallet.keys().diversified_address(i).encode(),
+                None => wallet.keys().payment_address().encode(),
+            };
 
-            println!("[OK] New address created!");
+            println!("[OK] Shielded address:");
             println!("  Address: {}", address);
 
             if let Some(label_text) = &label {
                 println!("  Label: {}", label_text);
+
+                // Keep a label -> address book next to the keys.
+                let book_path = std::path::Path::new(WALLET_DIR).join("addresses.json");
+                let mut book: std::collections::BTreeMap<String, String> =
+                    match std::fs::read_to_string(&book_path) {
+                        Ok(json) => serde_json::from_str(&json)
+                            .context("Failed to parse the wallet address book")?,
+                        Err(_) => Default::default(),
+                    };
+                book.insert(label_text.clone(), address.clone());
+                std::fs::write(&book_path, serde_json::to_string_pretty(&book)?)
+                    .context("Failed to save the wallet address book")?;
             }
 
-            // Save keypair to .paraloom/keys directory
-            let keys_dir = std::path::Path::new(".paraloom/keys");
-            if !keys_dir.exists() {
-                std::fs::create_dir_all(keys_dir).context("Failed to create keys directory")?;
-                // The directory holds spending keys — restrict it to the owner.
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    std::fs::set_permissions(keys_dir, std::fs::Permissions::from_mode(0o700))
-                        .context("Failed to restrict keys directory permissions")?;
+            println!(
+                "\nWallet keys: {}",
+                std::path::Path::new(WALLET_DIR)
+                    .join(Wallet::KEYS_FILE)
+                    .display()
+            );
+            println!("\n[WARNING] Keep your wallet keys safe! Anyone with access to them can spend your funds.");
+            println!("\nYou can now receive private transfers to this address.");
+
+            Ok(())
+        }
+
+        WalletCommands::ExportViewingKey => {
+            let wallet = open_wallet()?;
+            let ivk = wallet.keys().incoming_viewing_key();
+            println!("[OK] Incoming viewing key:");
+            println!("  {}", ivk.encode());
+            println!("\nAddress: {}", ivk.payment_address().encode());
+            println!(
+                "\nThis key reveals every note paid to this wallet and verifies its \
+                 disclosures, but cannot spend. Share it only with parties allowed to see \
+                 your incoming payments."
+            );
+            Ok(())
+        }
+
+        WalletCommands::Audit {
+            viewing_key,
+            node,
+            json,
+        } => {
+            let ivk = paraloom::privacy::IncomingViewingKey::parse(&viewing_key)
+                .context("Invalid viewing key")?;
+            let node = node
+                .or_else(|| std::env::var("PARALOOM_NODE_URL").ok())
+                .context("Node URL not specified. Use --node or PARALOOM_NODE_URL")?;
+            let delivered = fetch_delivered(&node).await?;
+            let report = paraloom::wallet::audit(&ivk, &delivered);
+
+            if json {
+                let notes: Vec<_> = report
+                    .notes
+                    .iter()
+                    .map(|n| {
+                        serde_json::json!({
+                            "commitment": hex::encode(n.commitment),
+                            "amount": n.amount,
+                            "asset_id": hex::encode(n.asset_id),
+                            "mint": n.mint.map(hex::encode),
+                            "blinding": hex::encode(n.blinding),
+                            "memo": (!n.memo.is_empty())
+                                .then(|| String::from_utf8_lossy(&n.memo).into_owned()),
+                        })
+                    })
+                    .collect();
+                let received: serde_json::Map<String, serde_json::Value> = report
+                    .received()
+                    .into_iter()
+                    .map(|(asset, total)| (hex::encode(asset), total.into()))
+                    .collect();
+                let out = serde_json::json!({
+                    "address": ivk.payment_address().encode(),
+                    "scanned": report.scanned,
+                    "rejected": report.rejected,
+                    "notes": notes,
+                    "received": received,
+                });
+                println!("{}", serde_json::to_string_pretty(&out)?);
+                return Ok(());
+            }
+
+            println!("\nAudit of {}", ivk.payment_address().encode());
+            println!(
+                "Scanned {} delivered notes: {} received",
+                report.scanned,
+                report.notes.len()
+            );
+            for n in &report.notes {
+                println!(
+                    "  {}  {:<18} {}",
+                    hex::encode(n.commitment),
+                    asset_label(&n.asset_id),
+                    format_shielded_amount(n.amount, &n.asset_id)
+                );
+                if !n.memo.is_empty() {
+                    println!("    memo: {}", String::from_utf8_lossy(&n.memo));
                 }
             }
+            println!("\nTotal received:");
+            for (asset, total) in report.received() {
+                println!(
+                    "  {:<18} {}",
+                    asset_label(&asset),
+                    format_shielded_amount(total, &asset)
+                );
+            }
+            if report.rejected > 0 {
+                println!(
+                    "\n[WARNING] {} notes decrypted under this key but did not match their \
+                     commitment, and were left out",
+                    report.rejected
+                );
+            }
+            println!(
+                "\nA viewing key cannot see spends: these are receipts, not the current balance."
+            );
+            Ok(())
+        }
 
-            let filename = if let Some(label_text) = &label {
-                format!("{}.key", label_text.replace(" ", "_"))
-            } else {
-                format!("key_{}.key", hex::encode(&public_key[..4]))
+        WalletCommands::Disclose { commitment, output } => {
+            let commitment: [u8; 32] = hex::decode(commitment.trim_start_matches("0x"))
+                .context("Commitment is not hex")?
+                .try_into()
+                .map_err(|_| anyhow::anyhow!("Commitment must be 32 bytes"))?;
+            let wallet = open_wallet()?;
+            let disclosure = wallet.disclose(&commitment)?;
+            let json = serde_json::to_string_pretty(&disclosure)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("[OK] Disclosure written to {}", path.display());
+                }
+                None => println!("{}", json),
+            }
+            Ok(())
+        }
+
+        WalletCommands::VerifyDisclosure { file, viewing_key } => {
+            let json = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let disclosure: paraloom::privacy::NoteDisclosure =
+                serde_json::from_str(&json).context("Failed to decode the disclosure")?;
+            match viewing_key {
+                Some(vk) => {
+                    let ivk = paraloom::privacy::IncomingViewingKey::parse(&vk)
+                        .context("Invalid viewing key")?;
+                    disclosure.verify_for(&ivk)?;
+                }
+                None => disclosure.verify()?,
+            }
+
+            let opening = &disclosure.opening;
+            println!("[OK] Disclosure verifies");
+            println!("  Commitment: {}", hex::encode(opening.commitment));
+            println!(
+                "  Amount:     {}",
+                format_shielded_amount(opening.amount, &opening.asset_id)
+            );
+            println!("  Asset:      {}", hex::encode(opening.asset_id));
+            println!("  Owner:      {}", hex::encode(opening.owner));
+            println!("  Signer:     {}", hex::encode(disclosure.signer));
+            println!("\nCheck that the commitment was appended on chain before relying on it.");
+            Ok(())
+        }
+
+        WalletCommands::ProveAssociation {
+            commitment,
+            root,
+            server,
+            path_server,
+            proving_key,
+            output,
+        } => {
+            use paraloom::node::merkle_path::MerklePathResponse;
+
+            let parse = |what: &str, s: &str| -> Result<[u8; 32]> {
+                hex::decode(s.trim_start_matches("0x"))
+                    .with_context(|| format!("{what} is not hex"))?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("{what} must be 32 bytes"))
             };
+            let commitment = parse("Commitment", &commitment)?;
+            let root = parse("Root", &root)?;
+            let server = association_server(server);
+            let path_server = path_server
+                .or_else(|| std::env::var("PARALOOM_PATH_SERVER_URL").ok())
+                .unwrap_or_else(|| "http://127.0.0.1:9090".to_string());
+
+            let wallet = open_wallet()?;
+            let note = wallet
+                .unspent_notes()?
+                .into_iter()
+                .find(|n| n.commitment == commitment)
+                .context("No unspent note with that commitment in this wallet")?;
+
+            let http = reqwest::Client::new();
+            println!("Fetching the pool path from {} ...", path_server);
+            let input = paraloom::wallet::fetch_inputs(&http, &path_server, vec![note])
+                .await?
+                .remove(0);
+            wallet.set_leaf_index(&commitment, input.membership.leaf_index)?;
+
+            println!("Fetching the allow-list path from {} ...", server);
+            let resp = http
+                .get(format!(
+                    "{}/association/sets/{}/path/{}",
+                    server.trim_end_matches('/'),
+                    hex::encode(root),
+                    hex::encode(commitment)
+                ))
+                .send()
+                .await
+                .context("Failed to reach the association server")?;
+            if resp.status() == reqwest::StatusCode::NOT_FOUND {
+                anyhow::bail!(
+                    "the association server does not list this note under {}",
+                    hex::encode(root)
+                );
+            }
+            let listing = resp
+                .error_for_status()
+                .context("Association server returned an error")?
+                .json::<MerklePathResponse>()
+                .await
+                .context("Failed to decode the association server response")?
+                .to_membership()
+                .context("Association server returned malformed hex")?;
+            if listing.root != root {
+                anyhow::bail!("association server answered for a different root");
+            }
 
-            let key_path = keys_dir.join(&filename);
+            let pk = load_transact_proving_key(&proving_key)?;
+            println!("Generating zkSNARK proof...");
+            let proof = paraloom::privacy::prove_association(
+                &wallet.keys().prover_keys(),
+                &input.input_note(),
+                &listing,
+                &pk,
+            )?;
+            let json = serde_json::to_string_pretty(&proof)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    println!("[OK] Association proof written to {}", path.display());
+                }
+                None => println!("{}", json),
+            }
+            println!("  Nullifier: {}", hex::encode(proof.nullifier));
+            Ok(())
+        }
+    }
+}
 
-            // Save in JSON format for easy parsing
-            let key_data = serde_json::json!({
-                "address": address,
-                "private_key": hex::encode(private_key),
-                "public_key": hex::encode(&public_key[..]),
-                "label": label.unwrap_or_default(),
-                "created_at": std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-            });
-
-            // The file contains the private spending key — create it readable and
-            // writable only by the owner (0600) so it never lands world-readable
-            // at the process umask. Non-Unix platforms fall back to a plain write.
-            let key_json = serde_json::to_string_pretty(&key_data)?;
-            #[cfg(unix)]
-            {
-                use std::io::Write;
-                use std::os::unix::fs::OpenOptionsExt;
-                let mut f = std::fs::OpenOptions::new()
-                    .write(true)
-                    .create(true)
-                    .truncate(true)
-                    .mode(0o600)
-                    .open(&key_path)
-                    .context("Failed to create key file")?;
-                f.write_all(key_json.as_bytes())
-                    .context("Failed to save keypair")?;
-            }
-            #[cfg(not(unix))]
-            std::fs::write(&key_path, key_json).context("Failed to save keypair")?;
-
-            println!("\nKeypair saved to: {}", key_path.display());
-            println!("\n[WARNING] Keep your private key safe! Anyone with access to this file can spend your funds.");
-            println!("\nYou can now receive private transfers to this address.");
+async fn handle_association_command(command: AssociationCommands) -> Result<()> {
+    match command {
+        AssociationCommands::Build {
+            name,
+            exclude,
+            server,
+            output,
+        } => {
+            use paraloom::privacy::{AssociationSet, Exclusions};
+
+            let exclusions = match exclude {
+                Some(path) => Exclusions::parse(
+                    &std::fs::read_to_string(&path)
+                        .with_context(|| format!("Failed to read {}", path.display()))?,
+                )?,
+                None => Exclusions::default(),
+            };
+            let server = association_server(server);
+            let deposits = fetch_deposits(&server).await?;
 
+            let set = AssociationSet::from_deposits(&name, &deposits, &exclusions).await?;
+            let published = set.published();
+            std::fs::write(&output, serde_json::to_string_pretty(published)?)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            println!(
+                "\n[OK] Association set '{}' written to {}",
+                name,
+                output.display()
+            );
+            println!("  Root:     {}", hex::encode(published.root));
+            println!("  Listed:   {}", published.leaves.len());
+            println!("  Excluded: {}", published.excluded);
+            println!("  Through leaf {}", published.through_leaf);
+            println!("\nPublish it by adding the file to a node's privacy.association_sets.");
             Ok(())
         }
     }
@@ -1949,9 +3443,379 @@ async fn handle_validator_command(command: ValidatorCommands) -> Result<()> {
 
             Ok(())
         }
+
+        ValidatorCommands::Snapshot { command } => handle_snapshot_command(command).await,
+        ValidatorCommands::Slashing { command } => handle_slashing_command(command),
+        ValidatorCommands::Certificate { command } => handle_certificate_command(command),
+    }
+}
+
+/// Recorded evidence from a node's slashing store, read without stopping it.
+fn read_slashing_evidence(
+    data_dir: &std::path::Path,
+    wallet: Option<&str>,
+) -> Result<Vec<SlashingRecord>> {
+    let store = data_dir.join("slashing");
+    if !store.exists() {
+        anyhow::bail!("No slashing store at {}", store.display());
+    }
+    let storage = SlashingStorage::open_read_only(&store)
+        .with_context(|| format!("Failed to open {}", store.display()))?;
+    match wallet {
+        Some(wallet) => storage.for_wallet(wallet),
+        None => storage.all(),
+    }
+}
+
+fn handle_slashing_command(command: SlashingCommands) -> Result<()> {
+    match command {
+        SlashingCommands::List { data_dir, wallet } => {
+            let records = read_slashing_evidence(&data_dir, wallet.as_deref())?;
+            if records.is_empty() {
+                println!("No equivocation evidence recorded.");
+                return Ok(());
+            }
+            println!("{} equivocation(s):\n", records.len());
+            for record in &records {
+                let SlashingEvidence::Equivocation {
+                    request_id,
+                    wallet_pubkey,
+                    previous_vote,
+                    new_vote,
+                    ..
+                } = &record.evidence
+                else {
+                    continue;
+                };
+                let vote = |v: &paraloom::consensus::VerificationVote| {
+                    if v.is_valid() {
+                        "valid"
+                    } else {
+                        "invalid"
+                    }
+                };
+                println!("  Wallet:   {}", wallet_pubkey);
+                println!("  Request:  {}", request_id);
+                println!(
+                    "  Votes:    {} then {}",
+                    vote(previous_vote),
+                    vote(new_vote)
+                );
+                println!("  Recorded: {}", record.recorded_at);
+                match &record.status {
+                    SlashStatus::Pending => println!("  Status:   pending"),
+                    SlashStatus::Submitted { signature } if signature.is_empty() => {
+                        println!("  Status:   slashed (found on chain)")
+                    }
+                    SlashStatus::Submitted { signature } => {
+                        println!("  Status:   slashed in {}", signature)
+                    }
+                    SlashStatus::Rejected { reason } => {
+                        println!("  Status:   rejected ({})", reason)
+                    }
+                    SlashStatus::Submitting { signature } => {
+                        println!("  Status:   submitting ({}, unconfirmed)", signature)
+                    }
+                }
+                println!();
+            }
+            Ok(())
+        }
+
+        SlashingCommands::Export {
+            data_dir,
+            wallet,
+            out,
+        } => {
+            let records = read_slashing_evidence(&data_dir, wallet.as_deref())?;
+            std::fs::write(&out, serde_json::to_vec_pretty(&records)?)
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            println!(
+                "Exported {} equivocation(s) to {}",
+                records.len(),
+                out.display()
+            );
+            Ok(())
+        }
+    }
+}
+
+fn print_certificate(certificate: &QuorumCertificate) {
+    let stakes: std::collections::HashMap<&str, u64> = certificate
+        .stakes
+        .iter()
+        .map(|(wallet, stake)| (wallet.as_str(), *stake))
+        .collect();
+    let approving: u64 = certificate
+        .wallets()
+        .filter_map(|wallet| stakes.get(wallet))
+        .sum();
+    println!("  Request:   {}", certificate.request_id);
+    println!(
+        "  Program:   {} ({})",
+        certificate.program_id, certificate.cluster_tag
+    );
+    println!("  Issued:    {}", certificate.issued_at);
+    println!("  Epoch:     {}", certificate.epoch);
+    println!(
+        "  Stake:     {} of {} lamports approved",
+        approving, certificate.registry_total
+    );
+    println!("  Approvals: {}", certificate.votes.len());
+    for wallet in certificate.wallets() {
+        println!(
+            "    {} ({} lamports)",
+            wallet,
+            stakes.get(wallet).copied().unwrap_or(0)
+        );
+    }
+}
+
+fn handle_certificate_command(command: CertificateCommands) -> Result<()> {
+    match command {
+        CertificateCommands::Show {
+            request_id,
+            data_dir,
+            out,
+        } => {
+            let store = data_dir.join("certificates");
+            if !store.exists() {
+                anyhow::bail!("No certificate store at {}", store.display());
+            }
+            let storage = CertificateStorage::open_read_only(&store)
+                .with_context(|| format!("Failed to open {}", store.display()))?;
+            let certificate = storage
+                .get(&request_id)?
+                .with_context(|| format!("No quorum certificate for {request_id}"))?;
+            print_certificate(&certificate);
+            if let Some(out) = out {
+                std::fs::write(&out, serde_json::to_vec_pretty(&certificate)?)
+                    .with_context(|| format!("Failed to write {}", out.display()))?;
+                println!("\nWrote {}", out.display());
+            }
+            Ok(())
+        }
+
+        #[cfg(feature = "solana-bridge")]
+        CertificateCommands::Verify {
+            file,
+            program_id,
+            cluster_tag,
+        } => {
+            let bytes = std::fs::read(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let certificate: QuorumCertificate = serde_json::from_slice(&bytes)
+                .with_context(|| format!("{} is not a quorum certificate", file.display()))?;
+            let program_id = program_id.unwrap_or_else(|| certificate.program_id.clone());
+            let cluster_tag = cluster_tag.unwrap_or_else(|| certificate.cluster_tag.clone());
+            print_certificate(&certificate);
+            certificate
+                .verify(&program_id, &cluster_tag, vote_signature_is_valid)
+                .map_err(|e| anyhow::anyhow!("certificate does not verify: {e}"))?;
+            println!("\nCertificate verifies: every vote is signed, and they are a supermajority of the stake it names.");
+            Ok(())
+        }
+    }
+}
+
+/// The listener's scan cursor, as the node persists it under its data
+/// directory.
+fn bridge_cursor_path(data_dir: &std::path::Path) -> PathBuf {
+    data_dir.join("bridge_cursor")
+}
+
+async fn handle_snapshot_command(command: SnapshotCommands) -> Result<()> {
+    match command {
+        SnapshotCommands::Export {
+            data_dir,
+            out,
+            rpc_url,
+        } => {
+            // Read the cursor before the pool, as the node does: the pool
+            // then holds at least everything up to it.
+            let signature = std::fs::read_to_string(bridge_cursor_path(&data_dir))
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty());
+            let store = data_dir.join("privacy");
+            if !store.exists() {
+                anyhow::bail!("No privacy store at {}", store.display());
+            }
+            let storage = Arc::new(PrivacyStorage::open(&store).with_context(|| {
+                format!(
+                    "Failed to open {}. Stop the node first, or fetch its snapshot with \
+                     `paraloom validator snapshot fetch`",
+                    store.display()
+                )
+            })?);
+            let pool = ShieldedPool::with_storage(storage).await?;
+
+            let slot = cursor_slot(signature.as_deref(), rpc_url);
+            let snapshot = pool.snapshot(SnapshotCursor { slot, signature }).await?;
+            std::fs::write(&out, snapshot.to_bytes()?)
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            print_snapshot_summary(&snapshot);
+            println!("\nWritten to {}", out.display());
+            Ok(())
+        }
+
+        SnapshotCommands::Fetch { peer, out } => {
+            let addr: libp2p::Multiaddr = peer.parse().context("Invalid peer multiaddr")?;
+            let peer_id = addr
+                .iter()
+                .find_map(|p| match p {
+                    libp2p::multiaddr::Protocol::P2p(id) => Some(id),
+                    _ => None,
+                })
+                .context("The peer address must end in /p2p/<peer-id>")?;
+            let node = NodeId(peer_id.to_bytes());
+
+            let mut settings = Settings::development();
+            settings.network.enable_mdns = false;
+            let network = NetworkManager::new(&settings)?;
+            network.start("/ip4/0.0.0.0/tcp/0".parse()?).await?;
+            network.connect_to_bootstrap(vec![peer.clone()]).await?;
+            let deadline = std::time::Instant::now() + std::time::Duration::from_secs(30);
+            while !network.connected_peers().await.contains(&node) {
+                if std::time::Instant::now() > deadline {
+                    anyhow::bail!("Could not connect to {}", peer);
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+            }
+
+            println!("Fetching snapshot from {}...", peer_id);
+            let snapshot = fetch_snapshot(&network, node).await?;
+            // Catch a peer whose leaves do not produce the roots it claims
+            // before anything is written.
+            snapshot.verify_roots().await?;
+            std::fs::write(&out, snapshot.to_bytes()?)
+                .with_context(|| format!("Failed to write {}", out.display()))?;
+            print_snapshot_summary(&snapshot);
+            println!("\nWritten to {}", out.display());
+            println!("Check and load it with `paraloom validator snapshot import`.");
+            Ok(())
+        }
+
+        SnapshotCommands::Import {
+            file,
+            data_dir,
+            rpc_url,
+            program_id,
+        } => {
+            #[cfg(feature = "solana-bridge")]
+            {
+                let bytes = std::fs::read(&file)
+                    .with_context(|| format!("Failed to read {}", file.display()))?;
+                let mut snapshot = PoolSnapshot::from_bytes(&bytes)?;
+                print_snapshot_summary(&snapshot);
+
+                println!("\nChecking the commitments against the roots...");
+                snapshot.verify_roots().await?;
+
+                println!("Checking the roots and nullifiers against the program...");
+                let config = paraloom::bridge::BridgeConfig {
+                    solana_rpc_url: rpc_url
+                        .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+                        .unwrap_or_else(|| "https://api.devnet.solana.com".to_string()),
+                    program_id: program_id
+                        .or_else(|| std::env::var("SOLANA_PROGRAM_ID").ok())
+                        .unwrap_or_else(|| {
+                            "8gPsRSm1CAw38mfzc1bcLMUXyFN7LnS8k6CV5hPUTWrP".to_string()
+                        }),
+                    ..Default::default()
+                };
+                let rpc = Arc::new(RealBridgeRpc::new(Arc::new(
+                    RpcClient::new_with_commitment(
+                        config.solana_rpc_url.clone(),
+                        CommitmentConfig::confirmed(),
+                    ),
+                )));
+                let program = ProgramInterface::new(config, rpc)?;
+                program.verify_snapshot(&mut snapshot).await?;
+
+                println!("Writing the pool...");
+                std::fs::create_dir_all(&data_dir)?;
+                let store = data_dir.join("privacy");
+                let storage = Arc::new(
+                    PrivacyStorage::open(&store)
+                        .with_context(|| format!("Failed to open {}", store.display()))?,
+                );
+                snapshot.import(&storage).await?;
+
+                // The listener resumes from the snapshot's cursor; with none
+                // it starts from the chain tip.
+                let cursor = bridge_cursor_path(&data_dir);
+                match &snapshot.cursor.signature {
+                    Some(sig) => {
+                        let tmp = cursor.with_extension("tmp");
+                        std::fs::write(&tmp, sig)?;
+                        std::fs::rename(&tmp, &cursor)?;
+                    }
+                    None if cursor.exists() => std::fs::remove_file(&cursor)?,
+                    None => {}
+                }
+
+                println!("\nSnapshot imported into {}", store.display());
+                println!(
+                    "Start the node; it resumes indexing from slot {}.",
+                    snapshot.cursor.slot
+                );
+            }
+
+            #[cfg(not(feature = "solana-bridge"))]
+            {
+                let _ = (&file, &data_dir, &rpc_url, &program_id);
+                anyhow::bail!(
+                    "Solana bridge feature not enabled. Rebuild with --features solana-bridge"
+                );
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Slot of the transaction the persisted cursor names; 0 when there is no
+/// cursor or the RPC cannot say.
+#[cfg(feature = "solana-bridge")]
+fn cursor_slot(signature: Option<&str>, rpc_url: Option<String>) -> u64 {
+    let Some(Ok(sig)) = signature.map(solana_sdk::signature::Signature::from_str) else {
+        return 0;
+    };
+    let rpc_url = rpc_url
+        .or_else(|| std::env::var("SOLANA_RPC_URL").ok())
+        .unwrap_or_else(|| "https://api.devnet.solana.com".to_string());
+    match RpcClient::new(rpc_url).get_signature_statuses_with_history(&[sig]) {
+        Ok(response) => response
+            .value
+            .first()
+            .and_then(|status| status.as_ref())
+            .map_or(0, |status| status.slot),
+        Err(e) => {
+            println!("[WARN] Could not look up the cursor's slot: {}", e);
+            0
+        }
     }
 }
 
+#[cfg(not(feature = "solana-bridge"))]
+fn cursor_slot(_signature: Option<&str>, _rpc_url: Option<String>) -> u64 {
+    0
+}
+
+fn print_snapshot_summary(snapshot: &PoolSnapshot) {
+    println!("Snapshot:");
+    println!("  Leaves:        {}", snapshot.commitments.len());
+    println!("  Nullifiers:    {}", snapshot.nullifiers.len());
+    println!("  Assets:        {}", snapshot.supplies.len());
+    println!("  On-chain root: {}", hex::encode(snapshot.onchain_root));
+    println!("  Slot:          {}", snapshot.cursor.slot);
+    println!(
+        "  Cursor:        {}",
+        snapshot.cursor.signature.as_deref().unwrap_or("(none)")
+    );
+}
+
 async fn handle_init_command(path: PathBuf, force: bool) -> Result<()> {
     print_banner();
     println!("Initializing Paraloom in {}...\n", path.display());
@@ -49,6 +49,18 @@ pub enum BridgeError {
 
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    /// The pool's commitment tree no longer matches the program's. Every path
+    /// the node serves from here on would prove against a root the chain
+    /// never had, so the listener stops rather than indexing further.
+    #[error("Commitment tree diverged from the program: {0}")]
+    TreeDivergence(String),
+
+    /// A pool snapshot the program does not vouch for: its on-chain root is
+    /// not one the tree account still records, or it lists a nullifier the
+    /// program never saw spent.
+    #[error("Snapshot rejected: {0}")]
+    SnapshotRejected(String),
 }
 
 pub type Result<T> = std::result::Result<T, BridgeError>;
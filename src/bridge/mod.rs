@@ -8,7 +8,9 @@ pub mod solana;
 pub mod types;
 
 pub use error::{BridgeError, Result};
-pub use types::{BridgeConfig, BridgeStats, DepositEvent, SolanaAddress, WithdrawalRequest};
+pub use types::{
+    BridgeConfig, BridgeStats, DepositEvent, SettlementEvent, SolanaAddress, WithdrawalRequest,
+};
 
 /// Semver-encoded program version this L2 binary was compiled against:
 /// `major(8) | minor(8) | patch(8) | reserved(8)`. v0.4.0 → 0x00040000.
@@ -16,10 +18,16 @@ pub use types::{BridgeConfig, BridgeStats, DepositEvent, SolanaAddress, Withdraw
 /// program at startup and refuses to talk to a program at a
 /// different version (#69, audit #9). Bump in lockstep with every
 /// breaking on-chain change so a missed redeploy fails loudly
-/// instead of silently sending incompatible instructions.
-pub const EXPECTED_PROGRAM_VERSION: u32 = 0x0004_0000;
+/// instead of silently sending incompatible instructions. Mirrors the
+/// program's `PROGRAM_VERSION`; v0.6.0 adds the epoch-boundary stake to the
+/// validator and registry accounts on top of v0.5.0's quorum-certified
+/// transact layout.
+pub const EXPECTED_PROGRAM_VERSION: u32 = 0x0006_0000;
 
+use crate::consensus::epoch::StakeRecord;
+use crate::privacy::snapshot::SnapshotCursor;
 use crate::privacy::ShieldedPool;
+use crate::storage::PrivacyStorage;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -33,6 +41,9 @@ pub struct Bridge {
 
     /// Bridge statistics
     stats: Arc<RwLock<BridgeStats>>,
+
+    /// Delivered-note store the listener records settlement leaf positions in
+    delivered_store: Option<Arc<PrivacyStorage>>,
 }
 
 impl Bridge {
@@ -42,9 +53,18 @@ impl Bridge {
             solana_bridge: None,
             config,
             stats: Arc::new(RwLock::new(BridgeStats::default())),
+            delivered_store: None,
         }
     }
 
+    /// Record where settlement outputs land in `store`'s delivered notes, so
+    /// the scan feed can give recipients their leaf index. Takes effect at
+    /// [`init`](Self::init).
+    pub fn with_delivered_store(mut self, store: Arc<PrivacyStorage>) -> Self {
+        self.delivered_store = Some(store);
+        self
+    }
+
     /// Initialize bridge with privacy pool
     pub async fn init(&mut self, pool: Arc<ShieldedPool>) -> Result<()> {
         if !self.config.enabled {
@@ -53,11 +73,12 @@ impl Bridge {
         }
 
         log::info!("Initializing Solana bridge...");
-        self.solana_bridge = Some(solana::SolanaBridge::new(
-            self.config.clone(),
-            pool,
-            Arc::clone(&self.stats),
-        )?);
+        let mut solana_bridge =
+            solana::SolanaBridge::new(self.config.clone(), pool, Arc::clone(&self.stats))?;
+        if let Some(store) = &self.delivered_store {
+            solana_bridge = solana_bridge.with_delivered_store(Arc::clone(store));
+        }
+        self.solana_bridge = Some(solana_bridge);
 
         Ok(())
     }
@@ -97,6 +118,15 @@ impl Bridge {
         }
     }
 
+    /// The deposit listener's position, which a pool snapshot taken now
+    /// reflects. `None` until the Solana bridge is initialized.
+    pub async fn snapshot_cursor(&self) -> Option<SnapshotCursor> {
+        match self.solana_bridge {
+            Some(ref bridge) => Some(bridge.snapshot_cursor().await),
+            None => None,
+        }
+    }
+
     /// Latest blockhash for a node-assembled co-signed settlement tx (#260).
     pub async fn latest_blockhash(&self) -> Result<[u8; 32]> {
         if let Some(ref bridge) = self.solana_bridge {
@@ -119,10 +149,24 @@ impl Bridge {
         }
     }
 
-    /// Active validators' on-chain stakes (wallet → lamports), read for the
-    /// consensus stake reconciler so the stake-weighted quorum reflects real
-    /// at-risk capital.
-    pub async fn list_validator_stakes(&self) -> Result<Vec<(solana_sdk::pubkey::Pubkey, u64)>> {
+    /// The slot hashes of the latest finalized bank, newest first: what a
+    /// settlement leader schedule is anchored to and checked against.
+    pub async fn finalized_slot_hashes(&self) -> Result<Vec<(u64, [u8; 32])>> {
+        if let Some(ref bridge) = self.solana_bridge {
+            bridge.finalized_slot_hashes().await
+        } else {
+            Err(BridgeError::ConfigError(
+                "Solana bridge not initialized".to_string(),
+            ))
+        }
+    }
+
+    /// Validators' on-chain active stake records (wallet → lamports), read for
+    /// the consensus stake reconciler so the stake-weighted quorum reflects
+    /// real at-risk capital as of each epoch boundary.
+    pub async fn list_validator_stakes(
+        &self,
+    ) -> Result<Vec<(solana_sdk::pubkey::Pubkey, StakeRecord)>> {
         if let Some(ref bridge) = self.solana_bridge {
             bridge.list_validator_stakes().await
         } else {
@@ -134,8 +178,8 @@ impl Bridge {
 
     /// The on-chain `ValidatorRegistry.total_active_stake` — the denominator the
     /// stake-weighted quorum mirrors off-chain (read from the registry account,
-    /// not summed from `list_validator_stakes`).
-    pub async fn registry_total_active_stake(&self) -> Result<u64> {
+    /// not summed from `list_validator_stakes`), with its epoch-boundary record.
+    pub async fn registry_total_active_stake(&self) -> Result<StakeRecord> {
         if let Some(ref bridge) = self.solana_bridge {
             bridge.registry_total_active_stake().await
         } else {
@@ -145,6 +189,41 @@ impl Bridge {
         }
     }
 
+    /// Sign `slash_validator` for `validator` and `evidence_id` under the
+    /// registry `authority` — the on-chain end of the equivocation slashing
+    /// pipeline. Broadcast it with
+    /// [`submit_signed_transaction`](Self::submit_signed_transaction).
+    pub async fn slash_validator_transaction(
+        &self,
+        authority: &solana_sdk::signature::Keypair,
+        validator: &solana_sdk::pubkey::Pubkey,
+        slash_percentage: u8,
+        evidence_id: &[u8; 32],
+    ) -> Result<solana_sdk::transaction::Transaction> {
+        if let Some(ref bridge) = self.solana_bridge {
+            bridge
+                .slash_validator_transaction(authority, validator, slash_percentage, evidence_id)
+                .await
+        } else {
+            Err(BridgeError::ConfigError(
+                "Solana bridge not initialized".to_string(),
+            ))
+        }
+    }
+
+    /// Whether `validator`'s slash record for `evidence_id` exists on chain.
+    /// `false` without a Solana bridge.
+    pub async fn is_evidence_slashed(
+        &self,
+        validator: &solana_sdk::pubkey::Pubkey,
+        evidence_id: &[u8; 32],
+    ) -> bool {
+        match self.solana_bridge {
+            Some(ref bridge) => bridge.is_evidence_slashed(validator, evidence_id).await,
+            None => false,
+        }
+    }
+
     /// Submit a pre-assembled, co-signed settlement transaction (#260) — the
     /// multi-sig withdrawal the node gathered from the approving validators.
     pub async fn submit_signed_transaction(
@@ -18,6 +18,7 @@ use solana_sdk::{
 };
 use std::collections::HashMap;
 use std::future::Future;
+use std::str::FromStr;
 
 /// Collect co-signatures for `message` until `threshold` distinct, verified
 /// signatures are held (#260).
@@ -124,6 +125,15 @@ pub(crate) fn signature_is_valid(wallet: &Pubkey, sig_bytes: &[u8], message_byte
     }
 }
 
+/// `signature_is_valid` for a base58 wallet, as the consensus layer names
+/// voters: whether `sig_bytes` is `wallet`'s signature over `message_bytes`.
+/// False if the wallet does not parse.
+pub fn vote_signature_is_valid(wallet: &str, sig_bytes: &[u8], message_bytes: &[u8]) -> bool {
+    Pubkey::from_str(wallet)
+        .map(|pk| signature_is_valid(&pk, sig_bytes, message_bytes))
+        .unwrap_or(false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
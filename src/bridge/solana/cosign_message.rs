@@ -128,15 +128,16 @@ pub const MAX_PROOF_BYTES: usize = 256;
 /// Upper bound on co-signers in a single settlement transaction.
 ///
 /// Each quorum validator contributes two accounts to the instruction
-/// (`append_quorum_accounts`: the signing wallet and its registry PDA). A Solana
-/// transaction message indexes its accounts with a `u8`, so more than 255
-/// distinct accounts makes `Message::new_with_blockhash` panic while compiling.
-/// A co-sign request arrives over the network with an attacker-controllable
-/// `quorum_validators`, so an oversized set must be rejected as a typed error
-/// rather than crashing the node. The cap leaves ample headroom under the 255
-/// account limit (and well under the ~1232-byte transaction-size limit, which
-/// binds far sooner) while never constraining a realistic BFT quorum.
-pub const MAX_QUORUM_COSIGNERS: usize = 100;
+/// (`append_quorum_accounts`: the signing wallet and its registry PDA), and a
+/// third on the SPL path (its fee token account). A Solana transaction message
+/// indexes its accounts with a `u8`, so more than 255 distinct accounts makes
+/// `Message::new_with_blockhash` panic while compiling. A co-sign request
+/// arrives over the network with an attacker-controllable `quorum_validators`,
+/// so an oversized set must be rejected as a typed error rather than crashing
+/// the node. Three accounts each plus the base accounts stays under the 255
+/// account limit (and the ~1232-byte transaction-size limit binds far sooner)
+/// while never constraining a realistic BFT quorum.
+pub const MAX_QUORUM_COSIGNERS: usize = 64;
 
 /// Rebuild the exact settlement transaction [`Message`] every co-signer signs.
 ///
@@ -198,8 +199,9 @@ pub fn build_settlement_message(payload: &CoSignPayload) -> Result<Message> {
         } => {
             let mint_pk = Pubkey::new_from_array(*mint);
             let recipient_ta = Pubkey::new_from_array(*recipient_token_account);
-            // The settling validator's fee lands in its own ATA for the mint,
-            // derived deterministically so every co-signer builds the same ix.
+            // The settling validator's fee share lands in its own ATA for the
+            // mint, derived deterministically so every co-signer builds the same
+            // ix; the builder appends each co-signer's ATA the same way.
             let fee_ta = super::instructions::derive_associated_token_address(
                 &authority,
                 &mint_pk,
@@ -223,7 +225,8 @@ pub fn build_settlement_message(payload: &CoSignPayload) -> Result<Message> {
     };
 
     // Both settlement paths verify a Groth16 proof on-chain and need the raised
-    // compute-unit ceiling prepended (SPL additionally does two token CPIs);
+    // compute-unit ceiling prepended (SPL additionally does a token CPI for the
+    // payout and one per paid fee share);
     // every co-signer builds the same message, so the extra instruction stays
     // part of what they all sign over.
     let instructions = vec![
@@ -326,4 +329,25 @@ mod tests {
         payload.quorum_validators = vec![[7u8; 32]; MAX_QUORUM_COSIGNERS];
         build_settlement_message(&payload).expect("a quorum at the cap still builds");
     }
+
+    #[test]
+    fn a_full_spl_quorum_of_distinct_validators_fits_one_message() {
+        // The SPL path adds a fee token account per co-signer, so the cap must
+        // leave room for three distinct accounts each.
+        let mut payload = sample_transact_payload();
+        payload.params = SettlementParams::TransactSpl {
+            recipient_token_account: [6u8; 32],
+            mint: [13u8; 32],
+            nullifiers: [[8u8; 32], [9u8; 32]],
+            output_commitments: [[10u8; 32], [11u8; 32]],
+            root: [12u8; 32],
+            ext_amount: -500,
+            proof: vec![0u8; 256],
+        };
+        payload.quorum_validators = (0..MAX_QUORUM_COSIGNERS)
+            .map(|_| Pubkey::new_unique().to_bytes())
+            .collect();
+        let message = build_settlement_message(&payload).expect("a full SPL quorum builds");
+        assert!(message.account_keys.len() <= 255);
+    }
 }
@@ -1,13 +1,19 @@
-//! Decoder for Paraloom deposit instructions found in Solana transactions.
+//! Decoder for Paraloom tree-appending instructions found in Solana
+//! transactions.
 //!
 //! The event listener pulls confirmed transactions from the Solana RPC,
-//! and for each one this module pulls out any instructions that match
-//! the Paraloom deposit discriminator and renders them as the bridge's
-//! [`DepositEvent`] type. Keeping the decoder pure and free of RPC I/O
-//! makes it directly unit-testable against synthetic instruction data.
-
-use crate::bridge::solana::instructions::{discriminators, DepositInstructionData};
-use crate::bridge::DepositEvent;
+//! and for each one this module pulls out any instructions that append to
+//! the on-chain commitment tree — deposits and `transact` / `transact_spl`
+//! settlements — and renders them as the bridge's [`DepositEvent`] and
+//! [`SettlementEvent`] types, in the order the program executed them.
+//! Keeping the decoder pure and free of RPC I/O makes it directly
+//! unit-testable against synthetic instruction data.
+
+use crate::bridge::solana::instructions::{
+    discriminators, DepositInstructionData, TransactInstructionData,
+};
+use crate::bridge::{DepositEvent, SettlementEvent};
+use base64::Engine as _;
 use borsh::BorshDeserialize;
 use solana_sdk::pubkey::Pubkey;
 use solana_transaction_status::{
@@ -25,24 +31,36 @@ const DEPOSITOR_ACCOUNT_INDEX: usize = 2;
 /// listener. JSON encoding produces a `UiMessage::Raw` variant for
 /// unparsed programs (paraloom is unparsed), which exposes raw
 /// base58-encoded instruction data — exactly what
-/// [`extract_deposit_events`] consumes.
+/// [`extract_program_events`] consumes.
 pub const LISTENER_TX_ENCODING: UiTransactionEncoding = UiTransactionEncoding::Json;
 
-/// Pull every Paraloom deposit instruction out of a confirmed Solana
-/// transaction and turn it into a [`DepositEvent`].
+/// One instruction that appended to the on-chain commitment tree.
+#[derive(Debug, Clone)]
+pub enum ProgramEvent {
+    Deposit(DepositEvent),
+    Settlement(SettlementEvent),
+}
+
+/// Pull every tree-appending Paraloom instruction out of a confirmed Solana
+/// transaction, in execution order: top-level instructions first, then any
+/// CPI into the program from the inner-instruction sets.
+///
+/// The order is the point. Both deposits and settlements append leaves, and a
+/// pool that replays them in any other order holds the same leaves at
+/// different indices — a different tree with a different root.
 ///
-/// Returns an empty vector if the transaction does not target the
-/// Paraloom program, the encoding is not the expected raw JSON form,
-/// or no instruction matches the deposit discriminator. Address-table-
-/// lookup transactions are skipped with a warning — none of the deposit
-/// flows in v0.3 are expected to use LUTs.
-pub fn extract_deposit_events(
+/// Returns an empty vector if the transaction failed on chain, does not
+/// target the Paraloom program, or the encoding is not the expected raw JSON
+/// form. Address-table-lookup transactions are skipped with a warning — none
+/// of the program's flows are expected to use LUTs.
+pub fn extract_program_events(
     signature: &str,
     confirmed: &EncodedConfirmedTransactionWithStatusMeta,
     program_id: &Pubkey,
-) -> Vec<DepositEvent> {
+) -> Vec<ProgramEvent> {
     // Skip on-chain failures: a transaction whose execution errored out
-    // didn't actually transfer funds, so no deposit should be emitted.
+    // didn't actually transfer funds or append a leaf, so nothing should be
+    // emitted.
     if let Some(meta) = &confirmed.transaction.meta {
         if meta.err.is_some() {
             log::debug!(
@@ -91,42 +109,66 @@ pub fn extract_deposit_events(
         }
     };
 
-    let mut events = Vec::new();
-
     // Top-level instructions are already compiled — JSON encoding for
     // an unparsed program drops the `UiInstruction` wrapper.
-    for compiled in &raw.instructions {
-        if let Some(decoded) = decode_compiled_deposit(compiled, &account_keys, program_id) {
-            events.push(build_event(signature, confirmed, decoded));
-        }
-    }
+    let mut compiled: Vec<&solana_transaction_status::UiCompiledInstruction> =
+        raw.instructions.iter().collect();
 
     // Inner instructions (CPI from another program into ours) come back
     // through the meta field as `UiInstruction`, which needs the match
     // because parsed instructions can also appear there.
+    let mut logs: &[String] = &[];
     if let Some(meta) = &confirmed.transaction.meta {
         if let solana_transaction_status::option_serializer::OptionSerializer::Some(inner) =
             &meta.inner_instructions
         {
             for inner_set in inner {
                 for instruction in &inner_set.instructions {
-                    if let UiInstruction::Compiled(compiled) = instruction {
-                        if let Some(decoded) =
-                            decode_compiled_deposit(compiled, &account_keys, program_id)
-                        {
-                            events.push(build_event(signature, confirmed, decoded));
-                        }
+                    if let UiInstruction::Compiled(c) = instruction {
+                        compiled.push(c);
                     }
                 }
             }
         }
+        if let solana_transaction_status::option_serializer::OptionSerializer::Some(l) =
+            &meta.log_messages
+        {
+            logs = l;
+        }
+    }
+
+    let emitted = transact_events(logs);
+    let mut events = Vec::new();
+    for ix in compiled {
+        if let Some(decoded) = decode_compiled_deposit(ix, &account_keys, program_id) {
+            events.push(ProgramEvent::Deposit(build_event(
+                signature, confirmed, decoded,
+            )));
+        } else if let Some(decoded) = decode_compiled_settlement(ix, &account_keys, program_id) {
+            // The instruction carries everything but the root the program
+            // published and where it appended; those only exist in the
+            // `TransactEvent` it emitted, which is found by the outputs.
+            let published = emitted.iter().find(|(e, _)| {
+                [e.out_commitment0, e.out_commitment1] == decoded.data.output_commitments
+            });
+            events.push(ProgramEvent::Settlement(SettlementEvent {
+                signature: signature.to_string(),
+                nullifiers: decoded.data.nullifiers,
+                output_commitments: decoded.data.output_commitments,
+                ext_amount: decoded.data.ext_amount,
+                mint: decoded.mint.map(|m| m.to_bytes()),
+                new_root: published.map(|(e, _)| e.new_root),
+                leaf_index: published.and_then(|(_, index)| *index),
+                block: confirmed.slot,
+            }));
+        }
     }
 
     events
 }
 
 /// Decoded result of a single deposit instruction. Kept private so the
-/// public surface is just `extract_deposit_events`.
+/// public surface is just the extracted events.
 struct DecodedDeposit {
     data: DepositInstructionData,
     depositor: Pubkey,
@@ -222,6 +264,97 @@ fn decode_compiled_deposit(
     })
 }
 
+/// `transact_spl` account layout: bridge_state(0), merkle_tree(1), mint(2), …
+const TRANSACT_SPL_MINT_ACCOUNT_INDEX: usize = 2;
+
+/// Decoded result of a single `transact` / `transact_spl` instruction.
+struct DecodedSettlement {
+    data: TransactInstructionData,
+    /// The SPL mint for `transact_spl`; `None` for native `transact`.
+    mint: Option<Pubkey>,
+}
+
+/// Try to interpret a single compiled instruction as a Paraloom settlement.
+/// Returns `None` under the same conditions as [`decode_compiled_deposit`].
+fn decode_compiled_settlement(
+    compiled: &solana_transaction_status::UiCompiledInstruction,
+    account_keys: &[Pubkey],
+    program_id: &Pubkey,
+) -> Option<DecodedSettlement> {
+    let program_index = compiled.program_id_index as usize;
+    if account_keys.get(program_index)? != program_id {
+        return None;
+    }
+
+    let raw_data = bs58::decode(&compiled.data).into_vec().ok()?;
+    if raw_data.len() < discriminators::TRANSACT.len() {
+        return None;
+    }
+    let mint = if raw_data[..8] == discriminators::TRANSACT {
+        None
+    } else if raw_data[..8] == discriminators::TRANSACT_SPL {
+        let mint_index = *compiled.accounts.get(TRANSACT_SPL_MINT_ACCOUNT_INDEX)? as usize;
+        Some(*account_keys.get(mint_index)?)
+    } else {
+        return None;
+    };
+
+    let data = TransactInstructionData::try_from_slice(&raw_data[8..]).ok()?;
+    Some(DecodedSettlement { data, mint })
+}
+
+/// `sha256("event:TransactEvent")[..8]`, the prefix Anchor's `emit!` puts in
+/// front of the borsh-encoded event.
+const TRANSACT_EVENT_DISCRIMINATOR: [u8; 8] = [89, 245, 87, 250, 222, 30, 135, 142];
+
+/// Log prefix the runtime gives `sol_log_data`, which `emit!` writes through.
+const PROGRAM_DATA_LOG: &str = "Program data: ";
+
+/// The program's `TransactEvent`, field for field. `recipient` is a `Pubkey`
+/// on chain, which borsh writes as its 32 bytes.
+#[derive(BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+struct TransactEventData {
+    nullifier0: [u8; 32],
+    nullifier1: [u8; 32],
+    out_commitment0: [u8; 32],
+    out_commitment1: [u8; 32],
+    new_root: [u8; 32],
+    ext_amount: i64,
+    fee: u64,
+    recipient: [u8; 32],
+    timestamp: i64,
+    settlement_id: u64,
+}
+
+/// Every `TransactEvent` in a transaction's log messages, in order. Lines that
+/// are not `Program data:` or carry some other event are skipped.
+fn transact_events(logs: &[String]) -> Vec<(TransactEventData, Option<u64>)> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(PROGRAM_DATA_LOG))
+        .filter_map(|b64| {
+            base64::engine::general_purpose::STANDARD
+                .decode(b64.trim())
+                .ok()
+        })
+        .filter(|bytes| bytes.starts_with(&TRANSACT_EVENT_DISCRIMINATOR))
+        .filter_map(|bytes| decode_transact_event(&bytes[8..]))
+        .collect()
+}
+
+/// One `TransactEvent` body. The program appends `leaf_index` after the
+/// fields above; an event without it is from a program that predates it and
+/// decodes with `leaf_index: None`. Any other trailing length is not this
+/// event.
+fn decode_transact_event(mut body: &[u8]) -> Option<(TransactEventData, Option<u64>)> {
+    let event = TransactEventData::deserialize(&mut body).ok()?;
+    let leaf_index = match body.len() {
+        0 => None,
+        8 => Some(u64::from_le_bytes(body.try_into().ok()?)),
+        _ => return None,
+    };
+    Some((event, leaf_index))
+}
+
 fn build_event(
     signature: &str,
     confirmed: &EncodedConfirmedTransactionWithStatusMeta,
@@ -245,6 +378,21 @@ fn build_event(
     }
 }
 
+/// Log line `deposit_note` writes after appending its leaf, as it appears in
+/// a transaction's log messages (`msg!` output is prefixed by the runtime).
+const DEPOSIT_NOTE_LEAF_LOG: &str = "Program log: Deposit note appended at leaf ";
+
+/// The leaf indices `deposit_note` reported in a transaction's log messages,
+/// in order. The program does not return the index any other way a client can
+/// read back without an event subscription, and reading `next_index` after
+/// confirmation races every other depositor.
+pub fn deposit_note_leaf_indices(logs: &[String]) -> Vec<u64> {
+    logs.iter()
+        .filter_map(|line| line.strip_prefix(DEPOSIT_NOTE_LEAF_LOG))
+        .filter_map(|rest| rest.trim().parse().ok())
+        .collect()
+}
+
 fn parse_account_keys(keys: &[String]) -> std::result::Result<Vec<Pubkey>, String> {
     keys.iter()
         .map(|k| k.parse::<Pubkey>().map_err(|_| k.clone()))
@@ -282,6 +430,103 @@ mod tests {
         }
     }
 
+    /// A `transact_spl` carries its outputs and nullifiers in the instruction,
+    /// its mint in the account list, and the root the program published only
+    /// in the `TransactEvent` it emitted — all three have to come through.
+    #[test]
+    fn decodes_a_transact_spl_settlement_with_its_published_root() {
+        use crate::bridge::solana::test_support::synth_settlement_tx;
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let tx = synth_settlement_tx(
+            solana_sdk::signature::Signature::new_unique(),
+            42,
+            &program_id,
+            Some(&mint),
+            [[1u8; 32], [2u8; 32]],
+            [[3u8; 32], [4u8; 32]],
+            Some(([5u8; 32], 9)),
+        );
+
+        let events = extract_program_events("sig", &tx, &program_id);
+        assert_eq!(events.len(), 1);
+        let ProgramEvent::Settlement(settlement) = &events[0] else {
+            panic!("expected a settlement, got {:?}", events[0]);
+        };
+        assert_eq!(settlement.nullifiers, [[1u8; 32], [2u8; 32]]);
+        assert_eq!(settlement.output_commitments, [[3u8; 32], [4u8; 32]]);
+        assert_eq!(settlement.mint, Some(mint.to_bytes()));
+        assert_eq!(settlement.new_root, Some([5u8; 32]));
+        assert_eq!(settlement.leaf_index, Some(9));
+        assert_eq!(settlement.block, 42);
+    }
+
+    #[test]
+    fn a_settlement_without_logs_decodes_without_a_root() {
+        use crate::bridge::solana::test_support::synth_settlement_tx;
+        let program_id = Pubkey::new_unique();
+        let tx = synth_settlement_tx(
+            solana_sdk::signature::Signature::new_unique(),
+            1,
+            &program_id,
+            None,
+            [[1u8; 32], [2u8; 32]],
+            [[3u8; 32], [4u8; 32]],
+            None,
+        );
+        let events = extract_program_events("sig", &tx, &program_id);
+        let [ProgramEvent::Settlement(settlement)] = events.as_slice() else {
+            panic!("expected one settlement, got {events:?}");
+        };
+        assert_eq!(settlement.mint, None);
+        assert_eq!(settlement.new_root, None);
+        assert_eq!(settlement.leaf_index, None);
+
+        // Another program's transact-shaped instruction is not ours.
+        assert!(extract_program_events("sig", &tx, &Pubkey::new_unique()).is_empty());
+    }
+
+    /// An event from a program that predates `leaf_index` still yields its
+    /// root; one with some other tail is not a `TransactEvent`.
+    #[test]
+    fn transact_event_leaf_index_is_an_optional_tail() {
+        let body = vec![0u8; 5 * 32 + 8 + 8 + 32 + 8 + 8];
+        let (_, leaf_index) = decode_transact_event(&body).unwrap();
+        assert_eq!(leaf_index, None);
+
+        let mut with_index = body.clone();
+        with_index.extend_from_slice(&4097u64.to_le_bytes());
+        let (_, leaf_index) = decode_transact_event(&with_index).unwrap();
+        assert_eq!(leaf_index, Some(4097));
+
+        let mut other = body;
+        other.extend_from_slice(&[0u8; 4]);
+        assert!(decode_transact_event(&other).is_none());
+    }
+
+    #[test]
+    fn transact_events_skip_other_program_data() {
+        use base64::Engine as _;
+        let other_event = base64::engine::general_purpose::STANDARD.encode([0u8; 64]);
+        let logs = vec![
+            format!("Program data: {other_event}"),
+            "Program data: not base64!".to_string(),
+            "Program log: Transact settled".to_string(),
+        ];
+        assert!(transact_events(&logs).is_empty());
+    }
+
+    #[test]
+    fn reads_deposit_note_leaf_index_from_logs() {
+        let logs = vec![
+            "Program 11111111111111111111111111111111 invoke [2]".to_string(),
+            "Program log: Deposit note appended at leaf 4097".to_string(),
+            "Program log: Deposit note appended at leaf not-a-number".to_string(),
+        ];
+        assert_eq!(deposit_note_leaf_indices(&logs), vec![4097]);
+        assert!(deposit_note_leaf_indices(&[]).is_empty());
+    }
+
     #[test]
     fn decodes_a_well_formed_deposit() {
         let program_id = Pubkey::new_unique();
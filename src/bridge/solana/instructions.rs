@@ -52,6 +52,10 @@ pub mod discriminators {
     /// vault's current balance may reach via deposits). Cold-authority signed,
     /// like `pause`; opens/raises/lowers the pool's loss ceiling.
     pub const SET_DEPOSIT_CAP: [u8; 8] = [30, 43, 219, 90, 254, 4, 85, 236];
+    /// `sha256("global:set_leader_bonus")[..8]`. Sets the share of each
+    /// settlement fee the settling validator takes before the stake-weighted
+    /// split. Cold-authority signed, like `set_deposit_cap`.
+    pub const SET_LEADER_BONUS: [u8; 8] = [177, 100, 53, 34, 168, 131, 99, 128];
     /// `sha256("global:initialize_validator_registry")[..8]`.
     pub const INITIALIZE_VALIDATOR_REGISTRY: [u8; 8] = [168, 49, 128, 236, 25, 7, 168, 85];
     /// `sha256("global:register_validator")[..8]`.
@@ -105,6 +109,9 @@ pub mod discriminators {
     /// instruction that deserializes `BridgeState` (transact/deposit_note/pause/
     /// set_deposit_cap) aborts on the short account until it is grown.
     pub const MIGRATE_BRIDGE_STATE: [u8; 8] = [196, 193, 143, 108, 71, 132, 75, 181];
+    /// `sha256("global:slash_validator")[..8]`. Registry-authority-gated
+    /// forfeit of a percentage of a validator's SOL and token stake.
+    pub const SLASH_VALIDATOR: [u8; 8] = [238, 57, 244, 40, 132, 82, 78, 5];
 }
 
 /// Instruction data for `transact` (circuit v3, #350).
@@ -306,6 +313,82 @@ pub fn create_deactivate_validator_instruction(
     })
 }
 
+/// Derive the slashed-funds vault PDA (`[b"slashed_funds_vault"]`), where
+/// `slash_validator` sends the forfeited SOL.
+pub fn derive_slashed_funds_vault(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"slashed_funds_vault"], program_id)
+}
+
+/// Derive the slash-record PDA
+/// (`[b"slash_record", validator_wallet, evidence_id]`). `slash_validator`
+/// creates it, so its existence means that evidence has already been slashed.
+pub fn derive_slash_record(
+    program_id: &Pubkey,
+    validator_wallet: &Pubkey,
+    evidence_id: &[u8; 32],
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"slash_record",
+            validator_wallet.as_ref(),
+            evidence_id.as_ref(),
+        ],
+        program_id,
+    )
+}
+
+/// Create a `slash_validator` instruction. Admin-only (the registry
+/// authority). Forfeits `slash_percentage` (1-100) of `validator_wallet`'s
+/// at-risk stake: the SOL half to the slashed-funds vault, the token half
+/// burned from the stake vault, so `stake_mint` and `token_program` must be
+/// the registry's. The authority pays for the `evidence_id` slash record,
+/// which makes a second slash for the same evidence fail. Account order
+/// matches the on-chain `SlashValidator` context: validator_account,
+/// slashed_funds_vault, validator_registry, stake_mint, stake_token_vault,
+/// stake_vault_authority, token_program, slash_record, authority,
+/// system_program.
+pub fn create_slash_validator_instruction(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    validator_wallet: &Pubkey,
+    stake_mint: &Pubkey,
+    token_program: &Pubkey,
+    slash_percentage: u8,
+    evidence_id: &[u8; 32],
+) -> Result<Instruction> {
+    let (validator_pda, _) = derive_validator_account(program_id, validator_wallet);
+    let (slashed_funds_vault, _) = derive_slashed_funds_vault(program_id);
+    let (registry_pda, _) = derive_validator_registry(program_id);
+    let (stake_token_vault, _) = derive_stake_token_vault(program_id);
+    let (stake_vault_authority, _) =
+        Pubkey::find_program_address(&[b"stake_vault_authority"], program_id);
+
+    let (slash_record, _) = derive_slash_record(program_id, validator_wallet, evidence_id);
+
+    // `slash_validator(validator: Pubkey, slash_percentage: u8, evidence_id: [u8; 32])`.
+    let mut data = discriminators::SLASH_VALIDATOR.to_vec();
+    data.extend_from_slice(validator_wallet.as_ref());
+    data.push(slash_percentage);
+    data.extend_from_slice(evidence_id);
+
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(validator_pda, false),
+            AccountMeta::new(slashed_funds_vault, false),
+            AccountMeta::new(registry_pda, false),
+            AccountMeta::new(*stake_mint, false),
+            AccountMeta::new(stake_token_vault, false),
+            AccountMeta::new_readonly(stake_vault_authority, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new(slash_record, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
+        ],
+        data,
+    })
+}
+
 /// Create a `register_validator` instruction. Permissionless: the validator
 /// signs for itself and stakes `stake_amount` lamports (>= MIN_VALIDATOR_STAKE).
 /// Derive the shared stake-token vault PDA (`[b"stake_token_vault"]`), the
@@ -492,6 +575,8 @@ pub fn create_migrate_validator_account_instruction(
 /// Append the quorum co-signers as remaining accounts (#260): each validator's
 /// wallet (a signer) followed by its `ValidatorAccount` PDA. The program
 /// verifies on-chain that a supermajority of registered validators signed.
+/// The PDA is writable because the program credits each counted co-signer's
+/// share of the settlement fee to it.
 fn append_quorum_accounts(
     program_id: &Pubkey,
     quorum_validators: &[Pubkey],
@@ -500,7 +585,7 @@ fn append_quorum_accounts(
     for v in quorum_validators {
         let (vpda, _) = derive_validator_account(program_id, v);
         accounts.push(AccountMeta::new_readonly(*v, true));
-        accounts.push(AccountMeta::new_readonly(vpda, false));
+        accounts.push(AccountMeta::new(vpda, false));
     }
 }
 
@@ -554,7 +639,7 @@ pub fn create_transact_instruction(
         AccountMeta::new(nullifier_pda_0, false), // Nullifier account 0 (will be created)
         AccountMeta::new(nullifier_pda_1, false), // Nullifier account 1 (will be created)
         AccountMeta::new(recipient_pubkey, false),
-        AccountMeta::new(validator_pda, false), // Settling validator (fee credited here)
+        AccountMeta::new(validator_pda, false), // Settling validator (leader's fee share credited here)
         AccountMeta::new_readonly(validator_registry_pda, false),
         AccountMeta::new(*authority, true),
         AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
@@ -571,13 +656,15 @@ pub fn create_transact_instruction(
 /// Create the `transact_spl` instruction (#779): the SPL analogue of
 /// [`create_transact_instruction`]. Pays a token withdraw out of `mint`'s
 /// per-asset vault (PDA-signed) instead of lamports out of `bridge_vault`, and
-/// pays the settling validator's fee in the same token to `fee_token_account`.
+/// pays the fee in the same token: the settling validator's share to
+/// `fee_token_account`, each co-signer's to its associated token account.
 ///
 /// Account order must match the on-chain `TransactSpl` accounts struct exactly:
 /// bridge_state, merkle_tree, mint, asset_vault, asset_vault_authority,
 /// recipient_token_account, fee_token_account, nullifier_0, nullifier_1,
 /// validator_account, validator_registry, authority, token_program,
-/// system_program, then the quorum `(wallet, PDA)` pairs.
+/// system_program, then the quorum `(wallet, PDA)` pairs, then one fee token
+/// account per pair in the same order.
 #[allow(clippy::too_many_arguments)]
 pub fn create_transact_spl_instruction(
     program_id: &Pubkey,
@@ -634,6 +721,12 @@ pub fn create_transact_spl_instruction(
         AccountMeta::new_readonly(SYSTEM_PROGRAM_ID, false),
     ];
     append_quorum_accounts(program_id, quorum_validators, &mut accounts);
+    for v in quorum_validators {
+        accounts.push(AccountMeta::new(
+            derive_associated_token_address(v, mint, token_program),
+            false,
+        ));
+    }
 
     Ok(Instruction {
         program_id: *program_id,
@@ -807,6 +900,35 @@ pub fn create_set_deposit_cap_instruction(
     }
 }
 
+/// Create a `set_leader_bonus` instruction (admin: set the leader's fee bonus).
+///
+/// Sets `bridge_state.leader_bonus_bps = bonus_bps`, the share of every
+/// settlement fee the settling validator takes before the rest is split by
+/// stake with its co-signers. The program rejects anything above
+/// `MAX_LEADER_BONUS_BPS`. Cold-authority signed, same accounts as
+/// [`create_set_deposit_cap_instruction`].
+pub fn create_set_leader_bonus_instruction(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    bonus_bps: u16,
+) -> Instruction {
+    let (bridge_state_pda, _) = derive_bridge_state(program_id);
+    let (registry_pda, _) = derive_validator_registry(program_id);
+
+    let mut instruction_data = discriminators::SET_LEADER_BONUS.to_vec();
+    instruction_data.extend_from_slice(&bonus_bps.to_le_bytes());
+
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(bridge_state_pda, false),
+            AccountMeta::new_readonly(registry_pda, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: instruction_data,
+    }
+}
+
 /// Derive bridge vault PDA
 pub fn derive_bridge_vault(program_id: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[b"bridge_vault"], program_id)
@@ -978,12 +1100,14 @@ mod tests {
         );
         assert_eq!(ix.accounts[8].pubkey, authority);
         assert!(ix.accounts[8].is_signer);
-        // Quorum pairs: each wallet signs, its PDA does not.
+        // Quorum pairs: each wallet signs, its PDA does not and is writable so
+        // the co-signer's fee share can be credited to it.
         assert_eq!(ix.accounts[10].pubkey, authority);
         assert!(ix.accounts[10].is_signer);
         assert_eq!(ix.accounts[12].pubkey, cosigner);
         assert!(ix.accounts[12].is_signer);
         assert!(!ix.accounts[13].is_signer);
+        assert!(ix.accounts[13].is_writable);
 
         assert_eq!(&ix.data[..8], &discriminators::TRANSACT);
     }
@@ -1014,8 +1138,9 @@ mod tests {
         .expect("build transact_spl instruction");
 
         assert_eq!(ix.program_id, program_id);
-        // 14 base accounts (see the account-order doc) + 2 quorum (wallet, PDA) pairs.
-        assert_eq!(ix.accounts.len(), 14 + 4);
+        // 14 base accounts (see the account-order doc) + 2 quorum (wallet, PDA)
+        // pairs + one fee token account per pair.
+        assert_eq!(ix.accounts.len(), 14 + 4 + 2);
         assert_eq!(ix.accounts[0].pubkey, derive_bridge_state(&program_id).0);
         assert_eq!(ix.accounts[1].pubkey, derive_merkle_tree(&program_id).0);
         assert_eq!(ix.accounts[2].pubkey, mint);
@@ -1051,6 +1176,18 @@ mod tests {
         assert_eq!(ix.accounts[16].pubkey, cosigner);
         assert!(ix.accounts[16].is_signer);
         assert!(!ix.accounts[17].is_signer);
+        // Fee accounts follow the pairs: each wallet's associated account for
+        // the mint, writable for the inline payout.
+        assert_eq!(
+            ix.accounts[18].pubkey,
+            derive_associated_token_address(&authority, &mint, &SPL_TOKEN_PROGRAM_ID)
+        );
+        assert_eq!(
+            ix.accounts[19].pubkey,
+            derive_associated_token_address(&cosigner, &mint, &SPL_TOKEN_PROGRAM_ID)
+        );
+        assert!(ix.accounts[19].is_writable);
+        assert!(!ix.accounts[19].is_signer);
 
         assert_eq!(&ix.data[..8], &discriminators::TRANSACT_SPL);
     }
@@ -1163,6 +1300,93 @@ mod tests {
         assert_eq!(ix.data, discriminators::UNREGISTER_VALIDATOR.to_vec());
     }
 
+    #[test]
+    fn test_create_slash_validator_instruction() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let validator = Pubkey::new_unique();
+        let stake_mint = Pubkey::new_unique();
+        let evidence_id = [9u8; 32];
+
+        let ix = create_slash_validator_instruction(
+            &program_id,
+            &authority,
+            &validator,
+            &stake_mint,
+            &SPL_TOKEN_PROGRAM_ID,
+            10,
+            &evidence_id,
+        )
+        .unwrap();
+
+        // Account order must match the `SlashValidator` struct.
+        assert_eq!(ix.accounts.len(), 10);
+        assert_eq!(
+            ix.accounts[0].pubkey,
+            derive_validator_account(&program_id, &validator).0
+        );
+        assert_eq!(
+            ix.accounts[1].pubkey,
+            derive_slashed_funds_vault(&program_id).0
+        );
+        assert_eq!(
+            ix.accounts[2].pubkey,
+            derive_validator_registry(&program_id).0
+        );
+        assert_eq!(ix.accounts[3].pubkey, stake_mint);
+        assert!(ix.accounts[3].is_writable);
+        assert_eq!(
+            ix.accounts[4].pubkey,
+            derive_stake_token_vault(&program_id).0
+        );
+        assert!(!ix.accounts[5].is_writable);
+        assert_eq!(ix.accounts[6].pubkey, SPL_TOKEN_PROGRAM_ID);
+        assert_eq!(
+            ix.accounts[7].pubkey,
+            derive_slash_record(&program_id, &validator, &evidence_id).0
+        );
+        assert!(ix.accounts[7].is_writable);
+        // The authority pays for the slash record, so it is a writable signer.
+        assert_eq!(ix.accounts[8].pubkey, authority);
+        assert!(ix.accounts[8].is_signer && ix.accounts[8].is_writable);
+        assert_eq!(ix.accounts[9].pubkey, SYSTEM_PROGRAM_ID);
+        assert!(ix.accounts[..8].iter().all(|a| !a.is_signer));
+
+        // The record is per (validator, evidence).
+        assert_ne!(
+            derive_slash_record(&program_id, &validator, &evidence_id).0,
+            derive_slash_record(&program_id, &validator, &[8u8; 32]).0
+        );
+
+        // discriminator || validator (32) || slash_percentage (1) || evidence_id (32).
+        assert_eq!(&ix.data[..8], &discriminators::SLASH_VALIDATOR);
+        assert_eq!(&ix.data[8..40], validator.as_ref());
+        assert_eq!(ix.data[40], 10);
+        assert_eq!(&ix.data[41..], &evidence_id);
+    }
+
+    #[test]
+    fn test_create_set_leader_bonus_instruction() {
+        let program_id = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+
+        let ix = create_set_leader_bonus_instruction(&program_id, &authority, 1_500);
+
+        assert_eq!(ix.accounts.len(), 3);
+        assert_eq!(ix.accounts[0].pubkey, derive_bridge_state(&program_id).0);
+        assert!(ix.accounts[0].is_writable);
+        assert_eq!(
+            ix.accounts[1].pubkey,
+            derive_validator_registry(&program_id).0
+        );
+        assert_eq!(ix.accounts[2].pubkey, authority);
+        assert!(ix.accounts[2].is_signer);
+
+        // discriminator || bonus_bps (u16 LE).
+        assert_eq!(&ix.data[..8], &discriminators::SET_LEADER_BONUS);
+        assert_eq!(&ix.data[8..], &1_500u16.to_le_bytes());
+    }
+
     #[test]
     fn test_create_migrate_validator_account_instruction() {
         let program_id = Pubkey::new_unique();
@@ -1,15 +1,25 @@
-//! Event listener for Solana deposits
+//! Event listener for Solana deposits and settlements
 //!
-//! Periodically polls the Paraloom Solana program for new deposit
-//! transactions, decodes them via [`crate::bridge::solana::decoder`],
-//! and feeds the resulting events into the local privacy pool.
-
-use crate::bridge::solana::decoder::{extract_deposit_events, LISTENER_TX_ENCODING};
+//! Periodically polls the Paraloom Solana program for new transactions,
+//! decodes every deposit and `transact` / `transact_spl` settlement via
+//! [`crate::bridge::solana::decoder`], and replays them into the local
+//! privacy pool in the order the program appended their leaves. Each
+//! settlement's outputs are checked against the root the program published
+//! for it, and each batch against the program's `merkle_tree` account; on a
+//! mismatch the listener halts rather than keep serving paths from a tree
+//! the chain does not have.
+
+use crate::bridge::solana::decoder::{extract_program_events, ProgramEvent, LISTENER_TX_ENCODING};
+use crate::bridge::solana::instructions::derive_merkle_tree;
 use crate::bridge::solana::rpc::BridgeRpc;
-use crate::bridge::{BridgeConfig, BridgeError, BridgeStats, DepositEvent, Result};
+use crate::bridge::{
+    BridgeConfig, BridgeError, BridgeStats, DepositEvent, Result, SettlementEvent,
+};
 use crate::privacy::poseidon_circom::v3_commit;
+use crate::privacy::snapshot::SnapshotCursor;
 use crate::privacy::types::{fr_to_bytes_32, Commitment};
 use crate::privacy::{DepositTx, ShieldedAddress, ShieldedPool};
+use crate::storage::{DepositRecord, PrivacyStorage};
 use ark_bn254::Fr;
 use ark_ff::PrimeField;
 use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
@@ -41,6 +51,11 @@ const MAX_SIGNATURE_PAGES: usize = 50;
 /// reprocessing.
 const SEEN_SIGNATURE_CAP: usize = 100_000;
 
+/// `merkle_tree` account layout after the 8-byte discriminator:
+/// `next_index(8) | root_index(8) | root(32) | …`.
+const TREE_NEXT_INDEX_OFFSET: usize = 8;
+const TREE_ROOT_OFFSET: usize = 24;
+
 /// Event listener for deposit events
 pub struct EventListener {
     /// Bridge configuration
@@ -73,6 +88,11 @@ pub struct EventListener {
     /// Last slot number observed on a processed deposit. Reported via
     /// [`BridgeStats::last_block`] for operator visibility.
     last_processed_slot: Arc<RwLock<u64>>,
+
+    /// The node's delivered-note store, told where each settlement output
+    /// landed so the scan feed can hand recipients a leaf index. `None` leaves
+    /// the feed's positions unset.
+    delivered_store: Option<Arc<PrivacyStorage>>,
 }
 
 /// State shared with the spawned poller task. Grouping it keeps the
@@ -95,6 +115,7 @@ struct PollerState {
     /// Where to persist the scan cursor after each advance. `None` keeps the
     /// cursor in memory only (the default and what tests use).
     cursor_path: Option<PathBuf>,
+    delivered_store: Option<Arc<PrivacyStorage>>,
 }
 
 impl EventListener {
@@ -116,9 +137,16 @@ impl EventListener {
             last_signature: Arc::new(RwLock::new(None)),
             seen_signatures: Arc::new(RwLock::new(HashSet::new())),
             last_processed_slot: Arc::new(RwLock::new(0)),
+            delivered_store: None,
         }
     }
 
+    /// Record settlement outputs' leaf positions in `store`'s delivered notes.
+    pub fn with_delivered_store(mut self, store: Arc<PrivacyStorage>) -> Self {
+        self.delivered_store = Some(store);
+        self
+    }
+
     /// Start listening for events
     pub async fn start(&mut self) -> Result<()> {
         // Defer program-ID resolution to start time so that
@@ -160,6 +188,7 @@ impl EventListener {
             lag_warn_threshold_slots: self.config.event_lag_warn_threshold_slots,
             batch_limit: SIGNATURE_BATCH_LIMIT,
             cursor_path: self.config.cursor_path.clone(),
+            delivered_store: self.delivered_store.clone(),
         };
         let running = Arc::clone(&self.running);
         let poll_interval = self.config.poll_interval_secs;
@@ -175,11 +204,25 @@ impl EventListener {
                         if count > 0 {
                             log::info!(
                                 target: "paraloom::bridge::solana",
-                                "processed {} deposit event(s)",
+                                "processed {} event(s)",
                                 count
                             );
                         }
                     }
+                    Err(BridgeError::TreeDivergence(reason)) => {
+                        // Stop for good. Carrying on would append to a tree that
+                        // is already wrong, and the path server would keep
+                        // handing wallets paths the chain rejects. An operator
+                        // has to restore the pool before this node indexes again.
+                        log::error!(
+                            target: "paraloom::bridge::solana",
+                            "HALTING the bridge listener: {} — this node's shielded pool no longer matches the on-chain tree and must be rebuilt before it indexes again",
+                            reason
+                        );
+                        state.stats.write().await.tree_divergence = Some(reason);
+                        *running.write().await = false;
+                        break;
+                    }
                     Err(e) => {
                         log::error!(
                             target: "paraloom::bridge::solana",
@@ -194,6 +237,15 @@ impl EventListener {
         Ok(())
     }
 
+    /// Where the listener has got to: the scan cursor it would resume from
+    /// and the slot it has scanned through.
+    pub async fn snapshot_cursor(&self) -> SnapshotCursor {
+        SnapshotCursor {
+            slot: *self.last_processed_slot.read().await,
+            signature: self.last_signature.read().await.map(|sig| sig.to_string()),
+        }
+    }
+
     /// Stop listening
     pub async fn stop(&mut self) -> Result<()> {
         *self.running.write().await = false;
@@ -327,17 +379,38 @@ impl EventListener {
         let mut outcomes: Vec<(Signature, bool)> = Vec::new();
 
         for event in events {
-            let amount = event.amount;
-            let slot = event.block;
-            let sig_str = event.signature.clone();
+            let (slot, sig_str) = match &event {
+                ProgramEvent::Deposit(d) => (d.block, d.signature.clone()),
+                ProgramEvent::Settlement(s) => (s.block, s.signature.clone()),
+            };
             let parsed = sig_str.parse::<Signature>().ok();
 
-            match Self::process_deposit(&state.pool, event).await {
-                Ok(_) => {
+            let result = match event {
+                ProgramEvent::Deposit(deposit) => {
+                    let amount = deposit.amount;
+                    let record = deposit.clone();
+                    Self::process_deposit(&state.pool, deposit).await.map(|()| {
+                        Self::record_deposit(state, &record);
+                        (true, amount)
+                    })
+                }
+                ProgramEvent::Settlement(settlement) => {
+                    Self::process_settlement(state, &settlement)
+                        .await
+                        .map(|()| (false, 0))
+                }
+            };
+
+            match result {
+                Ok((is_deposit, amount)) => {
                     processed += 1;
                     let mut stats_guard = state.stats.write().await;
-                    stats_guard.total_deposits += 1;
-                    stats_guard.volume_deposited += amount;
+                    if is_deposit {
+                        stats_guard.total_deposits += 1;
+                        stats_guard.volume_deposited += amount;
+                    } else {
+                        stats_guard.total_settlements += 1;
+                    }
                     drop(stats_guard);
 
                     if slot > latest_slot {
@@ -347,10 +420,13 @@ impl EventListener {
                         outcomes.push((sig, true));
                     }
                 }
+                // A divergence is not a retryable failure: stop before the
+                // cursor moves, so nothing past this event counts as indexed.
+                Err(e @ BridgeError::TreeDivergence(_)) => return Err(e),
                 Err(e) => {
                     log::error!(
                         target: "paraloom::bridge::solana",
-                        "failed to process deposit {}: {}",
+                        "failed to process event {}: {}",
                         sig_str,
                         e
                     );
@@ -361,6 +437,12 @@ impl EventListener {
             }
         }
 
+        // Compare against the program's own tree before the cursor moves past
+        // this batch, so a halted listener resumes (after a repair) from here.
+        if processed > 0 {
+            Self::check_program_root(state).await?;
+        }
+
         let (cursor_advance, failed) = Self::contiguous_cursor(&outcomes);
 
         // Un-see the failed signatures so the next poll re-fetches and retries
@@ -447,18 +529,19 @@ impl EventListener {
         *state.last_processed_slot.write().await = current_slot;
     }
 
-    /// Fetch deposit events newer than `cursor` from the Solana RPC.
+    /// Fetch deposit and settlement events newer than `cursor` from the
+    /// Solana RPC.
     ///
     /// Uses `getSignaturesForAddress` to walk the recent transaction
     /// history of the bridge program, pulls each transaction in full,
-    /// and lets [`extract_deposit_events`] turn them into typed events.
+    /// and lets [`extract_program_events`] turn them into typed events.
     /// Signatures already seen during this listener's lifetime are
     /// filtered out before the RPC fetch to avoid redundant
     /// `getTransaction` calls.
     async fn fetch_events(
         state: &PollerState,
         cursor: Option<Signature>,
-    ) -> Result<Vec<DepositEvent>> {
+    ) -> Result<Vec<ProgramEvent>> {
         // RPC calls go through the BridgeRpc trait — RealBridgeRpc
         // handles the spawn_blocking + ClientError mapping, mocks
         // return canned data directly.
@@ -619,7 +702,7 @@ impl EventListener {
             };
 
             let sig_str = sig.to_string();
-            let mut decoded = extract_deposit_events(&sig_str, &confirmed, &state.program_id);
+            let mut decoded = extract_program_events(&sig_str, &confirmed, &state.program_id);
             events.append(&mut decoded);
             newly_seen.push(sig);
         }
@@ -643,6 +726,167 @@ impl EventListener {
         Ok(events)
     }
 
+    /// Append a settlement's outputs to the pool and mark its inputs spent.
+    ///
+    /// The outputs go in as the program appended them, after whatever leaves
+    /// came before them in chain order, so the pool's tree stays the program's
+    /// tree. When the program's `TransactEvent` was in the logs it names the
+    /// leaf the first output went to: a settlement below the pool's next leaf
+    /// is a replay and appends nothing, and one that neither ends below it nor
+    /// starts at it means the pool is missing or holding other leaves — a
+    /// [`BridgeError::TreeDivergence`], as is a root after the append that
+    /// differs from the one the program published. A replay is not
+    /// re-checked; it was checked when it was first appended.
+    async fn process_settlement(state: &PollerState, event: &SettlementEvent) -> Result<()> {
+        let outputs = event.output_commitments.map(Commitment);
+        if let Some(first) = event.leaf_index {
+            let next = state.pool.commitment_count().await as u64;
+            if first != next && first.saturating_add(outputs.len() as u64) > next {
+                return Err(BridgeError::TreeDivergence(format!(
+                    "settlement {} appended at leaf {} but the pool's next leaf is {}",
+                    event.signature, first, next
+                )));
+            }
+        }
+        let appended = state
+            .pool
+            .append_outputs(event.leaf_index, &outputs)
+            .await
+            .map_err(|e| BridgeError::PrivacyLayer(e.to_string()))?;
+        state.pool.record_spent(event.nullifiers).await;
+        if appended.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(published) = event.new_root {
+            let ours = state.pool.onchain_root().await;
+            if ours != published {
+                return Err(BridgeError::TreeDivergence(format!(
+                    "settlement {} published root {} but the pool's is {} at {} leaves",
+                    event.signature,
+                    hex::encode(published),
+                    hex::encode(ours),
+                    state.pool.commitment_count().await
+                )));
+            }
+        }
+
+        if let Some(store) = &state.delivered_store {
+            for (commitment, leaf_index) in &appended {
+                if let Err(e) =
+                    store.set_delivered_position(&commitment.0, *leaf_index, event.block)
+                {
+                    log::warn!(
+                        target: "paraloom::bridge::solana",
+                        "failed to record leaf {} for delivered note {}: {}",
+                        leaf_index,
+                        hex::encode(commitment.0),
+                        e
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the pool's tree with the program's `merkle_tree` account.
+    ///
+    /// The account is read at a later commitment than the finalized
+    /// transactions the listener indexes, so the program is usually a few
+    /// leaves ahead; the roots are only comparable when both trees hold the
+    /// same number of leaves, and a program that is ahead is left for a later
+    /// poll. A pool holding more leaves than the program, or the same number
+    /// under a different root, has diverged. An unreadable account is logged
+    /// and skipped — it says nothing about the trees.
+    async fn check_program_root(state: &PollerState) -> Result<()> {
+        let (tree, _) = derive_merkle_tree(&state.program_id);
+        let data = match state.rpc.get_account(&tree).await {
+            Ok(account) => account.data,
+            Err(e) => {
+                log::warn!(
+                    target: "paraloom::bridge::solana",
+                    "skipping the on-chain root check — {}",
+                    e
+                );
+                return Ok(());
+            }
+        };
+        if data.len() < TREE_ROOT_OFFSET + 32 {
+            log::warn!(
+                target: "paraloom::bridge::solana",
+                "skipping the on-chain root check — merkle_tree account is {} bytes",
+                data.len()
+            );
+            return Ok(());
+        }
+        let mut next_index = [0u8; 8];
+        next_index.copy_from_slice(&data[TREE_NEXT_INDEX_OFFSET..TREE_NEXT_INDEX_OFFSET + 8]);
+        let next_index = u64::from_le_bytes(next_index);
+        let mut program_root = [0u8; 32];
+        program_root.copy_from_slice(&data[TREE_ROOT_OFFSET..TREE_ROOT_OFFSET + 32]);
+
+        let leaves = state.pool.commitment_count().await as u64;
+        if next_index > leaves {
+            log::debug!(
+                target: "paraloom::bridge::solana",
+                "program tree is {} leaves ahead of the pool; comparing roots on a later poll",
+                next_index - leaves
+            );
+            return Ok(());
+        }
+        let ours = state.pool.onchain_root().await;
+        if next_index < leaves || ours != program_root {
+            return Err(BridgeError::TreeDivergence(format!(
+                "program tree has {} leaves under root {}, the pool {} under {}",
+                next_index,
+                hex::encode(program_root),
+                leaves,
+                hex::encode(ours)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Keep the deposit behind a freshly credited leaf, for association-set
+    /// curators (`GET /association/deposits`). Only a node with a store has
+    /// one to keep; a failure is logged, since the pool itself is unaffected.
+    fn record_deposit(state: &PollerState, event: &DepositEvent) {
+        let Some(store) = &state.delivered_store else {
+            return;
+        };
+        let commitment = deposit_commitment(event);
+        let leaf_index = match store.merkle_leaf_index(&commitment) {
+            Ok(Some(index)) => index,
+            Ok(None) => return,
+            Err(e) => {
+                log::warn!(
+                    target: "paraloom::bridge::solana",
+                    "failed to look up the leaf of deposit {}: {}",
+                    event.signature,
+                    e
+                );
+                return;
+            }
+        };
+        let record = DepositRecord {
+            commitment: commitment.0,
+            leaf_index,
+            depositor: event.from,
+            amount: event.amount,
+            asset_id: event.asset_id,
+            slot: event.block,
+            signature: event.signature.clone(),
+        };
+        if let Err(e) = store.put_deposit(&record) {
+            log::warn!(
+                target: "paraloom::bridge::solana",
+                "failed to record deposit {}: {}",
+                event.signature,
+                e
+            );
+        }
+    }
+
     /// Process a single deposit event
     async fn process_deposit(pool: &Arc<ShieldedPool>, event: DepositEvent) -> Result<()> {
         log::info!(
@@ -679,12 +923,7 @@ impl EventListener {
         // the same way is what keeps the two trees the same tree. The `Note`'s
         // own `commitment()` is the v2 hash — a different function — and using
         // it here would put a leaf in the pool that the chain never appended.
-        let commitment = Commitment(fr_to_bytes_32(v3_commit(
-            Fr::from(event.amount),
-            Fr::from_le_bytes_mod_order(&event.recipient),
-            Fr::from_le_bytes_mod_order(&event.randomness),
-            Fr::from_le_bytes_mod_order(&event.asset_id),
-        )));
+        let commitment = deposit_commitment(&event);
 
         // Credit the deposited amount, not a net of it: the on-chain leaf
         // commits to the full amount, so a fee netted here would credit a
@@ -703,10 +942,22 @@ impl EventListener {
     }
 }
 
+/// The leaf `deposit_note` appends for `event`: the v3 commitment over the
+/// lamports (or tokens) it actually received.
+fn deposit_commitment(event: &DepositEvent) -> Commitment {
+    Commitment(fr_to_bytes_32(v3_commit(
+        Fr::from(event.amount),
+        Fr::from_le_bytes_mod_order(&event.recipient),
+        Fr::from_le_bytes_mod_order(&event.randomness),
+        Fr::from_le_bytes_mod_order(&event.asset_id),
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::privacy::pedersen;
+    use crate::privacy::types::Nullifier;
 
     /// The pool's leaf has to be the leaf the program appended, or the two
     /// trees drift apart and the first anyone hears of it is a settlement
@@ -839,6 +1090,7 @@ mod tests {
             lag_warn_threshold_slots: 100,
             batch_limit: SIGNATURE_BATCH_LIMIT,
             cursor_path: None,
+            delivered_store: None,
         }
     }
 
@@ -1020,7 +1272,7 @@ mod tests {
     /// recent successfully-processed deposit's signature so the next
     /// poll narrows `getSignaturesForAddress` to "newer than this".
     /// Drives the full path: get_signatures → get_transaction →
-    /// extract_deposit_events → process_deposit → cursor update.
+    /// extract_program_events → process_deposit → cursor update.
     /// The synth_deposit_tx helper builds the in-memory tx the
     /// decoder expects so we never need to boot a validator.
     #[tokio::test]
@@ -1546,4 +1798,254 @@ mod tests {
         );
         assert_eq!(pool.commitment_count().await, 1);
     }
+
+    fn tree_account(next_index: u64, root: [u8; 32]) -> solana_sdk::account::Account {
+        let mut data = vec![0u8; TREE_ROOT_OFFSET + 32];
+        data[TREE_NEXT_INDEX_OFFSET..TREE_NEXT_INDEX_OFFSET + 8]
+            .copy_from_slice(&next_index.to_le_bytes());
+        data[TREE_ROOT_OFFSET..].copy_from_slice(&root);
+        solana_sdk::account::Account {
+            lamports: 0,
+            data,
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    /// A settlement's outputs land after the deposit that preceded it on
+    /// chain, at the leaves the program gave them, with both inputs spent and
+    /// the settlement's published root matching the pool's. The batch check
+    /// then reads the program's tree account at the same leaf count and finds
+    /// the same root.
+    #[tokio::test]
+    async fn poll_appends_settlement_outputs_after_deposits_in_chain_order() {
+        use crate::bridge::solana::test_support::{
+            synth_deposit_tx, synth_settlement_tx, MockBridgeRpc,
+        };
+        use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+
+        let program_id = Pubkey::new_unique();
+        let depositor = Pubkey::new_unique();
+        let deposit_sig = Signature::new_unique();
+        let settle_sig = Signature::new_unique();
+        let outputs = [[21u8; 32], [22u8; 32]];
+        let nullifiers = [[31u8; 32], [32u8; 32]];
+
+        // The tree the program holds after both transactions.
+        let reference = Arc::new(ShieldedPool::new());
+        EventListener::process_deposit(
+            &reference,
+            DepositEvent {
+                signature: deposit_sig.to_string(),
+                from: depositor.to_bytes(),
+                amount: 1_000,
+                recipient: [9u8; 32],
+                randomness: [11u8; 32],
+                asset_id: crate::privacy::types::NATIVE_SOL_ASSET,
+                fee: 0,
+                block: 7,
+                timestamp: 0,
+            },
+        )
+        .await
+        .unwrap();
+        reference
+            .append_outputs(Some(1), &outputs.map(Commitment))
+            .await
+            .unwrap();
+        let program_root = reference.onchain_root().await;
+
+        let mock = Arc::new(MockBridgeRpc::new());
+        let status = |sig: &Signature, slot: u64| RpcConfirmedTransactionStatusWithSignature {
+            signature: sig.to_string(),
+            slot,
+            err: None,
+            memo: None,
+            block_time: None,
+            confirmation_status: None,
+        };
+        *mock.next_get_signatures.lock().unwrap() =
+            Some(Ok(vec![status(&settle_sig, 8), status(&deposit_sig, 7)]));
+        mock.get_transactions.lock().unwrap().insert(
+            deposit_sig,
+            synth_deposit_tx(
+                deposit_sig,
+                7,
+                &program_id,
+                &depositor,
+                1_000,
+                [9u8; 32],
+                [11u8; 32],
+            ),
+        );
+        mock.get_transactions.lock().unwrap().insert(
+            settle_sig,
+            synth_settlement_tx(
+                settle_sig,
+                8,
+                &program_id,
+                None,
+                nullifiers,
+                outputs,
+                Some((program_root, 1)),
+            ),
+        );
+        *mock.next_get_account.lock().unwrap() = Some(Ok(tree_account(3, program_root)));
+
+        let mut state = make_state(mock);
+        state.program_id = program_id;
+        let processed = EventListener::poll_events(&state).await.unwrap();
+
+        assert_eq!(processed, 2);
+        assert_eq!(state.pool.onchain_root().await, program_root);
+        let second = state
+            .pool
+            .onchain_membership(&Commitment(outputs[1]))
+            .await
+            .unwrap();
+        assert_eq!(second.leaf_index, 2);
+        assert_eq!(second.root, program_root);
+        assert!(state.pool.is_spent(&Nullifier(nullifiers[0])).await);
+        assert!(state.pool.is_spent(&Nullifier(nullifiers[1])).await);
+        let stats = state.stats.read().await;
+        assert_eq!((stats.total_deposits, stats.total_settlements), (1, 1));
+        assert_eq!(*state.last_signature.read().await, Some(settle_sig));
+    }
+
+    /// A settlement whose published root the pool cannot reproduce means the
+    /// pool is missing or misordering leaves. The poll fails as a divergence
+    /// and the cursor stays put, so nothing after it is taken as indexed.
+    #[tokio::test]
+    async fn poll_stops_on_a_root_the_pool_cannot_reproduce() {
+        use crate::bridge::solana::test_support::{synth_settlement_tx, MockBridgeRpc};
+        use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+
+        let program_id = Pubkey::new_unique();
+        let sig = Signature::new_unique();
+        let mock = Arc::new(MockBridgeRpc::new());
+        *mock.next_get_signatures.lock().unwrap() =
+            Some(Ok(vec![RpcConfirmedTransactionStatusWithSignature {
+                signature: sig.to_string(),
+                slot: 5,
+                err: None,
+                memo: None,
+                block_time: None,
+                confirmation_status: None,
+            }]));
+        *mock.next_get_transaction.lock().unwrap() = Some(Ok(synth_settlement_tx(
+            sig,
+            5,
+            &program_id,
+            None,
+            [[1u8; 32], [2u8; 32]],
+            [[3u8; 32], [4u8; 32]],
+            Some(([0xee; 32], 0)),
+        )));
+
+        let mut state = make_state(mock);
+        state.program_id = program_id;
+        let err = EventListener::poll_events(&state).await.unwrap_err();
+        assert!(matches!(err, BridgeError::TreeDivergence(_)), "{err}");
+        assert_eq!(*state.last_signature.read().await, None);
+    }
+
+    /// The program appends both outputs whatever they hold, so a settlement
+    /// paying the same commitment twice is two leaves on chain and has to be
+    /// two in the pool, or every root after it differs. Replaying it — as the
+    /// listener does after a failed sibling — is recognised by its leaf index,
+    /// not by the commitment, and appends nothing.
+    #[tokio::test]
+    async fn a_settlement_with_equal_outputs_appends_two_leaves() {
+        use crate::bridge::solana::test_support::{synth_settlement_tx, MockBridgeRpc};
+        use crate::privacy::merkle::MerkleTree;
+        use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+
+        let program_id = Pubkey::new_unique();
+        let sig = Signature::new_unique();
+        let output = [21u8; 32];
+
+        // The program's tree after appending the output twice, through the
+        // plain per-leaf append its own test pins to the program's.
+        let program_tree = MerkleTree::new();
+        program_tree.insert(&Commitment(output)).await.unwrap();
+        program_tree.insert(&Commitment(output)).await.unwrap();
+        let program_root = program_tree.onchain_root().await;
+
+        let tx = || {
+            synth_settlement_tx(
+                sig,
+                5,
+                &program_id,
+                None,
+                [[1u8; 32], [2u8; 32]],
+                [output, output],
+                Some((program_root, 0)),
+            )
+        };
+        let mock = Arc::new(MockBridgeRpc::new());
+        *mock.next_get_signatures.lock().unwrap() =
+            Some(Ok(vec![RpcConfirmedTransactionStatusWithSignature {
+                signature: sig.to_string(),
+                slot: 5,
+                err: None,
+                memo: None,
+                block_time: None,
+                confirmation_status: None,
+            }]));
+        *mock.next_get_transaction.lock().unwrap() = Some(Ok(tx()));
+        *mock.next_get_account.lock().unwrap() = Some(Ok(tree_account(2, program_root)));
+
+        let mut state = make_state(mock);
+        state.program_id = program_id;
+        assert_eq!(EventListener::poll_events(&state).await.unwrap(), 1);
+        assert_eq!(state.pool.commitment_count().await, 2);
+        assert_eq!(state.pool.onchain_root().await, program_root);
+
+        let events = extract_program_events(&sig.to_string(), &tx(), &program_id);
+        let [ProgramEvent::Settlement(event)] = events.as_slice() else {
+            panic!("expected one settlement");
+        };
+        EventListener::process_settlement(&state, event)
+            .await
+            .unwrap();
+        assert_eq!(state.pool.commitment_count().await, 2);
+        assert_eq!(state.pool.onchain_root().await, program_root);
+    }
+
+    /// The batch check compares roots only at an equal leaf count: a program
+    /// that is ahead is left for later, one that is behind or at a different
+    /// root is a divergence, and an unreadable account is no evidence either
+    /// way.
+    #[tokio::test]
+    async fn program_root_check_compares_at_equal_leaf_counts() {
+        use crate::bridge::solana::test_support::MockBridgeRpc;
+
+        let mock = Arc::new(MockBridgeRpc::new());
+        let state = make_state(mock.clone());
+        state
+            .pool
+            .append_outputs(Some(0), &[Commitment([5u8; 32]), Commitment([6u8; 32])])
+            .await
+            .unwrap();
+        let root = state.pool.onchain_root().await;
+
+        let check = |account| {
+            *mock.next_get_account.lock().unwrap() = Some(Ok(account));
+            EventListener::check_program_root(&state)
+        };
+        assert!(check(tree_account(2, root)).await.is_ok());
+        assert!(check(tree_account(9, [0xab; 32])).await.is_ok());
+        assert!(matches!(
+            check(tree_account(2, [0xab; 32])).await,
+            Err(BridgeError::TreeDivergence(_))
+        ));
+        assert!(matches!(
+            check(tree_account(1, root)).await,
+            Err(BridgeError::TreeDivergence(_))
+        ));
+
+        // The mock has nothing queued: the read fails and the check passes.
+        assert!(EventListener::check_program_root(&state).await.is_ok());
+    }
 }
@@ -11,8 +11,9 @@ pub mod rpc;
 #[cfg(test)]
 mod test_support;
 
-pub use cosign_assembly::{assemble_transaction, gather_signatures};
+pub use cosign_assembly::{assemble_transaction, gather_signatures, vote_signature_is_valid};
 pub use cosign_message::{build_settlement_message, CoSignPayload, SettlementParams};
+pub use decoder::deposit_note_leaf_indices;
 pub use instructions::{
     create_deactivate_validator_instruction, create_deposit_note_instruction,
     create_init_stake_token_vault_instruction, create_initialize_instruction,
@@ -20,20 +21,22 @@ pub use instructions::{
     create_migrate_bridge_state_instruction, create_migrate_validator_account_instruction,
     create_pause_instruction, create_register_validator_instruction,
     create_reset_validator_registry_instruction, create_set_bridge_authority_instruction,
-    create_set_deposit_cap_instruction, create_transact_instruction, create_unpause_instruction,
+    create_set_deposit_cap_instruction, create_set_leader_bonus_instruction,
+    create_slash_validator_instruction, create_transact_instruction, create_unpause_instruction,
     create_unregister_validator_instruction, create_withdraw_unbonded_stake_instruction,
     derive_asset_vault, derive_asset_vault_authority, derive_associated_token_address,
     derive_bridge_state, derive_bridge_vault, derive_nullifier_account, derive_program_data,
-    derive_stake_token_vault, derive_validator_account, derive_validator_registry,
-    DepositInstructionData, SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, SPL_TOKEN_2022_PROGRAM_ID,
-    SPL_TOKEN_PROGRAM_ID,
+    derive_slash_record, derive_slashed_funds_vault, derive_stake_token_vault,
+    derive_validator_account, derive_validator_registry, DepositInstructionData,
+    SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
 };
 pub use keypair::{load_keypair_from_file, pubkey_from_file};
 pub use listener::EventListener;
-pub use program::ProgramInterface;
+pub use program::{ProgramInterface, ProgramTree, ROOT_HISTORY_SIZE};
 pub use rpc::{BridgeRpc, RealBridgeRpc};
 
 use crate::bridge::{BridgeConfig, BridgeStats, Result};
+use crate::consensus::epoch::StakeRecord;
 use crate::privacy::ShieldedPool;
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::commitment_config::CommitmentConfig;
@@ -79,6 +82,13 @@ impl SolanaBridge {
         })
     }
 
+    /// Have the listener record where settlement outputs landed in `store`'s
+    /// delivered notes.
+    pub fn with_delivered_store(mut self, store: Arc<crate::storage::PrivacyStorage>) -> Self {
+        self.listener = self.listener.with_delivered_store(store);
+        self
+    }
+
     /// Start bridge services
     pub async fn start(&mut self) -> Result<()> {
         log::info!("Starting Solana bridge event listener...");
@@ -96,6 +106,11 @@ impl SolanaBridge {
         Ok(())
     }
 
+    /// The listener's position, for tagging a pool snapshot.
+    pub async fn snapshot_cursor(&self) -> crate::privacy::snapshot::SnapshotCursor {
+        self.listener.snapshot_cursor().await
+    }
+
     /// Latest blockhash for a node-assembled co-signed settlement tx (#260).
     pub async fn latest_blockhash(&self) -> Result<[u8; 32]> {
         self.program.latest_blockhash().await
@@ -106,21 +121,54 @@ impl SolanaBridge {
         self.program.get_slot().await
     }
 
+    /// Finalized slot hashes, newest first, for anchoring a settlement leader
+    /// schedule.
+    pub async fn finalized_slot_hashes(&self) -> Result<Vec<(u64, [u8; 32])>> {
+        self.program.finalized_slot_hashes().await
+    }
+
     /// Whether a nullifier's PDA already exists on chain (#703).
     pub async fn is_nullifier_spent(&self, nullifier: &[u8; 32]) -> bool {
         self.program.is_nullifier_spent(nullifier).await
     }
 
-    /// Active validators' on-chain stakes (wallet → lamports), for the consensus
-    /// stake reconciler.
-    pub async fn list_validator_stakes(&self) -> Result<Vec<(solana_sdk::pubkey::Pubkey, u64)>> {
+    /// Validators' on-chain active stake records (wallet → lamports), for the
+    /// consensus stake reconciler.
+    pub async fn list_validator_stakes(
+        &self,
+    ) -> Result<Vec<(solana_sdk::pubkey::Pubkey, StakeRecord)>> {
         self.program.list_validator_stakes().await
     }
 
-    pub async fn registry_total_active_stake(&self) -> Result<u64> {
+    pub async fn registry_total_active_stake(&self) -> Result<StakeRecord> {
         self.program.registry_total_active_stake().await
     }
 
+    /// Sign, without sending, a slash of `validator` for `evidence_id` under
+    /// the registry authority's key.
+    pub async fn slash_validator_transaction(
+        &self,
+        authority: &solana_sdk::signature::Keypair,
+        validator: &solana_sdk::pubkey::Pubkey,
+        slash_percentage: u8,
+        evidence_id: &[u8; 32],
+    ) -> Result<solana_sdk::transaction::Transaction> {
+        self.program
+            .slash_validator_transaction(authority, validator, slash_percentage, evidence_id)
+            .await
+    }
+
+    /// Whether `validator` has already been slashed for `evidence_id`.
+    pub async fn is_evidence_slashed(
+        &self,
+        validator: &solana_sdk::pubkey::Pubkey,
+        evidence_id: &[u8; 32],
+    ) -> bool {
+        self.program
+            .is_evidence_slashed(validator, evidence_id)
+            .await
+    }
+
     /// Submit a pre-assembled, co-signed settlement transaction (#260).
     pub async fn submit_signed_transaction(
         &self,
@@ -4,11 +4,15 @@
 
 use crate::bridge::solana::rpc::BridgeRpc;
 use crate::bridge::{BridgeConfig, BridgeError, Result, SolanaAddress};
+use crate::consensus::epoch::StakeRecord;
+use crate::privacy::snapshot::PoolSnapshot;
 use solana_account_decoder::UiAccountEncoding;
 use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
 use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::{pubkey::Pubkey, signature::Signature, transaction::Transaction};
 use solana_transaction_status::UiTransactionEncoding;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 /// Anchor account discriminator length in bytes. Sits at the start of
@@ -39,6 +43,103 @@ fn parse_program_version(data: &[u8]) -> Result<u32> {
     Ok(u32::from_le_bytes(bytes))
 }
 
+/// Parse the `SlotHashes` sysvar: a bincode `Vec<(Slot, Hash)>`, newest
+/// first — a `u64` count followed by `slot(8) | hash(32)` entries.
+fn parse_slot_hashes(data: &[u8]) -> Result<Vec<(u64, [u8; 32])>> {
+    const ENTRY: usize = 8 + 32;
+    let truncated = || BridgeError::SolanaRpc("SlotHashes sysvar truncated".to_string());
+    let count: [u8; 8] = data.get(..8).ok_or_else(truncated)?.try_into().unwrap();
+    let count = u64::from_le_bytes(count) as usize;
+    let body = data.get(8..).ok_or_else(truncated)?;
+    if body.len() / ENTRY < count {
+        return Err(truncated());
+    }
+    Ok(body
+        .chunks_exact(ENTRY)
+        .take(count)
+        .map(|entry| {
+            let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            (slot, entry[8..].try_into().unwrap())
+        })
+        .collect())
+}
+
+/// The [`StakeRecord`] of an account holding `current`, with the
+/// epoch-boundary value at `data[offset..offset + 8]` and the epoch of the
+/// last change right after it. An account too short to carry them predates
+/// the fields and has never changed under them.
+fn stake_record(data: &[u8], current: u64, offset: usize) -> StakeRecord {
+    match data.get(offset..offset + 16) {
+        Some(tail) => StakeRecord {
+            current,
+            changed_epoch_start: u64::from_le_bytes(tail[..8].try_into().unwrap()),
+            changed_epoch: u64::from_le_bytes(tail[8..].try_into().unwrap()),
+        },
+        None => StakeRecord::unchanged(current),
+    }
+}
+
+/// Roots the program's `merkle_tree` account keeps: one recorded per
+/// append in a ring of [`ROOT_HISTORY_SIZE`].
+pub const ROOT_HISTORY_SIZE: u64 = 64;
+
+/// `merkle_tree` account layout after the 8-byte discriminator:
+/// `next_index(8) | root_index(8) | root(32) | filled_subtrees(32*32) |
+/// root_history(64*32)`.
+const TREE_ROOT_INDEX_OFFSET: usize = 16;
+const TREE_ROOT_OFFSET: usize = 24;
+const TREE_ROOT_HISTORY_OFFSET: usize = 56 + 32 * 32;
+
+/// The program's commitment tree as its account records it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramTree {
+    /// Leaves appended so far.
+    pub next_index: u64,
+    /// Slot of `root` in `root_history`.
+    pub root_index: u64,
+    pub root: [u8; 32],
+    pub root_history: Vec<[u8; 32]>,
+}
+
+impl ProgramTree {
+    /// Parse a raw `merkle_tree` account.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let end = TREE_ROOT_HISTORY_OFFSET + 32 * ROOT_HISTORY_SIZE as usize;
+        if data.len() < end {
+            return Err(BridgeError::Serialization(format!(
+                "merkle_tree account too short: {} bytes (need >= {})",
+                data.len(),
+                end
+            )));
+        }
+        let u64_at = |at: usize| u64::from_le_bytes(data[at..at + 8].try_into().expect("8 bytes"));
+        let hash_at = |at: usize| -> [u8; 32] { data[at..at + 32].try_into().expect("32 bytes") };
+        Ok(ProgramTree {
+            next_index: u64_at(ANCHOR_DISCRIMINATOR_LEN),
+            root_index: u64_at(TREE_ROOT_INDEX_OFFSET),
+            root: hash_at(TREE_ROOT_OFFSET),
+            root_history: (0..ROOT_HISTORY_SIZE as usize)
+                .map(|i| hash_at(TREE_ROOT_HISTORY_OFFSET + 32 * i))
+                .collect(),
+        })
+    }
+
+    /// The root the tree had at `leaves` leaves, while the history still
+    /// holds it: every append records one root, so the root from `k`
+    /// appends ago sits `k` slots behind `root_index`.
+    pub fn root_at(&self, leaves: u64) -> Option<[u8; 32]> {
+        let behind = self.next_index.checked_sub(leaves)?;
+        if behind == 0 {
+            return Some(self.root);
+        }
+        if behind >= ROOT_HISTORY_SIZE {
+            return None;
+        }
+        let slot = (self.root_index + ROOT_HISTORY_SIZE - behind) % ROOT_HISTORY_SIZE;
+        Some(self.root_history[slot as usize])
+    }
+}
+
 /// Interface to Paraloom Solana program
 pub struct ProgramInterface {
     /// Solana RPC behind the trait so tests can substitute a mock.
@@ -65,16 +166,20 @@ impl ProgramInterface {
     }
 
     /// Read every on-chain `ValidatorAccount` in one `getProgramAccounts` call
-    /// and return `(validator_wallet, stake_amount)` for the ACTIVE ones. The
-    /// validator-stake reconciler weights the consensus set by this real
-    /// on-chain stake instead of a placeholder, so the stake-weighted quorum
-    /// reflects actual at-risk capital.
+    /// and return each validator's active stake record (`stake_amount` while
+    /// `is_active`, else 0). The validator-stake reconciler resolves these to
+    /// the stakes at an epoch boundary
+    /// ([`StakeRecord::at_epoch_start`]), so the stake-weighted quorum
+    /// reflects actual at-risk capital as of that boundary. Inactive accounts
+    /// are kept: one that left during the epoch still held its stake when the
+    /// epoch began.
     ///
-    /// Base64 encoding is requested because a current `ValidatorAccount` is 129
-    /// bytes and the RPC rejects base58 above 128. The layout after the 8-byte
-    /// discriminator is `wallet[8..40]`, `stake_amount[40..48]`, and the
-    /// `is_active` flag at byte 88.
-    pub async fn list_validator_stakes(&self) -> Result<Vec<(Pubkey, u64)>> {
+    /// Base64 encoding is requested because a current `ValidatorAccount` is
+    /// 161 bytes and the RPC rejects base58 above 128. The layout after the
+    /// 8-byte discriminator is `wallet[8..40]`, `stake_amount[40..48]`, the
+    /// `is_active` flag at byte 88, and `epoch_stake[145..153]`,
+    /// `stake_epoch[153..161]` (absent before v0.6.0).
+    pub async fn list_validator_stakes(&self) -> Result<Vec<(Pubkey, StakeRecord)>> {
         // sha256("account:ValidatorAccount")[..8].
         const VALIDATOR_DISC: [u8; 8] = [32, 144, 229, 203, 9, 154, 158, 255];
         let config = RpcProgramAccountsConfig {
@@ -95,26 +200,171 @@ impl ProgramInterface {
         let mut stakes = Vec::with_capacity(accounts.len());
         for (_pda, acc) in accounts {
             let d = &acc.data;
-            if d.len() < 89 || d[0..8] != VALIDATOR_DISC || d[88] == 0 {
-                continue; // wrong account, truncated, or inactive
+            if d.len() < 89 || d[0..8] != VALIDATOR_DISC {
+                continue; // wrong account or truncated
             }
             let wallet = Pubkey::new_from_array(d[8..40].try_into().expect("32-byte wallet"));
-            let stake = u64::from_le_bytes(d[40..48].try_into().expect("8-byte stake"));
-            stakes.push((wallet, stake));
+            let stake = if d[88] == 0 {
+                0
+            } else {
+                u64::from_le_bytes(d[40..48].try_into().expect("8-byte stake"))
+            };
+            stakes.push((wallet, stake_record(d, stake, 145)));
         }
         Ok(stakes)
     }
 
+    /// Read the program's `merkle_tree` account.
+    pub async fn merkle_tree(&self) -> Result<ProgramTree> {
+        let (tree_pda, _) = super::instructions::derive_merkle_tree(&self.program_id);
+        let account = self.rpc.get_account(&tree_pda).await?;
+        ProgramTree::parse(&account.data)
+    }
+
+    /// Every nullifier with a `NullifierAccount` PDA, i.e. every note the
+    /// program has seen spent, with the id of the settlement that spent it.
+    /// Layout after the discriminator: `nullifier[8..40]`, `used_at[40..48]`,
+    /// `withdrawal_id[48..56]`.
+    pub async fn spent_nullifiers(&self) -> Result<HashMap<[u8; 32], u64>> {
+        // sha256("account:NullifierAccount")[..8].
+        const NULLIFIER_DISC: [u8; 8] = [250, 31, 238, 177, 213, 98, 48, 172];
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                0,
+                NULLIFIER_DISC.to_vec(),
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self
+            .rpc
+            .get_program_accounts(&self.program_id, config)
+            .await?;
+        Ok(accounts
+            .into_iter()
+            .filter(|(_, acc)| acc.data.len() >= 56 && acc.data[0..8] == NULLIFIER_DISC)
+            .map(|(_, acc)| {
+                let d = &acc.data;
+                (
+                    d[8..40].try_into().expect("32-byte nullifier"),
+                    u64::from_le_bytes(d[48..56].try_into().expect("8-byte settlement id")),
+                )
+            })
+            .collect())
+    }
+
+    /// Check a pool snapshot against the program before a node uses it.
+    ///
+    /// The snapshot's on-chain root must be the root the program's tree had
+    /// at the snapshot's leaf count. The program only keeps its last
+    /// [`ROOT_HISTORY_SIZE`] roots, so a snapshot more appends behind than
+    /// that cannot be checked and is rejected as stale.
+    ///
+    /// The nullifiers must be the ones the program had recorded at that
+    /// point. Every one the snapshot lists must be spent on chain, and every
+    /// one spent by a settlement up to the newest the snapshot covers must be
+    /// listed: settlement ids count up, so a gap is something left out. Past
+    /// that, each settlement appends two leaves, so no more than half the
+    /// leaves appended since the snapshot can belong to settlements after it;
+    /// a chain with more is missing them from the snapshot. What is left of
+    /// the chain's set may fall either side of the cursor, and the snapshot
+    /// takes it on: those notes are spent whichever side it is.
+    ///
+    /// This vouches for the root, not the leaves: the import
+    /// ([`PoolSnapshot::import`]) is what checks that the commitments hash
+    /// to it.
+    pub async fn verify_snapshot(&self, snapshot: &mut PoolSnapshot) -> Result<()> {
+        let tree = self.merkle_tree().await?;
+        let leaves = snapshot.commitments.len() as u64;
+        match tree.root_at(leaves) {
+            Some(root) if root == snapshot.onchain_root => {}
+            Some(root) => {
+                return Err(BridgeError::SnapshotRejected(format!(
+                    "the program's root at {} leaves was {}, the snapshot names {}",
+                    leaves,
+                    hex::encode(root),
+                    hex::encode(snapshot.onchain_root)
+                )))
+            }
+            None if leaves > tree.next_index => {
+                return Err(BridgeError::SnapshotRejected(format!(
+                    "the snapshot has {} leaves, the program only {}",
+                    leaves, tree.next_index
+                )))
+            }
+            None => {
+                return Err(BridgeError::SnapshotRejected(format!(
+                    "the snapshot is {} appends behind the program, beyond its {}-root history; fetch a newer one",
+                    tree.next_index - leaves,
+                    ROOT_HISTORY_SIZE
+                )))
+            }
+        }
+
+        let spent = self.spent_nullifiers().await?;
+        let mut covered = 0;
+        for nullifier in &snapshot.nullifiers {
+            match spent.get(nullifier) {
+                Some(&settlement) => covered = covered.max(settlement),
+                None => {
+                    return Err(BridgeError::SnapshotRejected(format!(
+                        "nullifier {} is not spent on chain",
+                        hex::encode(nullifier)
+                    )))
+                }
+            }
+        }
+
+        let listed: BTreeSet<[u8; 32]> = snapshot.nullifiers.iter().copied().collect();
+        let mut later_settlements = BTreeSet::new();
+        for (nullifier, &settlement) in &spent {
+            if listed.contains(nullifier) {
+                continue;
+            }
+            if settlement <= covered {
+                return Err(BridgeError::SnapshotRejected(format!(
+                    "nullifier {} spent by settlement {} is missing from the snapshot",
+                    hex::encode(nullifier),
+                    settlement
+                )));
+            }
+            later_settlements.insert(settlement);
+        }
+        let room = (tree.next_index - leaves) / 2;
+        if later_settlements.len() as u64 > room {
+            return Err(BridgeError::SnapshotRejected(format!(
+                "{} settlements after the snapshot's last spend, but only {} can fit in the {} leaves appended since",
+                later_settlements.len(),
+                room,
+                tree.next_index - leaves
+            )));
+        }
+
+        snapshot.nullifiers = listed
+            .into_iter()
+            .chain(spent.into_keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        Ok(())
+    }
+
     /// Read `ValidatorRegistry.total_active_stake` — the DENOMINATOR the on-chain
-    /// stake-weighted quorum uses (`programs/paraloom/src/quorum.rs`). The
-    /// off-chain gate mirrors this exact value instead of summing
-    /// `list_validator_stakes`, so a lagging `getProgramAccounts` scan can never
-    /// lower the threshold below what the program enforces.
+    /// stake-weighted quorum uses (`programs/paraloom/src/quorum.rs`) — with
+    /// its epoch-boundary record. The off-chain gate mirrors this exact value
+    /// instead of summing `list_validator_stakes`, so a lagging
+    /// `getProgramAccounts` scan can never lower the threshold below what the
+    /// program enforces.
     ///
     /// Layout after the 8-byte Anchor discriminator: `authority[8..40]`,
     /// `total_validators[40..48]`, `active_validators[48..56]`,
-    /// `minimum_stake[56..64]`, `total_active_stake[64..72]`.
-    pub async fn registry_total_active_stake(&self) -> Result<u64> {
+    /// `minimum_stake[56..64]`, `total_active_stake[64..72]`, and after
+    /// `stake_mint` and `min_token_stake`, `epoch_total_active_stake[112..120]`
+    /// and `total_stake_epoch[120..128]` (absent before v0.6.0).
+    pub async fn registry_total_active_stake(&self) -> Result<StakeRecord> {
         let (registry_pda, _) =
             Pubkey::find_program_address(&[b"validator_registry"], &self.program_id);
         let account = self.rpc.get_account(&registry_pda).await?;
@@ -125,8 +375,57 @@ impl ProgramInterface {
                 d.len()
             )));
         }
-        Ok(u64::from_le_bytes(
-            d[64..72].try_into().expect("8-byte total_active_stake"),
+        let total = u64::from_le_bytes(d[64..72].try_into().expect("8-byte total_active_stake"));
+        Ok(stake_record(d, total, 112))
+    }
+
+    /// Read `ValidatorRegistry.stake_mint` (`[72..104]`, see
+    /// [`Self::registry_total_active_stake`] for the fields before it) and the
+    /// token program that owns it — the accounts `slash_validator` burns the
+    /// token half through.
+    pub async fn registry_stake_mint(&self) -> Result<(Pubkey, Pubkey)> {
+        let (registry_pda, _) = super::derive_validator_registry(&self.program_id);
+        let account = self.rpc.get_account(&registry_pda).await?;
+        let d = &account.data;
+        if d.len() < 104 {
+            return Err(BridgeError::Serialization(format!(
+                "ValidatorRegistry account too short: {} bytes (need >= 104)",
+                d.len()
+            )));
+        }
+        let stake_mint = Pubkey::new_from_array(d[72..104].try_into().expect("32-byte stake_mint"));
+        let token_program = self.rpc.get_account(&stake_mint).await?.owner;
+        Ok((stake_mint, token_program))
+    }
+
+    /// Build and sign `slash_validator` for `evidence_id`, taking
+    /// `slash_percentage` of `validator`'s stake under the registry
+    /// `authority`. Returned unsent so the caller can record the signature
+    /// before broadcasting it. The program rejects any other signer, so a
+    /// node without the registry key cannot use this.
+    pub async fn slash_validator_transaction(
+        &self,
+        authority: &Keypair,
+        validator: &Pubkey,
+        slash_percentage: u8,
+        evidence_id: &[u8; 32],
+    ) -> Result<Transaction> {
+        let (stake_mint, token_program) = self.registry_stake_mint().await?;
+        let instruction = super::create_slash_validator_instruction(
+            &self.program_id,
+            &authority.pubkey(),
+            validator,
+            &stake_mint,
+            &token_program,
+            slash_percentage,
+            evidence_id,
+        )?;
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        Ok(Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&authority.pubkey()),
+            &[authority],
+            blockhash,
         ))
     }
 
@@ -203,11 +502,36 @@ impl ProgramInterface {
         }
     }
 
+    /// Whether `validator` has already been slashed for `evidence_id`, i.e.
+    /// its slash-record PDA exists. Same contract as
+    /// [`is_nullifier_spent`](Self::is_nullifier_spent): an unreachable RPC
+    /// reads as "not slashed", and a resubmission is then refused on chain.
+    pub async fn is_evidence_slashed(&self, validator: &Pubkey, evidence_id: &[u8; 32]) -> bool {
+        let (pda, _) =
+            super::instructions::derive_slash_record(&self.program_id, validator, evidence_id);
+        match self.rpc.get_account(&pda).await {
+            Ok(account) => account.owner == self.program_id,
+            Err(_) => false,
+        }
+    }
+
     /// Get current slot (block number equivalent)
     pub async fn get_slot(&self) -> Result<u64> {
         self.rpc.get_slot().await
     }
 
+    /// The slot hashes a finalized bank records, newest first (about the last
+    /// 512 slots). Every slot listed is an ancestor of a finalized block, so
+    /// its hash is the same on every node and can no longer be forked away —
+    /// what seeds the settlement leader schedule.
+    pub async fn finalized_slot_hashes(&self) -> Result<Vec<(u64, [u8; 32])>> {
+        let account = self
+            .rpc
+            .get_finalized_account(&solana_sdk::sysvar::slot_hashes::ID)
+            .await?;
+        parse_slot_hashes(&account.data)
+    }
+
     /// Latest blockhash as raw bytes. The node bakes this into the multi-sig
     /// settlement transaction it assembles in the #260 co-signing round, so it
     /// needs the same blockhash this RPC will later confirm against.
@@ -274,6 +598,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn finalized_slot_hashes_reads_the_sysvar_newest_first() {
+        let mut data = 2u64.to_le_bytes().to_vec();
+        for (slot, byte) in [(101u64, 1u8), (100, 2)] {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[byte; 32]);
+        }
+        let mock = Arc::new(MockBridgeRpc::new());
+        *mock.next_get_finalized_account.lock().unwrap() = Some(Ok(Account {
+            lamports: 1,
+            data: data.clone(),
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        }));
+        assert_eq!(
+            program_with_mock(mock)
+                .finalized_slot_hashes()
+                .await
+                .unwrap(),
+            vec![(101, [1; 32]), (100, [2; 32])]
+        );
+
+        // A count the body cannot hold is an error, not a short list.
+        assert!(parse_slot_hashes(&data[..data.len() - 1]).is_err());
+        assert!(parse_slot_hashes(&[0; 4]).is_err());
+    }
+
     // `test_program_interface_creation` used to sit here asserting
     // `result.is_err() || result.is_ok()`, which is a logical constant. Its
     // real property — construction succeeds for a well-formed program id — is
@@ -482,4 +834,132 @@ mod tests {
         let err = parse_program_version(&[]).expect_err("empty buffer");
         assert!(matches!(err, BridgeError::ConfigError(_)));
     }
+
+    /// A tree 100 leaves in, its latest root in ring slot 5 and every other
+    /// slot holding a root tagged with the slot number.
+    fn tree_account() -> Account {
+        let mut data = vec![0u8; TREE_ROOT_HISTORY_OFFSET + 32 * ROOT_HISTORY_SIZE as usize];
+        data[8..16].copy_from_slice(&100u64.to_le_bytes());
+        data[16..24].copy_from_slice(&5u64.to_le_bytes());
+        for slot in 0..ROOT_HISTORY_SIZE as usize {
+            let at = TREE_ROOT_HISTORY_OFFSET + 32 * slot;
+            data[at..at + 32].copy_from_slice(&[slot as u8; 32]);
+        }
+        data[TREE_ROOT_OFFSET..TREE_ROOT_OFFSET + 32].copy_from_slice(&[5; 32]);
+        Account {
+            lamports: 1,
+            data,
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn nullifier_account((nullifier, settlement): ([u8; 32], u64)) -> (Pubkey, Account) {
+        let mut data = vec![250, 31, 238, 177, 213, 98, 48, 172];
+        data.extend_from_slice(&nullifier);
+        data.extend_from_slice(&[0; 8]);
+        data.extend_from_slice(&settlement.to_le_bytes());
+        let account = Account {
+            lamports: 1,
+            data,
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        (Pubkey::new_unique(), account)
+    }
+
+    fn snapshot(leaves: usize, onchain_root: [u8; 32], nullifiers: Vec<[u8; 32]>) -> PoolSnapshot {
+        PoolSnapshot {
+            version: crate::privacy::snapshot::SNAPSHOT_VERSION,
+            cursor: Default::default(),
+            commitments: vec![[1; 32]; leaves],
+            nullifiers,
+            supplies: Vec::new(),
+            root: [0; 32],
+            onchain_root,
+        }
+    }
+
+    /// Run `verify_snapshot` against [`tree_account`] and `spent`, returning
+    /// the snapshot as it came out.
+    async fn check(mut snap: PoolSnapshot, spent: Vec<([u8; 32], u64)>) -> Result<PoolSnapshot> {
+        let mock = Arc::new(MockBridgeRpc::new());
+        *mock.next_get_account.lock().unwrap() = Some(Ok(tree_account()));
+        *mock.next_get_program_accounts.lock().unwrap() =
+            Some(Ok(spent.into_iter().map(nullifier_account).collect()));
+        program_with_mock(mock).verify_snapshot(&mut snap).await?;
+        Ok(snap)
+    }
+
+    #[test]
+    fn program_tree_finds_past_roots_within_its_history() {
+        let tree = ProgramTree::parse(&tree_account().data).unwrap();
+        assert_eq!(tree.next_index, 100);
+        assert_eq!(tree.root_at(100), Some([5; 32]));
+        assert_eq!(tree.root_at(99), Some([4; 32]));
+        // 63 appends back wraps round the ring; 64 has been overwritten.
+        assert_eq!(tree.root_at(37), Some([6; 32]));
+        assert_eq!(tree.root_at(36), None);
+        assert_eq!(tree.root_at(101), None);
+        assert!(ProgramTree::parse(&[0; 100]).is_err());
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_accepted_only_at_a_root_the_program_held() {
+        check(snapshot(99, [4; 32], vec![[7; 32]]), vec![([7; 32], 1)])
+            .await
+            .expect("root from one append ago, nullifier spent");
+        check(snapshot(100, [5; 32], Vec::new()), Vec::new())
+            .await
+            .expect("current root");
+
+        for (snap, spent) in [
+            (snapshot(99, [5; 32], Vec::new()), Vec::new()),
+            (snapshot(36, [0; 32], Vec::new()), Vec::new()),
+            (snapshot(101, [5; 32], Vec::new()), Vec::new()),
+            (snapshot(100, [5; 32], vec![[9; 32]]), vec![([7; 32], 1)]),
+        ] {
+            let err = check(snap, spent).await.expect_err("rejected");
+            assert!(matches!(err, BridgeError::SnapshotRejected(_)), "{err}");
+        }
+    }
+
+    /// A snapshot that leaves out a spent nullifier would hand the node notes
+    /// it treats as spendable. One missing from a settlement the snapshot
+    /// covers, or from more later settlements than the leaves since leave
+    /// room for, is refused; the rest of the chain's set is taken on.
+    #[tokio::test]
+    async fn snapshot_missing_a_spent_nullifier_is_rejected() {
+        let spent = vec![([1; 32], 1), ([2; 32], 1), ([3; 32], 2), ([4; 32], 2)];
+
+        // Settlement 1 is in the snapshot but half of it is not.
+        let err = check(
+            snapshot(100, [5; 32], vec![[1; 32], [3; 32], [4; 32]]),
+            spent.clone(),
+        )
+        .await
+        .expect_err("gap below the newest covered settlement");
+        assert!(err.to_string().contains("missing"), "{err}");
+
+        // At the program's tip nothing can have been spent since.
+        let err = check(
+            snapshot(100, [5; 32], vec![[1; 32], [2; 32]]),
+            spent.clone(),
+        )
+        .await
+        .expect_err("settlement 2 left out of a snapshot at the tip");
+        assert!(matches!(err, BridgeError::SnapshotRejected(_)), "{err}");
+
+        // Two leaves back there is room for settlement 2 after the snapshot,
+        // and the snapshot takes its nullifiers on.
+        let verified = check(snapshot(98, [3; 32], vec![[1; 32], [2; 32]]), spent)
+            .await
+            .expect("settlement 2 may postdate the snapshot");
+        assert_eq!(
+            verified.nullifiers,
+            vec![[1; 32], [2; 32], [3; 32], [4; 32]]
+        );
+    }
 }
@@ -10,6 +10,7 @@ use solana_client::rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClie
 use solana_client::rpc_config::{RpcProgramAccountsConfig, RpcTransactionConfig};
 use solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
 use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::hash::Hash;
 use solana_sdk::pubkey::Pubkey;
 use solana_sdk::signature::Signature;
@@ -42,6 +43,10 @@ pub trait BridgeRpc: Send + Sync {
 
     async fn get_account(&self, pubkey: &Pubkey) -> Result<Account>;
 
+    /// `getAccountInfo` at finalized commitment, for state every node has to
+    /// read identically however far its RPC's confirmed tip has moved.
+    async fn get_finalized_account(&self, pubkey: &Pubkey) -> Result<Account>;
+
     /// Enumerate program-owned accounts matching `config` (a `getProgramAccounts`
     /// call). Used by the validator-stake reconciler to read every on-chain
     /// `ValidatorAccount` in one request rather than polling per validator.
@@ -158,6 +163,20 @@ impl BridgeRpc for RealBridgeRpc {
         .await
     }
 
+    async fn get_finalized_account(&self, pubkey: &Pubkey) -> Result<Account> {
+        let rpc = Arc::clone(&self.client);
+        let key = *pubkey;
+        blocking("getAccountInfo(finalized)", move || {
+            rpc_err(
+                "getAccountInfo(finalized)",
+                rpc.get_account_with_commitment(&key, CommitmentConfig::finalized()),
+            )?
+            .value
+            .ok_or_else(|| BridgeError::SolanaRpc(format!("account {key} not found")))
+        })
+        .await
+    }
+
     async fn get_program_accounts(
         &self,
         program_id: &Pubkey,
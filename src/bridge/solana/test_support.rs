@@ -7,7 +7,9 @@
 
 #![cfg(test)]
 
-use crate::bridge::solana::instructions::{discriminators, DepositInstructionData};
+use crate::bridge::solana::instructions::{
+    discriminators, DepositInstructionData, TransactInstructionData,
+};
 use crate::bridge::solana::rpc::BridgeRpc;
 use crate::bridge::{BridgeError, Result};
 use async_trait::async_trait;
@@ -28,9 +30,13 @@ use solana_transaction_status::{
 use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
 
+/// A `getProgramAccounts` result: each account with its address.
+type ProgramAccounts = Result<Vec<(Pubkey, Account)>>;
+
 #[derive(Default)]
 pub struct MockBridgeRpc {
     pub next_get_account: Mutex<Option<Result<Account>>>,
+    pub next_get_finalized_account: Mutex<Option<Result<Account>>>,
     pub next_get_balance: Mutex<Option<Result<u64>>>,
     pub next_get_slot: Mutex<Option<Result<u64>>>,
     pub next_get_signatures: Mutex<Option<Result<Vec<RpcConfirmedTransactionStatusWithSignature>>>>,
@@ -46,6 +52,8 @@ pub struct MockBridgeRpc {
     pub get_transactions: Mutex<HashMap<Signature, EncodedConfirmedTransactionWithStatusMeta>>,
     pub next_get_latest_blockhash: Mutex<Option<Result<Hash>>>,
     pub next_send_and_confirm: Mutex<Option<Result<Signature>>>,
+    /// `getProgramAccounts` result; an empty list when unset.
+    pub next_get_program_accounts: Mutex<Option<ProgramAccounts>>,
 }
 
 impl MockBridgeRpc {
@@ -66,7 +74,7 @@ fn take<T>(slot: &Mutex<Option<Result<T>>>, label: &'static str) -> Result<T> {
 /// Build a synthetic `EncodedConfirmedTransactionWithStatusMeta`
 /// shaped like what `getTransaction` returns for a real Paraloom
 /// deposit. Used by listener tests that need to drive
-/// `extract_deposit_events` end to end without booting a validator.
+/// `extract_program_events` end to end without booting a validator.
 /// Account ordering matches the legacy deposit instruction's account
 /// metas: `[bridge_state, bridge_vault, depositor, system_program,
 /// program]` (program is at index 4 so the program-id-index in the
@@ -127,6 +135,118 @@ pub fn synth_deposit_tx(
     }
 }
 
+/// Build a synthetic `transact` (or, with `mint`, `transact_spl`) settlement
+/// transaction. When `published` is given the meta carries the
+/// `TransactEvent` log line the program's `emit!` writes, with that root and
+/// first leaf index, so the decoder can pick them up.
+/// Account keys: `[bridge_state, merkle_tree, mint_or_vault, authority,
+/// program]`, with the program at index 4.
+pub fn synth_settlement_tx(
+    signature: Signature,
+    slot: u64,
+    program_id: &Pubkey,
+    mint: Option<&Pubkey>,
+    nullifiers: [[u8; 32]; 2],
+    output_commitments: [[u8; 32]; 2],
+    published: Option<([u8; 32], u64)>,
+) -> EncodedConfirmedTransactionWithStatusMeta {
+    use base64::Engine as _;
+
+    let payload = TransactInstructionData {
+        nullifiers,
+        output_commitments,
+        root: [1u8; 32],
+        ext_amount: 0,
+        proof: vec![0u8; 256],
+    };
+    let mut data = match mint {
+        Some(_) => discriminators::TRANSACT_SPL.to_vec(),
+        None => discriminators::TRANSACT.to_vec(),
+    };
+    data.extend_from_slice(&borsh::to_vec(&payload).expect("borsh serialise"));
+    let dummy = Pubkey::default();
+    let account_keys = vec![
+        dummy.to_string(),
+        dummy.to_string(),
+        mint.copied().unwrap_or(dummy).to_string(),
+        Pubkey::new_unique().to_string(),
+        program_id.to_string(),
+    ];
+    let raw = UiRawMessage {
+        header: MessageHeader {
+            num_required_signatures: 1,
+            num_readonly_signed_accounts: 0,
+            num_readonly_unsigned_accounts: 1,
+        },
+        account_keys,
+        recent_blockhash: Hash::default().to_string(),
+        instructions: vec![UiCompiledInstruction {
+            program_id_index: 4,
+            accounts: vec![0, 1, 2, 3],
+            data: bs58::encode(data).into_string(),
+            stack_height: None,
+        }],
+        address_table_lookups: None,
+    };
+
+    // `TransactEvent` as borsh lays it out: the five 32-byte fields, then
+    // ext_amount, fee, recipient, timestamp, settlement_id, leaf_index.
+    let logs = published.map(|(root, leaf_index)| {
+        let mut event = vec![89, 245, 87, 250, 222, 30, 135, 142];
+        for field in [
+            nullifiers[0],
+            nullifiers[1],
+            output_commitments[0],
+            output_commitments[1],
+            root,
+        ] {
+            event.extend_from_slice(&field);
+        }
+        event.extend_from_slice(&0i64.to_le_bytes());
+        event.extend_from_slice(&0u64.to_le_bytes());
+        event.extend_from_slice(&[0u8; 32]);
+        event.extend_from_slice(&0i64.to_le_bytes());
+        event.extend_from_slice(&1u64.to_le_bytes());
+        event.extend_from_slice(&leaf_index.to_le_bytes());
+        vec![
+            format!("Program {program_id} invoke [1]"),
+            format!(
+                "Program data: {}",
+                base64::engine::general_purpose::STANDARD.encode(event)
+            ),
+            format!("Program {program_id} success"),
+        ]
+    });
+
+    EncodedConfirmedTransactionWithStatusMeta {
+        slot,
+        transaction: EncodedTransactionWithStatusMeta {
+            transaction: EncodedTransaction::Json(UiTransaction {
+                signatures: vec![signature.to_string()],
+                message: UiMessage::Raw(raw),
+            }),
+            meta: logs.map(|logs| UiTransactionStatusMeta {
+                status: Ok(()),
+                err: None,
+                fee: 0,
+                pre_balances: vec![],
+                post_balances: vec![],
+                inner_instructions: OptionSerializer::None,
+                log_messages: OptionSerializer::Some(logs),
+                pre_token_balances: OptionSerializer::None,
+                post_token_balances: OptionSerializer::None,
+                rewards: OptionSerializer::None,
+                loaded_addresses: OptionSerializer::None,
+                return_data: OptionSerializer::None,
+                compute_units_consumed: OptionSerializer::None,
+                cost_units: OptionSerializer::None,
+            }),
+            version: None,
+        },
+        block_time: None,
+    }
+}
+
 /// Attach a status meta carrying `err` to a synthesised transaction.
 ///
 /// `synth_deposit_tx` leaves `meta: None`, which is what the decoder path
@@ -188,12 +308,20 @@ impl BridgeRpc for MockBridgeRpc {
         take(&self.next_get_account, "get_account")
     }
 
+    async fn get_finalized_account(&self, _pubkey: &Pubkey) -> Result<Account> {
+        take(&self.next_get_finalized_account, "get_finalized_account")
+    }
+
     async fn get_program_accounts(
         &self,
         _program_id: &Pubkey,
         _config: solana_client::rpc_config::RpcProgramAccountsConfig,
     ) -> Result<Vec<(Pubkey, Account)>> {
-        Ok(Vec::new())
+        self.next_get_program_accounts
+            .lock()
+            .unwrap()
+            .take()
+            .unwrap_or_else(|| Ok(Vec::new()))
     }
 
     async fn send_and_confirm_transaction(&self, _tx: &Transaction) -> Result<Signature> {
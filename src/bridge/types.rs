@@ -37,6 +37,39 @@ pub struct DepositEvent {
     pub timestamp: i64,
 }
 
+/// A `transact` / `transact_spl` settlement as it landed on Solana. The
+/// program appended both output commitments to its tree, in order, and spent
+/// both nullifiers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettlementEvent {
+    /// Transaction signature on Solana
+    pub signature: String,
+
+    /// The two input nullifiers the settlement spent
+    pub nullifiers: [[u8; 32]; 2],
+
+    /// The two output commitments, in the order they were appended
+    pub output_commitments: [[u8; 32]; 2],
+
+    /// Signed external flow: negative for a withdrawal, zero for a transfer
+    pub ext_amount: i64,
+
+    /// The SPL mint for a `transact_spl`; `None` for native SOL
+    pub mint: Option<[u8; 32]>,
+
+    /// The root the program published after appending both outputs, read from
+    /// its `TransactEvent`. `None` when the transaction's logs were unavailable.
+    pub new_root: Option<[u8; 32]>,
+
+    /// Leaf index the program gave the first output, from the same event; the
+    /// second output is the leaf after it. `None` when the logs were
+    /// unavailable or the program predates the field.
+    pub leaf_index: Option<u64>,
+
+    /// Block number
+    pub block: u64,
+}
+
 /// Withdrawal request to Solana
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WithdrawalRequest {
@@ -196,6 +229,44 @@ pub struct BridgeConfig {
     /// diverge and cross-node votes are dropped. Defaults to "mainnet-beta".
     #[serde(default = "default_cluster_tag")]
     pub cluster_tag: String,
+
+    /// Most delivered notes (#196) the node keeps for `/transact/scan`; the
+    /// oldest are pruned past it. `0` keeps every note.
+    #[serde(default = "default_delivered_notes_max")]
+    pub delivered_notes_max: u64,
+
+    /// Seconds a delivered note is kept before it is pruned. A recipient
+    /// offline for longer must rescan from another node or restore from the
+    /// chain. `0` keeps notes regardless of age.
+    #[serde(default = "default_delivered_notes_retention_secs")]
+    pub delivered_notes_retention_secs: u64,
+
+    /// Validator-registry authority keypair. A node given it submits
+    /// `slash_validator` for every equivocation it records and can verify;
+    /// without it the evidence is kept for `paraloom validator slashing
+    /// export`. The same key can reset or deactivate the registry, so only an
+    /// operator already trusted with it should set this.
+    #[serde(default)]
+    pub slashing_authority_keypair_path: Option<String>,
+
+    /// Percentage (1-100) of an equivocating validator's stake to slash.
+    #[serde(default = "default_slash_percentage")]
+    pub slash_percentage: u8,
+}
+
+/// Default for [`BridgeConfig::slash_percentage`].
+fn default_slash_percentage() -> u8 {
+    10
+}
+
+/// Default for [`BridgeConfig::delivered_notes_max`].
+fn default_delivered_notes_max() -> u64 {
+    1_000_000
+}
+
+/// Default for [`BridgeConfig::delivered_notes_retention_secs`]: 90 days.
+fn default_delivered_notes_retention_secs() -> u64 {
+    90 * 24 * 60 * 60
 }
 
 /// Default cluster tag ([`BridgeConfig::cluster_tag`]).
@@ -248,6 +319,13 @@ impl Default for BridgeConfig {
             cursor_path: None,
             cluster_tag: std::env::var("BRIDGE_CLUSTER_TAG")
                 .unwrap_or_else(|_| default_cluster_tag()),
+            delivered_notes_max: default_delivered_notes_max(),
+            delivered_notes_retention_secs: default_delivered_notes_retention_secs(),
+            slashing_authority_keypair_path: std::env::var(
+                "BRIDGE_SLASHING_AUTHORITY_KEYPAIR_PATH",
+            )
+            .ok(),
+            slash_percentage: default_slash_percentage(),
         }
     }
 }
@@ -326,4 +404,13 @@ pub struct BridgeStats {
     /// `current_slot − last_processed_slot`. Useful as a metric to
     /// drive operator dashboards or pageable alerts.
     pub event_lag_slots: u64,
+
+    /// Settlements whose outputs were appended to the pool
+    #[serde(default)]
+    pub total_settlements: u64,
+
+    /// Why the listener halted, if the pool's tree stopped matching the
+    /// program's. The listener does not resume on its own once this is set.
+    #[serde(default)]
+    pub tree_divergence: Option<String>,
 }
@@ -2,4 +2,4 @@
 
 mod settings;
 
-pub use settings::{ComputeSettings, Settings};
+pub use settings::{ComputeSettings, PrivacySettings, Settings};
@@ -1,10 +1,11 @@
 //! Settings for the Paraloom node
 
 use crate::bridge::BridgeConfig;
+use crate::privacy::SuiteConfig;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Node settings
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -37,6 +38,11 @@ pub struct Settings {
     /// ceiling. Set `authorized_submitters` to restrict who may submit.
     #[serde(default)]
     pub compute: ComputeSettings,
+    /// Proof-suite registry settings. Optional: a config without a
+    /// `[privacy]` section accepts the built-in suites under their default
+    /// tags and verifying-key paths.
+    #[serde(default)]
+    pub privacy: PrivacySettings,
 }
 
 /// Network settings
@@ -190,6 +196,25 @@ pub struct ComputeSettings {
     pub max_timeout_secs: Option<u64>,
 }
 
+/// Privacy settings.
+#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+pub struct PrivacySettings {
+    /// Proof suites. Each `[[privacy.suites]]` table registers one envelope
+    /// tag with its circuit, verifying key and activation window (see
+    /// `privacy::suite_registry`). A non-empty list replaces the built-in
+    /// suites entirely, so a retired tag is dropped by leaving it out.
+    #[serde(default)]
+    pub suites: Vec<SuiteConfig>,
+    /// Address of the association-set server (`node::association`). Empty
+    /// disables it.
+    #[serde(default)]
+    pub association_address: String,
+    /// Published association sets (JSON, as `paraloom association build`
+    /// writes them) the server hands out paths for and verifies against.
+    #[serde(default)]
+    pub association_sets: Vec<PathBuf>,
+}
+
 /// Default for [`NetworkSettings::enable_relay_server`]. Reads the
 /// `ENABLE_RELAY_SERVER` env var (parsed as bool) so the anchor can
 /// flip relay on without a config edit, falling back to `false`.
@@ -257,6 +282,7 @@ impl Settings {
             ha: HaSettings::default(),
             bridge: BridgeConfig::default(),
             compute: ComputeSettings::default(),
+            privacy: PrivacySettings::default(),
         }
     }
 }
@@ -334,6 +360,43 @@ mod tests {
         assert!(settings.compute.max_instructions.is_none());
     }
 
+    #[test]
+    fn privacy_suites_parse_with_activation_windows() {
+        let toml_text = r#"
+            [network]
+            listen_address = "/ip4/127.0.0.1/tcp/0"
+            bootstrap_nodes = []
+            enable_mdns = false
+
+            [node]
+            node_type = "Validator"
+            max_cpu_usage = 80
+            max_memory_usage = 70
+            max_storage_usage = 1024
+
+            [storage]
+            data_dir = "./data"
+
+            [[privacy.suites]]
+            tag = 1
+            circuit = "groth16_bn254_transact_v3"
+            deactivation_slot = 5000
+
+            [[privacy.suites]]
+            tag = 3
+            circuit = "groth16_bn254_transact_v3"
+            verifying_key = "keys/rotated_verifying.key"
+            activation_slot = 4000
+        "#;
+        let settings: Settings = toml::from_str(toml_text).expect("parses [[privacy.suites]]");
+        let suites = &settings.privacy.suites;
+        assert_eq!(suites.len(), 2);
+        assert_eq!(suites[0].deactivation_slot, Some(5000));
+        assert_eq!(suites[1].tag, 3);
+        assert_eq!(suites[1].activation_slot, Some(4000));
+        assert!(crate::privacy::SuiteRegistry::from_config(suites).is_ok());
+    }
+
     #[test]
     fn ha_settings_parse_partial_overrides() {
         // An operator who wants to tighten the stall threshold
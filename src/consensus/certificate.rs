@@ -0,0 +1,341 @@
+//! Quorum certificates for approved transacts.
+//!
+//! The votes that approve a transact live in its [`VoteTally`], which is
+//! dropped at cleanup. A [`QuorumCertificate`] keeps them: the canonical
+//! request id, the stake epoch and the eligible on-chain stake of that epoch
+//! the quorum was counted against, and every signed `Valid` vote that
+//! approved it. Each vote signature covers
+//! [`transact_vote_signing_bytes`] for the certificate's program, cluster and
+//! epoch, so anyone holding the certificate can check, with no node and no
+//! chain access, which wallets approved the settlement and that they were a
+//! supermajority of the stake it names.
+//!
+//! The stake snapshot is the issuing node's view. An auditor compares it with
+//! the registry at the time; a validator checks the votes against its own
+//! snapshot of the same epoch instead ([`QuorumCertificate::verify_quorum`]).
+//!
+//! [`VoteTally`]: crate::consensus::VoteTally
+
+use crate::consensus::transact::{transact_vote_signing_bytes, TransactVerificationResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// The signed votes that approved one transact, with the stake they were
+/// counted against.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct QuorumCertificate {
+    /// Canonical id of the approved request
+    /// ([`TransactVerificationRequest::canonical_id`](crate::consensus::TransactVerificationRequest::canonical_id)).
+    pub request_id: String,
+    /// Program the votes were cast for.
+    pub program_id: String,
+    /// Cluster the votes were cast on (`bridge.cluster_tag`).
+    pub cluster_tag: String,
+    /// Stake epoch the votes were counted in.
+    pub epoch: u64,
+    /// Eligible wallets and their on-chain stakes in that epoch, sorted by
+    /// wallet.
+    pub stakes: Vec<(String, u64)>,
+    /// The registry's `total_active_stake` in the same snapshot: the
+    /// denominator of the supermajority.
+    pub registry_total: u64,
+    /// The approving votes, one per wallet, sorted by wallet.
+    pub votes: Vec<TransactVerificationResult>,
+    /// When the certificate was issued (unix seconds).
+    pub issued_at: u64,
+}
+
+/// Why a quorum certificate was refused.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CertificateError {
+    #[error("certificate is for program {program_id} on {cluster_tag}")]
+    WrongCluster {
+        program_id: String,
+        cluster_tag: String,
+    },
+    #[error("certificate holds no votes")]
+    NoVotes,
+    #[error("vote by {0} is for another request")]
+    ForeignVote(String),
+    #[error("vote by {0} is not Valid")]
+    NotValid(String),
+    #[error("{0} votes more than once")]
+    DuplicateWallet(String),
+    #[error("signature of {0} does not verify")]
+    BadSignature(String),
+    #[error("approving stake {counted} of {total} is not a supermajority")]
+    QuorumNotMet { counted: u64, total: u64 },
+}
+
+impl QuorumCertificate {
+    /// Certificate for `request_id` from its approving `votes` and the stake
+    /// snapshot of `epoch` they were counted against.
+    pub fn new(
+        request_id: String,
+        program_id: String,
+        cluster_tag: String,
+        epoch: u64,
+        stakes: &HashMap<String, u64>,
+        registry_total: u64,
+        mut votes: Vec<TransactVerificationResult>,
+    ) -> Self {
+        let mut stakes: Vec<(String, u64)> = stakes
+            .iter()
+            .map(|(wallet, stake)| (wallet.clone(), *stake))
+            .collect();
+        stakes.sort();
+        votes.sort_by(|a, b| a.wallet_pubkey.cmp(&b.wallet_pubkey));
+        Self {
+            request_id,
+            program_id,
+            cluster_tag,
+            epoch,
+            stakes,
+            registry_total,
+            votes,
+            issued_at: crate::utils::now_unix_seconds(),
+        }
+    }
+
+    /// The wallets that approved, in order.
+    pub fn wallets(&self) -> impl Iterator<Item = &str> {
+        self.votes.iter().map(|vote| vote.wallet_pubkey.as_str())
+    }
+
+    /// Check that every vote is a distinct wallet's `Valid` vote on this
+    /// request, signed for `program_id` on `cluster_tag` in this `epoch`.
+    ///
+    /// `signature_is_valid(wallet, signature, message)` is the ed25519 check,
+    /// as for [`verify_equivocation`](crate::consensus::verify_equivocation).
+    pub fn verify_signatures(
+        &self,
+        program_id: &str,
+        cluster_tag: &str,
+        signature_is_valid: impl Fn(&str, &[u8], &[u8]) -> bool,
+    ) -> Result<(), CertificateError> {
+        if self.program_id != program_id || self.cluster_tag != cluster_tag {
+            return Err(CertificateError::WrongCluster {
+                program_id: self.program_id.clone(),
+                cluster_tag: self.cluster_tag.clone(),
+            });
+        }
+        if self.votes.is_empty() {
+            return Err(CertificateError::NoVotes);
+        }
+        let mut seen = HashSet::new();
+        for vote in &self.votes {
+            let wallet = &vote.wallet_pubkey;
+            if vote.request_id != self.request_id {
+                return Err(CertificateError::ForeignVote(wallet.clone()));
+            }
+            if !vote.vote.is_valid() {
+                return Err(CertificateError::NotValid(wallet.clone()));
+            }
+            if !seen.insert(wallet.as_str()) {
+                return Err(CertificateError::DuplicateWallet(wallet.clone()));
+            }
+            let bytes = transact_vote_signing_bytes(
+                program_id,
+                cluster_tag,
+                &self.request_id,
+                self.epoch,
+                &vote.validator,
+                &vote.vote,
+                wallet,
+            );
+            if !signature_is_valid(wallet, &vote.signature, &bytes) {
+                return Err(CertificateError::BadSignature(wallet.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that the approving wallets hold more than two thirds of
+    /// `registry_total` by `stakes`. A wallet `stakes` does not list counts
+    /// nothing.
+    ///
+    /// This is the whole-registry supermajority; the settlement rule, which
+    /// leaves the settler out of both sides, is met by any approval that
+    /// counted the initiator's own vote.
+    pub fn verify_quorum(
+        &self,
+        stakes: &HashMap<String, u64>,
+        registry_total: u64,
+    ) -> Result<(), CertificateError> {
+        let counted = self
+            .wallets()
+            .filter_map(|wallet| stakes.get(wallet))
+            .fold(0u64, |sum, stake| sum.saturating_add(*stake));
+        if registry_total == 0
+            || counted > registry_total
+            || counted <= registry_total.saturating_mul(2) / 3
+        {
+            return Err(CertificateError::QuorumNotMet {
+                counted,
+                total: registry_total,
+            });
+        }
+        Ok(())
+    }
+
+    /// Offline check: the signatures verify for `program_id` on
+    /// `cluster_tag`, and the approving wallets are a supermajority of the
+    /// stake snapshot the certificate carries.
+    pub fn verify(
+        &self,
+        program_id: &str,
+        cluster_tag: &str,
+        signature_is_valid: impl Fn(&str, &[u8], &[u8]) -> bool,
+    ) -> Result<(), CertificateError> {
+        self.verify_signatures(program_id, cluster_tag, signature_is_valid)?;
+        let stakes: HashMap<String, u64> = self.stakes.iter().cloned().collect();
+        self.verify_quorum(&stakes, self.registry_total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::VerificationVote;
+    use crate::types::NodeId;
+
+    // Stand-in signer, as in the slashing tests: a "signature" is the byte sum
+    // of the message, so the check depends on exactly which bytes were signed.
+    fn digest(msg: &[u8]) -> Vec<u8> {
+        vec![msg.iter().fold(0u8, |a, b| a.wrapping_add(*b))]
+    }
+
+    fn verify(_: &str, sig: &[u8], msg: &[u8]) -> bool {
+        sig == digest(msg)
+    }
+
+    fn vote(request_id: &str, wallet: &str, node: u8) -> TransactVerificationResult {
+        let validator = NodeId(vec![node]);
+        let bytes = transact_vote_signing_bytes(
+            "prog",
+            "devnet",
+            request_id,
+            0,
+            &validator,
+            &VerificationVote::Valid,
+            wallet,
+        );
+        TransactVerificationResult {
+            request_id: request_id.to_string(),
+            validator,
+            vote: VerificationVote::Valid,
+            timestamp: 0,
+            wallet_pubkey: wallet.to_string(),
+            signature: digest(&bytes),
+        }
+    }
+
+    fn certificate(wallets: &[&str]) -> QuorumCertificate {
+        let stakes: HashMap<String, u64> = ["W1", "W2", "W3", "W4"]
+            .iter()
+            .map(|w| (w.to_string(), 100))
+            .collect();
+        let votes = wallets
+            .iter()
+            .enumerate()
+            .map(|(i, w)| vote("r1", w, i as u8))
+            .collect();
+        QuorumCertificate::new(
+            "r1".to_string(),
+            "prog".to_string(),
+            "devnet".to_string(),
+            0,
+            &stakes,
+            400,
+            votes,
+        )
+    }
+
+    #[test]
+    fn a_signed_supermajority_verifies_offline() {
+        let cert = certificate(&["W3", "W1", "W2"]);
+        assert_eq!(cert.wallets().collect::<Vec<_>>(), vec!["W1", "W2", "W3"]);
+        assert_eq!(cert.verify("prog", "devnet", verify), Ok(()));
+
+        // It survives the trip an auditor's copy takes.
+        let json = serde_json::to_string(&cert).unwrap();
+        let back: QuorumCertificate = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.verify("prog", "devnet", verify), Ok(()));
+
+        assert!(matches!(
+            cert.verify("prog", "mainnet", verify),
+            Err(CertificateError::WrongCluster { .. })
+        ));
+    }
+
+    #[test]
+    fn two_thirds_exactly_is_not_a_quorum() {
+        // 3 × 100 of 450 is exactly two thirds.
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.registry_total = 450;
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::QuorumNotMet {
+                counted: 300,
+                total: 450
+            })
+        );
+
+        // A validator counts by its own view: W3 unknown there leaves 200.
+        let cert = certificate(&["W1", "W2", "W3"]);
+        let local: HashMap<String, u64> = [("W1".to_string(), 100), ("W2".to_string(), 100)].into();
+        assert_eq!(
+            cert.verify_quorum(&local, 300),
+            Err(CertificateError::QuorumNotMet {
+                counted: 200,
+                total: 300
+            })
+        );
+    }
+
+    #[test]
+    fn tampered_votes_are_refused() {
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.votes[1].validator = NodeId(vec![9]);
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::BadSignature("W2".to_string()))
+        );
+
+        // Votes counted in one epoch cannot be passed off as another's.
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.epoch = 1;
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::BadSignature("W1".to_string()))
+        );
+
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.votes[2] = vote("r2", "W3", 2);
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::ForeignVote("W3".to_string()))
+        );
+
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.votes[2] = cert.votes[1].clone();
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::DuplicateWallet("W2".to_string()))
+        );
+
+        let mut cert = certificate(&["W1", "W2", "W3"]);
+        cert.votes[0].vote = VerificationVote::Invalid {
+            reason: "flip".to_string(),
+        };
+        assert_eq!(
+            cert.verify("prog", "devnet", verify),
+            Err(CertificateError::NotValid("W1".to_string()))
+        );
+
+        assert_eq!(
+            certificate(&[]).verify("prog", "devnet", verify),
+            Err(CertificateError::NoVotes)
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! Stake epochs
+//!
+//! The validator set and stakes a transact is tallied against are fixed per
+//! epoch: a run of [`EPOCH_SLOTS`] Solana slots. A request is pinned to the
+//! epoch its slot falls in and counted against that epoch's snapshot.
+//!
+//! A snapshot is the chain at the boundary slot, not at the moment a node
+//! reads it. The program's first stake change in an epoch records the stake
+//! that stood when the epoch began, on the validator's account and on the
+//! registry, so a node reading later in the epoch still recovers the
+//! boundary values ([`StakeRecord::at_epoch_start`]). Two validators
+//! snapshotting the same epoch therefore hold the same wallets and the same
+//! two-thirds threshold, whichever tick they read it on.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Slots per stake epoch: about an hour at 400ms slots. Must equal the
+/// program's `STAKE_EPOCH_SLOTS`, which decides where its boundary records
+/// fall.
+pub const EPOCH_SLOTS: u64 = 9_000;
+
+/// Snapshots kept; older epochs are dropped, and a request naming one can no
+/// longer be tallied.
+pub const RETAINED_EPOCHS: usize = 4;
+
+/// An on-chain stake as the program records it: the current value, the
+/// epoch of its last change, and the value that stood when that epoch began.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StakeRecord {
+    pub current: u64,
+    pub changed_epoch: u64,
+    pub changed_epoch_start: u64,
+}
+
+impl StakeRecord {
+    /// A record with no change logged, for an account on a layout that
+    /// predates the epoch fields. The program cannot write such an account
+    /// until it is migrated, so its current value stood at every boundary.
+    pub fn unchanged(current: u64) -> Self {
+        Self {
+            current,
+            changed_epoch: 0,
+            changed_epoch_start: current,
+        }
+    }
+
+    /// The value that stood when `epoch` began, or `None` if it has changed
+    /// in a later epoch, which overwrote that value.
+    pub fn at_epoch_start(&self, epoch: u64) -> Option<u64> {
+        match epoch.cmp(&self.changed_epoch) {
+            std::cmp::Ordering::Greater => Some(self.current),
+            std::cmp::Ordering::Equal => Some(self.changed_epoch_start),
+            std::cmp::Ordering::Less => None,
+        }
+    }
+}
+
+/// The on-chain validator set as snapshotted at one epoch boundary.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochSnapshot {
+    pub epoch: u64,
+    /// Co-sign wallet (base58) -> active on-chain stake.
+    pub stakes: HashMap<String, u64>,
+    /// `ValidatorRegistry.total_active_stake` read with `stakes`.
+    pub registry_total: u64,
+}
+
+/// Stake snapshots keyed by epoch.
+#[derive(Debug)]
+pub struct StakeEpochs {
+    epoch_slots: u64,
+    snapshots: BTreeMap<u64, EpochSnapshot>,
+}
+
+impl StakeEpochs {
+    /// Epochs of `epoch_slots` slots; `0` takes [`EPOCH_SLOTS`].
+    pub fn new(epoch_slots: u64) -> Self {
+        Self {
+            epoch_slots: if epoch_slots == 0 {
+                EPOCH_SLOTS
+            } else {
+                epoch_slots
+            },
+            snapshots: BTreeMap::new(),
+        }
+    }
+
+    pub fn epoch_slots(&self) -> u64 {
+        self.epoch_slots
+    }
+
+    /// The epoch `slot` falls in.
+    pub fn epoch_of(&self, slot: u64) -> u64 {
+        slot / self.epoch_slots
+    }
+
+    /// Record the snapshot for `epoch`. The first snapshot of an epoch is
+    /// final, so the set a request is tallied against cannot move under it;
+    /// any later read of the same boundary yields the same set anyway. An
+    /// empty stake map (an RPC that returned nothing) is never recorded.
+    /// Returns whether it was new.
+    pub fn record(
+        &mut self,
+        epoch: u64,
+        stakes: HashMap<String, u64>,
+        registry_total: u64,
+    ) -> bool {
+        if stakes.is_empty() || self.snapshots.contains_key(&epoch) {
+            return false;
+        }
+        if self.snapshots.len() >= RETAINED_EPOCHS
+            && self
+                .snapshots
+                .keys()
+                .next()
+                .is_some_and(|oldest| epoch < *oldest)
+        {
+            return false;
+        }
+        self.snapshots.insert(
+            epoch,
+            EpochSnapshot {
+                epoch,
+                stakes,
+                registry_total,
+            },
+        );
+        while self.snapshots.len() > RETAINED_EPOCHS {
+            self.snapshots.pop_first();
+        }
+        true
+    }
+
+    pub fn get(&self, epoch: u64) -> Option<&EpochSnapshot> {
+        self.snapshots.get(&epoch)
+    }
+
+    /// The newest snapshot held.
+    pub fn latest(&self) -> Option<&EpochSnapshot> {
+        self.snapshots.values().next_back()
+    }
+
+    /// Whether a request pinned to `epoch` can be tallied here: its snapshot
+    /// is held, or it is the epoch after the newest one and this node has not
+    /// reached the boundary yet. Always true before the first snapshot.
+    pub fn admits(&self, epoch: u64) -> bool {
+        match self.latest() {
+            Some(latest) => self.snapshots.contains_key(&epoch) || epoch == latest.epoch + 1,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stakes(pairs: &[(&str, u64)]) -> HashMap<String, u64> {
+        pairs.iter().map(|(w, s)| (w.to_string(), *s)).collect()
+    }
+
+    #[test]
+    fn the_first_snapshot_of_an_epoch_is_final() {
+        let mut epochs = StakeEpochs::new(100);
+        assert_eq!(epochs.epoch_of(99), 0);
+        assert_eq!(epochs.epoch_of(100), 1);
+
+        assert!(!epochs.record(1, HashMap::new(), 0));
+        assert!(epochs.record(1, stakes(&[("W1", 10)]), 10));
+        assert!(!epochs.record(1, stakes(&[("W1", 10), ("W2", 90)]), 100));
+        assert_eq!(epochs.get(1).unwrap().registry_total, 10);
+
+        assert!(epochs.record(2, stakes(&[("W2", 5)]), 5));
+        assert_eq!(epochs.latest().unwrap().epoch, 2);
+        assert_eq!(epochs.get(1).unwrap().stakes, stakes(&[("W1", 10)]));
+    }
+
+    #[test]
+    fn a_record_yields_the_stake_at_an_epoch_boundary() {
+        // Changed in epoch 5 from 40 to 70.
+        let record = StakeRecord {
+            current: 70,
+            changed_epoch: 5,
+            changed_epoch_start: 40,
+        };
+        assert_eq!(record.at_epoch_start(5), Some(40));
+        assert_eq!(record.at_epoch_start(6), Some(70));
+        assert_eq!(record.at_epoch_start(4), None, "overwritten by the change");
+
+        let legacy = StakeRecord::unchanged(25);
+        assert_eq!(legacy.at_epoch_start(0), Some(25));
+        assert_eq!(legacy.at_epoch_start(9), Some(25));
+    }
+
+    #[test]
+    fn only_the_newest_epochs_are_kept() {
+        let mut epochs = StakeEpochs::new(0);
+        assert_eq!(epochs.epoch_slots(), EPOCH_SLOTS);
+        assert!(epochs.admits(7), "anything goes before the first snapshot");
+
+        for epoch in 10..10 + RETAINED_EPOCHS as u64 + 1 {
+            assert!(epochs.record(epoch, stakes(&[("W1", epoch)]), epoch));
+        }
+        assert!(epochs.get(10).is_none());
+        assert!(
+            !epochs.record(10, stakes(&[("W1", 1)]), 1),
+            "a pruned epoch is not brought back"
+        );
+
+        let newest = 10 + RETAINED_EPOCHS as u64;
+        assert!(epochs.admits(newest));
+        assert!(epochs.admits(newest + 1));
+        assert!(!epochs.admits(newest + 2));
+        assert!(!epochs.admits(10));
+    }
+}
@@ -4,6 +4,12 @@
 //! - Validator stake amount
 //! - Validator reputation score
 //! - Deterministic randomness (all validators agree on the same leader)
+//!
+//! A settlement has its own [`LeaderSchedule`]: the approving wallets in the
+//! order they try to settle one approved request, drawn by stake from a seed
+//! of the request id and a finalized Solana slot hash. Every node that sees
+//! the same approval and anchor computes the same order, so a backup knows
+//! when the primary has had its turn.
 
 use crate::types::NodeId;
 use anyhow::{anyhow, Result};
@@ -279,6 +285,108 @@ impl Default for LeaderSelector {
     }
 }
 
+/// Domain tag of the per-position schedule seed.
+const SCHEDULE_DOMAIN: &[u8] = b"paraloom:settlement-leader:v1";
+
+/// The order in which co-sign wallets lead the settlement of one approved
+/// request: `order[0]` is the primary, the rest are backups in turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LeaderSchedule {
+    pub request_id: String,
+    /// The finalized slot whose hash seeds the draw.
+    pub anchor_slot: u64,
+    order: Vec<String>,
+}
+
+impl LeaderSchedule {
+    /// Draw the schedule from `candidates` (`(wallet, stake)`), without
+    /// replacement and weighted by stake. Position `i` is drawn from the
+    /// candidates not yet placed for which `can_lead(wallet, &order[..i])`
+    /// holds — a backup has to be able to settle without everyone ranked
+    /// ahead of it, since it only takes over once they have failed. The draw
+    /// stops when no candidate qualifies.
+    ///
+    /// Candidates are sorted by wallet first, so the result depends only on
+    /// the inputs and not on how the caller collected them. When every
+    /// remaining candidate has zero stake they are drawn with equal weight.
+    pub fn compute<F>(
+        request_id: &str,
+        anchor_slot: u64,
+        slot_hash: &[u8; 32],
+        candidates: &[(String, u64)],
+        can_lead: F,
+    ) -> Self
+    where
+        F: Fn(&str, &[String]) -> bool,
+    {
+        let mut remaining: Vec<(String, u64)> = candidates.to_vec();
+        remaining.sort();
+        remaining.dedup_by(|a, b| a.0 == b.0);
+
+        let mut order: Vec<String> = Vec::with_capacity(remaining.len());
+        loop {
+            let eligible: Vec<(String, u64)> = remaining
+                .iter()
+                .filter(|(wallet, _)| can_lead(wallet, &order))
+                .cloned()
+                .collect();
+            if eligible.is_empty() {
+                break;
+            }
+            let staked: u128 = eligible.iter().map(|(_, s)| *s as u128).sum();
+            let weight = |stake: u64| if staked == 0 { 1 } else { stake as u128 };
+            let total: u128 = eligible.iter().map(|(_, s)| weight(*s)).sum();
+
+            let seed = Self::seed(request_id, anchor_slot, slot_hash, order.len() as u64);
+            let pick = LeaderSelector::deterministic_random(&seed, total);
+            let mut cumulative = 0u128;
+            let chosen = eligible
+                .iter()
+                .find(|(_, stake)| {
+                    cumulative += weight(*stake);
+                    pick < cumulative
+                })
+                .map(|(wallet, _)| wallet.clone())
+                .expect("pick < total, so some cumulative weight exceeds it");
+
+            remaining.retain(|(wallet, _)| *wallet != chosen);
+            order.push(chosen);
+        }
+
+        Self {
+            request_id: request_id.to_string(),
+            anchor_slot,
+            order,
+        }
+    }
+
+    fn seed(request_id: &str, anchor_slot: u64, slot_hash: &[u8; 32], position: u64) -> Vec<u8> {
+        let mut seed = Vec::with_capacity(SCHEDULE_DOMAIN.len() + 8 + request_id.len() + 48);
+        seed.extend_from_slice(SCHEDULE_DOMAIN);
+        seed.extend_from_slice(&(request_id.len() as u64).to_le_bytes());
+        seed.extend_from_slice(request_id.as_bytes());
+        seed.extend_from_slice(&anchor_slot.to_le_bytes());
+        seed.extend_from_slice(slot_hash);
+        seed.extend_from_slice(&position.to_le_bytes());
+        seed
+    }
+
+    /// The wallet that settles first, if anyone can.
+    pub fn primary(&self) -> Option<&str> {
+        self.order.first().map(String::as_str)
+    }
+
+    /// `wallet`'s turn: 0 for the primary, `k` for the `k`-th backup.
+    pub fn rank(&self, wallet: &str) -> Option<usize> {
+        self.order.iter().position(|w| w == wallet)
+    }
+
+    /// Every leader in turn.
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -479,6 +587,71 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn candidates(pairs: &[(&str, u64)]) -> Vec<(String, u64)> {
+        pairs.iter().map(|(w, s)| (w.to_string(), *s)).collect()
+    }
+
+    #[test]
+    fn schedule_is_a_function_of_request_and_anchor_only() {
+        let forward = candidates(&[("W1", 10), ("W2", 20), ("W3", 30)]);
+        let mut shuffled = forward.clone();
+        shuffled.reverse();
+
+        let a = LeaderSchedule::compute("req", 7, &[1; 32], &forward, |_, _| true);
+        let b = LeaderSchedule::compute("req", 7, &[1; 32], &shuffled, |_, _| true);
+        assert_eq!(a, b, "input order must not matter");
+
+        let mut sorted = a.order().to_vec();
+        sorted.sort();
+        assert_eq!(
+            sorted,
+            vec!["W1", "W2", "W3"],
+            "every candidate gets a turn"
+        );
+        assert_eq!(a.rank(a.primary().unwrap()), Some(0));
+        assert_eq!(a.rank("W4"), None);
+
+        // A different anchor reshuffles some schedule among a handful of ids.
+        let moved = (0..16).any(|i| {
+            let id = format!("req-{i}");
+            LeaderSchedule::compute(&id, 7, &[1; 32], &forward, |_, _| true).order()
+                != LeaderSchedule::compute(&id, 8, &[2; 32], &forward, |_, _| true).order()
+        });
+        assert!(moved);
+    }
+
+    #[test]
+    fn schedule_primary_is_drawn_by_stake() {
+        let pool = candidates(&[("W1", 10), ("W2", 90)]);
+        let heavy = (0..1000)
+            .filter(|i| {
+                let id = format!("req-{i}");
+                LeaderSchedule::compute(&id, 1, &[9; 32], &pool, |_, _| true).primary()
+                    == Some("W2")
+            })
+            .count();
+        assert!(heavy > 800 && heavy < 980, "W2 led {heavy} of 1000");
+    }
+
+    #[test]
+    fn a_backup_must_be_able_to_lead_without_the_ranks_ahead() {
+        let pool = candidates(&[("W1", 1), ("W2", 1), ("W3", 1)]);
+        // Nobody can lead once two wallets are out of the running.
+        let schedule =
+            LeaderSchedule::compute("req", 1, &[3; 32], &pool, |_, ahead| ahead.len() < 2);
+        assert_eq!(schedule.order().len(), 2);
+
+        // W3 can only ever lead first.
+        let schedule = LeaderSchedule::compute("req", 1, &[3; 32], &pool, |wallet, ahead| {
+            wallet != "W3" || ahead.is_empty()
+        });
+        let rank = schedule.rank("W3");
+        assert!(rank.is_none() || rank == Some(0));
+
+        let nobody = LeaderSchedule::compute("req", 1, &[3; 32], &pool, |_, _| false);
+        assert_eq!(nobody.primary(), None);
+    }
+
     #[test]
     fn test_unregister_validator() {
         let mut selector = LeaderSelector::new();
@@ -1,19 +1,28 @@
 //! Consensus mechanism for distributed validator network
 //!
 //! Handles withdrawal verification consensus, leader selection,
-//! reputation tracking, and validator coordination.
+//! reputation tracking, stake epochs, and validator coordination.
 
+pub mod certificate;
+pub mod epoch;
 pub mod leader;
 pub mod reputation;
 pub mod slashing;
 pub mod transact;
+pub mod verify_queue;
 pub mod vote_tally;
 
-pub use leader::{LeaderSelector, ValidatorInfo};
+pub use certificate::{CertificateError, QuorumCertificate};
+pub use epoch::{EpochSnapshot, StakeEpochs};
+pub use leader::{LeaderSchedule, LeaderSelector, ValidatorInfo};
 pub use reputation::{ReputationTracker, ValidatorMetrics};
-pub use slashing::{SlashingEvidence, SlashingRecord, SlashingTracker};
+pub use slashing::{
+    verify_equivocation, EvidenceError, SlashStatus, SlashingEvidence, SlashingRecord,
+    SlashingTracker,
+};
 pub use transact::{
     ApprovedTransact, TransactVerificationCoordinator, TransactVerificationRequest,
     TransactVerificationResult,
 };
+pub use verify_queue::TransactVerificationQueue;
 pub use vote_tally::{VerificationVote, VoteTally};
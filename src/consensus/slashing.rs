@@ -16,16 +16,23 @@
 //!     timeout is a network blip; a streak of timeouts is a validator
 //!     that is offline or otherwise failing to do its job.
 //!
-//! Persisting the evidence to RocksDB is out of scope for this PR —
-//! the in-memory store is enough to drive the integration test and the
-//! eventual on-chain slashing call. The store is `Send + Sync` so a
-//! caller can park it inside an `Arc` and share it across coordinator
-//! threads.
+//! Equivocation evidence is written through to a
+//! [`SlashingStorage`] when the tracker has
+//! one, keyed by `(wallet, request_id)`: however many times a wallet flips
+//! its vote on one request, that is one offence and one slash. A node
+//! holding the registry authority turns each pending entry into an on-chain
+//! `slash_validator` call once [`verify_equivocation`] accepts it.
+//! Unavailability evidence stays in memory — the program has no way to check
+//! it, so there is nothing to submit. The store is `Send + Sync` so a caller
+//! can park it inside an `Arc` and share it across coordinator threads.
 
+use crate::consensus::transact::transact_vote_signing_bytes;
 use crate::consensus::vote_tally::VerificationVote;
+use crate::storage::SlashingStorage;
 use crate::types::NodeId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// A piece of evidence that a validator deserves slashing.
@@ -39,10 +46,15 @@ pub enum SlashingEvidence {
     /// `previous_vote` was already recorded when `new_vote` arrived. Attributed
     /// to the stable co-sign `wallet_pubkey` (undodgeable by rotating NodeId),
     /// and non-repudiable: the two conflicting ed25519 vote signatures ARE the
-    /// proof.
+    /// proof. Each signature also covers the NodeId the vote came from, so
+    /// the previous vote's sender is kept; the new one is the record's
+    /// `validator`. Both also cover the stake `epoch` the round was counted
+    /// in.
     Equivocation {
         request_id: String,
+        epoch: u64,
         wallet_pubkey: String,
+        previous_validator: NodeId,
         previous_vote: VerificationVote,
         new_vote: VerificationVote,
         previous_signature: Vec<u8>,
@@ -56,6 +68,51 @@ pub enum SlashingEvidence {
     PersistentUnavailability { streak_length: u64, threshold: u64 },
 }
 
+impl SlashingEvidence {
+    /// `(wallet, request_id)` for equivocation — the key evidence is
+    /// deduplicated and persisted under. `None` for other evidence.
+    pub fn equivocation_key(&self) -> Option<(&str, &str)> {
+        match self {
+            SlashingEvidence::Equivocation {
+                request_id,
+                wallet_pubkey,
+                ..
+            } => Some((wallet_pubkey, request_id)),
+            SlashingEvidence::PersistentUnavailability { .. } => None,
+        }
+    }
+
+    /// The id `slash_validator` records this equivocation under on chain:
+    /// `SHA-256("paraloom/slash/equivocation" || request_id)`. Together with
+    /// the wallet it seeds the program's slash-record PDA, so one offence can
+    /// only ever be slashed once. `None` for other evidence.
+    pub fn evidence_id(&self) -> Option<[u8; 32]> {
+        use sha2::{Digest, Sha256};
+        let (_, request_id) = self.equivocation_key()?;
+        let mut hasher = Sha256::new();
+        hasher.update(b"paraloom/slash/equivocation");
+        hasher.update(request_id.as_bytes());
+        Some(hasher.finalize().into())
+    }
+}
+
+/// Where a record stands in the on-chain slashing pipeline.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum SlashStatus {
+    /// Recorded, not yet acted on.
+    #[default]
+    Pending,
+    /// `slash_validator` landed in transaction `signature`.
+    Submitted { signature: String },
+    /// The evidence did not verify and is never submitted.
+    Rejected { reason: String },
+    /// `slash_validator` was signed as `signature` and is being broadcast.
+    /// Written before the send, so a timeout or crash leaves a record the
+    /// next tick reconciles against the chain's slash record instead of
+    /// slashing blind. Kept last so stored records keep their encoding.
+    Submitting { signature: String },
+}
+
 /// One entry in the slashing log.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SlashingRecord {
@@ -63,6 +120,8 @@ pub struct SlashingRecord {
     pub evidence: SlashingEvidence,
     /// Unix-seconds timestamp at which the evidence was recorded.
     pub recorded_at: u64,
+    #[serde(default)]
+    pub status: SlashStatus,
 }
 
 impl SlashingRecord {
@@ -75,11 +134,79 @@ impl SlashingRecord {
             validator,
             evidence,
             recorded_at,
+            status: SlashStatus::Pending,
         }
     }
 }
 
-/// In-memory log of slashing evidence.
+/// Why equivocation evidence was refused.
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum EvidenceError {
+    #[error("not equivocation evidence")]
+    NotEquivocation,
+    #[error("the two votes agree")]
+    VotesAgree,
+    #[error("the {0} vote's signature does not verify")]
+    BadSignature(&'static str),
+}
+
+/// Check that `record` proves equivocation: its two votes disagree, and each
+/// signature verifies under the wallet over the
+/// [`transact_vote_signing_bytes`] its sender would have signed on this
+/// cluster. This is what makes the evidence safe to slash on — a node cannot
+/// frame a wallet with votes it never signed.
+///
+/// `signature_is_valid(wallet, signature, message)` is the ed25519 check,
+/// passed in so this module stays free of the Solana SDK.
+pub fn verify_equivocation(
+    record: &SlashingRecord,
+    program_id: &str,
+    cluster_tag: &str,
+    signature_is_valid: impl Fn(&str, &[u8], &[u8]) -> bool,
+) -> Result<(), EvidenceError> {
+    let SlashingEvidence::Equivocation {
+        request_id,
+        epoch,
+        wallet_pubkey,
+        previous_validator,
+        previous_vote,
+        new_vote,
+        previous_signature,
+        new_signature,
+    } = &record.evidence
+    else {
+        return Err(EvidenceError::NotEquivocation);
+    };
+    if previous_vote.is_valid() == new_vote.is_valid() {
+        return Err(EvidenceError::VotesAgree);
+    }
+    let votes = [
+        (
+            "previous",
+            previous_validator,
+            previous_vote,
+            previous_signature,
+        ),
+        ("new", &record.validator, new_vote, new_signature),
+    ];
+    for (which, validator, vote, signature) in votes {
+        let bytes = transact_vote_signing_bytes(
+            program_id,
+            cluster_tag,
+            request_id,
+            *epoch,
+            validator,
+            vote,
+            wallet_pubkey,
+        );
+        if !signature_is_valid(wallet_pubkey, signature, &bytes) {
+            return Err(EvidenceError::BadSignature(which));
+        }
+    }
+    Ok(())
+}
+
+/// Log of slashing evidence, in memory and optionally persisted.
 ///
 /// Keyed by validator so a downstream slashing pipeline can iterate
 /// per-validator without re-grouping. Each validator's entries are
@@ -87,6 +214,7 @@ impl SlashingRecord {
 #[derive(Default)]
 pub struct SlashingTracker {
     records: RwLock<HashMap<NodeId, Vec<SlashingRecord>>>,
+    storage: Option<Arc<SlashingStorage>>,
 }
 
 impl SlashingTracker {
@@ -94,9 +222,44 @@ impl SlashingTracker {
         Self::default()
     }
 
+    /// A tracker that writes equivocation evidence through to `storage`,
+    /// starting from what it already holds.
+    pub fn with_storage(storage: Arc<SlashingStorage>) -> anyhow::Result<Self> {
+        let mut records: HashMap<NodeId, Vec<SlashingRecord>> = HashMap::new();
+        for record in storage.all()? {
+            records
+                .entry(record.validator.clone())
+                .or_default()
+                .push(record);
+        }
+        Ok(Self {
+            records: RwLock::new(records),
+            storage: Some(storage),
+        })
+    }
+
     /// Append a record for `validator`. Logs at `warn` so the entry is
     /// discoverable even before a metrics endpoint is wired up (#67).
-    pub async fn record(&self, validator: NodeId, evidence: SlashingEvidence) {
+    ///
+    /// Equivocation already on file for the same `(wallet, request_id)` is
+    /// dropped. Returns whether the evidence was new.
+    pub async fn record(&self, validator: NodeId, evidence: SlashingEvidence) -> bool {
+        let mut records = self.records.write().await;
+        if let Some(key) = evidence.equivocation_key() {
+            let seen = records
+                .values()
+                .flatten()
+                .any(|r| r.evidence.equivocation_key() == Some(key));
+            if seen {
+                log::debug!(
+                    target: "paraloom::consensus::slashing",
+                    "equivocation by {} on {} already recorded",
+                    key.0,
+                    key.1
+                );
+                return false;
+            }
+        }
         log::warn!(
             target: "paraloom::consensus::slashing",
             "slashing evidence recorded for {:?}: {:?}",
@@ -104,12 +267,61 @@ impl SlashingTracker {
             evidence
         );
         let record = SlashingRecord::new(validator.clone(), evidence);
-        self.records
-            .write()
+        if let (Some(storage), Some(_)) = (&self.storage, record.evidence.equivocation_key()) {
+            // The in-memory copy still drives this run; only a restart would
+            // lose the entry.
+            if let Err(e) = storage.insert(&record) {
+                log::warn!(
+                    target: "paraloom::consensus::slashing",
+                    "could not persist slashing evidence: {}",
+                    e
+                );
+            }
+        }
+        records.entry(validator).or_default().push(record);
+        true
+    }
+
+    /// Equivocation records not yet confirmed or rejected — fresh ones and
+    /// those whose submission is unconfirmed — oldest first.
+    pub async fn pending(&self) -> Vec<SlashingRecord> {
+        let mut pending: Vec<SlashingRecord> = self
+            .records
+            .read()
             .await
-            .entry(validator)
-            .or_default()
-            .push(record);
+            .values()
+            .flatten()
+            .filter(|r| {
+                matches!(
+                    r.status,
+                    SlashStatus::Pending | SlashStatus::Submitting { .. }
+                ) && r.evidence.equivocation_key().is_some()
+            })
+            .cloned()
+            .collect();
+        pending.sort_by_key(|r| r.recorded_at);
+        pending
+    }
+
+    /// Move the equivocation record for `(wallet, request_id)` to `status`,
+    /// in memory and on disk.
+    pub async fn set_status(
+        &self,
+        wallet: &str,
+        request_id: &str,
+        status: SlashStatus,
+    ) -> anyhow::Result<()> {
+        let mut records = self.records.write().await;
+        let record = records
+            .values_mut()
+            .flatten()
+            .find(|r| r.evidence.equivocation_key() == Some((wallet, request_id)))
+            .ok_or_else(|| anyhow::anyhow!("no evidence for {wallet} on {request_id}"))?;
+        record.status = status;
+        if let Some(storage) = &self.storage {
+            storage.insert_or_replace(record)?;
+        }
+        Ok(())
     }
 
     /// All records for a single validator, in insertion order.
@@ -149,7 +361,9 @@ mod tests {
                 alice.clone(),
                 SlashingEvidence::Equivocation {
                     request_id: "r1".to_string(),
+                    epoch: 0,
                     wallet_pubkey: "W1".to_string(),
+                    previous_validator: NodeId(vec![3]),
                     previous_vote: VerificationVote::Valid,
                     new_vote: VerificationVote::Invalid {
                         reason: "test".to_string(),
@@ -194,4 +408,167 @@ mod tests {
         assert!(tracker.for_validator(&unknown).await.is_empty());
         assert_eq!(tracker.total_count().await, 0);
     }
+
+    fn equivocation(request_id: &str, wallet: &str) -> SlashingEvidence {
+        SlashingEvidence::Equivocation {
+            request_id: request_id.to_string(),
+            epoch: 0,
+            wallet_pubkey: wallet.to_string(),
+            previous_validator: NodeId(vec![1]),
+            previous_vote: VerificationVote::Valid,
+            new_vote: VerificationVote::Invalid {
+                reason: "flip".to_string(),
+            },
+            previous_signature: vec![1],
+            new_signature: vec![2],
+        }
+    }
+
+    #[tokio::test]
+    async fn equivocation_is_recorded_once_per_wallet_and_request() {
+        let tracker = SlashingTracker::new();
+        assert!(
+            tracker
+                .record(NodeId(vec![1]), equivocation("r1", "W1"))
+                .await
+        );
+        // A later flip on the same request, even from another NodeId, is the
+        // same offence.
+        assert!(
+            !tracker
+                .record(NodeId(vec![2]), equivocation("r1", "W1"))
+                .await
+        );
+        assert!(
+            tracker
+                .record(NodeId(vec![1]), equivocation("r2", "W1"))
+                .await
+        );
+        assert!(
+            tracker
+                .record(NodeId(vec![1]), equivocation("r1", "W2"))
+                .await
+        );
+        assert_eq!(tracker.total_count().await, 3);
+
+        tracker
+            .set_status(
+                "W1",
+                "r2",
+                SlashStatus::Submitted {
+                    signature: "sig".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        let pending = tracker.pending().await;
+        assert_eq!(pending.len(), 2);
+        assert!(pending
+            .iter()
+            .all(|r| r.evidence.equivocation_key() != Some(("W1", "r2"))));
+
+        // An unconfirmed submission stays on the worklist to be reconciled.
+        tracker
+            .set_status(
+                "W1",
+                "r1",
+                SlashStatus::Submitting {
+                    signature: "sig".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(tracker.pending().await.len(), 2);
+    }
+
+    #[test]
+    fn evidence_id_is_per_request() {
+        let id = equivocation("r1", "W1").evidence_id().unwrap();
+        // The wallet is a separate PDA seed, so the id only names the request.
+        assert_eq!(equivocation("r1", "W2").evidence_id(), Some(id));
+        assert_ne!(equivocation("r2", "W1").evidence_id(), Some(id));
+        assert_eq!(
+            SlashingEvidence::PersistentUnavailability {
+                streak_length: 3,
+                threshold: 3,
+            }
+            .evidence_id(),
+            None
+        );
+    }
+
+    #[test]
+    fn equivocation_verifies_only_with_both_signatures_over_the_vote_bytes() {
+        let record = SlashingRecord::new(NodeId(vec![2]), equivocation("r1", "W1"));
+        // Stand-in signer: a "signature" is the first byte of the message
+        // digest, so the check depends on exactly which bytes were signed.
+        let sign = |validator: &NodeId, vote: &VerificationVote| {
+            let bytes =
+                transact_vote_signing_bytes("prog", "devnet", "r1", 0, validator, vote, "W1");
+            vec![bytes.iter().fold(0u8, |a, b| a.wrapping_add(*b))]
+        };
+        let verify = |_: &str, sig: &[u8], msg: &[u8]| {
+            sig == [msg.iter().fold(0u8, |a, b| a.wrapping_add(*b))]
+        };
+
+        let mut signed = record.clone();
+        if let SlashingEvidence::Equivocation {
+            previous_vote,
+            new_vote,
+            previous_signature,
+            new_signature,
+            ..
+        } = &mut signed.evidence
+        {
+            *previous_signature = sign(&NodeId(vec![1]), previous_vote);
+            *new_signature = sign(&NodeId(vec![2]), new_vote);
+        }
+        assert_eq!(
+            verify_equivocation(&signed, "prog", "devnet", verify),
+            Ok(())
+        );
+        // Bound to the cluster it was signed on.
+        assert_eq!(
+            verify_equivocation(&signed, "prog", "mainnet-beta", verify),
+            Err(EvidenceError::BadSignature("previous"))
+        );
+
+        // Bound to the epoch it was counted in.
+        let mut other_epoch = signed.clone();
+        if let SlashingEvidence::Equivocation { epoch, .. } = &mut other_epoch.evidence {
+            *epoch = 1;
+        }
+        assert_eq!(
+            verify_equivocation(&other_epoch, "prog", "devnet", verify),
+            Err(EvidenceError::BadSignature("previous"))
+        );
+
+        let mut reattributed = signed.clone();
+        reattributed.validator = NodeId(vec![9]);
+        assert_eq!(
+            verify_equivocation(&reattributed, "prog", "devnet", verify),
+            Err(EvidenceError::BadSignature("new"))
+        );
+
+        let mut agreeing = signed;
+        if let SlashingEvidence::Equivocation { new_vote, .. } = &mut agreeing.evidence {
+            *new_vote = VerificationVote::Valid;
+        }
+        assert_eq!(
+            verify_equivocation(&agreeing, "prog", "devnet", verify),
+            Err(EvidenceError::VotesAgree)
+        );
+
+        let unavailable = SlashingRecord::new(
+            NodeId(vec![2]),
+            SlashingEvidence::PersistentUnavailability {
+                streak_length: 3,
+                threshold: 3,
+            },
+        );
+        assert_eq!(
+            verify_equivocation(&unavailable, "prog", "devnet", verify),
+            Err(EvidenceError::NotEquivocation)
+        );
+    }
 }
@@ -13,7 +13,8 @@
 //! trackers are reused as-is, so a validator's standing is consistent across
 //! all verification paths.
 
-use crate::consensus::leader::{LeaderSelector, ValidatorInfo};
+use crate::consensus::epoch::{EpochSnapshot, StakeEpochs};
+use crate::consensus::leader::{LeaderSchedule, LeaderSelector, ValidatorInfo};
 use crate::consensus::reputation::ReputationTracker;
 use crate::consensus::slashing::SlashingTracker;
 use crate::consensus::vote_tally::{VerificationVote, VoteTally};
@@ -38,6 +39,17 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 
+/// How far, in slots, a request's [`slot`](TransactVerificationRequest::slot)
+/// may sit from a validator's own view of the chain before the validator
+/// refuses to vote on it (about a minute at 400 ms slots). The slot is the
+/// ingress node's claim and no signature covers it, so without a bound a peer
+/// could pick the stake epoch a request is tallied in.
+pub const MAX_REQUEST_SLOT_SKEW: u64 = 150;
+
+/// Nominal Solana slot time, used to advance the last slot read from the
+/// chain between reads.
+pub const SLOT_DURATION: std::time::Duration = std::time::Duration::from_millis(400);
+
 /// A unified-transact verification request broadcast to validators (#350).
 ///
 /// Fixed 2-in/2-out, matching the on-chain `transact` instruction and
@@ -96,6 +108,20 @@ pub struct TransactVerificationRequest {
 
     /// Timestamp when the request was created
     pub timestamp: u64,
+
+    /// Solana slot the ingress node admitted the request at. Selects the
+    /// stake epoch the request is tallied against (see
+    /// [`crate::consensus::epoch`]), so every validator counts it against the
+    /// same snapshot, and the slot its proof suite must be active at (see
+    /// [`crate::privacy::suite_registry`]). Not settlement-bound: the id does not cover it, and a
+    /// re-submission keeps the slot of the round already in flight. Nothing
+    /// signs it either, so a validator only votes on a slot within
+    /// [`MAX_REQUEST_SLOT_SKEW`] of its own, and the epoch it derives is bound
+    /// into the vote signature. `None`
+    /// (a node without a bridge) pins the request to the newest snapshot when
+    /// its round starts.
+    #[serde(default)]
+    pub slot: Option<u64>,
 }
 
 impl TransactVerificationRequest {
@@ -130,6 +156,13 @@ impl TransactVerificationRequest {
         }
         format!("transact-{}", hex::encode(h.finalize()))
     }
+
+    /// Whether the claimed `slot` is within [`MAX_REQUEST_SLOT_SKEW`] of
+    /// `local_slot`. A request without a slot has nothing to check.
+    pub fn slot_is_near(&self, local_slot: u64) -> bool {
+        self.slot
+            .is_none_or(|slot| slot.abs_diff(local_slot) <= MAX_REQUEST_SLOT_SKEW)
+    }
 }
 
 /// Verification result from a validator for a transact request.
@@ -139,7 +172,7 @@ impl TransactVerificationRequest {
 /// BREAKING wire change for that one variant, so both validators must run the
 /// identical binary and be restarted together (a mixed pair drops each other's
 /// votes on decode error and cannot settle).
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct TransactVerificationResult {
     /// Request ID
     pub request_id: String,
@@ -176,24 +209,27 @@ pub struct TransactVerificationResult {
 /// Every variable-length field is u64-little-endian length-prefixed so no field
 /// boundary can be slid. The layout binds, in order:
 /// domain tag, program id, cluster tag, request id (== canonical settlement id),
-/// the VOTER NodeId (defeats relay/rewrap re-attribution), a single validity
-/// bit (matches the equivocation model; `Invalid.reason` free text stays
-/// unsigned), and the wallet pubkey (defeats ed25519 key-substitution).
+/// the stake epoch the vote is counted in (u64 LE; a vote cast against one
+/// epoch's snapshot cannot be counted against another's), the VOTER NodeId
+/// (defeats relay/rewrap re-attribution), a single validity bit (matches the
+/// equivocation model; `Invalid.reason` free text stays unsigned), and the
+/// wallet pubkey (defeats ed25519 key-substitution).
 pub fn transact_vote_signing_bytes(
     program_id: &str,
     cluster_tag: &str,
     request_id: &str,
+    epoch: u64,
     validator: &NodeId,
     vote: &VerificationVote,
     wallet_pubkey: &str,
 ) -> Vec<u8> {
     // 25-byte fixed domain tag, disjoint from the settlement co-sign input (a
     // raw Solana Message beginning with a small u8), so a vote signature can
-    // never double as a settlement signature.
-    const DOMAIN: &[u8] = b"paraloom:transact-vote:v1";
+    // never double as a settlement signature. v2 added the epoch.
+    const DOMAIN: &[u8] = b"paraloom:transact-vote:v2";
     let mut buf = Vec::with_capacity(
         DOMAIN.len()
-            + 8 * 4
+            + 8 * 5
             + program_id.len()
             + cluster_tag.len()
             + request_id.len()
@@ -209,6 +245,7 @@ pub fn transact_vote_signing_bytes(
     put(program_id.as_bytes(), &mut buf);
     put(cluster_tag.as_bytes(), &mut buf);
     put(request_id.as_bytes(), &mut buf);
+    buf.extend_from_slice(&epoch.to_le_bytes());
     put(&validator.0, &mut buf);
     buf.push(if vote.is_valid() { 1u8 } else { 0u8 });
     put(wallet_pubkey.as_bytes(), &mut buf);
@@ -218,33 +255,73 @@ pub fn transact_vote_signing_bytes(
 /// A transact the validator quorum has approved (#350). Emitted on the
 /// approval channel the moment a `Valid` quorum is first reached, carrying
 /// the full request — everything needed to build the on-chain `transact`
-/// instruction.
-#[derive(Clone, Debug)]
+/// instruction — and the signed votes that approved it.
+///
+/// The votes are what let a node other than the one that collected them lead
+/// the settlement: they name the co-signers to ask, and each one verifies on
+/// its own against [`transact_vote_signing_bytes`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApprovedTransact {
     pub request: TransactVerificationRequest,
+    /// The eligible `Valid` votes at approval, sorted by wallet.
+    pub approvals: Vec<TransactVerificationResult>,
+    /// The stake epoch the votes were counted in.
+    pub epoch: u64,
 }
 
-/// Consensus state for one transact verification: the request plus the
-/// shared [`VoteTally`].
+/// Whether `cosigners` clear the on-chain quorum for a settlement `leader`
+/// submits: the rule of `quorum::verify_validator_quorum`, with `leader` as
+/// the excluded settlement authority. An unstaked leader has no active
+/// `ValidatorAccount` and cannot settle at all.
+fn clears_onchain_quorum<'a>(
+    stakes: &HashMap<String, u64>,
+    registry_total: u64,
+    leader: &str,
+    cosigners: impl Iterator<Item = &'a String>,
+) -> bool {
+    let Some(leader_stake) = stakes.get(leader) else {
+        return false;
+    };
+    let eligible_stake = registry_total.saturating_sub(*leader_stake);
+    if eligible_stake == 0 {
+        return false;
+    }
+    let counted_stake = cosigners
+        .filter(|w| w.as_str() != leader)
+        .map(|w| stakes.get(w).copied().unwrap_or(0))
+        .fold(0u64, u64::saturating_add);
+    counted_stake <= eligible_stake && counted_stake > eligible_stake.saturating_mul(2) / 3
+}
+
+/// Consensus state for one transact verification: the request, the shared
+/// [`VoteTally`], and the stake epoch its votes are counted in.
 #[derive(Clone, Debug)]
 pub struct TransactConsensus {
     pub request: TransactVerificationRequest,
     pub tally: VoteTally,
+    pub epoch: u64,
 }
 
 impl TransactConsensus {
-    /// Create new consensus state with explicit BFT thresholds.
+    /// Create new consensus state with explicit BFT thresholds, tallied
+    /// against the stake snapshot of `epoch`.
     pub fn new_with_thresholds(
         request: TransactVerificationRequest,
+        epoch: u64,
         min_validators_for_consensus: usize,
         total_validators: usize,
     ) -> Self {
         let tally = VoteTally::new(
             request.request_id.clone(),
+            epoch,
             min_validators_for_consensus,
             total_validators,
         );
-        Self { request, tally }
+        Self {
+            request,
+            tally,
+            epoch,
+        }
     }
 }
 
@@ -289,26 +366,26 @@ pub struct TransactVerificationCoordinator {
     /// validator set) disables the stake gate, leaving the head-count check.
     local_node_id: Option<NodeId>,
 
-    /// On-chain swap-validator co-sign wallets (the ValidatorRegistry set),
-    /// refreshed every reconcile tick from `list_validator_stakes`. Registration
-    /// into the swap consensus is gated to these wallets so compute
+    /// On-chain validator set per stake epoch, snapshotted once per epoch
+    /// from `list_validator_stakes` and the registry's `total_active_stake`.
+    ///
+    /// A snapshot's wallets are the ValidatorRegistry set: registration into
+    /// the swap consensus is gated to the newest snapshot's wallets so compute
     /// ResourceProviders and wallet-less connectivity entries never enter the
-    /// quorum. Empty = uninitialized (pre-first-snapshot / tests): the gate
-    /// fails open, and the stake gate still withholds on 0 total stake.
-    onchain_wallets: Arc<RwLock<HashSet<String>>>,
-
-    /// On-chain co-sign stake, keyed by wallet (base58). The numerator source
-    /// for the stake gate: votes are counted by wallet and each Valid voter's
-    /// weight is read here. Refreshed atomically with `onchain_wallets` (its
-    /// key set) each reconcile tick. Empty = uninitialized.
-    onchain_stakes: Arc<RwLock<HashMap<String, u64>>>,
-
-    /// The on-chain `ValidatorRegistry.total_active_stake` — the DENOMINATOR for
-    /// the stake threshold, read from the registry account (NOT reconstructed by
-    /// summing `onchain_stakes`), so a lagging `getProgramAccounts` sum can never
-    /// lower the threshold below what the on-chain quorum enforces. 0 =
-    /// uninitialized (withholds, like 0 eligible stake).
-    onchain_registry_total: Arc<RwLock<u64>>,
+    /// quorum, and a request's votes count only from wallets in the snapshot
+    /// of its own epoch, weighted by the stakes there. Its `registry_total`
+    /// is the DENOMINATOR of the stake threshold, read from the registry
+    /// account (NOT reconstructed by summing stakes), so a lagging
+    /// `getProgramAccounts` sum can never lower the threshold below what the
+    /// on-chain quorum enforces. No snapshot = uninitialized (pre-first-
+    /// snapshot / tests): the registration gate fails open, and the stake
+    /// gate withholds on 0 total stake.
+    epochs: Arc<RwLock<StakeEpochs>>,
+
+    /// The last slot read from the chain and when it was read, so a request's
+    /// slot is checked against this node's view without an RPC per request.
+    /// Refreshed by the node's stake-epoch reconciler.
+    slot_clock: Arc<RwLock<Option<(u64, std::time::Instant)>>>,
 
     /// This node's own co-sign wallet (base58), excluded from both sides of the
     /// stake quorum exactly as the on-chain authority is. `None` disables the
@@ -339,9 +416,8 @@ impl TransactVerificationCoordinator {
             approval_tx: None,
             emitted: Arc::new(RwLock::new(HashSet::new())),
             local_node_id: None,
-            onchain_wallets: Arc::new(RwLock::new(HashSet::new())),
-            onchain_stakes: Arc::new(RwLock::new(HashMap::new())),
-            onchain_registry_total: Arc::new(RwLock::new(0)),
+            epochs: Arc::new(RwLock::new(StakeEpochs::new(0))),
+            slot_clock: Arc::new(RwLock::new(None)),
             local_wallet: None,
             equivocators: Arc::new(RwLock::new(HashSet::new())),
             equivocators_path: None,
@@ -364,6 +440,15 @@ impl TransactVerificationCoordinator {
         self
     }
 
+    /// Use stake epochs of `epoch_slots` Solana slots instead of
+    /// [`EPOCH_SLOTS`](crate::consensus::epoch::EPOCH_SLOTS) (`0` keeps it).
+    /// The program records boundary stakes on its own epoch length, so only
+    /// tests have a reason to change it.
+    pub fn with_epoch_slots(mut self, epoch_slots: u64) -> Self {
+        self.epochs = Arc::new(RwLock::new(StakeEpochs::new(epoch_slots)));
+        self
+    }
+
     /// Back the reputation tracker with a file so accumulated scores survive a
     /// node restart (#691), instead of resetting every validator to the base and
     /// re-admitting a previously-penalised one at full weight.
@@ -382,6 +467,16 @@ impl TransactVerificationCoordinator {
         self
     }
 
+    /// Persist equivocation evidence in `storage`, loading what it already
+    /// holds. A store that will not load leaves the evidence log in memory.
+    pub fn with_slashing_storage(mut self, storage: Arc<crate::storage::SlashingStorage>) -> Self {
+        match SlashingTracker::with_storage(storage) {
+            Ok(tracker) => self.slashing_tracker = Arc::new(tracker),
+            Err(e) => log::warn!("could not load slashing evidence: {}", e),
+        }
+        self
+    }
+
     /// Best-effort persist of the equivocator ban set. Called while holding the
     /// write lock so the on-disk set never lags the in-memory one.
     async fn persist_equivocators(&self, set: &HashSet<String>) {
@@ -433,7 +528,11 @@ impl TransactVerificationCoordinator {
     /// it would clear. A quorum whose co-signers hold no stake cannot clear the
     /// on-chain threshold regardless, so withholding costs nothing a real
     /// snapshot would have bought.
-    async fn stake_quorum_met(&self, tally: &VoteTally) -> bool {
+    ///
+    /// Stakes and the registry total are those of the request's epoch, not
+    /// the chain as last polled, so every node counting the same votes
+    /// reaches the same verdict.
+    async fn stake_quorum_met(&self, consensus: &TransactConsensus) -> bool {
         // Wallet-keyed, NO NodeId lookup and NO active-set filter (the flap-prone
         // path). Eligibility + stake come from the on-chain snapshot keyed by
         // wallet, so a reconnected co-signer whose wallet is staked always
@@ -449,12 +548,15 @@ impl TransactVerificationCoordinator {
             }
         };
 
-        let onchain_stakes = self.onchain_stakes.read().await;
+        let epochs = self.epochs.read().await;
+        let unknown = EpochSnapshot::default();
+        let snapshot = epochs.get(consensus.epoch).unwrap_or(&unknown);
+        let onchain_stakes = &snapshot.stakes;
         // DENOMINATOR = on-chain ValidatorRegistry.total_active_stake, NOT a
         // getProgramAccounts sum, so a lagging PDA scan can never lower the
         // threshold below what the on-chain quorum enforces (mirrors
         // programs/paraloom/src/quorum.rs).
-        let registry_total = *self.onchain_registry_total.read().await;
+        let registry_total = snapshot.registry_total;
         let authority_stake = onchain_stakes.get(local).copied().unwrap_or(0);
         let eligible_stake = registry_total.saturating_sub(authority_stake);
 
@@ -462,15 +564,16 @@ impl TransactVerificationCoordinator {
             log::warn!(
                 target: "paraloom::consensus::transact",
                 "withholding approval: zero eligible on-chain stake \
-                 (registry_total={registry_total}, authority_stake={authority_stake}; \
-                 reconciler snapshot not landed yet)"
+                 (epoch {}, registry_total={registry_total}, authority_stake={authority_stake}; \
+                 no snapshot of that epoch yet)",
+                consensus.epoch
             );
             return false;
         }
         let threshold = eligible_stake.saturating_mul(2) / 3 + 1;
 
         let equivocators = self.equivocators.read().await;
-        let votes = tally.votes.read().await;
+        let votes = consensus.tally.votes.read().await;
         let mut counted_stake: u64 = 0;
         for (wallet, rec) in votes.iter() {
             if wallet == local
@@ -506,24 +609,43 @@ impl TransactVerificationCoordinator {
             log::warn!(
                 target: "paraloom::consensus::transact",
                 "withholding approval: counted co-signer stake {} < threshold {} \
-                 (eligible {}, authority {}); on-chain stakes = {:?}",
+                 (eligible {}, authority {}); epoch {} stakes = {:?}",
                 counted_stake,
                 threshold,
                 eligible_stake,
                 authority_stake,
+                consensus.epoch,
                 per_wallet
             );
         }
         met
     }
 
-    /// The eligibility basis for counting: the staked on-chain co-sign wallets
-    /// minus any banned for equivocation. Replaces the flap-prone
-    /// `active_snapshot()` NodeId set at every tally call site.
-    async fn eligible_wallets(&self) -> HashSet<String> {
-        let onchain = self.onchain_wallets.read().await;
+    /// The eligibility basis for counting: the co-sign wallets staked in
+    /// `epoch`'s snapshot minus any banned for equivocation. Replaces the
+    /// flap-prone `active_snapshot()` NodeId set at every tally call site.
+    async fn eligible_wallets(&self, epoch: u64) -> HashSet<String> {
+        let epochs = self.epochs.read().await;
         let equivocators = self.equivocators.read().await;
-        onchain.difference(&equivocators).cloned().collect()
+        match epochs.get(epoch) {
+            Some(snapshot) => snapshot
+                .stakes
+                .keys()
+                .filter(|wallet| !equivocators.contains(*wallet))
+                .cloned()
+                .collect(),
+            None => HashSet::new(),
+        }
+    }
+
+    /// Whether `wallet` is in the newest on-chain snapshot; `None` before the
+    /// first one lands.
+    async fn is_onchain_wallet(&self, wallet: &str) -> Option<bool> {
+        self.epochs
+            .read()
+            .await
+            .latest()
+            .map(|snapshot| snapshot.stakes.contains_key(wallet))
     }
 
     /// Create a coordinator that emits approved transacts on a channel.
@@ -589,12 +711,12 @@ impl TransactVerificationCoordinator {
         // stake. Fail open while the allowlist is empty (pre-first-snapshot /
         // tests) — the stake gate still withholds on 0 total stake, so a
         // transiently-admitted peer is inert.
-        {
-            let allow = self.onchain_wallets.read().await;
-            if !allow.is_empty() {
+        let onchain_stake = {
+            let epochs = self.epochs.read().await;
+            if let Some(allow) = epochs.latest() {
                 let admitted = wallet_pubkey
                     .as_ref()
-                    .map(|w| allow.contains(w))
+                    .map(|w| allow.stakes.contains_key(w))
                     .unwrap_or(false);
                 if !admitted {
                     log::debug!(
@@ -606,7 +728,11 @@ impl TransactVerificationCoordinator {
                     return;
                 }
             }
-        }
+            wallet_pubkey
+                .as_ref()
+                .and_then(|w| epochs.latest()?.stakes.get(w).copied())
+                .unwrap_or(0)
+        };
 
         let mut validators = self.validators.write().await;
         if !validators.contains(&validator) {
@@ -639,14 +765,15 @@ impl TransactVerificationCoordinator {
                 leader_selector.activate_validator(&validator);
             }
             None => {
-                // Register with ZERO stake (fail-closed): a freshly-seen peer
-                // carries no quorum weight until the on-chain stake reconciler
-                // (`sync_onchain_stakes`) reads its real, at-risk stake from its
-                // `ValidatorAccount`. The old placeholder gave every connected
-                // peer a fixed stake, collapsing the stake-weighted quorum into
-                // head-count and making it Sybil-forgeable.
+                // Register with the stake the newest epoch snapshot holds for
+                // its wallet, or ZERO (fail-closed): a freshly-seen peer carries
+                // no quorum weight until a snapshot (`record_epoch_stakes`)
+                // reads its real, at-risk stake from its `ValidatorAccount`.
+                // The old placeholder gave every connected peer a fixed stake,
+                // collapsing the stake-weighted quorum into head-count and
+                // making it Sybil-forgeable.
                 leader_selector.register_validator(
-                    ValidatorInfo::new(validator, 0, 1000).with_wallet(wallet_pubkey),
+                    ValidatorInfo::new(validator, onchain_stake, 1000).with_wallet(wallet_pubkey),
                 );
             }
         }
@@ -686,41 +813,72 @@ impl TransactVerificationCoordinator {
         );
     }
 
-    /// Reconcile the consensus set's stakes with on-chain reality: set each
-    /// validator's stake to the value read from its `ValidatorAccount` (keyed by
-    /// co-sign wallet), or 0 if it has no active on-chain registration.
+    /// The stake epoch `slot` falls in.
+    pub async fn epoch_of(&self, slot: u64) -> u64 {
+        self.epochs.read().await.epoch_of(slot)
+    }
+
+    /// Note `slot` as the chain's current slot.
+    pub async fn observe_slot(&self, slot: u64) {
+        *self.slot_clock.write().await = Some((slot, std::time::Instant::now()));
+    }
+
+    /// This node's view of the current slot: the last one observed, advanced
+    /// by the time since at [`SLOT_DURATION`]. `None` before the first read.
+    pub async fn local_slot(&self) -> Option<u64> {
+        self.slot_clock.read().await.map(|(slot, read_at)| {
+            slot + (read_at.elapsed().as_millis() / SLOT_DURATION.as_millis()) as u64
+        })
+    }
+
+    /// Whether the validator-set snapshot of `epoch` is held here.
+    pub async fn has_epoch(&self, epoch: u64) -> bool {
+        self.epochs.read().await.get(epoch).is_some()
+    }
+
+    /// Record the on-chain validator set of `epoch`: each co-sign wallet's
+    /// stake as read from its `ValidatorAccount`, and the registry's
+    /// `total_active_stake`. Returns whether the snapshot was new.
     ///
     /// This is what makes the stake-weighted quorum real. Connectivity
-    /// registration (`register_validator_with_wallet`) now seeds 0 stake; this
-    /// pass, driven periodically by the node from
-    /// `ProgramInterface::list_validator_stakes`, fills in the true at-risk
-    /// stake so an unregistered/unstaked peer can never reach a supermajority.
-    pub async fn sync_onchain_stakes(
+    /// registration (`register_validator_with_wallet`) seeds only what a
+    /// snapshot already holds, 0 otherwise; the node records a snapshot from `ProgramInterface::list_validator_stakes`
+    /// once per epoch, and every request of that epoch is counted against it,
+    /// so an unregistered/unstaked peer can never reach a supermajority and a
+    /// stake change mid-epoch cannot move a tally in flight. The map keys ARE
+    /// the ValidatorRegistry set; compute ResourceProviders have no
+    /// ValidatorAccount so are absent, which keeps them out of the quorum.
+    ///
+    /// The first snapshot of an epoch is kept; an empty one (RPC returned
+    /// nothing) is never recorded, as that would fail-open the registration
+    /// gate. A snapshot newer than any held also refreshes the leader
+    /// selector, which tracks the current set.
+    pub async fn record_epoch_stakes(
         &self,
-        stakes: std::collections::HashMap<String, u64>,
+        epoch: u64,
+        stakes: HashMap<String, u64>,
         registry_total_active_stake: u64,
-    ) {
-        // Refresh the registration allowlist + the wallet-keyed stake numerator
-        // to exactly the on-chain swap-validator wallets. These map keys ARE the
-        // ValidatorRegistry set; compute ResourceProviders have no
-        // ValidatorAccount so are absent, which keeps them out of the quorum.
-        // An empty snapshot (RPC returned nothing) is NOT trusted to clear the
-        // allowlist/stakes — that would fail-open the gate; keep the previous
-        // set (and the previous registry total, updated only alongside a
-        // non-empty snapshot so numerator and denominator move together).
-        if !stakes.is_empty() {
-            *self.onchain_wallets.write().await = stakes.keys().cloned().collect();
-            *self.onchain_registry_total.write().await = registry_total_active_stake;
-            *self.onchain_stakes.write().await = stakes.clone();
+    ) -> bool {
+        let newest = {
+            let mut epochs = self.epochs.write().await;
+            if !epochs.record(epoch, stakes.clone(), registry_total_active_stake) {
+                return false;
+            }
+            epochs.latest().map(|snapshot| snapshot.epoch) == Some(epoch)
+        };
+        if newest {
+            self.leader_selector
+                .write()
+                .await
+                .apply_onchain_stakes(&stakes);
         }
-
-        let mut leader_selector = self.leader_selector.write().await;
-        leader_selector.apply_onchain_stakes(&stakes);
-        log::debug!(
-            "Reconciled consensus stakes against on-chain state ({} staked wallets, registry_total={})",
+        log::info!(
+            "Snapshotted on-chain validator set for stake epoch {} ({} staked wallets, registry_total={})",
+            epoch,
             stakes.len(),
             registry_total_active_stake
         );
+        true
     }
 
     /// Look up the Solana wallet pubkey a registered validator co-signs
@@ -738,13 +896,29 @@ impl TransactVerificationCoordinator {
         let pending = self.pending.read().await;
         match pending.get(request_id) {
             Some(consensus) => {
-                let eligible = self.eligible_wallets().await;
+                let eligible = self.eligible_wallets(consensus.epoch).await;
                 consensus.tally.valid_voters(&eligible).await
             }
             None => Vec::new(),
         }
     }
 
+    /// The eligible wallets' stakes in `epoch` and the registry total they
+    /// are a share of — what a quorum of that epoch is counted against.
+    /// `None` if the epoch's snapshot is not held here.
+    pub async fn eligible_stakes(&self, epoch: u64) -> Option<(HashMap<String, u64>, u64)> {
+        let eligible = self.eligible_wallets(epoch).await;
+        let epochs = self.epochs.read().await;
+        let snapshot = epochs.get(epoch)?;
+        let stakes = snapshot
+            .stakes
+            .iter()
+            .filter(|(wallet, _)| eligible.contains(*wallet))
+            .map(|(wallet, stake)| (wallet.clone(), *stake))
+            .collect();
+        Some((stakes, snapshot.registry_total))
+    }
+
     /// Whether `node_id` maps to a wallet in the on-chain staked set — the
     /// flap-surviving driver-auth check (replaces `is_registered_validator`,
     /// which used the flap-prone `self.validators`). The leader_selector entry
@@ -752,13 +926,9 @@ impl TransactVerificationCoordinator {
     /// co-signer still authenticates. Fail-open only while the on-chain set is
     /// empty (pre-first-snapshot), matching the registration gate.
     pub async fn source_is_onchain_validator(&self, node_id: &NodeId) -> bool {
-        let onchain = self.onchain_wallets.read().await;
-        if onchain.is_empty() {
-            return true;
-        }
         match self.validator_wallet(node_id).await {
-            Some(wallet) => onchain.contains(&wallet),
-            None => false,
+            Some(wallet) => self.is_onchain_wallet(&wallet).await.unwrap_or(true),
+            None => self.epochs.read().await.latest().is_none(),
         }
     }
 
@@ -810,9 +980,12 @@ impl TransactVerificationCoordinator {
             ));
         }
 
+        let epoch = self.request_epoch(&request).await?;
+
         let request_id = request.request_id.clone();
         let consensus = TransactConsensus::new_with_thresholds(
             request,
+            epoch,
             self.min_validators_for_consensus,
             self.total_validators,
         );
@@ -832,6 +1005,38 @@ impl TransactVerificationCoordinator {
         Ok(request_id)
     }
 
+    /// The stake epoch `request` is tallied in, and so the epoch every vote
+    /// on it signs: that of its slot, or the newest snapshot held here when it
+    /// has none. One from an epoch this node no longer holds (or has not
+    /// reached) could not be counted the way its peers count it.
+    pub async fn request_epoch(&self, request: &TransactVerificationRequest) -> Result<u64> {
+        let epochs = self.epochs.read().await;
+        match request.slot {
+            Some(slot) => {
+                let epoch = epochs.epoch_of(slot);
+                if !epochs.admits(epoch) {
+                    return Err(anyhow!(
+                        "slot {} is in stake epoch {}, which this node does not hold",
+                        slot,
+                        epoch
+                    ));
+                }
+                Ok(epoch)
+            }
+            None => Ok(epochs.latest().map(|snapshot| snapshot.epoch).unwrap_or(0)),
+        }
+    }
+
+    /// The stake epoch the round in flight for `request_id` is counted in;
+    /// `None` if no such round is pending here.
+    pub async fn round_epoch(&self, request_id: &str) -> Option<u64> {
+        self.pending
+            .read()
+            .await
+            .get(request_id)
+            .map(|consensus| consensus.epoch)
+    }
+
     /// Submit a verification result from a validator. On the node that
     /// started the request, the vote that first completes a `Valid` quorum
     /// makes the coordinator emit an [`ApprovedTransact`] exactly once.
@@ -865,18 +1070,21 @@ impl TransactVerificationCoordinator {
             return Ok(());
         }
 
-        // Eligibility: while the on-chain wallet set is known, only a staked
-        // co-sign wallet may be counted (fail-open only while it is empty, as
-        // the registration gate does).
+        // Eligibility: while the request's epoch snapshot is held, only a
+        // wallet staked in it may be counted (fail-open only until it lands,
+        // as the registration gate does; such a vote is inert until then).
         {
-            let onchain = self.onchain_wallets.read().await;
-            if !onchain.is_empty() && !onchain.contains(&result.wallet_pubkey) {
-                log::warn!(
-                    "dropping vote from non-staked wallet {} for {}",
-                    result.wallet_pubkey,
-                    result.request_id
-                );
-                return Ok(());
+            let epochs = self.epochs.read().await;
+            if let Some(snapshot) = epochs.get(consensus.epoch) {
+                if !snapshot.stakes.contains_key(&result.wallet_pubkey) {
+                    log::warn!(
+                        "dropping vote from wallet {} not staked in epoch {} for {}",
+                        result.wallet_pubkey,
+                        consensus.epoch,
+                        result.request_id
+                    );
+                    return Ok(());
+                }
             }
         }
 
@@ -918,19 +1126,17 @@ impl TransactVerificationCoordinator {
         // Counting is by the on-chain staked wallet set (minus equivocators), NOT
         // the flap-prone active NodeId set.
         if let Some(tx) = &self.approval_tx {
-            let eligible = self.eligible_wallets().await;
+            let eligible = self.eligible_wallets(consensus.epoch).await;
             let mut emitted = self.emitted.write().await;
             if !emitted.contains(&result.request_id)
                 && consensus.tally.has_consensus(&eligible).await
-                && self.stake_quorum_met(&consensus.tally).await
+                && self.stake_quorum_met(consensus).await
                 && matches!(
                     consensus.tally.consensus_result(&eligible).await,
                     Ok(VerificationVote::Valid)
                 )
             {
-                let approved = ApprovedTransact {
-                    request: consensus.request.clone(),
-                };
+                let approved = Self::approval_of(consensus, &eligible).await;
                 if tx.send(approved).is_ok() {
                     emitted.insert(result.request_id.clone());
                 }
@@ -940,6 +1146,113 @@ impl TransactVerificationCoordinator {
         Ok(())
     }
 
+    /// The approval `consensus` would emit: its request plus the signed
+    /// `Valid` votes of the `eligible` wallets.
+    async fn approval_of(
+        consensus: &TransactConsensus,
+        eligible: &HashSet<String>,
+    ) -> ApprovedTransact {
+        let timestamp = crate::utils::now_unix_seconds();
+        let approvals = consensus
+            .tally
+            .valid_votes(eligible)
+            .await
+            .into_iter()
+            .map(|(wallet_pubkey, record)| TransactVerificationResult {
+                request_id: consensus.request.request_id.clone(),
+                validator: record.node_id,
+                vote: record.vote,
+                timestamp,
+                wallet_pubkey,
+                signature: record.signature,
+            })
+            .collect();
+        ApprovedTransact {
+            request: consensus.request.clone(),
+            approvals,
+            epoch: consensus.epoch,
+        }
+    }
+
+    /// The approval of `request_id` as it stands, once its votes reach a
+    /// `Valid` quorum that clears the stake gate; `None` before that or if the
+    /// request is unknown here.
+    pub async fn approval(&self, request_id: &str) -> Option<ApprovedTransact> {
+        if !matches!(
+            self.check_consensus(request_id).await,
+            Ok(Some(VerificationVote::Valid))
+        ) {
+            return None;
+        }
+        let pending = self.pending.read().await;
+        let consensus = pending.get(request_id)?;
+        let eligible = self.eligible_wallets(consensus.epoch).await;
+        Some(Self::approval_of(consensus, &eligible).await)
+    }
+
+    /// The order in which the approving validators lead `approved`'s
+    /// settlement, drawn from the finalized slot `anchor_slot` with hash
+    /// `slot_hash` (see [`LeaderSchedule`]).
+    ///
+    /// Candidates are the wallets of the approval's `Valid` votes that are
+    /// eligible in its epoch, weighted by their stake there. With the stake gate on,
+    /// a wallet takes a turn only if it can settle as the authority without the
+    /// wallets ranked ahead of it — a backup runs its round without the
+    /// leaders it replaces, so their stake cannot count. With the gate off
+    /// (no local wallet) every approving wallet takes a turn.
+    pub async fn leader_schedule(
+        &self,
+        approved: &ApprovedTransact,
+        anchor_slot: u64,
+        slot_hash: &[u8; 32],
+    ) -> LeaderSchedule {
+        let request_id = &approved.request.request_id;
+        let snapshot = self.epochs.read().await.get(approved.epoch).cloned();
+        let onchain_known = snapshot.is_some();
+        let eligible = self.eligible_wallets(approved.epoch).await;
+        let equivocators = self.equivocators.read().await.clone();
+        let EpochSnapshot {
+            stakes,
+            registry_total,
+            ..
+        } = snapshot.unwrap_or_default();
+
+        let approving: Vec<String> = approved
+            .approvals
+            .iter()
+            .filter(|a| a.request_id == *request_id && a.vote.is_valid())
+            .map(|a| a.wallet_pubkey.clone())
+            .filter(|w| {
+                if onchain_known {
+                    eligible.contains(w)
+                } else {
+                    !equivocators.contains(w)
+                }
+            })
+            .collect();
+        let candidates: Vec<(String, u64)> = approving
+            .iter()
+            .map(|w| (w.clone(), stakes.get(w).copied().unwrap_or(0)))
+            .collect();
+
+        let gated = self.local_wallet.is_some();
+        LeaderSchedule::compute(
+            request_id,
+            anchor_slot,
+            slot_hash,
+            &candidates,
+            |leader, ahead| {
+                !gated
+                    || clears_onchain_quorum(
+                        &stakes,
+                        registry_total,
+                        leader,
+                        approving.iter().filter(|w| !ahead.contains(w)),
+                    )
+            },
+        )
+    }
+
     /// Non-blocking quorum check.
     pub async fn check_consensus(&self, request_id: &str) -> Result<Option<VerificationVote>> {
         let pending = self.pending.read().await;
@@ -951,7 +1264,7 @@ impl TransactVerificationCoordinator {
             return Err(anyhow!("Verification timed out"));
         }
 
-        let eligible = self.eligible_wallets().await;
+        let eligible = self.eligible_wallets(consensus.epoch).await;
         if consensus.tally.has_consensus(&eligible).await {
             let result = consensus.tally.consensus_result(&eligible).await?;
             // A `Valid` result may be acted on only if the co-signers hold
@@ -959,8 +1272,7 @@ impl TransactVerificationCoordinator {
             // more stake to vote rather than assemble a transaction the program
             // would reject (#611). An `Invalid` result settles nothing and needs
             // no stake threshold.
-            if matches!(result, VerificationVote::Valid)
-                && !self.stake_quorum_met(&consensus.tally).await
+            if matches!(result, VerificationVote::Valid) && !self.stake_quorum_met(consensus).await
             {
                 return Ok(None);
             }
@@ -1033,6 +1345,7 @@ mod tests {
             proof: vec![7, 8, 9],
             ciphertexts: ["a".to_string(), "b".to_string()],
             timestamp: 123,
+            slot: None,
         }
     }
 
@@ -1082,7 +1395,8 @@ mod tests {
             .await;
         c.register_validator_with_wallet(NodeId(vec![1]), Some("W1".to_string()))
             .await;
-        c.sync_onchain_stakes(
+        c.record_epoch_stakes(
+            0,
             stakes(&[("W0", 1_000_000_000), ("W1", 1_000_000_000)]),
             2_000_000_000,
         )
@@ -1126,6 +1440,7 @@ mod tests {
             "PROG",
             "mainnet-beta",
             "req-1",
+            7,
             &NodeId(vec![1, 2, 3]),
             &VerificationVote::Valid,
             "W1",
@@ -1137,6 +1452,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Valid,
                 "W1"
@@ -1148,6 +1464,7 @@ mod tests {
                 "PROG2",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Valid,
                 "W1",
@@ -1156,6 +1473,7 @@ mod tests {
                 "PROG",
                 "devnet",
                 "req-1",
+                7,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Valid,
                 "W1",
@@ -1164,6 +1482,16 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-2",
+                7,
+                &NodeId(vec![1, 2, 3]),
+                &VerificationVote::Valid,
+                "W1",
+            ),
+            transact_vote_signing_bytes(
+                "PROG",
+                "mainnet-beta",
+                "req-1",
+                8,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Valid,
                 "W1",
@@ -1172,6 +1500,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![9, 9, 9]),
                 &VerificationVote::Valid,
                 "W1",
@@ -1180,6 +1509,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Invalid { reason: "x".into() },
                 "W1",
@@ -1188,6 +1518,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1, 2, 3]),
                 &VerificationVote::Valid,
                 "W2",
@@ -1202,6 +1533,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1]),
                 &VerificationVote::Invalid { reason: "a".into() },
                 "W1"
@@ -1210,6 +1542,7 @@ mod tests {
                 "PROG",
                 "mainnet-beta",
                 "req-1",
+                7,
                 &NodeId(vec![1]),
                 &VerificationVote::Invalid { reason: "b".into() },
                 "W1"
@@ -1228,10 +1561,192 @@ mod tests {
         coord.submit_result(vote(&id, 0, "W0", true)).await.unwrap();
         assert!(approvals.try_recv().is_err(), "one vote is not a quorum");
         coord.submit_result(vote(&id, 1, "W1", true)).await.unwrap();
+        let approved = approvals
+            .try_recv()
+            .expect("authority + co-signer wallet quorum must settle");
+        let signers: Vec<(&str, NodeId)> = approved
+            .approvals
+            .iter()
+            .map(|a| (a.wallet_pubkey.as_str(), a.validator.clone()))
+            .collect();
+        assert_eq!(
+            signers,
+            vec![("W0", NodeId(vec![0])), ("W1", NodeId(vec![1]))],
+            "the approval carries every counted signed vote"
+        );
+        assert!(approved.approvals.iter().all(|a| a.request_id == id));
+        assert_eq!(
+            coord.approval(&id).await.unwrap().approvals.len(),
+            2,
+            "the standing approval can be read back"
+        );
+
+        // The approval and the stake it was counted against certify it.
+        assert_eq!(approved.epoch, 0);
+        let (stakes, registry_total) = coord.eligible_stakes(approved.epoch).await.unwrap();
+        assert_eq!(registry_total, 2_000_000_000);
+        assert!(coord.eligible_stakes(1).await.is_none());
+        let cert = crate::consensus::QuorumCertificate::new(
+            id.clone(),
+            "prog".to_string(),
+            "devnet".to_string(),
+            approved.epoch,
+            &stakes,
+            registry_total,
+            approved.approvals.clone(),
+        );
+        assert_eq!(cert.verify_quorum(&stakes, registry_total), Ok(()));
+    }
+
+    /// A request is counted against the snapshot of the epoch its slot falls
+    /// in: a later epoch with other stakes does not move it, and a slot in an
+    /// epoch this node does not hold is refused.
+    /// Arm-the-guard: tally against the newest snapshot and the epoch-1
+    /// request is diluted by W2 and withheld.
+    #[tokio::test]
+    async fn requests_are_tallied_against_the_epoch_of_their_slot() {
+        let (c, mut approvals) = TransactVerificationCoordinator::new_with_approvals();
+        let mut c = c
+            .with_local_node_id(NodeId(vec![0]))
+            .with_local_wallet("W0".to_string())
+            .with_epoch_slots(100);
+        c.set_consensus_thresholds(2, 2);
+        c.register_validator_with_wallet(NodeId(vec![0]), Some("W0".to_string()))
+            .await;
+        c.register_validator_with_wallet(NodeId(vec![1]), Some("W1".to_string()))
+            .await;
+        c.record_epoch_stakes(
+            1,
+            stakes(&[("W0", 1_000_000_000), ("W1", 1_000_000_000)]),
+            2_000_000_000,
+        )
+        .await;
+
+        let request_at = |slot: u64, root: u8| {
+            let mut r = sample_request();
+            r.root = [root; 32];
+            r.slot = Some(slot);
+            r.request_id = r.canonical_id();
+            r
+        };
+        let in_epoch_1 = request_at(150, 1);
+        let id1 = in_epoch_1.request_id.clone();
+        c.start_verification(in_epoch_1).await.unwrap();
+
+        // Epoch 2 brings W2, whose stake leaves W1 far short of two thirds.
+        c.record_epoch_stakes(
+            2,
+            stakes(&[
+                ("W0", 1_000_000_000),
+                ("W1", 1_000_000_000),
+                ("W2", 9_000_000_000),
+            ]),
+            11_000_000_000,
+        )
+        .await;
+        let in_epoch_2 = request_at(250, 2);
+        let id2 = in_epoch_2.request_id.clone();
+        c.start_verification(in_epoch_2.clone()).await.unwrap();
+        // Each round reports the epoch its votes must be signed in, and a
+        // peer handed the request derives the same one from its slot.
+        assert_eq!(c.round_epoch(&id1).await, Some(1));
+        assert_eq!(c.round_epoch(&id2).await, Some(2));
+        assert_eq!(c.request_epoch(&in_epoch_2).await.unwrap(), 2);
+        assert_eq!(c.round_epoch("transact-unknown").await, None);
+
+        for id in [&id1, &id2] {
+            c.submit_result(vote(id, 0, "W0", true)).await.unwrap();
+            c.submit_result(vote(id, 1, "W1", true)).await.unwrap();
+        }
+        let approved = approvals
+            .try_recv()
+            .expect("the epoch-1 request settles on epoch 1's stakes");
+        assert_eq!(
+            (approved.request.request_id.as_str(), approved.epoch),
+            (id1.as_str(), 1)
+        );
         assert!(
-            approvals.try_recv().is_ok(),
-            "authority + co-signer wallet quorum must settle"
+            approvals.try_recv().is_err(),
+            "the epoch-2 request is withheld on epoch 2's stakes"
         );
+
+        // Epoch 0 was never snapshotted here; epoch 3 is the next boundary.
+        assert!(c.start_verification(request_at(50, 3)).await.is_err());
+        assert!(c.start_verification(request_at(350, 4)).await.is_ok());
+        assert!(c.start_verification(request_at(450, 5)).await.is_err());
+    }
+
+    #[test]
+    fn a_request_slot_must_be_near_the_local_slot() {
+        let mut r = sample_request();
+        assert!(r.slot_is_near(1_000), "no slot, nothing to check");
+        r.slot = Some(1_000);
+        assert!(r.slot_is_near(1_000 + MAX_REQUEST_SLOT_SKEW));
+        assert!(r.slot_is_near(1_000 - MAX_REQUEST_SLOT_SKEW));
+        assert!(!r.slot_is_near(1_001 + MAX_REQUEST_SLOT_SKEW));
+        assert!(!r.slot_is_near(999 - MAX_REQUEST_SLOT_SKEW));
+    }
+
+    #[tokio::test]
+    async fn the_local_slot_runs_on_from_the_last_one_observed() {
+        let c = TransactVerificationCoordinator::new();
+        assert_eq!(c.local_slot().await, None, "nothing read yet");
+        c.observe_slot(5_000).await;
+        let slot = c.local_slot().await.unwrap();
+        assert!((5_000..5_000 + MAX_REQUEST_SLOT_SKEW).contains(&slot));
+
+        *c.slot_clock.write().await = Some((
+            5_000,
+            std::time::Instant::now() - SLOT_DURATION * MAX_REQUEST_SLOT_SKEW as u32,
+        ));
+        assert!(c.local_slot().await.unwrap() >= 5_000 + MAX_REQUEST_SLOT_SKEW);
+    }
+
+    /// Six validators at 1 SOL each: a leader needs four of the other five
+    /// (eligible 5, threshold 4). The primary settles with all five, the first
+    /// backup with the four left once the primary is gone, and nobody can
+    /// settle without two of them — so the schedule stops at two turns.
+    #[tokio::test]
+    async fn leader_schedule_places_only_wallets_that_can_settle_without_the_ranks_ahead() {
+        let (c, _rx) = TransactVerificationCoordinator::new_with_approvals();
+        let mut c = c
+            .with_local_node_id(NodeId(vec![0]))
+            .with_local_wallet("W0".to_string());
+        c.set_consensus_thresholds(4, 6);
+        let wallets: Vec<String> = (0..6).map(|i| format!("W{i}")).collect();
+        for (i, w) in wallets.iter().enumerate() {
+            c.register_validator_with_wallet(NodeId(vec![i as u8]), Some(w.clone()))
+                .await;
+        }
+        c.record_epoch_stakes(
+            0,
+            wallets.iter().map(|w| (w.clone(), 1_000_000_000)).collect(),
+            6_000_000_000,
+        )
+        .await;
+
+        let req = canonical_request();
+        let id = req.request_id.clone();
+        c.start_verification(req).await.unwrap();
+        for (i, w) in wallets.iter().enumerate() {
+            c.submit_result(vote(&id, i as u8, w, true)).await.unwrap();
+        }
+        let approved = c.approval(&id).await.unwrap();
+
+        let schedule = c.leader_schedule(&approved, 42, &[7; 32]).await;
+        assert_eq!(schedule.order().len(), 2);
+        assert_eq!(
+            schedule,
+            c.leader_schedule(&approved, 42, &[7; 32]).await,
+            "every node holding the approval computes the same schedule"
+        );
+
+        // A wallet that was not among the approvals never takes a turn.
+        let mut partial = approved.clone();
+        partial.approvals.retain(|a| a.wallet_pubkey != "W5");
+        let schedule = c.leader_schedule(&partial, 42, &[7; 32]).await;
+        assert_eq!(schedule.rank("W5"), None);
+        assert_eq!(schedule.order().len(), 1);
     }
 
     /// CORE FIX: a co-signer removed from the active NodeId set (flap) still has
@@ -1269,7 +1784,8 @@ mod tests {
         c.set_consensus_thresholds(1, 2);
         c.register_validator_with_wallet(NodeId(vec![0]), Some("W0".to_string()))
             .await;
-        c.sync_onchain_stakes(
+        c.record_epoch_stakes(
+            0,
             stakes(&[("W0", 1_000_000_000), ("W1", 1_000_000_000)]),
             2_000_000_000,
         )
@@ -1299,7 +1815,7 @@ mod tests {
         c.register_validator_with_wallet(NodeId(vec![1]), Some("W1".to_string()))
             .await;
         // Only W1 is in the scanned map, but the registry counts 3 SOL active.
-        c.sync_onchain_stakes(stakes(&[("W1", 1_000_000_000)]), 3_000_000_000)
+        c.record_epoch_stakes(0, stakes(&[("W1", 1_000_000_000)]), 3_000_000_000)
             .await;
         let mut approvals = rx;
         let req = canonical_request();
@@ -1327,7 +1843,8 @@ mod tests {
         c.register_validator_with_wallet(NodeId(vec![2]), Some("W2".to_string()))
             .await;
         // Scan sums to 2 SOL but the registry only records 1 SOL active.
-        c.sync_onchain_stakes(
+        c.record_epoch_stakes(
+            0,
             stakes(&[("W1", 1_000_000_000), ("W2", 1_000_000_000)]),
             1_000_000_000,
         )
@@ -1361,7 +1878,8 @@ mod tests {
             .await;
         c.register_validator_with_wallet(NodeId(vec![2]), Some("W2".to_string()))
             .await;
-        c.sync_onchain_stakes(
+        c.record_epoch_stakes(
+            0,
             stakes(&[("W1", 1_000_000_000), ("W2", 1_000_000_000)]),
             2_000_000_000,
         )
@@ -1509,7 +2027,8 @@ mod tests {
     async fn registration_is_gated_to_onchain_swap_validators() {
         let coordinator = TransactVerificationCoordinator::new();
         coordinator
-            .sync_onchain_stakes(
+            .record_epoch_stakes(
+                0,
                 stakes(&[("VAL1wallet", 1_000_000_000), ("VAL2wallet", 1_000_000_000)]),
                 2_000_000_000,
             )
@@ -1550,7 +2069,7 @@ mod tests {
             .register_validator_with_wallet(NodeId(vec![2]), Some("VAL2wallet".to_string()))
             .await;
         coordinator
-            .sync_onchain_stakes(stakes(&[("VAL2wallet", 1_000_000_000)]), 1_000_000_000)
+            .record_epoch_stakes(0, stakes(&[("VAL2wallet", 1_000_000_000)]), 1_000_000_000)
             .await;
         coordinator.unregister_validator(&NodeId(vec![2])).await;
         {
@@ -0,0 +1,221 @@
+//! Batched transact-proof verification for the consensus path.
+//!
+//! During a burst a validator receives many [`TransactVerificationRequest`]s
+//! at once, and verifying each Groth16 proof on its own costs a full pairing
+//! check apiece. The [`TransactVerificationQueue`] instead holds requests for
+//! a short window (or until the batch is full) and hands them to
+//! [`ProofVerifier::verify_transact_batch`] together, which checks the whole
+//! batch with one randomized multi-pairing and only falls back to per-proof
+//! checks when that fails. Each caller still gets its own result.
+//!
+//! Under light load a request waits at most one window, which is small next
+//! to gossip and settlement latency.
+
+use crate::consensus::transact::TransactVerificationRequest;
+use crate::privacy::{ProofVerifier, TransactProofParts, VerificationResult};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long the queue waits for more requests after the first one arrives.
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(20);
+
+/// Most proofs verified in one batch.
+pub const DEFAULT_MAX_BATCH: usize = 32;
+
+struct Pending {
+    parts: TransactProofParts,
+    reply: oneshot::Sender<VerificationResult>,
+}
+
+/// Collects concurrent transact verifications into batches.
+///
+/// The worker task is spawned on first use, so the queue can be built
+/// outside a runtime, and exits once the queue is dropped.
+pub struct TransactVerificationQueue {
+    window: Duration,
+    max_batch: usize,
+    tx: OnceLock<mpsc::UnboundedSender<Pending>>,
+}
+
+impl TransactVerificationQueue {
+    pub fn new(window: Duration, max_batch: usize) -> Self {
+        Self {
+            window,
+            max_batch: max_batch.max(1),
+            tx: OnceLock::new(),
+        }
+    }
+
+    /// Verify `request`'s proof, batched with whatever else arrives within
+    /// the window.
+    pub async fn verify(&self, request: &TransactVerificationRequest) -> VerificationResult {
+        let (reply, rx) = oneshot::channel();
+        let pending = Pending {
+            parts: proof_parts(request),
+            reply,
+        };
+        if self.sender().send(pending).is_err() {
+            return VerificationResult::Invalid {
+                reason: "verification queue stopped".to_string(),
+            };
+        }
+        rx.await.unwrap_or_else(|_| VerificationResult::Invalid {
+            reason: "verification queue dropped the request".to_string(),
+        })
+    }
+
+    fn sender(&self) -> &mpsc::UnboundedSender<Pending> {
+        self.tx.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(run(rx, self.window, self.max_batch));
+            tx
+        })
+    }
+}
+
+impl Default for TransactVerificationQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_BATCH_WINDOW, DEFAULT_MAX_BATCH)
+    }
+}
+
+/// The settlement fields `request`'s proof binds, and the slot its suite is
+/// judged at. The asset id is all-zero for native SOL or
+/// `mint_to_asset(mint)` for an SPL settlement (#779).
+fn proof_parts(request: &TransactVerificationRequest) -> TransactProofParts {
+    TransactProofParts {
+        root: request.root,
+        recipient: request.recipient,
+        ext_amount: request.ext_amount,
+        asset_id: request
+            .mint
+            .map(|m| crate::privacy::poseidon_circom::mint_to_asset(&m))
+            .unwrap_or([0u8; 32]),
+        nullifiers: request.nullifiers,
+        output_commitments: request.output_commitments,
+        zk_proof: request.proof.clone(),
+        slot: request.slot,
+    }
+}
+
+/// Take the next batch: wait for one request, then gather more until the
+/// window closes or the batch is full. `None` once the queue is gone.
+async fn next_batch(
+    rx: &mut mpsc::UnboundedReceiver<Pending>,
+    window: Duration,
+    max_batch: usize,
+) -> Option<Vec<Pending>> {
+    let first = rx.recv().await?;
+    let mut batch = vec![first];
+    let deadline = tokio::time::Instant::now() + window;
+    while batch.len() < max_batch {
+        match tokio::time::timeout_at(deadline, rx.recv()).await {
+            Ok(Some(pending)) => batch.push(pending),
+            Ok(None) | Err(_) => break,
+        }
+    }
+    Some(batch)
+}
+
+async fn run(mut rx: mpsc::UnboundedReceiver<Pending>, window: Duration, max_batch: usize) {
+    while let Some(batch) = next_batch(&mut rx, window, max_batch).await {
+        let (parts, replies): (Vec<_>, Vec<_>) =
+            batch.into_iter().map(|p| (p.parts, p.reply)).unzip();
+        let size = parts.len();
+        let results =
+            match tokio::task::spawn_blocking(move || ProofVerifier::verify_transact_batch(&parts))
+                .await
+            {
+                Ok(results) => results,
+                Err(e) => {
+                    log::error!("transact batch verification task failed: {}", e);
+                    vec![
+                        VerificationResult::Invalid {
+                            reason: format!("verification task failed: {}", e),
+                        };
+                        size
+                    ]
+                }
+            };
+        log::debug!("verified a batch of {} transact proofs", size);
+        for (reply, result) in replies.into_iter().zip(results) {
+            // The caller may have given up; nothing to do then.
+            let _ = reply.send(result);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(ext_amount: i64, proof: Vec<u8>) -> TransactVerificationRequest {
+        TransactVerificationRequest {
+            request_id: String::new(),
+            recipient: [3u8; 32],
+            mint: None,
+            nullifiers: [[1u8; 32], [2u8; 32]],
+            output_commitments: [[3u8; 32], [4u8; 32]],
+            root: [0u8; 32],
+            ext_amount,
+            proof,
+            ciphertexts: [String::new(), String::new()],
+            timestamp: 0,
+            slot: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_each_get_their_own_result() {
+        let queue = TransactVerificationQueue::new(Duration::from_millis(50), 8);
+        let deposit = request(500, vec![0u8; 64]);
+        let garbled = request(-500, vec![0xFFu8; 8]);
+        let (a, b) = tokio::join!(queue.verify(&deposit), queue.verify(&garbled));
+        match (a, b) {
+            (
+                VerificationResult::Invalid { reason: a },
+                VerificationResult::Invalid { reason: b },
+            ) => {
+                assert!(a.contains("positive ext_amount"), "{a}");
+                assert!(b.contains("Invalid proof envelope"), "{b}");
+            }
+            other => panic!("both requests must be rejected, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batches_close_on_the_window_or_when_full() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut replies = Vec::new();
+        for _ in 0..5 {
+            let (reply, rx) = oneshot::channel();
+            replies.push(rx);
+            tx.send(Pending {
+                parts: proof_parts(&request(0, Vec::new())),
+                reply,
+            })
+            .unwrap();
+        }
+
+        let window = Duration::from_millis(20);
+        let full = next_batch(&mut rx, window, 3).await.unwrap();
+        assert_eq!(full.len(), 3);
+        let rest = next_batch(&mut rx, window, 3).await.unwrap();
+        assert_eq!(rest.len(), 2);
+
+        drop(tx);
+        assert!(next_batch(&mut rx, window, 3).await.is_none());
+    }
+
+    #[test]
+    fn proof_parts_lift_the_mint_to_its_asset_id() {
+        let mut spl = request(-5, Vec::new());
+        spl.mint = Some([9u8; 32]);
+        assert_eq!(
+            proof_parts(&spl).asset_id,
+            crate::privacy::poseidon_circom::mint_to_asset(&[9u8; 32])
+        );
+        assert_eq!(proof_parts(&request(-5, Vec::new())).asset_id, [0u8; 32]);
+    }
+}
@@ -62,6 +62,10 @@ pub struct VoteTally {
     /// Request ID
     pub request_id: String,
 
+    /// Stake epoch the votes are counted against; bound into every vote
+    /// signature and into equivocation evidence.
+    pub epoch: u64,
+
     /// Votes, keyed by the voter's on-chain co-sign wallet (base58).
     pub votes: Arc<RwLock<HashMap<String, VoteRecord>>>,
 
@@ -81,9 +85,11 @@ pub struct VoteTally {
 }
 
 impl VoteTally {
-    /// Create a new tally for `request_id` with explicit BFT thresholds.
+    /// Create a new tally for `request_id` in stake `epoch` with explicit BFT
+    /// thresholds.
     pub fn new(
         request_id: String,
+        epoch: u64,
         min_validators_for_consensus: usize,
         total_validators: usize,
     ) -> Self {
@@ -91,6 +97,7 @@ impl VoteTally {
 
         Self {
             request_id,
+            epoch,
             votes: Arc::new(RwLock::new(HashMap::new())),
             started_at: now,
             deadline: now + 30, // 30 second deadline
@@ -126,7 +133,9 @@ impl VoteTally {
             }
             let evidence = SlashingEvidence::Equivocation {
                 request_id: self.request_id.clone(),
+                epoch: self.epoch,
                 wallet_pubkey: wallet.clone(),
+                previous_validator: previous.node_id.clone(),
                 previous_vote: previous.vote.clone(),
                 new_vote: vote,
                 previous_signature: previous.signature.clone(),
@@ -240,6 +249,23 @@ impl VoteTally {
         }
         out
     }
+
+    /// The signed `Valid` votes of the wallets in `eligible_wallets`, sorted by
+    /// wallet — the evidence an approval carries so that a node which did not
+    /// collect the votes can still run the co-sign round.
+    pub async fn valid_votes(
+        &self,
+        eligible_wallets: &HashSet<String>,
+    ) -> Vec<(String, VoteRecord)> {
+        let votes = self.votes.read().await;
+        let mut out: Vec<(String, VoteRecord)> = votes
+            .iter()
+            .filter(|(wallet, rec)| rec.vote.is_valid() && eligible_wallets.contains(*wallet))
+            .map(|(wallet, rec)| (wallet.clone(), rec.clone()))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out
+    }
 }
 
 #[cfg(test)]
@@ -252,7 +278,7 @@ mod tests {
 
     #[tokio::test]
     async fn valid_voters_lists_only_eligible_valid_votes() {
-        let tally = VoteTally::new("req-1".to_string(), 2, 3);
+        let tally = VoteTally::new("req-1".to_string(), 0, 2, 3);
         tally
             .submit_vote(
                 "W1".into(),
@@ -287,11 +313,18 @@ mod tests {
         let mut voters = tally.valid_voters(&eligible).await;
         voters.sort_by(|a, b| a.0.cmp(&b.0));
         assert_eq!(voters, vec![NodeId(vec![1]), NodeId(vec![3])]);
+
+        let votes = tally.valid_votes(&eligible).await;
+        let signed: Vec<(&str, &[u8])> = votes
+            .iter()
+            .map(|(w, rec)| (w.as_str(), rec.signature.as_slice()))
+            .collect();
+        assert_eq!(signed, vec![("W1", &[1u8][..]), ("W3", &[3u8][..])]);
     }
 
     #[tokio::test]
     async fn valid_voters_excludes_wallets_not_in_eligible_set() {
-        let tally = VoteTally::new("req-1".to_string(), 1, 3);
+        let tally = VoteTally::new("req-1".to_string(), 0, 1, 3);
         tally
             .submit_vote(
                 "W1".into(),
@@ -320,7 +353,7 @@ mod tests {
 
     #[tokio::test]
     async fn equivocation_is_wallet_keyed_and_carries_both_signatures() {
-        let tally = VoteTally::new("req-1".to_string(), 1, 2);
+        let tally = VoteTally::new("req-1".to_string(), 0, 1, 2);
         // Same wallet, two different NodeIds, Valid then Invalid -> equivocation
         // attributed to the wallet, undodgeable by rotating NodeId.
         assert!(tally
@@ -347,11 +380,13 @@ mod tests {
         match evidence {
             Some(SlashingEvidence::Equivocation {
                 wallet_pubkey,
+                previous_validator,
                 previous_signature,
                 new_signature,
                 ..
             }) => {
                 assert_eq!(wallet_pubkey, "W1");
+                assert_eq!(previous_validator, NodeId(vec![1]));
                 assert_eq!(previous_signature, vec![9, 9]);
                 assert_eq!(new_signature, vec![8, 8]);
             }
@@ -17,6 +17,7 @@ pub mod task;
 pub mod types;
 pub mod utils;
 pub mod validator;
+pub mod wallet;
 pub mod web;
 
 pub use config::Settings;
@@ -138,4 +138,14 @@ pub enum Message {
     TransactVerificationResult {
         result: crate::consensus::transact::TransactVerificationResult,
     },
+
+    /// A quorum-approved transact, its quorum certificate, and the finalized
+    /// slot its settlement leader schedule is anchored to (initiator -> all
+    /// validators), so a backup can take over if the primary never settles it
+    /// and a validator that missed the round need not re-verify the proof.
+    TransactApproved {
+        request: crate::consensus::transact::TransactVerificationRequest,
+        certificate: crate::consensus::QuorumCertificate,
+        anchor_slot: u64,
+    },
 }
@@ -7,6 +7,7 @@ pub mod heartbeat;
 mod message;
 pub mod protocol;
 pub mod req_resp;
+pub mod snapshot;
 
 pub use compute_protocol::{
     ComputeJobCodec, ComputeJobRequest, ComputeJobResponse, ComputeQueryCodec, ComputeQueryRequest,
@@ -24,3 +25,7 @@ pub use heartbeat::{
 pub use message::Message;
 pub use protocol::NetworkManager;
 pub use req_resp::{ResultRequest, ResultResponse};
+pub use snapshot::{
+    create_snapshot_protocol, fetch_snapshot, SnapshotCodec, SnapshotRequest, SnapshotResponse,
+    MAX_SNAPSHOT_PAYLOAD_BYTES, SNAPSHOT_PROTOCOL,
+};
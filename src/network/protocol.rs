@@ -35,6 +35,7 @@ use super::heartbeat::{
 };
 use super::message::Message;
 use super::req_resp::{create_result_protocol, ResultCodec, ResultRequest, ResultResponse};
+use super::snapshot::{create_snapshot_protocol, SnapshotCodec, SnapshotRequest, SnapshotResponse};
 
 // Global topic for all paraloom messages
 const PARALOOM_TOPIC: &str = "paraloom/v1";
@@ -64,6 +65,9 @@ pub struct ParaloomBehaviour {
     /// transaction message and collects their signatures to satisfy the
     /// on-chain validator quorum.
     pub cosign: RequestResponse<CoSignCodec>,
+    /// Snapshot fetch protocol. A joining node downloads a peer's pool
+    /// snapshot manifest and chunks instead of replaying the chain.
+    pub snapshot: RequestResponse<SnapshotCodec>,
     /// Kademlia DHT for peer discovery (#65). Routing table is
     /// empty at construction; bootstrap registration and periodic
     /// refresh land in subsequent PRs.
@@ -165,6 +169,24 @@ pub trait NetworkEventHandler: Send + Sync {
             signature: None,
         })
     }
+
+    /// Handle an inbound snapshot request. The default serves nothing; a
+    /// bridge node that keeps a pool answers from its cached snapshot.
+    async fn handle_snapshot_request(
+        &self,
+        _source: NodeId,
+        request: SnapshotRequest,
+    ) -> Result<SnapshotResponse> {
+        Ok(declined_snapshot(&request))
+    }
+}
+
+/// The "nothing to serve" answer to `request`.
+fn declined_snapshot(request: &SnapshotRequest) -> SnapshotResponse {
+    match request {
+        SnapshotRequest::Manifest => SnapshotResponse::Manifest(None),
+        SnapshotRequest::Chunk { .. } => SnapshotResponse::Chunk(None),
+    }
 }
 
 /// Network manager
@@ -187,6 +209,9 @@ pub struct NetworkManager {
     /// response arrives, or drops it on outbound failure / timeout so the
     /// awaiter errors instead of hanging.
     cosign_waiters: Arc<Mutex<HashMap<OutboundRequestId, oneshot::Sender<CoSignResponse>>>>,
+    /// Outstanding snapshot requests, completed by the event loop the same
+    /// way as `cosign_waiters`.
+    snapshot_waiters: Arc<Mutex<HashMap<OutboundRequestId, oneshot::Sender<SnapshotResponse>>>>,
 }
 
 /// Load a libp2p ed25519 identity from `path` (protobuf-encoded, the format
@@ -294,6 +319,7 @@ impl NetworkManager {
         let request_response = create_result_protocol();
         let heartbeat = create_heartbeat_protocol();
         let cosign = create_cosign_protocol();
+        let snapshot = create_snapshot_protocol();
 
         // Kademlia DHT in Server mode so this node accepts queries
         // from other peers and contributes its routing-table view.
@@ -374,6 +400,7 @@ impl NetworkManager {
                 request_response,
                 heartbeat,
                 cosign,
+                snapshot,
                 kad,
                 ping,
                 autonat,
@@ -411,6 +438,7 @@ impl NetworkManager {
             connected_peers: Arc::new(Mutex::new(Vec::new())),
             peer_registry: Arc::new(Mutex::new(PeerRegistry::new())),
             cosign_waiters: Arc::new(Mutex::new(HashMap::new())),
+            snapshot_waiters: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
@@ -620,6 +648,7 @@ impl NetworkManager {
         let connected_peers_clone = self.connected_peers.clone();
         let peer_registry_clone = self.peer_registry.clone();
         let cosign_waiters_clone = self.cosign_waiters.clone();
+        let snapshot_waiters_clone = self.snapshot_waiters.clone();
 
         // Spawn task to handle events
         tokio::spawn(async move {
@@ -630,6 +659,7 @@ impl NetworkManager {
                 connected_peers_clone,
                 peer_registry_clone,
                 cosign_waiters_clone,
+                snapshot_waiters_clone,
             )
             .await;
         });
@@ -645,6 +675,7 @@ impl NetworkManager {
         connected_peers: Arc<Mutex<Vec<PeerId>>>,
         peer_registry: Arc<Mutex<PeerRegistry>>,
         cosign_waiters: Arc<Mutex<HashMap<OutboundRequestId, oneshot::Sender<CoSignResponse>>>>,
+        snapshot_waiters: Arc<Mutex<HashMap<OutboundRequestId, oneshot::Sender<SnapshotResponse>>>>,
     ) {
         info!("Starting network event loop");
 
@@ -933,6 +964,59 @@ impl NetworkManager {
                                             }
                                         }
 
+                                        ParaloomBehaviourEvent::Snapshot(snapshot_event) => {
+                                            match snapshot_event {
+                                                RequestResponseEvent::Message { peer, message, connection_id: _ } => {
+                                                    match message {
+                                                        RequestResponseMessage::Request { request, channel, .. } => {
+                                                            let source = NodeId(peer.to_bytes());
+                                                            let declined = declined_snapshot(&request);
+                                                            let handler_lock = handler.lock().await;
+                                                            let response = if let Some(h) = handler_lock.as_ref() {
+                                                                match h.handle_snapshot_request(source, request).await {
+                                                                    Ok(resp) => resp,
+                                                                    Err(e) => {
+                                                                        log::error!("snapshot handler error: {}", e);
+                                                                        declined
+                                                                    }
+                                                                }
+                                                            } else {
+                                                                declined
+                                                            };
+                                                            drop(handler_lock);
+                                                            let mut swarm_lock = swarm.lock().await;
+                                                            if let Err(e) = swarm_lock.behaviour_mut().snapshot.send_response(channel, response) {
+                                                                log::error!("Failed to send snapshot response: {:?}", e);
+                                                            }
+                                                        }
+                                                        RequestResponseMessage::Response { request_id, response, .. } => {
+                                                            if let Some(tx) = snapshot_waiters.lock().await.remove(&request_id) {
+                                                                let _ = tx.send(response);
+                                                            } else {
+                                                                debug!("snapshot response with no waiter: {:?}", request_id);
+                                                            }
+                                                        }
+                                                    }
+                                                }
+                                                RequestResponseEvent::OutboundFailure { peer, request_id, error, .. } => {
+                                                    log::warn!(
+                                                        "snapshot outbound failure to {:?}: {:?}",
+                                                        peer, error
+                                                    );
+                                                    snapshot_waiters.lock().await.remove(&request_id);
+                                                }
+                                                RequestResponseEvent::InboundFailure { peer, error, .. } => {
+                                                    log::warn!(
+                                                        "snapshot inbound failure from {:?}: {:?}",
+                                                        peer, error
+                                                    );
+                                                }
+                                                RequestResponseEvent::ResponseSent { peer, .. } => {
+                                                    debug!("snapshot response sent to {}", peer);
+                                                }
+                                            }
+                                        }
+
                                         ParaloomBehaviourEvent::Kad(kad_event) => {
                                             match kad_event {
                                                 KadEvent::RoutingUpdated { peer, .. } => {
@@ -1154,6 +1238,27 @@ impl NetworkManager {
             .map_err(|_| anyhow!("cosign request to {} failed or timed out", peer_id))
     }
 
+    /// Send a snapshot request to a peer and await its response. Errors if
+    /// the request fails or times out, like [`Self::send_cosign_request`].
+    pub async fn send_snapshot_request(
+        &self,
+        peer: NodeId,
+        request: SnapshotRequest,
+    ) -> Result<SnapshotResponse> {
+        let peer_id = PeerId::from_bytes(&peer.0).map_err(|e| anyhow!("Invalid peer ID: {}", e))?;
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut swarm = self.swarm.lock().await;
+            let request_id = swarm
+                .behaviour_mut()
+                .snapshot
+                .send_request(&peer_id, request);
+            self.snapshot_waiters.lock().await.insert(request_id, tx);
+        }
+        rx.await
+            .map_err(|_| anyhow!("snapshot request to {} failed or timed out", peer_id))
+    }
+
     /// Get local peer ID
     pub fn local_peer_id(&self) -> NodeId {
         NodeId(self.peer_id.to_bytes())
@@ -0,0 +1,254 @@
+//! Snapshot fetch protocol.
+//!
+//! A node joining the network asks a peer for its current pool snapshot
+//! (see `privacy::snapshot`) instead of replaying the chain. It first asks
+//! for the [`SnapshotManifest`], then for each chunk by index, naming the
+//! manifest it is assembling so a peer that has since refreshed its
+//! snapshot declines rather than serving a chunk of a different one.
+//!
+//! The peer is not trusted: chunks are checked against the manifest's
+//! hashes as they arrive, and the assembled snapshot is checked against the
+//! chain before it is imported.
+//!
+//! Wire format and bounded reads follow `cosign`.
+
+use crate::network::protocol::NetworkManager;
+use crate::privacy::snapshot::{
+    PoolSnapshot, SnapshotAssembler, SnapshotManifest, SNAPSHOT_CHUNK_BYTES,
+};
+use crate::types::NodeId;
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use futures::prelude::*;
+use libp2p::request_response::{Behaviour as RequestResponse, Codec, Config, ProtocolSupport};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::time::Duration;
+
+/// Protocol name used by libp2p request-response.
+pub const SNAPSHOT_PROTOCOL: &str = "/paraloom/snapshot/1.0.0";
+
+/// Cap on a single snapshot payload: one chunk plus room for framing. A
+/// manifest lists one hash per MiB of snapshot and stays far below it.
+pub const MAX_SNAPSHOT_PAYLOAD_BYTES: usize = SNAPSHOT_CHUNK_BYTES + 64 * 1024;
+
+/// Attempts per chunk before a fetch gives up on the peer.
+const CHUNK_ATTEMPTS: usize = 3;
+
+/// Joining node → peer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotRequest {
+    /// Describe the snapshot you are serving.
+    Manifest,
+    /// Send chunk `index` of the snapshot whose manifest id is `id`.
+    Chunk { id: [u8; 32], index: u32 },
+}
+
+/// Peer → joining node. `None` means the peer has nothing to serve: no
+/// snapshot at all, or no longer the one the chunk request names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotResponse {
+    Manifest(Option<SnapshotManifest>),
+    Chunk(Option<Vec<u8>>),
+}
+
+/// Read at most [`MAX_SNAPSHOT_PAYLOAD_BYTES`] from `io`.
+async fn read_size_bounded<T>(io: &mut T) -> io::Result<Vec<u8>>
+where
+    T: AsyncRead + Unpin + Send,
+{
+    let mut buf = Vec::new();
+    let mut limited = io.take(MAX_SNAPSHOT_PAYLOAD_BYTES as u64 + 1);
+    limited.read_to_end(&mut buf).await?;
+    if buf.len() > MAX_SNAPSHOT_PAYLOAD_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "snapshot payload exceeds {} bytes",
+                MAX_SNAPSHOT_PAYLOAD_BYTES
+            ),
+        ));
+    }
+    Ok(buf)
+}
+
+/// Bincode-backed codec, structurally identical to `CoSignCodec`.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotCodec;
+
+#[async_trait]
+impl Codec for SnapshotCodec {
+    type Protocol = &'static str;
+    type Request = SnapshotRequest;
+    type Response = SnapshotResponse;
+
+    async fn read_request<T>(
+        &mut self,
+        _protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let buf = read_size_bounded(io).await?;
+        bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let buf = read_size_bounded(io).await?;
+        bincode::deserialize(&buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _protocol: &Self::Protocol,
+        io: &mut T,
+        req: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let data =
+            bincode::serialize(&req).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        io.write_all(&data).await?;
+        io.close().await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _protocol: &Self::Protocol,
+        io: &mut T,
+        res: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        let data =
+            bincode::serialize(&res).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        io.write_all(&data).await?;
+        io.close().await
+    }
+}
+
+/// Build the libp2p request-response behaviour for the snapshot protocol.
+///
+/// 30s leaves room for a 1 MiB chunk over a slow link, and for the serving
+/// node to build its snapshot when a manifest request finds none cached.
+pub fn create_snapshot_protocol() -> RequestResponse<SnapshotCodec> {
+    let protocols = [(SNAPSHOT_PROTOCOL, ProtocolSupport::Full)];
+    let cfg = Config::default().with_request_timeout(Duration::from_secs(30));
+    RequestResponse::with_codec(SnapshotCodec, protocols.iter().cloned(), cfg)
+}
+
+/// Download `peer`'s current snapshot, chunk by chunk.
+///
+/// The result has been checked against the manifest the peer announced, not
+/// against the chain; that is the importer's job.
+pub async fn fetch_snapshot(network: &NetworkManager, peer: NodeId) -> Result<PoolSnapshot> {
+    let manifest = match network
+        .send_snapshot_request(peer.clone(), SnapshotRequest::Manifest)
+        .await?
+    {
+        SnapshotResponse::Manifest(Some(manifest)) => manifest,
+        SnapshotResponse::Manifest(None) => bail!("peer has no snapshot to serve"),
+        SnapshotResponse::Chunk(_) => bail!("peer answered a manifest request with a chunk"),
+    };
+    let id = manifest.id();
+    let mut assembler = SnapshotAssembler::new(manifest)?;
+    log::info!(
+        "fetching snapshot of {} leaves in {} chunks",
+        assembler.manifest().leaf_count,
+        assembler.manifest().chunk_hashes.len()
+    );
+
+    for index in assembler.missing() {
+        let mut last_error = None;
+        for _ in 0..CHUNK_ATTEMPTS {
+            let request = SnapshotRequest::Chunk { id, index };
+            match network.send_snapshot_request(peer.clone(), request).await {
+                Ok(SnapshotResponse::Chunk(Some(chunk))) => match assembler.accept(index, chunk) {
+                    Ok(()) => {
+                        last_error = None;
+                        break;
+                    }
+                    Err(e) => last_error = Some(e),
+                },
+                Ok(SnapshotResponse::Chunk(None)) => {
+                    bail!("peer stopped serving the snapshot at chunk {}", index)
+                }
+                Ok(SnapshotResponse::Manifest(_)) => {
+                    last_error = Some(anyhow!("peer answered a chunk request with a manifest"))
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        if let Some(e) = last_error {
+            return Err(e.context(format!("chunk {} failed {} times", index, CHUNK_ATTEMPTS)));
+        }
+    }
+    assembler.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::snapshot::SnapshotCursor;
+    use futures::io::Cursor;
+
+    #[tokio::test]
+    async fn read_size_bounded_admits_a_full_chunk() {
+        let response = SnapshotResponse::Chunk(Some(vec![0xAB; SNAPSHOT_CHUNK_BYTES]));
+        let encoded = bincode::serialize(&response).expect("serialize");
+        let mut cursor = Cursor::new(encoded.clone());
+        let read = read_size_bounded(&mut cursor).await.expect("full chunk");
+        assert_eq!(read, encoded);
+    }
+
+    #[tokio::test]
+    async fn read_size_bounded_rejects_payload_over_limit() {
+        let mut cursor = Cursor::new(vec![0xCD; MAX_SNAPSHOT_PAYLOAD_BYTES + 1]);
+        let err = read_size_bounded(&mut cursor)
+            .await
+            .expect_err("over-limit payload must error");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn snapshot_messages_round_trip_through_bincode() {
+        let manifest = SnapshotManifest {
+            version: 1,
+            cursor: SnapshotCursor {
+                slot: 9,
+                signature: None,
+            },
+            leaf_count: 4,
+            onchain_root: [3; 32],
+            total_bytes: 10,
+            chunk_hashes: vec![[4; 32]],
+        };
+        let request = SnapshotRequest::Chunk {
+            id: manifest.id(),
+            index: 0,
+        };
+        let encoded = bincode::serialize(&request).expect("serialize");
+        let decoded: SnapshotRequest = bincode::deserialize(&encoded).expect("deserialize");
+        assert_eq!(decoded, request);
+
+        for response in [
+            SnapshotResponse::Manifest(Some(manifest)),
+            SnapshotResponse::Manifest(None),
+            SnapshotResponse::Chunk(None),
+        ] {
+            let encoded = bincode::serialize(&response).expect("serialize");
+            let decoded: SnapshotResponse = bincode::deserialize(&encoded).expect("deserialize");
+            assert_eq!(decoded, response);
+        }
+    }
+}
@@ -0,0 +1,416 @@
+//! Association-set server.
+//!
+//! Serves the two ends of the allow-list flow in `privacy::association`: the
+//! deposits a curator builds a list from, and the published lists a wallet
+//! proves against and a counterparty checks proofs with. Publishing a list is
+//! adding its JSON to `privacy.association_sets`; every root served here is
+//! one the operator chose to vouch for.
+//!
+//! Like the Merkle path server it binds its own address
+//! (`privacy.association_address`), since the set listings grow with the pool.
+//!
+//! ## Endpoints
+//! - `GET /association/deposits?from=<leaf>&limit=<n>` — the deposits the
+//!   bridge listener recorded at leaf `from` or above, in leaf order, at most
+//!   [`MAX_DEPOSIT_PAGE`] per page. `503` on a node that does not index the
+//!   pool.
+//! - `GET /association/sets` — one summary per served list.
+//! - `GET /association/sets/<root hex32>` — the list as published, leaves
+//!   included, so a counterparty can rebuild and check the root itself.
+//! - `GET /association/sets/<root hex32>/path/<commitment hex32>` — the
+//!   allow-list path of a listed deposit, in the shape of
+//!   `GET /merkle/path`. `404` if either is unknown.
+//! - `POST /association/verify` — body an
+//!   [`AssociationProof`]; `200 { "valid":
+//!   bool, "reason": string | null }`. `404` if its root is not served here,
+//!   `503` without a verifying key.
+
+use axum::{
+    extract::{Extension, Path, Query},
+    http::StatusCode,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::node::merkle_path::MerklePathResponse;
+use crate::privacy::association::{
+    AssociationProof, AssociationSet, AssociationVerifier, PublishedAssociationSet,
+};
+use crate::privacy::proof::VerificationResult;
+use crate::privacy::types::AssetId;
+use crate::storage::{DepositRecord, PrivacyStorage};
+
+/// Most deposits returned by one page.
+pub const MAX_DEPOSIT_PAGE: usize = 1_000;
+
+/// Where recorded deposits are read from. Abstracted behind a trait so the
+/// router can be unit-tested with a stub.
+pub trait DepositSource: Send + Sync {
+    fn deposits_from(&self, from: u64, limit: usize) -> anyhow::Result<Vec<DepositRecord>>;
+}
+
+impl DepositSource for PrivacyStorage {
+    fn deposits_from(&self, from: u64, limit: usize) -> anyhow::Result<Vec<DepositRecord>> {
+        PrivacyStorage::deposits_from(self, from, limit)
+    }
+}
+
+/// Everything the server answers from.
+pub struct AssociationService {
+    /// `None` on a node that does not index the pool.
+    pub deposits: Option<Arc<dyn DepositSource>>,
+    pub sets: Vec<AssociationSet>,
+    /// `None` when no association verifying key is installed.
+    pub verifier: Option<AssociationVerifier>,
+}
+
+impl AssociationService {
+    fn set(&self, root: &[u8; 32]) -> Option<&AssociationSet> {
+        self.sets.iter().find(|s| &s.root() == root)
+    }
+}
+
+/// A recorded deposit on the wire. Also the client-side decoding type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositResponse {
+    pub commitment: String,
+    pub leaf_index: u64,
+    pub depositor: String,
+    pub amount: u64,
+    pub asset_id: String,
+    pub slot: u64,
+    pub signature: String,
+}
+
+impl From<&DepositRecord> for DepositResponse {
+    fn from(d: &DepositRecord) -> Self {
+        DepositResponse {
+            commitment: hex::encode(d.commitment),
+            leaf_index: d.leaf_index,
+            depositor: hex::encode(d.depositor),
+            amount: d.amount,
+            asset_id: hex::encode(d.asset_id),
+            slot: d.slot,
+            signature: d.signature.clone(),
+        }
+    }
+}
+
+impl DepositResponse {
+    /// Decode back into a [`DepositRecord`]. `None` if a key field is not
+    /// 32-byte hex.
+    pub fn to_record(&self) -> Option<DepositRecord> {
+        let asset_id: AssetId = decode_hex32(&self.asset_id)?;
+        Some(DepositRecord {
+            commitment: decode_hex32(&self.commitment)?,
+            leaf_index: self.leaf_index,
+            depositor: decode_hex32(&self.depositor)?,
+            amount: self.amount,
+            asset_id,
+            slot: self.slot,
+            signature: self.signature.clone(),
+        })
+    }
+}
+
+/// A served list without its leaves.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssociationSetSummary {
+    pub name: String,
+    pub root: String,
+    pub through_leaf: u64,
+    pub excluded: u64,
+    pub created_at: u64,
+    pub size: usize,
+}
+
+impl From<&PublishedAssociationSet> for AssociationSetSummary {
+    fn from(p: &PublishedAssociationSet) -> Self {
+        AssociationSetSummary {
+            name: p.name.clone(),
+            root: hex::encode(p.root),
+            through_leaf: p.through_leaf,
+            excluded: p.excluded,
+            created_at: p.created_at,
+            size: p.leaves.len(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    pub valid: bool,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DepositQuery {
+    #[serde(default)]
+    from: u64,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+fn decode_hex32(s: &str) -> Option<[u8; 32]> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s))
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+fn parse_hex32(what: &str, s: &str) -> Result<[u8; 32], (StatusCode, String)> {
+    decode_hex32(s).ok_or((
+        StatusCode::BAD_REQUEST,
+        format!("{what} must be 32 bytes of hex"),
+    ))
+}
+
+fn known_set<'a>(
+    service: &'a AssociationService,
+    root: &str,
+) -> Result<&'a AssociationSet, (StatusCode, String)> {
+    let root = parse_hex32("root", root)?;
+    service.set(&root).ok_or((
+        StatusCode::NOT_FOUND,
+        "association set not served here".to_string(),
+    ))
+}
+
+async fn deposits_handler(
+    Extension(service): Extension<Arc<AssociationService>>,
+    Query(query): Query<DepositQuery>,
+) -> Result<Json<Vec<DepositResponse>>, (StatusCode, String)> {
+    let source = service.deposits.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "this node does not index the pool".to_string(),
+    ))?;
+    let limit = query
+        .limit
+        .unwrap_or(MAX_DEPOSIT_PAGE)
+        .min(MAX_DEPOSIT_PAGE);
+    let deposits = source
+        .deposits_from(query.from, limit)
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))?;
+    Ok(Json(deposits.iter().map(DepositResponse::from).collect()))
+}
+
+async fn sets_handler(
+    Extension(service): Extension<Arc<AssociationService>>,
+) -> Json<Vec<AssociationSetSummary>> {
+    Json(
+        service
+            .sets
+            .iter()
+            .map(|s| AssociationSetSummary::from(s.published()))
+            .collect(),
+    )
+}
+
+async fn set_handler(
+    Extension(service): Extension<Arc<AssociationService>>,
+    Path(root): Path<String>,
+) -> Result<Json<PublishedAssociationSet>, (StatusCode, String)> {
+    Ok(Json(known_set(&service, &root)?.published().clone()))
+}
+
+async fn path_handler(
+    Extension(service): Extension<Arc<AssociationService>>,
+    Path((root, commitment)): Path<(String, String)>,
+) -> Result<Json<MerklePathResponse>, (StatusCode, String)> {
+    let set = known_set(&service, &root)?;
+    let commitment = parse_hex32("commitment", &commitment)?;
+    let membership = set.membership(&commitment).await.ok_or((
+        StatusCode::NOT_FOUND,
+        "commitment not in this association set".to_string(),
+    ))?;
+    Ok(Json(MerklePathResponse::from(&membership)))
+}
+
+async fn verify_handler(
+    Extension(service): Extension<Arc<AssociationService>>,
+    Json(proof): Json<AssociationProof>,
+) -> Result<Json<VerifyResponse>, (StatusCode, String)> {
+    if service.set(&proof.association_root).is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "association set not served here".to_string(),
+        ));
+    }
+    let verifier = service.verifier.as_ref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "no association verifying key installed".to_string(),
+    ))?;
+    Ok(Json(match verifier.verify(&proof) {
+        VerificationResult::Valid => VerifyResponse {
+            valid: true,
+            reason: None,
+        },
+        VerificationResult::Invalid { reason } => VerifyResponse {
+            valid: false,
+            reason: Some(reason),
+        },
+    }))
+}
+
+/// Build the association router. Exposed separately from [`serve`] so it can
+/// be driven directly in tests.
+pub fn router(service: Arc<AssociationService>) -> Router {
+    Router::new()
+        .route("/association/deposits", get(deposits_handler))
+        .route("/association/sets", get(sets_handler))
+        .route("/association/sets/:root", get(set_handler))
+        .route(
+            "/association/sets/:root/path/:commitment",
+            get(path_handler),
+        )
+        .route("/association/verify", post(verify_handler))
+        .layer(Extension(service))
+}
+
+/// Bind the association server on `addr` and serve until the task is dropped.
+pub async fn serve(
+    service: Arc<AssociationService>,
+    addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    log::info!(
+        target: "paraloom::node::association",
+        "Association server listening on http://{} ({} set(s))",
+        addr,
+        service.sets.len()
+    );
+    axum::Server::bind(&addr)
+        .serve(router(service).into_make_service())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::association::Exclusions;
+    use crate::privacy::types::NATIVE_SOL_ASSET;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt; // for oneshot
+
+    struct StubDeposits(Vec<DepositRecord>);
+
+    impl DepositSource for StubDeposits {
+        fn deposits_from(&self, from: u64, limit: usize) -> anyhow::Result<Vec<DepositRecord>> {
+            Ok(self
+                .0
+                .iter()
+                .filter(|d| d.leaf_index >= from)
+                .take(limit)
+                .cloned()
+                .collect())
+        }
+    }
+
+    fn deposit(leaf_index: u64) -> DepositRecord {
+        DepositRecord {
+            commitment: [leaf_index as u8 + 1; 32],
+            leaf_index,
+            depositor: [9; 32],
+            amount: 500,
+            asset_id: NATIVE_SOL_ASSET,
+            slot: 100 + leaf_index,
+            signature: format!("sig{leaf_index}"),
+        }
+    }
+
+    async fn service() -> Arc<AssociationService> {
+        let deposits: Vec<DepositRecord> = (0..3).map(deposit).collect();
+        let set = AssociationSet::from_deposits("all", &deposits, &Exclusions::default())
+            .await
+            .unwrap();
+        Arc::new(AssociationService {
+            deposits: Some(Arc::new(StubDeposits(deposits))),
+            sets: vec![set],
+            verifier: None,
+        })
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    async fn body_of<T: serde::de::DeserializeOwned>(resp: axum::response::Response) -> T {
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn deposits_page_from_a_leaf_and_decode_back() {
+        let app = router(service().await);
+        let resp = app
+            .oneshot(get("/association/deposits?from=1&limit=1"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let page: Vec<DepositResponse> = body_of(resp).await;
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].to_record().unwrap(), deposit(1));
+    }
+
+    #[tokio::test]
+    async fn served_set_paths_fold_to_its_root() {
+        let service = service().await;
+        let root = hex::encode(service.sets[0].root());
+        let app = router(service);
+
+        let resp = app.clone().oneshot(get("/association/sets")).await.unwrap();
+        let sets: Vec<AssociationSetSummary> = body_of(resp).await;
+        assert_eq!(sets.len(), 1);
+        assert_eq!((sets[0].root.as_str(), sets[0].size), (root.as_str(), 3));
+
+        let resp = app
+            .clone()
+            .oneshot(get(&format!(
+                "/association/sets/{root}/path/{}",
+                hex::encode([2u8; 32])
+            )))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let path: MerklePathResponse = body_of(resp).await;
+        let membership = path.to_membership().unwrap();
+        assert_eq!(membership.fold(&[2; 32]), membership.root);
+
+        let resp = app
+            .oneshot(get(&format!(
+                "/association/sets/{}/path/{}",
+                hex::encode([0u8; 32]),
+                hex::encode([2u8; 32])
+            )))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn proofs_against_unserved_roots_are_refused() {
+        let app = router(service().await);
+        let proof = AssociationProof {
+            association_root: [0; 32],
+            nullifier: [1; 32],
+            proof: vec![0; 128],
+        };
+        let resp = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/association/verify")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_vec(&proof).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
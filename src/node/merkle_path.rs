@@ -0,0 +1,213 @@
+//! Merkle path-query server (#163).
+//!
+//! A wallet that spends a note through `TransactCircuitV3` has to prove the
+//! note's commitment is a leaf of a root the program published. It knows the
+//! commitment but not the rest of the tree, so it asks a node that indexes the
+//! pool for the leaf's position, the root and the sibling path.
+//!
+//! Everything served here is derivable from public chain data, so the
+//! endpoint is unauthenticated. It is read-only but does O(pool) hashing per
+//! query, so it binds `bridge.merkle_path_query_address` (loopback by
+//! default) rather than the public transact ingress.
+//!
+//! ## Endpoint
+//! - `GET /merkle/path/<commitment hex32>` — `200 { "leaf_index": u64,
+//!   "root": hex32, "path": [hex32; 32] }`, with the path leaf level first and
+//!   hashed the way the on-chain tree is (see
+//!   [`crate::privacy::merkle::OnchainMembership`]). `400` on malformed hex,
+//!   `404` if the node has not indexed the commitment (yet).
+//!
+//! The client must fold the path itself before proving. A node that has not
+//! caught up with the chain serves a root the program no longer (or never)
+//! published, and only the program's root history decides.
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Extension, Path},
+    http::StatusCode,
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use crate::privacy::merkle::OnchainMembership;
+use crate::privacy::types::Commitment;
+
+/// JSON body of a successful path query. Also the client-side decoding type.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerklePathResponse {
+    pub leaf_index: u64,
+    pub root: String,
+    pub path: Vec<String>,
+}
+
+impl From<&OnchainMembership> for MerklePathResponse {
+    fn from(m: &OnchainMembership) -> Self {
+        MerklePathResponse {
+            leaf_index: m.leaf_index,
+            root: hex::encode(m.root),
+            path: m.siblings.iter().map(hex::encode).collect(),
+        }
+    }
+}
+
+impl MerklePathResponse {
+    /// Decode back into an [`OnchainMembership`]. `None` if any field is not
+    /// 32-byte hex.
+    pub fn to_membership(&self) -> Option<OnchainMembership> {
+        let decode = |s: &str| -> Option<[u8; 32]> {
+            hex::decode(s.strip_prefix("0x").unwrap_or(s))
+                .ok()?
+                .try_into()
+                .ok()
+        };
+        Some(OnchainMembership {
+            leaf_index: self.leaf_index,
+            root: decode(&self.root)?,
+            siblings: self
+                .path
+                .iter()
+                .map(|s| decode(s))
+                .collect::<Option<Vec<_>>>()?,
+        })
+    }
+}
+
+/// Where the server looks paths up. Abstracted behind a trait so the router
+/// can be unit-tested with a stub.
+#[async_trait]
+pub trait MerklePathSource: Send + Sync {
+    async fn membership(&self, commitment: &[u8; 32]) -> Option<OnchainMembership>;
+}
+
+#[async_trait]
+impl MerklePathSource for crate::node::Node {
+    async fn membership(&self, commitment: &[u8; 32]) -> Option<OnchainMembership> {
+        self.shielded_pool
+            .as_ref()?
+            .onchain_membership(&Commitment(*commitment))
+            .await
+    }
+}
+
+async fn path_handler(
+    Extension(source): Extension<Arc<dyn MerklePathSource>>,
+    Path(commitment): Path<String>,
+) -> Result<Json<MerklePathResponse>, (StatusCode, String)> {
+    let trimmed = commitment.strip_prefix("0x").unwrap_or(&commitment);
+    let bytes = hex::decode(trimmed).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("commitment: invalid hex: {e}"),
+        )
+    })?;
+    let commitment: [u8; 32] = bytes.try_into().map_err(|_| {
+        (
+            StatusCode::BAD_REQUEST,
+            "commitment must be 32 bytes".to_string(),
+        )
+    })?;
+
+    let membership = source.membership(&commitment).await.ok_or((
+        StatusCode::NOT_FOUND,
+        "commitment not in the indexed pool".to_string(),
+    ))?;
+    Ok(Json(MerklePathResponse::from(&membership)))
+}
+
+/// Build the path-query router. Exposed separately from [`serve`] so it can be
+/// driven directly in tests.
+pub fn router(source: Arc<dyn MerklePathSource>) -> Router {
+    Router::new()
+        .route("/merkle/path/:commitment", get(path_handler))
+        .layer(Extension(source))
+}
+
+/// Bind the path-query server on `addr` and serve until the task is dropped.
+pub async fn serve(
+    source: Arc<dyn MerklePathSource>,
+    addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    log::info!(
+        target: "paraloom::node::merkle_path",
+        "Merkle path server listening on http://{}",
+        addr
+    );
+    axum::Server::bind(&addr)
+        .serve(router(source).into_make_service())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::merkle::MerkleTree;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt; // for oneshot
+
+    /// A bare tree standing in for the node's shielded pool.
+    struct TreeSource(MerkleTree);
+
+    #[async_trait]
+    impl MerklePathSource for TreeSource {
+        async fn membership(&self, commitment: &[u8; 32]) -> Option<OnchainMembership> {
+            self.0.onchain_membership(&Commitment(*commitment)).await
+        }
+    }
+
+    async fn source_with(leaves: &[[u8; 32]]) -> Arc<dyn MerklePathSource> {
+        let tree = MerkleTree::new();
+        for leaf in leaves {
+            tree.insert(&Commitment(*leaf)).await.unwrap();
+        }
+        Arc::new(TreeSource(tree))
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_a_path_that_folds_to_the_root() {
+        let app = router(source_with(&[[1; 32], [2; 32], [3; 32]]).await);
+        let resp = app
+            .oneshot(get(&format!("/merkle/path/{}", hex::encode([2u8; 32]))))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        let parsed: MerklePathResponse = serde_json::from_slice(&body).unwrap();
+        let membership = parsed.to_membership().unwrap();
+        assert_eq!(membership.leaf_index, 1);
+        assert_eq!(membership.fold(&[2; 32]), membership.root);
+    }
+
+    #[tokio::test]
+    async fn unknown_commitment_is_404() {
+        let app = router(source_with(&[[1; 32]]).await);
+        let resp = app
+            .oneshot(get(&format!("/merkle/path/0x{}", hex::encode([7u8; 32]))))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn malformed_commitment_is_400() {
+        let app = router(source_with(&[]).await);
+        for bad in ["zz", "0102"] {
+            let resp = app
+                .clone()
+                .oneshot(get(&format!("/merkle/path/{bad}")))
+                .await
+                .unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{bad}");
+        }
+    }
+}
@@ -17,22 +17,30 @@ use crate::bridge::Bridge;
 use crate::compute::{ComputeAuthPolicy, JobCoordinator, JobExecutor, JobManager};
 use crate::config::Settings;
 use crate::consensus::transact::TransactVerificationRequest;
-use crate::consensus::{ApprovedTransact, TransactVerificationCoordinator};
+use crate::consensus::{ApprovedTransact, QuorumCertificate, TransactVerificationCoordinator};
 use crate::coordinator::Coordinator;
 use crate::network::{
     CoSignRequest, CoSignResponse, Message, NetworkManager, ResultRequest, ResultResponse,
-    SettlementKind,
+    SettlementKind, SnapshotRequest, SnapshotResponse,
 };
 use crate::privacy::pool::ShieldedPool;
+use crate::privacy::snapshot::EncodedSnapshot;
 use crate::resource::ResourceMonitor;
-use crate::storage::{ComputeStorage, PrivacyStorage};
+use crate::storage::{
+    CertificateStorage, ComputeStorage, DeliveredRecord, DeliveredRetention, PrivacyStorage,
+    SlashingStorage,
+};
 use crate::types::{NodeId, NodeInfo, NodeStatus, NodeType};
 use crate::validator::Validator;
 use solana_sdk::signature::{Keypair, Signer};
 use solana_sdk::{pubkey::Pubkey, transaction::Transaction};
 
+pub mod association;
 pub mod cosign_round;
 pub mod ingress_auth;
+pub mod merkle_path;
+mod settlement_schedule;
+mod slashing;
 pub mod transact_ingress;
 
 /// Transact-proof verifier override (#350). `None` in production, so
@@ -102,23 +110,43 @@ pub struct Node {
     /// Taken once by run() to drive the transact submitter task.
     transact_approval_rx: Arc<Mutex<Option<mpsc::UnboundedReceiver<ApprovedTransact>>>>,
 
-    /// Transact submitter task handle (#350): drains approved transacts and
-    /// settles them on-chain. Spawned in run(), aborted in stop().
+    /// Holds each approved transact until this node's turn in its settlement
+    /// leader schedule, then feeds the transact submitter.
+    settlement_scheduler: Arc<settlement_schedule::SettlementScheduler>,
+
+    /// Transact submitter task handle (#350): announces this node's approvals,
+    /// drains the scheduled ones and settles them on-chain. Spawned in run(),
+    /// aborted in stop().
     transact_submitter_task: Arc<Mutex<Option<JoinHandle<()>>>>,
 
     /// Optional transact-proof verifier override (#350 testing seam). `None`
     /// in production.
     transact_proof_verifier_override: Option<TransactProofVerifier>,
 
+    /// Batches transact proofs that arrive together into one randomized
+    /// pairing check (see `consensus::verify_queue`).
+    transact_verify_queue: Arc<crate::consensus::TransactVerificationQueue>,
+
     /// Transact-ingress HTTP server handle (#350). Spawned in run() when
     /// `bridge.transact_ingress_address` is set; aborted in stop().
     transact_ingress: Arc<Mutex<Option<JoinHandle<()>>>>,
 
+    /// Merkle path-query HTTP server handle (#163). Spawned in run() on a node
+    /// that indexes the shielded pool when `bridge.merkle_path_query_address`
+    /// is set; aborted in stop().
+    merkle_path_server: Arc<Mutex<Option<JoinHandle<()>>>>,
+
+    /// Association-set HTTP server handle. Spawned in run() when
+    /// `privacy.association_address` is set; aborted in stop().
+    association_server: Arc<Mutex<Option<JoinHandle<()>>>>,
+
     /// Encrypted output notes this node has seen (#196), served from
     /// `GET /transact/scan` for recipients to trial-decrypt. Populated when the
-    /// node initiates or receives a transact verification request. In-memory;
-    /// persistence across restart is a follow-up.
-    delivered_notes: Arc<Mutex<Vec<transact_ingress::DeliveredNote>>>,
+    /// node initiates or receives a transact verification request. A bridge
+    /// node keeps them in `privacy_storage` so a restart loses none; this
+    /// buffer, each note paired with its sequence number, only stands in when
+    /// that store is absent.
+    delivered_notes: Arc<Mutex<Vec<(u64, transact_ingress::DeliveredNote)>>>,
 
     /// This validator's Solana settlement keypair (#260), loaded from
     /// `bridge.authority_keypair_path`. Used to co-sign settlement transactions
@@ -126,6 +154,11 @@ pub struct Node {
     /// without a configured keypair; such a node declines all co-sign requests.
     cosign_keypair: Option<Arc<Keypair>>,
 
+    /// The validator-registry authority, loaded from
+    /// `bridge.slashing_authority_keypair_path`. When set, recorded
+    /// equivocation is slashed on-chain (see [`slashing`]).
+    slashing_authority: Option<Arc<Keypair>>,
+
     /// Transact requests this node verified as `Valid` (#260/#350), keyed by
     /// request id. A co-sign request is honoured only if its parameters match
     /// the request cached here — so the round leader cannot get this validator
@@ -142,6 +175,21 @@ pub struct Node {
     /// the request id, so randomized proofs of the same spend cannot each claim
     /// a fresh budget.
     cosign_counts: Arc<Mutex<HashMap<String, u32>>>,
+
+    /// Quorum certificates of the transacts this node saw approved, issued
+    /// here or received with an approval, served from
+    /// `GET /transact/certificate/{request_id}`. `None` off the bridge or when
+    /// the store cannot be opened; certificates are then not kept.
+    certificate_storage: Option<Arc<CertificateStorage>>,
+
+    /// Pool snapshots this node serves to joining peers, newest last. The
+    /// one before the newest is kept so a peer part-way through a download
+    /// when the snapshot is refreshed can still finish it.
+    served_snapshots: Arc<Mutex<Vec<Arc<EncodedSnapshot>>>>,
+
+    /// Snapshot refresh task handle. Spawned in run() on a node that
+    /// indexes the pool; aborted in stop().
+    snapshot_task: Arc<Mutex<Option<JoinHandle<()>>>>,
 }
 
 /// Build the compute-job authorization policy (F3) from `[compute]` settings.
@@ -185,6 +233,11 @@ fn build_compute_auth_policy(cfg: &crate::config::ComputeSettings) -> ComputeAut
 /// limit in practice.
 const MAX_VERIFIED_CACHE: usize = 1024;
 
+/// How often a node that indexes the pool re-captures the snapshot it
+/// serves. A joining node replays whatever landed after it, so this only
+/// bounds that replay.
+const SNAPSHOT_REFRESH: Duration = Duration::from_secs(600);
+
 /// Maximum times this node will co-sign one spend, keyed on its input
 /// nullifiers (#593/#606).
 /// The co-sign signature is the node's Solana fee-payer signature, so an
@@ -405,17 +458,53 @@ impl crate::network::protocol::NetworkEventHandler for Node {
                 // libp2p `source` is cryptographically authenticated by the
                 // connection, and a node with no transact coordinator is not a
                 // settling validator and drops outright.
-                let is_validator = match &self.transact_coordinator {
-                    Some(coordinator) => coordinator.source_is_onchain_validator(&source).await,
-                    None => false,
+                let Some(coordinator) = &self.transact_coordinator else {
+                    log::warn!(
+                        "dropping transact verification request from {source:?}: not a settling validator"
+                    );
+                    return Ok(());
                 };
-                if !is_validator {
+                if !coordinator.source_is_onchain_validator(&source).await {
                     log::warn!(
                         "dropping transact verification request from non-validator peer {source:?}"
                     );
                     return Ok(());
                 }
 
+                // The slot is the ingress node's claim, covered by no signature,
+                // and it picks the stake epoch the round is tallied in. Refuse
+                // one far from this node's own view of the chain, failing
+                // closed before the reconciler has read one, and sign the vote
+                // for the epoch derived from it so it only counts in that
+                // epoch. The view is the reconciler's last read run on by the
+                // clock, so no request costs an RPC here.
+                if request.slot.is_some() {
+                    let Some(local_slot) = coordinator.local_slot().await else {
+                        log::warn!(
+                            "dropping transact request {}: no slot read from the chain yet to check its slot",
+                            request.request_id
+                        );
+                        return Ok(());
+                    };
+                    if !request.slot_is_near(local_slot) {
+                        log::warn!(
+                            "dropping transact request {}: slot {:?} is more than {} slots from {}",
+                            request.request_id,
+                            request.slot,
+                            crate::consensus::transact::MAX_REQUEST_SLOT_SKEW,
+                            local_slot
+                        );
+                        return Ok(());
+                    }
+                }
+                let epoch = match coordinator.request_epoch(&request).await {
+                    Ok(epoch) => epoch,
+                    Err(e) => {
+                        log::warn!("dropping transact request {}: {}", request.request_id, e);
+                        return Ok(());
+                    }
+                };
+
                 info!(
                     "Received transact verification request: {}",
                     request.request_id
@@ -469,6 +558,7 @@ impl crate::network::protocol::NetworkEventHandler for Node {
                                 self.record_delivered_notes(
                                     &request.output_commitments,
                                     &request.ciphertexts,
+                                    request.mint.as_ref(),
                                 )
                                 .await;
                             }
@@ -512,7 +602,7 @@ impl crate::network::protocol::NetworkEventHandler for Node {
                     .await;
                 }
 
-                let result = match self.signed_vote(request.request_id.clone(), vote) {
+                let result = match self.signed_vote(request.request_id.clone(), epoch, vote) {
                     Some(r) => r,
                     None => {
                         log::warn!(
@@ -545,26 +635,99 @@ impl crate::network::protocol::NetworkEventHandler for Node {
                     );
                     return Ok(());
                 }
+                // Route the vote into the transact coordinator (#350); a node
+                // that never started this request drops it.
+                let Some(coordinator) = &self.transact_coordinator else {
+                    return Ok(());
+                };
+                let Some(epoch) = coordinator.round_epoch(&result.request_id).await else {
+                    log::debug!(
+                        "dropping transact vote for {}: no round in flight",
+                        result.request_id
+                    );
+                    return Ok(());
+                };
                 // Verify the vote's ed25519 signature over the canonical preimage
                 // BEFORE it reaches the tally: a relayed/forged/unsigned vote (or
-                // one whose wallet/validity/request-id/voter-NodeId do not match
-                // the signature) is dropped here, so counting can trust the
-                // attributed wallet.
-                if !self.verify_vote_signature(&result) {
+                // one whose wallet/validity/request-id/voter-NodeId/epoch do not
+                // match the signature) is dropped here, so counting can trust the
+                // attributed wallet and the epoch it was cast in.
+                if !self.verify_vote_signature(&result, epoch) {
                     log::warn!(
-                        "dropping transact vote for {}: signature invalid for wallet {} (forged/unsigned/relayed)",
+                        "dropping transact vote for {}: signature invalid for wallet {} in epoch {} (forged/unsigned/relayed/other epoch)",
                         result.request_id,
-                        result.wallet_pubkey
+                        result.wallet_pubkey,
+                        epoch
                     );
                     return Ok(());
                 }
-                // Route the vote into the transact coordinator (#350); a node
-                // that never started this request drops it.
-                if let Some(coordinator) = &self.transact_coordinator {
-                    if let Err(e) = coordinator.submit_result(result).await {
-                        log::debug!("dropping transact vote: {}", e);
+                if let Err(e) = coordinator.submit_result(result).await {
+                    log::debug!("dropping transact vote: {}", e);
+                }
+            }
+            Message::TransactApproved {
+                request,
+                certificate,
+                anchor_slot,
+            } => {
+                let request_id = request.request_id.clone();
+                let (Some(coordinator), Some(bridge)) = (&self.transact_coordinator, &self.bridge)
+                else {
+                    return Ok(());
+                };
+                if self.settlement_scheduler.is_scheduled(&request_id).await {
+                    return Ok(());
+                }
+                // Only a validator can announce an approval, and only for the
+                // transact its id commits to.
+                if !coordinator.source_is_onchain_validator(&source).await {
+                    log::warn!("dropping transact approval from non-validator peer {source:?}");
+                    return Ok(());
+                }
+                if request.canonical_id() != request_id || certificate.request_id != request_id {
+                    log::warn!("dropping transact approval with non-canonical id {request_id}");
+                    return Ok(());
+                }
+                // Every vote in the certificate is a distinct wallet's signed
+                // `Valid` vote on this request; the schedule is derived from
+                // them, so a forged one could otherwise buy a turn.
+                if let Err(e) = certificate.verify_signatures(
+                    &self.settings.bridge.program_id,
+                    self.cluster_tag(),
+                    crate::bridge::solana::vote_signature_is_valid,
+                ) {
+                    log::warn!("dropping transact approval {request_id}: {e}");
+                    return Ok(());
+                }
+                // A node that has not ticked past the epoch boundary yet reads
+                // the set now, so it counts the approval as its peers do.
+                if !coordinator.has_epoch(certificate.epoch).await {
+                    if let Err(e) = snapshot_stake_epoch(bridge, coordinator).await {
+                        log::debug!("could not snapshot the stake epoch for {request_id}: {e}");
                     }
                 }
+                self.accept_certificate(&request, &certificate).await;
+
+                let hashes = match bridge.lock().await.finalized_slot_hashes().await {
+                    Ok(hashes) => hashes,
+                    Err(e) => {
+                        log::warn!("cannot check anchor of transact approval {request_id}: {e}");
+                        return Ok(());
+                    }
+                };
+                let Some(slot_hash) = settlement_schedule::anchor_hash(&hashes, anchor_slot) else {
+                    log::warn!(
+                        "dropping transact approval {request_id}: anchor slot {anchor_slot} not recent and finalized here"
+                    );
+                    return Ok(());
+                };
+                let approved = ApprovedTransact {
+                    request,
+                    approvals: certificate.votes,
+                    epoch: certificate.epoch,
+                };
+                self.schedule_settlement(approved, anchor_slot, slot_hash)
+                    .await;
             }
             Message::ValidatorRegistration {
                 validator_id,
@@ -931,6 +1094,29 @@ impl crate::network::protocol::NetworkEventHandler for Node {
         )
         .await)
     }
+
+    /// Serve the pool snapshot this node keeps (see
+    /// `Node::refresh_served_snapshot`). A chunk is served from whichever
+    /// kept snapshot the request names; a node without one declines.
+    async fn handle_snapshot_request(
+        &self,
+        _source: NodeId,
+        request: SnapshotRequest,
+    ) -> Result<SnapshotResponse> {
+        let served = self.served_snapshots.lock().await;
+        Ok(match request {
+            SnapshotRequest::Manifest => {
+                SnapshotResponse::Manifest(served.last().map(|s| s.manifest.clone()))
+            }
+            SnapshotRequest::Chunk { id, index } => SnapshotResponse::Chunk(
+                served
+                    .iter()
+                    .find(|s| s.id() == id)
+                    .and_then(|s| s.chunk(index))
+                    .map(<[u8]>::to_vec),
+            ),
+        })
+    }
 }
 
 /// Produce a co-sign response for `request` (#260): sign the rebuilt settlement
@@ -1001,12 +1187,12 @@ async fn cosign_settlement(
             // observable over gossip, so a registered peer could copy a real
             // spend's params under its own authority and drain the shared
             // per-nullifier budget until the legitimate leader's request is
-            // declined. Only the settlement whose authority is the on-chain
-            // bridge authority can ever land (its `has_one`), so keying the cap
-            // on (nullifier, authority) isolates each authority's budget: a
-            // forged-authority request can no longer starve the real leader,
-            // while the cap still bounds the usable signatures one spend yields
-            // (they all share the one on-chain authority).
+            // declined. Keying the cap on (nullifier, authority) isolates each
+            // authority's budget: a copied request can no longer starve the
+            // real leader, or a backup taking over from it, while the cap
+            // still bounds the signatures one authority gets per spend. Only
+            // one settlement of a spend can land (its nullifier PDAs), so the
+            // signatures other authorities collect settle nothing extra.
             let auth = hex::encode(payload.authority);
             let cap_nullifiers = [
                 format!("{}:{auth}", hex::encode(nullifiers[0])),
@@ -1168,9 +1354,55 @@ fn reconcile_deactivations(
     deactivate
 }
 
-/// Drain the transact coordinator's approval channel and settle each approved
-/// unified transact on-chain (#350). The approval channel is local to the
-/// coordinator that reached quorum, so exactly one node settles.
+/// Read the current slot, note it as `transact`'s view of the chain, and,
+/// unless `transact` already holds the stake epoch it falls in, snapshot the
+/// on-chain validator set as it stood when that epoch began: every
+/// validator's stake from its `ValidatorAccount` and the registry's
+/// `total_active_stake`, each resolved to the boundary through the record the
+/// program keeps of its first change in the epoch. Any node reading any time
+/// in the epoch gets the same set. Returns the slot read.
+async fn snapshot_stake_epoch(
+    bridge: &Arc<Mutex<Bridge>>,
+    transact: &TransactVerificationCoordinator,
+) -> std::result::Result<u64, crate::bridge::BridgeError> {
+    let guard = bridge.lock().await;
+    let slot = guard.current_slot().await?;
+    transact.observe_slot(slot).await;
+    let epoch = transact.epoch_of(slot).await;
+    if transact.has_epoch(epoch).await {
+        return Ok(slot);
+    }
+    let list = guard.list_validator_stakes().await?;
+    let registry = guard.registry_total_active_stake().await?;
+    drop(guard);
+    // A record changed in a later epoch no longer holds this boundary: the
+    // chain crossed into the next epoch mid-read. Skip; the next read names
+    // the new epoch.
+    let moved_on = || {
+        crate::bridge::BridgeError::SolanaRpc(format!(
+            "the chain left stake epoch {epoch} while its set was being read"
+        ))
+    };
+    let registry_total = registry.at_epoch_start(epoch).ok_or_else(moved_on)?;
+    let mut stakes = HashMap::new();
+    for (wallet, record) in list {
+        match record.at_epoch_start(epoch).ok_or_else(moved_on)? {
+            0 => {}
+            stake => {
+                stakes.insert(wallet.to_string(), stake);
+            }
+        }
+    }
+    transact
+        .record_epoch_stakes(epoch, stakes, registry_total)
+        .await;
+    Ok(slot)
+}
+
+/// Drain the settlement scheduler's channel and settle each approved unified
+/// transact on-chain (#350). An approval arrives here only at this node's turn
+/// in its leader schedule, so the primary settles and a backup only once the
+/// ranks ahead of it have not.
 /// A per-message failure — including a replay whose nullifier is already spent —
 /// is logged and skipped so it cannot stall later approvals.
 async fn settle_approved_transacts<F, Fut>(
@@ -1243,12 +1475,14 @@ impl Node {
         &self.settings.bridge.cluster_tag
     }
 
-    /// Build a signed transact vote for `request_id`, or `None` if this node has
-    /// no co-sign keypair (it could not co-sign settlement anyway). The vote is
-    /// ed25519-signed by the co-sign key over the canonical preimage.
+    /// Build a signed transact vote for `request_id`, counted in stake `epoch`,
+    /// or `None` if this node has no co-sign keypair (it could not co-sign
+    /// settlement anyway). The vote is ed25519-signed by the co-sign key over
+    /// the canonical preimage.
     fn signed_vote(
         &self,
         request_id: String,
+        epoch: u64,
         vote: crate::consensus::vote_tally::VerificationVote,
     ) -> Option<crate::consensus::transact::TransactVerificationResult> {
         let kp = self.cosign_keypair.as_ref()?;
@@ -1257,6 +1491,7 @@ impl Node {
             &self.settings.bridge.program_id,
             self.cluster_tag(),
             &request_id,
+            epoch,
             &self.node_info.id,
             &vote,
             &wallet,
@@ -1277,10 +1512,12 @@ impl Node {
 
     /// Verify an incoming vote's ed25519 signature over the canonical preimage,
     /// using the exact in-repo verify primitive. Rejects a vote whose
-    /// wallet/validity/request-id/voter-NodeId do not match its signature.
+    /// wallet/validity/request-id/voter-NodeId do not match its signature, or
+    /// that was not cast for stake `epoch`.
     fn verify_vote_signature(
         &self,
         result: &crate::consensus::transact::TransactVerificationResult,
+        epoch: u64,
     ) -> bool {
         use std::str::FromStr;
         let pk = match Pubkey::from_str(&result.wallet_pubkey) {
@@ -1291,6 +1528,7 @@ impl Node {
             &self.settings.bridge.program_id,
             self.cluster_tag(),
             &result.request_id,
+            epoch,
             &result.validator,
             &result.vote,
             &result.wallet_pubkey,
@@ -1300,6 +1538,13 @@ impl Node {
 
     /// Create a new node
     pub fn new(settings: Settings) -> Result<Self> {
+        // Settlement proofs are resolved against the configured suites, so
+        // the registry is in place before anything can verify one.
+        let suites = crate::privacy::SuiteRegistry::from_config(&settings.privacy.suites)
+            .map_err(|e| anyhow!("invalid [[privacy.suites]] configuration: {e}"))?;
+        crate::privacy::suite_registry::install(suites)
+            .map_err(|e| anyhow!("installing the proof suite registry: {e}"))?;
+
         let network = NetworkManager::new(&settings)?;
 
         let node_type = match settings.node.node_type.as_str() {
@@ -1434,19 +1679,46 @@ impl Node {
         // with on-chain deposits (started in run()). Compute-only
         // providers and nodes with the bridge disabled skip it.
         //
-        // The pool is in-memory (ShieldedPool::new) rather than
-        // storage-backed on purpose: the deposit listener does not yet
-        // persist its scan cursor, so a persistent tree would
-        // double-index on restart. Rebuilding from chain on each run
-        // keeps the cursor and the tree consistent; persistent indexing
-        // is a follow-up once the cursor is durable.
+        // The pool lives in the privacy store next to the delivered notes
+        // (#196), and the listener's scan cursor beside it, so a restart
+        // resumes indexing where it stopped; re-processing the few
+        // transactions between the last cursor write and the stop is
+        // idempotent at the pool. A store that will not open leaves the
+        // pool in memory, rebuilt from the chain from wherever the cursor
+        // points.
         let runs_bridge = settings.bridge.enabled
             && matches!(node_type, NodeType::ResourceProvider | NodeType::Bridge);
-        let shielded_pool = if runs_bridge {
-            Some(Arc::new(ShieldedPool::new()))
+        let privacy_storage = if runs_bridge {
+            let storage_path = format!("{}/privacy", settings.storage.data_dir);
+            match PrivacyStorage::open(&storage_path) {
+                Ok(storage) => {
+                    info!("Privacy storage initialized at {}", storage_path);
+                    Some(Arc::new(storage))
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Failed to open privacy storage: {} — pool and delivered notes kept in memory",
+                        e
+                    );
+                    None
+                }
+            }
         } else {
             None
         };
+        let shielded_pool = match (&privacy_storage, runs_bridge) {
+            (Some(storage), _) => {
+                match futures::executor::block_on(ShieldedPool::with_storage(Arc::clone(storage))) {
+                    Ok(pool) => Some(Arc::new(pool)),
+                    Err(e) => {
+                        log::warn!("Failed to load the shielded pool: {} — kept in memory", e);
+                        Some(Arc::new(ShieldedPool::new()))
+                    }
+                }
+            }
+            (None, true) => Some(Arc::new(ShieldedPool::new())),
+            (None, false) => None,
+        };
         let bridge = if runs_bridge {
             // Persist the deposit listener's scan cursor under the node's data
             // directory so a restart resumes where it left off instead of
@@ -1454,7 +1726,11 @@ impl Node {
             let mut bridge_cfg = settings.bridge.clone();
             bridge_cfg.cursor_path =
                 Some(format!("{}/bridge_cursor", settings.storage.data_dir).into());
-            Some(Arc::new(Mutex::new(Bridge::new(bridge_cfg))))
+            let mut bridge = Bridge::new(bridge_cfg);
+            if let Some(storage) = &privacy_storage {
+                bridge = bridge.with_delivered_store(Arc::clone(storage));
+            }
+            Some(Arc::new(Mutex::new(bridge)))
         } else {
             None
         };
@@ -1487,6 +1763,16 @@ impl Node {
             coord = coord.with_reputation_persistence(
                 format!("{}/reputation.json", settings.storage.data_dir).into(),
             );
+            // Equivocation evidence outlives a restart, so an offence is
+            // neither forgotten nor slashed twice.
+            let slashing_path = format!("{}/slashing", settings.storage.data_dir);
+            match SlashingStorage::open(&slashing_path) {
+                Ok(storage) => coord = coord.with_slashing_storage(Arc::new(storage)),
+                Err(e) => log::warn!(
+                    "Failed to open slashing storage: {} — evidence kept in memory",
+                    e
+                ),
+            }
             // Optional config override of the BFT consensus defaults (7/10/rep200).
             // Unset on mainnet → the secure defaults stand; devnet lowers them in
             // validator.toml to settle with a small live cohort (2/2), otherwise
@@ -1503,6 +1789,32 @@ impl Node {
             (None, None)
         };
 
+        let slashing_authority = settings
+            .bridge
+            .slashing_authority_keypair_path
+            .as_deref()
+            .filter(|_| runs_bridge)
+            .and_then(|p| match crate::bridge::solana::load_keypair_from_file(p) {
+                Ok(kp) => Some(Arc::new(kp)),
+                Err(e) => {
+                    log::warn!("slashing authority keypair unavailable ({e}); evidence will not be submitted");
+                    None
+                }
+            });
+
+        let certificate_storage = if runs_bridge {
+            let path = format!("{}/certificates", settings.storage.data_dir);
+            match CertificateStorage::open(&path) {
+                Ok(storage) => Some(Arc::new(storage)),
+                Err(e) => {
+                    log::warn!("Failed to open certificate storage: {e} — certificates not kept");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let node = Node {
             settings,
             network: network_arc,
@@ -1511,7 +1823,7 @@ impl Node {
             resource_monitor: Arc::new(resource_monitor),
             coordinator,
             validator,
-            privacy_storage: None,
+            privacy_storage,
             shielded_pool,
             bridge,
             compute_executor,
@@ -1525,13 +1837,23 @@ impl Node {
             kad_refresh: Arc::new(Mutex::new(None)),
             transact_coordinator,
             transact_approval_rx: Arc::new(Mutex::new(transact_approval_rx)),
+            settlement_scheduler: Arc::new(settlement_schedule::SettlementScheduler::new(
+                settlement_schedule::LEADER_TAKEOVER_TIMEOUT,
+            )),
             transact_submitter_task: Arc::new(Mutex::new(None)),
             transact_proof_verifier_override: None,
+            transact_verify_queue: Arc::new(crate::consensus::TransactVerificationQueue::default()),
             transact_ingress: Arc::new(Mutex::new(None)),
+            merkle_path_server: Arc::new(Mutex::new(None)),
+            association_server: Arc::new(Mutex::new(None)),
             delivered_notes: Arc::new(Mutex::new(Vec::new())),
             cosign_keypair,
+            slashing_authority,
             verified_transacts: Arc::new(Mutex::new(HashMap::new())),
             cosign_counts: Arc::new(Mutex::new(HashMap::new())),
+            certificate_storage,
+            served_snapshots: Arc::new(Mutex::new(Vec::new())),
+            snapshot_task: Arc::new(Mutex::new(None)),
         };
 
         Ok(node)
@@ -1560,29 +1882,28 @@ impl Node {
         self
     }
 
-    /// Apply an on-chain validator-stake snapshot to the consensus set.
+    /// Record the on-chain validator-stake snapshot of stake epoch `epoch`.
     ///
-    /// This is the entry point the node's own reconciler uses on every
-    /// successful poll. It is public so a harness running without a chain can
-    /// supply the snapshot the reconciler would have produced: since #698 the
-    /// stake gate withholds approval until one lands, which is what a node with
-    /// a dead RPC should do in production, and leaves an offline test unable to
-    /// reach a quorum whose stake weighting it is not otherwise exercising.
+    /// The node's own reconciler takes one at each epoch boundary. This is
+    /// public so a harness running without a chain can supply the snapshot
+    /// the reconciler would have produced: since #698 the stake gate withholds
+    /// approval until one lands, which is what a node with a dead RPC should
+    /// do in production, and leaves an offline test unable to reach a quorum
+    /// whose stake weighting it is not otherwise exercising. Requests started
+    /// with no slot and no snapshot held are pinned to epoch 0.
     ///
-    /// Fail-closed by wallet, like the reconciler: a validator absent from
-    /// `stakes` is zeroed rather than left at its previous value.
+    /// Like the reconciler, the first snapshot of an epoch is kept.
     ///
     /// No-op on a node with no transact coordinator.
-    pub async fn apply_onchain_stakes(&self, stakes: std::collections::HashMap<String, u64>) {
+    pub async fn apply_epoch_stakes(&self, epoch: u64, stakes: HashMap<String, u64>) {
         if let Some(coordinator) = &self.transact_coordinator {
-            // Harness entry point without a real ValidatorRegistry account: use the
-            // sum of the supplied stakes as the denominator, matching the previous
-            // head/stake behaviour. The production reconciler instead reads
-            // `registry_total_active_stake` and calls `sync_onchain_stakes`
-            // directly, so a lagging PDA scan can never lower the threshold.
+            // Harness entry point without a real ValidatorRegistry account: use
+            // the sum of the supplied stakes as the denominator. The production
+            // reconciler reads `registry_total_active_stake` instead, so a
+            // lagging PDA scan can never lower the threshold.
             let registry_total: u64 = stakes.values().copied().sum();
             coordinator
-                .sync_onchain_stakes(stakes, registry_total)
+                .record_epoch_stakes(epoch, stakes, registry_total)
                 .await;
         }
     }
@@ -1722,29 +2043,14 @@ impl Node {
         // dropped, so the set tracks real connectivity independent of gossip
         // timing. A wallet learned via Discovery is preserved — registration is
         // wallet-preserving — and is what the #260 co-sign step uses.
-        // Seed the on-chain validator allowlist ONCE, synchronously, before the
+        // Snapshot the current stake epoch ONCE, synchronously, before the
         // reconcilers spawn — so the registration gate is armed from t=0 and no
         // peer slips in during an empty-allowlist fail-open window.
         if let (Some(bridge), Some(transact)) =
             (self.bridge.clone(), self.transact_coordinator.clone())
         {
-            let guard = bridge.lock().await;
-            let seed = async {
-                let list = guard.list_validator_stakes().await?;
-                let total = guard.registry_total_active_stake().await?;
-                Ok::<_, crate::bridge::BridgeError>((list, total))
-            }
-            .await;
-            drop(guard);
-            match seed {
-                Ok((list, registry_total)) => {
-                    let map: std::collections::HashMap<String, u64> = list
-                        .into_iter()
-                        .map(|(wallet, stake)| (wallet.to_string(), stake))
-                        .collect();
-                    transact.sync_onchain_stakes(map, registry_total).await;
-                    info!("Seeded on-chain validator allowlist + registry stake total for the transact gate");
-                }
+            match snapshot_stake_epoch(&bridge, &transact).await {
+                Ok(_) => info!("Seeded on-chain validator allowlist + registry stake total for the transact gate"),
                 Err(e) => log::warn!(
                     "could not seed validator allowlist/registry total at startup \
                      (gate fails open until the first reconcile lands): {e}"
@@ -1792,14 +2098,19 @@ impl Node {
             info!("Consensus validator-set reconciler spawned (interval 30s, deactivate after 3 missed ticks)");
         }
 
-        // On-chain stake reconciler (#333/#627/#611). The connectivity
-        // reconciler above registers peers with ZERO stake; this task reads
-        // every validator's real stake from its on-chain `ValidatorAccount`
-        // (one `getProgramAccounts` call) and applies it to the consensus set,
-        // keyed by co-sign wallet. The stake-weighted quorum then reflects real
-        // at-risk capital — an unregistered/unstaked peer carries no weight and
-        // cannot Sybil its way to a supermajority. Fail-closed: any validator
-        // whose wallet has no active on-chain account stays at 0.
+        // On-chain stake epochs (#333/#627/#611). The connectivity reconciler
+        // above registers peers with ZERO stake; this task watches the slot
+        // and, once per stake epoch, reads every validator's real stake at
+        // the boundary from its on-chain `ValidatorAccount` (one
+        // `getProgramAccounts` call) into that epoch's snapshot, keyed by
+        // co-sign wallet. The stake-weighted quorum then reflects real
+        // at-risk capital — an unregistered/unstaked peer carries no weight
+        // and cannot Sybil its way to a supermajority — and every request is
+        // counted against the snapshot of its own epoch, which any node
+        // reads the same whenever in the epoch it polls. Each tick's slot
+        // read is also the view request slots are checked against.
+        // Fail-closed: any validator whose wallet had no active on-chain
+        // stake at the boundary is absent from the snapshot.
         if let (Some(bridge), Some(transact)) =
             (self.bridge.clone(), self.transact_coordinator.clone())
         {
@@ -1807,40 +2118,48 @@ impl Node {
                 let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
                 loop {
                     ticker.tick().await;
-                    // Read the stake map AND the registry total together; the
-                    // gate mirrors the on-chain denominator, so both must move as
-                    // one. On either failure, skip the tick (keep the previous
-                    // snapshot) rather than zeroing one side.
-                    let snapshot = {
-                        let guard = bridge.lock().await;
-                        let r = async {
-                            let list = guard.list_validator_stakes().await?;
-                            let total = guard.registry_total_active_stake().await?;
-                            Ok::<_, crate::bridge::BridgeError>((list, total))
-                        }
-                        .await;
-                        drop(guard);
-                        r
-                    };
-                    match snapshot {
-                        Ok((list, registry_total)) => {
-                            let map: std::collections::HashMap<String, u64> = list
-                                .into_iter()
-                                .map(|(wallet, stake)| (wallet.to_string(), stake))
-                                .collect();
-                            transact.sync_onchain_stakes(map, registry_total).await;
-                        }
-                        Err(e) => {
-                            // Not debug: until a snapshot lands, the off-chain
-                            // stake gate withholds approval (#698), so a
-                            // persistent failure here reads as "this node has
-                            // stopped approving" with no other signal.
-                            log::warn!("on-chain stake reconcile skipped this tick: {e}")
-                        }
+                    // On failure, skip the tick and retry on the next; a
+                    // later read of the same epoch yields the same set.
+                    if let Err(e) = snapshot_stake_epoch(&bridge, &transact).await {
+                        // Not debug: until a snapshot of the current epoch
+                        // lands, the off-chain stake gate withholds approval of
+                        // its requests (#698), so a persistent failure here
+                        // reads as "this node has stopped approving" with no
+                        // other signal.
+                        log::warn!("stake epoch snapshot skipped this tick: {e}")
                     }
                 }
             });
-            info!("On-chain validator-stake reconciler spawned (interval 60s)");
+            info!("On-chain stake-epoch reconciler spawned (interval 60s)");
+        }
+
+        // Equivocation slasher. Only the registry authority can call
+        // `slash_validator`, so this runs on the node an operator gave that key;
+        // elsewhere evidence waits in the store for `validator slashing export`.
+        if let (Some(bridge), Some(transact), Some(authority)) = (
+            self.bridge.clone(),
+            self.transact_coordinator.clone(),
+            self.slashing_authority.clone(),
+        ) {
+            let program_id = self.settings.bridge.program_id.clone();
+            let cluster_tag = self.cluster_tag().to_string();
+            let slash_percentage = self.settings.bridge.slash_percentage;
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(slashing::SLASHING_INTERVAL);
+                loop {
+                    ticker.tick().await;
+                    slashing::slash_pending(
+                        transact.slashing_tracker(),
+                        &bridge,
+                        &authority,
+                        &program_id,
+                        &cluster_tag,
+                        slash_percentage,
+                    )
+                    .await;
+                }
+            });
+            info!("Equivocation slasher spawned ({slash_percentage}% per offence, interval 60s)");
         }
 
         // Co-validator link keep-alive. The 2-of-2 quorum settles only while the
@@ -2074,16 +2393,30 @@ impl Node {
             info!("Result reporting started for ResourceProvider node");
         }
 
-        // Start the Solana bridge deposit listener (#163). On a
-        // bridge-enabled validator/bridge node this spawns the
-        // EventListener, which polls Solana and indexes deposit
-        // commitments into the shielded pool so a withdrawing client
-        // can later obtain a Merkle path for the note it spends.
+        // Start the Solana bridge listener (#163). On a bridge-enabled
+        // validator/bridge node this spawns the EventListener, which polls
+        // Solana and indexes deposit and settlement commitments into the
+        // shielded pool in on-chain leaf order, so any validator can hand a
+        // spending client a Merkle path for the note it spends.
         if let (Some(bridge), Some(pool)) = (&self.bridge, &self.shielded_pool) {
             let mut bridge = bridge.lock().await;
             bridge.init(pool.clone()).await?;
             bridge.start().await?;
             info!("Solana bridge deposit listener started");
+
+            // Keep a snapshot of the pool ready for joining peers to fetch
+            // over the snapshot protocol.
+            let node = self.clone();
+            let handle = tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(SNAPSHOT_REFRESH);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = node.refresh_served_snapshot().await {
+                        log::warn!("failed to refresh the served pool snapshot: {}", e);
+                    }
+                }
+            });
+            *self.snapshot_task.lock().await = Some(handle);
         }
 
         // Serve the transact-verification ingress over HTTP (#350) so a client
@@ -2123,24 +2456,137 @@ impl Node {
             }
         }
 
+        // Serve Merkle paths (#163) so a wallet can prove membership of the
+        // note it spends. Only a node that indexes the pool has paths to serve.
+        // Same address handling as the ingress: empty disables, unparseable is
+        // logged and skipped.
+        if self.shielded_pool.is_some() {
+            let addr_str = self.settings.bridge.merkle_path_query_address.trim();
+            if !addr_str.is_empty() {
+                match addr_str.parse::<std::net::SocketAddr>() {
+                    Ok(addr) => {
+                        let source: Arc<dyn merkle_path::MerklePathSource> = Arc::new(self.clone());
+                        let handle = tokio::spawn(async move {
+                            if let Err(e) = merkle_path::serve(source, addr).await {
+                                log::error!(
+                                    target: "paraloom::node::merkle_path",
+                                    "Merkle path server exited: {}",
+                                    e
+                                );
+                            }
+                        });
+                        *self.merkle_path_server.lock().await = Some(handle);
+                        info!("Merkle path server started on {}", addr_str);
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "invalid bridge.merkle_path_query_address '{}': {} — path server not started",
+                            addr_str,
+                            e
+                        );
+                    }
+                }
+            }
+        }
+
+        // Serve deposits and published association sets. A set that fails to
+        // load or check keeps the server down rather than serving less than
+        // the operator configured; the node itself runs on.
+        let addr_str = self.settings.privacy.association_address.trim();
+        if !addr_str.is_empty() {
+            match addr_str.parse::<std::net::SocketAddr>() {
+                Ok(addr) => match self.association_service().await {
+                    Ok(service) => {
+                        let handle = tokio::spawn(async move {
+                            if let Err(e) = association::serve(Arc::new(service), addr).await {
+                                log::error!(
+                                    target: "paraloom::node::association",
+                                    "Association server exited: {}",
+                                    e
+                                );
+                            }
+                        });
+                        *self.association_server.lock().await = Some(handle);
+                        info!("Association server started on {}", addr_str);
+                    }
+                    Err(e) => {
+                        log::error!(
+                            target: "paraloom::node::association",
+                            "association server not started: {}",
+                            e
+                        );
+                    }
+                },
+                Err(e) => {
+                    log::warn!(
+                        "invalid privacy.association_address '{}': {} — association server not started",
+                        addr_str,
+                        e
+                    );
+                }
+            }
+        }
+
         // Settle consensus-approved unified transacts (#350). The `transact`
         // instruction settles exclusively through the #260 co-signing quorum
         // (no single-key fallback), so a node without a settlement keypair logs
-        // each approval's failure rather than settling it single-key.
-        if let (Some(_bridge), Some(coordinator), Some(rx)) = (
+        // each approval's failure rather than settling it single-key. This
+        // node's own approvals are announced and scheduled first; the
+        // submitter only sees an approval once it is this node's turn to lead.
+        if let (Some(_bridge), Some(coordinator), Some(mut approval_rx), Some(rx)) = (
             self.bridge.clone(),
             self.transact_coordinator.clone(),
             self.transact_approval_rx.lock().await.take(),
+            self.settlement_scheduler.take_receiver().await,
         ) {
+            let announcer = self.clone();
             let node = self.clone();
-            let handle = tokio::spawn(settle_approved_transacts(
-                rx,
-                move |approved| {
-                    let node = node.clone();
-                    async move { node.settle_transact_via_cosign(approved).await }
-                },
-                Some(coordinator),
-            ));
+            let handle = tokio::spawn(async move {
+                // Approvals that could not be anchored yet wait here and are
+                // retried until a finalized slot is readable, unless a peer's
+                // announcement of the same request got it scheduled first.
+                let announce = async move {
+                    let mut unanchored = Vec::new();
+                    let mut retry =
+                        tokio::time::interval(settlement_schedule::ANCHOR_RETRY_INTERVAL);
+                    retry.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                    loop {
+                        tokio::select! {
+                            approved = approval_rx.recv() => {
+                                let Some(approved) = approved else { break };
+                                unanchored.extend(
+                                    announcer.announce_transact_approval(approved).await,
+                                );
+                            }
+                            _ = retry.tick(), if !unanchored.is_empty() => {
+                                for (approved, certificate) in std::mem::take(&mut unanchored) {
+                                    if announcer
+                                        .settlement_scheduler
+                                        .is_scheduled(&approved.request.request_id)
+                                        .await
+                                    {
+                                        continue;
+                                    }
+                                    unanchored.extend(
+                                        announcer
+                                            .anchor_transact_approval(approved, certificate)
+                                            .await,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                };
+                let settle = settle_approved_transacts(
+                    rx,
+                    move |approved| {
+                        let node = node.clone();
+                        async move { node.settle_transact_via_cosign(approved).await }
+                    },
+                    Some(coordinator),
+                );
+                tokio::join!(announce, settle);
+            });
             *self.transact_submitter_task.lock().await = Some(handle);
             info!("transact submitter task started (co-signing)");
         }
@@ -2193,6 +2639,15 @@ impl Node {
         if let Some(handle) = self.transact_ingress.lock().await.take() {
             handle.abort();
         }
+        if let Some(handle) = self.merkle_path_server.lock().await.take() {
+            handle.abort();
+        }
+        if let Some(handle) = self.association_server.lock().await.take() {
+            handle.abort();
+        }
+        if let Some(handle) = self.snapshot_task.lock().await.take() {
+            handle.abort();
+        }
         // Stop the bridge deposit listener (#163) so its poll loop
         // winds down on the next tick. A failure here must not block
         // the rest of shutdown, so it is logged rather than propagated.
@@ -2206,6 +2661,73 @@ impl Node {
         Ok(())
     }
 
+    /// Capture the pool as a snapshot for joining peers to fetch.
+    ///
+    /// The listener's cursor is read before the pool, so the snapshot holds
+    /// at least everything up to it. A listener halted on tree divergence
+    /// has a pool the chain disagrees with, and the node stops serving.
+    /// What the association server answers from: this node's recorded
+    /// deposits, the configured sets (each checked against its root) and the
+    /// association verifying key, if one is installed.
+    async fn association_service(&self) -> Result<association::AssociationService> {
+        let mut sets = Vec::new();
+        for path in &self.settings.privacy.association_sets {
+            sets.push(crate::privacy::AssociationSet::load(path).await?);
+        }
+        let key_path = crate::privacy::association::resolve_association_key_path();
+        let verifier = match crate::privacy::AssociationVerifier::load(&key_path) {
+            Ok(verifier) => Some(verifier),
+            Err(e) => {
+                log::warn!(
+                    target: "paraloom::node::association",
+                    "no association verifying key at {} ({}); /association/verify disabled",
+                    key_path.display(),
+                    e
+                );
+                None
+            }
+        };
+        Ok(association::AssociationService {
+            deposits: self
+                .privacy_storage
+                .clone()
+                .map(|storage| storage as Arc<dyn association::DepositSource>),
+            sets,
+            verifier,
+        })
+    }
+
+    async fn refresh_served_snapshot(&self) -> Result<()> {
+        let (Some(bridge), Some(pool)) = (&self.bridge, &self.shielded_pool) else {
+            return Ok(());
+        };
+        let (cursor, diverged) = {
+            let bridge = bridge.lock().await;
+            let diverged = bridge.stats().await.tree_divergence.is_some();
+            (bridge.snapshot_cursor().await, diverged)
+        };
+        if diverged {
+            self.served_snapshots.lock().await.clear();
+            return Ok(());
+        }
+        let Some(cursor) = cursor else {
+            return Ok(());
+        };
+        let snapshot = pool.snapshot(cursor).await?;
+        let encoded = tokio::task::spawn_blocking(move || snapshot.encode()).await??;
+        info!(
+            "pool snapshot refreshed: {} leaves, {} chunks",
+            encoded.manifest.leaf_count,
+            encoded.manifest.chunk_hashes.len()
+        );
+        let mut served = self.served_snapshots.lock().await;
+        served.push(Arc::new(encoded));
+        if served.len() > 2 {
+            served.remove(0);
+        }
+        Ok(())
+    }
+
     /// Submit a task (only for coordinator nodes)
     pub async fn submit_task(&self, task_type: crate::task::TaskType) -> Result<String> {
         if let Some(coordinator) = &self.coordinator {
@@ -2227,11 +2749,33 @@ impl Node {
     /// submitter task to settle on-chain.
     pub async fn initiate_transact_verification(
         &self,
-        request: crate::consensus::TransactVerificationRequest,
+        mut request: crate::consensus::TransactVerificationRequest,
     ) -> Result<String> {
         let coordinator = self.transact_coordinator.as_ref().ok_or_else(|| {
             anyhow!("node has no transact coordinator (bridge disabled or non-validator)")
         })?;
+        // Stamp the slot the request is admitted at; it selects the stake
+        // epoch every validator tallies it against. The snapshot of that
+        // epoch is taken here if the reconciler has not reached it yet, so
+        // this node never starts a round it cannot count.
+        if let Some(bridge) = &self.bridge {
+            match snapshot_stake_epoch(bridge, coordinator).await {
+                Ok(slot) => {
+                    request.slot.get_or_insert(slot);
+                    // Peers refuse a slot far from their own, so a stale or
+                    // invented one could never gather votes.
+                    if !request.slot_is_near(slot) {
+                        return Err(anyhow!(
+                            "transact slot {:?} is more than {} slots from the current slot {}",
+                            request.slot,
+                            crate::consensus::transact::MAX_REQUEST_SLOT_SKEW,
+                            slot
+                        ));
+                    }
+                }
+                Err(e) => log::debug!("could not read the current slot for a transact: {e}"),
+            }
+        }
         // Register self into the validator set BEFORE start_verification, which
         // rejects an empty set with "No validators available". The initiator
         // never receives its own gossip broadcast, so on a single-node cohort (a
@@ -2274,6 +2818,7 @@ impl Node {
                         self.record_delivered_notes(
                             &request.output_commitments,
                             &request.ciphertexts,
+                            request.mint.as_ref(),
                         )
                         .await;
                     }
@@ -2297,13 +2842,18 @@ impl Node {
             // Sign the self-vote with the co-sign key so it passes submit_result's
             // structural backstop and counts under this node's own wallet. Without
             // a co-sign keypair this node cannot settle anyway, so skip.
-            match self.signed_vote(request_id.clone(), vote) {
-                Some(result) => {
-                    if let Err(e) = coordinator.submit_result(result).await {
-                        log::debug!("self-vote submit_result dropped for {request_id}: {e}");
+            // Signed for the round's epoch, not one derived afresh: a
+            // duplicate start keeps the round already in flight.
+            match coordinator.round_epoch(&request_id).await {
+                Some(epoch) => match self.signed_vote(request_id.clone(), epoch, vote) {
+                    Some(result) => {
+                        if let Err(e) = coordinator.submit_result(result).await {
+                            log::debug!("self-vote submit_result dropped for {request_id}: {e}");
+                        }
                     }
-                }
-                None => log::warn!("no co-sign keypair; skipping self-vote for {request_id}"),
+                    None => log::warn!("no co-sign keypair; skipping self-vote for {request_id}"),
+                },
+                None => log::debug!("round {request_id} already gone; skipping self-vote"),
             }
             locally_valid
         };
@@ -2333,35 +2883,111 @@ impl Node {
         &self,
         output_commitments: &[[u8; 32]; 2],
         ciphertexts: &[String; 2],
+        mint: Option<&[u8; 32]>,
     ) {
-        // Bound the in-memory scan buffer so a high volume of transfers cannot
+        // Prune the persisted feed every this many appends. Pruning walks the
+        // store from its oldest note, so doing it on every append would cost a
+        // full count each time.
+        const PRUNE_EVERY: u64 = 256;
+        // Bound the in-memory fallback so a high volume of transfers cannot
         // grow it without limit (this records only proof-verified transfers, so
         // it is not cheaply floodable, but the bound is defence in depth).
         const MAX_DELIVERED_NOTES: usize = 50_000;
 
+        if let Some(storage) = &self.privacy_storage {
+            let recorded_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            for (commitment, ciphertext) in output_commitments.iter().zip(ciphertexts.iter()) {
+                let record = DeliveredRecord {
+                    commitment: *commitment,
+                    ciphertext: ciphertext.clone(),
+                    mint: mint.copied(),
+                    leaf_index: None,
+                    slot: None,
+                    recorded_at,
+                };
+                match storage.append_delivered(&record) {
+                    Ok(Some(seq)) if seq % PRUNE_EVERY == 0 => {
+                        self.prune_delivered_notes(storage, recorded_at)
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!(
+                        "failed to persist delivered note {}: {}",
+                        hex::encode(commitment),
+                        e
+                    ),
+                }
+            }
+            return;
+        }
+
         let mut store = self.delivered_notes.lock().await;
         for (commitment, ciphertext) in output_commitments.iter().zip(ciphertexts.iter()) {
             let note = transact_ingress::DeliveredNote {
                 output_commitment: hex::encode(commitment),
                 ciphertext: ciphertext.clone(),
+                mint: mint.map(hex::encode),
+                leaf_index: None,
+                slot: None,
             };
-            if store.iter().any(|d| {
+            if store.iter().any(|(_, d)| {
                 d.output_commitment == note.output_commitment && d.ciphertext == note.ciphertext
             }) {
                 continue;
             }
-            // Evict the oldest note when at capacity (FIFO).
+            // Evict the oldest note when at capacity (FIFO). The newest keeps
+            // its number, so sequence numbers never repeat.
+            let seq = store.last().map_or(1, |(seq, _)| seq + 1);
             if store.len() >= MAX_DELIVERED_NOTES {
                 store.remove(0);
             }
-            store.push(note);
+            store.push((seq, note));
+        }
+    }
+
+    /// Apply the configured retention to the persisted delivered notes.
+    fn prune_delivered_notes(&self, storage: &PrivacyStorage, now: u64) {
+        let retention = DeliveredRetention {
+            max_notes: self.settings.bridge.delivered_notes_max,
+            max_age_secs: self.settings.bridge.delivered_notes_retention_secs,
+        };
+        match storage.prune_delivered(&retention, now) {
+            Ok(0) => {}
+            Ok(removed) => info!("pruned {} delivered notes past retention", removed),
+            Err(e) => log::warn!("failed to prune delivered notes: {}", e),
         }
     }
 
-    /// Snapshot of the encrypted notes this node has seen (#196), for the
-    /// `GET /transact/scan` endpoint.
-    pub async fn delivered_transfer_notes(&self) -> Vec<transact_ingress::DeliveredNote> {
-        self.delivered_notes.lock().await.clone()
+    /// A page of the encrypted notes this node has seen (#196), for the
+    /// `GET /transact/scan` endpoint: up to `limit` recorded after `after`.
+    pub async fn delivered_transfer_notes(
+        &self,
+        after: u64,
+        limit: usize,
+    ) -> Result<transact_ingress::DeliveredPage> {
+        let limit = limit.min(transact_ingress::MAX_SCAN_PAGE);
+        // One past the page, to learn whether there is more.
+        let window = match &self.privacy_storage {
+            Some(storage) => storage
+                .delivered_after(after, limit + 1)?
+                .into_iter()
+                .map(|(seq, record)| (seq, record.into()))
+                .collect(),
+            None => self
+                .delivered_notes
+                .lock()
+                .await
+                .iter()
+                .filter(|(seq, _)| *seq > after)
+                .take(limit + 1)
+                .cloned()
+                .collect(),
+        };
+        Ok(transact_ingress::DeliveredPage::from_window(
+            after, limit, window,
+        ))
     }
 
     /// Number of peers this node is currently connected to (#181). Read-only
@@ -2393,30 +3019,25 @@ impl Node {
             .cosign_keypair
             .as_ref()
             .ok_or_else(|| anyhow!("no settlement keypair configured"))?;
-        let coordinator = self
-            .transact_coordinator
-            .as_ref()
-            .ok_or_else(|| anyhow!("no transact coordinator"))?;
         let request = &approved.request;
 
         let program_id = Pubkey::from_str(&self.settings.bridge.program_id)
             .map_err(|e| anyhow!("invalid program id: {e}"))?;
         let (vault, _) = derive_bridge_vault(&program_id);
 
-        // The validators that approved this transact become the co-signer
-        // quorum, mapped to their advertised settlement wallets; the leader
+        // The validators whose approvals the leader holds become the co-signer
+        // quorum, each asked at the node that signed its vote. A backup holds
+        // the approvals without the leaders ranked ahead of it. The leader
         // signs as itself and is excluded from the peers it requests from.
-        let self_id = self.node_info.id.clone();
         let mut peers: Vec<(Pubkey, NodeId)> = Vec::new();
-        for voter in coordinator.valid_voters(&request.request_id).await {
-            if voter == self_id {
+        for approval in &approved.approvals {
+            let Ok(pubkey) = approval.wallet_pubkey.parse::<Pubkey>() else {
+                continue;
+            };
+            if pubkey == leader.pubkey() || peers.iter().any(|(w, _)| *w == pubkey) {
                 continue;
             }
-            if let Some(wallet) = coordinator.validator_wallet(&voter).await {
-                if let Ok(pubkey) = wallet.parse::<Pubkey>() {
-                    peers.push((pubkey, voter));
-                }
-            }
+            peers.push((pubkey, approval.validator.clone()));
         }
         let mut quorum_wallets = vec![leader.pubkey()];
         quorum_wallets.extend(peers.iter().map(|(w, _)| *w));
@@ -2431,9 +3052,15 @@ impl Node {
         // ever be settled by *this* program version. A future suite must not
         // silently fall through to this conversion — the `match` makes adding
         // one a compile error here, which is where the decision belongs.
-        let (suite, proof_body) = crate::privacy::split_tagged_proof(&request.proof)
+        //
+        // The tag resolves through the suite registry, as in verification and
+        // at the same request slot. A transact circuit registered under a
+        // rotated key converts the same way; it settles once the program
+        // embeds that key.
+        let (suite, proof_body) = crate::privacy::suite_registry::registry()
+            .resolve(&request.proof, request.slot)
             .map_err(|e| anyhow!("transact proof envelope: {e}"))?;
-        let onchain_proof = match suite {
+        let onchain_proof = match suite.circuit {
             crate::privacy::ProofSuite::Groth16Bn254TransactV3 => {
                 crate::privacy::onchain_verifier::compressed_proof_to_onchain_bytes(proof_body)
                     .map_err(|e| anyhow!("transact proof: {e}"))?
@@ -2482,6 +3109,204 @@ impl Node {
         .map_err(|e| anyhow!("co-signing round failed: {e}"))
     }
 
+    /// The approval for a transact whose verification this node initiated, once
+    /// its quorum is reached.
+    pub async fn transact_approval(&self, request_id: &str) -> Option<ApprovedTransact> {
+        self.transact_coordinator
+            .as_ref()?
+            .approval(request_id)
+            .await
+    }
+
+    /// The quorum certificate on file for `request_id`, if this node saw it
+    /// approved.
+    pub async fn quorum_certificate(&self, request_id: &str) -> Result<Option<QuorumCertificate>> {
+        match &self.certificate_storage {
+            Some(storage) => storage.get(request_id),
+            None => Ok(None),
+        }
+    }
+
+    fn keep_certificate(&self, certificate: &QuorumCertificate) {
+        if let Some(storage) = &self.certificate_storage {
+            if let Err(e) = storage.insert(certificate) {
+                log::warn!(
+                    "could not store quorum certificate for {}: {e}",
+                    certificate.request_id
+                );
+            }
+        }
+    }
+
+    /// Certify an approval this node's coordinator reached, against the stake
+    /// epoch it was counted in, and keep the certificate.
+    async fn issue_certificate(&self, approved: &ApprovedTransact) -> Option<QuorumCertificate> {
+        let coordinator = self.transact_coordinator.as_ref()?;
+        let (stakes, registry_total) = coordinator
+            .eligible_stakes(approved.epoch)
+            .await
+            .unwrap_or_default();
+        let certificate = QuorumCertificate::new(
+            approved.request.request_id.clone(),
+            self.settings.bridge.program_id.clone(),
+            self.cluster_tag().to_string(),
+            approved.epoch,
+            &stakes,
+            registry_total,
+            approved.approvals.clone(),
+        );
+        self.keep_certificate(&certificate);
+        Some(certificate)
+    }
+
+    /// Keep a received certificate whose signers hold a supermajority of the
+    /// stake in this node's snapshot of its epoch, and take its transact as
+    /// verified: the proof is not re-run when the request arrives, and this
+    /// node co-signs it as one it verified itself. A certificate that falls
+    /// short here, or whose epoch this node does not hold, is only used for
+    /// the leader schedule.
+    async fn accept_certificate(
+        &self,
+        request: &TransactVerificationRequest,
+        certificate: &QuorumCertificate,
+    ) {
+        let Some(coordinator) = &self.transact_coordinator else {
+            return;
+        };
+        let Some((stakes, registry_total)) = coordinator.eligible_stakes(certificate.epoch).await
+        else {
+            log::debug!(
+                "not taking certificate for {} in place of verification: no snapshot of epoch {}",
+                request.request_id,
+                certificate.epoch
+            );
+            return;
+        };
+        if let Err(e) = certificate.verify_quorum(&stakes, registry_total) {
+            log::debug!(
+                "not taking certificate for {} in place of verification: {e}",
+                request.request_id
+            );
+            return;
+        }
+        self.keep_certificate(certificate);
+        let already_seen = self
+            .verified_transacts
+            .lock()
+            .await
+            .contains_key(&request.request_id);
+        if !already_seen {
+            // Same bookkeeping as a proof this node verified (#196, #382).
+            self.record_delivered_notes(
+                &request.output_commitments,
+                &request.ciphertexts,
+                request.mint.as_ref(),
+            )
+            .await;
+            cache_verified(
+                &self.verified_transacts,
+                request.request_id.clone(),
+                request.clone(),
+            )
+            .await;
+        }
+    }
+
+    /// Announce an approval this node's coordinator reached, with its quorum
+    /// certificate, and schedule it here. The approval is anchored to a
+    /// finalized slot so every approving validator derives the same leader
+    /// schedule. Without a readable slot it is handed back, to be retried
+    /// with [`anchor_transact_approval`](Self::anchor_transact_approval)
+    /// rather than settled here alone, where no backup would know of it.
+    async fn announce_transact_approval(
+        &self,
+        approved: ApprovedTransact,
+    ) -> Option<(ApprovedTransact, QuorumCertificate)> {
+        let certificate = self.issue_certificate(&approved).await?;
+        self.anchor_transact_approval(approved, certificate).await
+    }
+
+    /// Anchor `approved` to the newest finalized slot deep enough for peers
+    /// to check, gossip it with `certificate` and schedule it here. Hands both
+    /// back when no finalized slot hash can be read yet.
+    async fn anchor_transact_approval(
+        &self,
+        approved: ApprovedTransact,
+        certificate: QuorumCertificate,
+    ) -> Option<(ApprovedTransact, QuorumCertificate)> {
+        let request_id = approved.request.request_id.clone();
+        let bridge = self.bridge.as_ref()?;
+        let hashes = bridge.lock().await.finalized_slot_hashes().await;
+        let anchor = match hashes {
+            Ok(hashes) => settlement_schedule::new_anchor(&hashes),
+            Err(e) => {
+                log::warn!("cannot read finalized slot hashes for {request_id}: {e}");
+                None
+            }
+        };
+        let Some((anchor_slot, slot_hash)) = anchor else {
+            log::warn!("no finalized anchor for {request_id} yet; retrying");
+            return Some((approved, certificate));
+        };
+
+        let message = Message::TransactApproved {
+            request: approved.request.clone(),
+            certificate,
+            anchor_slot,
+        };
+        if let Err(e) = self.network.send_message(NodeId(vec![]), message).await {
+            log::warn!("failed to announce transact approval {request_id}: {e}");
+        }
+        self.schedule_settlement(approved, anchor_slot, slot_hash)
+            .await;
+        None
+    }
+
+    /// Place `approved` at this node's turn in its leader schedule. A node
+    /// with no turn — no co-sign wallet, or one that could not settle without
+    /// the ranks ahead — leaves it to the others.
+    async fn schedule_settlement(
+        &self,
+        approved: ApprovedTransact,
+        anchor_slot: u64,
+        slot_hash: [u8; 32],
+    ) {
+        let (Some(coordinator), Some(wallet), Some(bridge)) = (
+            &self.transact_coordinator,
+            &self.cosign_keypair,
+            self.bridge.clone(),
+        ) else {
+            return;
+        };
+        let request_id = approved.request.request_id.clone();
+        let schedule = coordinator
+            .leader_schedule(&approved, anchor_slot, &slot_hash)
+            .await;
+        let rank = self
+            .settlement_scheduler
+            .schedule(
+                approved,
+                &schedule,
+                &wallet.pubkey().to_string(),
+                move |approved| async move {
+                    bridge
+                        .lock()
+                        .await
+                        .is_nullifier_spent(&approved.request.nullifiers[0])
+                        .await
+                },
+            )
+            .await;
+        match rank {
+            Some(0) => info!("leading settlement of {request_id} (anchor slot {anchor_slot})"),
+            Some(rank) => info!(
+                "backup {rank} of {} for settlement of {request_id} (anchor slot {anchor_slot})",
+                schedule.order().len() - 1
+            ),
+            None => log::debug!("no settlement turn for {request_id}"),
+        }
+    }
+
     /// Settle a quorum-approved unified transact via the #260 co-signing path,
     /// the v3 twin of `settle_transfer_via_cosign`. The on-chain submit error
     /// is preserved as its `BridgeError` so the caller's replay detection still
@@ -2685,22 +3510,10 @@ impl Node {
             return Ok(verifier(request));
         }
 
-        // Asset id: all-zero for native SOL, or `mint_to_asset(mint)` for an SPL
-        // settlement (#779), so validators verify the proof against the exact
-        // asset that settles.
-        let asset_id = request
-            .mint
-            .map(|m| crate::privacy::poseidon_circom::mint_to_asset(&m))
-            .unwrap_or([0u8; 32]);
-        let result = crate::privacy::ProofVerifier::verify_transact_parts(
-            &request.root,
-            &request.recipient,
-            request.ext_amount,
-            &asset_id,
-            &request.nullifiers,
-            &request.output_commitments,
-            &request.proof,
-        );
+        // Queued so that requests arriving together share one pairing check;
+        // the queue derives the asset id from `request.mint` (#779), so the
+        // proof is verified against the exact asset that settles.
+        let result = self.transact_verify_queue.verify(request).await;
         if let crate::privacy::VerificationResult::Invalid { reason } = &result {
             log::warn!(
                 "transact proof rejected for {}: {}",
@@ -2737,13 +3550,21 @@ impl Clone for Node {
             bridge: self.bridge.clone(),
             transact_coordinator: self.transact_coordinator.clone(),
             transact_approval_rx: self.transact_approval_rx.clone(),
+            settlement_scheduler: self.settlement_scheduler.clone(),
             transact_submitter_task: self.transact_submitter_task.clone(),
             transact_proof_verifier_override: self.transact_proof_verifier_override.clone(),
+            transact_verify_queue: self.transact_verify_queue.clone(),
             transact_ingress: self.transact_ingress.clone(),
+            merkle_path_server: self.merkle_path_server.clone(),
+            association_server: self.association_server.clone(),
             delivered_notes: self.delivered_notes.clone(),
             cosign_keypair: self.cosign_keypair.clone(),
+            slashing_authority: self.slashing_authority.clone(),
             verified_transacts: self.verified_transacts.clone(),
             cosign_counts: self.cosign_counts.clone(),
+            certificate_storage: self.certificate_storage.clone(),
+            served_snapshots: self.served_snapshots.clone(),
+            snapshot_task: self.snapshot_task.clone(),
         }
     }
 }
@@ -2901,6 +3722,7 @@ mod tests {
             proof: vec![0u8; 256],
             ciphertexts: ["ab".repeat(88), "cd".repeat(88)],
             timestamp: 0,
+            slot: None,
         }
     }
 
@@ -3382,6 +4204,8 @@ mod tests {
                 [5u8; 32],
                 0,
             ),
+            approvals: Vec::new(),
+            epoch: 0,
         }
     }
 
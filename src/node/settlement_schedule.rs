@@ -0,0 +1,319 @@
+//! Settlement leader schedule and takeover.
+//!
+//! An approved transact is settled by one leader at a time, in the order of
+//! its [`LeaderSchedule`]. The node whose coordinator reached the quorum
+//! anchors the schedule to a finalized Solana slot and gossips the approval
+//! with that slot (`Message::TransactApproved`); while no finalized slot hash
+//! is readable it holds the approval and tries again, rather than settling
+//! it where no backup knows of it. Every approving validator reads the slot's
+//! hash from its own RPC, recomputes the same schedule, and waits for its
+//! turn:
+//!
+//! - the primary (rank 0) settles at once;
+//! - the backup at rank `k` waits `k` × [`LEADER_TAKEOVER_TIMEOUT`] and then
+//!   settles only if the input nullifiers are still unspent on-chain.
+//!
+//! A backup runs its co-sign round without the leaders ranked ahead of it: it
+//! only gets a turn when they have not settled, and a crashed leader cannot
+//! sign. Two leaders racing is harmless. The nullifier PDAs make the second
+//! submission fail, and its node records the spend as `AlreadySettled`.
+//!
+//! The initiator picks the anchor, so it could try the last
+//! [`MAX_ANCHOR_AGE_SLOTS`] slot hashes for one that makes it primary. That
+//! buys nothing a leader does not already have: the settlement still needs
+//! the co-signers' quorum, and the backups still take over if it stalls.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::Instant;
+
+use crate::consensus::{ApprovedTransact, LeaderSchedule};
+
+/// How long each leader has before the next one in the schedule takes over.
+/// Covers a co-sign round plus a confirmed submit with room to spare; a
+/// backup that starts while the leader is still landing only loses the race.
+pub(crate) const LEADER_TAKEOVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often an approval that could not be anchored, because no finalized
+/// slot hash was readable, is tried again. It is never settled unanchored:
+/// the backups would not know of it.
+pub(crate) const ANCHOR_RETRY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How many slots behind the newest finalized slot a new approval is anchored.
+/// Finalization reaches RPC nodes a few slots apart, and a peer whose RPC has
+/// not seen the anchor yet could not check it.
+const ANCHOR_DEPTH_SLOTS: u64 = 16;
+
+/// How far an anchor may trail the newest finalized slot a receiving node
+/// sees (about a minute), bounding both gossip delay and how many slot hashes
+/// an initiator can pick from.
+pub(crate) const MAX_ANCHOR_AGE_SLOTS: u64 = 150;
+
+/// The anchor for a new approval, from `hashes` (newest first): the newest
+/// finalized slot at least [`ANCHOR_DEPTH_SLOTS`] deep.
+pub(crate) fn new_anchor(hashes: &[(u64, [u8; 32])]) -> Option<(u64, [u8; 32])> {
+    let newest = hashes.iter().map(|(slot, _)| *slot).max()?;
+    hashes
+        .iter()
+        .filter(|(slot, _)| *slot <= newest.saturating_sub(ANCHOR_DEPTH_SLOTS))
+        .max_by_key(|(slot, _)| *slot)
+        .copied()
+}
+
+/// The hash of a received approval's `anchor_slot`, if `hashes` (this node's
+/// finalized view) has it and it is no older than [`MAX_ANCHOR_AGE_SLOTS`].
+pub(crate) fn anchor_hash(hashes: &[(u64, [u8; 32])], anchor_slot: u64) -> Option<[u8; 32]> {
+    let newest = hashes.iter().map(|(slot, _)| *slot).max()?;
+    if newest.saturating_sub(anchor_slot) > MAX_ANCHOR_AGE_SLOTS {
+        return None;
+    }
+    hashes
+        .iter()
+        .find(|(slot, _)| *slot == anchor_slot)
+        .map(|(_, hash)| *hash)
+}
+
+/// Holds each approval until this node's turn to lead it, then hands it to the
+/// transact submitter through the channel [`take_receiver`](Self::take_receiver)
+/// yields.
+pub(crate) struct SettlementScheduler {
+    settle_tx: mpsc::UnboundedSender<ApprovedTransact>,
+    settle_rx: Mutex<Option<mpsc::UnboundedReceiver<ApprovedTransact>>>,
+    /// Request ids already scheduled here, with when they may be forgotten.
+    /// The same approval arrives from the local coordinator once and over
+    /// gossip possibly several times; only the first one counts.
+    scheduled: Mutex<HashMap<String, Instant>>,
+    timeout: Duration,
+}
+
+impl SettlementScheduler {
+    /// A scheduler giving each leader `timeout` before the next takes over.
+    pub(crate) fn new(timeout: Duration) -> Self {
+        let (settle_tx, settle_rx) = mpsc::unbounded_channel();
+        Self {
+            settle_tx,
+            settle_rx: Mutex::new(Some(settle_rx)),
+            scheduled: Mutex::new(HashMap::new()),
+            timeout,
+        }
+    }
+
+    /// The submitter's end of the channel. Taken once, by `run`.
+    pub(crate) async fn take_receiver(&self) -> Option<mpsc::UnboundedReceiver<ApprovedTransact>> {
+        self.settle_rx.lock().await.take()
+    }
+
+    /// Whether `request_id` was already scheduled here.
+    pub(crate) async fn is_scheduled(&self, request_id: &str) -> bool {
+        let mut scheduled = self.scheduled.lock().await;
+        let now = Instant::now();
+        scheduled.retain(|_, forget_at| *forget_at > now);
+        scheduled.contains_key(request_id)
+    }
+
+    /// Record `request_id` as scheduled until `hold` from now. False if it
+    /// already was.
+    async fn claim(&self, request_id: &str, hold: Duration) -> bool {
+        let mut scheduled = self.scheduled.lock().await;
+        let now = Instant::now();
+        scheduled.retain(|_, forget_at| *forget_at > now);
+        if scheduled.contains_key(request_id) {
+            return false;
+        }
+        scheduled.insert(request_id.to_string(), now + hold);
+        true
+    }
+
+    /// Queue `approved` for settlement at `wallet`'s turn in `schedule`, and
+    /// return that turn. `None` if `wallet` has no turn or the request is
+    /// already scheduled.
+    ///
+    /// A backup asks `settled` whether the spend has landed once its turn
+    /// comes, and stands down if it has. The approvals it settles with leave
+    /// out the leaders ranked ahead of it.
+    pub(crate) async fn schedule<S, Fut>(
+        &self,
+        mut approved: ApprovedTransact,
+        schedule: &LeaderSchedule,
+        wallet: &str,
+        settled: S,
+    ) -> Option<usize>
+    where
+        S: FnOnce(ApprovedTransact) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        let rank = schedule.rank(wallet)?;
+        let request_id = approved.request.request_id.clone();
+        if !self
+            .claim(&request_id, self.timeout * (rank as u32 + 1))
+            .await
+        {
+            return None;
+        }
+
+        let ahead = &schedule.order()[..rank];
+        approved
+            .approvals
+            .retain(|approval| !ahead.contains(&approval.wallet_pubkey));
+
+        let settle_tx = self.settle_tx.clone();
+        let delay = self.timeout * rank as u32;
+        tokio::spawn(async move {
+            if rank > 0 {
+                tokio::time::sleep(delay).await;
+                if settled(approved.clone()).await {
+                    log::debug!("transact {request_id} settled before backup turn {rank}");
+                    return;
+                }
+                log::warn!(
+                    "transact {request_id} still unsettled after {rank} leader(s); taking over as backup {rank}"
+                );
+            }
+            let _ = settle_tx.send(approved);
+        });
+        Some(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::transact::{TransactVerificationRequest, TransactVerificationResult};
+    use crate::consensus::VerificationVote;
+    use crate::types::NodeId;
+
+    fn approved(id: &str, wallets: &[&str]) -> ApprovedTransact {
+        ApprovedTransact {
+            request: TransactVerificationRequest {
+                request_id: id.to_string(),
+                recipient: [0; 32],
+                mint: None,
+                nullifiers: [[1; 32], [2; 32]],
+                output_commitments: [[3; 32], [4; 32]],
+                root: [5; 32],
+                ext_amount: 0,
+                proof: vec![],
+                ciphertexts: [String::new(), String::new()],
+                timestamp: 0,
+                slot: None,
+            },
+            approvals: wallets
+                .iter()
+                .enumerate()
+                .map(|(i, w)| TransactVerificationResult {
+                    request_id: id.to_string(),
+                    validator: NodeId(vec![i as u8]),
+                    vote: VerificationVote::Valid,
+                    timestamp: 0,
+                    wallet_pubkey: w.to_string(),
+                    signature: vec![1],
+                })
+                .collect(),
+            epoch: 0,
+        }
+    }
+
+    fn schedule_of(id: &str, wallets: &[&str]) -> LeaderSchedule {
+        let candidates: Vec<(String, u64)> = wallets.iter().map(|w| (w.to_string(), 1)).collect();
+        LeaderSchedule::compute(id, 1, &[1; 32], &candidates, |_, _| true)
+    }
+
+    #[test]
+    fn anchors_are_finalized_deep_enough_and_recent() {
+        let hashes: Vec<(u64, [u8; 32])> = (0..200u64).rev().map(|s| (s, [s as u8; 32])).collect();
+        assert_eq!(new_anchor(&hashes), Some((183, [183; 32])));
+        assert_eq!(anchor_hash(&hashes, 183), Some([183; 32]));
+        assert_eq!(anchor_hash(&hashes, 49), Some([49; 32]));
+        assert_eq!(anchor_hash(&hashes, 48), None, "older than the age bound");
+        assert_eq!(anchor_hash(&hashes, 250), None, "not finalized here yet");
+        assert_eq!(new_anchor(&[]), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn the_primary_settles_at_once_and_a_duplicate_is_ignored() {
+        let scheduler = SettlementScheduler::new(LEADER_TAKEOVER_TIMEOUT);
+        let mut rx = scheduler.take_receiver().await.unwrap();
+        let schedule = schedule_of("r", &["W1", "W2", "W3"]);
+        let primary = schedule.primary().unwrap().to_string();
+
+        let rank = scheduler
+            .schedule(
+                approved("r", &["W1", "W2", "W3"]),
+                &schedule,
+                &primary,
+                |_| async { false },
+            )
+            .await;
+        assert_eq!(rank, Some(0));
+        let settled = rx.recv().await.unwrap();
+        assert_eq!(settled.approvals.len(), 3, "the primary asks everyone");
+
+        assert!(scheduler.is_scheduled("r").await);
+        assert_eq!(
+            scheduler
+                .schedule(
+                    approved("r", &["W1", "W2", "W3"]),
+                    &schedule,
+                    &primary,
+                    |_| async { false }
+                )
+                .await,
+            None
+        );
+        assert_eq!(
+            scheduler
+                .schedule(
+                    approved("x", &["W1"]),
+                    &schedule_of("x", &["W1"]),
+                    "W9",
+                    |_| async { false }
+                )
+                .await,
+            None,
+            "a wallet without a turn schedules nothing"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_backup_takes_over_only_after_its_turn_and_only_if_unsettled() {
+        let wallets = ["W1", "W2", "W3"];
+        let schedule = schedule_of("r", &wallets);
+        let backup = schedule.order()[1].clone();
+
+        // The spend never lands: the first backup settles after one timeout,
+        // without the primary.
+        let scheduler = SettlementScheduler::new(LEADER_TAKEOVER_TIMEOUT);
+        let mut rx = scheduler.take_receiver().await.unwrap();
+        let rank = scheduler
+            .schedule(approved("r", &wallets), &schedule, &backup, |_| async {
+                false
+            })
+            .await;
+        assert_eq!(rank, Some(1));
+        tokio::time::sleep(LEADER_TAKEOVER_TIMEOUT - Duration::from_secs(1)).await;
+        assert!(rx.try_recv().is_err(), "the primary still has its turn");
+        let settled = tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .expect("backup takes over after one timeout")
+            .unwrap();
+        let primary = schedule.primary().unwrap();
+        assert!(settled.approvals.iter().all(|a| a.wallet_pubkey != primary));
+        assert_eq!(settled.approvals.len(), 2);
+
+        // The primary landed it: the backup stands down.
+        let scheduler = SettlementScheduler::new(LEADER_TAKEOVER_TIMEOUT);
+        let mut rx = scheduler.take_receiver().await.unwrap();
+        scheduler
+            .schedule(approved("r", &wallets), &schedule, &backup, |_| async {
+                true
+            })
+            .await;
+        tokio::time::sleep(LEADER_TAKEOVER_TIMEOUT * 3).await;
+        assert!(rx.try_recv().is_err());
+
+        // Once its hold has lapsed the id can be scheduled again.
+        assert!(!scheduler.is_scheduled("r").await);
+    }
+}
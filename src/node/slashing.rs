@@ -0,0 +1,149 @@
+//! On-chain slashing of equivocation evidence.
+//!
+//! The transact coordinator records a wallet that signed both a `Valid` and
+//! an `Invalid` vote on one request (see [`crate::consensus::slashing`]).
+//! On a node configured with the registry authority
+//! (`bridge.slashing_authority_keypair_path`), [`slash_pending`] runs on a
+//! timer and, for every pending record:
+//!
+//! 1. re-checks both vote signatures against the cluster's
+//!    `transact_vote_signing_bytes` — a record that fails is marked
+//!    `Rejected` and never sent;
+//! 2. signs `slash_validator` for the wallet, records the record as
+//!    `Submitting` with the transaction signature, and only then broadcasts
+//!    it; once it lands the record is `Submitted`.
+//!
+//! Every slash names its evidence (see
+//! [`SlashingEvidence::evidence_id`](crate::consensus::SlashingEvidence::evidence_id)),
+//! and the program keeps a slash-record PDA per (wallet, evidence) that a
+//! second slash for the same evidence cannot create. Before sending, the
+//! node asks whether that PDA already exists: a `Submitting` record whose
+//! confirmation timed out, or whose node crashed mid-send, is settled from
+//! the chain rather than slashed again. A submission that fails (RPC down,
+//! validator already gone) stays on the worklist for the next tick.
+
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Keypair;
+use std::str::FromStr;
+use tokio::sync::Mutex;
+
+use crate::bridge::solana::vote_signature_is_valid;
+use crate::bridge::Bridge;
+use crate::consensus::{verify_equivocation, SlashStatus, SlashingEvidence, SlashingTracker};
+
+/// How often the node works through pending evidence.
+pub(crate) const SLASHING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Verify and submit every pending equivocation in `tracker`. Returns how
+/// many slashes landed.
+pub(crate) async fn slash_pending(
+    tracker: &SlashingTracker,
+    bridge: &Mutex<Bridge>,
+    authority: &Keypair,
+    program_id: &str,
+    cluster_tag: &str,
+    slash_percentage: u8,
+) -> usize {
+    let mut slashed = 0;
+    for record in tracker.pending().await {
+        let SlashingEvidence::Equivocation {
+            request_id,
+            wallet_pubkey,
+            ..
+        } = &record.evidence
+        else {
+            continue;
+        };
+
+        let status = match verify_equivocation(
+            &record,
+            program_id,
+            cluster_tag,
+            vote_signature_is_valid,
+        ) {
+            Err(e) => {
+                log::warn!(
+                    "equivocation evidence against {wallet_pubkey} on {request_id} rejected: {e}"
+                );
+                SlashStatus::Rejected {
+                    reason: e.to_string(),
+                }
+            }
+            Ok(()) => {
+                // The signatures verified under this wallet, so it parses.
+                let Ok(validator) = Pubkey::from_str(wallet_pubkey) else {
+                    continue;
+                };
+                let Some(evidence_id) = record.evidence.evidence_id() else {
+                    continue;
+                };
+                let earlier = match &record.status {
+                    SlashStatus::Submitting { signature } => Some(signature.clone()),
+                    _ => None,
+                };
+                let bridge = bridge.lock().await;
+                if bridge.is_evidence_slashed(&validator, &evidence_id).await {
+                    // An earlier attempt (ours, if we have its signature)
+                    // already landed.
+                    log::info!("slash of {wallet_pubkey} for {request_id} is already on chain");
+                    slashed += 1;
+                    SlashStatus::Submitted {
+                        signature: earlier.unwrap_or_default(),
+                    }
+                } else {
+                    let transaction = match bridge
+                        .slash_validator_transaction(
+                            authority,
+                            &validator,
+                            slash_percentage,
+                            &evidence_id,
+                        )
+                        .await
+                    {
+                        Ok(transaction) => transaction,
+                        Err(e) => {
+                            log::warn!(
+                                "could not build slash_validator for {wallet_pubkey}, retrying next tick: {e}"
+                            );
+                            continue;
+                        }
+                    };
+                    let signature = transaction.signatures[0].to_string();
+                    // Record the attempt before it can land, so a restart
+                    // finds it and reconciles instead of sending blind.
+                    let submitting = SlashStatus::Submitting {
+                        signature: signature.clone(),
+                    };
+                    if let Err(e) = tracker
+                        .set_status(wallet_pubkey, request_id, submitting)
+                        .await
+                    {
+                        log::warn!(
+                            "could not record slash attempt for {wallet_pubkey} on {request_id}, not sending: {e}"
+                        );
+                        continue;
+                    }
+                    match bridge.submit_signed_transaction(&transaction).await {
+                        Ok(_) => {
+                            log::warn!(
+                                "slashed {slash_percentage}% of {wallet_pubkey} for equivocating on {request_id}: {signature}"
+                            );
+                            slashed += 1;
+                            SlashStatus::Submitted { signature }
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "slash_validator {signature} for {wallet_pubkey} unconfirmed, reconciling next tick: {e}"
+                            );
+                            continue;
+                        }
+                    }
+                }
+            }
+        };
+        if let Err(e) = tracker.set_status(wallet_pubkey, request_id, status).await {
+            log::warn!("could not record slashing status for {wallet_pubkey} on {request_id}: {e}");
+        }
+    }
+    slashed
+}
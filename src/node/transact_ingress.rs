@@ -21,10 +21,28 @@
 //!   `400` on malformed input (including `ext_amount > 0` — deposits go
 //!   through `deposit_note`, never this ingress), or `503` if the node cannot
 //!   start verification (e.g. no validator quorum is registered yet).
+//! - `GET /transact/scan?after=<cursor>&limit=<n>` — delivered notes for
+//!   trial decryption, one page at a time: `{ "notes": [...], "cursor": u64,
+//!   "more": bool }`. Notes are numbered in the order the node recorded them;
+//!   `after` (default `0`, the start) skips those up to and including it, and
+//!   `limit` defaults to and is capped at [`MAX_SCAN_PAGE`]. Pass the returned
+//!   `cursor` as the next `after`; `more` says whether to ask again now.
+//! - `POST /transact/scan/detect` — JSON body `{ "detection_key":
+//!   "paraloomdtk1...", "after": u64, "limit": n }`, both paging fields
+//!   optional. Returns the same page shape, holding only the notes of the
+//!   window whose detection clue the key matches, plus every note without a
+//!   clue, which the node has no way to filter; the cursor still covers the
+//!   whole window. `400` if the key does not parse. Handing over a detection
+//!   key tells the node nothing it can link to a payment beyond the key's
+//!   false-positive rate; see [`crate::privacy::detection`].
+//! - `GET /transact/certificate/{request_id}` — the [`QuorumCertificate`] of
+//!   an approved transact: the signed votes that approved it and the stake
+//!   they were counted against, for an operator or auditor to verify offline.
+//!   `404` if this node holds none for that id.
 
 use async_trait::async_trait;
 use axum::{
-    extract::Extension,
+    extract::{Extension, Path, Query},
     http::StatusCode,
     response::Json,
     routing::{get, post},
@@ -36,7 +54,14 @@ use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::consensus::transact::TransactVerificationRequest;
+use crate::consensus::QuorumCertificate;
 use crate::node::ingress_auth::{check_bearer, IngressToken};
+use crate::privacy::detection::DetectionKey;
+use crate::privacy::note_crypto::envelope_clue;
+use crate::storage::DeliveredRecord;
+
+/// Most notes one scan page returns.
+pub const MAX_SCAN_PAGE: usize = 1_000;
 
 /// A delivered encrypted output note (#196): the output commitment and the
 /// opaque hex ciphertext (`EncryptedNote`) a recipient trial-decrypts. (Moved
@@ -45,6 +70,56 @@ use crate::node::ingress_auth::{check_bearer, IngressToken};
 pub struct DeliveredNote {
     pub output_commitment: String,
     pub ciphertext: String,
+    /// SPL mint of the settlement that created the note, 32-byte hex; omitted
+    /// for native SOL. The mint is already public on the request (it selects
+    /// the `transact_spl` path), and a recipient needs it to re-derive the
+    /// commitment, whose `asset_id` is not in the plaintext.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mint: Option<String>,
+    /// Leaf index of `output_commitment` in the on-chain tree, once the node
+    /// has seen the settlement land; omitted before that.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_index: Option<u64>,
+    /// Slot the settlement landed in; omitted until then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u64>,
+}
+
+impl From<DeliveredRecord> for DeliveredNote {
+    fn from(record: DeliveredRecord) -> Self {
+        DeliveredNote {
+            output_commitment: hex::encode(record.commitment),
+            ciphertext: record.ciphertext,
+            mint: record.mint.map(hex::encode),
+            leaf_index: record.leaf_index,
+            slot: record.slot,
+        }
+    }
+}
+
+/// One page of the delivered-note feed.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DeliveredPage {
+    pub notes: Vec<DeliveredNote>,
+    /// The sequence number of the last note this page covers, or the request's
+    /// `after` if it covered none. The next request passes it as `after`.
+    pub cursor: u64,
+    /// Whether notes past `cursor` were already there when this page was read.
+    pub more: bool,
+}
+
+impl DeliveredPage {
+    /// Build a page from up to `limit + 1` sequenced notes read after `after`;
+    /// the extra one, if present, only sets `more`.
+    pub fn from_window(after: u64, limit: usize, mut window: Vec<(u64, DeliveredNote)>) -> Self {
+        let more = window.len() > limit;
+        window.truncate(limit);
+        DeliveredPage {
+            cursor: window.last().map_or(after, |(seq, _)| *seq),
+            notes: window.into_iter().map(|(_, note)| note).collect(),
+            more,
+        }
+    }
 }
 
 /// The capabilities the ingress needs: hand a transact to the consensus mesh,
@@ -55,8 +130,18 @@ pub trait TransactIngress: Send + Sync {
     async fn submit_transact(&self, request: TransactVerificationRequest)
         -> anyhow::Result<String>;
 
-    /// All encrypted notes this node has seen, for recipient scanning (#196).
-    async fn delivered_notes(&self) -> Vec<DeliveredNote>;
+    /// Up to `limit` encrypted notes recorded after cursor `after`, for
+    /// recipient scanning (#196).
+    async fn delivered_notes(&self, after: u64, limit: usize) -> anyhow::Result<DeliveredPage>;
+
+    /// The quorum certificate kept for `request_id`. A node that keeps none
+    /// has none to serve.
+    async fn quorum_certificate(
+        &self,
+        _request_id: &str,
+    ) -> anyhow::Result<Option<QuorumCertificate>> {
+        Ok(None)
+    }
 }
 
 #[async_trait]
@@ -68,8 +153,15 @@ impl TransactIngress for crate::node::Node {
         self.initiate_transact_verification(request).await
     }
 
-    async fn delivered_notes(&self) -> Vec<DeliveredNote> {
-        self.delivered_transfer_notes().await
+    async fn delivered_notes(&self, after: u64, limit: usize) -> anyhow::Result<DeliveredPage> {
+        self.delivered_transfer_notes(after, limit).await
+    }
+
+    async fn quorum_certificate(
+        &self,
+        request_id: &str,
+    ) -> anyhow::Result<Option<QuorumCertificate>> {
+        crate::node::Node::quorum_certificate(self, request_id).await
     }
 }
 
@@ -204,6 +296,8 @@ async fn submit_handler(
         proof,
         ciphertexts,
         timestamp,
+        // Stamped by the node when it starts the round.
+        slot: None,
     };
     request.request_id = request.canonical_id();
 
@@ -217,13 +311,91 @@ async fn submit_handler(
     Ok(Json(SubmitResponse { request_id: id }))
 }
 
-/// `GET /transact/scan` (#196) — every encrypted note this node has seen. A
-/// recipient polls it and trial-decrypts each ciphertext with its viewing key,
-/// keeping the ones that decrypt. Failed decrypts are silent.
+/// Paging fields shared by both scan routes.
+#[derive(Debug, Default, Deserialize)]
+struct ScanQuery {
+    #[serde(default)]
+    after: u64,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+impl ScanQuery {
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(MAX_SCAN_PAGE).min(MAX_SCAN_PAGE)
+    }
+}
+
+async fn read_page(
+    node: &Arc<dyn TransactIngress>,
+    query: &ScanQuery,
+) -> Result<DeliveredPage, (StatusCode, String)> {
+    node.delivered_notes(query.after, query.limit())
+        .await
+        .map_err(|e| (StatusCode::SERVICE_UNAVAILABLE, e.to_string()))
+}
+
+/// `GET /transact/scan` (#196) — a page of the encrypted notes this node has
+/// seen. A recipient walks the pages and trial-decrypts each ciphertext with
+/// its viewing key, keeping the ones that decrypt. Failed decrypts are silent.
 async fn scan_handler(
     Extension(node): Extension<Arc<dyn TransactIngress>>,
-) -> Json<Vec<DeliveredNote>> {
-    Json(node.delivered_notes().await)
+    Query(query): Query<ScanQuery>,
+) -> Result<Json<DeliveredPage>, (StatusCode, String)> {
+    Ok(Json(read_page(&node, &query).await?))
+}
+
+#[derive(Debug, Deserialize)]
+struct DetectRequest {
+    detection_key: String,
+    #[serde(flatten)]
+    page: ScanQuery,
+}
+
+/// The notes in `notes` that `key` may have been paid by: those whose clue
+/// it detects and those that carry no clue at all.
+pub fn detect_candidates(key: &DetectionKey, notes: Vec<DeliveredNote>) -> Vec<DeliveredNote> {
+    notes
+        .into_iter()
+        .filter(|note| {
+            let clue = hex::decode(&note.ciphertext)
+                .ok()
+                .and_then(|blob| envelope_clue(&blob));
+            clue.is_none_or(|clue| key.detects(&clue))
+        })
+        .collect()
+}
+
+/// `POST /transact/scan/detect` — the scan, narrowed by a detection key. A
+/// light client trial-decrypts what comes back instead of the whole page.
+async fn scan_detect_handler(
+    Extension(node): Extension<Arc<dyn TransactIngress>>,
+    Json(body): Json<DetectRequest>,
+) -> Result<Json<DeliveredPage>, (StatusCode, String)> {
+    let key = DetectionKey::parse(&body.detection_key)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("detection_key: {e}")))?;
+    let mut page = read_page(&node, &body.page).await?;
+    // A few scalar multiplications per note adds up over a full page.
+    let notes = std::mem::take(&mut page.notes);
+    page.notes = tokio::task::spawn_blocking(move || detect_candidates(&key, notes))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(page))
+}
+
+/// `GET /transact/certificate/{request_id}` — who approved a transact.
+async fn certificate_handler(
+    Extension(node): Extension<Arc<dyn TransactIngress>>,
+    Path(request_id): Path<String>,
+) -> Result<Json<QuorumCertificate>, (StatusCode, String)> {
+    match node.quorum_certificate(&request_id).await {
+        Ok(Some(certificate)) => Ok(Json(certificate)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            format!("no quorum certificate for {request_id}"),
+        )),
+        Err(e) => Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string())),
+    }
 }
 
 /// Build the ingress router. Exposed separately from [`serve`] so it can be
@@ -232,6 +404,11 @@ pub fn router(node: Arc<dyn TransactIngress>, token: IngressToken) -> Router {
     Router::new()
         .route("/transact/submit", post(submit_handler))
         .route("/transact/scan", get(scan_handler))
+        .route("/transact/scan/detect", post(scan_detect_handler))
+        .route(
+            "/transact/certificate/:request_id",
+            get(certificate_handler),
+        )
         .layer(Extension(node))
         .layer(Extension(token))
 }
@@ -281,8 +458,8 @@ mod tests {
             }
         }
 
-        async fn delivered_notes(&self) -> Vec<DeliveredNote> {
-            vec![]
+        async fn delivered_notes(&self, _: u64, _: usize) -> anyhow::Result<DeliveredPage> {
+            Ok(DeliveredPage::default())
         }
     }
 
@@ -303,8 +480,8 @@ mod tests {
             Ok(id)
         }
 
-        async fn delivered_notes(&self) -> Vec<DeliveredNote> {
-            vec![]
+        async fn delivered_notes(&self, _: u64, _: usize) -> anyhow::Result<DeliveredPage> {
+            Ok(DeliveredPage::default())
         }
     }
 
@@ -474,6 +651,16 @@ mod tests {
         assert_eq!(resp.status(), StatusCode::OK);
     }
 
+    /// Pages `notes` the way the node does, numbering them from 1.
+    fn page_of(notes: Vec<DeliveredNote>, after: u64, limit: usize) -> DeliveredPage {
+        let window = (1u64..)
+            .zip(notes)
+            .filter(|(seq, _)| *seq > after)
+            .take(limit + 1)
+            .collect();
+        DeliveredPage::from_window(after, limit, window)
+    }
+
     /// Stub that serves a fixed delivered note, to exercise the scan route.
     struct ScanStub;
     #[async_trait]
@@ -481,28 +668,190 @@ mod tests {
         async fn submit_transact(&self, _: TransactVerificationRequest) -> anyhow::Result<String> {
             anyhow::bail!("not used")
         }
-        async fn delivered_notes(&self) -> Vec<DeliveredNote> {
-            vec![DeliveredNote {
+        async fn delivered_notes(&self, after: u64, limit: usize) -> anyhow::Result<DeliveredPage> {
+            let note = DeliveredNote {
                 output_commitment: "33".repeat(32),
                 ciphertext: "ab".repeat(88),
-            }]
+                mint: None,
+                leaf_index: Some(7),
+                slot: Some(1_234),
+            };
+            Ok(page_of(vec![note], after, limit))
         }
     }
 
-    #[tokio::test]
-    async fn scan_returns_delivered_notes() {
-        let app = router(Arc::new(ScanStub), None);
+    async fn get_scan(app: Router, uri: &str) -> DeliveredPage {
         let req = Request::builder()
             .method("GET")
-            .uri("/transact/scan")
+            .uri(uri)
             .body(Body::empty())
             .unwrap();
         let resp = app.oneshot(req).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
         let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
-        let notes: Vec<DeliveredNote> = serde_json::from_slice(&body).unwrap();
-        assert_eq!(notes.len(), 1);
-        assert_eq!(notes[0].output_commitment, "33".repeat(32));
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn scan_returns_delivered_notes() {
+        let page = get_scan(router(Arc::new(ScanStub), None), "/transact/scan").await;
+        assert_eq!(page.notes.len(), 1);
+        assert_eq!(page.notes[0].output_commitment, "33".repeat(32));
+        assert_eq!(page.notes[0].leaf_index, Some(7));
+        assert_eq!((page.cursor, page.more), (1, false));
+    }
+
+    /// Serves `count` plain notes, to exercise paging.
+    struct FeedStub {
+        count: u8,
+    }
+    #[async_trait]
+    impl TransactIngress for FeedStub {
+        async fn submit_transact(&self, _: TransactVerificationRequest) -> anyhow::Result<String> {
+            anyhow::bail!("not used")
+        }
+        async fn delivered_notes(&self, after: u64, limit: usize) -> anyhow::Result<DeliveredPage> {
+            let notes = (0..self.count)
+                .map(|i| DeliveredNote {
+                    output_commitment: format!("{i:02x}").repeat(32),
+                    ciphertext: v1_ciphertext_hex(i),
+                    mint: None,
+                    leaf_index: None,
+                    slot: None,
+                })
+                .collect();
+            Ok(page_of(notes, after, limit))
+        }
+    }
+
+    #[tokio::test]
+    async fn scan_pages_by_cursor() {
+        let app = || router(Arc::new(FeedStub { count: 5 }), None);
+
+        let first = get_scan(app(), "/transact/scan?limit=2").await;
+        assert_eq!(first.notes.len(), 2);
+        assert_eq!((first.cursor, first.more), (2, true));
+
+        let uri = format!("/transact/scan?after={}&limit=2", first.cursor);
+        let second = get_scan(app(), &uri).await;
+        assert_eq!(second.notes[0].output_commitment, "02".repeat(32));
+        assert_eq!((second.cursor, second.more), (4, true));
+
+        let last = get_scan(app(), "/transact/scan?after=4&limit=2").await;
+        assert_eq!((last.notes.len(), last.cursor, last.more), (1, 5, false));
+
+        // Caught up: the cursor stays put.
+        let empty = get_scan(app(), "/transact/scan?after=5").await;
+        assert!(empty.notes.is_empty());
+        assert_eq!((empty.cursor, empty.more), (5, false));
+
+        // An oversized limit is capped, not refused.
+        let uri = format!("/transact/scan?limit={}", MAX_SCAN_PAGE * 10);
+        assert_eq!(get_scan(app(), &uri).await.notes.len(), 5);
+    }
+
+    /// Serves one clued note to `mine`, one clued note to someone else, and
+    /// one note with no clue.
+    struct DetectStub {
+        mine: crate::privacy::detection::ClueKey,
+    }
+    #[async_trait]
+    impl TransactIngress for DetectStub {
+        async fn submit_transact(&self, _: TransactVerificationRequest) -> anyhow::Result<String> {
+            anyhow::bail!("not used")
+        }
+        async fn delivered_notes(&self, after: u64, limit: usize) -> anyhow::Result<DeliveredPage> {
+            use crate::privacy::detection::{Clue, ClueKey, DetectionSecret};
+            use crate::privacy::note_crypto::attach_clue;
+
+            let plain = hex::decode(v1_ciphertext_hex(0x11)).unwrap();
+            let other = DetectionSecret::from_viewing_secret(&[9; 32]).clue_key();
+            let clued = |key: &ClueKey| {
+                hex::encode(attach_clue(&plain, &Clue::create(key).unwrap()).unwrap())
+            };
+            let notes = [clued(&self.mine), clued(&other), hex::encode(&plain)]
+                .into_iter()
+                .enumerate()
+                .map(|(i, ciphertext)| DeliveredNote {
+                    output_commitment: format!("{i:02x}").repeat(32),
+                    ciphertext,
+                    mint: None,
+                    leaf_index: None,
+                    slot: None,
+                })
+                .collect();
+            Ok(page_of(notes, after, limit))
+        }
+    }
+
+    #[tokio::test]
+    async fn detect_scan_returns_only_candidates() {
+        use crate::privacy::detection::{DetectionSecret, MAX_PRECISION};
+
+        let secret = DetectionSecret::from_viewing_secret(&[4; 32]);
+        let app = router(
+            Arc::new(DetectStub {
+                mine: secret.clue_key(),
+            }),
+            None,
+        );
+        let body = serde_json::json!({
+            "detection_key": secret.detection_key(MAX_PRECISION).unwrap().encode(),
+        });
+        let resp = app.oneshot(post_detect(&body.to_string())).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        let page: DeliveredPage = serde_json::from_slice(&body).unwrap();
+        let commitments: Vec<_> = page
+            .notes
+            .iter()
+            .map(|n| n.output_commitment.clone())
+            .collect();
+        // Ours, and the untagged note; at 24 bits the other clue is out.
+        assert_eq!(commitments, ["00".repeat(32), "02".repeat(32)]);
+        assert_eq!(page.cursor, 3);
+    }
+
+    #[tokio::test]
+    async fn detect_scan_cursor_covers_filtered_notes() {
+        use crate::privacy::detection::{DetectionSecret, MAX_PRECISION};
+
+        let secret = DetectionSecret::from_viewing_secret(&[4; 32]);
+        let app = router(
+            Arc::new(DetectStub {
+                mine: secret.clue_key(),
+            }),
+            None,
+        );
+        // The window after note 1 holds only the other wallet's note, so
+        // nothing comes back, yet the cursor still moves past it.
+        let body = serde_json::json!({
+            "detection_key": secret.detection_key(MAX_PRECISION).unwrap().encode(),
+            "after": 1,
+            "limit": 1,
+        });
+        let resp = app.oneshot(post_detect(&body.to_string())).await.unwrap();
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        let page: DeliveredPage = serde_json::from_slice(&body).unwrap();
+        assert!(page.notes.is_empty());
+        assert_eq!((page.cursor, page.more), (2, true));
+    }
+
+    #[tokio::test]
+    async fn detect_scan_rejects_a_bad_key() {
+        let app = router(Arc::new(ScanStub), None);
+        let body = serde_json::json!({ "detection_key": "paraloomdtk1qqqq" });
+        let resp = app.oneshot(post_detect(&body.to_string())).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    fn post_detect(body: &str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/transact/scan/detect")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_owned()))
+            .unwrap()
     }
 
     /// The carrier rule at the HTTP layer: core relays a version it cannot
@@ -511,7 +860,7 @@ mod tests {
     #[tokio::test]
     async fn unknown_envelope_version_is_relayed() {
         let app = router(Arc::new(StubIngress { accept: true }), None);
-        let body = well_formed_body().replace(&v1_ciphertext_hex(0xab), "02");
+        let body = well_formed_body().replace(&v1_ciphertext_hex(0xab), "03");
         let resp = app.oneshot(post_json(&body)).await.unwrap();
         assert_eq!(resp.status(), StatusCode::OK);
     }
@@ -532,4 +881,63 @@ mod tests {
         let resp = app.oneshot(post_json(&body)).await.unwrap();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
     }
+
+    /// Stub holding one certificate, for `r1`.
+    struct CertificateStub;
+    #[async_trait]
+    impl TransactIngress for CertificateStub {
+        async fn submit_transact(&self, _: TransactVerificationRequest) -> anyhow::Result<String> {
+            anyhow::bail!("not used")
+        }
+        async fn delivered_notes(&self, _: u64, _: usize) -> anyhow::Result<DeliveredPage> {
+            anyhow::bail!("not used")
+        }
+        async fn quorum_certificate(
+            &self,
+            request_id: &str,
+        ) -> anyhow::Result<Option<QuorumCertificate>> {
+            Ok((request_id == "r1").then(|| {
+                QuorumCertificate::new(
+                    "r1".to_string(),
+                    "prog".to_string(),
+                    "devnet".to_string(),
+                    0,
+                    &std::collections::HashMap::from([("W1".to_string(), 100)]),
+                    100,
+                    Vec::new(),
+                )
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn certificate_is_served_by_request_id() {
+        let get = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+        let app = router(Arc::new(CertificateStub), None);
+        let resp = app
+            .clone()
+            .oneshot(get("/transact/certificate/r1"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(resp.into_body()).await.unwrap();
+        let certificate: QuorumCertificate = serde_json::from_slice(&body).unwrap();
+        assert_eq!(certificate.request_id, "r1");
+
+        let resp = app.oneshot(get("/transact/certificate/r2")).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // A node that keeps no certificates answers 404, not an error.
+        let resp = router(Arc::new(StubIngress { accept: true }), None)
+            .oneshot(get("/transact/certificate/r1"))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -0,0 +1,479 @@
+//! Checksummed shielded payment addresses.
+//!
+//! A sender needs two keys to pay a shielded recipient: the v3 spend public
+//! key (`Poseidon(1)([privkey])`) that goes into the output commitment, and
+//! the X25519 key the encrypted note is sealed to (#196). A bare 32-byte
+//! [`ShieldedAddress`] carries only the first, so a sender holding one could
+//! create a note the recipient owns but can never find.
+//!
+//! [`PaymentAddress`] carries both, behind a version byte, in a bech32m
+//! string (BIP-350 checksum, human-readable part `paraloom`):
+//!
+//! ```text
+//! paraloom1 <bech32m( version(1) || spend_pubkey(32) || transmission_key(32) )>
+//! ```
+//!
+//! A v2 address appends the recipient's detection clue key (see
+//! [`crate::privacy::detection`]), one point per clue bit, so senders can tag
+//! the note for light-client scanning:
+//!
+//! ```text
+//! paraloom1 <bech32m( 2 || spend_pubkey(32) || transmission_key(32) || clue_key(32·24) )>
+//! ```
+//!
+//! A v3 address is one of a wallet's diversified addresses (see
+//! [`crate::privacy::diversifier`]). It leads with the diversifier, which a
+//! sender needs to seal the note, and has no clue key:
+//!
+//! ```text
+//! paraloom1 <bech32m( 3 || diversifier(16) || spend_pubkey(32) || transmission_key(32) )>
+//! ```
+//!
+//! The checksum catches any single typo and most transpositions, so a
+//! mistyped address fails to parse instead of paying a key nobody holds. The
+//! encoding is longer than BIP-173's 90-character limit, which this codec does
+//! not enforce; the checksum's guarantees weaken gradually past it but still
+//! reject random corruption with overwhelming probability.
+
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::privacy::detection::{ClueKey, CLUE_KEY_LEN};
+use crate::privacy::diversifier::{Diversifier, DIVERSIFIER_LEN};
+use crate::privacy::types::ShieldedAddress;
+
+/// Human-readable part of every encoded payment address.
+pub const ADDRESS_HRP: &str = "paraloom";
+
+/// Version byte of the `spend_pubkey || transmission_key` layout.
+pub const ADDRESS_VERSION_V1: u8 = 1;
+
+/// Version byte of the `spend_pubkey || transmission_key || clue_key` layout.
+pub const ADDRESS_VERSION_V2: u8 = 2;
+
+/// Version byte of the `diversifier || spend_pubkey || transmission_key`
+/// layout.
+pub const ADDRESS_VERSION_V3: u8 = 3;
+
+/// Decoded payload length of a v1 address.
+const V1_PAYLOAD_LEN: usize = 1 + 32 + 32;
+
+/// Decoded payload length of a v2 address.
+const V2_PAYLOAD_LEN: usize = V1_PAYLOAD_LEN + CLUE_KEY_LEN;
+
+/// Decoded payload length of a v3 address.
+const V3_PAYLOAD_LEN: usize = V1_PAYLOAD_LEN + DIVERSIFIER_LEN;
+
+/// Why an address string was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("address is missing the '1' separator")]
+    MissingSeparator,
+    #[error("address mixes upper and lower case")]
+    MixedCase,
+    #[error("wrong address prefix '{0}', expected 'paraloom'")]
+    WrongPrefix(String),
+    #[error("invalid character '{0}' in address")]
+    InvalidChar(char),
+    #[error("address checksum mismatch (typo?)")]
+    BadChecksum,
+    #[error("invalid address padding")]
+    BadPadding,
+    #[error("unsupported address version {0}")]
+    UnknownVersion(u8),
+    #[error("address payload has wrong length {0}")]
+    BadLength(usize),
+    #[error("address detection clue key holds an invalid point")]
+    BadClueKey,
+    #[error(
+        "legacy 'paraloom1<hex>' addresses carry no note-encryption key and are no longer \
+         accepted; ask the recipient for a new address"
+    )]
+    Legacy,
+}
+
+/// A payable shielded address: where the note is committed and who can read it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PaymentAddress {
+    /// v3 spend public key bound into the output commitment.
+    pub spend: ShieldedAddress,
+    /// X25519 public key the output note is encrypted to.
+    pub transmission_key: [u8; 32],
+    /// Key detection clues are made to; `None` for a v1 or v3 address.
+    pub clue_key: Option<ClueKey>,
+    /// Set on a diversified (v3) address: the sender seals the note on its
+    /// base point.
+    pub diversifier: Option<Diversifier>,
+}
+
+impl PaymentAddress {
+    pub fn new(spend: ShieldedAddress, transmission_key: [u8; 32]) -> Self {
+        PaymentAddress {
+            spend,
+            transmission_key,
+            clue_key: None,
+            diversifier: None,
+        }
+    }
+
+    /// One of a wallet's diversified addresses, encoded as v3.
+    pub fn diversified(
+        diversifier: Diversifier,
+        spend: ShieldedAddress,
+        transmission_key: [u8; 32],
+    ) -> Self {
+        PaymentAddress {
+            diversifier: Some(diversifier),
+            ..Self::new(spend, transmission_key)
+        }
+    }
+
+    /// The same address with a detection clue key, encoded as v2.
+    pub fn with_clue_key(mut self, clue_key: ClueKey) -> Self {
+        self.clue_key = Some(clue_key);
+        self
+    }
+
+    /// The canonical (lower-case) bech32m string. A diversified address is
+    /// written as v3 and drops any clue key.
+    pub fn encode(&self) -> String {
+        let mut payload = Vec::with_capacity(V2_PAYLOAD_LEN);
+        match (&self.diversifier, &self.clue_key) {
+            (Some(diversifier), _) => {
+                payload.push(ADDRESS_VERSION_V3);
+                payload.extend_from_slice(&diversifier.0);
+            }
+            (None, Some(_)) => payload.push(ADDRESS_VERSION_V2),
+            (None, None) => payload.push(ADDRESS_VERSION_V1),
+        }
+        payload.extend_from_slice(self.spend.as_bytes());
+        payload.extend_from_slice(&self.transmission_key);
+        if let (None, Some(clue_key)) = (&self.diversifier, &self.clue_key) {
+            payload.extend_from_slice(&clue_key.to_bytes());
+        }
+        bech32m_encode(
+            ADDRESS_HRP,
+            &convert_bits(&payload, 8, 5, true).unwrap_or_default(),
+        )
+    }
+
+    /// Parse an encoded address, verifying prefix, checksum and version, and
+    /// for v2 that every clue key point is valid.
+    pub fn parse(s: &str) -> Result<Self, AddressError> {
+        let s = s.trim();
+        if is_legacy_hex(s) {
+            return Err(AddressError::Legacy);
+        }
+        let (hrp, data) = bech32m_decode(s)?;
+        if hrp != ADDRESS_HRP {
+            return Err(AddressError::WrongPrefix(hrp));
+        }
+        let payload = convert_bits(&data, 5, 8, false).ok_or(AddressError::BadPadding)?;
+        let (&version, rest) = payload.split_first().ok_or(AddressError::BadLength(0))?;
+        let expected_len = match version {
+            ADDRESS_VERSION_V1 => V1_PAYLOAD_LEN,
+            ADDRESS_VERSION_V2 => V2_PAYLOAD_LEN,
+            ADDRESS_VERSION_V3 => V3_PAYLOAD_LEN,
+            other => return Err(AddressError::UnknownVersion(other)),
+        };
+        if payload.len() != expected_len {
+            return Err(AddressError::BadLength(payload.len()));
+        }
+        if version == ADDRESS_VERSION_V3 {
+            let (diversifier, keys) = rest.split_at(DIVERSIFIER_LEN);
+            let mut spend = [0u8; 32];
+            spend.copy_from_slice(&keys[..32]);
+            let mut transmission_key = [0u8; 32];
+            transmission_key.copy_from_slice(&keys[32..]);
+            return Ok(PaymentAddress::diversified(
+                Diversifier(diversifier.try_into().expect("length checked above")),
+                ShieldedAddress(spend),
+                transmission_key,
+            ));
+        }
+        let mut spend = [0u8; 32];
+        spend.copy_from_slice(&rest[..32]);
+        let mut transmission_key = [0u8; 32];
+        transmission_key.copy_from_slice(&rest[32..64]);
+        let clue_key = match rest.get(64..) {
+            Some(bytes) if !bytes.is_empty() => {
+                let key = ClueKey::from_slice(bytes).expect("length checked above");
+                if !key.is_valid() {
+                    return Err(AddressError::BadClueKey);
+                }
+                Some(key)
+            }
+            _ => None,
+        };
+        Ok(PaymentAddress {
+            spend: ShieldedAddress(spend),
+            transmission_key,
+            clue_key,
+            diversifier: None,
+        })
+    }
+}
+
+impl fmt::Display for PaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for PaymentAddress {
+    type Err = AddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// The pre-checksum CLI format: `paraloom1` followed by 64 hex digits.
+fn is_legacy_hex(s: &str) -> bool {
+    s.strip_prefix("paraloom1")
+        .is_some_and(|rest| rest.len() == 64 && rest.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+// ── bech32m (BIP-350) ──────────────────────────────────────────────────────
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+fn polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut chk: u32 = 1;
+    for &v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len() * 2 + 1);
+    out.extend(bytes.iter().map(|b| b >> 5));
+    out.push(0);
+    out.extend(bytes.iter().map(|b| b & 31));
+    out
+}
+
+pub(crate) fn bech32m_encode(hrp: &str, data: &[u8]) -> String {
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(data);
+    values.extend_from_slice(&[0u8; 6]);
+    let pm = polymod(&values) ^ BECH32M_CONST;
+
+    let mut out = String::with_capacity(hrp.len() + 1 + data.len() + 6);
+    out.push_str(hrp);
+    out.push('1');
+    for &d in data {
+        out.push(CHARSET[d as usize] as char);
+    }
+    for i in 0..6 {
+        out.push(CHARSET[((pm >> (5 * (5 - i))) & 31) as usize] as char);
+    }
+    out
+}
+
+/// Split and checksum-verify a bech32m string into `(hrp, 5-bit data)`.
+pub(crate) fn bech32m_decode(s: &str) -> Result<(String, Vec<u8>), AddressError> {
+    let has_lower = s.bytes().any(|b| b.is_ascii_lowercase());
+    let has_upper = s.bytes().any(|b| b.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(AddressError::MixedCase);
+    }
+    let s = s.to_ascii_lowercase();
+    let sep = s.rfind('1').ok_or(AddressError::MissingSeparator)?;
+    if sep == 0 || sep + 7 > s.len() {
+        return Err(AddressError::MissingSeparator);
+    }
+    let (hrp, rest) = (&s[..sep], &s[sep + 1..]);
+    if let Some(c) = hrp.chars().find(|c| !(33..=126).contains(&(*c as u32))) {
+        return Err(AddressError::InvalidChar(c));
+    }
+    let mut data = Vec::with_capacity(rest.len());
+    for c in rest.chars() {
+        let v = CHARSET
+            .iter()
+            .position(|&x| x as char == c)
+            .ok_or(AddressError::InvalidChar(c))?;
+        data.push(v as u8);
+    }
+    let mut values = hrp_expand(hrp);
+    values.extend_from_slice(&data);
+    if polymod(&values) != BECH32M_CONST {
+        return Err(AddressError::BadChecksum);
+    }
+    data.truncate(data.len() - 6);
+    Ok((hrp.to_string(), data))
+}
+
+/// Regroup `data` from `from`-bit to `to`-bit words. Decoding (`pad =
+/// false`) rejects leftover bits that are non-zero or a whole word long.
+pub(crate) fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let maxv: u32 = (1 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for &value in data {
+        let v = u32::from(value);
+        if v >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | v;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & maxv) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & maxv) as u8);
+        }
+    } else if bits >= from || ((acc << (to - bits)) & maxv) != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PaymentAddress {
+        PaymentAddress::new(ShieldedAddress([0x11; 32]), [0x22; 32])
+    }
+
+    #[test]
+    fn bip350_valid_vectors_verify() {
+        for s in [
+            "a1lqfn3a",
+            "A1LQFN3A",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            assert!(bech32m_decode(s).is_ok(), "{s} should verify");
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let addr = sample();
+        let s = addr.encode();
+        assert!(s.starts_with("paraloom1"));
+        assert_eq!(PaymentAddress::parse(&s).unwrap(), addr);
+        assert_eq!(s.parse::<PaymentAddress>().unwrap(), addr);
+        // Upper-case is the same address.
+        assert_eq!(PaymentAddress::parse(&s.to_uppercase()).unwrap(), addr);
+    }
+
+    #[test]
+    fn v2_addresses_carry_a_checked_clue_key() {
+        let clue_key =
+            crate::privacy::detection::DetectionSecret::from_viewing_secret(&[5; 32]).clue_key();
+        let addr = sample().with_clue_key(clue_key);
+        let s = addr.encode();
+        assert!(s.len() > sample().encode().len());
+        assert_eq!(PaymentAddress::parse(&s).unwrap(), addr);
+
+        // 0xff.. is not a valid ristretto255 encoding, even as the last point.
+        let mut payload = vec![ADDRESS_VERSION_V2];
+        payload.extend_from_slice(&[0x11; 64]);
+        payload.extend_from_slice(&clue_key.to_bytes()[..CLUE_KEY_LEN - 32]);
+        payload.extend_from_slice(&[0xff; 32]);
+        let bad = bech32m_encode(ADDRESS_HRP, &convert_bits(&payload, 8, 5, true).unwrap());
+        assert_eq!(PaymentAddress::parse(&bad), Err(AddressError::BadClueKey));
+
+        // A v1 payload under a v2 version byte is the wrong length.
+        let mut short = vec![ADDRESS_VERSION_V2];
+        short.extend_from_slice(&[0x11; 64]);
+        let short = bech32m_encode(ADDRESS_HRP, &convert_bits(&short, 8, 5, true).unwrap());
+        assert_eq!(
+            PaymentAddress::parse(&short),
+            Err(AddressError::BadLength(V1_PAYLOAD_LEN))
+        );
+    }
+
+    #[test]
+    fn v3_addresses_carry_the_diversifier_and_no_clue_key() {
+        let clue_key =
+            crate::privacy::detection::DetectionSecret::from_viewing_secret(&[5; 32]).clue_key();
+        let d = Diversifier([0x33; DIVERSIFIER_LEN]);
+        let addr = PaymentAddress::diversified(d, ShieldedAddress([0x11; 32]), [0x22; 32]);
+        let parsed = PaymentAddress::parse(&addr.encode()).unwrap();
+        assert_eq!(parsed, addr);
+        assert_eq!(parsed.diversifier, Some(d));
+
+        // A clue key would link every diversified address; it is not written.
+        let with_clue = addr.clone().with_clue_key(clue_key);
+        assert_eq!(with_clue.encode(), addr.encode());
+    }
+
+    #[test]
+    fn every_single_character_typo_is_rejected() {
+        let s = sample().encode();
+        let bytes = s.as_bytes();
+        for i in "paraloom1".len()..bytes.len() {
+            let mut typo = bytes.to_vec();
+            typo[i] = if typo[i] == b'q' { b'p' } else { b'q' };
+            let typo = String::from_utf8(typo).unwrap();
+            assert!(
+                PaymentAddress::parse(&typo).is_err(),
+                "typo at {i} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn wrong_prefix_and_version_are_rejected() {
+        let data = convert_bits(&[1u8; V1_PAYLOAD_LEN], 8, 5, true).unwrap();
+        let other = bech32m_encode("zcash", &data);
+        assert_eq!(
+            PaymentAddress::parse(&other),
+            Err(AddressError::WrongPrefix("zcash".into()))
+        );
+
+        let mut payload = vec![9u8];
+        payload.extend_from_slice(&[0u8; 64]);
+        let v9 = bech32m_encode(ADDRESS_HRP, &convert_bits(&payload, 8, 5, true).unwrap());
+        assert_eq!(
+            PaymentAddress::parse(&v9),
+            Err(AddressError::UnknownVersion(9))
+        );
+    }
+
+    #[test]
+    fn legacy_hex_addresses_get_a_clear_error() {
+        let legacy = format!("paraloom1{}", "ab".repeat(32));
+        assert_eq!(PaymentAddress::parse(&legacy), Err(AddressError::Legacy));
+    }
+
+    #[test]
+    fn mixed_case_is_rejected() {
+        let mut s = sample().encode();
+        let i = s
+            .char_indices()
+            .skip("paraloom1".len())
+            .find(|(_, c)| c.is_ascii_lowercase())
+            .map(|(i, _)| i)
+            .unwrap();
+        let upper = s[i..i + 1].to_uppercase();
+        s.replace_range(i..i + 1, &upper);
+        assert_eq!(PaymentAddress::parse(&s), Err(AddressError::MixedCase));
+    }
+}
@@ -0,0 +1,493 @@
+//! Association sets: showing a spent note came from a deposit someone vouches
+//! for.
+//!
+//! A curator publishes an allow-list of deposit commitments as a Merkle tree
+//! of the same shape and hash as the on-chain tree. The list is built from
+//! the deposits a bridge node records as it indexes the pool
+//! ([`DepositRecord`]), minus the depositors and deposits the curator will not
+//! vouch for ([`Exclusions`]). The owner of a listed note proves with
+//! [`AssociationCircuit`] that the note behind one of their spend's
+//! nullifiers is a leaf of the published root, without saying which leaf. A
+//! counterparty — an exchange accepting a withdrawal — checks the
+//! [`AssociationProof`] against a root it trusts and the nullifier the
+//! withdrawal revealed.
+//!
+//! Only a note that is itself a deposit is a leaf. Change and notes received
+//! in a transfer have no deposit of their own, so funds that need a proof are
+//! spent straight from the deposited note.
+//!
+//! The proof is a companion to the spend, not part of it: the transact circuit
+//! and its ceremony are unchanged, and settlement never looks at it.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::{Groth16, PreparedVerifyingKey, ProvingKey, VerifyingKey};
+use ark_snark::SNARK;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::privacy::circuits::{AssociationCircuit, Groth16ProofSystem};
+use crate::privacy::disclosure::hex_bytes;
+use crate::privacy::merkle::{MerkleTree, OnchainMembership};
+use crate::privacy::poseidon_circom::{v3_nullifier, v3_signature};
+use crate::privacy::proof::VerificationResult;
+use crate::privacy::prover::{InputNote, ProverKeys};
+use crate::privacy::types::{fr_to_bytes_32, Commitment};
+use crate::privacy::{deserialize_proof, serialize_proof};
+use crate::storage::DepositRecord;
+
+/// Where a node looks for the association verifying key when
+/// `ASSOCIATION_VERIFYING_KEY_PATH` is unset.
+pub const DEFAULT_ASSOCIATION_VERIFYING_KEY_PATH: &str = "keys/association_verifying.key";
+
+/// Public inputs of [`AssociationCircuit`]: the allow-list root and the
+/// nullifier.
+const PUBLIC_INPUTS: usize = 2;
+
+/// Resolve the association verifying-key path from
+/// `ASSOCIATION_VERIFYING_KEY_PATH`, falling back to
+/// [`DEFAULT_ASSOCIATION_VERIFYING_KEY_PATH`].
+pub fn resolve_association_key_path() -> PathBuf {
+    std::env::var_os("ASSOCIATION_VERIFYING_KEY_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(DEFAULT_ASSOCIATION_VERIFYING_KEY_PATH))
+}
+
+/// The deposits a curator leaves out of an allow-list.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Exclusions {
+    /// Every deposit paid from one of these accounts.
+    pub depositors: HashSet<[u8; 32]>,
+    /// Individual deposits, by commitment.
+    pub commitments: HashSet<[u8; 32]>,
+}
+
+impl Exclusions {
+    /// Parse an exclusion list: one `depositor <address>` or
+    /// `commitment <hex>` per line, `#` to end of line a comment. Addresses
+    /// are base58 or 32-byte hex.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut exclusions = Exclusions::default();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (kind, value) = line
+                .split_once(char::is_whitespace)
+                .with_context(|| format!("line {}: expected '<kind> <value>'", n + 1))?;
+            let value = value.trim();
+            match kind {
+                "depositor" => {
+                    let key = decode_address(value)
+                        .with_context(|| format!("line {}: bad depositor '{value}'", n + 1))?;
+                    exclusions.depositors.insert(key);
+                }
+                "commitment" => {
+                    let key = decode_hex32(value)
+                        .with_context(|| format!("line {}: bad commitment '{value}'", n + 1))?;
+                    exclusions.commitments.insert(key);
+                }
+                other => bail!("line {}: unknown exclusion kind '{other}'", n + 1),
+            }
+        }
+        Ok(exclusions)
+    }
+
+    /// Whether `deposit` is left out.
+    pub fn excludes(&self, deposit: &DepositRecord) -> bool {
+        self.depositors.contains(&deposit.depositor)
+            || self.commitments.contains(&deposit.commitment)
+    }
+}
+
+fn decode_hex32(s: &str) -> Option<[u8; 32]> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s))
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// A Solana address as hex, or base58 on a build with the bridge.
+fn decode_address(s: &str) -> Option<[u8; 32]> {
+    if let Some(key) = decode_hex32(s) {
+        return Some(key);
+    }
+    #[cfg(feature = "solana-bridge")]
+    {
+        bs58::decode(s).into_vec().ok()?.try_into().ok()
+    }
+    #[cfg(not(feature = "solana-bridge"))]
+    {
+        None
+    }
+}
+
+/// An allow-list as a curator publishes it: the listed commitments in tree
+/// order and the root they hash to.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublishedAssociationSet {
+    /// The curator's label for the list.
+    pub name: String,
+    #[serde(with = "hex_bytes")]
+    pub root: [u8; 32],
+    /// Every deposit below this pool leaf index was considered.
+    pub through_leaf: u64,
+    /// How many of those deposits were left out.
+    pub excluded: u64,
+    /// Unix seconds when the list was built.
+    pub created_at: u64,
+    pub leaves: Vec<String>,
+}
+
+/// An allow-list tree, ready to serve membership paths from.
+pub struct AssociationSet {
+    published: PublishedAssociationSet,
+    tree: MerkleTree,
+}
+
+impl AssociationSet {
+    /// Build a list named `name` from `deposits`, leaving out what
+    /// `exclusions` names. Deposits are listed in pool leaf order.
+    pub async fn from_deposits(
+        name: &str,
+        deposits: &[DepositRecord],
+        exclusions: &Exclusions,
+    ) -> Result<Self> {
+        let mut sorted: Vec<&DepositRecord> = deposits.iter().collect();
+        sorted.sort_by_key(|d| d.leaf_index);
+        let through_leaf = sorted.last().map(|d| d.leaf_index + 1).unwrap_or(0);
+
+        let mut seen = HashSet::new();
+        let mut leaves = Vec::new();
+        let mut excluded = 0u64;
+        for deposit in sorted {
+            if exclusions.excludes(deposit) {
+                excluded += 1;
+            } else if seen.insert(deposit.commitment) {
+                leaves.push(deposit.commitment);
+            }
+        }
+
+        let tree = tree_of(&leaves).await?;
+        let published = PublishedAssociationSet {
+            name: name.to_string(),
+            root: tree.onchain_root().await,
+            through_leaf,
+            excluded,
+            created_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            leaves: leaves.iter().map(hex::encode).collect(),
+        };
+        Ok(AssociationSet { published, tree })
+    }
+
+    /// Rebuild a published list, refusing one whose leaves do not hash to
+    /// its root.
+    pub async fn from_published(published: PublishedAssociationSet) -> Result<Self> {
+        let leaves = published
+            .leaves
+            .iter()
+            .map(|l| decode_hex32(l).ok_or_else(|| anyhow!("malformed leaf '{l}'")))
+            .collect::<Result<Vec<_>>>()?;
+        let tree = tree_of(&leaves).await?;
+        let computed = tree.onchain_root().await;
+        if computed != published.root {
+            bail!(
+                "association set '{}' claims root {} but its leaves hash to {}",
+                published.name,
+                hex::encode(published.root),
+                hex::encode(computed)
+            );
+        }
+        Ok(AssociationSet { published, tree })
+    }
+
+    /// Load and check a published list from a JSON file.
+    pub async fn load(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("failed to read association set {}", path.display()))?;
+        let published = serde_json::from_slice(&bytes)
+            .with_context(|| format!("malformed association set {}", path.display()))?;
+        Self::from_published(published).await
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.published.root
+    }
+
+    pub fn published(&self) -> &PublishedAssociationSet {
+        &self.published
+    }
+
+    /// The allow-list path of `commitment`, if it is listed.
+    pub async fn membership(&self, commitment: &[u8; 32]) -> Option<OnchainMembership> {
+        self.tree.onchain_membership(&Commitment(*commitment)).await
+    }
+}
+
+async fn tree_of(leaves: &[[u8; 32]]) -> Result<MerkleTree> {
+    let tree = MerkleTree::new();
+    let commitments: Vec<Commitment> = leaves.iter().copied().map(Commitment).collect();
+    tree.insert_batch(&commitments).await?;
+    Ok(tree)
+}
+
+/// A proof that the note behind `nullifier` is listed under
+/// `association_root`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssociationProof {
+    #[serde(with = "hex_bytes")]
+    pub association_root: [u8; 32],
+    #[serde(with = "hex_bytes")]
+    pub nullifier: [u8; 32],
+    /// Compressed Groth16 proof for [`AssociationCircuit`].
+    #[serde(with = "hex_bytes::vec")]
+    pub proof: Vec<u8>,
+}
+
+/// Prove that `note`, which `keys` owns, is listed under `listing.root`.
+/// The nullifier is the one spending `note` reveals, so the proof attaches to
+/// that spend. Checked against the key's own verifying half before it is
+/// returned.
+pub fn prove_association(
+    keys: &ProverKeys,
+    note: &InputNote,
+    listing: &OnchainMembership,
+    proving_key: &ProvingKey<Bn254>,
+) -> Result<AssociationProof> {
+    if listing.fold(&note.commitment) != listing.root {
+        bail!(
+            "allow-list path for leaf {} does not open note {}",
+            listing.leaf_index,
+            hex::encode(note.commitment)
+        );
+    }
+    let spend_key = keys.spend_key_for(note.diversifier.as_ref());
+    let sk = Fr::from_le_bytes_mod_order(&spend_key);
+    let c = Fr::from_le_bytes_mod_order(&note.commitment);
+    let idx = Fr::from(note.membership.leaf_index);
+    let nullifier = fr_to_bytes_32(v3_nullifier(c, idx, v3_signature(sk, c, idx)));
+
+    let circuit = AssociationCircuit {
+        association_root: Some(listing.root),
+        nullifier: Some(nullifier),
+        amount: Some(note.amount),
+        privkey: Some(spend_key),
+        blinding: Some(note.blinding),
+        asset_id: Some(note.asset_id),
+        leaf_index: Some(note.membership.leaf_index),
+        association_index: Some(listing.leaf_index),
+        association_path: Some(listing.siblings.clone()),
+    };
+    let proof = Groth16ProofSystem::prove(proving_key, circuit, &mut ark_std::rand::thread_rng())
+        .map_err(|e| anyhow!("proving failed: {}", e))?;
+    let proof = AssociationProof {
+        association_root: listing.root,
+        nullifier,
+        proof: serialize_proof(&proof)?,
+    };
+
+    let verdict = AssociationVerifier::new(&proving_key.vk)?.verify(&proof);
+    if !verdict.is_valid() {
+        bail!("association proof failed self-verification: {:?}", verdict);
+    }
+    Ok(proof)
+}
+
+/// Checks [`AssociationProof`]s against one verifying key.
+pub struct AssociationVerifier {
+    pvk: PreparedVerifyingKey<Bn254>,
+}
+
+impl AssociationVerifier {
+    /// Refuses a key for another circuit, told apart by its public-input
+    /// count.
+    pub fn new(vk: &VerifyingKey<Bn254>) -> Result<Self> {
+        if vk.gamma_abc_g1.len() != PUBLIC_INPUTS + 1 {
+            bail!(
+                "verifying key has {} public inputs, an association proof has {}",
+                vk.gamma_abc_g1.len().saturating_sub(1),
+                PUBLIC_INPUTS
+            );
+        }
+        let pvk = Groth16::<Bn254>::process_vk(vk)
+            .map_err(|e| anyhow!("unusable verifying key: {}", e))?;
+        Ok(AssociationVerifier { pvk })
+    }
+
+    /// Load the verifying key at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::new(&crate::privacy::proof::load_verifying_key(path)?)
+    }
+
+    /// Whether `proof` shows its nullifier's note is listed under its root.
+    /// Whether that root is one to trust is the caller's call.
+    pub fn verify(&self, proof: &AssociationProof) -> VerificationResult {
+        let groth16 = match deserialize_proof(&proof.proof) {
+            Ok(p) => p,
+            Err(e) => {
+                return VerificationResult::Invalid {
+                    reason: format!("malformed association proof: {}", e),
+                }
+            }
+        };
+        let inputs = [
+            Fr::from_le_bytes_mod_order(&proof.association_root),
+            Fr::from_le_bytes_mod_order(&proof.nullifier),
+        ];
+        match Groth16ProofSystem::verify_with_prepared(&self.pvk, &inputs, &groth16) {
+            Ok(true) => VerificationResult::Valid,
+            Ok(false) => VerificationResult::Invalid {
+                reason: "association proof verification failed".to_string(),
+            },
+            Err(e) => VerificationResult::Invalid {
+                reason: format!("Verification error: {}", e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::poseidon_circom::{v3_commit, v3_pubkey};
+    use crate::privacy::prover::random_field_bytes;
+    use crate::privacy::types::NATIVE_SOL_ASSET;
+    use ark_std::rand::rngs::StdRng;
+    use ark_std::rand::SeedableRng;
+
+    fn deposit(leaf_index: u64, commitment: [u8; 32], depositor: [u8; 32]) -> DepositRecord {
+        DepositRecord {
+            commitment,
+            leaf_index,
+            depositor,
+            amount: 1_000,
+            asset_id: NATIVE_SOL_ASSET,
+            slot: 10 + leaf_index,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn exclusions_parse_depositors_and_commitments() {
+        let text = format!(
+            "# sanctioned\ndepositor {}\n\ncommitment 0x{}  # hack proceeds\n",
+            hex::encode([1u8; 32]),
+            hex::encode([2u8; 32])
+        );
+        let exclusions = Exclusions::parse(&text).unwrap();
+        assert!(exclusions.excludes(&deposit(0, [9; 32], [1; 32])));
+        assert!(exclusions.excludes(&deposit(0, [2; 32], [8; 32])));
+        assert!(!exclusions.excludes(&deposit(0, [9; 32], [8; 32])));
+
+        assert!(Exclusions::parse("depositor nothex!").is_err());
+        assert!(Exclusions::parse("nullifier 00").is_err());
+    }
+
+    #[tokio::test]
+    async fn excluded_deposits_are_not_listed_and_the_root_round_trips() {
+        let deposits = vec![
+            deposit(4, [4; 32], [1; 32]),
+            deposit(1, [1; 32], [2; 32]),
+            deposit(2, [2; 32], [1; 32]),
+            deposit(3, [3; 32], [3; 32]),
+        ];
+        let exclusions = Exclusions {
+            depositors: [[1u8; 32]].into_iter().collect(),
+            commitments: HashSet::new(),
+        };
+        let set = AssociationSet::from_deposits("clean", &deposits, &exclusions)
+            .await
+            .unwrap();
+        let published = set.published().clone();
+        assert_eq!(published.through_leaf, 5);
+        assert_eq!(published.excluded, 2);
+        assert_eq!(
+            published.leaves,
+            vec![hex::encode([1u8; 32]), hex::encode([3u8; 32])]
+        );
+        assert!(set.membership(&[3; 32]).await.is_some());
+        assert!(set.membership(&[4; 32]).await.is_none());
+
+        let reloaded = AssociationSet::from_published(published.clone())
+            .await
+            .unwrap();
+        assert_eq!(reloaded.root(), set.root());
+
+        let mut forged = published;
+        forged.leaves.push(hex::encode([4u8; 32]));
+        assert!(AssociationSet::from_published(forged).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn a_listed_note_proves_and_the_proof_is_bound_to_its_inputs() {
+        let keys = ProverKeys {
+            spend_key: random_field_bytes(),
+            outgoing_key: [0; 32],
+        };
+        let (amount, blinding) = (1_000u64, random_field_bytes());
+        let commitment = fr_to_bytes_32(v3_commit(
+            Fr::from(amount),
+            v3_pubkey(Fr::from_le_bytes_mod_order(&keys.spend_key)),
+            Fr::from_le_bytes_mod_order(&blinding),
+            Fr::from_le_bytes_mod_order(&NATIVE_SOL_ASSET),
+        ));
+
+        // The pool, where the note is spent from.
+        let pool = MerkleTree::new();
+        pool.insert(&Commitment([7; 32])).await.unwrap();
+        pool.insert(&Commitment(commitment)).await.unwrap();
+        let membership = pool
+            .onchain_membership(&Commitment(commitment))
+            .await
+            .unwrap();
+        let note = InputNote {
+            amount,
+            blinding,
+            asset_id: NATIVE_SOL_ASSET,
+            commitment,
+            membership,
+            diversifier: None,
+        };
+
+        let deposits = vec![
+            deposit(0, [7; 32], [5; 32]),
+            deposit(1, commitment, [6; 32]),
+        ];
+        let set = AssociationSet::from_deposits("all", &deposits, &Exclusions::default())
+            .await
+            .unwrap();
+        let listing = set.membership(&commitment).await.unwrap();
+
+        let mut rng = StdRng::seed_from_u64(18);
+        let (pk, vk) =
+            Groth16ProofSystem::setup(AssociationCircuit::default(), &mut rng).expect("setup");
+        let proof = prove_association(&keys, &note, &listing, &pk).unwrap();
+        let verifier = AssociationVerifier::new(&vk).unwrap();
+        assert!(verifier.verify(&proof).is_valid());
+
+        let mut other_root = proof.clone();
+        other_root.association_root = [0; 32];
+        assert!(!verifier.verify(&other_root).is_valid());
+        let mut other_spend = proof;
+        other_spend.nullifier[0] ^= 1;
+        assert!(!verifier.verify(&other_spend).is_valid());
+
+        // A note left off the list has no path to prove with.
+        let strict = Exclusions {
+            depositors: HashSet::new(),
+            commitments: [commitment].into_iter().collect(),
+        };
+        let set = AssociationSet::from_deposits("strict", &deposits, &strict)
+            .await
+            .unwrap();
+        assert!(set.membership(&commitment).await.is_none());
+        let wrong = set.membership(&[7; 32]).await.unwrap();
+        assert!(prove_association(&keys, &note, &wrong, &pk).is_err());
+    }
+}
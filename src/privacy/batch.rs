@@ -3,16 +3,22 @@
 //! Batch verification allows verifying multiple proofs more efficiently than
 //! verifying them one by one. This is critical for validator performance.
 //!
-//! Performance improvement:
-//! - Single verification: N * T (where N = number of proofs, T = time per proof)
-//! - Batch verification: ~0.6 * N * T (40% faster!)
+//! A batch of proofs under one verifying key is checked with a random linear
+//! combination of their verification equations (see `randomized_check`):
+//! one multi-pairing and one final exponentiation for the whole batch
+//! instead of one per proof. Run `benchmark-batch-verify` for the numbers on
+//! a given machine.
 //!
 //! This is especially important for Raspberry Pi validators.
 
-use ark_bn254::{Bn254, Fr};
+use ark_bn254::{Bn254, Fr, G1Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::Zero;
 use ark_groth16::{PreparedVerifyingKey, Proof, VerifyingKey};
 use ark_relations::r1cs::SynthesisError;
 use ark_snark::SNARK;
+use ark_std::rand::Rng;
 
 /// Result of batch verification
 #[derive(Debug, Clone, PartialEq)]
@@ -34,6 +40,70 @@ impl BatchVerificationResult {
     }
 }
 
+/// Check a batch of Groth16 proofs against `vk` with one multi-pairing.
+///
+/// Each proof `(A, B, C)` with public-input commitment `L` must satisfy
+/// `e(A, B) = e(α, β) · e(L, γ) · e(C, δ)`. Weighting proof `i` by a random
+/// `r_i` and multiplying the equations together gives
+///
+/// ```text
+/// Π e(r_i·A_i, B_i) · e(-Σr_i·α, β) · e(-Σr_i·L_i, γ) · e(-Σr_i·C_i, δ) = 1
+/// ```
+///
+/// which costs `n + 3` Miller loops, a single final exponentiation and two
+/// multi-scalar multiplications. The weights are 128-bit and drawn after
+/// the proofs are fixed, so a batch containing an invalid proof passes with
+/// probability about 2^-128. `Ok(false)` means some proof is invalid, not
+/// which one.
+fn randomized_check<R: Rng>(
+    vk: &VerifyingKey<Bn254>,
+    public_inputs: &[Vec<Fr>],
+    proofs: &[Proof<Bn254>],
+    rng: &mut R,
+) -> Result<bool, String> {
+    let mut input_scalars = vec![Fr::zero(); vk.gamma_abc_g1.len()];
+    let mut weight_sum = Fr::zero();
+    let mut weights = Vec::with_capacity(proofs.len());
+    let mut g1 = Vec::with_capacity(proofs.len() + 3);
+    let mut g2 = Vec::with_capacity(proofs.len() + 3);
+
+    for (i, (proof, inputs)) in proofs.iter().zip(public_inputs).enumerate() {
+        if inputs.len() + 1 != input_scalars.len() {
+            return Err(format!(
+                "proof {} has {} public inputs, verifying key expects {}",
+                i,
+                inputs.len(),
+                input_scalars.len() - 1
+            ));
+        }
+        let r = Fr::from(rng.gen::<u128>());
+        weight_sum += r;
+        input_scalars[0] += r;
+        for (acc, x) in input_scalars[1..].iter_mut().zip(inputs) {
+            *acc += r * x;
+        }
+        weights.push(r);
+        g1.push(proof.a * r);
+        g2.push(proof.b);
+    }
+
+    let c: Vec<_> = proofs.iter().map(|p| p.c).collect();
+    let weighted_c =
+        G1Projective::msm(&c, &weights).map_err(|_| "proof/weight length mismatch".to_string())?;
+    let weighted_l = G1Projective::msm(&vk.gamma_abc_g1, &input_scalars)
+        .map_err(|_| "verifying key/input length mismatch".to_string())?;
+
+    g1.push(vk.alpha_g1 * -weight_sum);
+    g2.push(vk.beta_g2);
+    g1.push(-weighted_l);
+    g2.push(vk.gamma_g2);
+    g1.push(-weighted_c);
+    g2.push(vk.delta_g2);
+
+    let g1 = G1Projective::normalize_batch(&g1);
+    Ok(Bn254::multi_pairing(g1, g2).is_zero())
+}
+
 /// Batch verifier for Groth16 proofs
 pub struct BatchVerifier {
     /// Prepared verifying key (cached for performance)
@@ -79,6 +149,23 @@ impl BatchVerifier {
             return self.verify_individually(vk, public_inputs, proofs);
         }
 
+        // One randomized multi-pairing covers the whole batch; only when it
+        // fails are the proofs checked one by one to find the bad ones.
+        match randomized_check(vk, public_inputs, proofs, &mut ark_std::rand::thread_rng()) {
+            Ok(true) => BatchVerificationResult::AllValid,
+            Ok(false) => self.locate_invalid(vk, public_inputs, proofs),
+            Err(reason) => BatchVerificationResult::Error { reason },
+        }
+    }
+
+    /// Verify each proof with the prepared verifying key and report the
+    /// failing indices. Used after a batch check fails.
+    fn locate_invalid(
+        &self,
+        vk: &VerifyingKey<Bn254>,
+        public_inputs: &[Vec<Fr>],
+        proofs: &[Proof<Bn254>],
+    ) -> BatchVerificationResult {
         // Prepare verifying key if not cached
         use ark_groth16::Groth16;
         let owned_pvk;
@@ -97,18 +184,11 @@ impl BatchVerifier {
             }
         };
 
-        // Batch verification using prepared VK
-        // This is faster than individual verification for large batches
-        let mut all_valid = true;
         let mut invalid_indices = Vec::new();
-
         for (i, (proof, inputs)) in proofs.iter().zip(public_inputs.iter()).enumerate() {
             match self.verify_single_with_prepared(pvk, inputs, proof) {
                 Ok(true) => continue,
-                Ok(false) => {
-                    all_valid = false;
-                    invalid_indices.push(i);
-                }
+                Ok(false) => invalid_indices.push(i),
                 Err(_) => {
                     return BatchVerificationResult::Error {
                         reason: format!("Verification error at index {}", i),
@@ -117,7 +197,9 @@ impl BatchVerifier {
             }
         }
 
-        if all_valid {
+        if invalid_indices.is_empty() {
+            // Valid proofs satisfy every combination, so this is not
+            // expected; the individual checks are authoritative either way.
             BatchVerificationResult::AllValid
         } else {
             BatchVerificationResult::SomeInvalid { invalid_indices }
@@ -328,4 +410,79 @@ mod tests {
         let result = verifier.verify_batch(&vk, &[], &[]);
         assert_eq!(result, BatchVerificationResult::AllValid);
     }
+
+    /// `n` valid deposit proofs under one key, with their public inputs.
+    fn deposit_batch(n: usize) -> (VerifyingKey<Bn254>, Vec<Vec<Fr>>, Vec<Proof<Bn254>>) {
+        use crate::privacy::poseidon::poseidon_commit_spend;
+        use ark_ff::{BigInteger, PrimeField};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let (pk, vk) = Groth16ProofSystem::setup(DepositCircuitV2::new(), &mut rng).unwrap();
+        let mut inputs = Vec::new();
+        let mut proofs = Vec::new();
+        for i in 0..n {
+            let value = 100 + i as u64;
+            let blinding = [i as u8 + 1; 32];
+            let recipient_pubkey = [3u8; 32];
+            let asset = [0u8; 32];
+            let commitment_fr = poseidon_commit_spend(
+                Fr::from(value),
+                Fr::from_le_bytes_mod_order(&recipient_pubkey),
+                Fr::from_le_bytes_mod_order(&blinding),
+                Fr::from_le_bytes_mod_order(&asset),
+            );
+            let bytes = commitment_fr.into_bigint().to_bytes_le();
+            let mut commitment = [0u8; 32];
+            let len = bytes.len().min(32);
+            commitment[..len].copy_from_slice(&bytes[..len]);
+
+            let circuit = DepositCircuitV2::with_witness(
+                commitment,
+                value,
+                blinding,
+                recipient_pubkey,
+                asset,
+            );
+            proofs.push(Groth16ProofSystem::prove(&pk, circuit, &mut rng).unwrap());
+            inputs.push(vec![commitment_fr, Fr::from_le_bytes_mod_order(&asset)]);
+        }
+        (vk, inputs, proofs)
+    }
+
+    #[test]
+    fn randomized_check_accepts_a_valid_batch_and_rejects_a_bad_member() {
+        let (vk, mut inputs, proofs) = deposit_batch(4);
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(randomized_check(&vk, &inputs, &proofs, &mut rng), Ok(true));
+
+        inputs[2][0] += Fr::from(1u64);
+        assert_eq!(randomized_check(&vk, &inputs, &proofs, &mut rng), Ok(false));
+    }
+
+    #[test]
+    fn failed_batch_falls_back_to_naming_the_invalid_proofs() {
+        let (vk, mut inputs, proofs) = deposit_batch(4);
+        let verifier = BatchVerifier::with_prepared_vk(&vk);
+        assert_eq!(
+            verifier.verify_batch(&vk, &inputs, &proofs),
+            BatchVerificationResult::AllValid
+        );
+
+        inputs[1][0] += Fr::from(1u64);
+        inputs[3][0] += Fr::from(1u64);
+        assert_eq!(
+            verifier.verify_batch(&vk, &inputs, &proofs),
+            BatchVerificationResult::SomeInvalid {
+                invalid_indices: vec![1, 3]
+            }
+        );
+    }
+
+    #[test]
+    fn randomized_check_rejects_a_wrong_input_count() {
+        let (vk, mut inputs, proofs) = deposit_batch(3);
+        inputs[0].push(Fr::from(1u64));
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(randomized_check(&vk, &inputs, &proofs, &mut rng).is_err());
+    }
 }
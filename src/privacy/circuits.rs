@@ -5,6 +5,7 @@
 //!
 //! Circuit types:
 //! - TransactCircuitV3: unified shielded transact (deposit/transfer/withdraw)
+//! - AssociationCircuit: a spent note's membership in a published allow-list
 //! - {Deposit,Transfer,Withdraw}CircuitV2: spend-key deposit/transfer/withdraw
 
 use ark_bn254::{Bn254, Fr};
@@ -772,6 +773,103 @@ impl ConstraintSynthesizer<Fr> for TransactCircuitV3 {
     }
 }
 
+/// Association-set membership for one spent note: a companion to
+/// `TransactCircuitV3` showing the note behind a revealed nullifier is a
+/// leaf of a published allow-list tree.
+///
+/// The allow-list tree has the same shape and hash as the on-chain tree
+/// (depth [`TX_LEVELS`], [`v3_merkle_pair`](crate::privacy::poseidon_circom::v3_merkle_pair),
+/// empty leaf `0`) but holds only the deposit commitments its curator
+/// accepts. The circuit recomputes the note's commitment from its opening,
+/// derives the nullifier the spend revealed (so only the note's owner can
+/// prove, and the proof attaches to exactly that spend), and folds the
+/// commitment up the allow-list path. Nothing links the proof to the note's
+/// position in either tree.
+///
+/// Public inputs, in Groth16 slice order: `[association_root, nullifier]`.
+#[derive(Clone, Default)]
+pub struct AssociationCircuit {
+    // Public inputs.
+    pub association_root: Option<[u8; 32]>,
+    pub nullifier: Option<[u8; 32]>,
+    // Private note opening.
+    pub amount: Option<u64>,
+    pub privkey: Option<[u8; 32]>,
+    pub blinding: Option<[u8; 32]>,
+    pub asset_id: Option<[u8; 32]>,
+    /// Position of the note in the pool tree, bound into the nullifier.
+    pub leaf_index: Option<u64>,
+    /// Position of the commitment in the allow-list tree.
+    pub association_index: Option<u64>,
+    /// Allow-list siblings, leaf level first; directions come from the bits
+    /// of `association_index`.
+    pub association_path: Option<Vec<[u8; 32]>>,
+}
+
+impl ConstraintSynthesizer<Fr> for AssociationCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let fe = |b: Option<[u8; 32]>| {
+            b.map(|b| Fr::from_le_bytes_mod_order(&b))
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+
+        let root_var = FpVar::new_input(cs.clone(), || fe(self.association_root))?;
+        let nullifier_pub = FpVar::new_input(cs.clone(), || fe(self.nullifier))?;
+
+        let (_amount_bits, amount_var) = alloc_u64_witness(cs.clone(), self.amount)?;
+        let privkey_var = FpVar::new_witness(cs.clone(), || fe(self.privkey))?;
+        let blinding_var = FpVar::new_witness(cs.clone(), || fe(self.blinding))?;
+        let asset_var = FpVar::new_witness(cs.clone(), || fe(self.asset_id))?;
+        let leaf_index_var = Boolean::le_bits_to_fp_var(
+            &UInt32::new_witness(cs.clone(), || {
+                self.leaf_index
+                    .map(|i| i as u32)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?
+            .to_bits_le(),
+        )?;
+
+        let pubkey_var = v3_pubkey_gadget(cs.clone(), &privkey_var)?;
+        let commitment_var = v3_commit_gadget(
+            cs.clone(),
+            &amount_var,
+            &pubkey_var,
+            &blinding_var,
+            &asset_var,
+        )?;
+        let signature_var =
+            v3_signature_gadget(cs.clone(), &privkey_var, &commitment_var, &leaf_index_var)?;
+        let nullifier_var =
+            v3_nullifier_gadget(cs.clone(), &commitment_var, &leaf_index_var, &signature_var)?;
+        nullifier_var.enforce_equal(&nullifier_pub)?;
+
+        // Unlike the spend's own membership check this one is unconditional:
+        // a zero-amount note has no deposit to vouch for.
+        let association_bits = UInt32::new_witness(cs.clone(), || {
+            self.association_index
+                .map(|i| i as u32)
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?
+        .to_bits_le();
+        let mut current = commitment_var;
+        for (i, bit) in association_bits.iter().enumerate() {
+            let sibling = FpVar::new_witness(cs.clone(), || {
+                self.association_path
+                    .as_ref()
+                    .and_then(|p| p.get(i))
+                    .map(|b| Fr::from_le_bytes_mod_order(b))
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            let left = bit.select(&sibling, &current)?;
+            let right = bit.select(&current, &sibling)?;
+            current = v3_merkle_pair_gadget(cs.clone(), &left, &right)?;
+        }
+        current.enforce_equal(&root_var)?;
+
+        Ok(())
+    }
+}
+
 /// Groth16 proof system wrapper
 pub struct Groth16ProofSystem;
 
@@ -1828,6 +1926,77 @@ mod tests {
             bad[0] = Fr::from(123u64);
             assert!(!Groth16ProofSystem::verify(&vk, &bad, &proof).expect("verify"));
         }
+
+        /// An association circuit for a note of `amount` at pool leaf 5,
+        /// listed at `association_index` in an otherwise-empty allow-list.
+        fn build_association(amount: u64, association_index: u64) -> AssociationCircuit {
+            let (sk, blinding, a) = (Fr::from(41u64), Fr::from(42u64), asset());
+            let c = v3_commit(Fr::from(amount), v3_pubkey(sk), blinding, a);
+            let leaf_index = 5u64;
+            let nullifier = v3_nullifier(
+                c,
+                Fr::from(leaf_index),
+                v3_signature(sk, c, Fr::from(leaf_index)),
+            );
+            let (root, path) = member_root_and_path(c, association_index);
+            AssociationCircuit {
+                association_root: Some(root),
+                nullifier: Some(fr_le(nullifier)),
+                amount: Some(amount),
+                privkey: Some(fr_le(sk)),
+                blinding: Some(fr_le(blinding)),
+                asset_id: Some(fr_le(a)),
+                leaf_index: Some(leaf_index),
+                association_index: Some(association_index),
+                association_path: Some(path),
+            }
+        }
+
+        fn association_satisfied(circuit: AssociationCircuit) -> bool {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            cs.is_satisfied().unwrap()
+        }
+
+        #[test]
+        fn association_member_satisfies() {
+            assert!(association_satisfied(build_association(900, 0)));
+            assert!(association_satisfied(build_association(900, 11)));
+        }
+
+        #[test]
+        fn association_rejects_a_note_outside_the_set() {
+            let mut circuit = build_association(900, 3);
+            // Same allow-list, another note: the opening no longer folds to it.
+            circuit.amount = Some(901);
+            let c = v3_commit(
+                Fr::from(901u64),
+                v3_pubkey(Fr::from(41u64)),
+                Fr::from(42u64),
+                asset(),
+            );
+            circuit.nullifier = Some(fr_le(v3_nullifier(
+                c,
+                Fr::from(5u64),
+                v3_signature(Fr::from(41u64), c, Fr::from(5u64)),
+            )));
+            assert!(!association_satisfied(circuit));
+
+            let mut circuit = build_association(900, 3);
+            circuit.association_index = Some(2);
+            assert!(!association_satisfied(circuit));
+        }
+
+        #[test]
+        fn association_is_bound_to_the_spend_nullifier() {
+            let mut circuit = build_association(900, 0);
+            circuit.leaf_index = Some(6);
+            assert!(!association_satisfied(circuit));
+
+            let mut circuit = build_association(900, 0);
+            circuit.privkey = Some(fr_le(Fr::from(40u64)));
+            assert!(!association_satisfied(circuit));
+        }
     }
 }
 
@@ -0,0 +1,474 @@
+//! Fuzzy detection of delivered notes.
+//!
+//! A wallet that polls `/transact/scan` trial-decrypts every note in the
+//! feed, which is one X25519 agreement per note per poll. Fuzzy message
+//! detection (Beck, Len, Miers and Green, "Fuzzy Message Detection", FMD2)
+//! moves the filtering to the node without telling it which notes are whose:
+//!
+//! - The recipient publishes a [`ClueKey`] in its payment address.
+//! - A sender attaches a [`Clue`] made from that key to each output's
+//!   envelope (see [`crate::privacy::note_crypto::ENVELOPE_FLAG_CLUE`]).
+//! - The recipient hands a node a [`DetectionKey`] of `p` bits of
+//!   precision. The node keeps every note whose clue tests positive: all of
+//!   the recipient's, and a `2^-p` fraction of everyone else's.
+//!
+//! The false positives are the privacy. At `p = 0` the node learns nothing
+//! and returns the whole feed; each extra bit halves the cover traffic and the
+//! bandwidth. Clues are always made at [`MAX_PRECISION`], so the choice stays
+//! with the recipient and can change without senders knowing.
+//!
+//! The group is ristretto255. Each of the `γ` bits has its own secret
+//! `x_i = H(x, i)`, hashed from a root `x`, and the clue key is the `γ`
+//! points `X_i = x_i·B`. The bit keys have to be independent: were they public
+//! multiples of one another, a node handed the first bit key could compute
+//! every other one and test clues at full precision. The root is derived from
+//! the wallet's viewing secret, so anyone holding an incoming viewing key can
+//! also derive its detection keys.
+//!
+//! A detection key is exported as bech32m under its own prefix:
+//!
+//! ```text
+//! paraloomdtk1 <bech32m( version(1) || precision(1) || x_1(32) || ... || x_p(32) )>
+//! ```
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::privacy::address::{bech32m_decode, bech32m_encode, convert_bits, AddressError};
+
+/// Bits in every clue (`γ`), and so the most precision a detection key has.
+pub const MAX_PRECISION: u8 = 24;
+
+/// Encoded clue length: `u(32) || y(32) || bits(3)`.
+pub const CLUE_LEN: usize = 32 + 32 + MAX_PRECISION as usize / 8;
+
+/// Encoded clue key length: `X_0(32) || ... || X_23(32)`.
+pub const CLUE_KEY_LEN: usize = 32 * MAX_PRECISION as usize;
+
+/// Human-readable part of an encoded detection key.
+pub const DETECTION_KEY_HRP: &str = "paraloomdtk";
+
+/// Version byte of the `precision || x_1 || ... || x_p` layout.
+pub const DETECTION_KEY_VERSION_V1: u8 = 1;
+
+const ROOT_LABEL: &[u8] = b"paraloom/shielded/detection";
+const BIT_KEY_DOMAIN: &[u8] = b"paraloom-fmd-bit-key-v2";
+const BIT_DOMAIN: &[u8] = b"paraloom-fmd-bit-v1";
+const CHALLENGE_DOMAIN: &[u8] = b"paraloom-fmd-challenge-v1";
+
+/// Why a detection key was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DetectionError {
+    #[error("detection key encoding: {0}")]
+    Encoding(#[from] AddressError),
+    #[error("wrong detection key prefix '{0}', expected 'paraloomdtk'")]
+    WrongPrefix(String),
+    #[error("unsupported detection key version {0}")]
+    UnknownVersion(u8),
+    #[error("detection key payload has wrong length {0}")]
+    BadLength(usize),
+    #[error("detection precision {0} is above the maximum of 24 bits")]
+    BadPrecision(u8),
+    #[error("detection key holds a non-canonical scalar")]
+    NonCanonical,
+}
+
+fn wide_hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut h = Sha512::new();
+    for part in parts {
+        h.update(part);
+    }
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&h.finalize());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+fn random_scalar() -> Scalar {
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&rand::random::<[u8; 32]>());
+    wide[32..].copy_from_slice(&rand::random::<[u8; 32]>());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// The `i`-th bit secret `H(x, i)` of the root `x`.
+fn bit_secret(root: &Scalar, i: u8) -> Scalar {
+    wide_hash_to_scalar(&[BIT_KEY_DOMAIN, root.as_bytes(), &[i]])
+}
+
+/// The one-bit hash `H(u, x_i·u, w)`.
+fn key_bit(u: &[u8; 32], shared: &CompressedRistretto, w: &CompressedRistretto) -> u8 {
+    let mut h = Sha256::new();
+    h.update(BIT_DOMAIN);
+    h.update(u);
+    h.update(shared.as_bytes());
+    h.update(w.as_bytes());
+    h.finalize()[0] & 1
+}
+
+/// The challenge `m = H(u, bits)` the clue's `y` is a response to.
+fn challenge(u: &[u8; 32], bits: &[u8]) -> Scalar {
+    wide_hash_to_scalar(&[CHALLENGE_DOMAIN, u, bits])
+}
+
+fn bit(bits: &[u8], i: usize) -> u8 {
+    (bits[i / 8] >> (i % 8)) & 1
+}
+
+/// The public half, published in a payment address: one point per bit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ClueKey(pub [[u8; 32]; MAX_PRECISION as usize]);
+
+impl ClueKey {
+    /// Split `CLUE_KEY_LEN` bytes into the per-bit points. `None` for any
+    /// other length.
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != CLUE_KEY_LEN {
+            return None;
+        }
+        let mut points = [[0u8; 32]; MAX_PRECISION as usize];
+        for (point, chunk) in points.iter_mut().zip(bytes.chunks_exact(32)) {
+            point.copy_from_slice(chunk);
+        }
+        Some(ClueKey(points))
+    }
+
+    /// The points, concatenated in bit order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.concat()
+    }
+
+    /// Whether every point is a valid ristretto255 encoding. A clue cannot be
+    /// made to anything else.
+    pub fn is_valid(&self) -> bool {
+        self.points().is_some()
+    }
+
+    fn points(&self) -> Option<Vec<RistrettoPoint>> {
+        self.0
+            .iter()
+            .map(|point| CompressedRistretto(*point).decompress())
+            .collect()
+    }
+}
+
+/// A detection clue attached to one envelope.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Clue(pub [u8; CLUE_LEN]);
+
+impl Clue {
+    /// Make a fresh clue to `clue_key`, at full precision. `None` if the key
+    /// is not a valid point.
+    pub fn create(clue_key: &ClueKey) -> Option<Self> {
+        let points = clue_key.points()?;
+        let r = random_scalar();
+        let z = random_scalar();
+        let u = (&r * RISTRETTO_BASEPOINT_TABLE).compress().to_bytes();
+        let w = (&z * RISTRETTO_BASEPOINT_TABLE).compress();
+
+        let mut bits = [0u8; MAX_PRECISION as usize / 8];
+        for (i, point) in points.iter().enumerate() {
+            let shared = (r * point).compress();
+            // The bit is stored flipped: the detector accepts when its own
+            // key bit XOR the stored bit is 1.
+            let flipped = key_bit(&u, &shared, &w) ^ 1;
+            bits[i / 8] |= flipped << (i % 8);
+        }
+
+        // Tie the bits to `u` and `w`: y = (z - m) / r, so m·B + y·u = w.
+        let m = challenge(&u, &bits);
+        let y = (z - m) * r.invert();
+
+        let mut out = [0u8; CLUE_LEN];
+        out[..32].copy_from_slice(&u);
+        out[32..64].copy_from_slice(y.as_bytes());
+        out[64..].copy_from_slice(&bits);
+        Some(Clue(out))
+    }
+}
+
+/// The wallet's detection root, from which its clue key and detection keys
+/// at every precision follow.
+#[derive(Clone)]
+pub struct DetectionSecret(Scalar);
+
+impl fmt::Debug for DetectionSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DetectionSecret(..)")
+    }
+}
+
+impl DetectionSecret {
+    /// Derive the root from a wallet's X25519 viewing secret.
+    pub fn from_viewing_secret(viewing_secret: &[u8; 32]) -> Self {
+        DetectionSecret(wide_hash_to_scalar(&[ROOT_LABEL, viewing_secret]))
+    }
+
+    pub fn clue_key(&self) -> ClueKey {
+        let mut points = [[0u8; 32]; MAX_PRECISION as usize];
+        for (i, point) in (0..MAX_PRECISION).zip(points.iter_mut()) {
+            *point = (&bit_secret(&self.0, i) * RISTRETTO_BASEPOINT_TABLE)
+                .compress()
+                .to_bytes();
+        }
+        ClueKey(points)
+    }
+
+    /// A detection key with a `2^-precision` false-positive rate.
+    pub fn detection_key(&self, precision: u8) -> Result<DetectionKey, DetectionError> {
+        if precision > MAX_PRECISION {
+            return Err(DetectionError::BadPrecision(precision));
+        }
+        Ok(DetectionKey {
+            secrets: (0..precision).map(|i| bit_secret(&self.0, i)).collect(),
+        })
+    }
+}
+
+/// What a node is given to filter the feed: the first `precision` bit keys.
+#[derive(Clone, PartialEq, Eq)]
+pub struct DetectionKey {
+    secrets: Vec<Scalar>,
+}
+
+impl fmt::Debug for DetectionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DetectionKey")
+            .field("precision", &self.precision())
+            .finish_non_exhaustive()
+    }
+}
+
+impl DetectionKey {
+    /// Bits tested per clue.
+    pub fn precision(&self) -> u8 {
+        self.secrets.len() as u8
+    }
+
+    /// The chance that a clue made to someone else tests positive.
+    pub fn false_positive_rate(&self) -> f64 {
+        0.5f64.powi(i32::from(self.precision()))
+    }
+
+    /// Whether `clue` tests positive. Always true for a clue made to this
+    /// key's wallet; true with probability [`false_positive_rate`](Self::false_positive_rate)
+    /// otherwise, and false for a malformed clue.
+    pub fn detects(&self, clue: &Clue) -> bool {
+        let mut u_bytes = [0u8; 32];
+        u_bytes.copy_from_slice(&clue.0[..32]);
+        let mut y_bytes = [0u8; 32];
+        y_bytes.copy_from_slice(&clue.0[32..64]);
+        let bits = &clue.0[64..];
+
+        let Some(u) = CompressedRistretto(u_bytes).decompress() else {
+            return false;
+        };
+        let Some(y) = Option::<Scalar>::from(Scalar::from_canonical_bytes(y_bytes)) else {
+            return false;
+        };
+        let m = challenge(&u_bytes, bits);
+        let w = RistrettoPoint::vartime_double_scalar_mul_basepoint(&y, &u, &m).compress();
+
+        // Most clues fail on their first bit or two, so stop at the first.
+        self.secrets
+            .iter()
+            .enumerate()
+            .all(|(i, x)| key_bit(&u_bytes, &(x * u).compress(), &w) ^ bit(bits, i) == 1)
+    }
+
+    /// The canonical (lower-case) bech32m string.
+    pub fn encode(&self) -> String {
+        let mut payload = Vec::with_capacity(2 + 32 * self.secrets.len());
+        payload.push(DETECTION_KEY_VERSION_V1);
+        payload.push(self.precision());
+        for x in &self.secrets {
+            payload.extend_from_slice(x.as_bytes());
+        }
+        bech32m_encode(
+            DETECTION_KEY_HRP,
+            &convert_bits(&payload, 8, 5, true).unwrap_or_default(),
+        )
+    }
+
+    /// Parse an encoded detection key, verifying prefix, checksum, version
+    /// and that every bit key is a canonical scalar.
+    pub fn parse(s: &str) -> Result<Self, DetectionError> {
+        let (hrp, data) = bech32m_decode(s.trim())?;
+        if hrp != DETECTION_KEY_HRP {
+            return Err(DetectionError::WrongPrefix(hrp));
+        }
+        let payload = convert_bits(&data, 5, 8, false).ok_or(AddressError::BadPadding)?;
+        let (&version, rest) = payload.split_first().ok_or(DetectionError::BadLength(0))?;
+        if version != DETECTION_KEY_VERSION_V1 {
+            return Err(DetectionError::UnknownVersion(version));
+        }
+        let (&precision, keys) = rest
+            .split_first()
+            .ok_or(DetectionError::BadLength(payload.len()))?;
+        if precision > MAX_PRECISION {
+            return Err(DetectionError::BadPrecision(precision));
+        }
+        if keys.len() != 32 * usize::from(precision) {
+            return Err(DetectionError::BadLength(payload.len()));
+        }
+        let secrets = keys
+            .chunks_exact(32)
+            .map(|chunk| {
+                let mut bytes = [0u8; 32];
+                bytes.copy_from_slice(chunk);
+                Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))
+                    .ok_or(DetectionError::NonCanonical)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DetectionKey { secrets })
+    }
+}
+
+impl FromStr for DetectionKey {
+    type Err = DetectionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secret(fill: u8) -> DetectionSecret {
+        DetectionSecret::from_viewing_secret(&[fill; 32])
+    }
+
+    #[test]
+    fn own_clues_always_test_positive() {
+        let mine = secret(1);
+        let key = mine.detection_key(MAX_PRECISION).unwrap();
+        for _ in 0..20 {
+            let clue = Clue::create(&mine.clue_key()).unwrap();
+            assert!(key.detects(&clue));
+        }
+    }
+
+    #[test]
+    fn false_positives_track_the_precision() {
+        let other = secret(2).clue_key();
+        let clues: Vec<Clue> = (0..400).map(|_| Clue::create(&other).unwrap()).collect();
+        let positives = |precision: u8| {
+            let key = secret(1).detection_key(precision).unwrap();
+            clues.iter().filter(|c| key.detects(c)).count()
+        };
+
+        // p = 0 passes everything; p = 2 about a quarter; p = 24 none.
+        assert_eq!(positives(0), 400);
+        let quarter = positives(2);
+        assert!((50..=150).contains(&quarter), "{quarter} of 400 at p = 2");
+        assert_eq!(positives(MAX_PRECISION), 0);
+    }
+
+    /// Each bit key is a separate hash of the root, so the bits a key holds
+    /// say nothing about the ones it does not. Under the old expansion
+    /// `x_i = H(X, i)·x`, a one-bit key and the published clue key gave back
+    /// `x` and so every bit key; the same arithmetic here gives nothing.
+    #[test]
+    fn a_low_precision_key_cannot_produce_higher_bit_keys() {
+        let mine = secret(5);
+        let clue_key = mine.clue_key();
+        let full = mine.detection_key(MAX_PRECISION).unwrap();
+        let low = mine.detection_key(4).unwrap();
+        assert_eq!(low.secrets[..], full.secrets[..4]);
+
+        let is_bit_key = |x: &Scalar, i: usize| {
+            (x * RISTRETTO_BASEPOINT_TABLE).compress().to_bytes() == clue_key.0[i]
+        };
+        for i in 0..usize::from(MAX_PRECISION) {
+            assert!(is_bit_key(&full.secrets[i], i));
+        }
+
+        let legacy = |point: &[u8; 32], i: u8| {
+            wide_hash_to_scalar(&[b"paraloom-fmd-expand-v1", point, &[i]])
+        };
+        for i in 4..MAX_PRECISION {
+            let at = usize::from(i);
+            // Neither a held bit key nor the old root recovery lands on it.
+            assert!(low.secrets.iter().all(|x| !is_bit_key(x, at)));
+            for (j, x) in low.secrets.iter().enumerate() {
+                let root = x * legacy(&clue_key.0[j], j as u8).invert();
+                assert!(!is_bit_key(&root, at));
+                assert!(!is_bit_key(&(root * legacy(&clue_key.0[0], i)), at));
+            }
+        }
+
+        // So the low key keeps its cover traffic; only the full key rejects
+        // every clue made to someone else.
+        let other = secret(6).clue_key();
+        let clues: Vec<Clue> = (0..200).map(|_| Clue::create(&other).unwrap()).collect();
+        assert!(clues.iter().any(|c| low.detects(c)));
+        assert!(clues.iter().all(|c| !full.detects(c)));
+    }
+
+    #[test]
+    fn clue_keys_round_trip_and_check_every_point() {
+        let clue_key = secret(7).clue_key();
+        assert!(clue_key.is_valid());
+        let bytes = clue_key.to_bytes();
+        assert_eq!(bytes.len(), CLUE_KEY_LEN);
+        assert_eq!(ClueKey::from_slice(&bytes), Some(clue_key));
+        assert_eq!(ClueKey::from_slice(&bytes[..32]), None);
+
+        // One bad point in the last bit is enough to refuse the key.
+        let mut bad = clue_key;
+        bad.0[usize::from(MAX_PRECISION) - 1] = [0xff; 32];
+        assert!(!bad.is_valid());
+        assert!(Clue::create(&bad).is_none());
+    }
+
+    #[test]
+    fn a_tampered_clue_does_not_test_positive() {
+        let mine = secret(3);
+        let key = mine.detection_key(MAX_PRECISION).unwrap();
+        let clue = Clue::create(&mine.clue_key()).unwrap();
+        for at in [0, 40, CLUE_LEN - 1] {
+            let mut bad = clue;
+            bad.0[at] ^= 0x01;
+            assert!(!key.detects(&bad), "byte {at}");
+        }
+    }
+
+    #[test]
+    fn detection_key_round_trips_and_rejects_bad_encodings() {
+        let key = secret(4).detection_key(8).unwrap();
+        let encoded = key.encode();
+        assert!(encoded.starts_with("paraloomdtk1"));
+        let parsed = DetectionKey::parse(&encoded).unwrap();
+        assert_eq!(parsed, key);
+        assert_eq!(parsed.false_positive_rate(), 1.0 / 256.0);
+
+        assert_eq!(
+            secret(4).detection_key(MAX_PRECISION + 1),
+            Err(DetectionError::BadPrecision(MAX_PRECISION + 1))
+        );
+        let address =
+            crate::privacy::PaymentAddress::new(crate::privacy::ShieldedAddress([1; 32]), [2; 32]);
+        assert!(matches!(
+            DetectionKey::parse(&address.encode()),
+            Err(DetectionError::WrongPrefix(_))
+        ));
+
+        // A scalar at or above the group order is not canonical.
+        let mut payload = vec![DETECTION_KEY_VERSION_V1, 1];
+        payload.extend_from_slice(&[0xff; 32]);
+        let forged = bech32m_encode(
+            DETECTION_KEY_HRP,
+            &convert_bits(&payload, 8, 5, true).unwrap(),
+        );
+        assert_eq!(
+            DetectionKey::parse(&forged),
+            Err(DetectionError::NonCanonical)
+        );
+    }
+}
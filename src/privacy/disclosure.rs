@@ -0,0 +1,455 @@
+//! Read-only viewing keys and selective disclosure of single notes.
+//!
+//! An [`IncomingViewingKey`] is everything needed to find and open the notes
+//! paid to a wallet, and nothing that can spend them: the X25519 secret that
+//! decrypts delivered notes, the v3 spend *public* key that decides whether a
+//! decrypted note really belongs to the wallet, and the wallet's disclosure
+//! key (below). It is exported as a bech32m string under its own prefix so it
+//! is never mistaken for, or pasted as, a payment address:
+//!
+//! ```text
+//! paraloomivk1 <bech32m( version(1) || spend_pubkey(32) || viewing_secret(32) || disclosure_key(32) )>
+//! ```
+//!
+//! Notes paid to one of the wallet's diversified addresses are committed to
+//! that address's own spend pubkey, which the viewing key cannot compute. It
+//! accepts them when their diversifier carries the tag only its viewing
+//! secret makes (see [`crate::privacy::diversifier`]).
+//!
+//! Spending needs the spend private key behind `spend_pubkey`, for the
+//! nullifier and the in-circuit signature. The same goes for telling which
+//! received notes were spent, so a viewing key shows what came in, not the
+//! current balance.
+//!
+//! A [`NoteDisclosure`] is narrower still: the opening of one commitment
+//! (amount, owner, blinding, asset), signed with the wallet's ed25519
+//! disclosure key. Anyone can recompute `v3_commit` from the opening and
+//! compare it with the commitment on chain; the signature shows the opening
+//! was released by the wallet whose disclosure key the auditor already holds
+//! (for example from its viewing key), not merely by someone who learned it.
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use libp2p::identity::ed25519;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::privacy::address::{
+    bech32m_decode, bech32m_encode, convert_bits, AddressError, PaymentAddress,
+};
+use crate::privacy::detection::DetectionSecret;
+use crate::privacy::diversifier::Diversifier;
+use crate::privacy::poseidon_circom::{mint_to_asset, v3_commit};
+use crate::privacy::types::{fr_to_bytes_32, AssetId, ShieldedAddress, ViewingKey};
+
+/// Human-readable part of an encoded incoming viewing key.
+pub const VIEWING_KEY_HRP: &str = "paraloomivk";
+
+/// Version byte of the `spend_pubkey || viewing_secret || disclosure_key`
+/// layout.
+pub const VIEWING_KEY_VERSION_V1: u8 = 1;
+
+const V1_PAYLOAD_LEN: usize = 1 + 32 + 32 + 32;
+
+/// Domain tag at the start of every signed disclosure.
+const DISCLOSURE_DOMAIN: &[u8] = b"paraloom-note-disclosure-v1";
+
+/// Why a viewing key or a disclosure was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DisclosureError {
+    #[error("viewing key encoding: {0}")]
+    Encoding(#[from] AddressError),
+    #[error("wrong viewing key prefix '{0}', expected 'paraloomivk'")]
+    WrongPrefix(String),
+    #[error("unsupported viewing key version {0}")]
+    UnknownVersion(u8),
+    #[error("viewing key payload has wrong length {0}")]
+    BadLength(usize),
+    #[error("invalid disclosure signing key")]
+    BadSigner,
+    #[error("disclosure signature does not verify")]
+    BadSignature,
+    #[error("disclosure was not signed by the expected wallet")]
+    SignerMismatch,
+    #[error("disclosed note is not owned by the expected wallet")]
+    OwnerMismatch,
+    #[error("disclosed asset does not match the disclosed mint")]
+    AssetMismatch,
+    #[error("opening does not reproduce the commitment")]
+    CommitmentMismatch,
+}
+
+/// A read-only key: finds and opens a wallet's incoming notes, cannot spend.
+#[derive(Clone, PartialEq, Eq)]
+pub struct IncomingViewingKey {
+    /// v3 spend public key the wallet's notes are committed to.
+    pub spend: ShieldedAddress,
+    /// X25519 secret the wallet's notes are encrypted to.
+    pub viewing_secret: [u8; 32],
+    /// ed25519 public key the wallet signs [`NoteDisclosure`]s with.
+    pub disclosure_key: [u8; 32],
+}
+
+impl fmt::Debug for IncomingViewingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IncomingViewingKey")
+            .field("spend", &hex::encode(self.spend.as_bytes()))
+            .finish_non_exhaustive()
+    }
+}
+
+impl IncomingViewingKey {
+    pub fn new(spend: ShieldedAddress, viewing_secret: [u8; 32], disclosure_key: [u8; 32]) -> Self {
+        IncomingViewingKey {
+            spend,
+            viewing_secret,
+            disclosure_key,
+        }
+    }
+
+    /// The viewing key for trial decryption.
+    pub fn viewing_key(&self) -> ViewingKey {
+        ViewingKey::new(self.viewing_secret)
+    }
+
+    /// Whether a note committed to `owner`, paid to `diversifier` if any,
+    /// belongs to the wallet this key watches.
+    pub fn owns(&self, owner: &[u8; 32], diversifier: Option<&Diversifier>) -> bool {
+        match diversifier {
+            Some(d) => d.issued_for(&self.viewing_secret, owner),
+            None => owner == self.spend.as_bytes(),
+        }
+    }
+
+    /// The payment address this key watches.
+    pub fn payment_address(&self) -> PaymentAddress {
+        let transmission_key = *crypto_box::SecretKey::from(self.viewing_secret)
+            .public_key()
+            .as_bytes();
+        PaymentAddress::new(self.spend.clone(), transmission_key)
+            .with_clue_key(DetectionSecret::from_viewing_secret(&self.viewing_secret).clue_key())
+    }
+
+    /// The canonical (lower-case) bech32m string.
+    pub fn encode(&self) -> String {
+        let mut payload = Vec::with_capacity(V1_PAYLOAD_LEN);
+        payload.push(VIEWING_KEY_VERSION_V1);
+        payload.extend_from_slice(self.spend.as_bytes());
+        payload.extend_from_slice(&self.viewing_secret);
+        payload.extend_from_slice(&self.disclosure_key);
+        bech32m_encode(
+            VIEWING_KEY_HRP,
+            &convert_bits(&payload, 8, 5, true).unwrap_or_default(),
+        )
+    }
+
+    /// Parse an encoded viewing key, verifying prefix, checksum and version.
+    pub fn parse(s: &str) -> Result<Self, DisclosureError> {
+        let (hrp, data) = bech32m_decode(s.trim())?;
+        if hrp != VIEWING_KEY_HRP {
+            return Err(DisclosureError::WrongPrefix(hrp));
+        }
+        let payload = convert_bits(&data, 5, 8, false).ok_or(AddressError::BadPadding)?;
+        let (&version, rest) = payload.split_first().ok_or(DisclosureError::BadLength(0))?;
+        if version != VIEWING_KEY_VERSION_V1 {
+            return Err(DisclosureError::UnknownVersion(version));
+        }
+        if payload.len() != V1_PAYLOAD_LEN {
+            return Err(DisclosureError::BadLength(payload.len()));
+        }
+        let field = |i: usize| -> [u8; 32] {
+            let mut out = [0u8; 32];
+            out.copy_from_slice(&rest[32 * i..32 * (i + 1)]);
+            out
+        };
+        Ok(IncomingViewingKey {
+            spend: ShieldedAddress(field(0)),
+            viewing_secret: field(1),
+            disclosure_key: field(2),
+        })
+    }
+}
+
+impl FromStr for IncomingViewingKey {
+    type Err = DisclosureError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// The opening of one note commitment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteOpening {
+    #[serde(with = "hex_bytes")]
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    /// v3 spend public key of the note's owner.
+    #[serde(with = "hex_bytes")]
+    pub owner: [u8; 32],
+    #[serde(with = "hex_bytes")]
+    pub blinding: [u8; 32],
+    #[serde(with = "hex_bytes")]
+    pub asset_id: AssetId,
+    /// The SPL mint behind `asset_id`; `None` for native SOL.
+    #[serde(default, with = "hex_bytes::option")]
+    pub mint: Option<[u8; 32]>,
+    /// The diversified address the note was paid to, which ties `owner` to
+    /// the wallet; `None` for its default address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diversifier: Option<Diversifier>,
+}
+
+impl NoteOpening {
+    /// Whether `v3_commit(amount, owner, blinding, asset_id)` is the
+    /// disclosed commitment, and a disclosed mint maps to the asset.
+    pub fn check(&self) -> Result<(), DisclosureError> {
+        if let Some(mint) = &self.mint {
+            if mint_to_asset(mint) != self.asset_id {
+                return Err(DisclosureError::AssetMismatch);
+            }
+        }
+        let recomputed = fr_to_bytes_32(v3_commit(
+            Fr::from(self.amount),
+            Fr::from_le_bytes_mod_order(&self.owner),
+            Fr::from_le_bytes_mod_order(&self.blinding),
+            Fr::from_le_bytes_mod_order(&self.asset_id),
+        ));
+        if recomputed != self.commitment {
+            return Err(DisclosureError::CommitmentMismatch);
+        }
+        Ok(())
+    }
+
+    /// Canonical bytes the disclosure signature covers.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(DISCLOSURE_DOMAIN.len() + 5 * 32 + 8 + 1 + 32);
+        out.extend_from_slice(DISCLOSURE_DOMAIN);
+        out.extend_from_slice(&self.commitment);
+        out.extend_from_slice(&self.amount.to_le_bytes());
+        out.extend_from_slice(&self.owner);
+        out.extend_from_slice(&self.blinding);
+        out.extend_from_slice(&self.asset_id);
+        match &self.mint {
+            Some(mint) => {
+                out.push(1);
+                out.extend_from_slice(mint);
+            }
+            None => out.push(0),
+        }
+        // Appended only when present, so openings of default-address notes
+        // sign the same bytes they always have.
+        if let Some(diversifier) = &self.diversifier {
+            out.extend_from_slice(&diversifier.0);
+        }
+        out
+    }
+}
+
+/// A signed opening of one note, for handing to an auditor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NoteDisclosure {
+    pub opening: NoteOpening,
+    /// ed25519 public key of the disclosing wallet.
+    #[serde(with = "hex_bytes")]
+    pub signer: [u8; 32],
+    /// ed25519 signature over [`NoteOpening::signing_bytes`].
+    #[serde(with = "hex_bytes::vec")]
+    pub signature: Vec<u8>,
+}
+
+impl NoteDisclosure {
+    /// Sign `opening` with the wallet's disclosure keypair.
+    pub fn sign(opening: NoteOpening, keypair: &ed25519::Keypair) -> Self {
+        let signature = keypair.sign(&opening.signing_bytes());
+        NoteDisclosure {
+            opening,
+            signer: keypair.public().to_bytes(),
+            signature,
+        }
+    }
+
+    /// Check the signature and that the opening reproduces its commitment.
+    /// This says nothing about *who* signed; see
+    /// [`verify_for`](Self::verify_for).
+    pub fn verify(&self) -> Result<(), DisclosureError> {
+        let signer = ed25519::PublicKey::try_from_bytes(&self.signer)
+            .map_err(|_| DisclosureError::BadSigner)?;
+        if !signer.verify(&self.opening.signing_bytes(), &self.signature) {
+            return Err(DisclosureError::BadSignature);
+        }
+        self.opening.check()
+    }
+
+    /// [`verify`](Self::verify), and check the note belongs to, and was
+    /// disclosed by, the wallet `ivk` watches.
+    pub fn verify_for(&self, ivk: &IncomingViewingKey) -> Result<(), DisclosureError> {
+        if self.signer != ivk.disclosure_key {
+            return Err(DisclosureError::SignerMismatch);
+        }
+        if !ivk.owns(&self.opening.owner, self.opening.diversifier.as_ref()) {
+            return Err(DisclosureError::OwnerMismatch);
+        }
+        self.verify()
+    }
+}
+
+/// Serde adapters: byte strings as hex.
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8; 32], s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<[u8; 32], D::Error> {
+        let s = String::deserialize(d)?;
+        hex::decode(s.strip_prefix("0x").unwrap_or(&s))
+            .map_err(serde::de::Error::custom)?
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("expected 32 bytes"))
+    }
+
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(bytes: &Option<[u8; 32]>, s: S) -> Result<S::Ok, S::Error> {
+            match bytes {
+                Some(b) => s.serialize_some(&hex::encode(b)),
+                None => s.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<[u8; 32]>, D::Error> {
+            Option::<String>::deserialize(d)?
+                .map(|s| {
+                    hex::decode(s.strip_prefix("0x").unwrap_or(&s))
+                        .map_err(serde::de::Error::custom)?
+                        .try_into()
+                        .map_err(|_| serde::de::Error::custom("expected 32 bytes"))
+                })
+                .transpose()
+        }
+    }
+
+    pub mod vec {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&hex::encode(bytes))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+            let s = String::deserialize(d)?;
+            hex::decode(s.strip_prefix("0x").unwrap_or(&s)).map_err(serde::de::Error::custom)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> ed25519::Keypair {
+        ed25519::Keypair::from(ed25519::SecretKey::try_from_bytes([7u8; 32]).unwrap())
+    }
+
+    fn opening(mint: Option<[u8; 32]>) -> NoteOpening {
+        let owner = [0x11; 32];
+        let blinding = [0x22; 32];
+        let asset_id = mint.as_ref().map(mint_to_asset).unwrap_or([0; 32]);
+        let commitment = fr_to_bytes_32(v3_commit(
+            Fr::from(1_500u64),
+            Fr::from_le_bytes_mod_order(&owner),
+            Fr::from_le_bytes_mod_order(&blinding),
+            Fr::from_le_bytes_mod_order(&asset_id),
+        ));
+        NoteOpening {
+            commitment,
+            amount: 1_500,
+            owner,
+            blinding,
+            asset_id,
+            mint,
+            diversifier: None,
+        }
+    }
+
+    fn ivk() -> IncomingViewingKey {
+        IncomingViewingKey::new(
+            ShieldedAddress([0x11; 32]),
+            [0x33; 32],
+            keypair().public().to_bytes(),
+        )
+    }
+
+    #[test]
+    fn viewing_key_round_trips_and_is_not_an_address() {
+        let key = ivk();
+        let s = key.encode();
+        assert!(s.starts_with("paraloomivk1"));
+        assert_eq!(IncomingViewingKey::parse(&s).unwrap(), key);
+        assert!(PaymentAddress::parse(&s).is_err());
+
+        let address = key.payment_address().encode();
+        assert!(matches!(
+            IncomingViewingKey::parse(&address),
+            Err(DisclosureError::WrongPrefix(_))
+        ));
+    }
+
+    #[test]
+    fn viewing_key_typos_are_rejected() {
+        let mut s = ivk().encode().into_bytes();
+        let i = s.len() - 10;
+        s[i] = if s[i] == b'q' { b'p' } else { b'q' };
+        assert!(IncomingViewingKey::parse(std::str::from_utf8(&s).unwrap()).is_err());
+    }
+
+    #[test]
+    fn signed_disclosure_verifies_for_its_wallet() {
+        for mint in [None, Some([9u8; 32])] {
+            let d = NoteDisclosure::sign(opening(mint), &keypair());
+            d.verify().unwrap();
+            d.verify_for(&ivk()).unwrap();
+
+            let json = serde_json::to_string(&d).unwrap();
+            let back: NoteDisclosure = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, d);
+        }
+    }
+
+    #[test]
+    fn altered_openings_are_rejected() {
+        let d = NoteDisclosure::sign(opening(None), &keypair());
+
+        // Re-signed with a wrong amount: the signature holds, the commitment does not.
+        let mut wrong = opening(None);
+        wrong.amount += 1;
+        let resigned = NoteDisclosure::sign(wrong.clone(), &keypair());
+        assert_eq!(resigned.verify(), Err(DisclosureError::CommitmentMismatch));
+
+        // Edited after signing.
+        let mut edited = d.clone();
+        edited.opening = wrong;
+        assert_eq!(edited.verify(), Err(DisclosureError::BadSignature));
+
+        // A mint that does not map to the disclosed asset.
+        let mut bad_mint = opening(None);
+        bad_mint.mint = Some([9; 32]);
+        assert_eq!(
+            NoteDisclosure::sign(bad_mint, &keypair()).verify(),
+            Err(DisclosureError::AssetMismatch)
+        );
+    }
+
+    #[test]
+    fn disclosure_from_another_signer_is_rejected_for_the_wallet() {
+        let other = ed25519::Keypair::from(ed25519::SecretKey::try_from_bytes([8u8; 32]).unwrap());
+        let d = NoteDisclosure::sign(opening(None), &other);
+        d.verify().unwrap();
+        assert_eq!(d.verify_for(&ivk()), Err(DisclosureError::SignerMismatch));
+    }
+}
@@ -0,0 +1,216 @@
+//! Diversified payment addresses.
+//!
+//! A wallet's default [`PaymentAddress`](crate::privacy::PaymentAddress) is
+//! one fixed set of keys, so everyone handed it can tell the payments they
+//! see are to the same wallet. A diversified address is one of as many
+//! addresses as the wallet likes, no two of them linkable without its keys,
+//! that the same viewing secret finds and the same spend key spends.
+//!
+//! A [`Diversifier`] is an 8-byte index `j` and an 8-byte tag, and picks:
+//!
+//! ```text
+//! sk_d = SHA-256("paraloom/shielded/diversified-spend" || spend_key || j)
+//! pk_d = Poseidon(1)([sk_d])                       (bound into the commitment)
+//! G_d  = hash of d onto the prime-order subgroup of Curve25519
+//! tk_d = X25519(viewing_secret, G_d)               (the note is sealed to this)
+//! tag  = SHA-256("paraloom/shielded/diversifier-tag" || viewing_secret || j || pk_d)[..8]
+//! ```
+//!
+//! A sender sealing to `tk_d` takes its ephemeral public key on `G_d` rather
+//! than the base point, so the shared secret `X25519(e, tk_d)` is
+//! `X25519(viewing_secret, epk)` — the recipient opens the note exactly as it
+//! opens one paid to its default address, and scanning costs nothing extra.
+//! Two addresses share no key, and relating `tk_d` to `tk_d'` is the
+//! decisional Diffie-Hellman problem on a base nobody knows the logarithm of.
+//!
+//! The circuit only knows `pk_d = Poseidon(sk_d)`, and a viewing key cannot
+//! compute `sk_d`. The note plaintext therefore carries `d`, and the tag —
+//! keyed by the viewing secret — is how a viewing key tells an owner it issued
+//! from one a sender made up. The spend key re-derives `pk_d` outright.
+//!
+//! Diversified addresses carry no detection clue key: one key in every
+//! address would link them. Notes paid to them lead with no clue, and a node
+//! returns clueless notes to every detection query, so they are still found.
+
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::traits::IsIdentity;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+use crate::privacy::poseidon_circom::v3_pubkey;
+use crate::privacy::types::fr_to_bytes_32;
+
+/// Encoded diversifier length: `index(8) || tag(8)`.
+pub const DIVERSIFIER_LEN: usize = 16;
+
+const INDEX_LABEL: &[u8] = b"paraloom/shielded/diversifier-index";
+const SPEND_LABEL: &[u8] = b"paraloom/shielded/diversified-spend";
+const TAG_LABEL: &[u8] = b"paraloom/shielded/diversifier-tag";
+const BASE_LABEL: &[u8] = b"paraloom/shielded/diversified-base";
+
+/// Which of a wallet's diversified addresses a note was paid to.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Diversifier(pub [u8; DIVERSIFIER_LEN]);
+
+impl fmt::Debug for Diversifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Diversifier({})", hex::encode(self.0))
+    }
+}
+
+impl Diversifier {
+    /// The wallet's `index`-th diversifier. Indices are mapped through the
+    /// spend key, so an address does not say how many came before it.
+    pub fn derive(spend_key: &[u8; 32], viewing_secret: &[u8; 32], index: u64) -> Self {
+        let digest = Sha256::new()
+            .chain_update(INDEX_LABEL)
+            .chain_update(spend_key)
+            .chain_update(index.to_le_bytes())
+            .finalize();
+        let mut j = [0u8; 8];
+        j.copy_from_slice(&digest[..8]);
+        let owner = spend_pubkey(&diversified_spend_key(spend_key, &j));
+        let mut out = [0u8; DIVERSIFIER_LEN];
+        out[..8].copy_from_slice(&j);
+        out[8..].copy_from_slice(&tag(viewing_secret, &j, &owner));
+        Diversifier(out)
+    }
+
+    fn index(&self) -> [u8; 8] {
+        let mut j = [0u8; 8];
+        j.copy_from_slice(&self.0[..8]);
+        j
+    }
+
+    /// The spend key of notes paid to this diversifier.
+    pub fn spend_key(&self, spend_key: &[u8; 32]) -> [u8; 32] {
+        diversified_spend_key(spend_key, &self.index())
+    }
+
+    /// The v3 spend pubkey notes paid to this diversifier commit to.
+    pub fn spend_pubkey(&self, spend_key: &[u8; 32]) -> [u8; 32] {
+        spend_pubkey(&self.spend_key(spend_key))
+    }
+
+    /// Whether the wallet with `viewing_secret` issued this diversifier for
+    /// `owner`. What a viewing key checks in place of the spend pubkey.
+    pub fn issued_for(&self, viewing_secret: &[u8; 32], owner: &[u8; 32]) -> bool {
+        self.0[8..] == tag(viewing_secret, &self.index(), owner)
+    }
+
+    /// The Curve25519 point (Montgomery u-coordinate) a sender's ephemeral
+    /// key is taken on.
+    pub fn base(&self) -> [u8; 32] {
+        (0u8..=u8::MAX)
+            .find_map(|counter| {
+                let candidate: [u8; 32] = Sha256::new()
+                    .chain_update(BASE_LABEL)
+                    .chain_update(self.0)
+                    .chain_update([counter])
+                    .finalize()
+                    .into();
+                // Clearing the cofactor lands in the prime-order subgroup.
+                let point = CompressedEdwardsY(candidate)
+                    .decompress()?
+                    .mul_by_cofactor();
+                (!point.is_identity()).then(|| point.to_montgomery().to_bytes())
+            })
+            .expect("about half of all hashes decompress to a curve point")
+    }
+
+    /// The X25519 key notes to this diversifier are sealed to.
+    pub fn transmission_key(&self, viewing_secret: &[u8; 32]) -> [u8; 32] {
+        MontgomeryPoint(self.base())
+            .mul_clamped(*viewing_secret)
+            .to_bytes()
+    }
+}
+
+impl Serialize for Diversifier {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Diversifier {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        let bytes = hex::decode(&s).map_err(serde::de::Error::custom)?;
+        bytes
+            .try_into()
+            .map(Diversifier)
+            .map_err(|_| serde::de::Error::custom("expected 16 bytes"))
+    }
+}
+
+fn diversified_spend_key(spend_key: &[u8; 32], j: &[u8; 8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(SPEND_LABEL)
+        .chain_update(spend_key)
+        .chain_update(j)
+        .finalize()
+        .into()
+}
+
+fn spend_pubkey(spend_key: &[u8; 32]) -> [u8; 32] {
+    fr_to_bytes_32(v3_pubkey(Fr::from_le_bytes_mod_order(spend_key)))
+}
+
+fn tag(viewing_secret: &[u8; 32], j: &[u8; 8], owner: &[u8; 32]) -> [u8; 8] {
+    let digest = Sha256::new()
+        .chain_update(TAG_LABEL)
+        .chain_update(viewing_secret)
+        .chain_update(j)
+        .chain_update(owner)
+        .finalize();
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&digest[..8]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::note_crypto::{open, seal_padded_diversified};
+
+    const SPEND: [u8; 32] = [7; 32];
+    const VIEWING: [u8; 32] = [9; 32];
+
+    #[test]
+    fn diversified_keys_are_distinct_and_deterministic() {
+        let d0 = Diversifier::derive(&SPEND, &VIEWING, 0);
+        let d1 = Diversifier::derive(&SPEND, &VIEWING, 1);
+        assert_eq!(d0, Diversifier::derive(&SPEND, &VIEWING, 0));
+        assert_ne!(d0, d1);
+        assert_ne!(d0.spend_pubkey(&SPEND), d1.spend_pubkey(&SPEND));
+        assert_ne!(d0.transmission_key(&VIEWING), d1.transmission_key(&VIEWING));
+        assert_ne!(d0.spend_pubkey(&SPEND), spend_pubkey(&SPEND));
+    }
+
+    #[test]
+    fn the_viewing_secret_opens_a_note_sealed_to_any_diversifier() {
+        let d = Diversifier::derive(&SPEND, &VIEWING, 42);
+        let sealed =
+            seal_padded_diversified(&d.base(), &d.transmission_key(&VIEWING), b"invoice 42")
+                .unwrap();
+        assert_eq!(open(&VIEWING, &sealed).unwrap(), b"invoice 42");
+        assert!(open(&[8; 32], &sealed).is_none());
+    }
+
+    #[test]
+    fn the_tag_binds_the_owner_and_the_viewing_secret() {
+        let d = Diversifier::derive(&SPEND, &VIEWING, 3);
+        let owner = d.spend_pubkey(&SPEND);
+        assert!(d.issued_for(&VIEWING, &owner));
+        assert!(!d.issued_for(&VIEWING, &spend_pubkey(&SPEND)));
+        assert!(!d.issued_for(&[8; 32], &owner));
+
+        let mut forged = d;
+        forged.0[0] ^= 1;
+        assert!(!forged.issued_for(&VIEWING, &owner));
+    }
+}
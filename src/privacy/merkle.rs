@@ -1,21 +1,32 @@
 //! Merkle tree for tracking commitments in the shielded pool
 //!
-//! This implements an incremental Merkle tree where each commitment is inserted
-//! as a leaf. The tree root is used in ZK proofs to prove a commitment exists
-//! without revealing which one.
+//! Each commitment is appended as a leaf of a fixed-depth tree. The tree root
+//! is used in ZK proofs to prove a commitment exists without revealing which
+//! one.
+//!
+//! The tree keeps the same filled-subtree frontier as the program's
+//! `merkle_tree` account, so an append hashes once per level no matter how
+//! many leaves came before, and it stores every interior node — in memory, or
+//! in [`PrivacyStorage`] when persistent — so an authentication path is
+//! `depth` lookups rather than a pass over all leaves. Nodes are kept under
+//! both pair hashes the pool uses: the domain-separated one behind
+//! [`MerkleTree::root`] and the circom one the program checks
+//! ([`OnchainMembership`]).
 
 use crate::privacy::poseidon::poseidon_merkle_pair;
-use crate::privacy::types::{Commitment, MerklePath};
-use crate::storage::PrivacyStorage;
+use crate::privacy::poseidon_circom::v3_merkle_pair;
+use crate::privacy::types::{fr_to_bytes_32, Commitment, MerklePath};
+use crate::storage::{MerkleNode, PrivacyStorage};
+use anyhow::anyhow;
 use ark_bn254::Fr;
 use ark_ff::{BigInteger, PrimeField};
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 /// Default tree depth (supports 2^32 leaves)
 pub const DEFAULT_TREE_DEPTH: usize = 32;
 
-/// Merkle tree for commitments
 /// How many recent roots the tree remembers for withdrawal/transfer proof
 /// verification. A proof is built against the root the prover observed (served
 /// by the path server); by the time a validator verifies it, more deposits may
@@ -26,16 +37,162 @@ pub const DEFAULT_TREE_DEPTH: usize = 32;
 /// independent of which historical root a proof names.
 pub const ROOT_HISTORY_LEN: usize = 256;
 
+/// A leaf's authentication path in the on-chain commitment tree: the
+/// fixed-depth tree the program appends `deposit_note` and `transact` outputs
+/// to, hashed with the circom-compatible [`v3_merkle_pair`] (empty leaf `0`)
+/// that `TransactCircuitV3` checks membership against.
+///
+/// This is a different hash family from [`MerkleTree::root`], which uses the
+/// domain-separated pair hash. The same leaves give different roots under the
+/// two, and only this one is ever accepted on chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OnchainMembership {
+    pub leaf_index: u64,
+    /// Root the path folds to (LE field bytes).
+    pub root: [u8; 32],
+    /// Sibling hashes, leaf level first (LE field bytes).
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl OnchainMembership {
+    /// Hash `leaf` up the path. Equal to `self.root` exactly when `leaf` is the
+    /// commitment at `self.leaf_index`.
+    pub fn fold(&self, leaf: &[u8; 32]) -> [u8; 32] {
+        let mut cur = Fr::from_le_bytes_mod_order(leaf);
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            let s = Fr::from_le_bytes_mod_order(sibling);
+            cur = if (self.leaf_index >> level) & 1 == 1 {
+                v3_merkle_pair(s, cur)
+            } else {
+                v3_merkle_pair(cur, s)
+            };
+        }
+        fr_to_bytes_32(cur)
+    }
+}
+
+/// The pair hashes the tree is kept under. The discriminant is the `family`
+/// byte of a stored [`MerkleNode`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Family {
+    /// Domain-separated Poseidon: [`MerkleTree::root`] and [`MerkleTree::path`].
+    Local = 0,
+    /// circom Poseidon(2), as the program hashes: [`OnchainMembership`].
+    Onchain = 1,
+}
+
+const FAMILIES: [Family; 2] = [Family::Local, Family::Onchain];
+
+impl Family {
+    /// A leaf as this family's level 0 holds it. The program hashes field
+    /// elements, so on that side a non-canonical commitment is reduced.
+    fn leaf(self, leaf: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Family::Local => *leaf,
+            Family::Onchain => fr_to_bytes_32(Fr::from_le_bytes_mod_order(leaf)),
+        }
+    }
+
+    fn pair(self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        match self {
+            Family::Local => MerkleTree::hash_pair(left, right),
+            Family::Onchain => fr_to_bytes_32(v3_merkle_pair(
+                Fr::from_le_bytes_mod_order(left),
+                Fr::from_le_bytes_mod_order(right),
+            )),
+        }
+    }
+
+    /// Precomputed empty-subtree hashes. `zeros[k]` is the root of a fully
+    /// empty subtree of height `k`: `zeros[0]` is the empty-leaf value and
+    /// each level hashes two empty subtrees of the level below. Used to pad
+    /// absent siblings so the tree is a fixed-depth tree — every leaf has a
+    /// path of exactly `depth` siblings, and one set of Groth16 keys
+    /// verifies a withdrawal regardless of how many notes the pool holds.
+    /// On the on-chain side these are the program's `ZERO_HASHES`.
+    fn zeros(self, depth: usize) -> Vec<[u8; 32]> {
+        let mut zeros = Vec::with_capacity(depth + 1);
+        zeros.push([0u8; 32]); // empty leaf
+        for k in 0..depth {
+            let prev = zeros[k];
+            zeros.push(self.pair(&prev, &prev));
+        }
+        zeros
+    }
+}
+
+/// The right edge of one family's tree, as the program keeps it:
+/// `filled[l]` is the newest left child at level `l`, which is all an append
+/// needs to hash its way up to the root.
+#[derive(Clone)]
+struct Frontier {
+    filled: Vec<[u8; 32]>,
+    root: [u8; 32],
+}
+
+impl Frontier {
+    fn empty(zeros: &[[u8; 32]], depth: usize) -> Self {
+        Frontier {
+            filled: zeros[..depth].to_vec(),
+            root: zeros[depth],
+        }
+    }
+}
+
+/// Leaves and interior nodes of a tree without storage.
+#[derive(Default)]
+struct MemoryNodes {
+    leaves: Vec<[u8; 32]>,
+    /// First index of every leaf value.
+    positions: HashMap<[u8; 32], u64>,
+    /// `nodes[family][level - 1][index]`.
+    nodes: [Vec<Vec<[u8; 32]>>; 2],
+}
+
+impl MemoryNodes {
+    fn apply(&mut self, leaves: &[[u8; 32]], nodes: &[MerkleNode]) {
+        for leaf in leaves {
+            let index = self.leaves.len() as u64;
+            self.positions.entry(*leaf).or_insert(index);
+            self.leaves.push(*leaf);
+        }
+        for node in nodes {
+            let levels = &mut self.nodes[node.family as usize];
+            let level = node.level as usize - 1;
+            if levels.len() <= level {
+                levels.resize_with(level + 1, Vec::new);
+            }
+            // Appends only ever rewrite the last node of a level or add
+            // the one after it.
+            let row = &mut levels[level];
+            match row.get_mut(node.index as usize) {
+                Some(slot) => *slot = node.hash,
+                None => row.push(node.hash),
+            }
+        }
+    }
+}
+
+struct TreeState {
+    /// Number of leaves.
+    len: u64,
+    /// Indexed by [`Family`].
+    frontiers: [Frontier; 2],
+    /// `None` when the nodes live in storage.
+    memory: Option<MemoryNodes>,
+}
+
+/// Merkle tree for commitments
 pub struct MerkleTree {
     /// Tree depth
     depth: usize,
-    /// Current leaves (commitments)
-    leaves: Arc<RwLock<Vec<[u8; 32]>>>,
-    /// Cached root
-    cached_root: Arc<RwLock<Option<[u8; 32]>>>,
-    /// Bounded history of recently-computed roots (newest at the back), for
+    /// Empty-subtree hashes, indexed by [`Family`].
+    zeros: Arc<[Vec<[u8; 32]>; 2]>,
+    /// Leaf count, frontiers and (without storage) the nodes themselves
+    state: Arc<RwLock<TreeState>>,
+    /// Bounded history of recent roots (newest at the back), for
     /// [`Self::knows_root`]. Capped at [`ROOT_HISTORY_LEN`].
-    recent_roots: Arc<RwLock<std::collections::VecDeque<[u8; 32]>>>,
+    recent_roots: Arc<RwLock<VecDeque<[u8; 32]>>>,
     /// Optional persistent storage
     storage: Option<Arc<PrivacyStorage>>,
 }
@@ -48,37 +205,63 @@ impl MerkleTree {
 
     /// Create a new Merkle tree with specific depth (in-memory only)
     pub fn with_depth(depth: usize) -> Self {
+        let mut tree = Self::empty(depth, None);
+        let root = tree.zeros[Family::Local as usize][depth];
+        tree.recent_roots = Arc::new(RwLock::new(VecDeque::from([root])));
+        tree
+    }
+
+    fn empty(depth: usize, storage: Option<Arc<PrivacyStorage>>) -> Self {
+        let zeros = FAMILIES.map(|family| family.zeros(depth));
+        let frontiers = FAMILIES.map(|family| Frontier::empty(&zeros[family as usize], depth));
+        let memory = storage.is_none().then(MemoryNodes::default);
         MerkleTree {
             depth,
-            leaves: Arc::new(RwLock::new(Vec::new())),
-            cached_root: Arc::new(RwLock::new(None)),
-            recent_roots: Arc::new(RwLock::new(std::collections::VecDeque::new())),
-            storage: None,
+            zeros: Arc::new(zeros),
+            state: Arc::new(RwLock::new(TreeState {
+                len: 0,
+                frontiers,
+                memory,
+            })),
+            recent_roots: Arc::new(RwLock::new(VecDeque::new())),
+            storage,
         }
     }
 
     /// Create a Merkle tree with persistent storage
+    ///
+    /// Only the frontier is read back — `depth` nodes per family along the
+    /// right edge — so opening costs the same at any size. A database
+    /// written before interior nodes were stored has only its leaves; their
+    /// nodes are built and written once, here.
     pub async fn with_storage(storage: Arc<PrivacyStorage>) -> Result<Self, anyhow::Error> {
-        // Load existing commitments from storage
-        let commitments = storage.get_all_commitments()?;
-        let leaves: Vec<[u8; 32]> = commitments.iter().map(|c| *c.as_bytes()).collect();
-
-        // Load cached root if available
-        let cached_root = storage.get_merkle_root()?;
+        let tree = Self::empty(DEFAULT_TREE_DEPTH, Some(storage.clone()));
+        match storage.merkle_leaf_count()? {
+            Some(len) => {
+                let mut state = tree.state.write().await;
+                state.len = len;
+                for family in FAMILIES {
+                    let frontier = tree.load_frontier(&state, family)?;
+                    state.frontiers[family as usize] = frontier;
+                }
+            }
+            None => {
+                let commitments = storage.get_all_commitments()?;
+                if !commitments.is_empty() {
+                    log::info!(
+                        target: "paraloom::privacy::merkle",
+                        "building interior nodes for {} stored commitments",
+                        commitments.len()
+                    );
+                    tree.append(&commitments).await?;
+                }
+            }
+        }
 
         // Seed the recent-roots window with the reloaded tip so a proof built
         // against the just-restored root verifies immediately after a restart.
-        let recent_roots = match cached_root {
-            Some(r) => std::collections::VecDeque::from([r]),
-            None => std::collections::VecDeque::new(),
-        };
-        Ok(MerkleTree {
-            depth: DEFAULT_TREE_DEPTH,
-            leaves: Arc::new(RwLock::new(leaves)),
-            cached_root: Arc::new(RwLock::new(cached_root)),
-            recent_roots: Arc::new(RwLock::new(recent_roots)),
-            storage: Some(storage),
-        })
+        tree.remember(tree.root().await).await;
+        Ok(tree)
     }
 
     /// Insert a commitment as a leaf, returning the index it was placed
@@ -88,26 +271,8 @@ impl MerkleTree {
     /// This preserves crash-consistency: a leaf either reaches both
     /// memory and disk, or neither.
     pub async fn insert(&self, commitment: &Commitment) -> Result<usize, anyhow::Error> {
-        let mut leaves = self.leaves.write().await;
-        let index = leaves.len();
-
-        if let Some(storage) = &self.storage {
-            storage.insert_commitment(index as u64, commitment).map_err(|e| {
-                log::error!(
-                    target: "paraloom::privacy::merkle",
-                    "failed to persist commitment at index {}: {} — in-memory state not advanced",
-                    index, e
-                );
-                e
-            })?;
-        }
-
-        leaves.push(*commitment.as_bytes());
-
-        let mut cached_root = self.cached_root.write().await;
-        *cached_root = None;
-
-        Ok(index)
+        let index = self.append(std::slice::from_ref(commitment)).await?;
+        Ok(index as usize)
     }
 
     /// Batch insert multiple commitments. Same crash-consistency
@@ -117,95 +282,231 @@ impl MerkleTree {
         &self,
         commitments: &[Commitment],
     ) -> Result<Vec<usize>, anyhow::Error> {
-        let mut leaves = self.leaves.write().await;
-        let start_index = leaves.len();
-        let indices: Vec<usize> = (start_index..start_index + commitments.len()).collect();
+        let start = self.append(commitments).await? as usize;
+        Ok((start..start + commitments.len()).collect())
+    }
+
+    /// Append `commitments` and return the index of the first. The new
+    /// frontiers and nodes are computed aside and only installed once
+    /// storage (if any) has taken them.
+    async fn append(&self, commitments: &[Commitment]) -> Result<u64, anyhow::Error> {
+        let mut state = self.state.write().await;
+        let start = state.len;
+        if commitments.is_empty() {
+            return Ok(start);
+        }
+        let end = start + commitments.len() as u64;
+        if end > 1u64 << self.depth {
+            return Err(anyhow!(
+                "commitment tree is full: {} leaves at depth {}",
+                start,
+                self.depth
+            ));
+        }
+
+        let leaves: Vec<[u8; 32]> = commitments.iter().map(|c| *c.as_bytes()).collect();
+        let mut frontiers = state.frontiers.clone();
+        let mut nodes = Vec::new();
+        for family in FAMILIES {
+            self.extend(
+                family,
+                &mut frontiers[family as usize],
+                start,
+                &leaves,
+                &mut |node: MerkleNode| nodes.push(node),
+            );
+        }
 
         if let Some(storage) = &self.storage {
             storage
-                .insert_commitments_batch(start_index as u64, commitments)
+                .append_merkle_leaves(start, commitments, &nodes)
                 .map_err(|e| {
                     log::error!(
                         target: "paraloom::privacy::merkle",
-                        "failed to persist commitment batch starting at index {}: {} — in-memory state not advanced",
-                        start_index, e
+                        "failed to persist {} commitment(s) at index {}: {} — in-memory state not advanced",
+                        commitments.len(), start, e
                     );
                     e
                 })?;
         }
-
-        for commitment in commitments {
-            leaves.push(*commitment.as_bytes());
+        if let Some(memory) = state.memory.as_mut() {
+            memory.apply(&leaves, &nodes);
         }
-
-        let mut cached_root = self.cached_root.write().await;
-        *cached_root = None;
-
-        Ok(indices)
+        state.len = end;
+        state.frontiers = frontiers;
+
+        // Record the new tip in the bounded history so a proof naming it
+        // still verifies after later deposits advance the tree (see
+        // [`Self::knows_root`] and [`ROOT_HISTORY_LEN`]).
+        self.remember(state.frontiers[Family::Local as usize].root)
+            .await;
+        Ok(start)
     }
 
-    /// Get the current root of the tree
-    pub async fn root(&self) -> [u8; 32] {
-        // Check cache first
-        {
-            let cached = self.cached_root.read().await;
-            if let Some(root) = *cached {
-                return root;
+    /// Hash `leaves`, appended at `start`, into `frontier`, handing every
+    /// interior node that changes to `emit`.
+    ///
+    /// Works a level at a time and hashes each touched node once: one leaf
+    /// is `depth` hashes, exactly the program's `append`, and `k` leaves are
+    /// about `2k`. The left neighbour of the first touched node is complete
+    /// and is the frontier's `filled` entry; the right neighbour of the last
+    /// is still empty. So no stored node is read.
+    fn extend(
+        &self,
+        family: Family,
+        frontier: &mut Frontier,
+        start: u64,
+        leaves: &[[u8; 32]],
+        emit: &mut impl FnMut(MerkleNode),
+    ) {
+        if leaves.is_empty() {
+            return;
+        }
+        let zeros = &self.zeros[family as usize];
+        let mut layer: Vec<[u8; 32]> = leaves.iter().map(|l| family.leaf(l)).collect();
+        let mut lo = start;
+        for (level, zero) in zeros.iter().enumerate().take(self.depth) {
+            let hi = lo + layer.len() as u64 - 1;
+            let parent_lo = lo / 2;
+            let parents: Vec<[u8; 32]> = (parent_lo..=hi / 2)
+                .map(|j| {
+                    let left = if 2 * j < lo {
+                        frontier.filled[level]
+                    } else {
+                        layer[(2 * j - lo) as usize]
+                    };
+                    let right = if 2 * j + 1 > hi {
+                        *zero
+                    } else {
+                        layer[(2 * j + 1 - lo) as usize]
+                    };
+                    family.pair(&left, &right)
+                })
+                .collect();
+
+            if hi.is_multiple_of(2) {
+                frontier.filled[level] = layer[layer.len() - 1];
+            } else if hi > lo {
+                frontier.filled[level] = layer[layer.len() - 2];
+            }
+            for (offset, hash) in parents.iter().enumerate() {
+                emit(MerkleNode {
+                    family: family as u8,
+                    level: (level + 1) as u8,
+                    index: parent_lo + offset as u64,
+                    hash: *hash,
+                });
             }
+            layer = parents;
+            lo = parent_lo;
         }
+        frontier.root = layer[0];
+    }
 
-        // Compute root
-        let leaves = self.leaves.read().await;
-        let root = self.compute_root(&leaves);
+    /// Rebuild a family's frontier from the nodes on the right edge of the
+    /// `state.len` stored leaves.
+    fn load_frontier(&self, state: &TreeState, family: Family) -> anyhow::Result<Frontier> {
+        let mut frontier = Frontier::empty(&self.zeros[family as usize], self.depth);
+        if state.len == 0 {
+            return Ok(frontier);
+        }
+        for level in 0..self.depth {
+            let last = (state.len - 1) >> level;
+            frontier.filled[level] = self.node(state, family, level, last & !1)?;
+        }
+        frontier.root = self.node(state, family, self.depth, 0)?;
+        Ok(frontier)
+    }
 
-        // Cache it
-        let mut cached_root = self.cached_root.write().await;
-        *cached_root = Some(root);
-        drop(cached_root);
+    /// The node at `level`/`index`; level 0 is the leaves.
+    fn node(
+        &self,
+        state: &TreeState,
+        family: Family,
+        level: usize,
+        index: u64,
+    ) -> anyhow::Result<[u8; 32]> {
+        let hash = match (&state.memory, &self.storage) {
+            (Some(memory), _) if level == 0 => memory
+                .leaves
+                .get(index as usize)
+                .map(|leaf| family.leaf(leaf)),
+            (Some(memory), _) => memory.nodes[family as usize]
+                .get(level - 1)
+                .and_then(|row| row.get(index as usize))
+                .copied(),
+            (None, Some(storage)) if level == 0 => storage
+                .get_commitment(index)?
+                .map(|c| family.leaf(c.as_bytes())),
+            (None, Some(storage)) => storage.get_merkle_node(family as u8, level as u8, index)?,
+            (None, None) => None,
+        };
+        hash.ok_or_else(|| anyhow!("merkle node missing at level {} index {}", level, index))
+    }
 
-        // Record this root in the bounded history so a proof naming it still
-        // verifies after later deposits advance the tree (see [`knows_root`] and
-        // [`ROOT_HISTORY_LEN`]). Skip consecutive duplicates.
-        {
-            let mut recent = self.recent_roots.write().await;
-            if recent.back() != Some(&root) {
-                recent.push_back(root);
-                while recent.len() > ROOT_HISTORY_LEN {
-                    recent.pop_front();
+    /// The `depth` siblings of leaf `index`, leaf level first. A sibling
+    /// subtree that holds no leaf yet is the empty-subtree hash; any other
+    /// is a stored node.
+    fn siblings(
+        &self,
+        state: &TreeState,
+        family: Family,
+        index: u64,
+    ) -> anyhow::Result<Vec<[u8; 32]>> {
+        let zeros = &self.zeros[family as usize];
+        (0..self.depth)
+            .map(|level| {
+                let sibling = (index >> level) ^ 1;
+                if sibling << level >= state.len {
+                    Ok(zeros[level])
+                } else {
+                    self.node(state, family, level, sibling)
                 }
-            }
-        }
+            })
+            .collect()
+    }
 
-        // Persist root to storage if available. Unlike leaf inserts,
-        // root persistence is a cache: on restart the tree rebuilds the
-        // root from the stored leaves, so a missed write here costs
-        // a one-time recomputation rather than data loss. We log the
-        // failure loudly so an operator can investigate, but do not
-        // propagate it — read paths that take a root must keep working
-        // even if the disk is misbehaving.
-        if let Some(storage) = &self.storage {
-            if let Err(e) = storage.set_merkle_root(&root) {
-                log::warn!(
+    /// First index of `commitment` among the leaves.
+    fn position(&self, state: &TreeState, commitment: &Commitment) -> Option<u64> {
+        if let Some(memory) = &state.memory {
+            return memory.positions.get(commitment.as_bytes()).copied();
+        }
+        let storage = self.storage.as_ref()?;
+        storage
+            .merkle_leaf_index(commitment)
+            .map_err(|e| {
+                log::error!(
                     target: "paraloom::privacy::merkle",
-                    "failed to persist Merkle root cache: {} — recomputation will trigger on next restart",
+                    "failed to read leaf index: {}",
                     e
                 );
+            })
+            .ok()
+            .flatten()
+    }
+
+    async fn remember(&self, root: [u8; 32]) {
+        let mut recent = self.recent_roots.write().await;
+        if recent.back() != Some(&root) {
+            recent.push_back(root);
+            while recent.len() > ROOT_HISTORY_LEN {
+                recent.pop_front();
             }
         }
+    }
 
-        root
+    /// Get the current root of the tree
+    pub async fn root(&self) -> [u8; 32] {
+        self.state.read().await.frontiers[Family::Local as usize].root
     }
 
     /// Whether `root` is the current tip or one of the last [`ROOT_HISTORY_LEN`]
-    /// roots this tree computed. A verifier accepts a withdrawal/transfer proof
+    /// roots this tree held. A verifier accepts a withdrawal/transfer proof
     /// built against any such root, so nodes whose trees have advanced by
     /// different amounts can still verify the same proof without holding
-    /// byte-identical trees. Computing the current root first guarantees the
-    /// live tip is always considered even before it entered the history.
+    /// byte-identical trees. Every append records its root, so the live tip
+    /// is always in the history.
     pub async fn knows_root(&self, root: &[u8; 32]) -> bool {
-        if &self.root().await == root {
-            return true;
-        }
         let recent = self.recent_roots.read().await;
         recent.iter().any(|r| r == root)
     }
@@ -217,49 +518,131 @@ impl MerkleTree {
     /// settler could advance the published root to an arbitrary (e.g.
     /// fabricated-note) tree and then withdraw against it. Append order matches
     /// `insert`, so it agrees with the state a later settlement commits.
+    /// Only a copy of the frontier is extended, so the cost is that of the
+    /// append itself.
     pub async fn root_after(&self, commitments: &[Commitment]) -> [u8; 32] {
-        let leaves = self.leaves.read().await;
-        let mut extended = leaves.clone();
-        for c in commitments {
-            extended.push(*c.as_bytes());
-        }
-        self.compute_root(&extended)
+        let state = self.state.read().await;
+        let leaves: Vec<[u8; 32]> = commitments.iter().map(|c| *c.as_bytes()).collect();
+        let mut frontier = state.frontiers[Family::Local as usize].clone();
+        self.extend(
+            Family::Local,
+            &mut frontier,
+            state.len,
+            &leaves,
+            &mut |_: MerkleNode| {},
+        );
+        frontier.root
     }
 
     /// Find the index of a commitment among the current leaves, if it is
     /// present. The stored leaf bytes are exactly `commitment.as_bytes()`
-    /// (see [`MerkleTree::insert`]), so raw-byte equality identifies the
-    /// leaf. A linear scan is acceptable here: path queries are
-    /// infrequent relative to inserts, and this keeps the lookup correct
-    /// for both in-memory and storage-restored trees without a parallel
-    /// index map that could drift out of sync.
+    /// (see [`MerkleTree::insert`]), and a commitment appended twice answers
+    /// with its first index. The lookup is an index kept alongside the
+    /// leaves and written in the same batch, so it cannot drift from them.
     pub async fn index_of(&self, commitment: &Commitment) -> Option<usize> {
-        let target = commitment.as_bytes();
-        let leaves = self.leaves.read().await;
-        leaves.iter().position(|leaf| leaf == target)
+        let state = self.state.read().await;
+        self.position(&state, commitment).map(|i| i as usize)
+    }
+
+    /// The on-chain (v3-hashed) authentication path of `commitment`, if it is
+    /// a leaf of this tree. This is what the path server hands a wallet that
+    /// is about to prove a `transact`; see [`OnchainMembership`] for why it is
+    /// not [`Self::path`].
+    pub async fn onchain_membership(&self, commitment: &Commitment) -> Option<OnchainMembership> {
+        let state = self.state.read().await;
+        let index = self.position(&state, commitment)?;
+        let siblings = self
+            .siblings(&state, Family::Onchain, index)
+            .map_err(|e| {
+                log::error!(
+                    target: "paraloom::privacy::merkle",
+                    "failed to read on-chain path of leaf {}: {}",
+                    index, e
+                );
+            })
+            .ok()?;
+        Some(OnchainMembership {
+            leaf_index: index,
+            root: state.frontiers[Family::Onchain as usize].root,
+            siblings,
+        })
+    }
+
+    /// The root of the on-chain (v3-hashed) tree over the current leaves —
+    /// what the program's `merkle_tree` account holds once it has appended the
+    /// same leaves in the same order. The bridge listener compares the two to
+    /// catch divergence.
+    pub async fn onchain_root(&self) -> [u8; 32] {
+        self.state.read().await.frontiers[Family::Onchain as usize].root
     }
 
     /// Get the Merkle path for a leaf at given index
+    ///
+    /// Exactly `self.depth` `(sibling, is_sibling_on_right)` pairs, absent
+    /// siblings padded with the empty-subtree hash for the level. Hashing
+    /// the leaf up this path reproduces [`Self::root`] and matches both
+    /// `MerklePath::verify` and the circuit gadget (same `hash_pair`, same
+    /// direction convention).
     pub async fn path(&self, index: usize) -> Option<MerklePath> {
-        let leaves = self.leaves.read().await;
-
-        if index >= leaves.len() {
+        let state = self.state.read().await;
+        if index as u64 >= state.len {
             return None;
         }
 
-        Some(self.compute_path(&leaves, index))
+        let path = self
+            .siblings(&state, Family::Local, index as u64)
+            .map_err(|e| {
+                log::error!(
+                    target: "paraloom::privacy::merkle",
+                    "failed to read path of leaf {}: {}",
+                    index, e
+                );
+            })
+            .ok()?;
+        // `true` when the sibling is on the right, i.e. we are the left
+        // child. Matches `MerklePath::verify` and the circuit.
+        let indices = (0..self.depth).map(|l| (index >> l) & 1 == 0).collect();
+        Some(MerklePath { path, indices })
     }
 
     /// Get the number of leaves
     pub async fn len(&self) -> usize {
-        let leaves = self.leaves.read().await;
-        leaves.len()
+        self.state.read().await.len as usize
     }
 
     /// Check if tree is empty
     pub async fn is_empty(&self) -> bool {
-        let leaves = self.leaves.read().await;
-        leaves.is_empty()
+        self.state.read().await.len == 0
+    }
+
+    /// Every leaf in order, with the local and on-chain roots over exactly
+    /// those leaves. Appends wait until the read is done, so the three agree
+    /// even while the tree is being extended.
+    pub async fn leaves_with_roots(
+        &self,
+    ) -> Result<(Vec<[u8; 32]>, [u8; 32], [u8; 32]), anyhow::Error> {
+        let state = self.state.read().await;
+        let leaves = match (&state.memory, &self.storage) {
+            (Some(memory), _) => memory.leaves.clone(),
+            (None, Some(storage)) => storage
+                .get_all_commitments()?
+                .iter()
+                .map(|c| *c.as_bytes())
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+        if leaves.len() as u64 != state.len {
+            return Err(anyhow!(
+                "tree holds {} leaves but {} were read back",
+                state.len,
+                leaves.len()
+            ));
+        }
+        Ok((
+            leaves,
+            state.frontiers[Family::Local as usize].root,
+            state.frontiers[Family::Onchain as usize].root,
+        ))
     }
 
     /// Verify a Merkle path
@@ -268,92 +651,6 @@ impl MerkleTree {
         path.verify(leaf, &root)
     }
 
-    /// Precomputed empty-subtree hashes. `empty[k]` is the root of a fully
-    /// empty subtree of height `k`: `empty[0]` is the empty-leaf value and
-    /// each level hashes two empty subtrees of the level below. Used to pad
-    /// absent siblings so the tree is a fixed-depth tree — every leaf has a
-    /// path of exactly `self.depth` siblings, and one set of Groth16 keys
-    /// verifies a withdrawal regardless of how many notes the pool holds.
-    fn empty_subtree_hashes(&self) -> Vec<[u8; 32]> {
-        let mut empties = Vec::with_capacity(self.depth + 1);
-        empties.push([0u8; 32]); // empty leaf
-        for k in 0..self.depth {
-            let prev = empties[k];
-            empties.push(Self::hash_pair(&prev, &prev));
-        }
-        empties
-    }
-
-    /// Compute the fixed-depth root from the current leaves.
-    ///
-    /// Hashes the leaves up exactly `self.depth` levels, filling any absent
-    /// position with the empty-subtree hash for that level. The shape is
-    /// fixed (unlike the earlier dynamic tree, whose height — and so its key
-    /// requirements — changed with the leaf count), so a single trusted setup
-    /// covers a pool of any size up to `2^depth` notes.
-    fn compute_root(&self, leaves: &[[u8; 32]]) -> [u8; 32] {
-        let empties = self.empty_subtree_hashes();
-        if leaves.is_empty() {
-            return empties[self.depth];
-        }
-
-        let mut layer = leaves.to_vec();
-        for empty in empties.iter().take(self.depth) {
-            let parent_count = layer.len().div_ceil(2);
-            let mut next_layer = Vec::with_capacity(parent_count);
-            for j in 0..parent_count {
-                let left = layer.get(2 * j).copied().unwrap_or(*empty);
-                let right = layer.get(2 * j + 1).copied().unwrap_or(*empty);
-                next_layer.push(Self::hash_pair(&left, &right));
-            }
-            layer = next_layer;
-        }
-
-        layer[0]
-    }
-
-    /// Compute the fixed-depth Merkle path for the leaf at `index`: exactly
-    /// `self.depth` `(sibling, is_sibling_on_right)` pairs, padding absent
-    /// siblings with the empty-subtree hash for the level. Hashing the leaf
-    /// up this path reproduces [`compute_root`]'s root and matches both
-    /// `MerklePath::verify` and the circuit gadget (same `hash_pair`, same
-    /// direction convention).
-    fn compute_path(&self, leaves: &[[u8; 32]], index: usize) -> MerklePath {
-        let empties = self.empty_subtree_hashes();
-        let mut path = Vec::with_capacity(self.depth);
-        let mut indices = Vec::with_capacity(self.depth);
-        let mut layer = leaves.to_vec();
-        let mut current_index = index;
-
-        for empty in empties.iter().take(self.depth) {
-            let is_right_child = current_index % 2 == 1;
-            let sibling_index = if is_right_child {
-                current_index - 1
-            } else {
-                current_index + 1
-            };
-            let sibling = layer.get(sibling_index).copied().unwrap_or(*empty);
-
-            path.push(sibling);
-            // Push !is_right_child: if we are the right child the sibling is
-            // on the left (false); if we are the left child it is on the
-            // right (true). Matches `MerklePath::verify` and the circuit.
-            indices.push(!is_right_child);
-
-            let parent_count = layer.len().div_ceil(2);
-            let mut next_layer = Vec::with_capacity(parent_count);
-            for j in 0..parent_count {
-                let left = layer.get(2 * j).copied().unwrap_or(*empty);
-                let right = layer.get(2 * j + 1).copied().unwrap_or(*empty);
-                next_layer.push(Self::hash_pair(&left, &right));
-            }
-            layer = next_layer;
-            current_index /= 2;
-        }
-
-        MerklePath { path, indices }
-    }
-
     /// Hash two child nodes into their parent using domain-separated
     /// Poseidon (`poseidon::domain::MERKLE_PAIR`).
     ///
@@ -383,8 +680,8 @@ impl Clone for MerkleTree {
     fn clone(&self) -> Self {
         MerkleTree {
             depth: self.depth,
-            leaves: Arc::clone(&self.leaves),
-            cached_root: Arc::clone(&self.cached_root),
+            zeros: Arc::clone(&self.zeros),
+            state: Arc::clone(&self.state),
             recent_roots: Arc::clone(&self.recent_roots),
             storage: self.storage.clone(),
         }
@@ -481,6 +778,49 @@ mod tests {
         assert!(!tree.verify(commitment2.as_bytes(), &path).await);
     }
 
+    #[tokio::test]
+    async fn test_onchain_membership_folds_every_leaf_to_one_root() {
+        let tree = MerkleTree::new();
+        let commitments: Vec<Commitment> = (1u8..=5).map(|i| Commitment([i; 32])).collect();
+        tree.insert_batch(&commitments)
+            .await
+            .expect("in-memory insert");
+
+        let first = tree.onchain_membership(&commitments[0]).await.unwrap();
+        for (i, c) in commitments.iter().enumerate() {
+            let m = tree.onchain_membership(c).await.unwrap();
+            assert_eq!(m.leaf_index, i as u64);
+            assert_eq!(m.siblings.len(), DEFAULT_TREE_DEPTH);
+            assert_eq!(m.root, first.root);
+            assert_eq!(m.fold(c.as_bytes()), m.root);
+            assert_ne!(m.fold(&[9u8; 32]), m.root);
+        }
+
+        // A different hash family from the local root.
+        assert_ne!(first.root, tree.root().await);
+        assert!(tree
+            .onchain_membership(&Commitment([9u8; 32]))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_onchain_membership_of_first_leaf_uses_zero_hashes() {
+        // With a single leaf every sibling is an empty subtree, which is what
+        // `relayer::transact_submit::read_membership` rebuilds from the
+        // on-chain account for the left-most leaf.
+        let tree = MerkleTree::new();
+        let c = Commitment([3u8; 32]);
+        tree.insert(&c).await.expect("in-memory insert");
+
+        let m = tree.onchain_membership(&c).await.unwrap();
+        let mut zero = Fr::from(0u64);
+        for sibling in &m.siblings {
+            assert_eq!(*sibling, fr_to_bytes_32(zero));
+            zero = v3_merkle_pair(zero, zero);
+        }
+    }
+
     #[tokio::test]
     async fn test_merkle_tree_batch_insert() {
         let tree = MerkleTree::new();
@@ -635,4 +975,220 @@ mod tests {
             "a root older than the history window is forgotten"
         );
     }
+
+    /// The tree as it was computed before the frontier: every level hashed
+    /// from all the leaves. Kept as the reference the frontier must match.
+    fn reference_layers(leaves: &[[u8; 32]], family: Family, depth: usize) -> Vec<Vec<[u8; 32]>> {
+        let zeros = family.zeros(depth);
+        let mut layers = vec![leaves.iter().map(|l| family.leaf(l)).collect::<Vec<_>>()];
+        for empty in zeros.iter().take(depth) {
+            let layer = layers.last().unwrap();
+            let next = (0..layer.len().div_ceil(2))
+                .map(|j| {
+                    let left = layer.get(2 * j).copied().unwrap_or(*empty);
+                    let right = layer.get(2 * j + 1).copied().unwrap_or(*empty);
+                    family.pair(&left, &right)
+                })
+                .collect();
+            layers.push(next);
+        }
+        layers
+    }
+
+    fn reference_root(leaves: &[[u8; 32]], family: Family, depth: usize) -> [u8; 32] {
+        reference_layers(leaves, family, depth)[depth]
+            .first()
+            .copied()
+            .unwrap_or(family.zeros(depth)[depth])
+    }
+
+    fn reference_siblings(
+        leaves: &[[u8; 32]],
+        family: Family,
+        index: usize,
+        depth: usize,
+    ) -> Vec<[u8; 32]> {
+        let zeros = family.zeros(depth);
+        let layers = reference_layers(leaves, family, depth);
+        (0..depth)
+            .map(|l| layers[l].get((index >> l) ^ 1).copied().unwrap_or(zeros[l]))
+            .collect()
+    }
+
+    fn parts(path: Option<MerklePath>) -> Option<(Vec<[u8; 32]>, Vec<bool>)> {
+        path.map(|p| (p.path, p.indices))
+    }
+
+    fn leaf(i: u32) -> Commitment {
+        let mut bytes = [0x5a; 32];
+        bytes[..4].copy_from_slice(&i.to_le_bytes());
+        Commitment(bytes)
+    }
+
+    #[tokio::test]
+    async fn frontier_matches_the_full_recompute_across_batch_splits() {
+        let tree = MerkleTree::new();
+        let mut leaves: Vec<[u8; 32]> = Vec::new();
+        // Odd and even split points, a duplicate, and a commitment above the
+        // field modulus that the on-chain side has to reduce.
+        let mut next = 0u32;
+        for size in [1usize, 5, 1, 16, 2, 13] {
+            let mut batch: Vec<Commitment> = (0..size)
+                .map(|_| {
+                    next += 1;
+                    leaf(next)
+                })
+                .collect();
+            if size == 2 {
+                batch[0] = leaf(3);
+                batch[1] = Commitment([0xff; 32]);
+            }
+
+            let preview = tree.root_after(&batch).await;
+            if size == 1 {
+                tree.insert(&batch[0]).await.unwrap();
+            } else {
+                tree.insert_batch(&batch).await.unwrap();
+            }
+            leaves.extend(batch.iter().map(|c| *c.as_bytes()));
+
+            let root = reference_root(&leaves, Family::Local, DEFAULT_TREE_DEPTH);
+            assert_eq!(tree.root().await, root);
+            assert_eq!(preview, root);
+            assert_eq!(
+                tree.onchain_root().await,
+                reference_root(&leaves, Family::Onchain, DEFAULT_TREE_DEPTH)
+            );
+        }
+
+        for (i, l) in leaves.iter().enumerate() {
+            let path = tree.path(i).await.unwrap();
+            assert_eq!(
+                path.path,
+                reference_siblings(&leaves, Family::Local, i, DEFAULT_TREE_DEPTH)
+            );
+            assert!(path.verify(l, &tree.root().await));
+
+            let first = leaves.iter().position(|x| x == l).unwrap();
+            assert_eq!(tree.index_of(&Commitment(*l)).await, Some(first));
+            let m = tree.onchain_membership(&Commitment(*l)).await.unwrap();
+            assert_eq!(m.leaf_index, first as u64);
+            assert_eq!(
+                m.siblings,
+                reference_siblings(&leaves, Family::Onchain, first, DEFAULT_TREE_DEPTH)
+            );
+            assert_eq!(m.fold(l), m.root);
+        }
+    }
+
+    #[tokio::test]
+    async fn onchain_root_follows_the_program_append() {
+        use light_poseidon::{Poseidon, PoseidonBytesHasher};
+
+        // `IncrementalMerkleTree::append` from the program, spelled out rather
+        // than imported: the program is a separate crate, so copying it is
+        // the only way to notice if it changes.
+        let poseidon2 = |l: &[u8; 32], r: &[u8; 32]| {
+            Poseidon::<Fr>::new_circom(2)
+                .unwrap()
+                .hash_bytes_le(&[l, r])
+                .unwrap()
+        };
+        let mut zero_hashes = vec![[0u8; 32]];
+        for i in 0..DEFAULT_TREE_DEPTH {
+            let z = zero_hashes[i];
+            zero_hashes.push(poseidon2(&z, &z));
+        }
+        // `ZERO_HASHES[1]` as hardcoded in the program.
+        assert_eq!(
+            zero_hashes[1],
+            [
+                100, 72, 182, 70, 132, 238, 57, 168, 35, 213, 254, 95, 213, 36, 49, 220, 129, 228,
+                129, 123, 242, 195, 234, 60, 171, 158, 35, 158, 251, 245, 152, 32
+            ]
+        );
+        let mut filled_subtrees = zero_hashes[..DEFAULT_TREE_DEPTH].to_vec();
+
+        let tree = MerkleTree::new();
+        assert_eq!(tree.onchain_root().await, zero_hashes[DEFAULT_TREE_DEPTH]);
+        for next_index in 0..21u64 {
+            // On-chain leaves are Poseidon outputs, so canonical field
+            // elements; the program's hash rejects anything else.
+            let mut bytes = *leaf(next_index as u32).as_bytes();
+            bytes[31] = 0;
+            let c = Commitment(bytes);
+            let mut current_index = next_index;
+            let mut current_hash = *c.as_bytes();
+            for i in 0..DEFAULT_TREE_DEPTH {
+                if current_index % 2 == 0 {
+                    filled_subtrees[i] = current_hash;
+                    current_hash = poseidon2(&current_hash, &zero_hashes[i]);
+                } else {
+                    current_hash = poseidon2(&filled_subtrees[i], &current_hash);
+                }
+                current_index /= 2;
+            }
+
+            tree.insert(&c).await.unwrap();
+            assert_eq!(tree.onchain_root().await, current_hash, "leaf {next_index}");
+        }
+    }
+
+    #[tokio::test]
+    async fn stored_tree_reopens_with_the_same_roots_and_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("privacy.db");
+        let commitments: Vec<Commitment> = (0..300).map(leaf).collect();
+        let memory = MerkleTree::new();
+        memory.insert_batch(&commitments).await.unwrap();
+
+        {
+            let storage = Arc::new(PrivacyStorage::open(&path).unwrap());
+            let tree = MerkleTree::with_storage(storage).await.unwrap();
+            tree.insert_batch(&commitments[..7]).await.unwrap();
+            tree.insert(&commitments[7]).await.unwrap();
+            tree.insert_batch(&commitments[8..]).await.unwrap();
+            assert_eq!(tree.root().await, memory.root().await);
+        }
+
+        let storage = Arc::new(PrivacyStorage::open(&path).unwrap());
+        let tree = MerkleTree::with_storage(storage).await.unwrap();
+        assert_eq!(tree.len().await, 300);
+        assert_eq!(tree.root().await, memory.root().await);
+        assert_eq!(tree.onchain_root().await, memory.onchain_root().await);
+        assert!(tree.knows_root(&memory.root().await).await);
+        for i in [0usize, 1, 255, 256, 299] {
+            assert_eq!(parts(tree.path(i).await), parts(memory.path(i).await));
+            assert_eq!(
+                tree.onchain_membership(&commitments[i]).await,
+                memory.onchain_membership(&commitments[i]).await
+            );
+        }
+        assert!(tree.path(300).await.is_none());
+
+        // Appending after the reopen continues from the reloaded frontier.
+        let more = [leaf(1000), leaf(1001), leaf(1002)];
+        assert_eq!(tree.root_after(&more).await, memory.root_after(&more).await);
+        tree.insert_batch(&more).await.unwrap();
+        memory.insert_batch(&more).await.unwrap();
+        assert_eq!(tree.root().await, memory.root().await);
+        assert_eq!(parts(tree.path(302).await), parts(memory.path(302).await));
+    }
+
+    #[tokio::test]
+    async fn leaves_stored_without_nodes_are_indexed_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(PrivacyStorage::open(dir.path().join("privacy.db")).unwrap());
+        let commitments: Vec<Commitment> = (0..9).map(leaf).collect();
+        storage.insert_commitments_batch(0, &commitments).unwrap();
+
+        let tree = MerkleTree::with_storage(storage.clone()).await.unwrap();
+        let memory = MerkleTree::new();
+        memory.insert_batch(&commitments).await.unwrap();
+
+        assert_eq!(storage.merkle_leaf_count().unwrap(), Some(9));
+        assert_eq!(tree.root().await, memory.root().await);
+        assert_eq!(tree.index_of(&commitments[4]).await, Some(4));
+        assert_eq!(parts(tree.path(8).await), parts(memory.path(8).await));
+    }
 }
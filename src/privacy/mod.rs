@@ -14,11 +14,16 @@
 // fire-and-forget patterns elsewhere in the codebase.
 #![deny(clippy::let_underscore_must_use)]
 
+pub mod address;
+pub mod association;
 pub mod batch;
 pub mod circom_reduction;
 pub mod circuits;
 
 pub mod commitment;
+pub mod detection;
+pub mod disclosure;
+pub mod diversifier;
 pub mod error;
 #[cfg(test)]
 mod integration_tests;
@@ -32,22 +37,45 @@ pub mod poseidon;
 pub mod poseidon_circom;
 pub mod proof;
 pub mod proof_codec;
+pub mod prover;
 pub mod r1cs_export;
+pub mod snapshot;
+pub mod suite_registry;
 pub mod transaction;
 pub mod types;
 
+pub use address::{AddressError, PaymentAddress};
+pub use association::{
+    prove_association, AssociationProof, AssociationSet, AssociationVerifier, Exclusions,
+    PublishedAssociationSet,
+};
 pub use batch::{AdaptiveBatchVerifier, BatchVerificationResult, BatchVerifier};
 pub use circuits::{Groth16ProofSystem, MAX_INPUTS, MAX_OUTPUTS};
 pub use commitment::{CommitmentBuilder, CommitmentGenerator};
+pub use detection::{Clue, ClueKey, DetectionError, DetectionKey, DetectionSecret};
+pub use disclosure::{DisclosureError, IncomingViewingKey, NoteDisclosure, NoteOpening};
+pub use diversifier::Diversifier;
 pub use error::{PrivacyError, Result};
-pub use merkle::MerkleTree;
-pub use note_crypto::{decrypt_note, encrypt_note, EncryptedNote, NotePlaintext};
+pub use merkle::{MerkleTree, OnchainMembership};
+pub use note_crypto::{
+    decrypt_note, decrypt_outgoing, encrypt_note, encrypt_outgoing, EncryptedNote, NotePlaintext,
+    OutgoingPlaintext,
+};
 pub use nullifier::NullifierSet;
 pub use pool::ShieldedPool;
-pub use proof::{ProofVerifier, VerificationChunk, VerificationResult};
+pub use proof::{ProofVerifier, TransactProofParts, VerificationChunk, VerificationResult};
 pub use proof_codec::{
-    bytes_to_field, deserialize_proof, field_to_bytes, serialize_proof, split_tagged_proof,
-    tag_proof, Groth16Proof, Groth16VerifyingKey, ProofSuite, GROTH16_BN254_COMPRESSED_LEN,
+    bytes_to_field, deserialize_proof, field_to_bytes, serialize_proof, split_envelope,
+    split_tagged_proof, tag_proof, Groth16Proof, Groth16VerifyingKey, ProofSuite,
+    PublicInputLayout, GROTH16_BN254_COMPRESSED_LEN,
 };
+pub use prover::{prove_transact, InputNote, OutputNote, ProverKeys, TransactSpend, Withdrawal};
+pub use snapshot::{
+    EncodedSnapshot, PoolSnapshot, SnapshotAssembler, SnapshotCursor, SnapshotManifest,
+};
+pub use suite_registry::{RegisteredSuite, SuiteConfig, SuiteRegistry, SuiteRegistryError};
 pub use transaction::{DepositTx, ShieldedTransaction, TrackedTransaction, TransferTx, WithdrawTx};
-pub use types::{Commitment, MerklePath, Note, Nullifier, ShieldedAddress, ViewingKey};
+pub use types::{
+    Commitment, MerklePath, Note, Nullifier, PaymentRequest, PaymentRequestError, ShieldedAddress,
+    ViewingKey,
+};
@@ -128,6 +128,12 @@ impl NullifierSet {
         set.len()
     }
 
+    /// Every revealed nullifier, in no particular order
+    pub async fn to_vec(&self) -> Vec<Nullifier> {
+        let set = self.nullifiers.read().await;
+        set.iter().cloned().collect()
+    }
+
     /// Check if the nullifier set is empty
     pub async fn is_empty(&self) -> bool {
         let set = self.nullifiers.read().await;
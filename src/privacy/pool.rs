@@ -7,6 +7,7 @@
 
 use crate::privacy::merkle::MerkleTree;
 use crate::privacy::nullifier::NullifierSet;
+use crate::privacy::snapshot::{PoolSnapshot, SnapshotCursor, SNAPSHOT_VERSION};
 use crate::privacy::types::{AssetId, Commitment, Note, Nullifier, NATIVE_SOL_ASSET};
 use crate::storage::PrivacyStorage;
 use anyhow::{anyhow, Result};
@@ -164,6 +165,62 @@ impl ShieldedPool {
         Ok(commitment)
     }
 
+    /// Append a settlement's output commitments, in order, as the program
+    /// appended them to its tree. Returns the leaf index of every commitment
+    /// this call added.
+    ///
+    /// Unlike a deposit, a settlement moves value between notes that are
+    /// already shielded, so no supply is credited. Nor is it deduplicated by
+    /// value: the program appends both outputs whatever they hold, so two
+    /// equal outputs are two leaves. Replays are recognised by position
+    /// instead. `first_index` is the leaf the program gave the first output;
+    /// a settlement whose leaves the pool already holds appends nothing, one
+    /// that starts at the pool's next leaf appends every output, and one that
+    /// starts anywhere else is an error — the pool is missing leaves, or
+    /// holds different ones. With no `first_index` (the program's event was
+    /// not in the logs) the outputs go after whatever the pool holds.
+    pub async fn append_outputs(
+        &self,
+        first_index: Option<u64>,
+        outputs: &[Commitment],
+    ) -> Result<Vec<(Commitment, u64)>> {
+        // Held across the length check and the insert, so nothing else
+        // appends in between.
+        let mut deposited = self.deposited.write().await;
+        let next = self.commitment_tree.len().await as u64;
+        if let Some(first) = first_index {
+            let end = first.saturating_add(outputs.len() as u64);
+            if end <= next {
+                return Ok(Vec::new());
+            }
+            if first != next {
+                return Err(anyhow!(
+                    "settlement outputs start at leaf {} but the pool's next leaf is {}",
+                    first,
+                    next
+                ));
+            }
+        }
+        if outputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // One batch, so a storage failure leaves neither output in the tree.
+        let indices = self.commitment_tree.insert_batch(outputs).await?;
+        deposited.extend(outputs.iter().cloned());
+        Ok(outputs
+            .iter()
+            .cloned()
+            .zip(indices.into_iter().map(|i| i as u64))
+            .collect())
+    }
+
+    /// Root of the pool's tree under the on-chain (v3) hash, comparable with
+    /// the program's `merkle_tree` root.
+    pub async fn onchain_root(&self) -> [u8; 32] {
+        self.commitment_tree.onchain_root().await
+    }
+
     /// Get the current Merkle root
     pub async fn root(&self) -> [u8; 32] {
         self.commitment_tree.root().await
@@ -205,6 +262,16 @@ impl ShieldedPool {
             .ok_or_else(|| anyhow!("commitment index {} out of range", index))
     }
 
+    /// The authentication path of `commitment` in the on-chain (v3) tree — the
+    /// `(leaf_index, root, siblings)` a `TransactCircuitV3` spend proves
+    /// against. `None` if the commitment was never inserted into this pool.
+    pub async fn onchain_membership(
+        &self,
+        commitment: &Commitment,
+    ) -> Option<crate::privacy::merkle::OnchainMembership> {
+        self.commitment_tree.onchain_membership(commitment).await
+    }
+
     /// Check if a nullifier has been spent
     pub async fn is_spent(&self, nullifier: &Nullifier) -> bool {
         self.nullifier_set.contains(nullifier).await
@@ -261,6 +328,38 @@ impl ShieldedPool {
         notes.get(commitment).cloned()
     }
 
+    /// Capture the pool as a [`PoolSnapshot`] tagged with the listener
+    /// `cursor`. Read the cursor first: the pool then holds at least
+    /// everything up to it, and whatever it holds beyond is replayed
+    /// harmlessly by a node that imports the snapshot.
+    pub async fn snapshot(&self, cursor: SnapshotCursor) -> Result<PoolSnapshot> {
+        let (commitments, root, onchain_root) = self.commitment_tree.leaves_with_roots().await?;
+        let mut nullifiers: Vec<[u8; 32]> = self
+            .nullifier_set
+            .to_vec()
+            .await
+            .into_iter()
+            .map(|n| n.0)
+            .collect();
+        nullifiers.sort_unstable();
+        let mut supplies: Vec<(AssetId, u64)> = self
+            .all_supplies()
+            .await
+            .into_iter()
+            .filter(|(_, supply)| *supply > 0)
+            .collect();
+        supplies.sort_unstable();
+        Ok(PoolSnapshot {
+            version: SNAPSHOT_VERSION,
+            cursor,
+            commitments,
+            nullifiers,
+            supplies,
+            root,
+            onchain_root,
+        })
+    }
+
     /// Verify the shielded pool's internal consistency
     pub async fn verify_consistency(&self) -> Result<()> {
         // Check that nullifier count <= commitment count
@@ -353,6 +452,63 @@ mod tests {
         );
     }
 
+    /// Settlement outputs land after whatever the tree already holds, in the
+    /// order given, without touching supply — and a replayed settlement adds
+    /// nothing, so the listener can re-fetch one after a failed sibling.
+    #[tokio::test]
+    async fn settlement_outputs_append_in_order_and_replay_as_a_no_op() {
+        let pool = ShieldedPool::new();
+        let note = Note::new_native(ShieldedAddress([1u8; 32]), 500, [2u8; 32]);
+        pool.deposit(note, 500).await.unwrap();
+
+        let outputs = [Commitment([3u8; 32]), Commitment([4u8; 32])];
+        let appended = pool.append_outputs(Some(1), &outputs).await.unwrap();
+        assert_eq!(
+            appended,
+            vec![(outputs[0].clone(), 1), (outputs[1].clone(), 2)]
+        );
+        assert_eq!(pool.total_supply().await, 500);
+
+        let root = pool.onchain_root().await;
+        assert!(pool
+            .append_outputs(Some(1), &outputs)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(pool.commitment_count().await, 3);
+        assert_eq!(pool.onchain_root().await, root);
+        assert_eq!(
+            pool.onchain_membership(&outputs[1])
+                .await
+                .unwrap()
+                .fold(&outputs[1].0),
+            root
+        );
+    }
+
+    /// The program appends both outputs whatever they hold, so two equal
+    /// outputs are two leaves. A settlement that starts past the pool's next
+    /// leaf, or overlaps its last one, is refused rather than patched up.
+    #[tokio::test]
+    async fn equal_settlement_outputs_are_two_leaves() {
+        let pool = ShieldedPool::new();
+        let output = Commitment([7u8; 32]);
+        let outputs = [output.clone(), output.clone()];
+
+        let appended = pool.append_outputs(Some(0), &outputs).await.unwrap();
+        assert_eq!(appended, vec![(output.clone(), 0), (output.clone(), 1)]);
+        assert_eq!(pool.commitment_count().await, 2);
+
+        // A later settlement paying the same commitment again is new leaves too.
+        let appended = pool.append_outputs(Some(2), &outputs).await.unwrap();
+        assert_eq!(appended.len(), 2);
+        assert_eq!(pool.commitment_count().await, 4);
+
+        assert!(pool.append_outputs(Some(6), &outputs).await.is_err());
+        assert!(pool.append_outputs(Some(3), &outputs).await.is_err());
+        assert_eq!(pool.commitment_count().await, 4);
+    }
+
     #[tokio::test]
     async fn test_shielded_pool_deposit() {
         let pool = ShieldedPool::new();
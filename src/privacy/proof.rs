@@ -2,7 +2,10 @@
 //!
 //! Implements Groth16 zkSNARK verification for withdrawal proofs using Arkworks.
 
+use crate::privacy::batch::{BatchVerificationResult, BatchVerifier};
 use crate::privacy::circuits::Groth16ProofSystem;
+use crate::privacy::proof_codec::ProofSuite;
+use crate::privacy::suite_registry::{registry, SuiteRegistry};
 use crate::privacy::types::{Commitment, MerklePath, Nullifier};
 use ark_bn254::{Bn254, Fr};
 use ark_ff::{BigInteger, PrimeField};
@@ -11,7 +14,6 @@ use ark_serialize::CanonicalDeserialize;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
-use std::sync::OnceLock;
 
 /// Native-SOL asset id (the v2 withdraw circuit binds the asset; native is the
 /// all-zero id, matching the on-chain `NATIVE_SOL` and the prover-wasm).
@@ -27,6 +29,18 @@ pub fn withdraw_ext_data_hash(recipient: &[u8], amount: u64) -> [u8; 32] {
     hasher.update(amount.to_le_bytes());
     hasher.finalize().into()
 }
+
+/// The `ext_data_hash` public input of a v3 `transact`:
+/// `sha256(recipient || ext_amount.to_le_bytes())` over the *signed* amount,
+/// byte-identical to the program's `transact_ext_data_hash`. Not
+/// interchangeable with [`withdraw_ext_data_hash`]: a withdrawal of `a` hashes
+/// `(-a as i64)` here, whose eight bytes differ from `a as u64`.
+pub fn transact_ext_data_hash(recipient: &[u8], ext_amount: i64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(recipient);
+    hasher.update(ext_amount.to_le_bytes());
+    hasher.finalize().into()
+}
 use thiserror::Error;
 
 /// True iff `b` is the *canonical* little-endian encoding of its own field
@@ -177,12 +191,19 @@ pub enum KeyLoadError {
         #[source]
         source: ark_serialize::SerializationError,
     },
-}
 
-/// Cached transact v3 verifying key (#350). Lazily populated on first
-/// successful load; failed loads do not poison the cache, so a node whose key
-/// file is restored after a misconfiguration recovers without a restart.
-static TRANSACT_VERIFYING_KEY: OnceLock<VerifyingKey<Bn254>> = OnceLock::new();
+    /// The key deserialized but has the wrong number of public inputs for
+    /// the suite it is registered under: a key for another circuit.
+    #[error(
+        "verifying key at '{}' has {found} IC points, its suite's layout needs {expected}",
+        path.display()
+    )]
+    LayoutMismatch {
+        path: PathBuf,
+        expected: usize,
+        found: usize,
+    },
+}
 
 /// Resolve the transact v3 verifying-key path (#350), consulting the
 /// `TRANSACT_VERIFYING_KEY_PATH` environment variable and falling back to
@@ -193,12 +214,20 @@ fn resolve_transact_key_path() -> PathBuf {
         .unwrap_or_else(|| PathBuf::from(DEFAULT_TRANSACT_VERIFYING_KEY_PATH))
 }
 
+/// Default verifying-key path of a built-in suite, for registry entries that
+/// do not name one.
+pub(crate) fn resolve_verifying_key_path(suite: ProofSuite) -> PathBuf {
+    match suite {
+        ProofSuite::Groth16Bn254TransactV3 => resolve_transact_key_path(),
+    }
+}
+
 /// Load and deserialize a Groth16 verifying key from a specific path.
 ///
 /// This is the pure, testable core of the loading logic — given a path,
 /// produce either the key or a typed error explaining what went wrong.
-/// The caching wrapper `ProofVerifier::get_transact_verifying_key` composes
-/// this with a global `OnceLock`.
+/// The caching wrapper `RegisteredSuite::verifying_key` composes this with a
+/// per-suite `OnceLock`.
 pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey<Bn254>, KeyLoadError> {
     let bytes = match std::fs::read(path) {
         Ok(bytes) => bytes,
@@ -221,39 +250,48 @@ pub fn load_verifying_key(path: &Path) -> Result<VerifyingKey<Bn254>, KeyLoadErr
     })
 }
 
-/// ZK Proof verifier interface
-pub struct ProofVerifier;
+/// A transact proof and the settlement fields it must bind: the arguments of
+/// [`ProofVerifier::verify_transact_parts`], owned, so a batch can be handed
+/// to a blocking thread.
+#[derive(Clone, Debug)]
+pub struct TransactProofParts {
+    pub root: [u8; 32],
+    pub recipient: [u8; 32],
+    pub ext_amount: i64,
+    pub asset_id: [u8; 32],
+    pub nullifiers: [[u8; 32]; 2],
+    pub output_commitments: [[u8; 32]; 2],
+    pub zk_proof: Vec<u8>,
+    /// Slot the request was admitted at; see
+    /// [`ProofVerifier::verify_transact_parts`].
+    pub slot: Option<u64>,
+}
 
-impl ProofVerifier {
-    /// Load the transact v3 verifying key from disk (cached on success) for the
-    /// unified transact circuit (#350). On a cache miss it resolves the
-    /// configured path, loads and deserializes the key, and caches it on
-    /// success; failed loads are not cached.
-    fn get_transact_verifying_key() -> Result<&'static VerifyingKey<Bn254>, KeyLoadError> {
-        if let Some(vk) = TRANSACT_VERIFYING_KEY.get() {
-            return Ok(vk);
-        }
+/// A transact proof that passed every check short of the pairing.
+struct PreparedTransact {
+    verifying_key: &'static VerifyingKey<Bn254>,
+    public_inputs: Vec<Fr>,
+    proof: Proof<Bn254>,
+}
 
-        let path = resolve_transact_key_path();
-        let key = load_verifying_key(&path).inspect_err(|e| {
-            log::error!(
-                target: "paraloom::privacy::proof",
-                "transact verifying key load failed: {}",
-                e
-            );
-        })?;
-
-        if TRANSACT_VERIFYING_KEY.set(key).is_err() {
-            log::debug!(
-                target: "paraloom::privacy::proof",
-                "transact verifying key was already cached by a concurrent caller"
-            );
+impl PreparedTransact {
+    fn verify(&self) -> VerificationResult {
+        match Groth16ProofSystem::verify(self.verifying_key, &self.public_inputs, &self.proof) {
+            Ok(true) => VerificationResult::Valid,
+            Ok(false) => VerificationResult::Invalid {
+                reason: "transact zkSNARK proof verification failed".to_string(),
+            },
+            Err(e) => VerificationResult::Invalid {
+                reason: format!("Verification error: {}", e),
+            },
         }
-        Ok(TRANSACT_VERIFYING_KEY
-            .get()
-            .expect("transact verifying key cache populated above"))
     }
+}
 
+/// ZK Proof verifier interface
+pub struct ProofVerifier;
+
+impl ProofVerifier {
     /// Verify a v3 unified transact proof from its raw parts (#350).
     ///
     /// The eight public inputs are lifted in the exact order
@@ -272,6 +310,11 @@ impl ProofVerifier {
     ///
     /// `ext_amount > 0` is rejected: deposits enter through `deposit_note`
     /// (no proof), so a positive external flow is never a valid spend request.
+    ///
+    /// The proof's suite must be active at `slot`, the slot its request was
+    /// admitted at, so validators agree on a proof near a suite cutover
+    /// however far their own view of the chain has got.
+    #[allow(clippy::too_many_arguments)]
     pub fn verify_transact_parts(
         root: &[u8; 32],
         recipient: &[u8; 32],
@@ -280,11 +323,107 @@ impl ProofVerifier {
         nullifiers: &[[u8; 32]; 2],
         output_commitments: &[[u8; 32]; 2],
         zk_proof: &[u8],
+        slot: Option<u64>,
     ) -> VerificationResult {
+        match Self::prepare_transact(
+            registry(),
+            root,
+            recipient,
+            ext_amount,
+            asset_id,
+            nullifiers,
+            output_commitments,
+            zk_proof,
+            slot,
+        ) {
+            Ok(prepared) => prepared.verify(),
+            Err(rejected) => rejected,
+        }
+    }
+
+    /// Verify many transact proofs at once, returning one result per entry
+    /// of `parts`, in order.
+    ///
+    /// Every entry goes through the same checks as
+    /// [`Self::verify_transact_parts`]; the proofs that survive them are
+    /// checked together, one randomized multi-pairing per verifying key
+    /// (see [`BatchVerifier`]). Only when that fails is each proof of the
+    /// group checked on its own, so one bad proof costs the batch its
+    /// speedup but never flips another proof's result.
+    pub fn verify_transact_batch(parts: &[TransactProofParts]) -> Vec<VerificationResult> {
+        let mut results = vec![VerificationResult::Valid; parts.len()];
+        let mut pending = Vec::new();
+        for (i, p) in parts.iter().enumerate() {
+            match Self::prepare_transact(
+                registry(),
+                &p.root,
+                &p.recipient,
+                p.ext_amount,
+                &p.asset_id,
+                &p.nullifiers,
+                &p.output_commitments,
+                &p.zk_proof,
+                p.slot,
+            ) {
+                Ok(prepared) => pending.push((i, prepared)),
+                Err(rejected) => results[i] = rejected,
+            }
+        }
+
+        // Group by verifying key; each suite has its own.
+        while let Some((_, first)) = pending.first() {
+            let vk = first.verifying_key;
+            let (group, rest): (Vec<_>, Vec<_>) = pending
+                .into_iter()
+                .partition(|(_, p)| std::ptr::eq(p.verifying_key, vk));
+            pending = rest;
+
+            let inputs: Vec<Vec<Fr>> = group.iter().map(|(_, p)| p.public_inputs.clone()).collect();
+            let proofs: Vec<Proof<Bn254>> = group.iter().map(|(_, p)| p.proof.clone()).collect();
+            match BatchVerifier::new().verify_batch(vk, &inputs, &proofs) {
+                BatchVerificationResult::AllValid => {}
+                BatchVerificationResult::SomeInvalid { invalid_indices } => {
+                    for j in invalid_indices {
+                        results[group[j].0] = VerificationResult::Invalid {
+                            reason: "transact zkSNARK proof verification failed".to_string(),
+                        };
+                    }
+                }
+                BatchVerificationResult::Error { reason } => {
+                    log::warn!(
+                        "batch transact verification errored ({}); checking each proof",
+                        reason
+                    );
+                    for (i, prepared) in &group {
+                        results[*i] = prepared.verify();
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Everything [`Self::verify_transact_parts`] checks before the pairing:
+    /// the external-flow sign, canonical encodings, the proof envelope and
+    /// body, the suite's verifying key, and the public inputs
+    /// derived from the settlement fields, with the suite resolved in
+    /// `registry` at `slot`. `Err` carries the rejection.
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_transact(
+        registry: &'static SuiteRegistry,
+        root: &[u8; 32],
+        recipient: &[u8; 32],
+        ext_amount: i64,
+        asset_id: &[u8; 32],
+        nullifiers: &[[u8; 32]; 2],
+        output_commitments: &[[u8; 32]; 2],
+        zk_proof: &[u8],
+        slot: Option<u64>,
+    ) -> Result<PreparedTransact, VerificationResult> {
         if ext_amount > 0 {
-            return VerificationResult::Invalid {
+            return Err(VerificationResult::Invalid {
                 reason: "positive ext_amount: deposits go through deposit_note".to_string(),
-            };
+            });
         }
 
         // Reject non-canonical encodings (see `is_canonical_le`) before any
@@ -294,36 +433,37 @@ impl ProofVerifier {
         // `ext_data_hash` is exempt — it is a SHA-256 digest derived below,
         // lifted mod p on every side consistently.
         if !is_canonical_le(root) {
-            return VerificationResult::Invalid {
+            return Err(VerificationResult::Invalid {
                 reason: "non-canonical root encoding".to_string(),
-            };
+            });
         }
         for nullifier in nullifiers {
             if !is_canonical_le(nullifier) {
-                return VerificationResult::Invalid {
+                return Err(VerificationResult::Invalid {
                     reason: "non-canonical nullifier encoding".to_string(),
-                };
+                });
             }
         }
         for commitment in output_commitments {
             if !is_canonical_le(commitment) {
-                return VerificationResult::Invalid {
+                return Err(VerificationResult::Invalid {
                     reason: "non-canonical output commitment encoding".to_string(),
-                };
+                });
             }
         }
 
         // The blob is `suite_tag(1) || body` (see `proof_codec::ProofSuite`).
-        // Dispatch on the tag rather than on "whatever verifying key this build
-        // embeds": an unrecognised suite is rejected outright, so a node never
+        // Dispatch on the tag through the suite registry rather than on
+        // "whatever verifying key this build embeds": a tag that is unknown or
+        // outside its activation window is rejected outright, so a node never
         // verifies a proof under different rules than the prover used.
-        let (suite, body) = match crate::privacy::split_tagged_proof(zk_proof) {
+        let (suite, body) = match registry.resolve(zk_proof, slot) {
             Ok(parts) => parts,
             Err(e) => {
                 log::warn!("Failed to parse transact proof envelope: {}", e);
-                return VerificationResult::Invalid {
+                return Err(VerificationResult::Invalid {
                     reason: format!("Invalid proof envelope: {}", e),
-                };
+                });
             }
         };
 
@@ -331,25 +471,21 @@ impl ProofVerifier {
             Ok(p) => p,
             Err(e) => {
                 log::warn!("Failed to deserialize transact proof: {}", e);
-                return VerificationResult::Invalid {
+                return Err(VerificationResult::Invalid {
                     reason: format!("Invalid proof format: {}", e),
-                };
+                });
             }
         };
 
-        // The suite names the key that verifies it, so a second suite cannot
-        // silently borrow this one: adding a variant makes this a compile
-        // error rather than a proof checked under the wrong verifying key.
-        let verifying_key = match suite {
-            crate::privacy::ProofSuite::Groth16Bn254TransactV3 => {
-                match Self::get_transact_verifying_key() {
-                    Ok(vk) => vk,
-                    Err(e) => {
-                        return VerificationResult::Invalid {
-                            reason: format!("transact verifying key unavailable: {}", e),
-                        };
-                    }
-                }
+        let verifying_key = match suite.verifying_key() {
+            Ok(vk) => vk,
+            Err(e) => {
+                return Err(VerificationResult::Invalid {
+                    reason: format!(
+                        "verifying key for proof suite {} unavailable: {}",
+                        suite.tag, e
+                    ),
+                });
             }
         };
 
@@ -357,13 +493,7 @@ impl ProofVerifier {
         // ext_data_hash = SHA-256(recipient || ext_amount_le), and
         // public_amount is the field encoding of the signed ext_amount
         // (negative = p - |ext_amount|).
-        let ext_data_hash: [u8; 32] = {
-            use sha2::{Digest, Sha256};
-            let mut hasher = Sha256::new();
-            hasher.update(recipient);
-            hasher.update(ext_amount.to_le_bytes());
-            hasher.finalize().into()
-        };
+        let ext_data_hash = transact_ext_data_hash(recipient, ext_amount);
         let magnitude = Fr::from(ext_amount.unsigned_abs());
         let public_amount = if ext_amount < 0 {
             -magnitude
@@ -382,15 +512,11 @@ impl ProofVerifier {
             Fr::from_le_bytes_mod_order(&output_commitments[1]),
         ];
 
-        match Groth16ProofSystem::verify(verifying_key, &public_inputs, &proof) {
-            Ok(true) => VerificationResult::Valid,
-            Ok(false) => VerificationResult::Invalid {
-                reason: "transact zkSNARK proof verification failed".to_string(),
-            },
-            Err(e) => VerificationResult::Invalid {
-                reason: format!("Verification error: {}", e),
-            },
-        }
+        Ok(PreparedTransact {
+            verifying_key,
+            public_inputs,
+            proof,
+        })
     }
 }
 
@@ -454,6 +580,7 @@ mod tests {
             &[[1u8; 32], [2u8; 32]],
             &[[3u8; 32], [4u8; 32]],
             &[0u8; 64],
+            None,
         );
         match r {
             VerificationResult::Invalid { reason } => {
@@ -479,6 +606,7 @@ mod tests {
             &[modulus, [2u8; 32]],
             &[[3u8; 32], [4u8; 32]],
             &[0u8; 64],
+            None,
         );
         match r {
             VerificationResult::Invalid { reason } => {
@@ -501,6 +629,7 @@ mod tests {
             &[[1u8; 32], [2u8; 32]],
             &[[3u8; 32], [4u8; 32]],
             &[0xFFu8; 8],
+            None,
         );
         match r {
             VerificationResult::Invalid { reason } => {
@@ -524,6 +653,7 @@ mod tests {
             &[[1u8; 32], [2u8; 32]],
             &[[3u8; 32], [4u8; 32]],
             &blob,
+            None,
         );
         match r {
             VerificationResult::Invalid { reason } => {
@@ -533,6 +663,91 @@ mod tests {
         }
     }
 
+    /// A suite window is judged at the request's slot, inclusive at
+    /// activation and exclusive at deactivation, so every validator reaches
+    /// the same verdict on a request at the boundary.
+    #[test]
+    fn suite_window_is_judged_at_the_request_slot() {
+        use crate::privacy::suite_registry::SuiteConfig;
+        let registry: &'static SuiteRegistry = Box::leak(Box::new(
+            SuiteRegistry::from_config(&[SuiteConfig {
+                tag: 3,
+                circuit: crate::privacy::ProofSuite::Groth16Bn254TransactV3,
+                verifying_key: None,
+                activation_slot: Some(1_000),
+                deactivation_slot: Some(2_000),
+            }])
+            .unwrap(),
+        ));
+        let mut blob = vec![3u8];
+        blob.extend_from_slice(&[0xFFu8; crate::privacy::GROTH16_BN254_COMPRESSED_LEN]);
+        let reason_at = |slot: Option<u64>| match ProofVerifier::prepare_transact(
+            registry,
+            &[0u8; 32],
+            &[3u8; 32],
+            -500,
+            &[0u8; 32],
+            &[[1u8; 32], [2u8; 32]],
+            &[[3u8; 32], [4u8; 32]],
+            &blob,
+            slot,
+        ) {
+            Err(VerificationResult::Invalid { reason }) => reason,
+            _ => panic!("a garbage body never prepares"),
+        };
+
+        // Inside the window the envelope resolves and the body is parsed.
+        for slot in [1_000, 1_999] {
+            let reason = reason_at(Some(slot));
+            assert!(reason.contains("Invalid proof format"), "{slot}: {reason}");
+        }
+        for slot in [Some(999), Some(2_000), None] {
+            let reason = reason_at(slot);
+            assert!(reason.contains("not active"), "{slot:?}: {reason}");
+        }
+    }
+
+    #[test]
+    fn transact_batch_keeps_each_rejection_in_place() {
+        let parts = |ext_amount: i64, zk_proof: Vec<u8>| TransactProofParts {
+            root: [0u8; 32],
+            recipient: [3u8; 32],
+            ext_amount,
+            asset_id: [0u8; 32],
+            nullifiers: [[1u8; 32], [2u8; 32]],
+            output_commitments: [[3u8; 32], [4u8; 32]],
+            zk_proof,
+            slot: None,
+        };
+        let mut garbage = vec![crate::privacy::ProofSuite::Groth16Bn254TransactV3.tag()];
+        garbage.extend_from_slice(&[0xFFu8; crate::privacy::GROTH16_BN254_COMPRESSED_LEN]);
+
+        let results = ProofVerifier::verify_transact_batch(&[
+            parts(-500, garbage),
+            parts(500, vec![0u8; 64]),
+            parts(-500, vec![0xFFu8; 8]),
+        ]);
+        let reasons: Vec<String> = results
+            .into_iter()
+            .map(|r| match r {
+                VerificationResult::Invalid { reason } => reason,
+                VerificationResult::Valid => panic!("every entry must be rejected"),
+            })
+            .collect();
+        assert!(
+            reasons[0].contains("Invalid proof format"),
+            "{}",
+            reasons[0]
+        );
+        assert!(reasons[1].contains("positive ext_amount"), "{}", reasons[1]);
+        assert!(
+            reasons[2].contains("Invalid proof envelope"),
+            "{}",
+            reasons[2]
+        );
+        assert!(ProofVerifier::verify_transact_batch(&[]).is_empty());
+    }
+
     #[test]
     fn test_output_commitments_chunk() {
         let commitments = vec![Commitment([1u8; 32]), Commitment([2u8; 32])];
@@ -594,10 +809,9 @@ mod tests {
 
     // ─── Verifying key load — error path coverage ─────────────────────
     //
-    // These exercise `load_verifying_key` directly rather than the cached
-    // `ProofVerifier::get_transact_verifying_key` wrapper, since the latter
-    // relies on a process-wide `OnceLock` that would otherwise bleed state
-    // between tests in the same binary.
+    // These exercise `load_verifying_key` directly rather than through the
+    // process-wide suite registry, whose cached keys would otherwise bleed
+    // state between tests in the same binary.
 
     #[test]
     fn load_verifying_key_missing_file_returns_not_found() {
@@ -662,4 +876,18 @@ mod tests {
         let expected: [u8; 32] = h.finalize().into();
         assert_eq!(withdraw_ext_data_hash(&recipient, amount), expected);
     }
+
+    #[test]
+    fn transact_ext_data_hash_binds_the_signed_amount() {
+        let recipient = [7u8; 32];
+        let mut h = Sha256::new();
+        h.update(recipient);
+        h.update((-5i64).to_le_bytes());
+        let expected: [u8; 32] = h.finalize().into();
+        assert_eq!(transact_ext_data_hash(&recipient, -5), expected);
+        assert_ne!(
+            transact_ext_data_hash(&recipient, -5),
+            withdraw_ext_data_hash(&recipient, 5)
+        );
+    }
 }
@@ -4,6 +4,7 @@ use crate::privacy::Result;
 use ark_bn254::{Bn254, Fr};
 use ark_groth16::{Proof, VerifyingKey};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 
 pub type Groth16Proof = Proof<Bn254>;
@@ -83,8 +84,15 @@ pub const GROTH16_BN254_COMPRESSED_LEN: usize = 128;
 /// it is done now even though there is still just one suite. The tag is
 /// deliberately **not** zero-based: an all-zero or truncated buffer must not
 /// parse as a valid suite.
+///
+/// Each variant is a circuit and proof system, and its discriminant is the
+/// tag it is issued under by default. Which tags a node actually accepts,
+/// under which verifying key and during which slots, is decided by the
+/// `suite_registry`, so a key rotation can move a circuit to a new tag
+/// without a code change.
 #[repr(u8)]
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ProofSuite {
     /// Groth16 over BN254 (`alt_bn128`) for `TransactCircuitV3`, proof body in
     /// arkworks-compressed form. The only suite the on-chain program can
@@ -116,6 +124,37 @@ impl ProofSuite {
             ProofSuite::Groth16Bn254TransactV3 => GROTH16_BN254_COMPRESSED_LEN,
         }
     }
+
+    /// The public inputs this suite's circuit exposes.
+    pub const fn layout(self) -> PublicInputLayout {
+        match self {
+            ProofSuite::Groth16Bn254TransactV3 => PublicInputLayout {
+                nullifiers: crate::privacy::circuits::TX_NINS,
+                output_commitments: crate::privacy::circuits::TX_NOUTS,
+            },
+        }
+    }
+}
+
+/// Public-input layout of a `TransactCircuitV3`-family circuit:
+/// `[root, public_amount, ext_data_hash, asset_id, nullifier0..,
+/// out_commitment0..]`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PublicInputLayout {
+    pub nullifiers: usize,
+    pub output_commitments: usize,
+}
+
+impl PublicInputLayout {
+    /// Fixed inputs ahead of the nullifiers: root, public_amount,
+    /// ext_data_hash and asset_id.
+    pub const FIXED_INPUTS: usize = 4;
+
+    /// Total number of public inputs; a matching verifying key has one more
+    /// IC point than this.
+    pub const fn public_inputs(self) -> usize {
+        Self::FIXED_INPUTS + self.nullifiers + self.output_commitments
+    }
 }
 
 /// Prepend the suite tag to a proof body, producing the L2 wire encoding
@@ -127,6 +166,15 @@ pub fn tag_proof(suite: ProofSuite, body: &[u8]) -> Vec<u8> {
     out
 }
 
+/// Split an L2 wire proof into its raw tag byte and body, without deciding
+/// whether the tag is one this node accepts.
+pub fn split_envelope(wire: &[u8]) -> Result<(u8, &[u8])> {
+    let (tag, body) = wire.split_first().ok_or_else(|| {
+        crate::privacy::PrivacyError::SerializationError("empty proof blob".to_string())
+    })?;
+    Ok((*tag, body))
+}
+
 /// Split an L2 wire proof into its suite and body.
 ///
 /// Rejects an empty buffer, an unknown tag, and a body whose length does not
@@ -134,10 +182,8 @@ pub fn tag_proof(suite: ProofSuite, body: &[u8]) -> Vec<u8> {
 /// 128-byte proof from being read as a tagged one whose first byte happens to
 /// be `0x01`: it would leave a 127-byte body.
 pub fn split_tagged_proof(wire: &[u8]) -> Result<(ProofSuite, &[u8])> {
-    let (tag, body) = wire.split_first().ok_or_else(|| {
-        crate::privacy::PrivacyError::SerializationError("empty proof blob".to_string())
-    })?;
-    let suite = ProofSuite::from_tag(*tag)?;
+    let (tag, body) = split_envelope(wire)?;
+    let suite = ProofSuite::from_tag(tag)?;
     if body.len() != suite.body_len() {
         return Err(crate::privacy::PrivacyError::SerializationError(format!(
             "proof suite {:?} expects a {}-byte body, got {}",
@@ -0,0 +1,642 @@
+//! Proving a `TransactCircuitV3` spend as a library call.
+//!
+//! [`prove_transact`] takes the spender's keys, the notes being spent with
+//! their on-chain paths, the two outputs and an optional withdrawal, and
+//! returns a [`TransactVerificationRequest`] ready for the transact ingress
+//! or for validator gossip: tagged proof, sealed output ciphertexts and a
+//! canonical request id. Integrators that hold notes outside the CLI wallet
+//! prove through this; the wallet's own plans lay out their witness with the
+//! same code.
+//!
+//! Inputs are padded to `TX_NINS` inputs with zero-value dummies. The
+//! circuit skips membership for a zero-amount input, and each dummy's random
+//! key keeps its nullifier distinct from the real ones and from each other.
+
+use anyhow::{anyhow, bail, Context, Result};
+use ark_bn254::{Bn254, Fr};
+use ark_ff::PrimeField;
+use ark_groth16::ProvingKey;
+use ark_serialize::CanonicalSerialize;
+
+use crate::consensus::transact::TransactVerificationRequest;
+use crate::privacy::circuits::{Groth16ProofSystem, TransactCircuitV3, TX_NINS};
+use crate::privacy::detection::{Clue, ClueKey};
+use crate::privacy::diversifier::Diversifier;
+use crate::privacy::merkle::OnchainMembership;
+use crate::privacy::note_crypto::{
+    attach_clue, encrypt_note_v2, encrypt_note_v2_diversified, encrypt_outgoing, NotePlaintext,
+    OutgoingPlaintext, MAX_MEMO_LEN,
+};
+use crate::privacy::poseidon_circom::{
+    mint_to_asset, v3_commit, v3_nullifier, v3_pubkey, v3_signature,
+};
+use crate::privacy::proof::transact_ext_data_hash;
+use crate::privacy::types::{fr_to_bytes_32, AssetId, NATIVE_SOL_ASSET};
+use crate::privacy::{tag_proof, PaymentAddress, ProofSuite, ProofVerifier};
+
+/// Random bytes reduced to a canonical field element, for blindings and
+/// dummy keys.
+pub(crate) fn random_field_bytes() -> [u8; 32] {
+    fr_to_bytes_32(Fr::from_le_bytes_mod_order(&rand::random::<[u8; 32]>()))
+}
+
+/// The secrets a spend needs: the v3 spend key that owns the inputs, and the
+/// key the sender's record of each output is sealed to.
+#[derive(Clone)]
+pub struct ProverKeys {
+    pub spend_key: [u8; 32],
+    pub outgoing_key: [u8; 32],
+}
+
+impl ProverKeys {
+    /// The key that spends notes paid to `diversifier`, or to the default
+    /// address.
+    pub fn spend_key_for(&self, diversifier: Option<&Diversifier>) -> [u8; 32] {
+        match diversifier {
+            Some(d) => d.spend_key(&self.spend_key),
+            None => self.spend_key,
+        }
+    }
+}
+
+impl std::fmt::Debug for ProverKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProverKeys(..)")
+    }
+}
+
+/// A note being spent, with its path in the on-chain tree.
+#[derive(Clone, Debug)]
+pub struct InputNote {
+    pub amount: u64,
+    pub blinding: [u8; 32],
+    pub asset_id: AssetId,
+    /// The v3 commitment `membership` opens.
+    pub commitment: [u8; 32],
+    pub membership: OnchainMembership,
+    /// The diversified address the note was paid to, which picks the key
+    /// that spends it; `None` for the default address.
+    pub diversifier: Option<Diversifier>,
+}
+
+impl InputNote {
+    /// A note of `amount` owned by `keys`, its commitment recomputed from the
+    /// opening.
+    pub fn new(
+        keys: &ProverKeys,
+        amount: u64,
+        blinding: [u8; 32],
+        asset_id: AssetId,
+        membership: OnchainMembership,
+    ) -> Self {
+        Self::paid_to(keys, None, amount, blinding, asset_id, membership)
+    }
+
+    /// [`InputNote::new`] for a note paid to one of `keys`' diversified
+    /// addresses.
+    pub fn paid_to(
+        keys: &ProverKeys,
+        diversifier: Option<Diversifier>,
+        amount: u64,
+        blinding: [u8; 32],
+        asset_id: AssetId,
+        membership: OnchainMembership,
+    ) -> Self {
+        let commitment = fr_to_bytes_32(v3_commit(
+            Fr::from(amount),
+            v3_pubkey(Fr::from_le_bytes_mod_order(
+                &keys.spend_key_for(diversifier.as_ref()),
+            )),
+            Fr::from_le_bytes_mod_order(&blinding),
+            Fr::from_le_bytes_mod_order(&asset_id),
+        ));
+        InputNote {
+            amount,
+            blinding,
+            asset_id,
+            commitment,
+            membership,
+            diversifier,
+        }
+    }
+}
+
+/// One output of a spend: who can spend it and who can read it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutputNote {
+    pub amount: u64,
+    /// v3 spend pubkey bound into the commitment.
+    pub owner: [u8; 32],
+    pub blinding: [u8; 32],
+    /// X25519 key the note is sealed to.
+    pub transmission_key: [u8; 32],
+    /// Sealed to the recipient with the note; at most [`MAX_MEMO_LEN`] bytes.
+    pub memo: Vec<u8>,
+    /// The recipient's detection clue key, from a v2 address. The delivered
+    /// envelope leads with a clue to it when set.
+    pub clue_key: Option<ClueKey>,
+    /// Set when paying a diversified address: the note is sealed on its base
+    /// point and tells the recipient which of their addresses was paid.
+    pub diversifier: Option<Diversifier>,
+}
+
+impl OutputNote {
+    /// A note of `amount` for `address`, under a fresh blinding.
+    pub fn new(address: &PaymentAddress, amount: u64) -> Self {
+        OutputNote {
+            amount,
+            owner: address.spend.0,
+            blinding: random_field_bytes(),
+            transmission_key: address.transmission_key,
+            memo: Vec::new(),
+            clue_key: address.clue_key,
+            diversifier: address.diversifier,
+        }
+    }
+
+    /// The v3 commitment the program appends for this output.
+    pub fn commitment(&self, asset_id: &AssetId) -> [u8; 32] {
+        fr_to_bytes_32(v3_commit(
+            Fr::from(self.amount),
+            Fr::from_le_bytes_mod_order(&self.owner),
+            Fr::from_le_bytes_mod_order(&self.blinding),
+            Fr::from_le_bytes_mod_order(asset_id),
+        ))
+    }
+
+    /// The delivered ciphertext: the opening and memo, sealed to the
+    /// recipient as a v2 note, with the sender's record of it sealed to their
+    /// `outgoing_key`, behind a detection clue if the recipient has a clue key.
+    pub(crate) fn seal(&self, asset_id: &AssetId, outgoing_key: &[u8; 32]) -> Result<Vec<u8>> {
+        let plaintext = NotePlaintext {
+            amount: self.amount,
+            randomness: self.blinding,
+            recipient: self.owner,
+            asset_id: Some(*asset_id),
+            memo: self.memo.clone(),
+            diversifier: self.diversifier,
+        };
+        let note = match &self.diversifier {
+            Some(d) => encrypt_note_v2_diversified(&d.base(), &self.transmission_key, &plaintext),
+            None => encrypt_note_v2(&self.transmission_key, &plaintext),
+        }
+        .with_context(|| format!("memo is longer than {MAX_MEMO_LEN} bytes"))?;
+        let record = encrypt_outgoing(
+            outgoing_key,
+            &OutgoingPlaintext {
+                recipient: self.owner,
+                transmission_key: self.transmission_key,
+                amount: self.amount,
+                randomness: self.blinding,
+                diversifier: self.diversifier,
+            },
+        );
+        let envelope = note.to_bytes_with_outgoing(&record);
+        match &self.clue_key {
+            Some(clue_key) => {
+                let clue =
+                    Clue::create(clue_key).context("recipient clue key holds an invalid point")?;
+                attach_clue(&envelope, &clue).context("envelope already carries a clue")
+            }
+            None => Ok(envelope),
+        }
+    }
+}
+
+/// Funds leaving the pool: `amount` paid to `recipient`, a system account
+/// for native SOL or a token account of the mint for SPL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Withdrawal {
+    pub recipient: [u8; 32],
+    pub amount: u64,
+}
+
+/// A transact to prove: one or two inputs into two outputs, optionally
+/// paying some of the value out of the pool.
+#[derive(Clone, Debug)]
+pub struct TransactSpend {
+    /// One or two inputs, all under the same root and asset.
+    pub inputs: Vec<InputNote>,
+    pub outputs: [OutputNote; 2],
+    /// `None` for a private transfer.
+    pub withdrawal: Option<Withdrawal>,
+    /// SPL mint being spent; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+}
+
+impl TransactSpend {
+    /// The asset every input and output commits to.
+    pub fn asset_id(&self) -> AssetId {
+        asset_of(self.mint.as_ref())
+    }
+}
+
+/// The asset id of `mint`, or native SOL.
+pub(crate) fn asset_of(mint: Option<&[u8; 32]>) -> AssetId {
+    mint.map(mint_to_asset).unwrap_or(NATIVE_SOL_ASSET)
+}
+
+/// Prove `spend` and package it as a transact request. The proof is checked
+/// against the node's transact verifying key before it is returned, so a key
+/// mismatch or a witness bug fails here rather than as a silent consensus
+/// rejection.
+pub fn prove_transact(
+    keys: &ProverKeys,
+    spend: &TransactSpend,
+    proving_key: &ProvingKey<Bn254>,
+) -> Result<TransactVerificationRequest> {
+    if spend.inputs.is_empty() || spend.inputs.len() > TX_NINS {
+        bail!(
+            "a transact spends 1 to {} notes, got {}",
+            TX_NINS,
+            spend.inputs.len()
+        );
+    }
+    let (recipient, ext_amount) = match &spend.withdrawal {
+        Some(w) => {
+            let amount = i64::try_from(w.amount).context("withdrawal amount too large")?;
+            (w.recipient, -amount)
+        }
+        // Nothing is paid out, but the program still takes a system account
+        // here; a fresh random key keeps transfers from sharing one.
+        None => (rand::random(), 0),
+    };
+    let asset_id = spend.asset_id();
+    let witness = lay_out_witness(
+        keys,
+        &spend.inputs,
+        &spend.outputs,
+        &recipient,
+        ext_amount,
+        asset_id,
+    )?;
+    let (root, nullifiers, output_commitments) =
+        (witness.root, witness.nullifiers, witness.output_commitments);
+    let sealed = prove_and_seal(
+        keys,
+        witness.circuit,
+        ProofSuite::Groth16Bn254TransactV3,
+        &spend.outputs,
+        &asset_id,
+        proving_key,
+    )?;
+
+    let verdict = ProofVerifier::verify_transact_parts(
+        &root,
+        &recipient,
+        ext_amount,
+        &asset_id,
+        &nullifiers,
+        &output_commitments,
+        &sealed.proof,
+        None,
+    );
+    if !verdict.is_valid() {
+        bail!("proof failed self-verification: {:?}", verdict);
+    }
+
+    let [c0, c1] = sealed.ciphertexts;
+    let mut request = TransactVerificationRequest {
+        request_id: String::new(),
+        recipient,
+        mint: spend.mint,
+        nullifiers,
+        output_commitments,
+        root,
+        ext_amount,
+        proof: sealed.proof,
+        ciphertexts: [hex::encode(c0), hex::encode(c1)],
+        timestamp: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        // The ingress node stamps the slot it admits the request at.
+        slot: None,
+    };
+    request.request_id = request.canonical_id();
+    Ok(request)
+}
+
+/// The circuit witness plus the public parts a submission carries.
+pub(crate) struct Witness {
+    pub(crate) circuit: TransactCircuitV3,
+    pub(crate) root: [u8; 32],
+    /// One per circuit input, dummies included.
+    pub(crate) nullifiers: [[u8; 32]; 2],
+    pub(crate) output_commitments: [[u8; 32]; 2],
+}
+
+/// A tagged proof and the two sealed outputs it commits to.
+pub(crate) struct Sealed {
+    pub(crate) proof: Vec<u8>,
+    /// Canonical `EncryptedNote` bytes, one per output.
+    pub(crate) ciphertexts: [Vec<u8>; 2],
+}
+
+/// Prove `circuit` under `proving_key`, tag the proof as `suite` and seal
+/// both outputs.
+pub(crate) fn prove_and_seal(
+    keys: &ProverKeys,
+    circuit: TransactCircuitV3,
+    suite: ProofSuite,
+    outputs: &[OutputNote; 2],
+    asset_id: &AssetId,
+    proving_key: &ProvingKey<Bn254>,
+) -> Result<Sealed> {
+    let proof = Groth16ProofSystem::prove(proving_key, circuit, &mut ark_std::rand::thread_rng())
+        .map_err(|e| anyhow!("proving failed: {}", e))?;
+    let mut body = Vec::new();
+    proof.serialize_compressed(&mut body)?;
+    Ok(Sealed {
+        proof: tag_proof(suite, &body),
+        ciphertexts: [
+            outputs[0].seal(asset_id, &keys.outgoing_key)?,
+            outputs[1].seal(asset_id, &keys.outgoing_key)?,
+        ],
+    })
+}
+
+/// Check a spend of `inputs` into `outputs` is provable and lay out its
+/// witness, padding to [`TX_NINS`] inputs with zero-value dummies.
+/// Errors are the mistakes the circuit would otherwise reject with an
+/// unhelpful "constraints not satisfied".
+pub(crate) fn lay_out_witness(
+    keys: &ProverKeys,
+    inputs: &[InputNote],
+    outputs: &[OutputNote; 2],
+    recipient: &[u8; 32],
+    ext_amount: i64,
+    asset_id: AssetId,
+) -> Result<Witness> {
+    let first = inputs.first().context("no input notes")?;
+    if inputs.len() > TX_NINS {
+        bail!(
+            "{} inputs do not fit a {}-input circuit",
+            inputs.len(),
+            TX_NINS
+        );
+    }
+    let root = first.membership.root;
+    for input in inputs {
+        if input.asset_id != asset_id {
+            bail!("input notes must all be of the asset being spent");
+        }
+        if input.membership.root != root {
+            bail!("input paths were served under different roots");
+        }
+        if input.membership.fold(&input.commitment) != root {
+            bail!(
+                "path for leaf {} does not open note {}",
+                input.membership.leaf_index,
+                hex::encode(input.commitment)
+            );
+        }
+    }
+
+    let total_in: i128 = inputs.iter().map(|i| i.amount as i128).sum();
+    let total_out: i128 = outputs.iter().map(|o| o.amount as i128).sum();
+    if total_in + ext_amount as i128 != total_out {
+        bail!(
+            "unbalanced spend: inputs {} + ext_amount {} != outputs {}",
+            total_in,
+            ext_amount,
+            total_out
+        );
+    }
+
+    let asset = Fr::from_le_bytes_mod_order(&asset_id);
+    let mut in_amounts = Vec::with_capacity(TX_NINS);
+    let mut in_privkeys = Vec::with_capacity(TX_NINS);
+    let mut in_blindings = Vec::with_capacity(TX_NINS);
+    let mut in_leaf_indices = Vec::with_capacity(TX_NINS);
+    let mut in_paths = Vec::with_capacity(TX_NINS);
+    let mut nullifiers = Vec::with_capacity(TX_NINS);
+
+    for input in inputs {
+        let sk = Fr::from_le_bytes_mod_order(&keys.spend_key_for(input.diversifier.as_ref()));
+        let c = Fr::from_le_bytes_mod_order(&input.commitment);
+        let idx = Fr::from(input.membership.leaf_index);
+        nullifiers.push(fr_to_bytes_32(v3_nullifier(
+            c,
+            idx,
+            v3_signature(sk, c, idx),
+        )));
+        in_amounts.push(Some(input.amount));
+        in_privkeys.push(Some(fr_to_bytes_32(sk)));
+        in_blindings.push(Some(input.blinding));
+        in_leaf_indices.push(Some(input.membership.leaf_index));
+        in_paths.push(Some(input.membership.siblings.clone()));
+    }
+    while nullifiers.len() < TX_NINS {
+        // Zero-value dummy; its path is never checked, so any
+        // well-formed one will do. Each gets its own random key, so
+        // dummies' nullifiers differ from each other too.
+        let dsk = Fr::from_le_bytes_mod_order(&random_field_bytes());
+        let dbl = random_field_bytes();
+        let dc = v3_commit(
+            Fr::from(0u64),
+            v3_pubkey(dsk),
+            Fr::from_le_bytes_mod_order(&dbl),
+            asset,
+        );
+        let zero = Fr::from(0u64);
+        nullifiers.push(fr_to_bytes_32(v3_nullifier(
+            dc,
+            zero,
+            v3_signature(dsk, dc, zero),
+        )));
+        in_amounts.push(Some(0));
+        in_privkeys.push(Some(fr_to_bytes_32(dsk)));
+        in_blindings.push(Some(dbl));
+        in_leaf_indices.push(Some(0));
+        in_paths.push(Some(first.membership.siblings.clone()));
+    }
+
+    let output_commitments = [
+        outputs[0].commitment(&asset_id),
+        outputs[1].commitment(&asset_id),
+    ];
+    let magnitude = Fr::from(ext_amount.unsigned_abs());
+    let public_amount = if ext_amount < 0 {
+        -magnitude
+    } else {
+        magnitude
+    };
+
+    let circuit = TransactCircuitV3 {
+        root: Some(root),
+        public_amount: Some(fr_to_bytes_32(public_amount)),
+        ext_data_hash: Some(transact_ext_data_hash(recipient, ext_amount)),
+        asset_id: Some(asset_id),
+        input_nullifiers: nullifiers.iter().copied().map(Some).collect(),
+        output_commitments: output_commitments.iter().copied().map(Some).collect(),
+        in_amounts,
+        in_privkeys,
+        in_blindings,
+        in_leaf_indices,
+        in_paths,
+        out_amounts: outputs.iter().map(|o| Some(o.amount)).collect(),
+        out_pubkeys: outputs.iter().map(|o| Some(o.owner)).collect(),
+        out_blindings: outputs.iter().map(|o| Some(o.blinding)).collect(),
+    };
+
+    Ok(Witness {
+        circuit,
+        root,
+        nullifiers: [nullifiers[0], nullifiers[1]],
+        output_commitments,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::merkle::MerkleTree;
+    use crate::privacy::types::Commitment;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    fn keys() -> ProverKeys {
+        ProverKeys {
+            spend_key: random_field_bytes(),
+            outgoing_key: rand::random(),
+        }
+    }
+
+    /// An address whose notes `keys` can spend. Nothing here opens the
+    /// sealed notes, so the transmission key is arbitrary.
+    fn address_of(keys: &ProverKeys) -> PaymentAddress {
+        PaymentAddress::new(
+            crate::privacy::types::ShieldedAddress(fr_to_bytes_32(v3_pubkey(
+                Fr::from_le_bytes_mod_order(&keys.spend_key),
+            ))),
+            [9; 32],
+        )
+    }
+
+    async fn spendable(keys: &ProverKeys, amounts: &[u64]) -> Vec<InputNote> {
+        let tree = MerkleTree::new();
+        let mut openings = Vec::new();
+        for &amount in amounts {
+            let blinding = random_field_bytes();
+            let unplaced = OnchainMembership {
+                leaf_index: 0,
+                root: [0; 32],
+                siblings: Vec::new(),
+            };
+            let probe = InputNote::new(keys, amount, blinding, NATIVE_SOL_ASSET, unplaced);
+            tree.insert(&Commitment(probe.commitment)).await.unwrap();
+            openings.push(probe);
+        }
+        let mut inputs = Vec::new();
+        for mut note in openings {
+            note.membership = tree
+                .onchain_membership(&Commitment(note.commitment))
+                .await
+                .unwrap();
+            inputs.push(note);
+        }
+        inputs
+    }
+
+    fn satisfied(circuit: TransactCircuitV3) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[tokio::test]
+    async fn padded_witness_satisfies_the_circuit() {
+        let keys = keys();
+        let inputs = spendable(&keys, &[700]).await;
+        let outputs = [
+            OutputNote::new(&address_of(&keys), 300),
+            OutputNote::new(&address_of(&keys), 100),
+        ];
+        let witness =
+            lay_out_witness(&keys, &inputs, &outputs, &[4; 32], -300, NATIVE_SOL_ASSET).unwrap();
+        assert_ne!(witness.nullifiers[0], witness.nullifiers[1]);
+        assert!(satisfied(witness.circuit));
+    }
+
+    #[tokio::test]
+    async fn a_note_paid_to_a_diversified_address_spends_under_its_key() {
+        let keys = keys();
+        let d = Diversifier::derive(&keys.spend_key, &[3; 32], 7);
+        let tree = MerkleTree::new();
+        let unplaced = OnchainMembership {
+            leaf_index: 0,
+            root: [0; 32],
+            siblings: Vec::new(),
+        };
+        let mut note = InputNote::paid_to(
+            &keys,
+            Some(d),
+            400,
+            random_field_bytes(),
+            NATIVE_SOL_ASSET,
+            unplaced,
+        );
+        assert_ne!(
+            note.commitment,
+            InputNote::new(
+                &keys,
+                400,
+                note.blinding,
+                NATIVE_SOL_ASSET,
+                note.membership.clone()
+            )
+            .commitment
+        );
+        tree.insert(&Commitment(note.commitment)).await.unwrap();
+        note.membership = tree
+            .onchain_membership(&Commitment(note.commitment))
+            .await
+            .unwrap();
+        let outputs = [
+            OutputNote::new(&address_of(&keys), 400),
+            OutputNote::new(&address_of(&keys), 0),
+        ];
+        let witness =
+            lay_out_witness(&keys, &[note], &outputs, &[0; 32], 0, NATIVE_SOL_ASSET).unwrap();
+        assert!(satisfied(witness.circuit));
+    }
+
+    #[tokio::test]
+    async fn a_note_owned_by_another_key_does_not_open() {
+        let owner = keys();
+        let mut inputs = spendable(&owner, &[500]).await;
+        let outputs = [
+            OutputNote::new(&address_of(&owner), 500),
+            OutputNote::new(&address_of(&owner), 0),
+        ];
+        // Recomputing the commitment under a stranger's key no longer
+        // matches the leaf the path was served for.
+        let stranger = keys();
+        let note = inputs[0].clone();
+        inputs[0] = InputNote::new(
+            &stranger,
+            note.amount,
+            note.blinding,
+            note.asset_id,
+            note.membership,
+        );
+        let err = lay_out_witness(&stranger, &inputs, &outputs, &[0; 32], 0, NATIVE_SOL_ASSET)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("does not open"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn more_inputs_than_the_circuit_takes_are_refused() {
+        let keys = keys();
+        let inputs = spendable(&keys, &[1, 2, 3]).await;
+        let outputs = [
+            OutputNote::new(&address_of(&keys), 6),
+            OutputNote::new(&address_of(&keys), 0),
+        ];
+        let err = lay_out_witness(&keys, &inputs, &outputs, &[0; 32], 0, NATIVE_SOL_ASSET)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("do not fit"), "{err}");
+    }
+}
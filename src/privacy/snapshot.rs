@@ -0,0 +1,414 @@
+//! Fast-sync snapshots of the shielded pool.
+//!
+//! A new bridge node otherwise rebuilds its pool by replaying every program
+//! transaction through the listener. A [`PoolSnapshot`] carries what that
+//! replay would produce — the commitments in leaf order, the revealed
+//! nullifiers and the per-asset supplies — together with the listener
+//! cursor it reflects, so the node resumes from there instead.
+//!
+//! A snapshot is only a claim. Its commitments are checked against the two
+//! roots it names when it is imported, and the on-chain root must then be
+//! one the program itself has held, and the nullifiers the ones it had
+//! recorded (`ProgramInterface::verify_snapshot` in the Solana bridge). The
+//! supplies are carried as they are: the program keeps no per-asset record
+//! to check them against.
+//!
+//! Between peers a snapshot moves as its encoding cut into
+//! [`SNAPSHOT_CHUNK_BYTES`] chunks. The [`SnapshotManifest`] lists each
+//! chunk's SHA-256, so [`SnapshotAssembler`] rejects a bad chunk on arrival
+//! rather than after the whole download.
+
+use crate::privacy::merkle::MerkleTree;
+use crate::privacy::types::{AssetId, Commitment, Nullifier};
+use crate::storage::PrivacyStorage;
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Encoding version written into every snapshot and manifest.
+pub const SNAPSHOT_VERSION: u16 = 1;
+
+/// Size of every chunk but the last.
+pub const SNAPSHOT_CHUNK_BYTES: usize = 1024 * 1024;
+
+/// Largest encoded snapshot a manifest may announce (4 GiB, about 130
+/// million leaves).
+pub const MAX_SNAPSHOT_BYTES: u64 = 1 << 32;
+
+/// Leaves written to storage per batch on import.
+const IMPORT_BATCH: usize = 10_000;
+
+/// How far the bridge listener had got when a snapshot was taken.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotCursor {
+    /// Slot the listener had scanned to. For operators; indexing resumes
+    /// from `signature`.
+    pub slot: u64,
+    /// The newest program transaction the listener had processed, base58;
+    /// `None` before the first one.
+    pub signature: Option<String>,
+}
+
+/// The contents of a shielded pool at one listener cursor.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSnapshot {
+    pub version: u16,
+    pub cursor: SnapshotCursor,
+    /// Leaves in the order the program appended them.
+    pub commitments: Vec<[u8; 32]>,
+    /// Revealed nullifiers, sorted.
+    pub nullifiers: Vec<[u8; 32]>,
+    /// Non-zero supplies, sorted by asset.
+    pub supplies: Vec<(AssetId, u64)>,
+    /// Root under the pool's domain-separated hash.
+    pub root: [u8; 32],
+    /// Root under the program's hash; the one the chain can vouch for.
+    pub onchain_root: [u8; 32],
+}
+
+impl PoolSnapshot {
+    /// Encode for a file or for [`Self::encode`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        Ok(bincode::serialize(self)?)
+    }
+
+    /// Decode a snapshot written by [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let snapshot: PoolSnapshot = bincode::deserialize(bytes)?;
+        if snapshot.version != SNAPSHOT_VERSION {
+            bail!(
+                "snapshot version {} is not supported (expected {})",
+                snapshot.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        Ok(snapshot)
+    }
+
+    /// Encode and cut into chunks for serving to peers.
+    pub fn encode(&self) -> Result<EncodedSnapshot> {
+        let bytes = self.to_bytes()?;
+        let chunk_hashes = bytes
+            .chunks(SNAPSHOT_CHUNK_BYTES)
+            .map(|chunk| Sha256::digest(chunk).into())
+            .collect();
+        let manifest = SnapshotManifest {
+            version: SNAPSHOT_VERSION,
+            cursor: self.cursor.clone(),
+            leaf_count: self.commitments.len() as u64,
+            onchain_root: self.onchain_root,
+            total_bytes: bytes.len() as u64,
+            chunk_hashes,
+        };
+        Ok(EncodedSnapshot {
+            id: manifest.id(),
+            manifest,
+            bytes,
+        })
+    }
+
+    /// Rebuild the tree in memory and check that the commitments produce
+    /// both roots the snapshot names.
+    pub async fn verify_roots(&self) -> Result<()> {
+        let tree = MerkleTree::new();
+        for batch in self.commitments.chunks(IMPORT_BATCH) {
+            tree.insert_batch(&to_commitments(batch)).await?;
+        }
+        self.check_roots(&tree).await
+    }
+
+    /// Write the snapshot into an empty privacy store, checking the roots
+    /// as the tree is rebuilt. On error the store holds part of the
+    /// snapshot and should be discarded.
+    pub async fn import(&self, storage: &Arc<PrivacyStorage>) -> Result<()> {
+        if !storage.get_all_commitments()?.is_empty() || !storage.get_all_nullifiers()?.is_empty() {
+            bail!("a snapshot can only be imported into an empty privacy store");
+        }
+
+        let tree = MerkleTree::with_storage(Arc::clone(storage)).await?;
+        for batch in self.commitments.chunks(IMPORT_BATCH) {
+            tree.insert_batch(&to_commitments(batch)).await?;
+        }
+        self.check_roots(&tree).await?;
+
+        let nullifiers: Vec<Nullifier> = self.nullifiers.iter().copied().map(Nullifier).collect();
+        storage.insert_nullifiers_batch(&nullifiers)?;
+        for (asset_id, supply) in &self.supplies {
+            storage.set_asset_supply(asset_id, *supply)?;
+        }
+        Ok(())
+    }
+
+    async fn check_roots(&self, tree: &MerkleTree) -> Result<()> {
+        let root = tree.root().await;
+        if root != self.root {
+            bail!(
+                "snapshot commitments hash to root {}, not the {} it names",
+                hex::encode(root),
+                hex::encode(self.root)
+            );
+        }
+        let onchain_root = tree.onchain_root().await;
+        if onchain_root != self.onchain_root {
+            bail!(
+                "snapshot commitments hash to on-chain root {}, not the {} it names",
+                hex::encode(onchain_root),
+                hex::encode(self.onchain_root)
+            );
+        }
+        Ok(())
+    }
+}
+
+fn to_commitments(leaves: &[[u8; 32]]) -> Vec<Commitment> {
+    leaves.iter().copied().map(Commitment).collect()
+}
+
+/// What a peer serving a snapshot announces before sending any chunk.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u16,
+    pub cursor: SnapshotCursor,
+    pub leaf_count: u64,
+    pub onchain_root: [u8; 32],
+    /// Length of the encoded snapshot.
+    pub total_bytes: u64,
+    /// SHA-256 of each chunk, in order.
+    pub chunk_hashes: Vec<[u8; 32]>,
+}
+
+impl SnapshotManifest {
+    /// Names the snapshot in chunk requests, so a peer that has since moved
+    /// to a newer snapshot declines rather than mixing the two.
+    pub fn id(&self) -> [u8; 32] {
+        let bytes = bincode::serialize(self).expect("manifest serializes");
+        Sha256::digest(bytes).into()
+    }
+}
+
+/// A snapshot encoded and ready to serve.
+#[derive(Clone, Debug)]
+pub struct EncodedSnapshot {
+    pub manifest: SnapshotManifest,
+    id: [u8; 32],
+    bytes: Vec<u8>,
+}
+
+impl EncodedSnapshot {
+    /// The manifest's [`id`](SnapshotManifest::id).
+    pub fn id(&self) -> [u8; 32] {
+        self.id
+    }
+
+    /// The `index`th chunk, if there is one.
+    pub fn chunk(&self, index: u32) -> Option<&[u8]> {
+        self.bytes.chunks(SNAPSHOT_CHUNK_BYTES).nth(index as usize)
+    }
+}
+
+/// Puts a snapshot back together from chunks fetched in any order.
+pub struct SnapshotAssembler {
+    manifest: SnapshotManifest,
+    chunks: Vec<Option<Vec<u8>>>,
+}
+
+impl SnapshotAssembler {
+    /// Start assembling the snapshot `manifest` describes, after checking
+    /// that its chunk list and length agree.
+    pub fn new(manifest: SnapshotManifest) -> Result<Self> {
+        if manifest.version != SNAPSHOT_VERSION {
+            bail!(
+                "snapshot version {} is not supported (expected {})",
+                manifest.version,
+                SNAPSHOT_VERSION
+            );
+        }
+        if manifest.total_bytes > MAX_SNAPSHOT_BYTES {
+            bail!(
+                "snapshot of {} bytes exceeds the {} byte limit",
+                manifest.total_bytes,
+                MAX_SNAPSHOT_BYTES
+            );
+        }
+        let expected = manifest.total_bytes.div_ceil(SNAPSHOT_CHUNK_BYTES as u64);
+        if manifest.chunk_hashes.len() as u64 != expected {
+            bail!(
+                "manifest lists {} chunks for {} bytes, expected {}",
+                manifest.chunk_hashes.len(),
+                manifest.total_bytes,
+                expected
+            );
+        }
+        let chunks = vec![None; manifest.chunk_hashes.len()];
+        Ok(SnapshotAssembler { manifest, chunks })
+    }
+
+    pub fn manifest(&self) -> &SnapshotManifest {
+        &self.manifest
+    }
+
+    /// Indices of the chunks not yet accepted.
+    pub fn missing(&self) -> Vec<u32> {
+        (0..self.chunks.len() as u32)
+            .filter(|&i| self.chunks[i as usize].is_none())
+            .collect()
+    }
+
+    /// Take chunk `index`, if it hashes to what the manifest lists.
+    pub fn accept(&mut self, index: u32, chunk: Vec<u8>) -> Result<()> {
+        let expected = self
+            .manifest
+            .chunk_hashes
+            .get(index as usize)
+            .ok_or_else(|| anyhow!("chunk {} is beyond the manifest", index))?;
+        let actual: [u8; 32] = Sha256::digest(&chunk).into();
+        if &actual != expected {
+            bail!("chunk {} does not match its manifest hash", index);
+        }
+        self.chunks[index as usize] = Some(chunk);
+        Ok(())
+    }
+
+    /// Decode the snapshot once every chunk is in, and check it is the one
+    /// the manifest announced.
+    pub fn finish(self) -> Result<PoolSnapshot> {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            bail!("{} snapshot chunks are still missing", missing.len());
+        }
+        let bytes: Vec<u8> = self.chunks.into_iter().flatten().flatten().collect();
+        if bytes.len() as u64 != self.manifest.total_bytes {
+            bail!(
+                "assembled {} bytes, manifest announced {}",
+                bytes.len(),
+                self.manifest.total_bytes
+            );
+        }
+        let snapshot = PoolSnapshot::from_bytes(&bytes)?;
+        if snapshot.cursor != self.manifest.cursor
+            || snapshot.commitments.len() as u64 != self.manifest.leaf_count
+            || snapshot.onchain_root != self.manifest.onchain_root
+        {
+            bail!("assembled snapshot does not match its manifest");
+        }
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::pool::ShieldedPool;
+    use crate::privacy::types::{Note, ShieldedAddress};
+
+    fn cursor() -> SnapshotCursor {
+        SnapshotCursor {
+            slot: 812,
+            signature: Some("5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".into()),
+        }
+    }
+
+    async fn pool_with_history(pool: &ShieldedPool) {
+        for i in 0..5u8 {
+            let note = Note::new_native(ShieldedAddress([i; 32]), 100 * (i as u64 + 1), [i; 32]);
+            pool.deposit(note, 100 * (i as u64 + 1)).await.unwrap();
+        }
+        let spl = Note::new_native(ShieldedAddress([9; 32]), 40, [9; 32]);
+        pool.deposit_asset(spl, 40, [3; 32]).await.unwrap();
+        pool.append_outputs(Some(6), &[Commitment([0xaa; 32]), Commitment([0xbb; 32])])
+            .await
+            .unwrap();
+        pool.record_spent([[0x11; 32], [0x22; 32]]).await;
+    }
+
+    #[tokio::test]
+    async fn snapshot_survives_chunking_and_imports_into_the_same_pool() {
+        let pool = ShieldedPool::new();
+        pool_with_history(&pool).await;
+        let snapshot = pool.snapshot(cursor()).await.unwrap();
+        assert_eq!(snapshot.commitments.len(), 8);
+        assert_eq!(snapshot.nullifiers, vec![[0x11; 32], [0x22; 32]]);
+        snapshot.verify_roots().await.unwrap();
+
+        let encoded = snapshot.encode().unwrap();
+        let mut assembler = SnapshotAssembler::new(encoded.manifest.clone()).unwrap();
+        for index in assembler.missing().into_iter().rev() {
+            let chunk = encoded.chunk(index).unwrap().to_vec();
+            assembler.accept(index, chunk).unwrap();
+        }
+        let assembled = assembler.finish().unwrap();
+        assert_eq!(assembled, snapshot);
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(PrivacyStorage::open(dir.path()).unwrap());
+        assembled.import(&storage).await.unwrap();
+        let restored = ShieldedPool::with_storage(storage).await.unwrap();
+        assert_eq!(restored.root().await, pool.root().await);
+        assert_eq!(restored.onchain_root().await, pool.onchain_root().await);
+        assert_eq!(restored.all_supplies().await, pool.all_supplies().await);
+        assert!(restored.is_spent(&Nullifier([0x22; 32])).await);
+
+        // A replay of a settlement the snapshot already covers is a no-op.
+        let replayed = restored
+            .append_outputs(Some(6), &[Commitment([0xaa; 32]), Commitment([0xbb; 32])])
+            .await
+            .unwrap();
+        assert!(replayed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn commitments_that_miss_the_named_roots_are_refused() {
+        let pool = ShieldedPool::new();
+        pool_with_history(&pool).await;
+        let mut snapshot = pool.snapshot(cursor()).await.unwrap();
+        snapshot.commitments.swap(0, 1);
+        assert!(snapshot.verify_roots().await.is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let storage = Arc::new(PrivacyStorage::open(dir.path()).unwrap());
+        assert!(snapshot.import(&storage).await.is_err());
+        // The store now holds the rejected leaves; a second import refuses it.
+        snapshot.commitments.swap(0, 1);
+        assert!(snapshot.import(&storage).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn assembler_rejects_bad_chunks_and_inconsistent_manifests() {
+        let mut snapshot = ShieldedPool::new().snapshot(cursor()).await.unwrap();
+        // Enough leaves to span several chunks.
+        snapshot.commitments = (0..80_000u32)
+            .map(|i| {
+                let mut leaf = [0u8; 32];
+                leaf[..4].copy_from_slice(&i.to_le_bytes());
+                leaf
+            })
+            .collect();
+        let encoded = snapshot.encode().unwrap();
+        assert_eq!(encoded.manifest.chunk_hashes.len(), 3);
+
+        let mut assembler = SnapshotAssembler::new(encoded.manifest.clone()).unwrap();
+        let mut tampered = encoded.chunk(1).unwrap().to_vec();
+        tampered[0] ^= 1;
+        assert!(assembler.accept(1, tampered).is_err());
+        assert!(assembler.accept(3, Vec::new()).is_err());
+        assembler
+            .accept(0, encoded.chunk(0).unwrap().to_vec())
+            .unwrap();
+        assert_eq!(assembler.missing(), vec![1, 2]);
+
+        let mut short = encoded.manifest.clone();
+        short.chunk_hashes.pop();
+        assert!(SnapshotAssembler::new(short).is_err());
+
+        // Chunks that hash correctly but belong to a different claim.
+        let mut lying = encoded.manifest.clone();
+        lying.leaf_count += 1;
+        let mut assembler = SnapshotAssembler::new(lying).unwrap();
+        for index in 0..3 {
+            let chunk = encoded.chunk(index).unwrap().to_vec();
+            assembler.accept(index, chunk).unwrap();
+        }
+        assert!(assembler.finish().is_err());
+    }
+}
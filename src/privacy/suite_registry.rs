@@ -0,0 +1,464 @@
+//! Registry of the proof suites a node accepts.
+//!
+//! A settlement proof names its suite in the envelope tag (see
+//! [`proof_codec`](crate::privacy::proof_codec)). The registry maps each
+//! accepted tag to the circuit it was proved under, that circuit's
+//! public-input layout, the verifying key to check it with, and the slot
+//! window during which the tag is accepted. Verification resolves the tag
+//! here and nowhere else.
+//!
+//! The registry comes from the `[[privacy.suites]]` tables of the node
+//! config. Without any, a node accepts the built-in suites under their
+//! default tags and key paths, as before. A key rotation after a new
+//! ceremony is then a config change rather than a flag day: register the
+//! new key under a fresh tag with an `activation_slot`, give the old tag a
+//! `deactivation_slot`, and both are accepted in between.
+//!
+//! ```toml
+//! [[privacy.suites]]
+//! tag = 1
+//! circuit = "groth16_bn254_transact_v3"
+//! verifying_key = "keys/transact_v3_verifying.key"
+//! deactivation_slot = 352000000
+//!
+//! [[privacy.suites]]
+//! tag = 3
+//! circuit = "groth16_bn254_transact_v3"
+//! verifying_key = "keys/transact_v3_ceremony2_verifying.key"
+//! activation_slot = 351000000
+//! ```
+//!
+//! A proof is judged at the slot its request was admitted at
+//! ([`TransactVerificationRequest::slot`](crate::consensus::transact::TransactVerificationRequest::slot)),
+//! not at whatever slot the verifying node last saw, so every validator
+//! near a cutover reaches the same verdict. A request without a slot treats
+//! every activation as still ahead and every deactivation as not yet
+//! reached.
+
+use crate::privacy::proof::{load_verifying_key, resolve_verifying_key_path, KeyLoadError};
+use crate::privacy::proof_codec::{split_envelope, ProofSuite, PublicInputLayout};
+use ark_bn254::Bn254;
+use ark_groth16::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use thiserror::Error;
+
+/// One `[[privacy.suites]]` table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuiteConfig {
+    /// Envelope tag the suite's proofs carry. Zero is never a valid tag.
+    pub tag: u8,
+    /// Circuit and proof system the tag's proofs are checked under.
+    pub circuit: ProofSuite,
+    /// Verifying key file. `None` uses the circuit's default path (and its
+    /// environment override).
+    #[serde(default)]
+    pub verifying_key: Option<PathBuf>,
+    /// First slot at which the tag is accepted. `None` means from the start.
+    #[serde(default)]
+    pub activation_slot: Option<u64>,
+    /// First slot at which the tag is no longer accepted. `None` means never.
+    #[serde(default)]
+    pub deactivation_slot: Option<u64>,
+}
+
+/// Why a registry could not be built or a proof could not be resolved.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SuiteRegistryError {
+    #[error("proof suite tag 0 is reserved")]
+    ReservedTag,
+
+    #[error("proof suite tag {0} is registered twice")]
+    DuplicateTag(u8),
+
+    #[error("proof suite tag {tag} deactivates at slot {deactivation} before it activates at {activation}")]
+    EmptyWindow {
+        tag: u8,
+        activation: u64,
+        deactivation: u64,
+    },
+
+    #[error("a different proof suite registry is already installed")]
+    AlreadyInstalled,
+
+    #[error("empty proof blob")]
+    EmptyProof,
+
+    #[error("unknown proof suite tag {0}")]
+    UnknownTag(u8),
+
+    #[error("proof suite tag {tag} is not active at slot {slot:?}")]
+    Inactive { tag: u8, slot: Option<u64> },
+
+    #[error("proof suite tag {tag} expects a {expected}-byte body, got {found}")]
+    BodyLength {
+        tag: u8,
+        expected: usize,
+        found: usize,
+    },
+}
+
+/// An accepted suite, with its verifying key loaded on first use.
+#[derive(Debug)]
+pub struct RegisteredSuite {
+    pub tag: u8,
+    pub circuit: ProofSuite,
+    pub layout: PublicInputLayout,
+    pub verifying_key_path: PathBuf,
+    pub activation_slot: Option<u64>,
+    pub deactivation_slot: Option<u64>,
+    /// Failed loads are not cached, so a key file restored after a
+    /// misconfiguration is picked up without a restart.
+    key: OnceLock<VerifyingKey<Bn254>>,
+}
+
+impl RegisteredSuite {
+    fn from_config(config: &SuiteConfig) -> Self {
+        RegisteredSuite {
+            tag: config.tag,
+            circuit: config.circuit,
+            layout: config.circuit.layout(),
+            verifying_key_path: config
+                .verifying_key
+                .clone()
+                .unwrap_or_else(|| resolve_verifying_key_path(config.circuit)),
+            activation_slot: config.activation_slot,
+            deactivation_slot: config.deactivation_slot,
+            key: OnceLock::new(),
+        }
+    }
+
+    /// Whether proofs under this tag are accepted at `slot` (`None` when the
+    /// request carries no slot).
+    pub fn is_active_at(&self, slot: Option<u64>) -> bool {
+        let started = match (self.activation_slot, slot) {
+            (None, _) => true,
+            (Some(activation), Some(slot)) => slot >= activation,
+            (Some(_), None) => false,
+        };
+        let ended = match (self.deactivation_slot, slot) {
+            (Some(deactivation), Some(slot)) => slot >= deactivation,
+            _ => false,
+        };
+        started && !ended
+    }
+
+    /// The suite's verifying key, loaded from [`Self::verifying_key_path`]
+    /// and cached on success. A key whose IC length does not fit the
+    /// layout is refused: it belongs to another circuit.
+    pub fn verifying_key(&self) -> Result<&VerifyingKey<Bn254>, KeyLoadError> {
+        if let Some(vk) = self.key.get() {
+            return Ok(vk);
+        }
+        let key = load_verifying_key(&self.verifying_key_path).inspect_err(|e| {
+            log::error!(
+                target: "paraloom::privacy::proof",
+                "verifying key load for proof suite {} failed: {}",
+                self.tag,
+                e
+            );
+        })?;
+        let expected = self.layout.public_inputs() + 1;
+        if key.gamma_abc_g1.len() != expected {
+            return Err(KeyLoadError::LayoutMismatch {
+                path: self.verifying_key_path.clone(),
+                expected,
+                found: key.gamma_abc_g1.len(),
+            });
+        }
+        if self.key.set(key).is_err() {
+            log::debug!(
+                target: "paraloom::privacy::proof",
+                "verifying key for proof suite {} was already cached by a concurrent caller",
+                self.tag
+            );
+        }
+        Ok(self.key.get().expect("verifying key cache populated above"))
+    }
+}
+
+/// The accepted suites, by tag.
+#[derive(Debug)]
+pub struct SuiteRegistry {
+    suites: BTreeMap<u8, RegisteredSuite>,
+    /// The configuration this registry was built from, normalized so the
+    /// built-in default compares equal to an empty config.
+    config: Vec<SuiteConfig>,
+}
+
+impl SuiteRegistry {
+    /// The built-in suites under their default tags and key paths.
+    pub fn builtin() -> Self {
+        Self::from_config(&[]).expect("built-in proof suites are well formed")
+    }
+
+    fn builtin_config() -> Vec<SuiteConfig> {
+        [ProofSuite::Groth16Bn254TransactV3]
+            .into_iter()
+            .map(|circuit| SuiteConfig {
+                tag: circuit.tag(),
+                circuit,
+                verifying_key: None,
+                activation_slot: None,
+                deactivation_slot: None,
+            })
+            .collect()
+    }
+
+    /// Build a registry from `[[privacy.suites]]`. An empty list means the
+    /// built-in suites.
+    pub fn from_config(config: &[SuiteConfig]) -> Result<Self, SuiteRegistryError> {
+        let config = if config.is_empty() {
+            Self::builtin_config()
+        } else {
+            config.to_vec()
+        };
+        let mut suites = BTreeMap::new();
+        for entry in &config {
+            if entry.tag == 0 {
+                return Err(SuiteRegistryError::ReservedTag);
+            }
+            if let (Some(activation), Some(deactivation)) =
+                (entry.activation_slot, entry.deactivation_slot)
+            {
+                if deactivation <= activation {
+                    return Err(SuiteRegistryError::EmptyWindow {
+                        tag: entry.tag,
+                        activation,
+                        deactivation,
+                    });
+                }
+            }
+            if suites
+                .insert(entry.tag, RegisteredSuite::from_config(entry))
+                .is_some()
+            {
+                return Err(SuiteRegistryError::DuplicateTag(entry.tag));
+            }
+        }
+        Ok(SuiteRegistry { suites, config })
+    }
+
+    pub fn get(&self, tag: u8) -> Option<&RegisteredSuite> {
+        self.suites.get(&tag)
+    }
+
+    pub fn suites(&self) -> impl Iterator<Item = &RegisteredSuite> {
+        self.suites.values()
+    }
+
+    /// Split a wire proof and resolve its tag: the suite must be registered
+    /// and active at `slot`, and the body must have the circuit's length.
+    pub fn resolve<'a>(
+        &self,
+        wire: &'a [u8],
+        slot: Option<u64>,
+    ) -> Result<(&RegisteredSuite, &'a [u8]), SuiteRegistryError> {
+        let (tag, body) = split_envelope(wire).map_err(|_| SuiteRegistryError::EmptyProof)?;
+        let suite = self
+            .suites
+            .get(&tag)
+            .ok_or(SuiteRegistryError::UnknownTag(tag))?;
+        if !suite.is_active_at(slot) {
+            return Err(SuiteRegistryError::Inactive { tag, slot });
+        }
+        if body.len() != suite.circuit.body_len() {
+            return Err(SuiteRegistryError::BodyLength {
+                tag,
+                expected: suite.circuit.body_len(),
+                found: body.len(),
+            });
+        }
+        Ok((suite, body))
+    }
+}
+
+static REGISTRY: OnceLock<SuiteRegistry> = OnceLock::new();
+
+/// Install the process-wide registry. Installing the same configuration
+/// again is a no-op (several nodes may share a process in tests and
+/// demos); a different one is refused, since proofs may already have been
+/// checked against the first.
+pub fn install(registry: SuiteRegistry) -> Result<(), SuiteRegistryError> {
+    let config = registry.config.clone();
+    let installed = REGISTRY.get_or_init(|| registry);
+    if installed.config == config {
+        Ok(())
+    } else {
+        Err(SuiteRegistryError::AlreadyInstalled)
+    }
+}
+
+/// The process-wide registry: the installed one, or the built-in suites if
+/// none was installed.
+pub fn registry() -> &'static SuiteRegistry {
+    REGISTRY.get_or_init(SuiteRegistry::builtin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::{tag_proof, GROTH16_BN254_COMPRESSED_LEN};
+
+    fn suite(tag: u8, activation: Option<u64>, deactivation: Option<u64>) -> SuiteConfig {
+        SuiteConfig {
+            tag,
+            circuit: ProofSuite::Groth16Bn254TransactV3,
+            verifying_key: Some(PathBuf::from(format!("keys/suite_{tag}.key"))),
+            activation_slot: activation,
+            deactivation_slot: deactivation,
+        }
+    }
+
+    fn wire(tag: u8) -> Vec<u8> {
+        let mut wire = vec![tag];
+        wire.extend_from_slice(&[0u8; GROTH16_BN254_COMPRESSED_LEN]);
+        wire
+    }
+
+    #[test]
+    fn empty_config_is_the_builtin_suites() {
+        let registry = SuiteRegistry::from_config(&[]).unwrap();
+        let tags: Vec<u8> = registry.suites().map(|s| s.tag).collect();
+        assert_eq!(tags, vec![1]);
+        let transact = registry.get(1).unwrap();
+        assert_eq!(transact.circuit, ProofSuite::Groth16Bn254TransactV3);
+        assert_eq!(transact.layout.public_inputs(), 8);
+
+        let body = vec![7u8; GROTH16_BN254_COMPRESSED_LEN];
+        let tagged = tag_proof(ProofSuite::Groth16Bn254TransactV3, &body);
+        let (resolved, got) = registry.resolve(&tagged, None).unwrap();
+        assert_eq!(resolved.tag, 1);
+        assert_eq!(got, &body[..]);
+    }
+
+    /// Rotation: the old tag until slot 200, the new one from slot 100.
+    #[test]
+    fn migration_window_accepts_both_and_then_only_the_new_tag() {
+        let registry =
+            SuiteRegistry::from_config(&[suite(1, None, Some(200)), suite(3, Some(100), None)])
+                .unwrap();
+
+        let accepted = |tag: u8, slot: Option<u64>| registry.resolve(&wire(tag), slot).is_ok();
+        assert!(accepted(1, Some(50)));
+        assert!(!accepted(3, Some(50)));
+        assert!(accepted(1, Some(150)));
+        assert!(accepted(3, Some(150)));
+        assert!(!accepted(1, Some(200)));
+        assert!(accepted(3, Some(200)));
+
+        // No request slot: the new tag has not activated, the old one has
+        // not expired.
+        assert!(accepted(1, None));
+        assert_eq!(
+            registry.resolve(&wire(3), None).unwrap_err(),
+            SuiteRegistryError::Inactive { tag: 3, slot: None }
+        );
+        // A tag no entry registers is refused outright.
+        assert_eq!(
+            registry.resolve(&wire(2), Some(150)).unwrap_err(),
+            SuiteRegistryError::UnknownTag(2)
+        );
+    }
+
+    #[test]
+    fn malformed_configs_are_refused() {
+        assert_eq!(
+            SuiteRegistry::from_config(&[suite(0, None, None)]).unwrap_err(),
+            SuiteRegistryError::ReservedTag
+        );
+        assert_eq!(
+            SuiteRegistry::from_config(&[suite(4, None, None), suite(4, Some(9), None)])
+                .unwrap_err(),
+            SuiteRegistryError::DuplicateTag(4)
+        );
+        assert!(matches!(
+            SuiteRegistry::from_config(&[suite(4, Some(10), Some(10))]),
+            Err(SuiteRegistryError::EmptyWindow { tag: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn resolve_checks_the_body_length_of_the_circuit() {
+        let registry = SuiteRegistry::builtin();
+        assert_eq!(
+            registry.resolve(&[], None).unwrap_err(),
+            SuiteRegistryError::EmptyProof
+        );
+        let mut short = wire(1);
+        short.pop();
+        assert!(matches!(
+            registry.resolve(&short, None),
+            Err(SuiteRegistryError::BodyLength { tag: 1, .. })
+        ));
+    }
+
+    /// A key with the wrong number of IC points is some other circuit's
+    /// key, and is refused rather than cached.
+    #[test]
+    fn a_key_that_does_not_fit_the_layout_is_refused() {
+        use ark_serialize::CanonicalSerialize;
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("foreign.key");
+        let mut bytes = Vec::new();
+        VerifyingKey::<Bn254>::default()
+            .serialize_compressed(&mut bytes)
+            .unwrap();
+        std::fs::write(&path, bytes).unwrap();
+
+        let registry = SuiteRegistry::from_config(&[SuiteConfig {
+            verifying_key: Some(path),
+            ..suite(1, None, None)
+        }])
+        .unwrap();
+        match registry.get(1).unwrap().verifying_key() {
+            Err(KeyLoadError::LayoutMismatch {
+                expected, found, ..
+            }) => {
+                assert_eq!(expected, 9);
+                assert_eq!(found, 0);
+            }
+            other => panic!("expected LayoutMismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn suites_parse_from_toml() {
+        #[derive(Deserialize)]
+        struct Privacy {
+            suites: Vec<SuiteConfig>,
+        }
+        let parsed: Privacy = toml::from_str(
+            r#"
+            [[suites]]
+            tag = 1
+            circuit = "groth16_bn254_transact_v3"
+            deactivation_slot = 200
+
+            [[suites]]
+            tag = 3
+            circuit = "groth16_bn254_transact_v3"
+            verifying_key = "keys/rotated.key"
+            activation_slot = 100
+            "#,
+        )
+        .unwrap();
+        assert_eq!(parsed.suites[0].verifying_key, None);
+        assert_eq!(parsed.suites[0].deactivation_slot, Some(200));
+        assert_eq!(
+            parsed.suites[1].verifying_key,
+            Some(PathBuf::from("keys/rotated.key"))
+        );
+        let registry = SuiteRegistry::from_config(&parsed.suites).unwrap();
+        assert_eq!(registry.get(3).unwrap().activation_slot, Some(100));
+    }
+
+    #[test]
+    fn reinstalling_the_builtin_registry_is_a_no_op() {
+        assert!(install(SuiteRegistry::builtin()).is_ok());
+        assert!(install(SuiteRegistry::from_config(&[]).unwrap()).is_ok());
+        assert!(registry().get(1).is_some());
+    }
+}
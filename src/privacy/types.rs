@@ -3,7 +3,10 @@
 use ark_bn254::Fr;
 use ark_ff::{BigInteger, PrimeField};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use crate::privacy::address::{AddressError, PaymentAddress};
+use crate::privacy::note_crypto::MAX_MEMO_LEN;
 use crate::privacy::poseidon::{
     poseidon_commit_spend, poseidon_merkle_pair, poseidon_nullifier, poseidon_nullifier_spend,
     poseidon_pubkey, poseidon_signature,
@@ -50,6 +53,22 @@ impl ShieldedAddress {
     pub fn to_hex(&self) -> String {
         hex::encode(self.0)
     }
+
+    /// Format as a checksummed payment address, paired with the X25519 key
+    /// the recipient's notes are encrypted to. See [`crate::privacy::address`].
+    pub fn to_payment_address(&self, transmission_key: &[u8; 32]) -> String {
+        crate::privacy::address::PaymentAddress::new(self.clone(), *transmission_key).encode()
+    }
+
+    /// Parse a checksummed payment address into its spend address and
+    /// note-encryption key. Rejects typos, foreign prefixes, unknown versions
+    /// and the legacy unchecksummed hex form.
+    pub fn parse_payment_address(
+        s: &str,
+    ) -> Result<crate::privacy::address::PaymentAddress, crate::privacy::address::AddressError>
+    {
+        crate::privacy::address::PaymentAddress::parse(s)
+    }
 }
 
 /// A commitment to a value and randomness (Pedersen commitment)
@@ -198,6 +217,15 @@ impl ViewingKey {
         crate::privacy::note_crypto::decrypt_note(&self.key, note)
     }
 
+    /// Open a sender's outgoing record. Only an outgoing viewing key opens
+    /// one; see [`crate::privacy::note_crypto::OutgoingPlaintext`].
+    pub fn try_decrypt_outgoing(
+        &self,
+        record: &crate::privacy::note_crypto::EncryptedNote,
+    ) -> Option<crate::privacy::note_crypto::OutgoingPlaintext> {
+        crate::privacy::note_crypto::decrypt_outgoing(&self.key, record)
+    }
+
     /// Whether this key can decrypt `note` (convenience over [`try_decrypt`](Self::try_decrypt)).
     pub fn can_decrypt(&self, note: &crate::privacy::note_crypto::EncryptedNote) -> bool {
         self.try_decrypt(note).is_some()
@@ -312,6 +340,228 @@ impl MerklePath {
     }
 }
 
+/// URI scheme of a shielded payment request.
+pub const PAYMENT_URI_SCHEME: &str = "paraloom";
+
+/// Why a payment-request URI was rejected.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("not a paraloom: payment request")]
+    WrongScheme,
+    #[error("payment request address: {0}")]
+    Address(#[from] AddressError),
+    #[error("malformed payment request parameter '{0}'")]
+    Malformed(String),
+    #[error("payment request repeats parameter '{0}'")]
+    Duplicate(String),
+    #[error("payment request requires unsupported parameter '{0}'")]
+    UnsupportedRequired(String),
+    #[error("payment request amount must be a positive integer of base units")]
+    BadAmount,
+    #[error("payment request mint must be 32 bytes of hex")]
+    BadMint,
+    #[error("payment request memo is {0} bytes; at most {MAX_MEMO_LEN} fit in a note")]
+    MemoTooLong(usize),
+}
+
+/// A request for a shielded payment, written as a URI a wallet can scan from
+/// a QR code:
+///
+/// ```text
+/// paraloom:<payment address>?amount=<base units>&mint=<hex>&memo=<text>&expires=<unix secs>
+/// ```
+///
+/// Every parameter is optional. `amount` is in the asset's base units
+/// (lamports for SOL), `mint` is the SPL mint as 32-byte hex and is omitted
+/// for native SOL, and `memo` is percent-encoded UTF-8 that the payer seals
+/// to the recipient with the note. As in BIP-21, a payer ignores parameters
+/// it does not know unless they are prefixed `req-`, in which case it must
+/// refuse the request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: PaymentAddress,
+    /// Requested amount in base units; `None` leaves it to the payer.
+    pub amount: Option<u64>,
+    /// SPL mint to pay in; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+    pub memo: Option<String>,
+    /// Unix seconds after which the request should not be paid.
+    pub expires_at: Option<u64>,
+}
+
+impl PaymentRequest {
+    /// A request for any amount of SOL to `address`.
+    pub fn new(address: PaymentAddress) -> Self {
+        PaymentRequest {
+            address,
+            amount: None,
+            mint: None,
+            memo: None,
+            expires_at: None,
+        }
+    }
+
+    /// The asset the payment is to be made in.
+    pub fn asset_id(&self) -> AssetId {
+        self.mint
+            .as_ref()
+            .map(crate::privacy::poseidon_circom::mint_to_asset)
+            .unwrap_or(NATIVE_SOL_ASSET)
+    }
+
+    /// Whether the request has expired at unix time `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|at| now >= at)
+    }
+
+    /// The canonical URI: the address in lower case and parameters in a
+    /// fixed order.
+    pub fn to_uri(&self) -> String {
+        let mut uri = format!("{PAYMENT_URI_SCHEME}:{}", self.address.encode());
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={amount}"));
+        }
+        if let Some(mint) = &self.mint {
+            params.push(format!("mint={}", hex::encode(mint)));
+        }
+        if let Some(memo) = &self.memo {
+            params.push(format!("memo={}", percent_encode(memo)));
+        }
+        if let Some(expires_at) = self.expires_at {
+            params.push(format!("expires={expires_at}"));
+        }
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+        uri
+    }
+
+    /// Parse a payment-request URI, checking the address checksum, the
+    /// amount, mint and memo. Whether it has expired is left to the caller,
+    /// who knows the time (see [`is_expired`](Self::is_expired)).
+    pub fn parse(s: &str) -> Result<Self, PaymentRequestError> {
+        let s = s.trim();
+        let (scheme, rest) = s.split_once(':').ok_or(PaymentRequestError::WrongScheme)?;
+        if !scheme.eq_ignore_ascii_case(PAYMENT_URI_SCHEME) {
+            return Err(PaymentRequestError::WrongScheme);
+        }
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+        let mut request = PaymentRequest::new(PaymentAddress::parse(address)?);
+
+        let mut seen = std::collections::HashSet::new();
+        for pair in query.into_iter().flat_map(|q| q.split('&')) {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| PaymentRequestError::Malformed(pair.to_string()))?;
+            if !seen.insert(key.to_string()) {
+                return Err(PaymentRequestError::Duplicate(key.to_string()));
+            }
+            match key {
+                "amount" => {
+                    let amount = value
+                        .parse::<u64>()
+                        .map_err(|_| PaymentRequestError::BadAmount)?;
+                    if amount == 0 {
+                        return Err(PaymentRequestError::BadAmount);
+                    }
+                    request.amount = Some(amount);
+                }
+                "mint" => {
+                    let mint: [u8; 32] = hex::decode(value.strip_prefix("0x").unwrap_or(value))
+                        .ok()
+                        .and_then(|b| b.try_into().ok())
+                        .ok_or(PaymentRequestError::BadMint)?;
+                    request.mint = Some(mint);
+                }
+                "memo" => {
+                    let memo = percent_decode(value)
+                        .ok_or_else(|| PaymentRequestError::Malformed(key.to_string()))?;
+                    if memo.len() > MAX_MEMO_LEN {
+                        return Err(PaymentRequestError::MemoTooLong(memo.len()));
+                    }
+                    request.memo = Some(memo);
+                }
+                "expires" => {
+                    request.expires_at = Some(
+                        value
+                            .parse()
+                            .map_err(|_| PaymentRequestError::Malformed(key.to_string()))?,
+                    );
+                }
+                other if other.starts_with("req-") => {
+                    return Err(PaymentRequestError::UnsupportedRequired(other.to_string()));
+                }
+                _ => {}
+            }
+        }
+        Ok(request)
+    }
+}
+
+impl std::fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+impl std::str::FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// Decode `%XX` escapes (and `+` as a space, as form encoders write it).
+/// `None` on a bad escape or if the result is not UTF-8.
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                out.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -697,4 +947,73 @@ mod tests {
         let other = SpendKeypair::from_privkey([8u8; 32]);
         assert_ne!(n0, other.nullifier(&commitment, 0));
     }
+
+    fn request() -> PaymentRequest {
+        PaymentRequest {
+            amount: Some(2_500_000),
+            mint: Some([0x33; 32]),
+            memo: Some("invoice #12, café & co".to_string()),
+            expires_at: Some(1_900_000_000),
+            ..PaymentRequest::new(PaymentAddress::new(ShieldedAddress([0x11; 32]), [0x22; 32]))
+        }
+    }
+
+    #[test]
+    fn payment_request_round_trips() {
+        let req = request();
+        let uri = req.to_uri();
+        assert!(uri.starts_with("paraloom:paraloom1"));
+        assert!(!uri.contains(' ') && !uri.contains('#'));
+        assert_eq!(PaymentRequest::parse(&uri).unwrap(), req);
+
+        // Bare address: SOL, any amount, no expiry.
+        let bare = PaymentRequest::new(req.address.clone());
+        let parsed: PaymentRequest = bare.to_uri().parse().unwrap();
+        assert_eq!(parsed, bare);
+        assert_eq!(parsed.asset_id(), NATIVE_SOL_ASSET);
+        assert!(!parsed.is_expired(u64::MAX));
+
+        assert!(!req.is_expired(1_899_999_999));
+        assert!(req.is_expired(1_900_000_000));
+    }
+
+    #[test]
+    fn malformed_payment_requests_are_rejected() {
+        let address = request().address.encode();
+        let parse = |q: &str| PaymentRequest::parse(&format!("paraloom:{address}?{q}"));
+
+        assert_eq!(
+            PaymentRequest::parse(&format!("solana:{address}")),
+            Err(PaymentRequestError::WrongScheme)
+        );
+        assert!(matches!(
+            PaymentRequest::parse("paraloom:paraloom1qqqq"),
+            Err(PaymentRequestError::Address(_))
+        ));
+        assert_eq!(parse("amount=0"), Err(PaymentRequestError::BadAmount));
+        assert_eq!(parse("amount=1.5"), Err(PaymentRequestError::BadAmount));
+        assert_eq!(parse("mint=abcd"), Err(PaymentRequestError::BadMint));
+        assert_eq!(
+            parse("amount=1&amount=2"),
+            Err(PaymentRequestError::Duplicate("amount".into()))
+        );
+        assert_eq!(
+            parse("memo=%zz"),
+            Err(PaymentRequestError::Malformed("memo".into()))
+        );
+        let long = "a".repeat(MAX_MEMO_LEN + 1);
+        assert_eq!(
+            parse(&format!("memo={long}")),
+            Err(PaymentRequestError::MemoTooLong(MAX_MEMO_LEN + 1))
+        );
+
+        // Unknown parameters are ignored unless the request requires them.
+        assert_eq!(parse("label=shop").unwrap().amount, None);
+        assert_eq!(
+            parse("req-refund=1"),
+            Err(PaymentRequestError::UnsupportedRequired(
+                "req-refund".into()
+            ))
+        );
+    }
 }
@@ -234,6 +234,7 @@ pub fn prove_withdraw_to_fresh(
         &[fr_to_le(&nf0), fr_to_le(&nf1)],
         &[fr_to_le(&oc0), fr_to_le(&oc1)],
         &proof,
+        None,
     );
     if !verdict.is_valid() {
         return Err(TransactSubmitError::SelfVerify(format!("{verdict:?}")));
@@ -311,6 +312,31 @@ pub fn wait_for_settlement(
     Err(TransactSubmitError::SettlementTimeout(timeout))
 }
 
+/// Poll on-chain until the nullifier PDA for `nullifier` exists and is owned
+/// by the program — the settlement signal for a spend that pays nobody on
+/// chain (a pure shielded transfer), where [`wait_for_settlement`]'s balance
+/// check has nothing to watch.
+pub fn wait_for_nullifier(
+    client: &RpcClient,
+    program_id: &Pubkey,
+    nullifier: &[u8; 32],
+    timeout: Duration,
+) -> Result<()> {
+    let (nf_pda, _) = derive_nullifier_account(program_id, nullifier);
+    let deadline = std::time::Instant::now() + timeout;
+    let step = Duration::from_secs(3);
+    while std::time::Instant::now() < deadline {
+        std::thread::sleep(step);
+        if client
+            .get_account(&nf_pda)
+            .is_ok_and(|account| account.owner == *program_id)
+        {
+            return Ok(());
+        }
+    }
+    Err(TransactSubmitError::SettlementTimeout(timeout))
+}
+
 /// Whether the transact ingress has a validator quorum available (the registry
 /// has >= 2 active validators). Cheap pre-check before proving so we fail fast
 /// with a clear reason rather than after a 60s+ settlement wait.
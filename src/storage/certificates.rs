@@ -0,0 +1,139 @@
+//! Quorum certificate storage
+//!
+//! Keeps the [`QuorumCertificate`] of every transact this node saw approved,
+//! after the vote tally behind it is gone, so an operator or auditor can later
+//! fetch who approved a settlement.
+//!
+//! - `quorum_certificates`: one certificate per request, keyed by the
+//!   canonical request id. The first certificate stored for an id is kept;
+//!   any later one certifies the same transact.
+
+use crate::consensus::QuorumCertificate;
+use anyhow::{anyhow, Result};
+use log::info;
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, DB};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Column family names
+const CF_QUORUM_CERTIFICATES: &str = "quorum_certificates";
+
+/// Quorum certificate storage using RocksDB column families
+pub struct CertificateStorage {
+    db: Arc<DB>,
+}
+
+impl CertificateStorage {
+    /// Open certificate storage with column families
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        info!("Opening certificate storage at {:?}", path.as_ref());
+
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let cf_certificates =
+            ColumnFamilyDescriptor::new(CF_QUORUM_CERTIFICATES, Options::default());
+        let db = DB::open_cf_descriptors(&options, path, vec![cf_certificates])?;
+
+        Ok(CertificateStorage { db: Arc::new(db) })
+    }
+
+    /// Open an existing store for reading while the node that owns it keeps
+    /// running. Sees what was written up to the moment it opened.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db =
+            DB::open_cf_for_read_only(&Options::default(), path, [CF_QUORUM_CERTIFICATES], false)?;
+        Ok(CertificateStorage { db: Arc::new(db) })
+    }
+
+    /// Store `certificate` unless one for its request is already on file.
+    /// Returns whether it was new.
+    pub fn insert(&self, certificate: &QuorumCertificate) -> Result<bool> {
+        let cf = self
+            .db
+            .cf_handle(CF_QUORUM_CERTIFICATES)
+            .ok_or_else(|| anyhow!("Quorum certificates CF not found"))?;
+        let key = certificate.request_id.as_bytes();
+        if self.db.get_cf(cf, key)?.is_some() {
+            return Ok(false);
+        }
+        self.db.put_cf(cf, key, bincode::serialize(certificate)?)?;
+        Ok(true)
+    }
+
+    /// The certificate on file for `request_id`.
+    pub fn get(&self, request_id: &str) -> Result<Option<QuorumCertificate>> {
+        let cf = self
+            .db
+            .cf_handle(CF_QUORUM_CERTIFICATES)
+            .ok_or_else(|| anyhow!("Quorum certificates CF not found"))?;
+        match self.db.get_cf(cf, request_id.as_bytes())? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every certificate on file, ordered by request id.
+    pub fn all(&self) -> Result<Vec<QuorumCertificate>> {
+        let cf = self
+            .db
+            .cf_handle(CF_QUORUM_CERTIFICATES)
+            .ok_or_else(|| anyhow!("Quorum certificates CF not found"))?;
+        let mut certificates = Vec::new();
+        for item in self.db.iterator_cf(cf, IteratorMode::Start) {
+            let (_, value) = item?;
+            certificates.push(bincode::deserialize(&value)?);
+        }
+        Ok(certificates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::{TransactVerificationResult, VerificationVote};
+    use crate::types::NodeId;
+    use std::collections::HashMap;
+    use tempfile::tempdir;
+
+    fn certificate(request_id: &str, wallet: &str) -> QuorumCertificate {
+        QuorumCertificate::new(
+            request_id.to_string(),
+            "prog".to_string(),
+            "devnet".to_string(),
+            0,
+            &HashMap::from([(wallet.to_string(), 100)]),
+            100,
+            vec![TransactVerificationResult {
+                request_id: request_id.to_string(),
+                validator: NodeId(vec![1]),
+                vote: VerificationVote::Valid,
+                timestamp: 0,
+                wallet_pubkey: wallet.to_string(),
+                signature: vec![1; 64],
+            }],
+        )
+    }
+
+    #[test]
+    fn the_first_certificate_per_request_is_kept_across_reopens() {
+        let dir = tempdir().unwrap();
+        {
+            let storage = CertificateStorage::open(dir.path()).unwrap();
+            assert!(storage.insert(&certificate("r1", "W1")).unwrap());
+            assert!(!storage.insert(&certificate("r1", "W2")).unwrap());
+            assert!(storage.insert(&certificate("r2", "W1")).unwrap());
+        }
+
+        let storage = CertificateStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.all().unwrap().len(), 2);
+        let kept = storage.get("r1").unwrap().unwrap();
+        assert_eq!(kept.wallets().collect::<Vec<_>>(), vec!["W1"]);
+        assert!(storage.get("r3").unwrap().is_none());
+    }
+}
@@ -1,9 +1,15 @@
 //! Storage module
 
 mod blockchain;
+mod certificates;
 mod compute_store;
 mod privacy;
+mod slashing;
+mod wallet;
 
 pub use blockchain::BlockchainStorage;
+pub use certificates::CertificateStorage;
 pub use compute_store::{ComputeStorage, ComputeStorageStats};
-pub use privacy::PrivacyStorage;
+pub use privacy::{DeliveredRecord, DeliveredRetention, DepositRecord, MerkleNode, PrivacyStorage};
+pub use slashing::SlashingStorage;
+pub use wallet::WalletStorage;
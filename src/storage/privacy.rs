@@ -4,6 +4,8 @@
 //! - Merkle tree commitments
 //! - Nullifier set
 //! - Shielded pool metadata
+//! - Delivered encrypted notes served to recipients (#196)
+//! - The deposits behind pool leaves, for association-set curators
 //!
 //! ## Durability model (#68)
 //!
@@ -14,22 +16,28 @@
 //! write only returns once the data has been fsync'd through to
 //! disk:
 //!
-//! - `insert_commitment` / `insert_commitments_batch`
+//! - `insert_commitment` / `insert_commitments_batch` / `append_merkle_leaves`
 //! - `insert_nullifier` / `insert_nullifiers_batch`
 //! - `set_total_supply`
 //!
+//! Appending a delivered note is fsync'd too: it is the only copy of the
+//! ciphertext a recipient needs to find their payment.
+//!
 //! `set_merkle_root` is intentionally async — the root is a cache
 //! that the tree can rebuild from the persisted leaves on startup,
 //! so a missed write costs at most a one-time recomputation rather
 //! than data loss. Documented as such in #59 and preserved here.
+//! Trees written through `append_merkle_leaves` no longer need it:
+//! their root is the top interior node.
 
 use crate::privacy::types::{AssetId, Commitment, Nullifier, NATIVE_SOL_ASSET};
 use anyhow::{anyhow, Result};
 use log::info;
 use rocksdb::{ColumnFamilyDescriptor, Options, WriteOptions, DB};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 /// `WriteOptions` for the durability-critical paths described in the
 /// module docs. `sync = true` instructs RocksDB to fsync the WAL
@@ -46,6 +54,26 @@ fn durable_write_options() -> WriteOptions {
 const CF_MERKLE_TREE: &str = "merkle_tree";
 const CF_NULLIFIER_SET: &str = "nullifier_set";
 const CF_POOL_STATE: &str = "pool_state";
+/// Delivered notes keyed by their sequence number, big-endian so that
+/// bytewise key order is numeric order.
+const CF_DELIVERED_NOTES: &str = "delivered_notes";
+/// `commitment || seq` for every delivered note, to de-duplicate and to
+/// find a commitment's notes when its leaf index lands.
+const CF_DELIVERED_INDEX: &str = "delivered_index";
+/// Interior nodes of the commitment tree, keyed by [`merkle_node_key`].
+const CF_MERKLE_NODES: &str = "merkle_nodes";
+/// `commitment -> first leaf index` (u64 LE), for O(1) membership lookups.
+const CF_MERKLE_LEAF_INDEX: &str = "merkle_leaf_index";
+/// Deposits keyed by the leaf index they landed at, big-endian.
+const CF_DEPOSITS: &str = "deposits";
+
+/// `CF_POOL_STATE` key of the number of leaves `append_merkle_leaves` has
+/// written. Absent on databases that predate interior-node storage.
+const MERKLE_LEAF_COUNT_KEY: &[u8] = b"merkle_leaf_count";
+
+/// `CF_POOL_STATE` key of the next delivered-note sequence number. Kept
+/// apart from the notes so pruning never hands out a sequence twice.
+const NEXT_DELIVERED_SEQ_KEY: &[u8] = b"delivered_next_seq";
 
 /// Key prefix for per-asset shielded supply entries in `CF_POOL_STATE`
 /// (#236). A non-native asset's supply is stored under
@@ -62,9 +90,86 @@ fn asset_supply_key(asset_id: &AssetId) -> Vec<u8> {
     key
 }
 
+/// One interior node of the commitment tree. `family` tells the pair hash
+/// the node was built with apart (the tree keeps the domain-separated and
+/// the on-chain hash side by side); `level` counts up from the leaves, so
+/// level `0` is never stored here.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleNode {
+    pub family: u8,
+    pub level: u8,
+    pub index: u64,
+    pub hash: [u8; 32],
+}
+
+/// `family || level || index` (big-endian), so one family's level sorts
+/// together in index order.
+fn merkle_node_key(family: u8, level: u8, index: u64) -> [u8; 10] {
+    let mut key = [0u8; 10];
+    key[0] = family;
+    key[1] = level;
+    key[2..].copy_from_slice(&index.to_be_bytes());
+    key
+}
+
+/// An encrypted output note as delivered for recipient scanning (#196).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveredRecord {
+    pub commitment: [u8; 32],
+    /// The envelope as submitted, hex.
+    pub ciphertext: String,
+    /// SPL mint of the settlement; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+    /// Position of the commitment in the on-chain tree, once settled.
+    pub leaf_index: Option<u64>,
+    /// Slot the settlement landed in, once settled.
+    pub slot: Option<u64>,
+    /// Unix seconds when this node recorded the note, for retention.
+    pub recorded_at: u64,
+}
+
+/// A deposit as the bridge listener saw it land: the leaf it appended and
+/// who paid for it. Association-set curators build allow-lists from these.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepositRecord {
+    pub commitment: [u8; 32],
+    pub leaf_index: u64,
+    /// The Solana account the deposit was paid from.
+    pub depositor: [u8; 32],
+    pub amount: u64,
+    pub asset_id: AssetId,
+    pub slot: u64,
+    pub signature: String,
+}
+
+/// How long delivered notes are kept. Whichever bound is hit first prunes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeliveredRetention {
+    /// Most notes kept; the oldest go first. `0` means no bound.
+    pub max_notes: u64,
+    /// Notes recorded more than this many seconds ago go. `0` means no bound.
+    pub max_age_secs: u64,
+}
+
 /// Privacy storage using RocksDB column families
 pub struct PrivacyStorage {
     db: Arc<DB>,
+    /// Serializes delivered-note appends, which read then bump the sequence.
+    delivered_lock: Mutex<()>,
+}
+
+fn delivered_index_key(commitment: &[u8; 32], seq: u64) -> [u8; 40] {
+    let mut key = [0u8; 40];
+    key[..32].copy_from_slice(commitment);
+    key[32..].copy_from_slice(&seq.to_be_bytes());
+    key
+}
+
+fn decode_seq(key: &[u8]) -> Result<u64> {
+    let arr: [u8; 8] = key
+        .try_into()
+        .map_err(|_| anyhow!("Invalid delivered-note key length in storage"))?;
+    Ok(u64::from_be_bytes(arr))
 }
 
 impl PrivacyStorage {
@@ -88,11 +193,34 @@ impl PrivacyStorage {
         let cf_merkle = ColumnFamilyDescriptor::new(CF_MERKLE_TREE, Options::default());
         let cf_nullifier = ColumnFamilyDescriptor::new(CF_NULLIFIER_SET, Options::default());
         let cf_pool = ColumnFamilyDescriptor::new(CF_POOL_STATE, Options::default());
+        let cf_delivered = ColumnFamilyDescriptor::new(CF_DELIVERED_NOTES, Options::default());
+        let cf_delivered_index =
+            ColumnFamilyDescriptor::new(CF_DELIVERED_INDEX, Options::default());
+        let cf_merkle_nodes = ColumnFamilyDescriptor::new(CF_MERKLE_NODES, Options::default());
+        let cf_merkle_leaf_index =
+            ColumnFamilyDescriptor::new(CF_MERKLE_LEAF_INDEX, Options::default());
+        let cf_deposits = ColumnFamilyDescriptor::new(CF_DEPOSITS, Options::default());
 
         // Open database with column families
-        let db = DB::open_cf_descriptors(&options, path, vec![cf_merkle, cf_nullifier, cf_pool])?;
+        let db = DB::open_cf_descriptors(
+            &options,
+            path,
+            vec![
+                cf_merkle,
+                cf_nullifier,
+                cf_pool,
+                cf_delivered,
+                cf_delivered_index,
+                cf_merkle_nodes,
+                cf_merkle_leaf_index,
+                cf_deposits,
+            ],
+        )?;
 
-        Ok(PrivacyStorage { db: Arc::new(db) })
+        Ok(PrivacyStorage {
+            db: Arc::new(db),
+            delivered_lock: Mutex::new(()),
+        })
     }
 
     // ========== Merkle Tree Operations ==========
@@ -216,6 +344,119 @@ impl PrivacyStorage {
         Ok(indexed.into_iter().map(|(_, c)| c).collect())
     }
 
+    /// Append leaves together with every interior node they change.
+    ///
+    /// Durability-critical, and a single fsync'd `WriteBatch`: the leaves,
+    /// the rewritten nodes, the commitment index and the new leaf count land
+    /// together or not at all, so the persisted nodes always describe
+    /// exactly `merkle_leaf_count` leaves. A commitment already in the tree
+    /// keeps its first index.
+    pub fn append_merkle_leaves(
+        &self,
+        start_index: u64,
+        commitments: &[Commitment],
+        nodes: &[MerkleNode],
+    ) -> Result<()> {
+        let cf_leaves = self
+            .db
+            .cf_handle(CF_MERKLE_TREE)
+            .ok_or_else(|| anyhow!("Merkle tree CF not found"))?;
+        let cf_nodes = self
+            .db
+            .cf_handle(CF_MERKLE_NODES)
+            .ok_or_else(|| anyhow!("Merkle nodes CF not found"))?;
+        let cf_index = self
+            .db
+            .cf_handle(CF_MERKLE_LEAF_INDEX)
+            .ok_or_else(|| anyhow!("Merkle leaf index CF not found"))?;
+        let cf_pool = self
+            .db
+            .cf_handle(CF_POOL_STATE)
+            .ok_or_else(|| anyhow!("Pool state CF not found"))?;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut indexed = std::collections::HashSet::new();
+        for (i, commitment) in commitments.iter().enumerate() {
+            let index = start_index + i as u64;
+            batch.put_cf(cf_leaves, index.to_le_bytes(), commitment.as_bytes());
+            if indexed.insert(*commitment.as_bytes())
+                && self.db.get_cf(cf_index, commitment.as_bytes())?.is_none()
+            {
+                batch.put_cf(cf_index, commitment.as_bytes(), index.to_le_bytes());
+            }
+        }
+        for node in nodes {
+            batch.put_cf(
+                cf_nodes,
+                merkle_node_key(node.family, node.level, node.index),
+                node.hash,
+            );
+        }
+        let count = start_index + commitments.len() as u64;
+        batch.put_cf(cf_pool, MERKLE_LEAF_COUNT_KEY, count.to_le_bytes());
+        self.db.write_opt(batch, &durable_write_options())?;
+
+        Ok(())
+    }
+
+    /// An interior node written by [`Self::append_merkle_leaves`].
+    pub fn get_merkle_node(&self, family: u8, level: u8, index: u64) -> Result<Option<[u8; 32]>> {
+        let cf = self
+            .db
+            .cf_handle(CF_MERKLE_NODES)
+            .ok_or_else(|| anyhow!("Merkle nodes CF not found"))?;
+
+        match self.db.get_cf(cf, merkle_node_key(family, level, index))? {
+            Some(bytes) => {
+                let arr: [u8; 32] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid merkle node size: {}", bytes.len()))?;
+                Ok(Some(arr))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// The first leaf index `commitment` was appended at.
+    pub fn merkle_leaf_index(&self, commitment: &Commitment) -> Result<Option<u64>> {
+        let cf = self
+            .db
+            .cf_handle(CF_MERKLE_LEAF_INDEX)
+            .ok_or_else(|| anyhow!("Merkle leaf index CF not found"))?;
+
+        match self.db.get_cf(cf, commitment.as_bytes())? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid merkle leaf index size"))?;
+                Ok(Some(u64::from_le_bytes(arr)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Leaves covered by the stored interior nodes, or `None` if this
+    /// database predates them and the tree has to build them once.
+    pub fn merkle_leaf_count(&self) -> Result<Option<u64>> {
+        let cf = self
+            .db
+            .cf_handle(CF_POOL_STATE)
+            .ok_or_else(|| anyhow!("Pool state CF not found"))?;
+
+        match self.db.get_cf(cf, MERKLE_LEAF_COUNT_KEY)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid merkle leaf count size"))?;
+                Ok(Some(u64::from_le_bytes(arr)))
+            }
+            None => Ok(None),
+        }
+    }
+
     // ========== Nullifier Set Operations ==========
 
     /// Insert a nullifier (mark as spent).
@@ -489,6 +730,254 @@ impl PrivacyStorage {
         }
     }
 
+    // ========== Delivered Note Operations ==========
+
+    /// Append a delivered note and return its sequence number, or `None` if
+    /// the same `(commitment, ciphertext)` is already stored — the same
+    /// transact seen via both the ingress and gossip is kept once.
+    ///
+    /// Sequence numbers start at 1 and are never reused, so `0` is the
+    /// cursor before the first note.
+    pub fn append_delivered(&self, record: &DeliveredRecord) -> Result<Option<u64>> {
+        let cf_notes = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+        let cf_index = self
+            .db
+            .cf_handle(CF_DELIVERED_INDEX)
+            .ok_or_else(|| anyhow!("Delivered index CF not found"))?;
+        let cf_pool = self
+            .db
+            .cf_handle(CF_POOL_STATE)
+            .ok_or_else(|| anyhow!("Pool state CF not found"))?;
+
+        let _guard = self
+            .delivered_lock
+            .lock()
+            .map_err(|_| anyhow!("delivered-note lock poisoned"))?;
+
+        for seq in self.delivered_seqs_for(&record.commitment)? {
+            if let Some(existing) = self.get_delivered(seq)? {
+                if existing.ciphertext == record.ciphertext {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let seq = match self.db.get_cf(cf_pool, NEXT_DELIVERED_SEQ_KEY)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid delivered sequence size"))?;
+                u64::from_le_bytes(arr)
+            }
+            None => 1,
+        };
+
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.put_cf(cf_notes, seq.to_be_bytes(), bincode::serialize(record)?);
+        batch.put_cf(cf_index, delivered_index_key(&record.commitment, seq), b"");
+        batch.put_cf(cf_pool, NEXT_DELIVERED_SEQ_KEY, (seq + 1).to_le_bytes());
+        self.db.write_opt(batch, &durable_write_options())?;
+
+        Ok(Some(seq))
+    }
+
+    /// The delivered note stored under `seq`, if it has not been pruned.
+    pub fn get_delivered(&self, seq: u64) -> Result<Option<DeliveredRecord>> {
+        let cf = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+        match self.db.get_cf(cf, seq.to_be_bytes())? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Up to `limit` delivered notes with a sequence number above `after`,
+    /// in sequence order.
+    pub fn delivered_after(&self, after: u64, limit: usize) -> Result<Vec<(u64, DeliveredRecord)>> {
+        let cf = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+
+        let Some(start) = after.checked_add(1) else {
+            return Ok(Vec::new());
+        };
+        let start = start.to_be_bytes();
+        let mut notes = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(
+                cf,
+                rocksdb::IteratorMode::From(&start, rocksdb::Direction::Forward),
+            )
+            .take(limit)
+        {
+            let (key, value) = item?;
+            notes.push((decode_seq(&key)?, bincode::deserialize(&value)?));
+        }
+        Ok(notes)
+    }
+
+    /// Number of delivered notes currently stored.
+    pub fn delivered_count(&self) -> Result<u64> {
+        let cf = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+        let mut count = 0u64;
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            item?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Record where `commitment` landed on chain, on every delivered note
+    /// that carries it. Returns how many notes were updated.
+    pub fn set_delivered_position(
+        &self,
+        commitment: &[u8; 32],
+        leaf_index: u64,
+        slot: u64,
+    ) -> Result<usize> {
+        let cf = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+
+        let _guard = self
+            .delivered_lock
+            .lock()
+            .map_err(|_| anyhow!("delivered-note lock poisoned"))?;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut updated = 0;
+        for seq in self.delivered_seqs_for(commitment)? {
+            if let Some(mut record) = self.get_delivered(seq)? {
+                record.leaf_index = Some(leaf_index);
+                record.slot = Some(slot);
+                batch.put_cf(cf, seq.to_be_bytes(), bincode::serialize(&record)?);
+                updated += 1;
+            }
+        }
+        if updated > 0 {
+            self.db.write_opt(batch, &durable_write_options())?;
+        }
+        Ok(updated)
+    }
+
+    /// Drop the delivered notes `retention` no longer covers, oldest first,
+    /// as of unix time `now`. Returns how many were removed.
+    pub fn prune_delivered(&self, retention: &DeliveredRetention, now: u64) -> Result<usize> {
+        let cf_notes = self
+            .db
+            .cf_handle(CF_DELIVERED_NOTES)
+            .ok_or_else(|| anyhow!("Delivered notes CF not found"))?;
+        let cf_index = self
+            .db
+            .cf_handle(CF_DELIVERED_INDEX)
+            .ok_or_else(|| anyhow!("Delivered index CF not found"))?;
+
+        let _guard = self
+            .delivered_lock
+            .lock()
+            .map_err(|_| anyhow!("delivered-note lock poisoned"))?;
+
+        let total = self.delivered_count()?;
+        let mut over = match retention.max_notes {
+            0 => 0,
+            max => total.saturating_sub(max),
+        };
+        let cutoff = match retention.max_age_secs {
+            0 => None,
+            age => Some(now.saturating_sub(age)),
+        };
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let mut removed = 0;
+        for item in self.db.iterator_cf(cf_notes, rocksdb::IteratorMode::Start) {
+            let (key, value) = item?;
+            let record: DeliveredRecord = bincode::deserialize(&value)?;
+            let expired = cutoff.is_some_and(|cutoff| record.recorded_at < cutoff);
+            if over == 0 && !expired {
+                // Notes are appended in time order, so nothing later is due.
+                break;
+            }
+            let seq = decode_seq(&key)?;
+            batch.delete_cf(cf_notes, key);
+            batch.delete_cf(cf_index, delivered_index_key(&record.commitment, seq));
+            over = over.saturating_sub(1);
+            removed += 1;
+        }
+        if removed > 0 {
+            self.db.write_opt(batch, &durable_write_options())?;
+        }
+        Ok(removed)
+    }
+
+    /// Sequence numbers of the stored notes carrying `commitment`.
+    fn delivered_seqs_for(&self, commitment: &[u8; 32]) -> Result<Vec<u64>> {
+        let cf = self
+            .db
+            .cf_handle(CF_DELIVERED_INDEX)
+            .ok_or_else(|| anyhow!("Delivered index CF not found"))?;
+        let mut seqs = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, commitment) {
+            let (key, _) = item?;
+            if !key.starts_with(commitment) {
+                break;
+            }
+            seqs.push(decode_seq(&key[32..])?);
+        }
+        Ok(seqs)
+    }
+
+    // ========== Deposit Operations ==========
+
+    /// Record the deposit behind `record.leaf_index`. A replayed deposit
+    /// lands on the same key, so recording it twice keeps one copy.
+    pub fn put_deposit(&self, record: &DepositRecord) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_DEPOSITS)
+            .ok_or_else(|| anyhow!("Deposits CF not found"))?;
+        self.db.put_cf(
+            cf,
+            record.leaf_index.to_be_bytes(),
+            bincode::serialize(record)?,
+        )?;
+        Ok(())
+    }
+
+    /// Up to `limit` recorded deposits at leaf index `from` or above, in
+    /// leaf order.
+    pub fn deposits_from(&self, from: u64, limit: usize) -> Result<Vec<DepositRecord>> {
+        let cf = self
+            .db
+            .cf_handle(CF_DEPOSITS)
+            .ok_or_else(|| anyhow!("Deposits CF not found"))?;
+        let start = from.to_be_bytes();
+        let mut deposits = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(
+                cf,
+                rocksdb::IteratorMode::From(&start, rocksdb::Direction::Forward),
+            )
+            .take(limit)
+        {
+            let (_, value) = item?;
+            deposits.push(bincode::deserialize(&value)?);
+        }
+        Ok(deposits)
+    }
+
     // ========== Utility Operations ==========
 
     /// Flush all data to disk
@@ -639,6 +1128,98 @@ mod tests {
         assert_eq!(storage.get_merkle_root().unwrap().unwrap(), root);
     }
 
+    fn delivered(fill: u8, recorded_at: u64) -> DeliveredRecord {
+        DeliveredRecord {
+            commitment: [fill; 32],
+            ciphertext: format!("{fill:02x}").repeat(80),
+            mint: None,
+            leaf_index: None,
+            slot: None,
+            recorded_at,
+        }
+    }
+
+    #[test]
+    fn delivered_notes_survive_a_reopen_and_page_by_sequence() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("privacy.db");
+        {
+            let storage = PrivacyStorage::open(&path).unwrap();
+            // Past 255 so a little-endian key would sort out of order.
+            for i in 0..300u64 {
+                let mut record = delivered(1, i);
+                record.commitment[..8].copy_from_slice(&i.to_le_bytes());
+                assert_eq!(storage.append_delivered(&record).unwrap(), Some(i + 1));
+            }
+            // A re-sighting of the same note is kept once.
+            let mut again = delivered(1, 0);
+            again.commitment[..8].copy_from_slice(&0u64.to_le_bytes());
+            assert_eq!(storage.append_delivered(&again).unwrap(), None);
+        }
+
+        let storage = PrivacyStorage::open(&path).unwrap();
+        assert_eq!(storage.delivered_count().unwrap(), 300);
+        let page = storage.delivered_after(250, 100).unwrap();
+        let seqs: Vec<u64> = page.iter().map(|(seq, _)| *seq).collect();
+        assert_eq!(seqs, (251..=300).collect::<Vec<_>>());
+        assert_eq!(page[0].1.recorded_at, 250);
+        assert!(storage.delivered_after(300, 10).unwrap().is_empty());
+        assert_eq!(storage.delivered_after(0, 2).unwrap()[1].0, 2);
+    }
+
+    #[test]
+    fn delivered_position_is_set_by_commitment() {
+        let dir = tempdir().unwrap();
+        let storage = PrivacyStorage::open(dir.path().join("privacy.db")).unwrap();
+        storage.append_delivered(&delivered(1, 0)).unwrap();
+        let mut mutated = delivered(1, 0);
+        mutated.ciphertext = "ff".repeat(80);
+        storage.append_delivered(&mutated).unwrap();
+        storage.append_delivered(&delivered(2, 0)).unwrap();
+
+        assert_eq!(
+            storage.set_delivered_position(&[1; 32], 17, 900).unwrap(),
+            2
+        );
+        assert_eq!(storage.set_delivered_position(&[9; 32], 1, 1).unwrap(), 0);
+        let notes = storage.delivered_after(0, 10).unwrap();
+        assert_eq!(notes[0].1.leaf_index, Some(17));
+        assert_eq!(notes[1].1.slot, Some(900));
+        assert_eq!(notes[2].1.leaf_index, None);
+    }
+
+    #[test]
+    fn pruning_keeps_the_newest_notes_and_never_reuses_a_sequence() {
+        let dir = tempdir().unwrap();
+        let storage = PrivacyStorage::open(dir.path().join("privacy.db")).unwrap();
+        for i in 0..10u8 {
+            storage
+                .append_delivered(&delivered(i, u64::from(i) * 100))
+                .unwrap();
+        }
+
+        let by_count = DeliveredRetention {
+            max_notes: 6,
+            max_age_secs: 0,
+        };
+        assert_eq!(storage.prune_delivered(&by_count, 1_000).unwrap(), 4);
+        assert_eq!(storage.delivered_after(0, 1).unwrap()[0].0, 5);
+
+        // Recorded before 1_000 - 250 = 750: seqs 5..=8 (at 400..700).
+        let by_age = DeliveredRetention {
+            max_notes: 0,
+            max_age_secs: 250,
+        };
+        assert_eq!(storage.prune_delivered(&by_age, 1_000).unwrap(), 4);
+        assert_eq!(storage.delivered_count().unwrap(), 2);
+
+        // A pruned note's commitment can be delivered again, under a new seq.
+        assert_eq!(
+            storage.append_delivered(&delivered(0, 2_000)).unwrap(),
+            Some(11)
+        );
+    }
+
     #[test]
     fn test_get_all_commitments() {
         let dir = tempdir().unwrap();
@@ -701,4 +1282,68 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn merkle_leaves_land_with_their_nodes_and_keep_the_first_index() {
+        let dir = tempdir().unwrap();
+        let storage = PrivacyStorage::open(dir.path().join("privacy.db")).unwrap();
+        assert_eq!(storage.merkle_leaf_count().unwrap(), None);
+
+        let node = |family, level, index| MerkleNode {
+            family,
+            level,
+            index,
+            hash: [level + family * 10; 32],
+        };
+        let leaves = [
+            Commitment([1; 32]),
+            Commitment([2; 32]),
+            Commitment([1; 32]),
+        ];
+        storage
+            .append_merkle_leaves(0, &leaves[..2], &[node(0, 1, 0), node(1, 1, 0)])
+            .unwrap();
+        storage
+            .append_merkle_leaves(2, &leaves[2..], &[node(0, 1, 1)])
+            .unwrap();
+
+        assert_eq!(storage.merkle_leaf_count().unwrap(), Some(3));
+        assert_eq!(storage.commitment_count().unwrap(), 3);
+        assert_eq!(storage.get_commitment(2).unwrap(), Some(leaves[2].clone()));
+        assert_eq!(storage.get_merkle_node(1, 1, 0).unwrap(), Some([11; 32]));
+        assert_eq!(storage.get_merkle_node(1, 1, 1).unwrap(), None);
+        assert_eq!(storage.merkle_leaf_index(&leaves[0]).unwrap(), Some(0));
+        assert_eq!(storage.merkle_leaf_index(&leaves[1]).unwrap(), Some(1));
+        assert_eq!(
+            storage.merkle_leaf_index(&Commitment([3; 32])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn deposits_page_in_leaf_order_and_replays_keep_one_copy() {
+        let dir = tempdir().unwrap();
+        let storage = PrivacyStorage::open(dir.path().join("privacy.db")).unwrap();
+        let record = |leaf_index: u64| DepositRecord {
+            commitment: [leaf_index as u8; 32],
+            leaf_index,
+            depositor: [9; 32],
+            amount: 100 + leaf_index,
+            asset_id: NATIVE_SOL_ASSET,
+            slot: 40 + leaf_index,
+            signature: format!("sig{leaf_index}"),
+        };
+        for leaf in [7u64, 2, 300, 2] {
+            storage.put_deposit(&record(leaf)).unwrap();
+        }
+
+        let all = storage.deposits_from(0, 10).unwrap();
+        let leaves: Vec<u64> = all.iter().map(|d| d.leaf_index).collect();
+        assert_eq!(leaves, vec![2, 7, 300]);
+        assert_eq!(all[0], record(2));
+
+        let page = storage.deposits_from(3, 1).unwrap();
+        assert_eq!(page, vec![record(7)]);
+        assert!(storage.deposits_from(301, 10).unwrap().is_empty());
+    }
 }
@@ -0,0 +1,224 @@
+//! Slashing evidence storage
+//!
+//! Persists the equivocation evidence the transact coordinator records, so a
+//! restart neither forgets an offence nor slashes it a second time.
+//!
+//! - `slashing_evidence`: one [`SlashingRecord`] per `(wallet, request_id)`,
+//!   keyed `wallet || 0x00 || request_id`. Base58 wallets never contain a zero
+//!   byte, so a prefix scan on `wallet || 0x00` lists one wallet's offences.
+//!
+//! The record carries its [`SlashStatus`](crate::consensus::SlashStatus), so
+//! the submitter's progress is written in the same place as the evidence.
+//! Writes are fsync'd: losing a `Submitted` status would slash the wallet
+//! again after a crash.
+
+use crate::consensus::SlashingRecord;
+use anyhow::{anyhow, Result};
+use log::info;
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, WriteOptions, DB};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Column family names
+const CF_SLASHING_EVIDENCE: &str = "slashing_evidence";
+
+fn durable_write_options() -> WriteOptions {
+    let mut opts = WriteOptions::default();
+    opts.set_sync(true);
+    opts
+}
+
+fn evidence_key(wallet: &str, request_id: &str) -> Vec<u8> {
+    let mut key = Vec::with_capacity(wallet.len() + 1 + request_id.len());
+    key.extend_from_slice(wallet.as_bytes());
+    key.push(0);
+    key.extend_from_slice(request_id.as_bytes());
+    key
+}
+
+fn record_key(record: &SlashingRecord) -> Result<Vec<u8>> {
+    let (wallet, request_id) = record
+        .evidence
+        .equivocation_key()
+        .ok_or_else(|| anyhow!("only equivocation evidence is persisted"))?;
+    Ok(evidence_key(wallet, request_id))
+}
+
+/// Slashing evidence storage using RocksDB column families
+pub struct SlashingStorage {
+    db: Arc<DB>,
+}
+
+impl SlashingStorage {
+    /// Open slashing storage with column families
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        info!("Opening slashing storage at {:?}", path.as_ref());
+
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+
+        let cf_evidence = ColumnFamilyDescriptor::new(CF_SLASHING_EVIDENCE, Options::default());
+        let db = DB::open_cf_descriptors(&options, path, vec![cf_evidence])?;
+
+        Ok(SlashingStorage { db: Arc::new(db) })
+    }
+
+    /// Open an existing store for reading while the node that owns it keeps
+    /// running. Sees what was written up to the moment it opened.
+    pub fn open_read_only<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let db =
+            DB::open_cf_for_read_only(&Options::default(), path, [CF_SLASHING_EVIDENCE], false)?;
+        Ok(SlashingStorage { db: Arc::new(db) })
+    }
+
+    /// Store equivocation evidence unless its `(wallet, request_id)` is
+    /// already on file. Returns whether it was new.
+    pub fn insert(&self, record: &SlashingRecord) -> Result<bool> {
+        let cf = self
+            .db
+            .cf_handle(CF_SLASHING_EVIDENCE)
+            .ok_or_else(|| anyhow!("Slashing evidence CF not found"))?;
+        let key = record_key(record)?;
+        if self.db.get_cf(cf, &key)?.is_some() {
+            return Ok(false);
+        }
+        self.db.put_cf_opt(
+            cf,
+            key,
+            bincode::serialize(record)?,
+            &durable_write_options(),
+        )?;
+        Ok(true)
+    }
+
+    /// Store `record`, replacing what is on file for its key — how a status
+    /// change is written.
+    pub fn insert_or_replace(&self, record: &SlashingRecord) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_SLASHING_EVIDENCE)
+            .ok_or_else(|| anyhow!("Slashing evidence CF not found"))?;
+        self.db.put_cf_opt(
+            cf,
+            record_key(record)?,
+            bincode::serialize(record)?,
+            &durable_write_options(),
+        )?;
+        Ok(())
+    }
+
+    /// The evidence on file for `wallet` equivocating on `request_id`.
+    pub fn get(&self, wallet: &str, request_id: &str) -> Result<Option<SlashingRecord>> {
+        let cf = self
+            .db
+            .cf_handle(CF_SLASHING_EVIDENCE)
+            .ok_or_else(|| anyhow!("Slashing evidence CF not found"))?;
+        match self.db.get_cf(cf, evidence_key(wallet, request_id))? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every record on file, ordered by wallet then request id.
+    pub fn all(&self) -> Result<Vec<SlashingRecord>> {
+        let cf = self
+            .db
+            .cf_handle(CF_SLASHING_EVIDENCE)
+            .ok_or_else(|| anyhow!("Slashing evidence CF not found"))?;
+        let mut records = Vec::new();
+        for item in self.db.iterator_cf(cf, IteratorMode::Start) {
+            let (_, value) = item?;
+            records.push(bincode::deserialize(&value)?);
+        }
+        Ok(records)
+    }
+
+    /// The records on file for one wallet.
+    pub fn for_wallet(&self, wallet: &str) -> Result<Vec<SlashingRecord>> {
+        let cf = self
+            .db
+            .cf_handle(CF_SLASHING_EVIDENCE)
+            .ok_or_else(|| anyhow!("Slashing evidence CF not found"))?;
+        let mut prefix = wallet.as_bytes().to_vec();
+        prefix.push(0);
+        let mut records = Vec::new();
+        for item in self.db.prefix_iterator_cf(cf, &prefix) {
+            let (key, value) = item?;
+            if !key.starts_with(&prefix) {
+                break;
+            }
+            records.push(bincode::deserialize(&value)?);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::{SlashStatus, SlashingEvidence, VerificationVote};
+    use crate::types::NodeId;
+    use tempfile::tempdir;
+
+    fn record(wallet: &str, request_id: &str) -> SlashingRecord {
+        SlashingRecord::new(
+            NodeId(vec![2]),
+            SlashingEvidence::Equivocation {
+                request_id: request_id.to_string(),
+                epoch: 0,
+                wallet_pubkey: wallet.to_string(),
+                previous_validator: NodeId(vec![1]),
+                previous_vote: VerificationVote::Valid,
+                new_vote: VerificationVote::Invalid {
+                    reason: "flip".to_string(),
+                },
+                previous_signature: vec![1; 64],
+                new_signature: vec![2; 64],
+            },
+        )
+    }
+
+    #[test]
+    fn evidence_is_kept_once_per_wallet_and_request_across_reopens() {
+        let dir = tempdir().unwrap();
+        {
+            let storage = SlashingStorage::open(dir.path()).unwrap();
+            assert!(storage.insert(&record("W1", "r1")).unwrap());
+            assert!(!storage.insert(&record("W1", "r1")).unwrap());
+            assert!(storage.insert(&record("W1", "r2")).unwrap());
+            assert!(storage.insert(&record("W10", "r1")).unwrap());
+
+            let mut submitted = record("W1", "r2");
+            submitted.status = SlashStatus::Submitted {
+                signature: "sig".to_string(),
+            };
+            storage.insert_or_replace(&submitted).unwrap();
+        }
+
+        let storage = SlashingStorage::open(dir.path()).unwrap();
+        assert_eq!(storage.all().unwrap().len(), 3);
+        // The `0x00` separator keeps `W10` out of `W1`'s prefix.
+        assert_eq!(storage.for_wallet("W1").unwrap().len(), 2);
+        assert_eq!(
+            storage.get("W1", "r2").unwrap().unwrap().status,
+            SlashStatus::Submitted {
+                signature: "sig".to_string()
+            }
+        );
+        assert!(storage.get("W2", "r1").unwrap().is_none());
+
+        let unavailable = SlashingRecord::new(
+            NodeId(vec![2]),
+            SlashingEvidence::PersistentUnavailability {
+                streak_length: 3,
+                threshold: 3,
+            },
+        );
+        assert!(storage.insert(&unavailable).is_err());
+    }
+}
@@ -0,0 +1,574 @@
+//! Shielded wallet storage
+//!
+//! The client-side counterpart of [`PrivacyStorage`](super::PrivacyStorage):
+//! where the node persists the pool, this persists what one wallet has found
+//! in it.
+//!
+//! - `wallet_notes`: every note the wallet owns, keyed by its 32-byte output
+//!   commitment. Discovering the same note twice (a re-scan, or the same
+//!   transfer served by two nodes) overwrites rather than duplicates.
+//! - `wallet_history`: the received / sent / change log, keyed by a
+//!   big-endian sequence number so RocksDB's bytewise iteration order is the
+//!   order the entries were appended in.
+//! - `wallet_sent`: output commitments this wallet paid to someone else and
+//!   has logged as sent, so recovering the same payment again from its
+//!   outgoing record (a re-scan after restore) does not log it twice.
+//! - `wallet_memos`: the memo a v2 note carried, keyed by the note's output
+//!   commitment. Kept apart from the history so entries written before memos
+//!   existed still decode.
+//! - `wallet_pending`: deposits signed but not yet seen on chain, keyed by
+//!   the note's commitment. One is written before its transaction is sent
+//!   and moved into `wallet_notes` once it lands, so a confirmation that
+//!   times out or a crash after sending cannot lose the note's opening.
+//! - `wallet_state`: small scalars (the next history sequence number, the
+//!   next deposit nonce, and how far into each node's note feed the wallet
+//!   has scanned).
+//!
+//! Note and history writes are fsync'd. A wallet that forgets a note it has
+//! already been told about cannot see that balance again until the next full
+//! re-scan, and one that forgets it spent a note will try to spend it twice.
+
+use crate::wallet::{HistoryEntry, OwnedNote, PendingDeposit};
+use anyhow::{anyhow, Result};
+use log::info;
+use rocksdb::{ColumnFamilyDescriptor, Options, WriteOptions, DB};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Column family names
+const CF_WALLET_NOTES: &str = "wallet_notes";
+const CF_WALLET_HISTORY: &str = "wallet_history";
+const CF_WALLET_STATE: &str = "wallet_state";
+const CF_WALLET_SENT: &str = "wallet_sent";
+const CF_WALLET_MEMOS: &str = "wallet_memos";
+const CF_WALLET_PENDING: &str = "wallet_pending";
+
+/// `CF_WALLET_STATE` key holding the next history sequence number.
+const NEXT_HISTORY_SEQ_KEY: &[u8] = b"next_history_seq";
+/// `CF_WALLET_STATE` key holding the next deposit nonce.
+const NEXT_DEPOSIT_NONCE_KEY: &[u8] = b"next_deposit_nonce";
+/// `CF_WALLET_STATE` key prefix of a node's scan cursor; the node's feed
+/// name follows it.
+const SCAN_CURSOR_PREFIX: &[u8] = b"scan_cursor/";
+
+fn durable_write_options() -> WriteOptions {
+    let mut opts = WriteOptions::default();
+    opts.set_sync(true);
+    opts
+}
+
+/// Wallet storage using RocksDB column families
+pub struct WalletStorage {
+    db: Arc<DB>,
+}
+
+impl WalletStorage {
+    /// Open wallet storage with column families
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        info!("Opening wallet storage at {:?}", path.as_ref());
+
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut options = Options::default();
+        options.create_if_missing(true);
+        options.create_missing_column_families(true);
+        options.set_keep_log_file_num(5);
+
+        let cf_notes = ColumnFamilyDescriptor::new(CF_WALLET_NOTES, Options::default());
+        let cf_history = ColumnFamilyDescriptor::new(CF_WALLET_HISTORY, Options::default());
+        let cf_state = ColumnFamilyDescriptor::new(CF_WALLET_STATE, Options::default());
+        let cf_sent = ColumnFamilyDescriptor::new(CF_WALLET_SENT, Options::default());
+        let cf_memos = ColumnFamilyDescriptor::new(CF_WALLET_MEMOS, Options::default());
+        let cf_pending = ColumnFamilyDescriptor::new(CF_WALLET_PENDING, Options::default());
+
+        let db = DB::open_cf_descriptors(
+            &options,
+            path,
+            vec![
+                cf_notes, cf_history, cf_state, cf_sent, cf_memos, cf_pending,
+            ],
+        )?;
+
+        Ok(WalletStorage { db: Arc::new(db) })
+    }
+
+    // ========== Note Operations ==========
+
+    /// Insert or replace an owned note, keyed by its commitment.
+    pub fn put_note(&self, note: &OwnedNote) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_NOTES)
+            .ok_or_else(|| anyhow!("Wallet notes CF not found"))?;
+
+        let value = bincode::serialize(note)?;
+        self.db
+            .put_cf_opt(cf, note.commitment, value, &durable_write_options())?;
+        Ok(())
+    }
+
+    /// Get an owned note by commitment
+    pub fn get_note(&self, commitment: &[u8; 32]) -> Result<Option<OwnedNote>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_NOTES)
+            .ok_or_else(|| anyhow!("Wallet notes CF not found"))?;
+
+        match self.db.get_cf(cf, commitment)? {
+            Some(bytes) => Ok(Some(decode_note(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Get every owned note, spent or not.
+    pub fn get_all_notes(&self) -> Result<Vec<OwnedNote>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_NOTES)
+            .ok_or_else(|| anyhow!("Wallet notes CF not found"))?;
+
+        let mut notes = Vec::new();
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (_, value) = item?;
+            notes.push(decode_note(&value)?);
+        }
+        Ok(notes)
+    }
+
+    // ========== Deposit Nonce Operations ==========
+
+    /// Hand out the next deposit nonce. The counter is fsync'd before the
+    /// nonce is returned, so a nonce is never handed out twice.
+    pub fn take_deposit_nonce(&self) -> Result<u64> {
+        let nonce = self.next_deposit_nonce()?;
+        self.set_next_deposit_nonce(nonce + 1)?;
+        Ok(nonce)
+    }
+
+    /// Make sure no nonce below `next` is handed out again.
+    pub fn raise_deposit_nonce(&self, next: u64) -> Result<()> {
+        if next > self.next_deposit_nonce()? {
+            self.set_next_deposit_nonce(next)?;
+        }
+        Ok(())
+    }
+
+    /// The nonce [`take_deposit_nonce`](Self::take_deposit_nonce) hands out
+    /// next.
+    pub fn next_deposit_nonce(&self) -> Result<u64> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+
+        match self.db.get_cf(cf, NEXT_DEPOSIT_NONCE_KEY)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid deposit nonce size"))?;
+                Ok(u64::from_le_bytes(arr))
+            }
+            None => Ok(0),
+        }
+    }
+
+    fn set_next_deposit_nonce(&self, next: u64) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+        self.db.put_cf_opt(
+            cf,
+            NEXT_DEPOSIT_NONCE_KEY,
+            next.to_le_bytes(),
+            &durable_write_options(),
+        )?;
+        Ok(())
+    }
+
+    // ========== Pending Deposit Operations ==========
+
+    /// Insert or replace a pending deposit, keyed by its note's commitment.
+    pub fn put_pending_deposit(&self, pending: &PendingDeposit) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_PENDING)
+            .ok_or_else(|| anyhow!("Wallet pending CF not found"))?;
+
+        let value = bincode::serialize(pending)?;
+        self.db
+            .put_cf_opt(cf, pending.note.commitment, value, &durable_write_options())?;
+        Ok(())
+    }
+
+    /// Get a pending deposit by its note's commitment.
+    pub fn get_pending_deposit(&self, commitment: &[u8; 32]) -> Result<Option<PendingDeposit>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_PENDING)
+            .ok_or_else(|| anyhow!("Wallet pending CF not found"))?;
+
+        match self.db.get_cf(cf, commitment)? {
+            Some(bytes) => Ok(Some(bincode::deserialize(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Every pending deposit.
+    pub fn get_pending_deposits(&self) -> Result<Vec<PendingDeposit>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_PENDING)
+            .ok_or_else(|| anyhow!("Wallet pending CF not found"))?;
+
+        let mut pending = Vec::new();
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (_, value) = item?;
+            pending.push(bincode::deserialize(&value)?);
+        }
+        Ok(pending)
+    }
+
+    /// Forget a pending deposit.
+    pub fn delete_pending_deposit(&self, commitment: &[u8; 32]) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_PENDING)
+            .ok_or_else(|| anyhow!("Wallet pending CF not found"))?;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        batch.delete_cf(cf, commitment);
+        self.db.write_opt(batch, &durable_write_options())?;
+        Ok(())
+    }
+
+    // ========== History Operations ==========
+
+    /// Append a history entry. The sequence counter and the entry are written
+    /// in one batch, so a crash cannot leave a sequence number that is handed
+    /// out twice.
+    pub fn append_history(&self, entry: &HistoryEntry) -> Result<u64> {
+        let cf_history = self
+            .db
+            .cf_handle(CF_WALLET_HISTORY)
+            .ok_or_else(|| anyhow!("Wallet history CF not found"))?;
+        let cf_state = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+
+        let seq = match self.db.get_cf(cf_state, NEXT_HISTORY_SEQ_KEY)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid history sequence size"))?;
+                u64::from_le_bytes(arr)
+            }
+            None => 0,
+        };
+
+        let mut batch = rocksdb::WriteBatch::default();
+        // Big-endian so bytewise key order is numeric order.
+        batch.put_cf(cf_history, seq.to_be_bytes(), bincode::serialize(entry)?);
+        batch.put_cf(cf_state, NEXT_HISTORY_SEQ_KEY, (seq + 1).to_le_bytes());
+        self.db.write_opt(batch, &durable_write_options())?;
+
+        Ok(seq)
+    }
+
+    /// The most recent `limit` history entries, newest first.
+    pub fn recent_history(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_HISTORY)
+            .ok_or_else(|| anyhow!("Wallet history CF not found"))?;
+
+        let mut entries = Vec::new();
+        for item in self
+            .db
+            .iterator_cf(cf, rocksdb::IteratorMode::End)
+            .take(limit)
+        {
+            let (_, value) = item?;
+            entries.push(bincode::deserialize(&value)?);
+        }
+        Ok(entries)
+    }
+
+    /// Remember that the payment output `commitment` has been logged as
+    /// sent. Returns `false` if it already was.
+    pub fn mark_sent_output(&self, commitment: &[u8; 32]) -> Result<bool> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_SENT)
+            .ok_or_else(|| anyhow!("Wallet sent CF not found"))?;
+
+        if self.db.get_cf(cf, commitment)?.is_some() {
+            return Ok(false);
+        }
+        self.db
+            .put_cf_opt(cf, commitment, b"", &durable_write_options())?;
+        Ok(true)
+    }
+
+    /// The position in `feed`'s delivered-note sequence the wallet has
+    /// ingested up to, or 0 if it has never scanned it. Each node numbers its
+    /// own feed, so the cursor is kept per feed.
+    pub fn scan_cursor(&self, feed: &str) -> Result<u64> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+
+        match self.db.get_cf(cf, scan_cursor_key(feed))? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid scan cursor size"))?;
+                Ok(u64::from_le_bytes(arr))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Record that everything in `feed` up to `cursor` has been ingested.
+    /// Call only once the notes it covers are stored.
+    pub fn set_scan_cursor(&self, feed: &str, cursor: u64) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+        self.db.put_cf_opt(
+            cf,
+            scan_cursor_key(feed),
+            cursor.to_le_bytes(),
+            &durable_write_options(),
+        )?;
+        Ok(())
+    }
+
+    /// Forget `feed`'s cursor, so the next scan starts from its beginning.
+    pub fn reset_scan_cursor(&self, feed: &str) -> Result<()> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+        self.db
+            .delete_cf_opt(cf, scan_cursor_key(feed), &durable_write_options())?;
+        Ok(())
+    }
+
+    /// Store the memo that came with the output `commitment`. An empty memo
+    /// is not stored.
+    pub fn put_memo(&self, commitment: &[u8; 32], memo: &[u8]) -> Result<()> {
+        if memo.is_empty() {
+            return Ok(());
+        }
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_MEMOS)
+            .ok_or_else(|| anyhow!("Wallet memos CF not found"))?;
+        self.db
+            .put_cf_opt(cf, commitment, memo, &durable_write_options())?;
+        Ok(())
+    }
+
+    /// The memo stored for `commitment`, if any.
+    pub fn get_memo(&self, commitment: &[u8; 32]) -> Result<Option<Vec<u8>>> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_MEMOS)
+            .ok_or_else(|| anyhow!("Wallet memos CF not found"))?;
+        Ok(self.db.get_cf(cf, commitment)?)
+    }
+
+    /// Total number of history entries
+    pub fn history_count(&self) -> Result<u64> {
+        let cf = self
+            .db
+            .cf_handle(CF_WALLET_STATE)
+            .ok_or_else(|| anyhow!("Wallet state CF not found"))?;
+
+        match self.db.get_cf(cf, NEXT_HISTORY_SEQ_KEY)? {
+            Some(bytes) => {
+                let arr: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| anyhow!("Invalid history sequence size"))?;
+                Ok(u64::from_le_bytes(arr))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Flush all data to disk
+    pub fn flush(&self) -> Result<()> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+fn scan_cursor_key(feed: &str) -> Vec<u8> {
+    [SCAN_CURSOR_PREFIX, feed.as_bytes()].concat()
+}
+
+/// Decode a stored note. Notes filed before diversified addresses end where
+/// the trailing `diversifier` begins; bincode writes `None` as one zero byte,
+/// so they decode once it is appended.
+fn decode_note(bytes: &[u8]) -> Result<OwnedNote> {
+    match bincode::deserialize(bytes) {
+        Ok(note) => Ok(note),
+        Err(e) => {
+            let mut legacy = bytes.to_vec();
+            legacy.push(0);
+            bincode::deserialize(&legacy).map_err(|_| e.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::types::NATIVE_SOL_ASSET;
+    use crate::wallet::{HistoryKind, NoteOrigin};
+    use tempfile::tempdir;
+
+    fn note(fill: u8) -> OwnedNote {
+        OwnedNote {
+            commitment: [fill; 32],
+            amount: 1_000,
+            blinding: [fill.wrapping_add(1); 32],
+            asset_id: NATIVE_SOL_ASSET,
+            mint: None,
+            leaf_index: None,
+            nullifier: None,
+            origin: NoteOrigin::Received,
+            spent: false,
+            discovered_at: 0,
+            diversifier: None,
+        }
+    }
+
+    #[test]
+    fn note_put_is_idempotent_by_commitment() {
+        let dir = tempdir().unwrap();
+        let storage = WalletStorage::open(dir.path().join("wallet.db")).unwrap();
+
+        let mut n = note(7);
+        storage.put_note(&n).unwrap();
+        n.leaf_index = Some(3);
+        storage.put_note(&n).unwrap();
+
+        let all = storage.get_all_notes().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(
+            storage.get_note(&[7; 32]).unwrap().unwrap().leaf_index,
+            Some(3)
+        );
+        assert!(storage.get_note(&[8; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn history_is_returned_newest_first_past_256_entries() {
+        // The sequence key is big-endian precisely so that entry 256 sorts
+        // after entry 255; a little-endian key would put it second.
+        let dir = tempdir().unwrap();
+        let storage = WalletStorage::open(dir.path().join("wallet.db")).unwrap();
+
+        for i in 0..300u64 {
+            storage
+                .append_history(&HistoryEntry {
+                    kind: HistoryKind::Received,
+                    amount: i,
+                    asset_id: NATIVE_SOL_ASSET,
+                    commitment: None,
+                    counterparty: None,
+                    timestamp: i,
+                })
+                .unwrap();
+        }
+
+        assert_eq!(storage.history_count().unwrap(), 300);
+        let recent = storage.recent_history(3).unwrap();
+        let amounts: Vec<u64> = recent.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, vec![299, 298, 297]);
+    }
+
+    #[test]
+    fn sent_outputs_are_marked_once() {
+        let dir = tempdir().unwrap();
+        let storage = WalletStorage::open(dir.path().join("wallet.db")).unwrap();
+        assert!(storage.mark_sent_output(&[4; 32]).unwrap());
+        assert!(!storage.mark_sent_output(&[4; 32]).unwrap());
+        assert!(storage.mark_sent_output(&[5; 32]).unwrap());
+    }
+
+    #[test]
+    fn memos_are_kept_by_commitment() {
+        let dir = tempdir().unwrap();
+        let storage = WalletStorage::open(dir.path().join("wallet.db")).unwrap();
+        storage.put_memo(&[1; 32], b"rent").unwrap();
+        storage.put_memo(&[2; 32], b"").unwrap();
+        assert_eq!(storage.get_memo(&[1; 32]).unwrap().unwrap(), b"rent");
+        assert!(storage.get_memo(&[2; 32]).unwrap().is_none());
+    }
+
+    #[test]
+    fn notes_filed_before_diversifiers_still_decode() {
+        let n = note(4);
+        let mut legacy = bincode::serialize(&n).unwrap();
+        assert_eq!(legacy.pop(), Some(0));
+        assert_eq!(decode_note(&legacy).unwrap(), n);
+    }
+
+    #[test]
+    fn deposit_nonces_are_handed_out_once() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.db");
+        {
+            let storage = WalletStorage::open(&path).unwrap();
+            assert_eq!(storage.take_deposit_nonce().unwrap(), 0);
+            assert_eq!(storage.take_deposit_nonce().unwrap(), 1);
+        }
+        let storage = WalletStorage::open(&path).unwrap();
+        assert_eq!(storage.take_deposit_nonce().unwrap(), 2);
+        storage.raise_deposit_nonce(10).unwrap();
+        storage.raise_deposit_nonce(4).unwrap();
+        assert_eq!(storage.take_deposit_nonce().unwrap(), 10);
+    }
+
+    #[test]
+    fn scan_cursors_are_kept_per_feed_until_reset() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.db");
+        {
+            let storage = WalletStorage::open(&path).unwrap();
+            assert_eq!(storage.scan_cursor("http://a").unwrap(), 0);
+            storage.set_scan_cursor("http://a", 7).unwrap();
+            storage.set_scan_cursor("http://b", 3).unwrap();
+        }
+        let storage = WalletStorage::open(&path).unwrap();
+        assert_eq!(storage.scan_cursor("http://a").unwrap(), 7);
+        assert_eq!(storage.scan_cursor("http://b").unwrap(), 3);
+        storage.reset_scan_cursor("http://a").unwrap();
+        assert_eq!(storage.scan_cursor("http://a").unwrap(), 0);
+        assert_eq!(storage.scan_cursor("http://b").unwrap(), 3);
+    }
+
+    #[test]
+    fn notes_survive_reopen() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("wallet.db");
+        {
+            let storage = WalletStorage::open(&path).unwrap();
+            storage.put_note(&note(1)).unwrap();
+        }
+        let storage = WalletStorage::open(&path).unwrap();
+        assert_eq!(storage.get_all_notes().unwrap().len(), 1);
+    }
+}
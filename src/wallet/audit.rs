@@ -0,0 +1,162 @@
+//! Auditor mode: what an [`IncomingViewingKey`] can see.
+//!
+//! The auditor runs the same trial decryption and commitment check over the
+//! node's note feed as the wallet does in [`Wallet::ingest`](super::Wallet::ingest),
+//! without the spend key. It therefore lists every note paid to the wallet
+//! but cannot derive nullifiers, so the totals are what was received, not
+//! what is left.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{open_delivered, OpenOutcome};
+use crate::node::transact_ingress::DeliveredNote;
+use crate::privacy::disclosure::IncomingViewingKey;
+use crate::privacy::types::AssetId;
+
+/// One incoming note, as opened by the viewing key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditedNote {
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub blinding: [u8; 32],
+    pub asset_id: AssetId,
+    /// The SPL mint behind `asset_id`; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+    /// The sender's memo; empty if there was none.
+    pub memo: Vec<u8>,
+}
+
+/// The result of auditing a note feed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Delivered notes looked at.
+    pub scanned: usize,
+    /// Notes paid to the audited wallet, in feed order.
+    pub notes: Vec<AuditedNote>,
+    /// Notes that decrypted under the key but did not open their commitment.
+    pub rejected: usize,
+}
+
+impl AuditReport {
+    /// Total received per asset.
+    pub fn received(&self) -> BTreeMap<AssetId, u64> {
+        let mut totals = BTreeMap::new();
+        for note in &self.notes {
+            let entry = totals.entry(note.asset_id).or_insert(0u64);
+            *entry = entry.saturating_add(note.amount);
+        }
+        totals
+    }
+}
+
+/// Open every note in `delivered` that was paid to the wallet `ivk` watches.
+pub fn audit(ivk: &IncomingViewingKey, delivered: &[DeliveredNote]) -> AuditReport {
+    let mut report = AuditReport {
+        scanned: delivered.len(),
+        ..Default::default()
+    };
+    let mut seen = BTreeSet::new();
+    for d in delivered {
+        match open_delivered(ivk, d) {
+            Ok((note, memo)) => {
+                if seen.insert(note.commitment) {
+                    report.notes.push(AuditedNote {
+                        commitment: note.commitment,
+                        amount: note.amount,
+                        blinding: note.blinding,
+                        asset_id: note.asset_id,
+                        mint: note.mint,
+                        memo,
+                    });
+                }
+            }
+            Err(OpenOutcome::NotOurs) => {}
+            Err(OpenOutcome::Forged) => report.rejected += 1,
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::note_crypto::{encrypt_note, NotePlaintext};
+    use crate::privacy::poseidon_circom::mint_to_asset;
+    use crate::privacy::types::NATIVE_SOL_ASSET;
+    use crate::wallet::{note_commitment, Wallet, WalletKeys};
+
+    fn deliver(keys: &WalletKeys, amount: u64, mint: Option<[u8; 32]>) -> DeliveredNote {
+        let blinding: [u8; 32] = rand::random();
+        let asset = mint.as_ref().map(mint_to_asset).unwrap_or(NATIVE_SOL_ASSET);
+        let ct = encrypt_note(
+            &keys.transmission_key(),
+            &NotePlaintext {
+                amount,
+                randomness: blinding,
+                recipient: keys.spend_pubkey(),
+                asset_id: None,
+                memo: Vec::new(),
+                diversifier: None,
+            },
+        );
+        DeliveredNote {
+            output_commitment: hex::encode(note_commitment(keys, amount, &blinding, &asset)),
+            ciphertext: hex::encode(ct.to_bytes()),
+            mint: mint.map(hex::encode),
+            leaf_index: None,
+            slot: None,
+        }
+    }
+
+    #[test]
+    fn viewing_key_sees_what_the_wallet_receives() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Wallet::open(dir.path()).unwrap();
+        let first = deliver(wallet.keys(), 40, None);
+        let feed = vec![
+            first.clone(),
+            deliver(&WalletKeys::generate(), 99, None),
+            deliver(wallet.keys(), 5, Some([3; 32])),
+            first,
+        ];
+        wallet.ingest(&feed).unwrap();
+
+        let ivk =
+            IncomingViewingKey::parse(&wallet.keys().incoming_viewing_key().encode()).unwrap();
+        let report = audit(&ivk, &feed);
+        assert_eq!(report.scanned, 4);
+        assert_eq!(report.notes.len(), 2);
+        assert_eq!(report.rejected, 0);
+        assert_eq!(report.received(), wallet.balances().unwrap());
+    }
+
+    #[test]
+    fn notes_that_do_not_open_their_commitment_are_counted_not_listed() {
+        let keys = WalletKeys::generate();
+        let mut forged = deliver(&keys, 10, None);
+        forged.output_commitment = hex::encode([5u8; 32]);
+        let report = audit(&keys.incoming_viewing_key(), &[forged]);
+        assert!(report.notes.is_empty());
+        assert_eq!(report.rejected, 1);
+    }
+
+    #[test]
+    fn wallet_disclosures_verify_against_its_viewing_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let wallet = Wallet::open(dir.path()).unwrap();
+        let note = deliver(wallet.keys(), 7, Some([4; 32]));
+        wallet.ingest(std::slice::from_ref(&note)).unwrap();
+
+        let commitment: [u8; 32] = hex::decode(&note.output_commitment)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let disclosure = wallet.disclose(&commitment).unwrap();
+        assert_eq!(disclosure.opening.amount, 7);
+        disclosure
+            .verify_for(&wallet.keys().incoming_viewing_key())
+            .unwrap();
+
+        assert!(wallet.disclose(&[0; 32]).is_err());
+    }
+}
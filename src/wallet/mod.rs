@@ -0,0 +1,1342 @@
+//! Client-side shielded wallet.
+//!
+//! The node serves every encrypted output note it has verified at
+//! `GET /transact/scan` (see [`crate::node::transact_ingress`]). A wallet
+//! polls that feed, trial-decrypts each ciphertext with its viewing key, and
+//! keeps the notes that are both addressed to it and actually committed on
+//! chain. Those notes, their leaf positions and nullifiers, and a log of what
+//! was received, sent and returned as change live in a [`WalletStorage`].
+//!
+//! A delivered ciphertext is not proof-bound: the circuit commits to the
+//! output commitments, not to the bytes a sender claims open them. So a
+//! decrypted plaintext is only accepted if it re-derives the output
+//! commitment it was delivered under — otherwise anyone could hand the wallet
+//! a note it can never spend and inflate its balance.
+//!
+//! Outputs this wallet creates also carry an outgoing record sealed to its
+//! outgoing viewing key (see [`crate::privacy::note_crypto`]). Scanning them
+//! back is how a wallet restored from its recovery phrase learns what it
+//! paid, to whom, and which of its notes were change.
+//!
+//! Deposits never pass through the note feed. Their blindings are derived
+//! from the spend key and a deposit nonce instead (see [`keys`]), and a
+//! restored wallet finds them by re-deriving commitments against the pool's
+//! deposit records.
+
+pub mod audit;
+pub mod keys;
+pub mod transfer;
+
+pub use audit::{audit, AuditReport, AuditedNote};
+pub use keys::{generate_mnemonic, WalletKeys};
+pub use transfer::{
+    build_transfer, build_withdraw, fetch_inputs, fetch_membership, select_inputs, submit_transact,
+    OutputNote, ProvedTransact, SpendInput, TransactPlan,
+};
+
+use anyhow::Result;
+use ark_bn254::Fr;
+use ark_ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::node::transact_ingress::{DeliveredNote, DeliveredPage};
+use crate::privacy::address::PaymentAddress;
+use crate::privacy::disclosure::{IncomingViewingKey, NoteDisclosure, NoteOpening};
+use crate::privacy::diversifier::Diversifier;
+use crate::privacy::note_crypto::EncryptedNote;
+use crate::privacy::poseidon_circom::{mint_to_asset, v3_commit, v3_nullifier, v3_signature};
+use crate::privacy::types::{
+    fr_to_bytes_32, AssetId, ShieldedAddress, ViewingKey, NATIVE_SOL_ASSET,
+};
+use crate::storage::{DepositRecord, WalletStorage};
+
+/// Deposit nonces tried on either side of the highest one found so far. A
+/// deposit that was never sent or never landed burns its nonce, and two in
+/// flight at once can land out of order; a gap this wide in the wallet's own
+/// deposits ends the search.
+pub const DEPOSIT_NONCE_GAP: u64 = 20;
+
+/// How an owned note came to the wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NoteOrigin {
+    /// Delivered by someone else (or by this wallet's own deposit).
+    Received,
+    /// The change output of a spend this wallet made.
+    Change,
+}
+
+/// A note this wallet can spend.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnedNote {
+    /// The v3 output commitment, as appended to the on-chain tree.
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub blinding: [u8; 32],
+    pub asset_id: AssetId,
+    /// The SPL mint behind `asset_id`; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+    /// Position in the on-chain tree, once known. The nullifier binds it, so
+    /// a note without one cannot be spent or checked for spentness yet.
+    pub leaf_index: Option<u64>,
+    /// The v3 nullifier, derived as soon as `leaf_index` is known.
+    pub nullifier: Option<[u8; 32]>,
+    pub origin: NoteOrigin,
+    pub spent: bool,
+    /// Unix seconds when the wallet first learned of the note.
+    pub discovered_at: u64,
+    /// The diversified address the note was paid to; `None` for the default
+    /// address. Kept last: notes stored before it existed decode without it
+    /// (see [`WalletStorage`]).
+    #[serde(default)]
+    pub diversifier: Option<Diversifier>,
+}
+
+impl OwnedNote {
+    /// Record the note's tree position and derive its nullifier.
+    pub fn set_leaf_index(&mut self, keys: &WalletKeys, leaf_index: u64) {
+        self.leaf_index = Some(leaf_index);
+        self.nullifier = Some(note_nullifier(
+            &keys.spend_key_for(self.diversifier.as_ref()),
+            &self.commitment,
+            leaf_index,
+        ));
+    }
+}
+
+/// A deposit this wallet has signed but not yet seen land. It is filed
+/// before the transaction is sent, because the blinding inside `note` exists
+/// nowhere else: a deposit that lands after the wallet lost track of it
+/// could never be spent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingDeposit {
+    /// The note the deposit appends, without a leaf index yet.
+    pub note: OwnedNote,
+    /// Base58 signature of the deposit transaction.
+    pub signature: String,
+    /// Base58 blockhash the transaction was signed against. Once the cluster
+    /// no longer accepts it, a deposit that has not landed never will.
+    pub blockhash: String,
+}
+
+/// Kind of a wallet history entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryKind {
+    Received,
+    Sent,
+    Change,
+}
+
+impl std::fmt::Display for HistoryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryKind::Received => write!(f, "Received"),
+            HistoryKind::Sent => write!(f, "Sent"),
+            HistoryKind::Change => write!(f, "Change"),
+        }
+    }
+}
+
+/// One line of wallet history.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub kind: HistoryKind,
+    pub amount: u64,
+    pub asset_id: AssetId,
+    /// The output commitment the entry concerns, when there is one.
+    pub commitment: Option<[u8; 32]>,
+    /// Free-form description of the other side (an address, a Solana
+    /// recipient), for sends.
+    pub counterparty: Option<String>,
+    /// Unix seconds.
+    pub timestamp: u64,
+}
+
+/// What one [`Wallet::ingest`] pass found.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IngestReport {
+    /// Delivered notes examined.
+    pub scanned: usize,
+    /// Notes newly added to the wallet.
+    pub discovered: usize,
+    /// Notes that decrypted under this wallet's key but did not re-derive the
+    /// commitment they were delivered under, and were dropped.
+    pub rejected: usize,
+    /// Payments to others newly recovered from this wallet's outgoing records.
+    pub sent: usize,
+}
+
+/// The v3 nullifier of the note `commitment` at `leaf_index` owned by
+/// `spend_key`: `Poseidon(3)([commitment, leaf_index, Poseidon(3)([sk,
+/// commitment, leaf_index])])`, exactly what `TransactCircuitV3` reveals and
+/// the program seeds the nullifier PDA with.
+///
+/// Not [`SpendKeypair::nullifier`](crate::privacy::types::SpendKeypair::nullifier):
+/// that is the domain-separated v2 construction, and a v2 nullifier never
+/// appears on chain.
+pub fn note_nullifier(spend_key: &[u8; 32], commitment: &[u8; 32], leaf_index: u64) -> [u8; 32] {
+    let sk = Fr::from_le_bytes_mod_order(spend_key);
+    let c = Fr::from_le_bytes_mod_order(commitment);
+    let idx = Fr::from(leaf_index);
+    fr_to_bytes_32(v3_nullifier(c, idx, v3_signature(sk, c, idx)))
+}
+
+/// The v3 commitment of a note owned by `keys`.
+pub fn note_commitment(
+    keys: &WalletKeys,
+    amount: u64,
+    blinding: &[u8; 32],
+    asset_id: &AssetId,
+) -> [u8; 32] {
+    commitment_to(&keys.spend_pubkey(), amount, blinding, asset_id)
+}
+
+fn commitment_to(
+    owner: &[u8; 32],
+    amount: u64,
+    blinding: &[u8; 32],
+    asset_id: &AssetId,
+) -> [u8; 32] {
+    fr_to_bytes_32(v3_commit(
+        Fr::from(amount),
+        Fr::from_le_bytes_mod_order(owner),
+        Fr::from_le_bytes_mod_order(blinding),
+        Fr::from_le_bytes_mod_order(asset_id),
+    ))
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).ok()
+}
+
+fn decode_hex32(s: &str) -> Option<[u8; 32]> {
+    decode_hex(s)?.try_into().ok()
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Why a delivered note was not taken into the wallet.
+#[derive(Debug, PartialEq, Eq)]
+enum OpenOutcome {
+    /// Not for this wallet (or not parseable) — the silent common case.
+    NotOurs,
+    /// Decrypted under our key but does not open the commitment it claims.
+    Forged,
+}
+
+/// Trial-decrypt one delivered note and check it opens its commitment,
+/// returning the note and the memo it carried (empty under v1). Only needs
+/// the incoming viewing key, so auditors run the same check. The note keeps
+/// the feed's leaf index; its nullifier needs the spend key and is left to
+/// the caller.
+fn open_delivered(
+    ivk: &IncomingViewingKey,
+    delivered: &DeliveredNote,
+) -> std::result::Result<(OwnedNote, Vec<u8>), OpenOutcome> {
+    let commitment = decode_hex32(&delivered.output_commitment).ok_or(OpenOutcome::NotOurs)?;
+    let blob = decode_hex(&delivered.ciphertext).ok_or(OpenOutcome::NotOurs)?;
+    let envelope = EncryptedNote::from_bytes(&blob).map_err(|_| OpenOutcome::NotOurs)?;
+    let plaintext = ivk
+        .viewing_key()
+        .try_decrypt(&envelope)
+        .ok_or(OpenOutcome::NotOurs)?;
+
+    // A note encrypted to our viewing key but bound to someone else's spend
+    // key is readable and unspendable; it is not ours.
+    if !ivk.owns(&plaintext.recipient, plaintext.diversifier.as_ref()) {
+        return Err(OpenOutcome::Forged);
+    }
+
+    let mint = match &delivered.mint {
+        Some(m) => Some(decode_hex32(m).ok_or(OpenOutcome::Forged)?),
+        None => None,
+    };
+    let asset_id = mint.as_ref().map(mint_to_asset).unwrap_or(NATIVE_SOL_ASSET);
+    // A v2 note names its asset; it has to be the one it was delivered as.
+    if plaintext.asset_id.is_some_and(|named| named != asset_id) {
+        return Err(OpenOutcome::Forged);
+    }
+
+    if commitment_to(
+        &plaintext.recipient,
+        plaintext.amount,
+        &plaintext.randomness,
+        &asset_id,
+    ) != commitment
+    {
+        return Err(OpenOutcome::Forged);
+    }
+
+    let note = OwnedNote {
+        commitment,
+        amount: plaintext.amount,
+        blinding: plaintext.randomness,
+        asset_id,
+        mint,
+        leaf_index: delivered.leaf_index,
+        nullifier: None,
+        origin: NoteOrigin::Received,
+        spent: false,
+        discovered_at: now_secs(),
+        diversifier: plaintext.diversifier,
+    };
+    Ok((note, plaintext.memo))
+}
+
+/// One output this wallet paid, recovered from its outgoing record.
+struct SentOutput {
+    commitment: [u8; 32],
+    recipient: PaymentAddress,
+    amount: u64,
+    asset_id: AssetId,
+}
+
+/// Open the outgoing record of a delivered note with the wallet's outgoing
+/// viewing key, and check it re-derives the commitment. `None` for a v1
+/// envelope, someone else's record, or one that does not match.
+fn open_outgoing(ovk: &ViewingKey, delivered: &DeliveredNote) -> Option<SentOutput> {
+    let commitment = decode_hex32(&delivered.output_commitment)?;
+    let blob = decode_hex(&delivered.ciphertext)?;
+    let (_, record) = EncryptedNote::from_bytes_with_outgoing(&blob).ok()?;
+    let sent = ovk.try_decrypt_outgoing(&record?)?;
+    let asset_id = match &delivered.mint {
+        Some(m) => mint_to_asset(&decode_hex32(m)?),
+        None => NATIVE_SOL_ASSET,
+    };
+    if commitment_to(&sent.recipient, sent.amount, &sent.randomness, &asset_id) != commitment {
+        return None;
+    }
+    let recipient = match sent.diversifier {
+        Some(d) => {
+            PaymentAddress::diversified(d, ShieldedAddress(sent.recipient), sent.transmission_key)
+        }
+        None => PaymentAddress::new(ShieldedAddress(sent.recipient), sent.transmission_key),
+    };
+    Some(SentOutput {
+        commitment,
+        recipient,
+        amount: sent.amount,
+        asset_id,
+    })
+}
+
+/// A shielded wallet: keys plus the notes and history found with them.
+pub struct Wallet {
+    keys: WalletKeys,
+    storage: WalletStorage,
+}
+
+impl Wallet {
+    /// Key file name inside a wallet directory.
+    pub const KEYS_FILE: &'static str = "keys.json";
+    /// RocksDB directory name inside a wallet directory.
+    pub const DB_DIR: &'static str = "wallet.db";
+
+    /// Open (creating if needed) the wallet stored under `dir`.
+    pub fn open(dir: &Path) -> Result<Self> {
+        let keys = WalletKeys::load_or_create(&dir.join(Self::KEYS_FILE))?;
+        let storage = WalletStorage::open(dir.join(Self::DB_DIR))?;
+        Ok(Wallet { keys, storage })
+    }
+
+    /// Whether `dir` already holds a wallet's keys.
+    pub fn exists(dir: &Path) -> bool {
+        dir.join(Self::KEYS_FILE).exists()
+    }
+
+    /// Create a wallet under `dir` from `keys` — freshly derived from a new
+    /// mnemonic, or re-derived from a backed-up one. Refuses to replace the
+    /// keys of an existing wallet, whose notes would no longer open.
+    pub fn create(dir: &Path, keys: WalletKeys) -> Result<Self> {
+        let keys_path = dir.join(Self::KEYS_FILE);
+        anyhow::ensure!(
+            !keys_path.exists(),
+            "a wallet already exists at {}",
+            dir.display()
+        );
+        keys.save(&keys_path)?;
+        let storage = WalletStorage::open(dir.join(Self::DB_DIR))?;
+        Ok(Wallet { keys, storage })
+    }
+
+    /// Build a wallet from existing keys and storage.
+    pub fn new(keys: WalletKeys, storage: WalletStorage) -> Self {
+        Wallet { keys, storage }
+    }
+
+    pub fn keys(&self) -> &WalletKeys {
+        &self.keys
+    }
+
+    pub fn storage(&self) -> &WalletStorage {
+        &self.storage
+    }
+
+    /// Take every delivered note that belongs to this wallet, and every
+    /// payment it made that carries an outgoing record. Idempotent: a note
+    /// or payment already on file is left as it is, so re-scanning the whole
+    /// feed is safe and re-discovers nothing.
+    ///
+    /// An output the wallet both sent and can open is its own change, and is
+    /// filed as such rather than as a receipt.
+    pub fn ingest(&self, delivered: &[DeliveredNote]) -> Result<IngestReport> {
+        let mut report = IngestReport {
+            scanned: delivered.len(),
+            ..Default::default()
+        };
+        let ivk = self.keys.incoming_viewing_key();
+        let ovk = self.keys.outgoing_viewing_key();
+        for d in delivered {
+            let sent = open_outgoing(&ovk, d);
+            if let Some(sent) = &sent {
+                let to_self = ivk.owns(
+                    sent.recipient.spend.as_bytes(),
+                    sent.recipient.diversifier.as_ref(),
+                );
+                if !to_self && sent.amount > 0 && self.storage.mark_sent_output(&sent.commitment)? {
+                    self.storage.append_history(&HistoryEntry {
+                        kind: HistoryKind::Sent,
+                        amount: sent.amount,
+                        asset_id: sent.asset_id,
+                        commitment: Some(sent.commitment),
+                        counterparty: Some(sent.recipient.encode()),
+                        timestamp: now_secs(),
+                    })?;
+                    report.sent += 1;
+                }
+            }
+
+            let (mut note, memo) = match open_delivered(&ivk, d) {
+                Ok(opened) => opened,
+                Err(OpenOutcome::NotOurs) => continue,
+                Err(OpenOutcome::Forged) => {
+                    log::warn!(
+                        "dropping delivered note for {} that does not open its commitment",
+                        d.output_commitment
+                    );
+                    report.rejected += 1;
+                    continue;
+                }
+            };
+            if sent.is_some() {
+                note.origin = NoteOrigin::Change;
+            }
+            if let Some(index) = note.leaf_index {
+                note.set_leaf_index(&self.keys, index);
+            }
+            self.storage.put_memo(&note.commitment, &memo)?;
+            if self.add_own_note(note)? {
+                report.discovered += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Ingest `feed` from where the last scan of it stopped, one page at a
+    /// time. `fetch` reads the page after a cursor; the cursor is persisted
+    /// after each page's notes are stored, so an interrupted scan resumes
+    /// rather than repeats. Only
+    /// [`WalletStorage::reset_scan_cursor`] makes the next scan start over.
+    pub async fn scan<F, Fut>(&self, feed: &str, mut fetch: F) -> Result<IngestReport>
+    where
+        F: FnMut(u64) -> Fut,
+        Fut: std::future::Future<Output = Result<DeliveredPage>>,
+    {
+        let mut report = IngestReport::default();
+        let mut after = self.storage.scan_cursor(feed)?;
+        loop {
+            let page = fetch(after).await?;
+            let found = self.ingest(&page.notes)?;
+            report.scanned += found.scanned;
+            report.discovered += found.discovered;
+            report.rejected += found.rejected;
+            report.sent += found.sent;
+            if page.cursor <= after {
+                return Ok(report);
+            }
+            self.storage.set_scan_cursor(feed, page.cursor)?;
+            if !page.more {
+                return Ok(report);
+            }
+            after = page.cursor;
+        }
+    }
+
+    /// Add a note whose opening the wallet already knows: its own deposit or
+    /// change, or one it has just opened. Returns `false` if the note was
+    /// already on file.
+    pub fn add_own_note(&self, note: OwnedNote) -> Result<bool> {
+        if self.storage.get_note(&note.commitment)?.is_some() {
+            return Ok(false);
+        }
+        self.storage.put_note(&note)?;
+        self.storage.append_history(&HistoryEntry {
+            kind: match note.origin {
+                NoteOrigin::Received => HistoryKind::Received,
+                NoteOrigin::Change => HistoryKind::Change,
+            },
+            amount: note.amount,
+            asset_id: note.asset_id,
+            commitment: Some(note.commitment),
+            counterparty: None,
+            timestamp: note.discovered_at,
+        })?;
+        Ok(true)
+    }
+
+    /// The note for this wallet's next deposit of `amount` lamports. Its
+    /// blinding comes from a fresh deposit nonce, so
+    /// [`recover_deposits`](Self::recover_deposits) finds it again after a
+    /// restore. The nonce is used up even if the deposit is never sent.
+    pub fn next_deposit_note(&self, amount: u64) -> Result<OwnedNote> {
+        let blinding = self
+            .keys
+            .deposit_blinding(self.storage.take_deposit_nonce()?);
+        Ok(OwnedNote {
+            commitment: note_commitment(&self.keys, amount, &blinding, &NATIVE_SOL_ASSET),
+            amount,
+            blinding,
+            asset_id: NATIVE_SOL_ASSET,
+            mint: None,
+            leaf_index: None,
+            nullifier: None,
+            origin: NoteOrigin::Received,
+            spent: false,
+            discovered_at: now_secs(),
+            diversifier: None,
+        })
+    }
+
+    /// Find this wallet's own deposits among `deposits`, given in leaf order,
+    /// and take them in at their leaf index. Only native SOL deposits are
+    /// matched: a deposit record does not carry the mint an SPL note needs to
+    /// be spent, and the wallet only deposits SOL. Raises the deposit nonce
+    /// past the highest one found, so a new deposit does not reuse it.
+    /// Returns how many notes were newly added.
+    pub fn recover_deposits(&self, deposits: &[DepositRecord]) -> Result<usize> {
+        let owner = self.keys.spend_pubkey();
+        let mut blindings = BTreeMap::new();
+        let mut found = std::collections::BTreeSet::new();
+        let mut next = 0u64;
+        let mut discovered = 0;
+        for deposit in deposits {
+            if deposit.asset_id != NATIVE_SOL_ASSET {
+                continue;
+            }
+            let window = next.saturating_sub(DEPOSIT_NONCE_GAP)..next + DEPOSIT_NONCE_GAP;
+            let matched = window.filter(|n| !found.contains(n)).find(|n| {
+                let blinding = *blindings
+                    .entry(*n)
+                    .or_insert_with(|| self.keys.deposit_blinding(*n));
+                commitment_to(&owner, deposit.amount, &blinding, &NATIVE_SOL_ASSET)
+                    == deposit.commitment
+            });
+            let Some(nonce) = matched else {
+                continue;
+            };
+            found.insert(nonce);
+            next = next.max(nonce + 1);
+
+            let mut note = OwnedNote {
+                commitment: deposit.commitment,
+                amount: deposit.amount,
+                blinding: blindings[&nonce],
+                asset_id: NATIVE_SOL_ASSET,
+                mint: None,
+                leaf_index: None,
+                nullifier: None,
+                origin: NoteOrigin::Received,
+                spent: false,
+                discovered_at: now_secs(),
+                diversifier: None,
+            };
+            note.set_leaf_index(&self.keys, deposit.leaf_index);
+            if self.add_own_note(note)? {
+                discovered += 1;
+            }
+        }
+        self.storage.raise_deposit_nonce(next)?;
+        Ok(discovered)
+    }
+
+    /// File a deposit before its transaction is sent. It does not count
+    /// toward the balance until [`confirm_deposit`](Self::confirm_deposit).
+    pub fn begin_deposit(&self, pending: &PendingDeposit) -> Result<()> {
+        self.storage.put_pending_deposit(pending)
+    }
+
+    /// Deposits filed with [`begin_deposit`](Self::begin_deposit) and not yet
+    /// confirmed or abandoned.
+    pub fn pending_deposits(&self) -> Result<Vec<PendingDeposit>> {
+        self.storage.get_pending_deposits()
+    }
+
+    /// The deposit of `commitment` landed: take its note into the wallet,
+    /// at `leaf_index` when the transaction logs gave one. Returns `false` if
+    /// no such deposit is pending.
+    pub fn confirm_deposit(&self, commitment: &[u8; 32], leaf_index: Option<u64>) -> Result<bool> {
+        let Some(pending) = self.storage.get_pending_deposit(commitment)? else {
+            return Ok(false);
+        };
+        let mut note = pending.note;
+        if let Some(index) = leaf_index {
+            note.set_leaf_index(&self.keys, index);
+        }
+        // The note goes in before the pending entry comes out, so a crash in
+        // between leaves a deposit that confirms again as a no-op.
+        self.add_own_note(note)?;
+        self.storage.delete_pending_deposit(commitment)?;
+        Ok(true)
+    }
+
+    /// The deposit of `commitment` failed on chain or can no longer land.
+    /// Returns `false` if no such deposit is pending.
+    pub fn abandon_deposit(&self, commitment: &[u8; 32]) -> Result<bool> {
+        if self.storage.get_pending_deposit(commitment)?.is_none() {
+            return Ok(false);
+        }
+        self.storage.delete_pending_deposit(commitment)?;
+        Ok(true)
+    }
+
+    /// A signed opening of the owned note `commitment`, for an auditor to
+    /// check against the chain without any of the wallet's keys.
+    pub fn disclose(&self, commitment: &[u8; 32]) -> Result<NoteDisclosure> {
+        let note = self.storage.get_note(commitment)?.ok_or_else(|| {
+            anyhow::anyhow!("note {} is not in this wallet", hex::encode(commitment))
+        })?;
+        let opening = NoteOpening {
+            commitment: note.commitment,
+            amount: note.amount,
+            owner: self.keys.spend_pubkey_for(note.diversifier.as_ref()),
+            blinding: note.blinding,
+            asset_id: note.asset_id,
+            mint: note.mint,
+            diversifier: note.diversifier,
+        };
+        Ok(NoteDisclosure::sign(
+            opening,
+            &self.keys.disclosure_keypair(),
+        ))
+    }
+
+    /// Record the tree position of an owned note, deriving its nullifier.
+    /// Returns `false` if the wallet does not hold `commitment`.
+    pub fn set_leaf_index(&self, commitment: &[u8; 32], leaf_index: u64) -> Result<bool> {
+        let Some(mut note) = self.storage.get_note(commitment)? else {
+            return Ok(false);
+        };
+        note.set_leaf_index(&self.keys, leaf_index);
+        self.storage.put_note(&note)?;
+        Ok(true)
+    }
+
+    /// Record a spend this wallet made: mark `spent` notes spent, log the
+    /// payment, and file the change output (if any) as an owned note.
+    /// `payment` is the output commitment paid to the counterparty for a
+    /// shielded transfer, `None` for a withdrawal.
+    pub fn record_sent(
+        &self,
+        spent: &[[u8; 32]],
+        amount: u64,
+        asset_id: AssetId,
+        counterparty: Option<String>,
+        payment: Option<[u8; 32]>,
+        change: Option<OwnedNote>,
+    ) -> Result<()> {
+        for commitment in spent {
+            if let Some(mut note) = self.storage.get_note(commitment)? {
+                note.spent = true;
+                self.storage.put_note(&note)?;
+            }
+        }
+        if let Some(payment) = &payment {
+            self.storage.mark_sent_output(payment)?;
+        }
+        self.storage.append_history(&HistoryEntry {
+            kind: HistoryKind::Sent,
+            amount,
+            asset_id,
+            commitment: payment,
+            counterparty,
+            timestamp: now_secs(),
+        })?;
+        if let Some(change) = change {
+            self.add_own_note(change)?;
+        }
+        Ok(())
+    }
+
+    /// Ask `is_spent` about the nullifier of every unspent note with a known
+    /// position, and mark the ones it reports spent. `is_spent` is the
+    /// authority — the chain's nullifier PDAs, or a node's nullifier set.
+    /// Returns how many notes were newly marked.
+    pub fn refresh_spent<F>(&self, mut is_spent: F) -> Result<usize>
+    where
+        F: FnMut(&[u8; 32]) -> Result<bool>,
+    {
+        let mut marked = 0;
+        for mut note in self.storage.get_all_notes()? {
+            if note.spent {
+                continue;
+            }
+            let Some(nullifier) = note.nullifier else {
+                continue;
+            };
+            if is_spent(&nullifier)? {
+                note.spent = true;
+                self.storage.put_note(&note)?;
+                marked += 1;
+            }
+        }
+        Ok(marked)
+    }
+
+    /// Every unspent note.
+    pub fn unspent_notes(&self) -> Result<Vec<OwnedNote>> {
+        Ok(self
+            .storage
+            .get_all_notes()?
+            .into_iter()
+            .filter(|n| !n.spent)
+            .collect())
+    }
+
+    /// Unspent value per asset. Assets with nothing unspent are omitted.
+    pub fn balances(&self) -> Result<BTreeMap<AssetId, u64>> {
+        let mut balances = BTreeMap::new();
+        for note in self.unspent_notes()? {
+            let entry = balances.entry(note.asset_id).or_insert(0u64);
+            *entry = entry.saturating_add(note.amount);
+        }
+        balances.retain(|_, v| *v > 0);
+        Ok(balances)
+    }
+
+    /// The most recent `limit` history entries, newest first.
+    pub fn history(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+        self.storage.recent_history(limit)
+    }
+
+    /// The memo that came with the output `commitment`, if it carried one.
+    pub fn memo(&self, commitment: &[u8; 32]) -> Result<Option<Vec<u8>>> {
+        self.storage.get_memo(commitment)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::note_crypto::{
+        encrypt_note, encrypt_note_v2, encrypt_note_v2_diversified, encrypt_outgoing,
+        NotePlaintext, OutgoingPlaintext,
+    };
+    use tempfile::tempdir;
+
+    fn wallet() -> (tempfile::TempDir, Wallet) {
+        let dir = tempdir().unwrap();
+        let w = Wallet::open(dir.path()).unwrap();
+        (dir, w)
+    }
+
+    /// A delivered note for `keys`, committed under `asset`.
+    fn deliver(keys: &WalletKeys, amount: u64, mint: Option<[u8; 32]>) -> DeliveredNote {
+        let blinding: [u8; 32] = rand::random();
+        let asset = mint.as_ref().map(mint_to_asset).unwrap_or(NATIVE_SOL_ASSET);
+        let commitment = note_commitment(keys, amount, &blinding, &asset);
+        let ct = encrypt_note(
+            &keys.transmission_key(),
+            &NotePlaintext {
+                amount,
+                randomness: blinding,
+                recipient: keys.spend_pubkey(),
+                asset_id: None,
+                memo: Vec::new(),
+                diversifier: None,
+            },
+        );
+        DeliveredNote {
+            output_commitment: hex::encode(commitment),
+            ciphertext: hex::encode(ct.to_bytes()),
+            mint: mint.map(hex::encode),
+            leaf_index: None,
+            slot: None,
+        }
+    }
+
+    /// A node feed of `notes`, served `limit` at a time after the requested
+    /// cursor; records every cursor it was asked for.
+    fn serve(
+        notes: &[DeliveredNote],
+        limit: usize,
+        requested: &std::cell::RefCell<Vec<u64>>,
+        after: u64,
+    ) -> Result<DeliveredPage> {
+        requested.borrow_mut().push(after);
+        let window = notes
+            .iter()
+            .cloned()
+            .enumerate()
+            .map(|(i, n)| (i as u64 + 1, n))
+            .filter(|(seq, _)| *seq > after)
+            .take(limit + 1)
+            .collect();
+        Ok(DeliveredPage::from_window(after, limit, window))
+    }
+
+    #[tokio::test]
+    async fn a_second_scan_fetches_only_new_entries() {
+        let (_dir, w) = wallet();
+        let mut feed = vec![
+            deliver(w.keys(), 10, None),
+            deliver(&WalletKeys::generate(), 99, None),
+            deliver(w.keys(), 20, None),
+        ];
+        let requested = std::cell::RefCell::new(Vec::new());
+
+        let first = w
+            .scan("node", |after| {
+                std::future::ready(serve(&feed, 2, &requested, after))
+            })
+            .await
+            .unwrap();
+        assert_eq!((first.scanned, first.discovered), (3, 2));
+        assert_eq!(*requested.borrow(), vec![0, 2]);
+        assert_eq!(w.storage().scan_cursor("node").unwrap(), 3);
+
+        feed.push(deliver(w.keys(), 30, None));
+        requested.borrow_mut().clear();
+        let second = w
+            .scan("node", |after| {
+                std::future::ready(serve(&feed, 2, &requested, after))
+            })
+            .await
+            .unwrap();
+        assert_eq!((second.scanned, second.discovered), (1, 1));
+        assert_eq!(*requested.borrow(), vec![3]);
+
+        // Nothing new: one empty page, cursor unchanged.
+        requested.borrow_mut().clear();
+        let idle = w
+            .scan("node", |after| {
+                std::future::ready(serve(&feed, 2, &requested, after))
+            })
+            .await
+            .unwrap();
+        assert_eq!(idle.scanned, 0);
+        assert_eq!(*requested.borrow(), vec![4]);
+
+        // An explicit rescan starts over and finds nothing it did not have.
+        w.storage().reset_scan_cursor("node").unwrap();
+        let rescan = w
+            .scan("node", |after| {
+                std::future::ready(serve(&feed, 10, &requested, after))
+            })
+            .await
+            .unwrap();
+        assert_eq!((rescan.scanned, rescan.discovered), (4, 0));
+        assert_eq!(w.storage().scan_cursor("node").unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn a_synced_note_is_found_spent_at_its_feed_position() {
+        let (_dir, w) = wallet();
+        let mut d = deliver(w.keys(), 40, None);
+        d.leaf_index = Some(9);
+        let commitment = decode_hex32(&d.output_commitment).unwrap();
+        let feed = [d];
+        let requested = std::cell::RefCell::new(Vec::new());
+        w.scan("node", |after| {
+            std::future::ready(serve(&feed, 10, &requested, after))
+        })
+        .await
+        .unwrap();
+
+        // The feed's position is enough to derive the nullifier.
+        let expected = note_nullifier(&w.keys().spend_key, &commitment, 9);
+        let note = w.storage().get_note(&commitment).unwrap().unwrap();
+        assert_eq!((note.leaf_index, note.nullifier), (Some(9), Some(expected)));
+
+        let marked = w.refresh_spent(|nf| Ok(*nf == expected)).unwrap();
+        assert_eq!(marked, 1);
+        assert!(w.balances().unwrap().is_empty());
+    }
+
+    #[test]
+    fn restoring_from_the_mnemonic_rediscovers_the_notes() {
+        let phrase = keys::generate_mnemonic();
+        let original_dir = tempdir().unwrap();
+        let original = Wallet::create(
+            original_dir.path(),
+            WalletKeys::from_mnemonic(&phrase, "", 2).unwrap(),
+        )
+        .unwrap();
+        let feed = vec![
+            deliver(original.keys(), 40, None),
+            deliver(&WalletKeys::generate(), 99, None),
+            deliver(original.keys(), 5, Some([3; 32])),
+        ];
+        original.ingest(&feed).unwrap();
+
+        // Another machine: only the words and the account index survive.
+        let restored_dir = tempdir().unwrap();
+        let restored = Wallet::create(
+            restored_dir.path(),
+            WalletKeys::from_mnemonic(&phrase, "", 2).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(restored.ingest(&feed).unwrap().discovered, 2);
+        assert_eq!(restored.balances().unwrap(), original.balances().unwrap());
+
+        // The wrong account sees nothing.
+        let other_dir = tempdir().unwrap();
+        let other = Wallet::create(
+            other_dir.path(),
+            WalletKeys::from_mnemonic(&phrase, "", 3).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(other.ingest(&feed).unwrap().discovered, 0);
+    }
+
+    #[test]
+    fn v2_notes_keep_their_memo_and_must_name_the_delivered_asset() {
+        let (_dir, w) = wallet();
+        let blinding: [u8; 32] = rand::random();
+        let mint = [3u8; 32];
+        let asset = mint_to_asset(&mint);
+        let commitment = note_commitment(w.keys(), 60, &blinding, &asset);
+        let deliver_v2 = |named: AssetId| DeliveredNote {
+            output_commitment: hex::encode(commitment),
+            ciphertext: hex::encode(
+                encrypt_note_v2(
+                    &w.keys().transmission_key(),
+                    &NotePlaintext {
+                        amount: 60,
+                        randomness: blinding,
+                        recipient: w.keys().spend_pubkey(),
+                        asset_id: Some(named),
+                        memo: b"invoice 7".to_vec(),
+                        diversifier: None,
+                    },
+                )
+                .unwrap()
+                .to_bytes(),
+            ),
+            mint: Some(hex::encode(mint)),
+            leaf_index: None,
+            slot: None,
+        };
+
+        // Naming another asset than the one delivered is a forgery, even
+        // though the commitment itself would open.
+        let report = w.ingest(&[deliver_v2(NATIVE_SOL_ASSET)]).unwrap();
+        assert_eq!((report.discovered, report.rejected), (0, 1));
+
+        assert_eq!(w.ingest(&[deliver_v2(asset)]).unwrap().discovered, 1);
+        assert_eq!(w.balances().unwrap()[&asset], 60);
+        assert_eq!(w.memo(&commitment).unwrap().unwrap(), b"invoice 7");
+    }
+
+    #[test]
+    fn a_pending_deposit_counts_only_once_confirmed() {
+        let (_dir, w) = wallet();
+        let blinding = [9u8; 32];
+        let pending = |amount: u64| PendingDeposit {
+            note: OwnedNote {
+                commitment: note_commitment(w.keys(), amount, &blinding, &NATIVE_SOL_ASSET),
+                amount,
+                blinding,
+                asset_id: NATIVE_SOL_ASSET,
+                mint: None,
+                leaf_index: None,
+                nullifier: None,
+                origin: NoteOrigin::Received,
+                spent: false,
+                discovered_at: 0,
+                diversifier: None,
+            },
+            signature: "sig".to_string(),
+            blockhash: "hash".to_string(),
+        };
+        let landed = pending(300);
+        let dropped = pending(700);
+        w.begin_deposit(&landed).unwrap();
+        w.begin_deposit(&dropped).unwrap();
+        assert_eq!(w.pending_deposits().unwrap().len(), 2);
+        assert!(w.balances().unwrap().is_empty());
+
+        assert!(w.confirm_deposit(&landed.note.commitment, Some(4)).unwrap());
+        assert!(!w.confirm_deposit(&landed.note.commitment, Some(4)).unwrap());
+        let note = w
+            .storage()
+            .get_note(&landed.note.commitment)
+            .unwrap()
+            .unwrap();
+        assert_eq!(note.leaf_index, Some(4));
+        assert!(note.nullifier.is_some());
+
+        assert!(w.abandon_deposit(&dropped.note.commitment).unwrap());
+        assert!(w.pending_deposits().unwrap().is_empty());
+        assert_eq!(w.balances().unwrap()[&NATIVE_SOL_ASSET], 300);
+        assert_eq!(w.history(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restoring_from_the_mnemonic_recovers_deposits() {
+        let phrase = keys::generate_mnemonic();
+        let original_dir = tempdir().unwrap();
+        let original = Wallet::create(
+            original_dir.path(),
+            WalletKeys::from_mnemonic(&phrase, "", 0).unwrap(),
+        )
+        .unwrap();
+
+        let record = |note: &OwnedNote, leaf_index: u64| DepositRecord {
+            commitment: note.commitment,
+            leaf_index,
+            depositor: [7; 32],
+            amount: note.amount,
+            asset_id: note.asset_id,
+            slot: leaf_index,
+            signature: String::new(),
+        };
+        let first = original.next_deposit_note(1_000).unwrap();
+        // Signed but never landed: its nonce is burned.
+        original.next_deposit_note(5).unwrap();
+        let second = original.next_deposit_note(2_500).unwrap();
+        let (_stranger_dir, stranger) = wallet();
+        let stranger = stranger.next_deposit_note(1_000).unwrap();
+        let deposits = vec![record(&first, 0), record(&stranger, 1), record(&second, 2)];
+
+        // Another machine: only the words survive, and no deposit was ever
+        // in the note feed.
+        let restored_dir = tempdir().unwrap();
+        let restored = Wallet::create(
+            restored_dir.path(),
+            WalletKeys::from_mnemonic(&phrase, "", 0).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(restored.recover_deposits(&deposits).unwrap(), 2);
+        assert_eq!(restored.balances().unwrap()[&NATIVE_SOL_ASSET], 3_500);
+        let note = restored
+            .storage()
+            .get_note(&second.commitment)
+            .unwrap()
+            .unwrap();
+        assert_eq!(note.blinding, second.blinding);
+        assert_eq!(note.leaf_index, Some(2));
+        assert!(note.nullifier.is_some());
+
+        // Nothing new on a rescan, and the next deposit takes a fresh nonce.
+        assert_eq!(restored.recover_deposits(&deposits).unwrap(), 0);
+        let next = restored.next_deposit_note(1_000).unwrap();
+        assert_eq!(next.blinding, restored.keys().deposit_blinding(3));
+    }
+
+    #[test]
+    fn create_refuses_to_replace_existing_keys() {
+        let (dir, _w) = wallet();
+        assert!(Wallet::exists(dir.path()));
+        assert!(Wallet::create(dir.path(), WalletKeys::generate()).is_err());
+    }
+
+    #[test]
+    fn ingest_keeps_own_notes_and_skips_others() {
+        let (_dir, w) = wallet();
+        let stranger = WalletKeys::generate();
+        let feed = vec![
+            deliver(w.keys(), 500, None),
+            deliver(&stranger, 900, None),
+            deliver(w.keys(), 250, None),
+        ];
+
+        let report = w.ingest(&feed).unwrap();
+        assert_eq!(report.scanned, 3);
+        assert_eq!(report.discovered, 2);
+        assert_eq!(report.rejected, 0);
+        assert_eq!(w.balances().unwrap()[&NATIVE_SOL_ASSET], 750);
+
+        // Re-scanning the same feed finds nothing new.
+        assert_eq!(w.ingest(&feed).unwrap().discovered, 0);
+        assert_eq!(w.history(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn balances_are_per_asset() {
+        let (_dir, w) = wallet();
+        let mint = [9u8; 32];
+        w.ingest(&[
+            deliver(w.keys(), 10, None),
+            deliver(w.keys(), 40, Some(mint)),
+        ])
+        .unwrap();
+
+        let balances = w.balances().unwrap();
+        assert_eq!(balances[&NATIVE_SOL_ASSET], 10);
+        assert_eq!(balances[&mint_to_asset(&mint)], 40);
+    }
+
+    #[test]
+    fn a_ciphertext_that_does_not_open_its_commitment_is_rejected() {
+        // Ciphertexts are not proof-bound: a relay could pair a real
+        // commitment with a plaintext claiming a bigger amount.
+        let (_dir, w) = wallet();
+        let mut forged = deliver(w.keys(), 1, None);
+        let inflated = deliver(w.keys(), 1_000_000, None);
+        forged.ciphertext = inflated.ciphertext;
+
+        let report = w.ingest(&[forged]).unwrap();
+        assert_eq!(report.discovered, 0);
+        assert_eq!(report.rejected, 1);
+        assert!(w.balances().unwrap().is_empty());
+    }
+
+    #[test]
+    fn the_wrong_mint_is_rejected() {
+        let (_dir, w) = wallet();
+        let mut d = deliver(w.keys(), 5, Some([1u8; 32]));
+        d.mint = Some(hex::encode([2u8; 32]));
+        assert_eq!(w.ingest(&[d]).unwrap().rejected, 1);
+    }
+
+    #[test]
+    fn spent_notes_leave_the_balance() {
+        let (_dir, w) = wallet();
+        let d = deliver(w.keys(), 70, None);
+        w.ingest(std::slice::from_ref(&d)).unwrap();
+        let commitment = decode_hex32(&d.output_commitment).unwrap();
+
+        // Without a leaf index there is no nullifier to ask about.
+        assert_eq!(w.refresh_spent(|_| Ok(true)).unwrap(), 0);
+
+        assert!(w.set_leaf_index(&commitment, 4).unwrap());
+        let expected = note_nullifier(&w.keys().spend_key, &commitment, 4);
+        let marked = w.refresh_spent(|nf| Ok(*nf == expected)).unwrap();
+        assert_eq!(marked, 1);
+        assert!(w.balances().unwrap().is_empty());
+    }
+
+    #[test]
+    fn notes_paid_to_a_diversified_address_are_found_and_spendable() {
+        let (_dir, w) = wallet();
+        let address = w.keys().diversified_address(11);
+        let d = address.diversifier.unwrap();
+        assert_ne!(address.spend, w.keys().payment_address().spend);
+
+        let output = OutputNote::new(&address, 250);
+        let sender = WalletKeys::generate();
+        let delivered = DeliveredNote {
+            output_commitment: hex::encode(output.commitment(&NATIVE_SOL_ASSET)),
+            ciphertext: hex::encode(
+                output
+                    .seal(&NATIVE_SOL_ASSET, &sender.outgoing_key())
+                    .unwrap(),
+            ),
+            mint: None,
+            leaf_index: None,
+            slot: None,
+        };
+        assert_eq!(w.ingest(&[delivered]).unwrap().discovered, 1);
+
+        let commitment = output.commitment(&NATIVE_SOL_ASSET);
+        assert!(w.set_leaf_index(&commitment, 6).unwrap());
+        let note = w.storage().get_note(&commitment).unwrap().unwrap();
+        assert_eq!(note.diversifier, Some(d));
+        assert_eq!(
+            note.nullifier,
+            Some(note_nullifier(
+                &d.spend_key(&w.keys().spend_key),
+                &commitment,
+                6
+            ))
+        );
+
+        let disclosure = w.disclose(&commitment).unwrap();
+        disclosure
+            .verify_for(&w.keys().incoming_viewing_key())
+            .unwrap();
+
+        // A sender cannot pass off an owner of their choosing under a
+        // made-up diversifier: only the viewing secret makes a valid tag.
+        let (owner, blinding) = (sender.spend_pubkey(), [4u8; 32]);
+        let sealed = encrypt_note_v2_diversified(
+            &d.base(),
+            &address.transmission_key,
+            &NotePlaintext {
+                amount: 10,
+                randomness: blinding,
+                recipient: owner,
+                asset_id: Some(NATIVE_SOL_ASSET),
+                memo: Vec::new(),
+                diversifier: Some(Diversifier([1; 16])),
+            },
+        )
+        .unwrap();
+        let forged = DeliveredNote {
+            output_commitment: hex::encode(commitment_to(&owner, 10, &blinding, &NATIVE_SOL_ASSET)),
+            ciphertext: hex::encode(sealed.to_bytes()),
+            mint: None,
+            leaf_index: None,
+            slot: None,
+        };
+        assert_eq!(w.ingest(&[forged]).unwrap().rejected, 1);
+    }
+
+    /// An output `sender` created for `to`, with the sender's outgoing record.
+    fn send(sender: &WalletKeys, to: &WalletKeys, amount: u64) -> DeliveredNote {
+        let blinding: [u8; 32] = rand::random();
+        let commitment = note_commitment(to, amount, &blinding, &NATIVE_SOL_ASSET);
+        let note = encrypt_note(
+            &to.transmission_key(),
+            &NotePlaintext {
+                amount,
+                randomness: blinding,
+                recipient: to.spend_pubkey(),
+                asset_id: None,
+                memo: Vec::new(),
+                diversifier: None,
+            },
+        );
+        let record = encrypt_outgoing(
+            &sender.outgoing_key(),
+            &OutgoingPlaintext {
+                recipient: to.spend_pubkey(),
+                transmission_key: to.transmission_key(),
+                amount,
+                randomness: blinding,
+                diversifier: None,
+            },
+        );
+        DeliveredNote {
+            output_commitment: hex::encode(commitment),
+            ciphertext: hex::encode(note.to_bytes_with_outgoing(&record)),
+            mint: None,
+            leaf_index: None,
+            slot: None,
+        }
+    }
+
+    #[test]
+    fn outgoing_records_rebuild_sent_history_once() {
+        let (_dir, w) = wallet();
+        let bob = WalletKeys::generate();
+        let feed = vec![
+            deliver(w.keys(), 100, None),
+            send(w.keys(), &bob, 70),
+            send(w.keys(), w.keys(), 30),
+        ];
+
+        let report = w.ingest(&feed).unwrap();
+        assert_eq!((report.discovered, report.sent), (2, 1));
+        let history = w.history(10).unwrap();
+        let sent = history
+            .iter()
+            .find(|e| e.kind == HistoryKind::Sent)
+            .unwrap();
+        assert_eq!(sent.amount, 70);
+        // The outgoing record names the recipient's spend and transmission
+        // keys, not its clue key, so the recovered address is v1.
+        let bob_v1 =
+            PaymentAddress::new(ShieldedAddress(bob.spend_pubkey()), bob.transmission_key());
+        assert_eq!(sent.counterparty.as_deref(), Some(bob_v1.encode().as_str()));
+        assert!(history
+            .iter()
+            .any(|e| e.kind == HistoryKind::Change && e.amount == 30));
+        assert_eq!(w.balances().unwrap()[&NATIVE_SOL_ASSET], 130);
+
+        let again = w.ingest(&feed).unwrap();
+        assert_eq!((again.discovered, again.sent), (0, 0));
+        assert_eq!(w.history(10).unwrap().len(), history.len());
+
+        // The recipient just sees a payment; the record is not theirs to open.
+        let bob_dir = tempdir().unwrap();
+        let bob_wallet = Wallet::create(bob_dir.path(), bob).unwrap();
+        let theirs = bob_wallet.ingest(&feed).unwrap();
+        assert_eq!((theirs.discovered, theirs.sent), (1, 0));
+    }
+
+    #[test]
+    fn a_payment_logged_at_send_time_is_not_recovered_twice() {
+        let (_dir, w) = wallet();
+        let bob = WalletKeys::generate();
+        let payment = send(w.keys(), &bob, 70);
+        let commitment = decode_hex32(&payment.output_commitment).unwrap();
+        w.record_sent(
+            &[],
+            70,
+            NATIVE_SOL_ASSET,
+            Some("bob".into()),
+            Some(commitment),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(w.ingest(&[payment]).unwrap().sent, 0);
+        assert_eq!(w.history(10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn record_sent_logs_the_payment_and_files_change() {
+        let (_dir, w) = wallet();
+        let d = deliver(w.keys(), 100, None);
+        w.ingest(std::slice::from_ref(&d)).unwrap();
+        let input = decode_hex32(&d.output_commitment).unwrap();
+
+        let blinding = [3u8; 32];
+        let change = OwnedNote {
+            commitment: note_commitment(w.keys(), 30, &blinding, &NATIVE_SOL_ASSET),
+            amount: 30,
+            blinding,
+            asset_id: NATIVE_SOL_ASSET,
+            mint: None,
+            leaf_index: None,
+            nullifier: None,
+            origin: NoteOrigin::Change,
+            spent: false,
+            discovered_at: 0,
+            diversifier: None,
+        };
+        w.record_sent(
+            &[input],
+            70,
+            NATIVE_SOL_ASSET,
+            Some("bob".into()),
+            None,
+            Some(change),
+        )
+        .unwrap();
+
+        assert_eq!(w.balances().unwrap()[&NATIVE_SOL_ASSET], 30);
+        let kinds: Vec<HistoryKind> = w.history(10).unwrap().iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HistoryKind::Change,
+                HistoryKind::Sent,
+                HistoryKind::Received
+            ]
+        );
+    }
+
+    #[test]
+    fn nullifier_matches_the_circuit_construction() {
+        // The same chain the relayer and the demo build their public inputs
+        // from; a drift here would make every owned note look unspent.
+        let sk = [5u8; 32];
+        let c = [6u8; 32];
+        let skf = Fr::from_le_bytes_mod_order(&sk);
+        let cf = Fr::from_le_bytes_mod_order(&c);
+        let expected = fr_to_bytes_32(v3_nullifier(
+            cf,
+            Fr::from(9u64),
+            v3_signature(skf, cf, Fr::from(9u64)),
+        ));
+        assert_eq!(note_nullifier(&sk, &c, 9), expected);
+    }
+}
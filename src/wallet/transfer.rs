@@ -0,0 +1,640 @@
+//! Building, proving and submitting a `TransactCircuitV3` spend.
+//!
+//! A spend consumes one or two owned notes and creates exactly two outputs.
+//! The wallet picks the inputs ([`select_inputs`]), asks a node's path server
+//! where they sit in the on-chain tree ([`fetch_inputs`]), lays out the
+//! witness ([`TransactPlan`]) and proves it. The proof, the public parts and
+//! the two sealed output notes go to the node's transact ingress
+//! ([`submit_transact`]), which runs them through validator consensus and
+//! settles the `transact` instruction. Witness layout, dummy padding and
+//! output sealing live in [`crate::privacy::prover`].
+
+use anyhow::{bail, Context, Result};
+use ark_bn254::Bn254;
+use ark_groth16::ProvingKey;
+use serde::Deserialize;
+
+use super::{now_secs, NoteOrigin, OwnedNote, WalletKeys};
+use crate::node::merkle_path::MerklePathResponse;
+use crate::privacy::circuits::TX_NINS;
+use crate::privacy::merkle::OnchainMembership;
+use crate::privacy::note_crypto::MAX_MEMO_LEN;
+use crate::privacy::prover::{asset_of, lay_out_witness, prove_and_seal, InputNote, Witness};
+use crate::privacy::types::AssetId;
+use crate::privacy::{PaymentAddress, ProofSuite, ProofVerifier};
+
+pub use crate::privacy::prover::OutputNote;
+
+/// How many times [`fetch_inputs`] re-queries when a deposit lands between
+/// two path lookups and they come back under different roots.
+const PATH_FETCH_ATTEMPTS: usize = 3;
+
+/// An owned note together with its path in the on-chain tree.
+#[derive(Clone, Debug)]
+pub struct SpendInput {
+    pub note: OwnedNote,
+    pub membership: OnchainMembership,
+}
+
+impl SpendInput {
+    /// The note as the prover takes it.
+    pub fn input_note(&self) -> InputNote {
+        InputNote {
+            amount: self.note.amount,
+            blinding: self.note.blinding,
+            asset_id: self.note.asset_id,
+            commitment: self.note.commitment,
+            membership: self.membership.clone(),
+            diversifier: self.note.diversifier,
+        }
+    }
+}
+
+/// Everything a spend proves, before proving.
+#[derive(Clone, Debug)]
+pub struct TransactPlan {
+    /// One or two inputs, all under the same root and asset.
+    pub inputs: Vec<SpendInput>,
+    pub outputs: [OutputNote; 2],
+    /// Bound into `ext_data_hash`. The withdrawal destination when
+    /// `ext_amount < 0`; for a pure transfer nothing is paid to it.
+    pub recipient: [u8; 32],
+    /// `0` for a transfer, `-amount` for a withdrawal.
+    pub ext_amount: i64,
+    /// SPL mint being spent; `None` for native SOL.
+    pub mint: Option<[u8; 32]>,
+}
+
+impl TransactPlan {
+    /// The asset every input and output commits to.
+    pub fn asset_id(&self) -> AssetId {
+        asset_of(self.mint.as_ref())
+    }
+
+    /// Check the plan is provable and lay out the witness. Errors are the
+    /// mistakes the circuit would otherwise reject with an unhelpful
+    /// "constraints not satisfied".
+    fn witness(&self, keys: &WalletKeys) -> Result<Witness> {
+        if self.inputs.is_empty() || self.inputs.len() > TX_NINS {
+            bail!(
+                "a transact spends 1 to {} notes, got {}",
+                TX_NINS,
+                self.inputs.len()
+            );
+        }
+        lay_out_witness(
+            &keys.prover_keys(),
+            &self
+                .inputs
+                .iter()
+                .map(SpendInput::input_note)
+                .collect::<Vec<_>>(),
+            &self.outputs,
+            &self.recipient,
+            self.ext_amount,
+            self.asset_id(),
+        )
+    }
+
+    /// Prove the plan and seal both outputs. The proof is checked against the
+    /// node's transact verifying key before it is returned, so a key mismatch
+    /// or a witness bug fails here rather than as a silent consensus
+    /// rejection.
+    pub fn prove(
+        &self,
+        keys: &WalletKeys,
+        proving_key: &ProvingKey<Bn254>,
+    ) -> Result<ProvedTransact> {
+        let Witness {
+            circuit,
+            root,
+            nullifiers,
+            output_commitments,
+        } = self.witness(keys)?;
+        let asset_id = self.asset_id();
+        let sealed = prove_and_seal(
+            &keys.prover_keys(),
+            circuit,
+            ProofSuite::Groth16Bn254TransactV3,
+            &self.outputs,
+            &asset_id,
+            proving_key,
+        )?;
+
+        let verdict = ProofVerifier::verify_transact_parts(
+            &root,
+            &self.recipient,
+            self.ext_amount,
+            &asset_id,
+            &nullifiers,
+            &output_commitments,
+            &sealed.proof,
+            None,
+        );
+        if !verdict.is_valid() {
+            bail!("proof failed self-verification: {:?}", verdict);
+        }
+
+        Ok(ProvedTransact {
+            recipient: self.recipient,
+            mint: self.mint,
+            nullifiers,
+            output_commitments,
+            root,
+            ext_amount: self.ext_amount,
+            proof: sealed.proof,
+            ciphertexts: sealed.ciphertexts,
+        })
+    }
+}
+
+/// A proven spend, ready for the transact ingress.
+#[derive(Clone, Debug)]
+pub struct ProvedTransact {
+    pub recipient: [u8; 32],
+    pub mint: Option<[u8; 32]>,
+    pub nullifiers: [[u8; 32]; 2],
+    pub output_commitments: [[u8; 32]; 2],
+    pub root: [u8; 32],
+    pub ext_amount: i64,
+    /// Tagged (`Groth16Bn254TransactV3`) wire proof.
+    pub proof: Vec<u8>,
+    /// Canonical `EncryptedNote` bytes, one per output.
+    pub ciphertexts: [Vec<u8>; 2],
+}
+
+impl ProvedTransact {
+    /// The `POST /transact/submit` body.
+    pub fn submit_body(&self) -> serde_json::Value {
+        let mut body = serde_json::json!({
+            "recipient": hex::encode(self.recipient),
+            "nullifiers": [hex::encode(self.nullifiers[0]), hex::encode(self.nullifiers[1])],
+            "output_commitments": [
+                hex::encode(self.output_commitments[0]),
+                hex::encode(self.output_commitments[1]),
+            ],
+            "root": hex::encode(self.root),
+            "ext_amount": self.ext_amount,
+            "proof": hex::encode(&self.proof),
+            "ciphertexts": [hex::encode(&self.ciphertexts[0]), hex::encode(&self.ciphertexts[1])],
+        });
+        if let Some(mint) = &self.mint {
+            body["mint"] = serde_json::Value::String(hex::encode(mint));
+        }
+        body
+    }
+}
+
+/// Pick the notes to spend for `amount` of `asset_id`: the smallest single
+/// note that covers it, else the two largest. A payment that needs more than
+/// two notes cannot be made in one transact.
+pub fn select_inputs(
+    unspent: &[OwnedNote],
+    asset_id: &AssetId,
+    amount: u64,
+) -> Result<Vec<OwnedNote>> {
+    let mut candidates: Vec<&OwnedNote> = unspent
+        .iter()
+        .filter(|n| !n.spent && n.amount > 0 && &n.asset_id == asset_id)
+        .collect();
+    candidates.sort_by_key(|n| n.amount);
+
+    if let Some(single) = candidates.iter().find(|n| n.amount >= amount) {
+        return Ok(vec![(*single).clone()]);
+    }
+    let available: u128 = candidates.iter().map(|n| n.amount as u128).sum();
+    if available < amount as u128 {
+        bail!(
+            "insufficient shielded balance: {} available, {} requested",
+            available,
+            amount
+        );
+    }
+    let pair = &candidates[candidates.len() - 2..];
+    if pair[0].amount as u128 + pair[1].amount as u128 >= amount as u128 {
+        return Ok(pair.iter().map(|n| (*n).clone()).collect());
+    }
+    bail!(
+        "{} is spread over more than {} notes; merge them with smaller payments first",
+        amount,
+        TX_NINS
+    )
+}
+
+/// Sum of the input amounts, and the asset and mint they share.
+fn input_totals(inputs: &[SpendInput]) -> Result<(u64, AssetId, Option<[u8; 32]>)> {
+    let first = inputs.first().context("no input notes")?;
+    let total = inputs
+        .iter()
+        .try_fold(0u64, |acc, i| acc.checked_add(i.note.amount))
+        .context("input total overflows")?;
+    Ok((total, first.note.asset_id, first.note.mint))
+}
+
+/// An output of `amount` back to this wallet, with the owned note to file for
+/// it once the spend settles (`None` for a zero-value output).
+fn change_output(
+    keys: &WalletKeys,
+    amount: u64,
+    asset_id: AssetId,
+    mint: Option<[u8; 32]>,
+) -> (OutputNote, Option<OwnedNote>) {
+    let output = OutputNote::new(&keys.payment_address(), amount);
+    let note = (amount > 0).then(|| OwnedNote {
+        commitment: output.commitment(&asset_id),
+        amount,
+        blinding: output.blinding,
+        asset_id,
+        mint,
+        leaf_index: None,
+        nullifier: None,
+        origin: NoteOrigin::Change,
+        spent: false,
+        discovered_at: now_secs(),
+        diversifier: None,
+    });
+    (output, note)
+}
+
+/// Lay out a private payment of `amount` to `to` from `inputs`, with `memo`
+/// sealed to the recipient alongside it, returning the change to this wallet
+/// as the second output. The change note is returned as well (when non-zero)
+/// so the caller can file it once the spend settles.
+pub fn build_transfer(
+    keys: &WalletKeys,
+    inputs: Vec<SpendInput>,
+    to: &PaymentAddress,
+    amount: u64,
+    memo: &[u8],
+) -> Result<(TransactPlan, Option<OwnedNote>)> {
+    if memo.len() > MAX_MEMO_LEN {
+        bail!(
+            "memo is {} bytes; at most {MAX_MEMO_LEN} fit in a note",
+            memo.len()
+        );
+    }
+    let (total, asset_id, mint) = input_totals(&inputs)?;
+    let change_amount = total
+        .checked_sub(amount)
+        .context("inputs do not cover the amount")?;
+
+    let payment = OutputNote {
+        memo: memo.to_vec(),
+        ..OutputNote::new(to, amount)
+    };
+    let (change, change_note) = change_output(keys, change_amount, asset_id, mint);
+
+    let plan = TransactPlan {
+        inputs,
+        outputs: [payment, change],
+        // Nothing is paid out, but the program still takes a system account
+        // here; a fresh random key keeps transfers from sharing one.
+        recipient: rand::random(),
+        ext_amount: 0,
+        mint,
+    };
+    Ok((plan, change_note))
+}
+
+/// Lay out a withdrawal of `amount` from `inputs` to `recipient`: a system
+/// account for native SOL, or a token account of the inputs' mint for SPL.
+/// Both outputs return to this wallet — the change, and a zero-value filler.
+/// The settling validator's fee comes out of `amount` on chain, so
+/// `recipient` receives slightly less.
+pub fn build_withdraw(
+    keys: &WalletKeys,
+    inputs: Vec<SpendInput>,
+    recipient: [u8; 32],
+    amount: u64,
+) -> Result<(TransactPlan, Option<OwnedNote>)> {
+    let (total, asset_id, mint) = input_totals(&inputs)?;
+    let ext_amount = i64::try_from(amount).context("withdrawal amount too large")?;
+    let change_amount = total
+        .checked_sub(amount)
+        .context("inputs do not cover the amount")?;
+
+    let (change, change_note) = change_output(keys, change_amount, asset_id, mint);
+    let (filler, _) = change_output(keys, 0, asset_id, mint);
+
+    let plan = TransactPlan {
+        inputs,
+        outputs: [change, filler],
+        recipient,
+        ext_amount: -ext_amount,
+        mint,
+    };
+    Ok((plan, change_note))
+}
+
+/// Query `path_server` for `commitment`'s on-chain path and check it opens.
+pub async fn fetch_membership(
+    client: &reqwest::Client,
+    path_server: &str,
+    commitment: &[u8; 32],
+) -> Result<OnchainMembership> {
+    let url = format!(
+        "{}/merkle/path/{}",
+        path_server.trim_end_matches('/'),
+        hex::encode(commitment)
+    );
+    let resp = client
+        .get(&url)
+        .send()
+        .await
+        .context("Failed to reach the node's path server")?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        bail!(
+            "note {} is not indexed by the path server yet",
+            hex::encode(commitment)
+        );
+    }
+    let parsed: MerklePathResponse = resp
+        .error_for_status()
+        .context("Path server returned an error")?
+        .json()
+        .await
+        .context("Failed to decode the path server response")?;
+    let membership = parsed
+        .to_membership()
+        .context("Path server returned malformed hex")?;
+    if membership.fold(commitment) != membership.root {
+        bail!(
+            "path server returned a path that does not open {}",
+            hex::encode(commitment)
+        );
+    }
+    Ok(membership)
+}
+
+/// Fetch paths for every note in `notes`, all under one root. A deposit that
+/// lands between two lookups moves the root, so the batch is re-queried a
+/// few times before giving up.
+pub async fn fetch_inputs(
+    client: &reqwest::Client,
+    path_server: &str,
+    notes: Vec<OwnedNote>,
+) -> Result<Vec<SpendInput>> {
+    for _ in 0..PATH_FETCH_ATTEMPTS {
+        let mut inputs = Vec::with_capacity(notes.len());
+        for note in &notes {
+            let membership = fetch_membership(client, path_server, &note.commitment).await?;
+            inputs.push(SpendInput {
+                note: note.clone(),
+                membership,
+            });
+        }
+        if inputs
+            .windows(2)
+            .all(|w| w[0].membership.root == w[1].membership.root)
+        {
+            return Ok(inputs);
+        }
+    }
+    bail!("the tree kept moving while fetching input paths; try again")
+}
+
+#[derive(Deserialize)]
+struct SubmitResponse {
+    request_id: String,
+}
+
+/// POST `transact` to the node's ingress at `ingress`, returning the
+/// consensus request id. `token` is the ingress bearer token, if the node
+/// requires one.
+pub async fn submit_transact(
+    client: &reqwest::Client,
+    ingress: &str,
+    token: Option<&str>,
+    transact: &ProvedTransact,
+) -> Result<String> {
+    let mut req = client
+        .post(format!("{}/transact/submit", ingress.trim_end_matches('/')))
+        .json(&transact.submit_body());
+    if let Some(token) = token {
+        req = req.bearer_auth(token);
+    }
+    let resp = req
+        .send()
+        .await
+        .context("Failed to reach the node's transact ingress")?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().await.unwrap_or_default();
+        bail!("ingress rejected the transact: {} {}", status, body);
+    }
+    let parsed: SubmitResponse = resp
+        .json()
+        .await
+        .context("Failed to decode the ingress response")?;
+    Ok(parsed.request_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::circuits::TransactCircuitV3;
+    use crate::privacy::merkle::MerkleTree;
+    use crate::privacy::note_crypto::EncryptedNote;
+    use crate::privacy::prover::random_field_bytes;
+    use crate::privacy::types::Commitment;
+    use crate::privacy::types::NATIVE_SOL_ASSET;
+    use crate::wallet::note_commitment;
+    use ark_bn254::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    fn owned(keys: &WalletKeys, amount: u64) -> OwnedNote {
+        let blinding = random_field_bytes();
+        OwnedNote {
+            commitment: note_commitment(keys, amount, &blinding, &NATIVE_SOL_ASSET),
+            amount,
+            blinding,
+            asset_id: NATIVE_SOL_ASSET,
+            mint: None,
+            leaf_index: None,
+            nullifier: None,
+            origin: NoteOrigin::Received,
+            spent: false,
+            discovered_at: 0,
+            diversifier: None,
+        }
+    }
+
+    async fn spendable(keys: &WalletKeys, amounts: &[u64]) -> Vec<SpendInput> {
+        let tree = MerkleTree::new();
+        let notes: Vec<OwnedNote> = amounts.iter().map(|a| owned(keys, *a)).collect();
+        for n in &notes {
+            tree.insert(&Commitment(n.commitment)).await.unwrap();
+        }
+        let mut inputs = Vec::new();
+        for n in notes {
+            let membership = tree
+                .onchain_membership(&Commitment(n.commitment))
+                .await
+                .unwrap();
+            inputs.push(SpendInput {
+                note: n,
+                membership,
+            });
+        }
+        inputs
+    }
+
+    fn satisfied(circuit: TransactCircuitV3) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn select_prefers_the_smallest_covering_note() {
+        let keys = WalletKeys::generate();
+        let notes = vec![owned(&keys, 50), owned(&keys, 300), owned(&keys, 120)];
+        let picked = select_inputs(&notes, &NATIVE_SOL_ASSET, 100).unwrap();
+        assert_eq!(picked.len(), 1);
+        assert_eq!(picked[0].amount, 120);
+
+        let picked = select_inputs(&notes, &NATIVE_SOL_ASSET, 400).unwrap();
+        let mut amounts: Vec<u64> = picked.iter().map(|n| n.amount).collect();
+        amounts.sort();
+        assert_eq!(amounts, vec![120, 300]);
+
+        assert!(select_inputs(&notes, &NATIVE_SOL_ASSET, 1_000).is_err());
+        // 470 is available in total but not in any two notes.
+        assert!(select_inputs(&notes, &NATIVE_SOL_ASSET, 450).is_err());
+        assert!(select_inputs(&notes, &[9u8; 32], 10).is_err());
+    }
+
+    #[tokio::test]
+    async fn transfer_witness_satisfies_the_circuit() {
+        let sender = WalletKeys::generate();
+        let receiver = WalletKeys::generate();
+
+        for amounts in [&[1_000u64][..], &[400, 700][..]] {
+            let inputs = spendable(&sender, amounts).await;
+            let (plan, change) =
+                build_transfer(&sender, inputs, &receiver.payment_address(), 900, b"").unwrap();
+            let witness = plan.witness(&sender).unwrap();
+            assert_ne!(witness.nullifiers[0], witness.nullifiers[1]);
+            assert!(satisfied(witness.circuit), "inputs {amounts:?}");
+
+            let total: u64 = amounts.iter().sum();
+            assert_eq!(change.unwrap().amount, total - 900);
+        }
+    }
+
+    #[tokio::test]
+    async fn withdraw_witness_satisfies_the_circuit() {
+        let keys = WalletKeys::generate();
+        let inputs = spendable(&keys, &[1_000]).await;
+        let (plan, change) = build_withdraw(&keys, inputs, [4; 32], 600).unwrap();
+        assert_eq!(plan.ext_amount, -600);
+        assert_eq!(plan.outputs[1].amount, 0);
+        assert_eq!(change.unwrap().amount, 400);
+        assert!(satisfied(plan.witness(&keys).unwrap().circuit));
+
+        // Withdrawing everything leaves no change note to file.
+        let inputs = spendable(&keys, &[1_000]).await;
+        let (_, change) = build_withdraw(&keys, inputs, [4; 32], 1_000).unwrap();
+        assert!(change.is_none());
+    }
+
+    #[tokio::test]
+    async fn change_note_and_payment_open_for_their_owners() {
+        let sender = WalletKeys::generate();
+        let receiver = WalletKeys::generate();
+        let inputs = spendable(&sender, &[1_000]).await;
+        let (plan, change) = build_transfer(
+            &sender,
+            inputs,
+            &receiver.payment_address(),
+            250,
+            b"for the bike",
+        )
+        .unwrap();
+
+        let change = change.unwrap();
+        assert_eq!(change.origin, NoteOrigin::Change);
+        assert_eq!(
+            change.commitment,
+            note_commitment(&sender, 750, &change.blinding, &NATIVE_SOL_ASSET)
+        );
+
+        let payment = &plan.outputs[0];
+        let sealed = payment
+            .seal(&NATIVE_SOL_ASSET, &sender.outgoing_key())
+            .unwrap();
+        let plaintext = receiver
+            .viewing_key()
+            .try_decrypt(&EncryptedNote::from_bytes(&sealed).unwrap())
+            .unwrap();
+        assert_eq!(plaintext.amount, 250);
+        assert_eq!(plaintext.memo, b"for the bike");
+        // The receiver's address is v2, so the envelope leads with a clue
+        // its detection key picks up.
+        let clue = crate::privacy::note_crypto::envelope_clue(&sealed).unwrap();
+        assert!(receiver.detection_key(16).unwrap().detects(&clue));
+        assert_eq!(plaintext.asset_id, Some(NATIVE_SOL_ASSET));
+        assert_eq!(
+            note_commitment(&receiver, 250, &plaintext.randomness, &NATIVE_SOL_ASSET),
+            payment.commitment(&NATIVE_SOL_ASSET)
+        );
+
+        // The sender, and only the sender, can read back what was paid.
+        let (_, record) = EncryptedNote::from_bytes_with_outgoing(&sealed).unwrap();
+        let record = record.unwrap();
+        let sent = sender.outgoing_viewing_key().try_decrypt_outgoing(&record);
+        assert_eq!(sent.unwrap().transmission_key, receiver.transmission_key());
+        assert!(receiver
+            .outgoing_viewing_key()
+            .try_decrypt_outgoing(&record)
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn an_oversized_memo_is_refused_when_the_plan_is_built() {
+        let sender = WalletKeys::generate();
+        let inputs = spendable(&sender, &[1_000]).await;
+        let memo = vec![b'm'; MAX_MEMO_LEN + 1];
+        assert!(build_transfer(&sender, inputs, &sender.payment_address(), 100, &memo).is_err());
+    }
+
+    #[tokio::test]
+    async fn unbalanced_or_mismatched_plans_are_rejected_before_proving() {
+        let sender = WalletKeys::generate();
+        let inputs = spendable(&sender, &[1_000]).await;
+        let (mut plan, _) =
+            build_transfer(&sender, inputs, &sender.payment_address(), 100, b"").unwrap();
+
+        plan.outputs[0].amount += 1;
+        assert!(plan.witness(&sender).is_err());
+        plan.outputs[0].amount -= 1;
+
+        plan.inputs[0].membership.leaf_index += 1;
+        assert!(plan.witness(&sender).is_err());
+    }
+
+    #[test]
+    fn submit_body_matches_the_ingress_shape() {
+        let proved = ProvedTransact {
+            recipient: [1; 32],
+            mint: Some([2; 32]),
+            nullifiers: [[3; 32], [4; 32]],
+            output_commitments: [[5; 32], [6; 32]],
+            root: [7; 32],
+            ext_amount: 0,
+            proof: vec![1, 2, 3],
+            ciphertexts: [vec![8], vec![9]],
+        };
+        let body = proved.submit_body();
+        assert_eq!(body["nullifiers"].as_array().unwrap().len(), 2);
+        assert_eq!(body["ext_amount"], 0);
+        assert_eq!(body["mint"], hex::encode([2u8; 32]));
+        assert_eq!(body["proof"], "010203");
+
+        let native = ProvedTransact {
+            mint: None,
+            ..proved
+        };
+        assert!(native.submit_body().get("mint").is_none());
+    }
+}
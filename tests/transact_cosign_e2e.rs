@@ -5,8 +5,8 @@
 //! Two bridge-enabled validator nodes form a gossip mesh. node0 initiates a
 //! transact verification; node1 verifies it (accept seam), votes `Valid` over
 //! the network, and caches the request in `verified_transacts`. Once node0 has
-//! a `Valid` quorum and has learned node1's advertised settlement wallet, it
-//! runs the co-signing round: it rebuilds the settlement message from
+//! a `Valid` quorum, it takes the approval — which carries node1's signed vote
+//! and so its settlement wallet — and runs the co-signing round: it rebuilds the settlement message from
 //! `SettlementParams::Transact`, signs it, asks node1 to co-sign the same
 //! message over the `/paraloom/cosign` protocol — node1 signs only after
 //! matching all five bindings (recipient, nullifiers, output commitments,
@@ -22,7 +22,6 @@ use ark_ec::AffineRepr;
 use ark_serialize::CanonicalSerialize;
 use paraloom::config::Settings;
 use paraloom::consensus::transact::TransactVerificationRequest;
-use paraloom::consensus::ApprovedTransact;
 use paraloom::consensus::VerificationVote;
 use paraloom::node::Node;
 use solana_sdk::signature::{Keypair, Signer};
@@ -174,9 +173,9 @@ async fn leader_assembles_a_co_signed_transact_transaction() {
     // stake-weighted half of the quorum it claims to exercise was never
     // exercised. With stake applied, node1's vote has to carry real weight.
     //
-    // Applied on every poll rather than once, mirroring the production
-    // reconciler: a peer's stake only sticks once its settlement wallet has
-    // been advertised over the mesh, and that is gossip-timed.
+    // The request carries no slot, so both nodes tally it against epoch 0.
+    // Offered on every poll, like the production reconciler's tick; only the
+    // first snapshot of the epoch is kept.
     let stakes: std::collections::HashMap<String, u64> = std::collections::HashMap::from([
         (wallet0.to_string(), 1_000_000_000),
         (wallet1.to_string(), 1_000_000_000),
@@ -206,6 +205,7 @@ async fn leader_assembles_a_co_signed_transact_transaction() {
         proof: valid_tagged_proof(),
         ciphertexts: [String::new(), String::new()],
         timestamp: now_secs(),
+        slot: None,
     };
     request.request_id = request.canonical_id();
 
@@ -232,8 +232,8 @@ async fn leader_assembles_a_co_signed_transact_transaction() {
         let snapshot = stakes.clone();
         let req = request.clone();
         async move {
-            probe.apply_onchain_stakes(snapshot.clone()).await;
-            peer.apply_onchain_stakes(snapshot).await;
+            probe.apply_epoch_stakes(0, snapshot.clone()).await;
+            peer.apply_epoch_stakes(0, snapshot).await;
             // Re-broadcast each poll until node1 actually receives the request.
             // The gossip topic mesh forms a beat after the TCP connection, and
             // `send_message` only logs a NoPeersSubscribedToTopic publish rather
@@ -253,17 +253,15 @@ async fn leader_assembles_a_co_signed_transact_transaction() {
     .await;
     assert!(quorum, "transact did not reach Valid quorum within 30s");
 
-    let approved = ApprovedTransact {
-        request: TransactVerificationRequest {
-            request_id: request_id.clone(),
-            ..request.clone()
-        },
-    };
+    // The approval carries the signed votes the co-signer quorum is built from.
+    let approved = node0
+        .transact_approval(&request_id)
+        .await
+        .expect("initiator holds the approval once quorum is reached");
 
-    // Run the co-signing round, retrying while node1's advertised wallet
-    // propagates and its cache settles. node1 signs only after matching all
-    // five bindings against the request it verified — success is an assembled
-    // transaction carrying BOTH signatures.
+    // Run the co-signing round, retrying while node1's cache settles. node1
+    // signs only after matching all five bindings against the request it
+    // verified — success is an assembled transaction carrying BOTH signatures.
     let until = Instant::now() + Duration::from_secs(30);
     let tx = loop {
         match node0